@@ -0,0 +1,28 @@
+// A bulging CRT tube: barrel distortion, scanlines and darkened corners.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t));
+    let centred = in.uv * 2.0 - 1.0;
+    let bent = centred * (1.0 + centred.yx * centred.yx * 0.08);
+    let uv = bent * 0.5 + 0.5;
+    // sample before deciding anything so the lookup stays in uniform control flow
+    let c = textureSample(t, s, clamp(uv, vec2<f32>(0.0), vec2<f32>(1.0)));
+    let inside = all(uv >= vec2<f32>(0.0)) && all(uv <= vec2<f32>(1.0));
+    let line = 0.8 + 0.2 * abs(sin(uv.y * size.y * 1.5708));
+    let vignette = clamp(1.0 - dot(centred, centred) * 0.18, 0.0, 1.0);
+    let rgb = select(vec3<f32>(0.0), c.rgb * line * vignette * 1.1, inside);
+    return in.color * vec4<f32>(rgb, 1.0);
+}
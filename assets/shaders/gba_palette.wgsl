@@ -0,0 +1,19 @@
+// The GBA's 15-bit colour: five bits, 32 levels, per channel.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let c = textureSample(t, s, in.uv);
+    return in.color * vec4<f32>(round(c.rgb * 31.0) / 31.0, c.a);
+}
@@ -0,0 +1,25 @@
+// A handheld LCD: each 3x3 block of screen pixels is a cell with red, green and blue stripes
+// and a dark gap along the bottom.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t));
+    let c = textureSample(t, s, in.uv);
+    let pixel = vec2<u32>(in.uv * size);
+    var stripe = vec3<f32>(0.7);
+    stripe[pixel.x % 3u] = 1.0;
+    let gap = select(1.0, 0.65, pixel.y % 3u == 2u);
+    return in.color * vec4<f32>(c.rgb * stripe * gap * 1.2, c.a);
+}
@@ -0,0 +1,22 @@
+// Dark gaps between every other row of screen pixels, like an old TV.
+
+struct VertexOutput {
+    @builtin(position) position: vec4<f32>,
+    @location(0) uv: vec2<f32>,
+    @location(1) color: vec4<f32>,
+}
+
+@group(1) @binding(0)
+var t: texture_2d<f32>;
+
+@group(1) @binding(1)
+var s: sampler;
+
+@fragment
+fn fs_main(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = vec2<f32>(textureDimensions(t));
+    let c = textureSample(t, s, in.uv);
+    // two-pixel period; brightened a little so the picture doesn't just get darker overall
+    let line = 0.7 + 0.3 * abs(sin(in.uv.y * size.y * 1.5708));
+    return in.color * vec4<f32>(c.rgb * line * 1.15, c.a);
+}
@@ -0,0 +1,220 @@
+//! Achievements and the local player profile that tracks them.
+//!
+//! The profile is shared by every save slot of a player profile and lives in its folder as
+//! `profile.txt`, using the same `key=value` format as save files. Counters only ever go up; an
+//! achievement unlocks the first time its counter reaches the goal and a toast pops up in the
+//! corner of the screen.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+
+/// How long an unlock toast stays on screen.
+const TOAST_SECS: f32 = 4.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Counter {
+    Kills,
+    DoorsOpened,
+    NightsSlept,
+}
+
+#[derive(Debug)]
+pub struct AchievementDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub description: &'static str,
+    pub counter: Counter,
+    pub goal: u32,
+}
+
+pub const ACHIEVEMENTS: &[AchievementDef] = &[
+    AchievementDef { id: "first_blood", name: "First Blood", description: "Defeat your first enemy.", counter: Counter::Kills, goal: 1 },
+    AchievementDef { id: "doorman", name: "Doorman", description: "Open 10 doors.", counter: Counter::DoorsOpened, goal: 10 },
+    AchievementDef { id: "well_rested", name: "Well Rested", description: "Sleep 5 nights in your bed.", counter: Counter::NightsSlept, goal: 5 },
+];
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Profile {
+    pub kills: u32,
+    pub doors_opened: u32,
+    pub nights_slept: u32,
+    /// Ids of unlocked achievements, in unlock order.
+    pub unlocked: Vec<&'static str>,
+    /// Ironman runs that ended in death, and the highest level any of them reached.
+    pub fallen_runs: u32,
+    pub best_fallen_level: u32,
+    /// Furthest arena run: the wave reached and the seconds it lasted.
+    pub best_arena_wave: u32,
+    pub best_arena_secs: u32,
+}
+
+impl Profile {
+    pub fn path() -> PathBuf {
+        crate::save::dir().join("profile.txt")
+    }
+
+    /// Load the profile, or start a fresh one if there is none yet.
+    pub fn load() -> Profile {
+        fs::read_to_string(Profile::path()).map(|t| Profile::from_text(&t)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = fs::create_dir_all(crate::save::dir()).and_then(|_| fs::write(Profile::path(), self.to_text()));
+        if let Err(e) = result {
+            println!("Achievements: failed to write profile: {}", e);
+        }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!("kills={}\n", self.kills));
+        s.push_str(&format!("doors_opened={}\n", self.doors_opened));
+        s.push_str(&format!("nights_slept={}\n", self.nights_slept));
+        s.push_str(&format!("unlocked={}\n", self.unlocked.join(",")));
+        s.push_str(&format!("fallen_runs={}\n", self.fallen_runs));
+        s.push_str(&format!("best_fallen_level={}\n", self.best_fallen_level));
+        s.push_str(&format!("best_arena_wave={}\n", self.best_arena_wave));
+        s.push_str(&format!("best_arena_secs={}\n", self.best_arena_secs));
+        s
+    }
+
+    pub fn from_text(text: &str) -> Profile {
+        let mut profile = Profile::default();
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "kills" => profile.kills = value.parse().unwrap_or(0),
+                "doors_opened" => profile.doors_opened = value.parse().unwrap_or(0),
+                "nights_slept" => profile.nights_slept = value.parse().unwrap_or(0),
+                "fallen_runs" => profile.fallen_runs = value.parse().unwrap_or(0),
+                "best_fallen_level" => profile.best_fallen_level = value.parse().unwrap_or(0),
+                "best_arena_wave" => profile.best_arena_wave = value.parse().unwrap_or(0),
+                "best_arena_secs" => profile.best_arena_secs = value.parse().unwrap_or(0),
+                "unlocked" => {
+                    profile.unlocked = value.split(',')
+                        .filter_map(|id| ACHIEVEMENTS.iter().find(|a| a.id == id.trim()))
+                        .map(|a| a.id)
+                        .collect();
+                }
+                _ => {}
+            }
+        }
+        profile
+    }
+
+    pub fn count(&self, counter: Counter) -> u32 {
+        match counter {
+            Counter::Kills => self.kills,
+            Counter::DoorsOpened => self.doors_opened,
+            Counter::NightsSlept => self.nights_slept,
+        }
+    }
+
+    pub fn is_unlocked(&self, id: &str) -> bool {
+        self.unlocked.contains(&id)
+    }
+
+    /// An ironman run ended in death at `level`.
+    pub fn record_fallen_run(&mut self, level: u32) {
+        self.fallen_runs += 1;
+        self.best_fallen_level = self.best_fallen_level.max(level);
+    }
+
+    /// An arena run ended at `wave` after `secs` seconds. Returns whether it's the new best: a
+    /// later wave, or the same wave survived for longer.
+    pub fn record_arena_run(&mut self, wave: u32, secs: u32) -> bool {
+        if (wave, secs) <= (self.best_arena_wave, self.best_arena_secs) { return false; }
+        (self.best_arena_wave, self.best_arena_secs) = (wave, secs);
+        true
+    }
+
+    /// Bump a counter and unlock anything that reached its goal. Returns the new unlocks.
+    pub fn record(&mut self, counter: Counter) -> Vec<&'static AchievementDef> {
+        match counter {
+            Counter::Kills => self.kills += 1,
+            Counter::DoorsOpened => self.doors_opened += 1,
+            Counter::NightsSlept => self.nights_slept += 1,
+        }
+        let mut unlocked = Vec::new();
+        for def in ACHIEVEMENTS.iter().filter(|a| a.counter == counter) {
+            if !self.is_unlocked(def.id) && self.count(counter) >= def.goal {
+                self.unlocked.push(def.id);
+                unlocked.push(def);
+            }
+        }
+        unlocked
+    }
+}
+
+/// Unlock toasts stacked in the top-right corner.
+pub struct Toasts {
+    active: Vec<(&'static AchievementDef, f32)>,
+}
+
+impl Toasts {
+    pub fn new() -> Toasts {
+        Toasts { active: Vec::new() }
+    }
+
+    pub fn push(&mut self, def: &'static AchievementDef) {
+        println!("Achievements: unlocked {}", def.name);
+        self.active.push((def, TOAST_SECS));
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for (_, t) in &mut self.active {
+            *t -= dt;
+        }
+        self.active.retain(|(_, t)| *t > 0.0);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let w = ctx.gfx.window().inner_size().width as f32;
+        let (box_w, box_h) = (280.0, 56.0);
+        for (i, (def, t)) in self.active.iter().enumerate() {
+            // slide in from the right, fade out at the end
+            let slide = ((TOAST_SECS - t) * 4.0).min(1.0);
+            let alpha = t.min(1.0);
+            let rect = graphics::Rect::new(w - (box_w + 16.0) * slide, 16.0 + i as f32 * (box_h + 8.0), box_w, box_h);
+            let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95 * alpha))?;
+            canvas.draw(&bg, DrawParam::new());
+            let mut border_color = crate::palette::selection();
+            border_color.a = alpha;
+            let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), rect, border_color)?;
+            canvas.draw(&border, DrawParam::new());
+            let heading = Text::new(TextFragment::new("Achievement unlocked!").scale(14.0));
+            canvas.draw(&heading, DrawParam::new().dest([rect.x + 12.0, rect.y + 8.0]).color(Color::new(1.0, 0.85, 0.2, alpha)));
+            let name = Text::new(TextFragment::new(def.name).scale(20.0));
+            canvas.draw(&name, DrawParam::new().dest([rect.x + 12.0, rect.y + 26.0]).color(Color::new(1.0, 1.0, 1.0, alpha)));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counters_unlock_once_and_round_trip() {
+        let mut profile = Profile::default();
+        assert_eq!(profile.record(Counter::Kills).len(), 1);
+        assert!(profile.record(Counter::Kills).is_empty());
+        for _ in 0..9 {
+            assert!(profile.record(Counter::DoorsOpened).is_empty());
+        }
+        assert_eq!(profile.record(Counter::DoorsOpened)[0].id, "doorman");
+        profile.record_fallen_run(4);
+        profile.record_fallen_run(2);
+        assert_eq!((profile.fallen_runs, profile.best_fallen_level), (2, 4));
+        assert!(profile.record_arena_run(3, 40));
+        assert!(!profile.record_arena_run(2, 90), "an earlier wave doesn't count, however long");
+        assert!(profile.record_arena_run(3, 45));
+        let loaded = Profile::from_text(&profile.to_text());
+        assert_eq!(loaded, profile);
+    }
+}
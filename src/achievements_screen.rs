@@ -0,0 +1,70 @@
+//! Achievements list opened from the title menu: every achievement with its progress, and how
+//! many ironman runs have been lost. It also opens by itself when an ironman run ends.
+//! Any of Z, C or Escape closes it.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::achievements::{Profile, ACHIEVEMENTS};
+use crate::input::Action;
+
+pub struct AchievementsScreen {
+    pub visible: bool,
+}
+
+impl AchievementsScreen {
+    pub fn new() -> AchievementsScreen {
+        AchievementsScreen { visible: false }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, profile: &Profile) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 520.0;
+        let box_h = 120.0 + ACHIEVEMENTS.len() as f32 * 64.0;
+        let left = (w - box_w) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let unlocked = profile.unlocked.len();
+        let title = Text::new(TextFragment::new(format!("Achievements  {}/{}", unlocked, ACHIEVEMENTS.len())).scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+        if profile.fallen_runs > 0 {
+            let fallen = Text::new(TextFragment::new(format!("Ironman runs lost: {}  (best Lv {})", profile.fallen_runs, profile.best_fallen_level)).scale(16.0));
+            canvas.draw(&fallen, DrawParam::new().dest([left + box_w - 20.0, top + 24.0]).offset([1.0, 0.0]).color(Color::new(1.0, 0.6, 0.5, 1.0)));
+        }
+        if profile.best_arena_wave > 0 {
+            let secs = profile.best_arena_secs;
+            let arena = Text::new(TextFragment::new(format!("Arena best: wave {}  ({}:{:02})", profile.best_arena_wave, secs / 60, secs % 60)).scale(16.0));
+            canvas.draw(&arena, DrawParam::new().dest([left + box_w - 20.0, top + 42.0]).offset([1.0, 0.0]).color(Color::new(1.0, 0.85, 0.4, 1.0)));
+        }
+
+        for (i, def) in ACHIEVEMENTS.iter().enumerate() {
+            let y = top + 64.0 + i as f32 * 64.0;
+            let done = profile.is_unlocked(def.id);
+            let (name_color, mark) = if done { (Color::new(1.0, 0.85, 0.2, 1.0), "*") } else { (Color::new(0.7, 0.7, 0.7, 1.0), " ") };
+            let name = Text::new(TextFragment::new(format!("{} {}", mark, def.name)).scale(22.0));
+            canvas.draw(&name, DrawParam::new().dest([left + 30.0, y]).color(name_color));
+            let progress = profile.count(def.counter).min(def.goal);
+            let desc = Text::new(TextFragment::new(format!("{}  ({}/{})", def.description, progress, def.goal)).scale(16.0));
+            canvas.draw(&desc, DrawParam::new().dest([left + 50.0, y + 26.0]).color(Color::new(0.85, 0.9, 1.0, 1.0)));
+        }
+
+        let prompt = Text::new(TextFragment::new(crate::input::prompt("{cancel}: back")).scale(16.0));
+        canvas.draw(&prompt, DrawParam::new().dest([left + 20.0, top + box_h - 30.0]).color(Color::WHITE));
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if Action::Confirm.matches(key) || Action::Cancel.matches(key) {
+            self.visible = false;
+        }
+    }
+}
@@ -0,0 +1,83 @@
+//! Ambient sound loops under the music.
+//!
+//! Rooms pick a loop with `ambience=key` in their data file (`crickets`, `fire`, `drips`; see
+//! `Assets::ambience`); rooms without one are quiet. The loop plays at the player's ambience
+//! volume from the audio options, scaled down so it sits under the music.
+
+use ggez::Context;
+use ggez::audio::SoundSource;
+
+use crate::assets::Assets;
+
+/// Loops are mixed at this fraction of the ambience volume, below the music.
+const AMBIENT_LEVEL: f32 = 0.5;
+
+pub struct Ambience {
+    current: Option<String>,
+    /// Ambience volume from the options, 0 to 1.
+    volume: f32,
+}
+
+impl Ambience {
+    pub fn new(volume: f32) -> Ambience {
+        Ambience { current: None, volume }
+    }
+
+    /// Swap to the loop for `key`, or silence. Does nothing if that loop is already playing.
+    pub fn switch_to(&mut self, ctx: &mut Context, assets: &mut Assets, key: Option<&str>) {
+        if self.current.as_deref() == key { return; }
+        self.stop(ctx, assets);
+        let Some(key) = key else { return; };
+        self.current = Some(key.to_string());
+        let level = self.level();
+        if let Some(source) = assets.ambience(key) {
+            let _ = source.play(ctx);
+            source.set_volume(level);
+        }
+        println!("Ambience: {}", key);
+    }
+
+    pub fn set_volume(&mut self, assets: &mut Assets, volume: f32) {
+        self.volume = volume;
+        let level = self.level();
+        if let Some(source) = self.current.as_deref().and_then(|key| assets.ambience(key)) {
+            source.set_volume(level);
+        }
+    }
+
+    /// Playback volume of the loop.
+    fn level(&self) -> f32 {
+        self.volume * AMBIENT_LEVEL
+    }
+
+    /// Hold the loop where it is (window lost focus).
+    pub fn pause(&self, assets: &mut Assets) {
+        if let Some(source) = self.current.as_deref().and_then(|key| assets.ambience(key)) {
+            source.pause();
+        }
+    }
+
+    pub fn resume(&self, assets: &mut Assets) {
+        if let Some(source) = self.current.as_deref().and_then(|key| assets.ambience(key)) {
+            source.resume();
+        }
+    }
+
+    pub fn stop(&mut self, ctx: &mut Context, assets: &mut Assets) {
+        if let Some(source) = self.current.take().and_then(|key| assets.ambience(&key)) {
+            let _ = source.stop(ctx);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn loops_sit_under_the_music() {
+        let ambience = Ambience::new(1.0);
+        assert!(ambience.level() < 1.0);
+        assert_eq!(Ambience::new(0.0).level(), 0.0, "off means silent");
+    }
+}
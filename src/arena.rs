@@ -0,0 +1,89 @@
+//! Arena survival: waves of enemies, each bigger and tougher than the last, until the player
+//! drops.
+//!
+//! The arena is room 2 (`assets/rooms/room2.txt`), reached by talking to the arena master in
+//! the village. Waves come from the usual spawner and fight with the usual combat; this module
+//! only decides when the next wave comes, how big it is and how much tougher its enemies are.
+//! How far the best run got is kept on the profile.
+
+use crate::enemy::Enemy;
+
+/// Room the arena is in.
+pub const ROOM: usize = 2;
+/// Seconds between clearing a wave (or stepping in) and the next one arriving.
+pub const BREATHER: f32 = 3.0;
+/// No wave is bigger than this, however far the run gets; they only get tougher.
+const MAX_WAVE_SIZE: usize = 8;
+
+/// One trip into the arena.
+pub struct Run {
+    /// The wave being fought, or the last one cleared; 0 before the first.
+    pub wave: u32,
+    /// Seconds survived so far.
+    pub elapsed: f32,
+    /// Seconds until the next wave, while the arena is clear.
+    breather: f32,
+}
+
+impl Run {
+    pub fn new() -> Run {
+        Run { wave: 0, elapsed: 0.0, breather: BREATHER }
+    }
+
+    /// Advance the clock; `cleared` is whether every enemy in the arena is down. Returns the
+    /// number of the wave to send in, when it's time.
+    pub fn update(&mut self, dt: f32, cleared: bool) -> Option<u32> {
+        self.elapsed += dt;
+        if !cleared { return None; }
+        self.breather -= dt;
+        if self.breather > 0.0 { return None; }
+        self.breather = BREATHER;
+        self.wave += 1;
+        Some(self.wave)
+    }
+
+    /// Seconds until the next wave while the arena is clear, for the countdown.
+    pub fn breather(&self) -> f32 {
+        self.breather.max(0.0)
+    }
+}
+
+/// Enemies in wave `wave` (counting from 1).
+pub fn wave_size(wave: u32) -> usize {
+    (1 + wave as usize).min(MAX_WAVE_SIZE)
+}
+
+/// Make a spawned enemy fit for wave `wave`: a quarter more HP and a point more damage every
+/// other wave, and a little more XP. Arena enemies aren't one-offs, so they lose their
+/// spawn number.
+pub fn toughen(mut enemy: Enemy, wave: u32) -> Enemy {
+    let extra = wave.saturating_sub(1) as i32;
+    enemy.hp += enemy.hp * extra / 4;
+    enemy.damage += extra / 2;
+    enemy.xp_reward += extra as u32;
+    enemy.unique = None;
+    enemy
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn waves_come_after_a_breather_and_escalate() {
+        let mut run = Run::new();
+        assert_eq!(run.update(BREATHER / 2.0, true), None);
+        assert_eq!(run.update(BREATHER, true), Some(1));
+        assert_eq!(run.update(10.0, false), None, "no new wave while enemies are up");
+        assert_eq!(run.update(BREATHER, true), Some(2));
+        assert!((run.elapsed - (BREATHER * 2.5 + 10.0)).abs() < 1e-4);
+
+        assert_eq!(wave_size(1), 2);
+        assert_eq!(wave_size(100), MAX_WAVE_SIZE);
+        let first = toughen(Enemy::at(0.0, 0.0).with_unique(3), 1);
+        let fifth = toughen(Enemy::at(0.0, 0.0), 5);
+        assert_eq!(first.unique, None);
+        assert_eq!((first.hp, first.damage), (Enemy::at(0.0, 0.0).hp, Enemy::at(0.0, 0.0).damage));
+        assert!(fifth.hp > first.hp && fifth.damage > first.damage && fifth.xp_reward > first.xp_reward);
+    }
+}
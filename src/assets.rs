@@ -1,3 +1,5 @@
+use std::collections::HashMap;
+
 use ggez::{Context, GameResult};
 use ggez::graphics::{Image, ImageFormat};
 use ggez::audio::SoundSource;
@@ -17,55 +19,515 @@ pub fn generate_plank_tile(ctx: &mut Context) -> Image {
     Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, TILE_SIZE as u32, TILE_SIZE as u32)
 }
 
-// Horizontal wall tile
-pub fn generate_wall_horizontal(ctx: &mut Context) -> Image {
-    let mut pixels = vec![0u8; TILE_SIZE * TILE_SIZE * 4];
-    for y in 0..TILE_SIZE {
-        for x in 0..TILE_SIZE {
-            let base = (y * TILE_SIZE + x) * 4;
-            let shade = if y % 8 < 4 { 120 } else { 100 };
-            pixels[base..base+4].copy_from_slice(&[shade, 80, 40, 255]);
+// Log wall tile: planks with vertical separators and diagonal shading
+pub fn generate_log_wall(ctx: &mut Context) -> Image {
+    let w = 32u16;
+    let h = 32u16;
+    let mut buf = vec![0u8; (w as usize) * (h as usize) * 4];
+    for y in 0..h {
+        for x in 0..w {
+            let idx = ((y as usize) * (w as usize) + (x as usize)) * 4;
+            // log base
+            let mut r = 120i32;
+            let mut g = 70i32;
+            let mut b = 30i32;
+            // vertical log separators every 8 pixels
+            if x % 8 == 0 {
+                r += 30; g += 20; b += 10;
+            }
+            // fake depth: diagonal shading
+            let shade = ((x as i32 + y as i32) % 6) - 3;
+            r = (r + shade * 3).clamp(0, 255);
+            g = (g + shade * 2).clamp(0, 255);
+            b = (b + shade).clamp(0, 255);
+            buf[idx] = r as u8;
+            buf[idx + 1] = g as u8;
+            buf[idx + 2] = b as u8;
+            buf[idx + 3] = 255u8;
         }
     }
-    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, TILE_SIZE as u32, TILE_SIZE as u32)
+    Image::from_pixels(ctx, &buf, ImageFormat::Rgba8Unorm, w as u32, h as u32)
 }
 
-// Vertical wall tile
-pub fn generate_wall_vertical(ctx: &mut Context) -> Image {
+/// Stand-in character sprite: a round body of `rgb` with a darker rim, on transparency, so the
+/// player and enemies still read as figures when their images are missing.
+fn figure_pixels(rgb: [u8; 3]) -> Vec<u8> {
     let mut pixels = vec![0u8; TILE_SIZE * TILE_SIZE * 4];
+    let centre = (TILE_SIZE as f32 - 1.0) / 2.0;
     for y in 0..TILE_SIZE {
         for x in 0..TILE_SIZE {
+            let d = ((x as f32 - centre).powi(2) + (y as f32 - centre).powi(2)).sqrt();
             let base = (y * TILE_SIZE + x) * 4;
-            let shade = if x % 8 < 4 { 120 } else { 100 };
-            pixels[base..base+4].copy_from_slice(&[shade, 80, 40, 255]);
+            if d <= centre - 2.0 {
+                pixels[base..base+4].copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+            } else if d <= centre {
+                pixels[base..base+4].copy_from_slice(&[rgb[0] / 3, rgb[1] / 3, rgb[2] / 3, 255]);
+            }
         }
     }
-    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, TILE_SIZE as u32, TILE_SIZE as u32)
+    pixels
 }
 
-// Corner wall tile: half vertical, half horizontal planks, diagonal joint
-pub fn generate_corner_tile(ctx: &mut Context) -> Image {
+// Missing-texture checkerboard (magenta and black) for props, so the gap is obvious
+fn checker_pixels() -> Vec<u8> {
     let mut pixels = vec![0u8; TILE_SIZE * TILE_SIZE * 4];
     for y in 0..TILE_SIZE {
         for x in 0..TILE_SIZE {
             let base = (y * TILE_SIZE + x) * 4;
-            // Half vertical, half horizontal
-            let shade = if x < TILE_SIZE / 2 { 
-                if x % 8 < 4 { 120 } else { 100 }
-            } else {
-                if y % 8 < 4 { 120 } else { 100 }
-            };
-            let mut r = shade;
-            let mut g = 80;
-            let mut b = 40;
-            // Diagonal joint from top-left to bottom-right
-            if (y as isize - x as isize).abs() < 2 {
-                r = 40; g = 30; b = 20;
+            let on = (x / 8 + y / 8) % 2 == 0;
+            pixels[base..base+4].copy_from_slice(&if on { [255, 0, 255, 255] } else { [0, 0, 0, 255] });
+        }
+    }
+    pixels
+}
+
+// Title background stand-in: a night-sky gradient
+fn title_gradient(ctx: &mut Context) -> Image {
+    let (w, h) = (4usize, 256usize);
+    let mut pixels = vec![0u8; w * h * 4];
+    for y in 0..h {
+        let t = y as f32 / (h - 1) as f32;
+        let rgb = [(10.0 + 30.0 * t) as u8, (15.0 + 40.0 * t) as u8, (40.0 + 60.0 * t) as u8];
+        for x in 0..w {
+            let base = (y * w + x) * 4;
+            pixels[base..base+4].copy_from_slice(&[rgb[0], rgb[1], rgb[2], 255]);
+        }
+    }
+    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, w as u32, h as u32)
+}
+
+fn tile_image(ctx: &mut Context, pixels: &[u8]) -> Image {
+    Image::from_pixels(ctx, pixels, ImageFormat::Rgba8Unorm, TILE_SIZE as u32, TILE_SIZE as u32)
+}
+
+/// Packed asset archive produced by the `pack_assets` tool.
+pub const DATA_ARCHIVE: &str = "data.pak";
+
+/// Folder (in the working directory) whose files replace the shipped ones with the same path,
+/// e.g. `mods/tile.png` reskins the floor and `mods/rooms/room2.txt` adds a room.
+pub const MODS_DIR: &str = "mods";
+
+/// Folders searched for loose files, first match wins: mods, then the shipped `assets/`.
+pub fn loose_dirs() -> Vec<std::path::PathBuf> {
+    let cwd = crate::platform::working_dir();
+    vec![cwd.join(MODS_DIR), cwd.join("assets")]
+}
+
+/// Read `path` from the first of `dirs` that has it.
+fn read_loose(dirs: &[std::path::PathBuf], path: &str) -> Option<Vec<u8>> {
+    let name = path.trim_start_matches('/');
+    dirs.iter().find_map(|dir| std::fs::read(dir.join(name)).ok())
+}
+
+/// Mount `data.pak` (if present in the working directory) behind the loose `assets/` folder, so
+/// every resource path resolves from either. Loose files win, which keeps iteration fast.
+pub fn mount_data_archive(ctx: &Context) -> GameResult {
+    let path = crate::platform::working_dir().join(DATA_ARCHIVE);
+    if !path.exists() {
+        return Ok(());
+    }
+    let file = std::fs::File::open(&path)?;
+    ctx.fs.add_zip_file(file)?;
+    println!("Assets: mounted data archive {:?}", path);
+    Ok(())
+}
+
+/// Read a UTF-8 text resource (e.g. "/title.txt") from mods, the loose assets or the data archive.
+pub fn read_text(ctx: &Context, path: &str) -> Option<String> {
+    use std::io::Read;
+    let mut file = ctx.fs.open(path).ok()?;
+    let mut s = String::new();
+    file.read_to_string(&mut s).ok()?;
+    Some(s)
+}
+
+/// Read a resource straight from disk, the way the game's resource paths resolve: `mods/`,
+/// then the loose `assets/` folder, then `data.pak`. Doesn't need a `Context`, so it works on a
+/// worker thread.
+pub fn read_resource(path: &str) -> Option<Vec<u8>> {
+    use std::io::Read;
+    if let Some(bytes) = read_loose(&loose_dirs(), path) {
+        return Some(bytes);
+    }
+    let name = path.trim_start_matches('/');
+    let path = crate::platform::working_dir().join(DATA_ARCHIVE);
+    let mut archive = zip::ZipArchive::new(std::fs::File::open(path).ok()?).ok()?;
+    let mut file = archive.by_name(name).ok()?;
+    let mut bytes = Vec::new();
+    file.read_to_end(&mut bytes).ok()?;
+    Some(bytes)
+}
+
+/// The kinds of text a screen can ask for a font for.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FontRole {
+    Title,
+    Body,
+    /// Debug overlays and anything else that wants fixed-width digits.
+    Mono,
+}
+
+impl FontRole {
+    pub const ALL: [FontRole; 3] = [FontRole::Title, FontRole::Body, FontRole::Mono];
+
+    /// Font files tried in order; the first that's there and loads is used.
+    fn files(self) -> &'static [&'static str] {
+        match self {
+            FontRole::Title => &["/fonts/title.ttf", "/fonts/Cinzel-Regular.ttf"],
+            FontRole::Body => &["/fonts/body.ttf"],
+            FontRole::Mono => &["/fonts/mono.ttf"],
+        }
+    }
+
+    /// Whose font to use when none of this role's files load. Past the end of the chain is
+    /// ggez's built-in font, which is already fixed-width.
+    fn fallback(self) -> Option<FontRole> {
+        match self {
+            FontRole::Body => Some(FontRole::Title),
+            FontRole::Title | FontRole::Mono => None,
+        }
+    }
+
+    /// Name the font is registered under with the graphics context.
+    fn registered_name(self) -> String {
+        format!("{:?}Font", self)
+    }
+}
+
+/// The font for `role` among those that loaded, following the fallback chain.
+fn resolve_font(loaded: &[FontRole], role: FontRole) -> Option<FontRole> {
+    let mut role = Some(role);
+    while let Some(r) = role {
+        if loaded.contains(&r) { return Some(r); }
+        role = r.fallback();
+    }
+    None
+}
+
+/// Every file `Assets::load` uses.
+pub fn files() -> Vec<String> {
+    let fixed = [
+        "/player.png", "/enemy.png", "/bed.png", "/table.png", "/tile.png", "/wall.png", "/wall-joint.png", "/title_bg.png",
+        crate::tileset::PATH,
+        "/Music/TALE-stay_strong.mp3", "/Music/TALE-you_feel_safe.mp3", "/Music/TALE-the_land_greets_you.mp3",
+        "/Sfx/splash.wav", "/Sfx/battle_sting.wav", "/Sfx/fanfare.wav", "/Sfx/blip.wav",
+    ];
+    let mut files: Vec<String> = fixed.iter().map(|f| f.to_string()).collect();
+    files.extend(FontRole::ALL.iter().flat_map(|role| role.files()).map(|f| f.to_string()));
+    files.extend(AMBIENCE_KEYS.iter().map(|key| format!("/Sfx/ambience_{}.wav", key)));
+    files.extend(crate::music::Layer::ALL.iter().map(|layer| format!("/Music/stem_{}.wav", layer.key())));
+    files.extend(crate::footsteps::Surface::ALL.iter().map(|s| format!("/Sfx/step_{}.wav", s.key())));
+    files.extend(crate::menu_sfx::Cue::ALL.iter().map(|cue| format!("/Sfx/menu_{}.wav", cue.key())));
+    files
+}
+
+/// A file read (and for images, decoded) ahead of time.
+pub enum Fetched {
+    Image { width: u32, height: u32, rgba: Vec<u8> },
+    Bytes(Vec<u8>),
+}
+
+/// Read and decode one of `files()`. Images come back as pixels; sound effects without a
+/// recording are synthesized instead. None if the file isn't there (or won't decode).
+pub fn fetch(path: &str) -> Option<Fetched> {
+    let bytes = read_resource(path).or_else(|| synthesize(path));
+    if !path.ends_with(".png") {
+        return bytes.map(Fetched::Bytes);
+    }
+    match image::load_from_memory(&bytes?) {
+        Ok(decoded) => {
+            let rgba = decoded.to_rgba8();
+            Some(Fetched::Image { width: rgba.width(), height: rgba.height(), rgba: rgba.into_raw() })
+        }
+        Err(e) => {
+            println!("Assets: can't decode {}: {}", path, e);
+            None
+        }
+    }
+}
+
+/// Generated stand-in for a sound effect or music stem path, if there is one.
+fn synthesize(path: &str) -> Option<Vec<u8>> {
+    if let Some(key) = path.strip_prefix("/Music/stem_").and_then(|name| name.strip_suffix(".wav")) {
+        return crate::music::Layer::ALL.into_iter().find(|layer| layer.key() == key).map(generate_stem_wav);
+    }
+    let name = path.strip_prefix("/Sfx/")?.strip_suffix(".wav")?;
+    if name == "splash" {
+        return Some(generate_splash_wav());
+    }
+    if name == "battle_sting" || name == "fanfare" {
+        return Some(generate_jingle_wav(name));
+    }
+    if name == "blip" {
+        return Some(generate_blip_wav());
+    }
+    if let Some(key) = name.strip_prefix("ambience_") {
+        return AMBIENCE_KEYS.contains(&key).then(|| generate_ambience_wav(key));
+    }
+    if let Some(key) = name.strip_prefix("menu_") {
+        return crate::menu_sfx::Cue::parse(key).map(generate_menu_wav);
+    }
+    name.strip_prefix("step_").and_then(crate::footsteps::Surface::parse).map(generate_footstep_wav)
+}
+
+/// Fetched files by path, waiting to become assets.
+#[derive(Default)]
+pub struct Preloaded {
+    files: HashMap<String, Fetched>,
+}
+
+impl Preloaded {
+    pub fn insert(&mut self, path: String, fetched: Fetched) {
+        self.files.insert(path, fetched);
+    }
+
+    #[cfg(test)]
+    pub fn get(&self, path: &str) -> Option<&Fetched> {
+        self.files.get(path)
+    }
+
+    /// The image fetched for `path`, or a generated stand-in (with a warning) so the game still
+    /// boots without it.
+    fn image_or(&mut self, ctx: &mut Context, path: &str, placeholder: impl FnOnce(&mut Context) -> Image) -> Image {
+        match self.files.remove(path) {
+            Some(Fetched::Image { width, height, rgba }) => Image::from_pixels(ctx, &rgba, ImageFormat::Rgba8UnormSrgb, width, height),
+            _ => {
+                println!("Assets::load: {} is missing, using a placeholder", path);
+                placeholder(ctx)
             }
-            pixels[base..base+4].copy_from_slice(&[r, g, b, 255]);
         }
     }
-    Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8Unorm, TILE_SIZE as u32, TILE_SIZE as u32)
+
+    /// The image fetched for `path`, for images the game does fine without.
+    fn image(&mut self, ctx: &mut Context, path: &str) -> Option<Image> {
+        match self.files.remove(path)? {
+            Fetched::Image { width, height, rgba } => Some(Image::from_pixels(ctx, &rgba, ImageFormat::Rgba8UnormSrgb, width, height)),
+            Fetched::Bytes(_) => None,
+        }
+    }
+
+    fn take_bytes(&mut self, path: &str) -> Option<Vec<u8>> {
+        match self.files.remove(path)? {
+            Fetched::Bytes(bytes) => Some(bytes),
+            Fetched::Image { .. } => None,
+        }
+    }
+
+    fn sound(&mut self, ctx: &mut Context, path: &str) -> GameResult<ggez::audio::Source> {
+        let bytes = self.take_bytes(path).ok_or_else(|| ggez::GameError::ResourceNotFound(path.to_string(), Vec::new()))?;
+        ggez::audio::Source::from_data(ctx, ggez::audio::SoundData::from(bytes))
+    }
+}
+
+/// Sample rate of the synthesized sounds.
+const SYNTH_RATE: u32 = 22050;
+
+/// Encode samples (-1 to 1) as a 16-bit mono WAV file.
+fn encode_wav(samples: &[f32]) -> Vec<u8> {
+    let len = samples.len() as u32;
+    let mut wav = Vec::with_capacity(44 + samples.len() * 2);
+    wav.extend_from_slice(b"RIFF");
+    wav.extend_from_slice(&(36 + len * 2).to_le_bytes());
+    wav.extend_from_slice(b"WAVEfmt ");
+    wav.extend_from_slice(&16u32.to_le_bytes());
+    wav.extend_from_slice(&1u16.to_le_bytes()); // PCM
+    wav.extend_from_slice(&1u16.to_le_bytes()); // mono
+    wav.extend_from_slice(&SYNTH_RATE.to_le_bytes());
+    wav.extend_from_slice(&(SYNTH_RATE * 2).to_le_bytes());
+    wav.extend_from_slice(&2u16.to_le_bytes());
+    wav.extend_from_slice(&16u16.to_le_bytes());
+    wav.extend_from_slice(b"data");
+    wav.extend_from_slice(&(len * 2).to_le_bytes());
+    for &sample in samples {
+        wav.extend_from_slice(&((sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16).to_le_bytes());
+    }
+    wav
+}
+
+/// xorshift noise, -1 to 1, so generated sounds are the same every run.
+struct Noise(u32);
+
+impl Noise {
+    fn next(&mut self) -> f32 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 17;
+        self.0 ^= self.0 << 5;
+        self.0 as f32 / u32::MAX as f32 * 2.0 - 1.0
+    }
+}
+
+// Splash sound: a short burst of low-passed noise that dies away, as a 16-bit mono WAV file
+pub fn generate_splash_wav() -> Vec<u8> {
+    let samples = (SYNTH_RATE as f32 * 0.3) as u32;
+    let mut noise = Noise(0x2545_f491);
+    let mut filtered = 0.0f32;
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        filtered += (noise.next() - filtered) * 0.25;
+        let t = i as f32 / SYNTH_RATE as f32;
+        let envelope = (t * 200.0).min(1.0) * (-t * 14.0).exp();
+        filtered * envelope * 0.8
+    }).collect();
+    encode_wav(&wave)
+}
+
+/// The short jingles around battles: `battle_sting` (two clashing stabs and a falling sweep, on
+/// the way in) and `fanfare` (a rising arpeggio, for a win).
+pub fn generate_jingle_wav(key: &str) -> Vec<u8> {
+    // (start, length, pitch) of each note in seconds and Hz
+    let notes: &[(f32, f32, f32)] = match key {
+        "battle_sting" => &[(0.0, 0.12, 233.0), (0.0, 0.12, 247.0), (0.14, 0.12, 233.0), (0.14, 0.12, 247.0), (0.3, 0.45, 880.0)],
+        _ => &[(0.0, 0.14, 523.0), (0.14, 0.14, 659.0), (0.28, 0.14, 784.0), (0.42, 0.6, 1047.0)],
+    };
+    let end = notes.iter().map(|&(at, len, _)| at + len).fold(0.0, f32::max);
+    let samples = (SYNTH_RATE as f32 * end) as usize;
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        let t = i as f32 / SYNTH_RATE as f32;
+        notes.iter().map(|&(at, len, pitch)| {
+            let dt = t - at;
+            if dt < 0.0 || dt > len { return 0.0; }
+            // the sting's long note sweeps down an octave
+            let pitch = if key == "battle_sting" && len > 0.3 { pitch * (1.0 - 0.5 * dt / len) } else { pitch };
+            // square-ish tone, chiptune style
+            let tone = (dt * pitch * std::f32::consts::TAU).sin().signum() * 0.6 + (dt * pitch * std::f32::consts::TAU).sin() * 0.4;
+            tone * (dt * 400.0).min(1.0) * (1.0 - dt / len) * 0.3
+        }).sum()
+    }).collect();
+    encode_wav(&wave)
+}
+
+/// Dialogue text blip: a 40ms square-wave chirp that falls away fast. Played at each speaker's
+/// own pitch (see `crate::dialogue::voice_pitch`).
+pub fn generate_blip_wav() -> Vec<u8> {
+    let samples = (SYNTH_RATE as f32 * 0.04) as usize;
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        let t = i as f32 / SYNTH_RATE as f32;
+        (t * 620.0 * std::f32::consts::TAU).sin().signum() * (t * 1000.0).min(1.0) * (-t * 60.0).exp() * 0.25
+    }).collect();
+    encode_wav(&wave)
+}
+
+/// A seamless four-second music stem for `layer`, at 120 beats a minute so the stems line up
+/// with each other: a low pulsing drone for tension, driving drums for combat.
+pub fn generate_stem_wav(layer: crate::music::Layer) -> Vec<u8> {
+    use crate::music::Layer;
+    let samples = SYNTH_RATE as usize * 4;
+    let mut noise = Noise(0x1b87_3593);
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        let t = i as f32 / SYNTH_RATE as f32;
+        let beat = t % 0.5;
+        match layer {
+            Layer::Tension => {
+                // two close low notes beating against each other, swelling on every beat
+                let drone = (t * 55.0 * std::f32::consts::TAU).sin() + (t * 58.0 * std::f32::consts::TAU).sin() * 0.6;
+                drone * (0.55 + 0.45 * (-beat * 6.0).exp()) * 0.22
+            }
+            Layer::Combat => {
+                // a kick on every beat and a hat on every off-beat
+                let kick = (beat * (50.0 + 90.0 * (-beat * 30.0).exp()) * std::f32::consts::TAU).sin() * (-beat * 12.0).exp();
+                let off = (t + 0.25) % 0.5;
+                let hat = noise.next() * (-off * 60.0).exp() * 0.35;
+                (kick * 0.6 + hat) * 0.5
+            }
+        }
+    }).collect();
+    encode_wav(&wave)
+}
+
+/// Ambience loops that can be synthesized when there's no recording in `/Sfx`.
+pub const AMBIENCE_KEYS: [&str; 3] = ["crickets", "fire", "drips"];
+
+/// A seamless two-second ambience loop for one of `AMBIENCE_KEYS`.
+pub fn generate_ambience_wav(key: &str) -> Vec<u8> {
+    let samples = SYNTH_RATE as usize * 2;
+    let secs = |i: usize| i as f32 / SYNTH_RATE as f32;
+    let mut noise = Noise(0x9e37_79b9);
+    let wave: Vec<f32> = match key {
+        // bursts of three quick chirps, twice a second
+        "crickets" => (0..samples).map(|i| {
+            let t = secs(i);
+            let in_burst = (t % 0.5) / 0.12;
+            let pulse = if in_burst < 1.0 { (in_burst * 3.0 * std::f32::consts::PI).sin().max(0.0) } else { 0.0 };
+            (t * 4400.0 * std::f32::consts::TAU).sin() * pulse * 0.35
+        }).collect(),
+        // a low rumble of filtered noise with sharp pops scattered through it
+        "fire" => {
+            let mut rumble = 0.0f32;
+            let mut pop = 0.0f32;
+            (0..samples).map(|_| {
+                let n = noise.next();
+                rumble += (n - rumble) * 0.02;
+                if n > 0.9995 { pop = 1.0; }
+                pop *= 0.992;
+                rumble * 0.9 + noise.next() * pop * 0.5
+            }).collect()
+        }
+        // a couple of echoing drops falling into water
+        "drips" => (0..samples).map(|i| {
+            let t = secs(i);
+            [(0.3, 1300.0), (1.35, 1050.0)].iter().map(|&(at, pitch): &(f32, f32)| {
+                let dt = t - at;
+                if dt < 0.0 { return 0.0; }
+                let freq = pitch * (1.0 - 0.4 * (dt * 8.0).min(1.0));
+                (dt * freq * std::f32::consts::TAU).sin() * (-dt * 18.0).exp() * 0.5
+            }).sum()
+        }).collect(),
+        _ => vec![0.0; samples],
+    };
+    encode_wav(&wave)
+}
+
+/// A single footstep (about 0.12s) on `surface`.
+pub fn generate_footstep_wav(surface: crate::footsteps::Surface) -> Vec<u8> {
+    use crate::footsteps::Surface;
+    let samples = (SYNTH_RATE as f32 * 0.12) as usize;
+    let mut noise = Noise(0x6c07_8965);
+    let mut filtered = 0.0f32;
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        let t = i as f32 / SYNTH_RATE as f32;
+        let n = noise.next();
+        match surface {
+            // a hollow knock on the boards
+            Surface::Wood => {
+                filtered += (n - filtered) * 0.15;
+                ((t * 170.0 * std::f32::consts::TAU).sin() * 0.7 + filtered * 0.5) * (-t * 45.0).exp()
+            }
+            // a hard, bright click
+            Surface::Stone => {
+                filtered += (n - filtered) * 0.6;
+                filtered * (-t * 90.0).exp() * 0.8
+            }
+            // a soft brush that swells and fades
+            Surface::Grass => {
+                filtered += (n - filtered) * 0.3;
+                filtered * (t * 60.0).min(1.0) * (-t * 30.0).exp() * 0.6
+            }
+        }
+    }).collect();
+    encode_wav(&wave)
+}
+
+/// A menu sound: a short tick for moving, two notes rising to confirm and falling to cancel,
+/// and a low double buzz for an error.
+pub fn generate_menu_wav(cue: crate::menu_sfx::Cue) -> Vec<u8> {
+    use crate::menu_sfx::Cue;
+    // (start, length, pitch) of each note in seconds and Hz
+    let notes: &[(f32, f32, f32)] = match cue {
+        Cue::Move => &[(0.0, 0.03, 880.0)],
+        Cue::Confirm => &[(0.0, 0.05, 660.0), (0.05, 0.08, 990.0)],
+        Cue::Cancel => &[(0.0, 0.05, 660.0), (0.05, 0.08, 440.0)],
+        Cue::Error => &[(0.0, 0.07, 140.0), (0.1, 0.09, 140.0)],
+    };
+    let end = notes.iter().map(|&(at, len, _)| at + len).fold(0.0, f32::max);
+    let samples = (SYNTH_RATE as f32 * end) as usize;
+    let wave: Vec<f32> = (0..samples).map(|i| {
+        let t = i as f32 / SYNTH_RATE as f32;
+        notes.iter().map(|&(at, len, pitch)| {
+            let dt = t - at;
+            if dt < 0.0 || dt > len { return 0.0; }
+            (dt * pitch * std::f32::consts::TAU).sin().signum() * (dt * 1000.0).min(1.0) * (1.0 - dt / len) * 0.2
+        }).sum()
+    }).collect();
+    encode_wav(&wave)
 }
 
 pub struct Assets {
@@ -77,83 +539,71 @@ pub struct Assets {
     pub table: Image,
     pub wall_joint: Image,
     pub title_bg: Image,
-    // store the registered font name so callers can reference it when building Text
-    pub title_font_name: String,
+    // Tile art addressed by index (see crate::tileset); tiles are painted without it
+    pub tileset: Option<crate::tileset::Tileset>,
+    // fonts that loaded, by role; see `font`
+    pub fonts: Vec<FontRole>,
     // Music tracks
     pub title_music: Option<ggez::audio::Source>,
     pub indoors_music: Option<ggez::audio::Source>,
     pub overworld_music: Option<ggez::audio::Source>,
+    // Sound effects
+    pub splash_sound: Option<ggez::audio::Source>,
+    pub battle_sting: Option<ggez::audio::Source>,
+    pub fanfare: Option<ggez::audio::Source>,
+    // Dialogue text blip (see crate::dialogue)
+    pub blip: Option<ggez::audio::Source>,
+    // Music stems layered over the room's track (see crate::music)
+    pub stems: Vec<(crate::music::Layer, ggez::audio::Source)>,
+    // Looping room ambience, by key (see crate::ambience)
+    pub ambience: Vec<(&'static str, ggez::audio::Source)>,
+    // One footstep per surface (see crate::footsteps)
+    pub footsteps: Vec<(crate::footsteps::Surface, ggez::audio::Source)>,
+    // Cursor, confirm, cancel and error cues (see crate::menu_sfx)
+    pub menu_sounds: Vec<(crate::menu_sfx::Cue, ggez::audio::Source)>,
 }
 
 impl Assets {
-    pub fn load(ctx: &mut Context) -> GameResult<Assets> {
-    // Use resource-relative paths. ContextBuilder should include the `assets/` folder.
-    let player = Image::from_path(ctx, "/player.png")?;
-    let enemy = Image::from_path(ctx, "/enemy.png")?;
-    let bed = Image::from_path(ctx, "/bed.png")?;
-    let table = Image::from_path(ctx, "/table.png")?;
+    /// Build the assets from files fetched ahead of time (see `crate::loading`). Only the GPU
+    /// uploads and audio sources are made here; anything missing gets a stand-in.
+    pub fn load(ctx: &mut Context, mut files: Preloaded) -> GameResult<Assets> {
+    // Missing images fall back to generated stand-ins, so a bare checkout (or a CI box) still boots
+    let player = files.image_or(ctx, "/player.png", |ctx| tile_image(ctx, &figure_pixels([70, 130, 230])));
+    let enemy = files.image_or(ctx, "/enemy.png", |ctx| tile_image(ctx, &figure_pixels([210, 60, 60])));
+    let bed = files.image_or(ctx, "/bed.png", |ctx| tile_image(ctx, &checker_pixels()));
+    let table = files.image_or(ctx, "/table.png", |ctx| tile_image(ctx, &checker_pixels()));
     // Try to load a dedicated tile image. If it doesn't exist, generate a procedural plank floor texture.
-    let plank = match Image::from_path(ctx, "/tile.png") {
-        Ok(img) => img,
-        Err(_) => generate_plank_tile(ctx),
-    };
+    let plank = files.image_or(ctx, "/tile.png", generate_plank_tile);
     // wall / log texture: try load, otherwise generate
-    let wall = match Image::from_path(ctx, "/wall.png") {
-        Ok(img) => img,
-        Err(_) => {
-            let w = 32u16;
-            let h = 32u16;
-            let mut buf = vec![0u8; (w as usize) * (h as usize) * 4];
-            for y in 0..h {
-                for x in 0..w {
-                    let idx = ((y as usize) * (w as usize) + (x as usize)) * 4;
-                    // log base
-                    let mut r = 120i32;
-                    let mut g = 70i32;
-                    let mut b = 30i32;
-                    // vertical log separators every 8 pixels
-                    if x % 8 == 0 {
-                        r += 30; g += 20; b += 10;
-                    }
-                    // fake depth: diagonal shading
-                    let shade = ((x as i32 + y as i32) % 6) - 3;
-                    r = (r + shade * 3).clamp(0, 255);
-                    g = (g + shade * 2).clamp(0, 255);
-                    b = (b + shade).clamp(0, 255);
-                    buf[idx] = r as u8;
-                    buf[idx + 1] = g as u8;
-                    buf[idx + 2] = b as u8;
-                    buf[idx + 3] = 255u8;
+    let wall = files.image_or(ctx, "/wall.png", generate_log_wall);
+    // Wall joint overlay image; without it the joints are simply not drawn over
+    let wall_joint = files.image_or(ctx, "/wall-joint.png", |ctx| tile_image(ctx, &[0u8; TILE_SIZE * TILE_SIZE * 4]));
+    // Title background image (recommended filename: assets/title_bg.png)
+    let title_bg = files.image_or(ctx, "/title_bg.png", title_gradient);
+    // Optional tile sheet; tile kinds only draw from it if their tile data says so
+    let tileset = files.image(ctx, crate::tileset::PATH).and_then(crate::tileset::Tileset::new);
+    // register each role's font with the graphics context; roles without one fall back (see FontRole)
+    let mut fonts = Vec::new();
+    for role in FontRole::ALL {
+        for &path in role.files() {
+            let Some(bytes) = files.take_bytes(path) else { continue };
+            match ggez::graphics::FontData::from_vec(bytes) {
+                Ok(data) => {
+                    ctx.gfx.add_font(&role.registered_name(), data);
+                    fonts.push(role);
+                    break;
                 }
+                Err(e) => println!("Assets::load: failed to load font {}: {}", path, e),
             }
-            Image::from_pixels(ctx, &buf, ImageFormat::Rgba8Unorm, w as u32, h as u32)
         }
-    };
-    // Wall joint overlay image
-    let wall_joint = Image::from_path(ctx, "/wall-joint.png")?;
-    // Title background image (recommended filename: assets/title_bg.png)
-    let title_bg = Image::from_path(ctx, "/title_bg.png")?;
-    // register the font with the graphics context and store its name
-    let font_name = "TitleFont".to_string();
-    // Register font using an absolute filesystem path so FontData::from_path accepts it.
-    let cwd = std::env::current_dir().map_err(|e| ggez::GameError::ResourceLoadError(e.to_string()))?;
-    let font_path = cwd.join("assets").join("fonts").join("Cinzel-Regular.ttf");
-    let mut loaded_font = false;
-    if font_path.exists() {
-        match ggez::graphics::FontData::from_path(ctx, font_path.to_str().unwrap()) {
-            Ok(fd) => { ctx.gfx.add_font(font_name.as_str(), fd); loaded_font = true; }
-            Err(e) => {
-                println!("Assets::load: failed to load font from {:?}: {}", font_path, e);
-            }
+        // the built-in font is a fine fixed-width one, so only a missing title font is news
+        if role == FontRole::Title && !fonts.contains(&role) {
+            println!("Assets::load: no title font found, falling back to the default font");
         }
-    } else {
-        println!("Assets::load: font not found at {:?}, falling back to default font", font_path);
     }
 
-    let title_font_name = if loaded_font { font_name } else { String::new() };
-    
     // Load music tracks
-    let title_music = match ggez::audio::Source::new(ctx, "/Music/TALE-stay_strong.mp3") {
+    let title_music = match files.sound(ctx, "/Music/TALE-stay_strong.mp3") {
         Ok(source) => {
             // Don't set repeat for title music - we'll handle the 3-second delay manually
             Some(source)
@@ -163,8 +613,8 @@ impl Assets {
             None
         }
     };
-    
-    let indoors_music = match ggez::audio::Source::new(ctx, "/Music/TALE-you_feel_safe.mp3") {
+
+    let indoors_music = match files.sound(ctx, "/Music/TALE-you_feel_safe.mp3") {
         Ok(mut source) => {
             source.set_repeat(true);
             Some(source)
@@ -174,8 +624,8 @@ impl Assets {
             None
         }
     };
-    
-    let overworld_music = match ggez::audio::Source::new(ctx, "/Music/TALE-the_land_greets_you.mp3") {
+
+    let overworld_music = match files.sound(ctx, "/Music/TALE-the_land_greets_you.mp3") {
         Ok(mut source) => {
             source.set_repeat(true);
             Some(source)
@@ -185,7 +635,80 @@ impl Assets {
             None
         }
     };
-    
+
+    // Sound effects were synthesized by `fetch` wherever there's no recording
+    let splash_sound = match files.sound(ctx, "/Sfx/splash.wav") {
+        Ok(source) => Some(source),
+        Err(e) => {
+            println!("Assets::load: failed to create splash sound: {}", e);
+            None
+        }
+    };
+
+    let battle_sting = match files.sound(ctx, "/Sfx/battle_sting.wav") {
+        Ok(source) => Some(source),
+        Err(e) => {
+            println!("Assets::load: failed to create battle sting: {}", e);
+            None
+        }
+    };
+    let fanfare = match files.sound(ctx, "/Sfx/fanfare.wav") {
+        Ok(source) => Some(source),
+        Err(e) => {
+            println!("Assets::load: failed to create fanfare: {}", e);
+            None
+        }
+    };
+    let blip = match files.sound(ctx, "/Sfx/blip.wav") {
+        Ok(source) => Some(source),
+        Err(e) => {
+            println!("Assets::load: failed to create dialogue blip: {}", e);
+            None
+        }
+    };
+
+    // Music stems: recordings in /Music/stem_<layer>.wav if present, otherwise synthesized
+    let mut stems = Vec::new();
+    for layer in crate::music::Layer::ALL {
+        match files.sound(ctx, &format!("/Music/stem_{}.wav", layer.key())) {
+            Ok(mut source) => {
+                source.set_repeat(true);
+                stems.push((layer, source));
+            }
+            Err(e) => println!("Assets::load: failed to create {} stem: {}", layer.key(), e),
+        }
+    }
+
+    // Ambience loops: recordings in /Sfx/ambience_<key>.wav if present, otherwise synthesized
+    let mut ambience = Vec::new();
+    for key in AMBIENCE_KEYS {
+        match files.sound(ctx, &format!("/Sfx/ambience_{}.wav", key)) {
+            Ok(mut source) => {
+                source.set_repeat(true);
+                ambience.push((key, source));
+            }
+            Err(e) => println!("Assets::load: failed to create {} ambience: {}", key, e),
+        }
+    }
+
+    // Footsteps: /Sfx/step_<surface>.wav if present, otherwise synthesized
+    let mut footsteps = Vec::new();
+    for surface in crate::footsteps::Surface::ALL {
+        match files.sound(ctx, &format!("/Sfx/step_{}.wav", surface.key())) {
+            Ok(source) => footsteps.push((surface, source)),
+            Err(e) => println!("Assets::load: failed to create {} footstep: {}", surface.key(), e),
+        }
+    }
+
+    // Menu cues: /Sfx/menu_<cue>.wav if present, otherwise synthesized
+    let mut menu_sounds = Vec::new();
+    for cue in crate::menu_sfx::Cue::ALL {
+        match files.sound(ctx, &format!("/Sfx/menu_{}.wav", cue.key())) {
+            Ok(source) => menu_sounds.push((cue, source)),
+            Err(e) => println!("Assets::load: failed to create {} menu sound: {}", cue.key(), e),
+        }
+    }
+
     Ok(Assets { 
         player, 
         enemy, 
@@ -195,10 +718,104 @@ impl Assets {
         table,
         wall_joint,
         title_bg, 
-        title_font_name,
+        tileset,
+        fonts,
         title_music,
         indoors_music,
         overworld_music,
+        splash_sound,
+        battle_sting,
+        fanfare,
+        blip,
+        stems,
+        ambience,
+        footsteps,
+        menu_sounds,
     })
     }
+
+    /// Music track for a key (see `crate::music`).
+    pub fn music(&mut self, key: &str) -> Option<&mut ggez::audio::Source> {
+        match key {
+            "title" => self.title_music.as_mut(),
+            "indoors" => self.indoors_music.as_mut(),
+            "overworld" => self.overworld_music.as_mut(),
+            _ => None,
+        }
+    }
+
+    /// Music stem for a layer, if there is one.
+    pub fn stem(&mut self, layer: crate::music::Layer) -> Option<&mut ggez::audio::Source> {
+        self.stems.iter_mut().find(|(l, _)| *l == layer).map(|(_, source)| source)
+    }
+
+    /// Ambience loop for a key, if there is one.
+    pub fn ambience(&mut self, key: &str) -> Option<&mut ggez::audio::Source> {
+        self.ambience.iter_mut().find(|(k, _)| *k == key).map(|(_, source)| source)
+    }
+
+    /// Footstep sound for a surface, if there is one.
+    pub fn footstep(&mut self, surface: crate::footsteps::Surface) -> Option<&mut ggez::audio::Source> {
+        self.footsteps.iter_mut().find(|(s, _)| *s == surface).map(|(_, source)| source)
+    }
+
+    /// Menu sound for a cue, if there is one.
+    pub fn menu_sound(&mut self, cue: crate::menu_sfx::Cue) -> Option<&mut ggez::audio::Source> {
+        self.menu_sounds.iter_mut().find(|(c, _)| *c == cue).map(|(_, source)| source)
+    }
+
+    /// Registered name of the font for `role`, for `TextFragment::font`; None means ggez's
+    /// built-in font.
+    pub fn font(&self, role: FontRole) -> Option<String> {
+        resolve_font(&self.fonts, role).map(FontRole::registered_name)
+    }
+
+    /// Image for a prop sprite name from `assets/props.txt`.
+    pub fn sprite(&self, name: &str) -> Option<&Image> {
+        match name {
+            "bed" => Some(&self.bed),
+            "table" => Some(&self.table),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn placeholder_figures_are_round_and_opaque_inside() {
+        let pixels = figure_pixels([70, 130, 230]);
+        let alpha = |x: usize, y: usize| pixels[(y * TILE_SIZE + x) * 4 + 3];
+        assert_eq!(alpha(0, 0), 0, "corners stay transparent");
+        assert_eq!(alpha(TILE_SIZE / 2, TILE_SIZE / 2), 255);
+        assert_eq!(&pixels[(TILE_SIZE / 2 * TILE_SIZE + TILE_SIZE / 2) * 4..][..3], &[70, 130, 230]);
+    }
+
+    #[test]
+    fn fonts_fall_back_along_the_chain() {
+        assert_eq!(resolve_font(&[FontRole::Title], FontRole::Body), Some(FontRole::Title));
+        assert_eq!(resolve_font(&[FontRole::Title, FontRole::Body], FontRole::Body), Some(FontRole::Body));
+        assert_eq!(resolve_font(&[FontRole::Body], FontRole::Title), None);
+        assert_eq!(resolve_font(&[FontRole::Title], FontRole::Mono), None);
+        assert_eq!(FontRole::Title.registered_name(), "TitleFont");
+        assert!(files().iter().any(|f| f == "/fonts/Cinzel-Regular.ttf"));
+    }
+
+    #[test]
+    fn mod_files_override_shipped_ones_by_path() {
+        let root = std::env::temp_dir().join(format!("tale-mods-{}", std::process::id()));
+        let (mods, shipped) = (root.join("mods"), root.join("assets"));
+        std::fs::create_dir_all(mods.join("rooms")).unwrap();
+        std::fs::create_dir_all(shipped.join("rooms")).unwrap();
+        std::fs::write(shipped.join("rooms/room0.txt"), "shipped").unwrap();
+        std::fs::write(shipped.join("title.txt"), "shipped").unwrap();
+        std::fs::write(mods.join("rooms/room0.txt"), "modded").unwrap();
+        let dirs = [mods, shipped];
+        assert_eq!(read_loose(&dirs, "/rooms/room0.txt").as_deref(), Some(&b"modded"[..]));
+        assert_eq!(read_loose(&dirs, "/title.txt").as_deref(), Some(&b"shipped"[..]));
+        assert_eq!(read_loose(&dirs, "/credits.txt"), None);
+        std::fs::remove_dir_all(root).unwrap();
+    }
 }
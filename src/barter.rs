@@ -0,0 +1,147 @@
+//! Item-for-item trading with NPCs who'd rather swap than take gold.
+//!
+//! A trader's goods live in `assets/barter/<id>.txt`, like shop stock (see `crate::shop`),
+//! with a `greed` saying how much more than an item's worth they want for it:
+//!
+//! ```text
+//! name=Old Tom's Swaps
+//! greed=1.25
+//! lucky_charm=1
+//! ```
+//!
+//! Items are valued at their shop price. An offer is taken when what the player puts up is
+//! worth enough; how well the trader thinks of the player (the `reputation_<name>` story
+//! variable, see `crate::game_vars`) shifts that a little either way, and every deal made
+//! raises it.
+
+use crate::items::{self, ItemId, ItemKind};
+use crate::player::Player;
+
+/// Each point of reputation knocks this share off what a trader asks (or adds it, below 0).
+const REPUTATION_STEP: f32 = 0.05;
+/// Reputation only counts this far either way.
+const REPUTATION_RANGE: (i64, i64) = (-5, 10);
+
+pub struct Barter {
+    pub name: String,
+    /// (item, quantity left). Items traded in are added here too.
+    pub goods: Vec<(ItemId, u32)>,
+    pub greed: f32,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum BarterError {
+    NothingOffered,
+    NoSuchItem,
+    /// Key items stay with the player.
+    KeyItem,
+    /// Not worth enough to the trader.
+    Refused,
+}
+
+/// Story variable holding how well `npc` thinks of the player.
+pub fn reputation_var(npc: &str) -> String {
+    format!("reputation_{}", npc.to_lowercase().replace(' ', "_"))
+}
+
+/// What items are worth in a swap.
+pub fn value(ids: &[ItemId]) -> u32 {
+    ids.iter().map(|id| crate::shop::Shop::buy_price(id)).sum()
+}
+
+impl Barter {
+    /// Parse a trader's data file. Unknown item ids are reported and skipped.
+    pub fn from_text(text: &str) -> Barter {
+        let mut barter = Barter { name: "Trader".to_string(), goods: Vec::new(), greed: 1.0 };
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (key.trim(), value.trim());
+            match key {
+                "name" => barter.name = value.to_string(),
+                "greed" => barter.greed = value.parse().unwrap_or(1.0),
+                _ => match (items::find(key), value.parse::<u32>()) {
+                    (Some(def), Ok(qty)) => barter.goods.push((def.id, qty)),
+                    _ => println!("Barter: skipping bad goods line {:?}", line),
+                },
+            }
+        }
+        barter
+    }
+
+    /// Load `assets/barter/<id>.txt`, or a trader with nothing to swap if the file is missing.
+    pub fn load(ctx: &ggez::Context, id: &str) -> Barter {
+        match crate::assets::read_text(ctx, &format!("/barter/{}.txt", id)) {
+            Some(text) => Barter::from_text(&text),
+            None => {
+                println!("Barter: no data file for trader {:?}", id);
+                Barter { name: "Nothing to trade".to_string(), goods: Vec::new(), greed: 1.0 }
+            }
+        }
+    }
+
+    /// Least an offer has to be worth for goods entry `index`, at `reputation`.
+    pub fn asking(&self, index: usize, reputation: i64) -> Option<u32> {
+        let &(id, _) = self.goods.get(index)?;
+        let reputation = reputation.clamp(REPUTATION_RANGE.0, REPUTATION_RANGE.1) as f32;
+        let share = (self.greed - reputation * REPUTATION_STEP).max(0.5);
+        Some((crate::shop::Shop::buy_price(id) as f32 * share).ceil() as u32)
+    }
+
+    /// Swap the player's inventory items at `offered` for goods entry `index`.
+    pub fn trade(&mut self, index: usize, offered: &[usize], player: &mut Player, reputation: i64) -> Result<ItemId, BarterError> {
+        let Some(&(wanted, qty)) = self.goods.get(index) else { return Err(BarterError::NoSuchItem) };
+        if qty == 0 { return Err(BarterError::NoSuchItem); }
+        if offered.is_empty() { return Err(BarterError::NothingOffered); }
+        let ids = offered.iter().map(|&i| player.inventory.items.get(i).copied().ok_or(BarterError::NoSuchItem)).collect::<Result<Vec<_>, _>>()?;
+        if ids.iter().any(|id| items::find(id).is_some_and(|def| def.kind == ItemKind::Key)) {
+            return Err(BarterError::KeyItem);
+        }
+        if value(&ids) < self.asking(index, reputation).unwrap_or(u32::MAX) {
+            return Err(BarterError::Refused);
+        }
+        // highest index first, so the rest stay put
+        let mut offered = offered.to_vec();
+        offered.sort_unstable_by(|a, b| b.cmp(a));
+        offered.dedup();
+        for i in offered {
+            player.inventory.take(i);
+        }
+        for id in ids {
+            match self.goods.iter_mut().find(|(g, _)| *g == id) {
+                Some(entry) => entry.1 += 1,
+                None => self.goods.push((id, 1)),
+            }
+        }
+        self.goods[index].1 -= 1;
+        player.inventory.add(wanted);
+        Ok(wanted)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Inventory;
+
+    #[test]
+    fn offers_worth_enough_are_taken_and_reputation_sweetens_them() {
+        let mut barter = Barter::from_text("name=Tom\ngreed=1.5\nlucky_charm=1\nbogus=2\n");
+        assert_eq!(barter.goods, vec![("lucky_charm", 1)]);
+        let mut player = Player::test_new();
+        player.inventory = Inventory { items: vec!["iron_sword", "old_key", "potion", "leather_armor"] };
+
+        assert_eq!(barter.trade(0, &[], &mut player, 0), Err(BarterError::NothingOffered));
+        assert_eq!(barter.trade(0, &[1], &mut player, 0), Err(BarterError::KeyItem));
+        // a 30 G charm at 1.5x wants 45 G worth: the 40 G sword alone won't do
+        assert_eq!(barter.asking(0, 0), Some(45));
+        assert_eq!(barter.trade(0, &[0], &mut player, 0), Err(BarterError::Refused));
+        assert_eq!(player.inventory.items.len(), 4, "a refused offer takes nothing");
+        // ...unless Tom thinks well of the player
+        assert_eq!(barter.asking(0, 3), Some(41));
+        assert_eq!(barter.asking(0, 99), barter.asking(0, 10), "reputation only goes so far");
+        assert_eq!(barter.trade(0, &[0, 2], &mut player, 0), Ok("lucky_charm"));
+        assert_eq!(player.inventory.items, vec!["old_key", "leather_armor", "lucky_charm"]);
+        assert_eq!(barter.goods, vec![("lucky_charm", 0), ("iron_sword", 1), ("potion", 1)]);
+        assert_eq!(reputation_var("Old Tom"), "reputation_old_tom");
+    }
+}
@@ -0,0 +1,172 @@
+//! Barter UI: the trader's goods on the left, the player's bag on the right.
+//!
+//! Left/Right moves between the two lists and Up/Down through one. Z on the trader's side
+//! picks what the player wants; Z in the bag adds an item to the offer or takes it back out.
+//! Enter makes the offer, and C leaves.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::barter::{self, Barter, BarterError};
+use crate::input::Action;
+use crate::items::{self, ItemId};
+use crate::player::Player;
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Side {
+    Goods,
+    Bag,
+}
+
+pub struct BarterScreen {
+    pub visible: bool,
+    /// Index of the trader NPC whose goods are shown.
+    pub npc: usize,
+    side: Side,
+    /// Highlighted row on each side.
+    goods_row: usize,
+    bag_row: usize,
+    /// Goods entry the player is after.
+    wanted: Option<usize>,
+    /// Bag items put up for it, by inventory index.
+    offered: Vec<usize>,
+    message: Option<String>,
+}
+
+impl BarterScreen {
+    pub fn new() -> BarterScreen {
+        BarterScreen { visible: false, npc: 0, side: Side::Goods, goods_row: 0, bag_row: 0, wanted: None, offered: Vec::new(), message: None }
+    }
+
+    pub fn open(&mut self, npc: usize) {
+        *self = BarterScreen::new();
+        self.visible = true;
+        self.npc = npc;
+    }
+
+    fn name(id: &str) -> &str {
+        items::find(id).map(|d| d.name).unwrap_or(id)
+    }
+
+    fn offered_ids(&self, player: &Player) -> Vec<ItemId> {
+        self.offered.iter().filter_map(|&i| player.inventory.items.get(i).copied()).collect()
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, barter: &Barter, player: &Player, reputation: i64) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 720.0;
+        let box_h = 440.0;
+        let left = (w - box_w) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new(barter.name.as_str()).scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+
+        let column_w = (box_w - 60.0) / 2.0;
+        let goods: Vec<(String, Color)> = barter.goods.iter().enumerate().map(|(i, (id, qty))| {
+            let asking = barter.asking(i, reputation).unwrap_or(0);
+            let mark = if self.wanted == Some(i) { "*" } else { " " };
+            let color = if *qty == 0 { Color::new(0.6, 0.6, 0.6, 1.0) } else { Color::WHITE };
+            (format!("{}{:<14} x{}  wants {} G", mark, BarterScreen::name(id), qty, asking), color)
+        }).collect();
+        let bag: Vec<(String, Color)> = player.inventory.items.iter().enumerate().map(|(i, id)| {
+            let mark = if self.offered.contains(&i) { "*" } else { " " };
+            (format!("{}{:<16} {:>4} G", mark, BarterScreen::name(id), barter::value(&[id])), Color::WHITE)
+        }).collect();
+        for (column, (heading, rows, side, row)) in [("Their goods", &goods, Side::Goods, self.goods_row), ("Your bag", &bag, Side::Bag, self.bag_row)].into_iter().enumerate() {
+            let x = left + 20.0 + column as f32 * (column_w + 20.0);
+            let color = if side == self.side { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::new(0.7, 0.7, 0.7, 1.0) };
+            let txt = Text::new(TextFragment::new(heading).scale(22.0));
+            canvas.draw(&txt, DrawParam::new().dest([x + 10.0, top + 60.0]).color(color));
+            for (i, (text, color)) in rows.iter().enumerate() {
+                let y = top + 100.0 + i as f32 * 28.0;
+                let txt = Text::new(TextFragment::new(text.as_str()).scale(18.0));
+                canvas.draw(&txt, DrawParam::new().dest([x + 10.0, y]).color(*color));
+                if side == self.side && i == row {
+                    let sel_rect = graphics::Rect::new(x, y - 4.0, column_w, 26.0);
+                    let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                    canvas.draw(&sel_box, DrawParam::new());
+                }
+            }
+        }
+
+        let offer = barter::value(&self.offered_ids(player));
+        let deal = match self.wanted.and_then(|i| barter.goods.get(i).map(|(id, _)| (id, barter.asking(i, reputation).unwrap_or(0)))) {
+            Some((id, asking)) => format!("Your offer: {} G worth for {} (wants {} G)", offer, BarterScreen::name(id), asking),
+            None => format!("Your offer: {} G worth - pick something of theirs", offer),
+        };
+        let footer = match &self.message {
+            Some(msg) => format!("{}\n{}", deal, msg),
+            None => format!("{}\n{}", deal, crate::input::prompt("{confirm}: pick   {submit}: offer   {left_right}: side   {cancel}: leave")),
+        };
+        let txt = Text::new(TextFragment::new(footer).scale(18.0));
+        canvas.draw(&txt, DrawParam::new().dest([left + 20.0, top + box_h - 56.0]).color(Color::WHITE));
+        Ok(())
+    }
+
+    /// Returns the item the player got when a deal goes through.
+    pub fn handle_key(&mut self, key: KeyCode, barter: &mut Barter, player: &mut Player, reputation: i64) -> Option<ItemId> {
+        if !self.visible { return None; }
+        self.message = None;
+        let count = match self.side {
+            Side::Goods => barter.goods.len(),
+            Side::Bag => player.inventory.items.len(),
+        };
+        let row = match self.side {
+            Side::Goods => &mut self.goods_row,
+            Side::Bag => &mut self.bag_row,
+        };
+        match key {
+            KeyCode::Left | KeyCode::Right => {
+                self.side = if self.side == Side::Goods { Side::Bag } else { Side::Goods };
+            }
+            KeyCode::Up => { *row = row.saturating_sub(1); }
+            KeyCode::Down => { *row = (*row + 1).min(count.saturating_sub(1)); }
+            // Enter confirms too, but here it makes the offer
+            k if Action::Submit.matches(k) => return self.offer(barter, player, reputation),
+            k if Action::Confirm.matches(k) && *row < count => {
+                let row = *row;
+                match self.side {
+                    Side::Goods => self.wanted = if self.wanted == Some(row) { None } else { Some(row) },
+                    Side::Bag => match self.offered.iter().position(|&i| i == row) {
+                        Some(at) => { self.offered.remove(at); }
+                        None => self.offered.push(row),
+                    },
+                }
+            }
+            k if Action::Cancel.matches(k) => { self.visible = false; }
+            _ => {}
+        }
+        None
+    }
+
+    fn offer(&mut self, barter: &mut Barter, player: &mut Player, reputation: i64) -> Option<ItemId> {
+        let Some(wanted) = self.wanted else {
+            self.message = Some("Pick something of theirs first.".to_string());
+            return None;
+        };
+        let result = barter.trade(wanted, &self.offered, player, reputation);
+        self.message = Some(match &result {
+            Ok(id) => format!("Traded for {}.", BarterScreen::name(id)),
+            Err(BarterError::NothingOffered) => "Put something up from your bag.".to_string(),
+            Err(BarterError::NoSuchItem) => "They're out of that.".to_string(),
+            Err(BarterError::KeyItem) => "You can't part with that.".to_string(),
+            Err(BarterError::Refused) => "\"Not worth it to me. Sweeten the deal.\"".to_string(),
+        });
+        if result.is_ok() {
+            // the bag has shifted under the offer
+            self.offered.clear();
+            self.bag_row = self.bag_row.min(player.inventory.items.len().saturating_sub(1));
+        }
+        result.ok()
+    }
+}
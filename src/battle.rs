@@ -0,0 +1,419 @@
+//! Turn-based battles, fought when a random encounter springs in a wild room.
+//!
+//! Each round the player picks Fight (on the foe chosen with Left/Right) or Run, then every
+//! ally still standing strikes a foe and every foe left hits someone in the party at random.
+//! Battles are won when the last foe falls and lost when the player does; running away works
+//! more often than not. A win ends on a results screen with the XP, gold and items the foes
+//! left. Rolls use the world's seeded RNG, like the encounters themselves.
+//!
+//! Whoever is hit flashes, with the damage floating up over them if damage numbers are on, and
+//! the next command waits until the hits have played out; the battle speed option sets how long
+//! that is.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, DrawParam, Text, TextFragment};
+use ggez::input::keyboard::KeyCode;
+
+use crate::assets::Assets;
+use crate::encounter::FoeDef;
+use crate::input::Action;
+use crate::items::{self, ItemId};
+use crate::party::Party;
+use crate::player::Player;
+use crate::rng::GameRng;
+
+/// Percent chance that running away works.
+const RUN_CHANCE: i32 = 60;
+/// Lines of the battle log kept on screen.
+const LOG_LINES: usize = 4;
+/// How long a round's hits play out at normal battle speed.
+const HIT_SECS: f32 = 0.5;
+/// How long a damage number floats, whatever the battle speed.
+const POP_SECS: f32 = 0.9;
+
+const COMMANDS: [&str; 2] = ["Fight", "Run"];
+
+struct Foe {
+    def: &'static FoeDef,
+    hp: i32,
+    /// Seconds left of the flash from the last hit it took.
+    flash: f32,
+}
+
+/// Someone on the field, for placing damage numbers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Who {
+    Foe(usize),
+    /// 0 is the player, then the allies in party order.
+    Member(usize),
+}
+
+/// A damage number floating up.
+struct Pop {
+    who: Who,
+    amount: i32,
+    age: f32,
+}
+
+/// How a battle ended.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won { xp: u32, gold: u32, foes: u32, drops: Vec<ItemId> },
+    Fled,
+    Lost,
+}
+
+pub struct Battle {
+    foes: Vec<Foe>,
+    command: usize,
+    target: usize,
+    log: Vec<String>,
+    outcome: Option<Outcome>,
+    /// Multiplies the speed hits play out at; infinite doesn't wait at all.
+    pace: f32,
+    damage_numbers: bool,
+    pops: Vec<Pop>,
+    /// Seconds until the next command is taken.
+    wait: f32,
+}
+
+impl Battle {
+    pub fn new(group: Vec<&'static FoeDef>) -> Battle {
+        let names: Vec<&str> = group.iter().map(|def| def.name).collect();
+        let foes = group.into_iter().map(|def| Foe { def, hp: def.hp, flash: 0.0 }).collect();
+        let mut battle = Battle { foes, command: 0, target: 0, log: Vec::new(), outcome: None, pace: 1.0, damage_numbers: true, pops: Vec::new(), wait: 0.0 };
+        battle.say(format!("{} attack!", names.join(" and ")));
+        battle
+    }
+
+    /// Play hits out `pace` times faster than normal (see `Options::battle_speed`).
+    pub fn with_pacing(mut self, pace: f32) -> Battle {
+        self.pace = pace;
+        self
+    }
+
+    pub fn with_damage_numbers(mut self, on: bool) -> Battle {
+        self.damage_numbers = on;
+        self
+    }
+
+    /// Run the hit animations on.
+    pub fn update(&mut self, dt: f32) {
+        self.wait = (self.wait - dt).max(0.0);
+        for foe in &mut self.foes {
+            foe.flash = (foe.flash - dt).max(0.0);
+        }
+        for pop in &mut self.pops {
+            pop.age += dt;
+        }
+        self.pops.retain(|pop| pop.age < POP_SECS);
+    }
+
+    /// Seconds a round's hits take at this battle's pace.
+    fn hit_secs(&self) -> f32 {
+        HIT_SECS / self.pace
+    }
+
+    fn hit(&mut self, who: Who, amount: i32) {
+        if let Who::Foe(i) = who {
+            self.foes[i].flash = self.hit_secs();
+        }
+        if self.damage_numbers {
+            self.pops.push(Pop { who, amount, age: 0.0 });
+        }
+    }
+
+    /// Set once the battle is over; the result stays up until dismissed.
+    pub fn outcome(&self) -> Option<&Outcome> {
+        self.outcome.as_ref()
+    }
+
+    fn say(&mut self, line: String) {
+        self.log.push(line);
+        if self.log.len() > LOG_LINES {
+            self.log.remove(0);
+        }
+    }
+
+    fn standing(&self) -> impl Iterator<Item = usize> + '_ {
+        self.foes.iter().enumerate().filter(|(_, foe)| foe.hp > 0).map(|(i, _)| i)
+    }
+
+    /// Move the target to the next foe still standing in direction `step` (+1 or -1).
+    fn cycle_target(&mut self, step: i32) {
+        let n = self.foes.len() as i32;
+        for k in 1..=n {
+            let i = (self.target as i32 + step * k).rem_euclid(n) as usize;
+            if self.foes[i].hp > 0 {
+                self.target = i;
+                return;
+            }
+        }
+    }
+
+    /// Returns true once the battle is over and the result has been dismissed.
+    pub fn handle_key(&mut self, key: KeyCode, player: &mut Player, party: &mut Party, rng: &mut GameRng) -> bool {
+        // let the last round's hits land before taking the next command
+        if self.wait > 0.0 && Action::Confirm.matches(key) {
+            return false;
+        }
+        if self.outcome.is_some() {
+            return Action::Confirm.matches(key);
+        }
+        match key {
+            KeyCode::Up => self.command = self.command.saturating_sub(1),
+            KeyCode::Down => self.command = (self.command + 1).min(COMMANDS.len() - 1),
+            KeyCode::Left => self.cycle_target(-1),
+            KeyCode::Right => self.cycle_target(1),
+            k if Action::Confirm.matches(k) => {
+                self.round(player, party, rng);
+                self.wait = self.hit_secs();
+            }
+            _ => {}
+        }
+        false
+    }
+
+    /// Play out one round with the chosen command.
+    fn round(&mut self, player: &mut Player, party: &mut Party, rng: &mut GameRng) {
+        if COMMANDS[self.command] == "Run" {
+            if rng.range(0, 100) < RUN_CHANCE {
+                self.say("You got away safely.".to_string());
+                self.outcome = Some(Outcome::Fled);
+                return;
+            }
+            self.say("Couldn't get away!".to_string());
+        } else {
+            self.strike("You", self.target, player.attack_power());
+        }
+        for ally in party.allies.iter().filter(|ally| !ally.is_down()) {
+            let Some(target) = self.standing().next() else { break };
+            self.strike(&ally.name, target, ally.attack_power());
+        }
+        if self.standing().next().is_none() {
+            self.win(rng);
+            return;
+        }
+        if self.foes[self.target].hp <= 0 {
+            self.cycle_target(1);
+        }
+        self.foes_attack(player, party, rng);
+    }
+
+    fn strike(&mut self, who: &str, target: usize, power: i32) {
+        let foe = &mut self.foes[target];
+        let damage = power.max(1);
+        foe.hp = (foe.hp - damage).max(0);
+        let name = foe.def.name;
+        let fell = foe.hp == 0;
+        self.hit(Who::Foe(target), damage);
+        self.say(format!("{} hit the {} for {}.", who, name, damage));
+        if fell {
+            self.say(format!("The {} is defeated!", name));
+        }
+    }
+
+    /// Each foe left hits the player or an ally still standing, picked at random.
+    fn foes_attack(&mut self, player: &mut Player, party: &mut Party, rng: &mut GameRng) {
+        let attackers: Vec<&'static FoeDef> = self.standing().map(|i| self.foes[i].def).collect();
+        for def in attackers {
+            let allies: Vec<usize> = (0..party.allies.len()).filter(|&i| !party.allies[i].is_down()).collect();
+            let pick = rng.range(0, allies.len() as i32 + 1) as usize;
+            if pick == 0 {
+                let hp = player.stats.hp;
+                let knocked_out = player.take_hit(def.damage);
+                if let Some((kind, secs)) = def.inflicts {
+                    player.status.apply(kind, secs);
+                }
+                self.hit(Who::Member(0), hp - player.stats.hp);
+                self.say(format!("The {} hit you for {}.", def.name, hp - player.stats.hp));
+                if knocked_out {
+                    self.say("You collapse...".to_string());
+                    self.outcome = Some(Outcome::Lost);
+                    return;
+                }
+            } else {
+                let ally = &mut party.allies[allies[pick - 1]];
+                let hp = ally.stats.hp;
+                let down = ally.take_hit(def.damage);
+                let damage = hp - ally.stats.hp;
+                let line = format!("The {} hit {} for {}.", def.name, ally.name, damage);
+                let name = ally.name.clone();
+                self.hit(Who::Member(allies[pick - 1] + 1), damage);
+                self.say(line);
+                if down {
+                    self.say(format!("{} is down!", name));
+                }
+            }
+        }
+    }
+
+    fn win(&mut self, rng: &mut GameRng) {
+        let xp = self.foes.iter().map(|foe| foe.def.xp).sum();
+        let gold = self.foes.iter().map(|foe| rng.range(foe.def.gold.0 as i32, foe.def.gold.1 as i32 + 1) as u32).sum();
+        let drops = self.foes.iter().filter_map(|foe| foe.def.loot.roll(rng)).map(|(id, _)| id).collect();
+        self.say("Victory!".to_string());
+        self.outcome = Some(Outcome::Won { xp, gold, foes: self.foes.len() as u32, drops });
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, player: &Player, party: &Party, assets: &Assets) -> GameResult {
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), graphics::Rect::new(0.0, 0.0, w, h), Color::new(0.08, 0.05, 0.12, 1.0))?;
+        canvas.draw(&bg, DrawParam::new());
+
+        // foes in a row across the upper half, the target marked underneath
+        let slot_w = w / self.foes.len() as f32;
+        let sprite = 96.0;
+        for (i, foe) in self.foes.iter().enumerate() {
+            let cx = slot_w * (i as f32 + 0.5);
+            let cy = h * 0.3;
+            let tint = match (foe.hp > 0, foe.def.inflicts) {
+                _ if foe.flash > 0.0 => Color::new(1.0, 0.35, 0.35, 1.0),
+                (false, _) => Color::new(1.0, 1.0, 1.0, 0.15),
+                (true, Some((kind, _))) => kind.color(),
+                (true, None) => Color::WHITE,
+            };
+            let img_scale = sprite / assets.enemy.width() as f32;
+            let shake = if foe.flash > 0.0 { (foe.flash * 60.0).sin() * 6.0 } else { 0.0 };
+            canvas.draw(&assets.enemy, DrawParam::new().dest([cx + shake, cy]).offset([0.5, 0.5]).scale([img_scale, img_scale]).color(tint));
+            let label = Text::new(TextFragment::new(format!("{} {}/{}", foe.def.name, foe.hp, foe.def.hp)).scale(18.0));
+            canvas.draw(&label, DrawParam::new().dest([cx - 50.0, cy + sprite / 2.0 + 8.0]).color(Color::WHITE));
+            if i == self.target && foe.hp > 0 && self.outcome.is_none() {
+                let marker = graphics::Rect::new(cx - sprite / 2.0 - 6.0, cy - sprite / 2.0 - 6.0, sprite + 12.0, sprite + 12.0);
+                let mesh = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), marker, crate::palette::selection())?;
+                canvas.draw(&mesh, DrawParam::new());
+            }
+        }
+
+        // bottom panel: commands, the log, and the party's HP
+        let top = h * 0.62;
+        let panel = graphics::Rect::new(10.0, top, w - 20.0, h - top - 10.0);
+        let fill = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), panel, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&fill, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), panel, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+        if self.outcome.is_none() {
+            for (i, command) in COMMANDS.iter().enumerate() {
+                let color = if i == self.command { crate::palette::selection() } else { Color::WHITE };
+                let marker = if i == self.command { "> " } else { "  " };
+                let txt = Text::new(TextFragment::new(format!("{}{}", marker, command)).scale(22.0));
+                canvas.draw(&txt, DrawParam::new().dest([30.0, top + 20.0 + i as f32 * 30.0]).color(color));
+            }
+        }
+        for (i, line) in self.log.iter().enumerate() {
+            let txt = Text::new(TextFragment::new(line.as_str()).scale(18.0));
+            canvas.draw(&txt, DrawParam::new().dest([160.0, top + 20.0 + i as f32 * 24.0]).color(Color::WHITE));
+        }
+        let members = std::iter::once(("You", player.stats.hp, player.stats.max_hp))
+            .chain(party.allies.iter().map(|ally| (ally.name.as_str(), ally.stats.hp, ally.stats.max_hp)));
+        for (i, (name, hp, max_hp)) in members.enumerate() {
+            let color = if hp == 0 { Color::new(0.6, 0.6, 0.6, 1.0) } else { Color::WHITE };
+            let txt = Text::new(TextFragment::new(format!("{:<6} {:>3}/{}", name, hp, max_hp)).scale(18.0));
+            canvas.draw(&txt, DrawParam::new().dest([w - 200.0, top + 20.0 + i as f32 * 24.0]).color(color));
+        }
+        for pop in &self.pops {
+            let (x, y, color) = match pop.who {
+                Who::Foe(i) => (slot_w * (i as f32 + 0.5) - 10.0, h * 0.3 - sprite / 2.0 - 10.0, Color::new(1.0, 0.95, 0.4, 1.0)),
+                Who::Member(i) => (w - 240.0, top + 20.0 + i as f32 * 24.0, Color::new(1.0, 0.4, 0.4, 1.0)),
+            };
+            let fade = Color { a: 1.0 - pop.age / POP_SECS, ..color };
+            let txt = Text::new(TextFragment::new(pop.amount.to_string()).scale(24.0));
+            canvas.draw(&txt, DrawParam::new().dest([x, y - pop.age * 40.0]).color(fade));
+        }
+        if let Some(Outcome::Won { xp, gold, drops, .. }) = &self.outcome {
+            self.draw_results(ctx, canvas, *xp, *gold, drops)?;
+        }
+        let hint = if self.outcome.is_some() { "{confirm}: continue" } else { "Up/Down: command   Left/Right: target   {confirm}: go" };
+        let txt = Text::new(TextFragment::new(crate::input::prompt(hint)).scale(16.0));
+        canvas.draw(&txt, DrawParam::new().dest([30.0, h - 40.0]).color(Color::new(0.8, 0.8, 0.8, 1.0)));
+        Ok(())
+    }
+
+    /// The spoils of a win, boxed over the middle of the screen.
+    fn draw_results(&self, ctx: &mut Context, canvas: &mut Canvas, xp: u32, gold: u32, drops: &[ItemId]) -> GameResult {
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let (box_w, box_h) = (360.0, 200.0);
+        let (left, top) = ((w - box_w) / 2.0, (h - box_h) / 2.0 - 60.0);
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let fill = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.1, 0.1, 0.25, 0.95))?;
+        canvas.draw(&fill, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::new(1.0, 0.85, 0.2, 1.0))?;
+        canvas.draw(&border, DrawParam::new());
+        let title = Text::new(TextFragment::new("Victory!").scale(32.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 24.0, top + 16.0]).color(Color::new(1.0, 0.85, 0.2, 1.0)));
+        let found = if drops.is_empty() {
+            "nothing".to_string()
+        } else {
+            drops.iter().map(|id| items::find(id).map_or(*id, |def| def.name)).collect::<Vec<_>>().join(", ")
+        };
+        let lines = [format!("XP    +{}", xp), format!("Gold  +{} G", gold), format!("Found {}", found)];
+        for (i, line) in lines.iter().enumerate() {
+            let txt = Text::new(TextFragment::new(line.as_str()).scale(20.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 24.0, top + 70.0 + i as f32 * 30.0]).color(Color::WHITE));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::encounter::find_foe;
+
+    #[test]
+    fn fighting_wins_and_pays_out_for_every_foe() {
+        let mut player = Player::test_new();
+        player.stats.attack = 50;
+        let mut party = Party::default();
+        let mut rng = GameRng::new(3);
+        let slime = find_foe("slime").unwrap();
+        let mut battle = Battle::new(vec![slime, slime]);
+        assert!(!battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng));
+        assert_eq!(battle.target, 1, "the target moves on from a fallen foe");
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        assert!(battle.outcome().is_none(), "the next command waits for the hits to play out");
+        battle.update(HIT_SECS);
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        let Some(Outcome::Won { xp, gold, foes, drops }) = battle.outcome() else { panic!("expected a win") };
+        assert_eq!((*xp, *foes), (slime.xp * 2, 2));
+        assert!((4..=12).contains(gold));
+        assert!(drops.iter().all(|id| slime.loot.entries.iter().any(|(e, _)| e == id)) && drops.len() <= 2);
+        battle.update(HIT_SECS);
+        assert!(battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng), "dismissed");
+    }
+
+    #[test]
+    fn battle_speed_and_damage_numbers_follow_the_options() {
+        let mut player = Player::test_new();
+        player.stats.attack = 1;
+        let (mut party, mut rng) = (Party::default(), GameRng::new(3));
+        let slime = find_foe("slime").unwrap();
+        let mut battle = Battle::new(vec![slime, slime]).with_pacing(f32::INFINITY).with_damage_numbers(false);
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        assert_eq!(battle.foes[0].hp, slime.hp - 2, "instant battles never wait");
+        assert!(battle.pops.is_empty());
+        let mut battle = Battle::new(vec![slime]).with_pacing(0.5);
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        assert_eq!(battle.pops[0].who, Who::Foe(0));
+        battle.update(HIT_SECS);
+        assert!(battle.wait > 0.0 && battle.foes[0].flash > 0.0, "slow battles take twice as long");
+        battle.update(POP_SECS);
+        assert!(battle.pops.is_empty() && battle.wait == 0.0);
+    }
+
+    #[test]
+    fn foes_hit_back_and_a_fallen_player_loses() {
+        let mut player = Player::test_new();
+        player.stats.attack = 0;
+        player.stats.hp = 1;
+        let mut party = Party::default();
+        let mut rng = GameRng::new(5);
+        let mut battle = Battle::new(vec![find_foe("mudling").unwrap()]);
+        battle.handle_key(KeyCode::Z, &mut player, &mut party, &mut rng);
+        assert_eq!(battle.outcome(), Some(&Outcome::Lost));
+        assert_eq!(player.stats.hp, 0);
+    }
+}
@@ -0,0 +1,135 @@
+//! Asset packer: validates the `assets/` folder and packs it into a single compressed archive.
+//!
+//! Usage:
+//!
+//! ```bash
+//! cargo run --bin pack_assets -- [assets_dir] [output]
+//! ```
+//!
+//! Defaults to `assets` and `data.pak`. The archive is a plain zip (deflate) that the game mounts
+//! at startup when it sits next to the executable's working directory, so a release only needs
+//! the binary plus `data.pak`. Loose files in `assets/` still take priority over the archive.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+use zip::write::FileOptions;
+use zip::CompressionMethod;
+
+/// Art a release must ship; the game boots without it, but only on placeholders (see `Assets::load`).
+const REQUIRED: &[&str] = &["player.png", "enemy.png", "bed.png", "table.png", "wall-joint.png", "title_bg.png"];
+
+/// Extensions the game knows how to load; everything else (editor autosaves etc.) is skipped.
+const PACKED_EXTENSIONS: &[&str] = &["png", "mp3", "ogg", "wav", "ttf", "txt", "ron"];
+
+fn main() -> ExitCode {
+    let mut args = std::env::args().skip(1);
+    let assets_dir = PathBuf::from(args.next().unwrap_or_else(|| "assets".to_string()));
+    let output = PathBuf::from(args.next().unwrap_or_else(|| "data.pak".to_string()));
+
+    let files = match collect_files(&assets_dir) {
+        Ok(files) => files,
+        Err(e) => {
+            eprintln!("pack_assets: can't read {:?}: {}", assets_dir, e);
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let errors = validate(&assets_dir, &files);
+    if !errors.is_empty() {
+        for e in &errors {
+            eprintln!("pack_assets: error: {}", e);
+        }
+        eprintln!("pack_assets: {} error(s), archive not written", errors.len());
+        return ExitCode::FAILURE;
+    }
+
+    match write_archive(&assets_dir, &files, &output) {
+        Ok(count) => {
+            println!("pack_assets: packed {} files into {:?}", count, output);
+            ExitCode::SUCCESS
+        }
+        Err(e) => {
+            eprintln!("pack_assets: failed to write {:?}: {}", output, e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Recursively list files under `dir`, sorted so archives are reproducible.
+fn collect_files(dir: &Path) -> std::io::Result<Vec<PathBuf>> {
+    let mut out = Vec::new();
+    let mut pending = vec![dir.to_path_buf()];
+    while let Some(current) = pending.pop() {
+        for entry in fs::read_dir(&current)? {
+            let path = entry?.path();
+            if path.is_dir() {
+                pending.push(path);
+            } else {
+                out.push(path);
+            }
+        }
+    }
+    out.sort();
+    Ok(out)
+}
+
+fn is_packed(path: &Path) -> bool {
+    path.extension()
+        .and_then(|e| e.to_str())
+        .map(|e| PACKED_EXTENSIONS.contains(&e.to_ascii_lowercase().as_str()))
+        .unwrap_or(false)
+}
+
+/// Archive path for a file: relative to the assets dir, always with forward slashes.
+fn archive_name(root: &Path, path: &Path) -> String {
+    let rel = path.strip_prefix(root).unwrap_or(path);
+    rel.components().map(|c| c.as_os_str().to_string_lossy()).collect::<Vec<_>>().join("/")
+}
+
+/// Check required assets exist, nothing packed is empty, and text data (rooms, dialogue,
+/// title) is valid UTF-8. Returns a list of human-readable problems.
+fn validate(root: &Path, files: &[PathBuf]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let names: Vec<String> = files.iter().map(|f| archive_name(root, f)).collect();
+    for required in REQUIRED {
+        if !names.iter().any(|n| n == required) {
+            errors.push(format!("missing required asset {}", required));
+        }
+    }
+    for (path, name) in files.iter().zip(&names) {
+        if !is_packed(path) {
+            println!("pack_assets: skipping {}", name);
+            continue;
+        }
+        match fs::read(path) {
+            Ok(bytes) if bytes.is_empty() => errors.push(format!("{} is empty", name)),
+            Ok(bytes) => {
+                if (name.ends_with(".txt") || name.ends_with(".ron")) && std::str::from_utf8(&bytes).is_err() {
+                    errors.push(format!("{} is not valid UTF-8", name));
+                }
+            }
+            Err(e) => errors.push(format!("{}: {}", name, e)),
+        }
+    }
+    errors
+}
+
+fn write_archive(root: &Path, files: &[PathBuf], output: &Path) -> zip::result::ZipResult<usize> {
+    let mut zip = zip::ZipWriter::new(File::create(output)?);
+    // already-compressed formats are stored as-is; deflating them only costs load time
+    let deflated = FileOptions::default().compression_method(CompressionMethod::Deflated);
+    let stored = FileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut count = 0;
+    for path in files.iter().filter(|p| is_packed(p)) {
+        let name = archive_name(root, path);
+        let options = if name.ends_with(".txt") || name.ends_with(".ron") || name.ends_with(".ttf") || name.ends_with(".wav") { deflated } else { stored };
+        zip.start_file(name, options)?;
+        zip.write_all(&fs::read(path)?)?;
+        count += 1;
+    }
+    zip.finish()?;
+    Ok(count)
+}
@@ -0,0 +1,189 @@
+//! Day counter and seasons. A day passes each night slept in bed; every `DAYS_PER_SEASON`
+//! days the season turns, which tints outdoor rooms and changes how likely rain and snow are.
+//!
+//! The day's weather is picked from the world seed and the day number, so reloading a save
+//! doesn't reroll it. Rooms are outdoor if their data file says `outdoor=true`.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
+
+use crate::rng::GameRng;
+
+pub const DAYS_PER_SEASON: u32 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Season {
+    Spring,
+    Summer,
+    Autumn,
+    Winter,
+}
+
+impl Season {
+    pub fn name(self) -> &'static str {
+        match self {
+            Season::Spring => "Spring",
+            Season::Summer => "Summer",
+            Season::Autumn => "Autumn",
+            Season::Winter => "Winter",
+        }
+    }
+
+    /// Wash laid over outdoor rooms.
+    pub fn tint(self) -> Color {
+        match self {
+            Season::Spring => Color::new(0.4, 0.9, 0.5, 0.08),
+            Season::Summer => Color::new(1.0, 0.85, 0.3, 0.1),
+            Season::Autumn => Color::new(0.9, 0.45, 0.1, 0.16),
+            Season::Winter => Color::new(0.8, 0.9, 1.0, 0.22),
+        }
+    }
+
+    /// Chances in percent of (rain, snow) on a day of this season.
+    pub fn weather_odds(self) -> (i32, i32) {
+        match self {
+            Season::Spring => (35, 0),
+            Season::Summer => (10, 0),
+            Season::Autumn => (40, 5),
+            Season::Winter => (5, 50),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Weather {
+    Clear,
+    Rain,
+    Snow,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Calendar {
+    /// Counted from 1, the first day of spring.
+    pub day: u32,
+}
+
+impl Default for Calendar {
+    fn default() -> Calendar {
+        Calendar { day: 1 }
+    }
+}
+
+impl Calendar {
+    pub fn new(day: u32) -> Calendar {
+        Calendar { day: day.max(1) }
+    }
+
+    pub fn season(&self) -> Season {
+        match ((self.day - 1) / DAYS_PER_SEASON) % 4 {
+            0 => Season::Spring,
+            1 => Season::Summer,
+            2 => Season::Autumn,
+            _ => Season::Winter,
+        }
+    }
+
+    /// Move on to the next morning.
+    pub fn next_day(&mut self) {
+        self.day += 1;
+        println!("Calendar: {}", self.label());
+    }
+
+    /// "Day 12, Summer"
+    pub fn label(&self) -> String {
+        format!("Day {}, {}", self.day, self.season().name())
+    }
+
+    /// Today's weather in a world with this seed.
+    pub fn weather(&self, seed: u64) -> Weather {
+        let mut rng = GameRng::new(seed ^ (self.day as u64).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        let (rain, snow) = self.season().weather_odds();
+        let roll = rng.range(0, 100);
+        if roll < snow {
+            Weather::Snow
+        } else if roll < snow + rain {
+            Weather::Rain
+        } else {
+            Weather::Clear
+        }
+    }
+}
+
+/// The season and weather outdoor rooms are drawn with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Sky {
+    pub season: Season,
+    pub weather: Weather,
+}
+
+impl Default for Sky {
+    fn default() -> Sky {
+        Sky { season: Season::Spring, weather: Weather::Clear }
+    }
+}
+
+impl Sky {
+    pub fn today(calendar: &Calendar, seed: u64) -> Sky {
+        Sky { season: calendar.season(), weather: calendar.weather(seed) }
+    }
+
+    /// Tint a `width` x `height` pixel room with the season's colours.
+    pub fn draw_tint(&self, ctx: &mut Context, canvas: &mut Canvas, width: f32, height: f32, scale: f32, offset: (f32, f32)) -> GameResult {
+        let rect = Rect::new(offset.0, offset.1, width * scale, height * scale);
+        let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, self.season.tint())?;
+        canvas.draw(&mesh, DrawParam::new());
+        Ok(())
+    }
+
+    /// Rain or snow falling over the room. Nothing on a clear day.
+    pub fn draw_weather(&self, ctx: &mut Context, canvas: &mut Canvas, width: f32, height: f32, scale: f32, offset: (f32, f32)) -> GameResult {
+        let t = ctx.time.time_since_start().as_secs_f32();
+        let (count, speed, drift) = match self.weather {
+            Weather::Clear => return Ok(()),
+            Weather::Rain => (90, 420.0, 60.0),
+            Weather::Snow => (70, 45.0, 12.0),
+        };
+        let mut builder = MeshBuilder::new();
+        for i in 0..count {
+            // each drop starts from its own fixed spot and wraps around the room as it falls
+            let h = crate::tiles::spot_hash(i, 7);
+            let x = ((h & 0xffff) as f32 / 65535.0 * width + t * drift) % width;
+            let y = (((h >> 16) as f32 / 65535.0 * height) + t * speed) % height;
+            let (px, py) = (offset.0 + x * scale, offset.1 + y * scale);
+            match self.weather {
+                Weather::Rain => builder.line(&[[px, py], [px - 1.5 * scale, py + 7.0 * scale]], scale.max(1.0), Color::new(0.65, 0.75, 1.0, 0.55))?,
+                _ => builder.circle(DrawMode::fill(), [px + (t * 2.0 + i as f32).sin() * 3.0 * scale, py], 1.5 * scale, 0.5, Color::new(1.0, 1.0, 1.0, 0.85))?,
+            };
+        }
+        let mesh = Mesh::from_data(ctx, builder.build());
+        canvas.draw(&mesh, DrawParam::new());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seasons_turn_every_ten_days_and_wrap_into_a_new_year() {
+        let mut calendar = Calendar::default();
+        assert_eq!((calendar.season(), calendar.label()), (Season::Spring, "Day 1, Spring".to_string()));
+        for _ in 0..DAYS_PER_SEASON {
+            calendar.next_day();
+        }
+        assert_eq!(calendar.season(), Season::Summer);
+        assert_eq!(Calendar::new(31).season(), Season::Winter);
+        assert_eq!(Calendar::new(41).season(), Season::Spring);
+        assert_eq!(Calendar::new(0).day, 1);
+    }
+
+    #[test]
+    fn weather_is_fixed_per_day_and_follows_the_season() {
+        let days = |first: u32| (first..first + DAYS_PER_SEASON).map(|day| Calendar::new(day).weather(42)).collect::<Vec<_>>();
+        assert_eq!(days(1), days(1));
+        let (summer, winter): (Vec<Weather>, Vec<Weather>) = (days(11), (0..20).flat_map(|year| days(31 + year * 40)).collect());
+        assert!(!summer.contains(&Weather::Snow));
+        assert!(winter.contains(&Weather::Snow));
+    }
+}
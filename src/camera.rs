@@ -0,0 +1,87 @@
+//! Camera that follows the player around rooms bigger than the screen.
+//!
+//! The player can move about a deadzone in the middle of the view without the camera moving;
+//! stepping past its edge drags the camera along, and the camera eases towards where it should
+//! be instead of locking on every pixel, which softens tile-by-tile movement. Warps and room
+//! changes cut straight to the new spot. Rooms that fit on screen are simply centred.
+
+use nalgebra as na;
+
+use crate::map::TILE_SIZE;
+
+/// Half-size of the deadzone, in world pixels.
+pub const DEFAULT_DEADZONE: (f32, f32) = (TILE_SIZE * 2.0, TILE_SIZE * 1.5);
+/// How quickly the camera catches up, per second; higher is snappier.
+pub const DEFAULT_SMOOTHING: f32 = 8.0;
+/// A target jumping further than this in one frame is a cut, not movement.
+const CUT_DISTANCE: f32 = TILE_SIZE * 3.0;
+
+pub struct Camera {
+    /// World point at the centre of the view.
+    pub centre: na::Point2<f32>,
+    pub deadzone: (f32, f32),
+    pub smoothing: f32,
+    last_target: Option<na::Point2<f32>>,
+}
+
+impl Camera {
+    pub fn new() -> Camera {
+        Camera { centre: na::Point2::origin(), deadzone: DEFAULT_DEADZONE, smoothing: DEFAULT_SMOOTHING, last_target: None }
+    }
+
+    /// Move towards keeping `target` inside the deadzone.
+    pub fn follow(&mut self, target: na::Point2<f32>, dt: f32) {
+        let cut = self.last_target.is_none_or(|last| (target - last).magnitude() > CUT_DISTANCE);
+        self.last_target = Some(target);
+        if cut {
+            self.centre = target;
+            return;
+        }
+        let pull = |centre: f32, target: f32, half: f32| {
+            if target > centre + half { target - half } else if target < centre - half { target + half } else { centre }
+        };
+        let wanted = na::Point2::new(
+            pull(self.centre.x, target.x, self.deadzone.0),
+            pull(self.centre.y, target.y, self.deadzone.1),
+        );
+        let ease = if self.smoothing > 0.0 { 1.0 - (-self.smoothing * dt).exp() } else { 1.0 };
+        self.centre += (wanted - self.centre) * ease;
+    }
+
+    /// World position of the view's top-left corner for a `view` sized window onto a `map`
+    /// sized room: kept inside the room, or centring the room if it fits.
+    pub fn view_origin(&self, view: (f32, f32), map: (f32, f32)) -> (f32, f32) {
+        let axis = |centre: f32, view: f32, map: f32| {
+            if view >= map { (map - view) / 2.0 } else { (centre - view / 2.0).clamp(0.0, map - view) }
+        };
+        (axis(self.centre.x, view.0, map.0), axis(self.centre.y, view.1, map.1))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deadzone_holds_still_then_the_camera_eases_after_the_player() {
+        let mut camera = Camera::new();
+        camera.follow(na::Point2::new(500.0, 300.0), 0.016);
+        assert_eq!(camera.centre, na::Point2::new(500.0, 300.0), "first sight is a cut");
+        camera.follow(na::Point2::new(532.0, 300.0), 0.016);
+        assert_eq!(camera.centre.x, 500.0, "one tile is inside the deadzone");
+        for _ in 0..3 {
+            camera.follow(na::Point2::new(532.0 + TILE_SIZE * 2.0, 300.0), 0.016);
+        }
+        assert!(camera.centre.x > 500.0 && camera.centre.x < 532.0, "eases, doesn't jump");
+        camera.follow(na::Point2::new(100.0, 100.0), 0.016);
+        assert_eq!(camera.centre, na::Point2::new(100.0, 100.0), "warps cut");
+    }
+
+    #[test]
+    fn small_rooms_are_centred_and_big_ones_clamped() {
+        let mut camera = Camera::new();
+        camera.follow(na::Point2::new(10.0, 10.0), 0.016);
+        assert_eq!(camera.view_origin((800.0, 600.0), (640.0, 480.0)), (-80.0, -60.0));
+        assert_eq!(camera.view_origin((400.0, 300.0), (1280.0, 960.0)), (0.0, 0.0));
+    }
+}
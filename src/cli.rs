@@ -0,0 +1,109 @@
+//! Command-line launch flags, for testing and for launchers.
+//!
+//! ```text
+//! TALE-the-rpg [--fullscreen | --windowed] [--skip-intro] [--load <slot>] [--room <n>] [--editor]
+//!              [--record <file> | --replay <file>] [--time-scale <x>] [--debug]
+//! ```
+//!
+//! Window flags override the profile's fullscreen setting; `--load` continues save slot 1-3 of
+//! the last profile straight from launch, and `--room` then drops the player at that room's
+//! entrance. `--record` writes every input to a file and `--replay` plays one back (see
+//! `replay`); replay with the same other flags that were recorded. `--time-scale` starts with game
+//! time running slower or faster (see `time_scale`). `--debug` turns the debug keys (game speed,
+//! overlays, entity list) on in release builds; debug builds always have them.
+
+pub const USAGE: &str = "usage: TALE-the-rpg [--fullscreen | --windowed] [--skip-intro] [--load <slot>] [--room <n>] [--editor] [--record <file> | --replay <file>] [--time-scale <x>] [--debug]";
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct LaunchOptions {
+    /// Some(true) for `--fullscreen`, Some(false) for `--windowed`.
+    pub fullscreen: Option<bool>,
+    /// Go straight to play after starting a new game.
+    pub skip_intro: bool,
+    /// Save slot to continue, 0-based.
+    pub load_slot: Option<usize>,
+    pub room: Option<usize>,
+    pub editor: bool,
+    /// Record inputs to this file.
+    pub record: Option<String>,
+    /// Play inputs back from this file.
+    pub replay: Option<String>,
+    /// Starting game speed, 1.0 being normal.
+    pub time_scale: Option<f32>,
+    /// Debug keys in a release build.
+    pub debug: bool,
+    pub help: bool,
+}
+
+impl LaunchOptions {
+    /// Parse the arguments after the program name.
+    pub fn parse(args: impl IntoIterator<Item = String>) -> Result<LaunchOptions, String> {
+        let mut launch = LaunchOptions::default();
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--fullscreen" => launch.fullscreen = Some(true),
+                "--windowed" => launch.fullscreen = Some(false),
+                "--skip-intro" => launch.skip_intro = true,
+                "--load" => {
+                    let slot = number(args.next(), "--load")?;
+                    if !(1..=crate::save::SAVE_SLOT_COUNT).contains(&slot) {
+                        return Err(format!("--load takes a slot from 1 to {}", crate::save::SAVE_SLOT_COUNT));
+                    }
+                    launch.load_slot = Some(slot - 1);
+                }
+                "--room" => launch.room = Some(number(args.next(), "--room")?),
+                "--editor" => launch.editor = true,
+                "--record" => launch.record = Some(args.next().ok_or("--record needs a file")?),
+                "--replay" => launch.replay = Some(args.next().ok_or("--replay needs a file")?),
+                "--time-scale" => {
+                    let value = args.next().ok_or("--time-scale needs a speed, like 0.5 or 4")?;
+                    match value.parse::<f32>() {
+                        Ok(scale) if scale > 0.0 => launch.time_scale = Some(scale),
+                        _ => return Err(format!("--time-scale needs a speed above 0, not {:?}", value)),
+                    }
+                }
+                "--debug" => launch.debug = true,
+                "--help" | "-h" => launch.help = true,
+                _ => return Err(format!("unknown argument {:?}", arg)),
+            }
+        }
+        if launch.room.is_some() && launch.load_slot.is_none() {
+            return Err("--room needs a world to go to; add --load <slot>".to_string());
+        }
+        if launch.record.is_some() && launch.replay.is_some() {
+            return Err("--record and --replay can't be used together".to_string());
+        }
+        Ok(launch)
+    }
+}
+
+fn number(value: Option<String>, flag: &str) -> Result<usize, String> {
+    let value = value.ok_or_else(|| format!("{} needs a number", flag))?;
+    value.parse().map_err(|_| format!("{} needs a number, not {:?}", flag, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &str) -> Result<LaunchOptions, String> {
+        LaunchOptions::parse(args.split_whitespace().map(str::to_string))
+    }
+
+    #[test]
+    fn parses_flags_and_rejects_nonsense() {
+        let launch = parse("--windowed --skip-intro --load 2 --room 1").unwrap();
+        assert_eq!(launch, LaunchOptions { fullscreen: Some(false), skip_intro: true, load_slot: Some(1), room: Some(1), ..LaunchOptions::default() });
+        assert_eq!(parse("").unwrap(), LaunchOptions::default());
+        assert!(parse("--load 0").is_err());
+        assert!(parse("--load").is_err());
+        assert!(parse("--room 1").is_err(), "no world to put the player in");
+        assert!(parse("--turbo").is_err());
+        assert_eq!(parse("--replay bug.txt").unwrap().replay.as_deref(), Some("bug.txt"));
+        assert!(parse("--record a.txt --replay b.txt").is_err());
+        assert_eq!(parse("--time-scale 0.5").unwrap().time_scale, Some(0.5));
+        assert!(parse("--time-scale 0").is_err() && parse("--time-scale fast").is_err());
+        assert!(parse("--debug").unwrap().debug);
+    }
+}
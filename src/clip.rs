@@ -0,0 +1,137 @@
+//! Rolling capture of the last few seconds of play, saved as an animated GIF on F9.
+//!
+//! While "Clip Recording" is on (Video options) the finished frame is shrunk on the GPU and read
+//! back a few times a second; the newest `SECONDS` worth are kept. Saving hands a copy of them to
+//! a background thread, so encoding never holds up the game. Clips go to a `clips` folder next
+//! to the saves. What's captured is the frame before any retro shader.
+
+use std::collections::VecDeque;
+use std::path::{Path, PathBuf};
+use std::thread;
+
+use ggez::graphics::{Canvas, Color, DrawParam, Image, ImageFormat};
+use ggez::{Context, GameResult};
+use image::codecs::gif::{GifEncoder, Repeat};
+use image::{Delay, Frame, RgbaImage};
+
+/// Length of a clip.
+pub const SECONDS: f32 = 10.0;
+/// Frames kept per second of play.
+const FPS: f32 = 10.0;
+/// Width of a clip; the height follows the window's shape.
+const WIDTH: u32 = 320;
+
+pub struct ClipBuffer {
+    frames: VecDeque<RgbaImage>,
+    /// Seconds since the last kept frame.
+    since: f32,
+    /// GPU image the frame is shrunk into before reading it back.
+    small: Option<Image>,
+}
+
+impl ClipBuffer {
+    pub fn new() -> ClipBuffer {
+        ClipBuffer { frames: VecDeque::new(), since: 0.0, small: None }
+    }
+
+    /// Forget what's been captured, e.g. when recording is turned off.
+    pub fn clear(&mut self) {
+        self.frames.clear();
+        self.since = 0.0;
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// Offer the finished frame `dt` seconds after the last one; one is kept every 1/FPS seconds.
+    pub fn capture(&mut self, ctx: &mut Context, frame: &Image, dt: f32) -> GameResult {
+        self.since += dt;
+        if self.since < 1.0 / FPS && !self.frames.is_empty() { return Ok(()); }
+        self.since = 0.0;
+        let size = fit_width(frame.width(), frame.height(), WIDTH);
+        if self.small.as_ref().map(|small| (small.width(), small.height())) != Some(size) {
+            // the window changed shape; frames of a different size can't share a clip
+            self.frames.clear();
+            self.small = Some(Image::new_canvas_image(ctx, ImageFormat::Rgba8UnormSrgb, size.0, size.1, 1));
+        }
+        let Some(small) = &self.small else { return Ok(()) };
+        if let Some(image) = shrink(ctx, frame, small)? {
+            self.push(image);
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, image: RgbaImage) {
+        self.frames.push_back(image);
+        while self.frames.len() > (SECONDS * FPS) as usize {
+            self.frames.pop_front();
+        }
+    }
+
+    /// Write what's been captured to a new GIF in `dir` in the background, returning its path.
+    pub fn save(&self, dir: &Path) -> PathBuf {
+        let stamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        let path = dir.join(format!("clip-{}.gif", stamp));
+        let frames: Vec<RgbaImage> = self.frames.iter().cloned().collect();
+        let (dir, target) = (dir.to_path_buf(), path.clone());
+        thread::spawn(move || {
+            let written = std::fs::create_dir_all(&dir)
+                .and_then(|_| std::fs::File::create(&target))
+                .map_err(image::ImageError::IoError)
+                .and_then(|file| encode(std::io::BufWriter::new(file), frames));
+            match written {
+                Ok(()) => println!("Clip: saved {:?}", target),
+                Err(e) => println!("Clip: failed to write {:?}: {}", target, e),
+            }
+        });
+        path
+    }
+}
+
+/// Size of a copy of a `width` x `height` image no wider than `max_width`, keeping its shape.
+pub fn fit_width(width: u32, height: u32, max_width: u32) -> (u32, u32) {
+    let w = max_width.min(width.max(1));
+    (w, (height as u64 * w as u64 / width.max(1) as u64).max(1) as u32)
+}
+
+/// Draw `frame` shrunk into the canvas image `small` and read it back.
+pub fn shrink(ctx: &mut Context, frame: &Image, small: &Image) -> GameResult<Option<RgbaImage>> {
+    let mut canvas = Canvas::from_image(ctx, small.clone(), Color::BLACK);
+    canvas.draw(frame, DrawParam::new().scale([small.width() as f32 / frame.width() as f32, small.height() as f32 / frame.height() as f32]));
+    canvas.finish(ctx)?;
+    let pixels = small.to_pixels(ctx)?;
+    Ok(RgbaImage::from_raw(small.width(), small.height(), pixels))
+}
+
+fn encode(writer: impl std::io::Write, frames: Vec<RgbaImage>) -> image::ImageResult<()> {
+    let mut encoder = GifEncoder::new_with_speed(writer, 10);
+    encoder.set_repeat(Repeat::Infinite)?;
+    let delay = Delay::from_numer_denom_ms(1000, FPS as u32);
+    encoder.encode_frames(frames.into_iter().map(|image| Frame::from_parts(image, 0, 0, delay)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_last_few_seconds_and_encodes_them() {
+        let mut clip = ClipBuffer::new();
+        for i in 0..(SECONDS * FPS) as u32 + 5 {
+            clip.push(RgbaImage::from_pixel(4, 3, image::Rgba([i as u8, 0, 0, 255])));
+        }
+        assert_eq!(clip.frames.len(), (SECONDS * FPS) as usize);
+        assert_eq!(clip.frames[0].get_pixel(0, 0)[0], 5, "the oldest frames went first");
+
+        let mut gif = Vec::new();
+        encode(&mut gif, clip.frames.iter().take(3).cloned().collect()).unwrap();
+        assert!(gif.starts_with(b"GIF89a"));
+
+        assert_eq!(fit_width(800, 600, WIDTH), (320, 240));
+        assert_eq!(fit_width(1600, 900, WIDTH), (320, 180));
+        assert_eq!(fit_width(200, 100, WIDTH), (200, 100), "small windows aren't blown up");
+        clip.clear();
+        assert!(clip.is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+//! Pip, the player's pet: trails one tile behind the back of the party, retracing their steps,
+//! and nips at enemies it ends up next to.
+//!
+//! Talking to Pip at a campfire toggles between following and waiting by the fire. A waiting
+//! companion stays in its room until collected again.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawParam};
+
+use crate::assets::Assets;
+use crate::map::TILE_SIZE;
+use crate::party::Trail;
+
+pub const COMPANION_NAME: &str = "Pip";
+/// Damage of a nip, before the enemy's minimum of 1.
+pub const NIP_DAMAGE: i32 = 2;
+/// Seconds between nips.
+const NIP_COOLDOWN: f32 = 1.5;
+/// How long the lunge of a nip shows.
+const NIP_SECS: f32 = 0.2;
+
+pub struct Companion {
+    trail: Trail,
+    /// Room it's waiting in by a campfire, or None while following.
+    waiting_in: Option<usize>,
+    nip_timer: f32,
+    /// Direction of the last nip, for the lunge.
+    nip_dir: (f32, f32),
+}
+
+impl Companion {
+    pub fn new(tile: (i32, i32)) -> Companion {
+        Companion { trail: Trail::new(tile), waiting_in: None, nip_timer: 0.0, nip_dir: (0.0, 0.0) }
+    }
+
+    /// A companion left waiting by a campfire at `tile` in `room`.
+    pub fn waiting(room: usize, tile: (i32, i32)) -> Companion {
+        let mut companion = Companion::new(tile);
+        companion.waiting_in = Some(room);
+        companion
+    }
+
+    pub fn tile(&self) -> (i32, i32) {
+        self.trail.tile()
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.waiting_in.is_none()
+    }
+
+    /// Where it's waiting (room and tile), if it isn't following.
+    pub fn waiting_at(&self) -> Option<(usize, (i32, i32))> {
+        self.waiting_in.map(|room| (room, self.tile()))
+    }
+
+    /// With the player in `room`?
+    pub fn is_in(&self, room: usize) -> bool {
+        self.waiting_in.is_none_or(|r| r == room)
+    }
+
+    /// Seconds until it can nip again.
+    pub fn nip_cooldown(&self) -> f32 {
+        self.nip_timer
+    }
+
+    /// Switch between following and waiting in `room`. Returns true if it now follows.
+    pub fn toggle(&mut self, room: usize) -> bool {
+        self.waiting_in = match self.waiting_in {
+            Some(_) => None,
+            None => Some(room),
+        };
+        let here = self.tile();
+        self.trail.warp_to(here);
+        self.is_following()
+    }
+
+    /// Retrace the steps of whoever is on `ahead` (the player, or the last ally in the party),
+    /// staying one tile behind.
+    pub fn update(&mut self, dt: f32, ahead: (i32, i32)) {
+        self.nip_timer = (self.nip_timer - dt).max(0.0);
+        if self.is_following() {
+            self.trail.follow(dt, ahead);
+        }
+    }
+
+    /// Nip an enemy on a tile next to the companion, if it's ready to. Returns the index of
+    /// the enemy bitten.
+    pub fn try_nip(&mut self, enemy_tiles: impl Iterator<Item = (i32, i32)>) -> Option<usize> {
+        if !self.is_following() || self.nip_timer > 0.0 { return None; }
+        let (tx, ty) = self.tile();
+        let (idx, (ex, ey)) = enemy_tiles.enumerate().find(|(_, (ex, ey))| (ex - tx).abs() + (ey - ty).abs() <= 1)?;
+        self.nip_timer = NIP_COOLDOWN;
+        self.nip_dir = ((ex - tx) as f32, (ey - ty) as f32);
+        Some(idx)
+    }
+
+    /// A small, tinted copy of the player sprite until Pip gets its own art.
+    pub fn draw_scaled(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32)) -> GameResult {
+        let lunge = ((self.nip_timer - (NIP_COOLDOWN - NIP_SECS)).max(0.0) / NIP_SECS) * TILE_SIZE * 0.3;
+        let position = self.trail.position();
+        let center_x = position.x + TILE_SIZE / 2.0 + self.nip_dir.0 * lunge;
+        // sits a little low in its tile, being small
+        let center_y = position.y + TILE_SIZE * 0.65 + self.nip_dir.1 * lunge;
+        let dest = ggez::mint::Point2 { x: offset.0 + center_x * scale, y: offset.1 + center_y * scale };
+        let img_scale = scale * TILE_SIZE * 0.6 / assets.player.width() as f32;
+        canvas.draw(&assets.player, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]).color(Color::new(1.0, 0.75, 0.45, 1.0)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn trails_one_tile_behind_and_catches_up_after_jumps() {
+        let mut pip = Companion::new((2, 2));
+        for tile in [(3, 2), (4, 2), (4, 3)] {
+            for _ in 0..30 {
+                pip.update(0.05, tile);
+            }
+        }
+        assert_eq!(pip.tile(), (4, 2), "retraces the corner, one step behind");
+        pip.update(0.05, (10, 8));
+        assert_eq!(pip.tile(), (10, 8), "teleports along with the player");
+        assert!(!pip.toggle(0));
+        pip.update(0.05, (12, 8));
+        assert_eq!(pip.waiting_at(), Some((0, (10, 8))));
+        assert!(!pip.is_in(1));
+    }
+
+    #[test]
+    fn nips_adjacent_enemies_on_a_cooldown() {
+        let mut pip = Companion::new((5, 5));
+        assert_eq!(pip.try_nip([(8, 8), (5, 6)].into_iter()), Some(1));
+        assert_eq!(pip.try_nip([(5, 6)].into_iter()), None);
+        pip.update(NIP_COOLDOWN, (5, 5));
+        assert_eq!(pip.try_nip([(5, 6)].into_iter()), Some(0));
+    }
+}
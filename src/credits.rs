@@ -0,0 +1,82 @@
+//! Scrolling credits, loaded from `assets/credits.txt`.
+//!
+//! Format: one entry per line. Lines starting with `# ` are section headings, blank lines add
+//! spacing. The roll scrolls up on its own; Z toggles fast-forward and C skips it.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam, PxScale};
+use ggez::input::keyboard::KeyCode;
+use crate::assets::FontRole;
+use crate::input::Action;
+
+/// Pixels per second.
+const SCROLL_SPEED: f32 = 40.0;
+const FAST_FORWARD: f32 = 4.0;
+const LINE_HEIGHT: f32 = 34.0;
+
+pub struct Credits {
+    pub lines: Vec<String>,
+    /// How far the roll has scrolled up, in pixels.
+    scroll: f32,
+    fast: bool,
+}
+
+impl Credits {
+    pub fn from_text(text: &str) -> Credits {
+        Credits { lines: text.lines().map(|l| l.trim_end().to_string()).collect(), scroll: 0.0, fast: false }
+    }
+
+    /// Used when `credits.txt` is missing.
+    pub fn default() -> Credits {
+        Credits::from_text("# TALE\n\nThanks for playing!")
+    }
+
+    pub fn restart(&mut self) {
+        self.scroll = 0.0;
+        self.fast = false;
+    }
+
+    fn total_height(&self) -> f32 {
+        self.lines.len() as f32 * LINE_HEIGHT
+    }
+
+    /// Scroll the roll; returns true once the last line has left the top of the screen.
+    pub fn update(&mut self, dt: f32, screen_h: f32) -> bool {
+        let speed = if self.fast { SCROLL_SPEED * FAST_FORWARD } else { SCROLL_SPEED };
+        self.scroll += speed * dt;
+        self.scroll > screen_h + self.total_height()
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, assets: &crate::assets::Assets) -> GameResult {
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), graphics::Rect::new(0.0, 0.0, w, h), Color::new(0.02, 0.02, 0.05, 1.0))?;
+        canvas.draw(&bg, DrawParam::new());
+
+        let (heading_font, body_font) = (assets.font(FontRole::Title), assets.font(FontRole::Body));
+        for (i, line) in self.lines.iter().enumerate() {
+            let y = h - self.scroll + i as f32 * LINE_HEIGHT;
+            // only lay out lines that are on screen
+            if y < -LINE_HEIGHT || y > h { continue; }
+            let (text, scale, color, font) = match line.strip_prefix("# ") {
+                Some(heading) => (heading, 30.0, Color::new(1.0, 0.85, 0.2, 1.0), &heading_font),
+                None => (line.as_str(), 22.0, Color::WHITE, &body_font),
+            };
+            let txt = Text::new(TextFragment { text: text.to_string(), font: font.clone(), scale: Some(PxScale::from(scale)), color: None });
+            canvas.draw(&txt, DrawParam::new().dest([w / 2.0, y]).offset([0.5, 0.0]).color(color));
+        }
+
+        let hint = Text::new(TextFragment::new(crate::input::prompt("{confirm}: faster   {cancel}: skip")).scale(14.0));
+        canvas.draw(&hint, DrawParam::new().dest([w - 160.0, h - 28.0]).color(Color::new(1.0, 1.0, 1.0, 0.5)));
+        Ok(())
+    }
+
+    /// Returns true when the player skips the credits.
+    pub fn handle_key(&mut self, key: KeyCode) -> bool {
+        match key {
+            k if Action::Confirm.matches(k) => { self.fast = !self.fast; false }
+            k if Action::Cancel.matches(k) => true,
+            _ => false,
+        }
+    }
+}
@@ -0,0 +1,163 @@
+//! Daily challenge run: one world a day, the same for everyone.
+//!
+//! The day's seed comes from its code, `DAILY-2026-10-18` (UTC), which is also what players
+//! share: typed into the seed box it starts that day's run, whichever day it is. Daily runs are
+//! ironman runs with fixed generation options so the world really is the same. Each day's best
+//! result (the highest level reached) is kept in `daily.txt` next to the saves.
+
+use std::fs;
+use std::path::PathBuf;
+
+use crate::save::GenerationOptions;
+
+/// Foes in each room of a daily run.
+const FOES: usize = 3;
+const PREFIX: &str = "DAILY-";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Date {
+    pub year: i32,
+    pub month: u32,
+    pub day: u32,
+}
+
+impl Date {
+    /// The date `days` days after 1970-01-01.
+    pub fn from_unix_days(days: i64) -> Date {
+        // Howard Hinnant's civil_from_days
+        let z = days + 719_468;
+        let era = z.div_euclid(146_097);
+        let doe = z.rem_euclid(146_097);
+        let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+        let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+        let mp = (5 * doy + 2) / 153;
+        let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+        let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+        let year = (yoe + era * 400 + if month <= 2 { 1 } else { 0 }) as i32;
+        Date { year, month, day }
+    }
+
+    /// Today, in UTC so everyone's day turns over together.
+    pub fn today() -> Date {
+        let secs = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+        Date::from_unix_days((secs / 86_400) as i64)
+    }
+
+    /// The shareable code for this day's run.
+    pub fn code(self) -> String {
+        format!("{}{:04}-{:02}-{:02}", PREFIX, self.year, self.month, self.day)
+    }
+
+    /// Read a daily code back (any case, spaces around it ignored).
+    pub fn parse_code(text: &str) -> Option<Date> {
+        let text = text.trim().to_ascii_uppercase();
+        let mut parts = text.strip_prefix(PREFIX)?.splitn(3, '-');
+        let year = parts.next()?.parse().ok()?;
+        let month = parts.next()?.parse().ok().filter(|m| (1..=12).contains(m))?;
+        let day = parts.next()?.parse().ok().filter(|d| (1..=31).contains(d))?;
+        Some(Date { year, month, day })
+    }
+
+    /// World seed for this day's run.
+    pub fn seed(self) -> u64 {
+        crate::rng::parse_seed(&self.code()).unwrap_or(0)
+    }
+}
+
+/// Generation options every daily run uses.
+pub fn generation() -> GenerationOptions {
+    GenerationOptions { enemy_count: FOES, ..GenerationOptions::default() }
+}
+
+/// Best result of each day's run played on this profile.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DailyLog {
+    /// (code, level reached, seconds played), oldest day first.
+    pub results: Vec<(String, u32, f64)>,
+}
+
+impl DailyLog {
+    pub fn path() -> PathBuf {
+        crate::save::dir().join("daily.txt")
+    }
+
+    pub fn load() -> DailyLog {
+        fs::read_to_string(DailyLog::path()).map(|t| DailyLog::from_text(&t)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = fs::create_dir_all(crate::save::dir()).and_then(|_| fs::write(DailyLog::path(), self.to_text()));
+        if let Err(e) = result {
+            println!("Daily: failed to write results: {}", e);
+        }
+    }
+
+    /// One `code=level,seconds` line per day.
+    pub fn to_text(&self) -> String {
+        self.results.iter().map(|(code, level, secs)| format!("{}={},{:.0}\n", code, level, secs)).collect()
+    }
+
+    pub fn from_text(text: &str) -> DailyLog {
+        let results = text.lines().filter_map(|line| {
+            let (code, result) = line.split_once('=')?;
+            let (level, secs) = result.split_once(',')?;
+            Some((code.trim().to_string(), level.trim().parse().ok()?, secs.trim().parse().ok()?))
+        }).collect();
+        DailyLog { results }
+    }
+
+    /// Note a daily run's progress in the saved log, if it's the day's best so far.
+    pub fn record_run(code: &str, level: u32, secs: f64) {
+        let mut log = DailyLog::load();
+        if log.record(code, level, secs) {
+            log.save();
+        }
+    }
+
+    /// Level reached and seconds played in the best run of the day with `code`.
+    pub fn best(&self, code: &str) -> Option<(u32, f64)> {
+        self.results.iter().find(|(c, ..)| c == code).map(|&(_, level, secs)| (level, secs))
+    }
+
+    /// A run of the day with `code` got to `level` in `secs` seconds. Returns whether it beat
+    /// the day's best: a higher level, or the same level sooner.
+    pub fn record(&mut self, code: &str, level: u32, secs: f64) -> bool {
+        match self.results.iter_mut().find(|(c, ..)| c == code) {
+            Some(best) if (level, -secs) <= (best.1, -best.2) => false,
+            Some(best) => {
+                (best.1, best.2) = (level, secs);
+                true
+            }
+            None => {
+                self.results.push((code.to_string(), level, secs));
+                true
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn dates_give_stable_codes_and_the_log_keeps_each_days_best() {
+        assert_eq!(Date::from_unix_days(0), Date { year: 1970, month: 1, day: 1 });
+        assert_eq!(Date::from_unix_days(11_016), Date { year: 2000, month: 2, day: 29 });
+        let day = Date::from_unix_days(20_744);
+        assert_eq!(day.code(), "DAILY-2026-10-18");
+        assert_eq!(Date::parse_code(" daily-2026-10-18 "), Some(day));
+        assert_eq!(Date::parse_code("DAILY-2026-13-01"), None);
+        assert_eq!(Date::parse_code("dragon"), None);
+        assert_eq!(day.seed(), Date::parse_code(&day.code()).unwrap().seed());
+        assert_ne!(day.seed(), Date::from_unix_days(20_745).seed());
+
+        let mut log = DailyLog::default();
+        assert!(log.record(&day.code(), 2, 300.0));
+        assert!(!log.record(&day.code(), 1, 100.0));
+        assert!(log.record(&day.code(), 2, 200.0), "the same level, sooner");
+        assert!(!log.record(&day.code(), 2, 250.0));
+        assert_eq!(log.best(&day.code()), Some((2, 200.0)));
+        assert_eq!(DailyLog::from_text(&log.to_text()), log);
+    }
+}
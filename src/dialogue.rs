@@ -0,0 +1,337 @@
+//! Dialogue box along the bottom of the screen, used for signs and other bits of text.
+//!
+//! Text is split into pages on `|` and typed out a letter at a time, with a blip for every
+//! other letter pitched to the speaker's voice. Z (or Enter) shows the rest of a page that's
+//! still being typed, then turns the page and closes the box after the last one; C (or Escape)
+//! closes it straight away.
+//!
+//! A page can start with a tag naming who's talking and how they look, which holds for the
+//! pages after it until the next tag: `[Mira]` switches speaker, `[Mira:smile]` also picks an
+//! expression and `[:frown]` changes only the expression. The speaker's name goes on a plate
+//! above the box, next to their portrait from `portraits/<name>_<expression>.png` (or
+//! `portraits/<name>.png` with no expression), if there is one.
+//!
+//! Page text can carry markup for colours, wobbling words, pauses, item names and key caps;
+//! see `crate::markup`.
+
+use std::collections::HashMap;
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Image, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+use crate::markup::{self, Marked};
+
+/// Side of the square a portrait is drawn in.
+const PORTRAIT_SIZE: f32 = 108.0;
+/// Letters typed out per second.
+const TYPE_RATE: f32 = 45.0;
+/// A blip sounds on every this many letters.
+const BLIP_EVERY: usize = 2;
+
+#[derive(Debug)]
+struct Page {
+    speaker: Option<String>,
+    expression: Option<String>,
+    /// The text without its markup, as it's typed out.
+    text: String,
+    marked: Marked,
+}
+
+pub struct DialogueBox {
+    pub visible: bool,
+    pages: Vec<Page>,
+    page: usize,
+    /// Characters of the current page typed out so far.
+    typed: f32,
+    /// Seconds left of a `<pause>` in the page.
+    hold: f32,
+    /// Pauses in the page held so far, so each only holds once.
+    paused: usize,
+    /// Multiplies `TYPE_RATE`; infinite shows whole pages at once (see `Options::text_speed`).
+    pub speed: f32,
+    /// Portraits by path, loaded the first time they're shown; None for ones that don't exist.
+    portraits: HashMap<String, Option<Image>>,
+}
+
+/// Split a leading `[speaker:expression]` tag off a page.
+fn split_tag(page: &str) -> (Option<(&str, Option<&str>)>, &str) {
+    let Some(rest) = page.strip_prefix('[') else { return (None, page) };
+    let Some((tag, text)) = rest.split_once(']') else { return (None, page) };
+    let tag = match tag.split_once(':') {
+        Some((speaker, expression)) => (speaker.trim(), Some(expression.trim()).filter(|e| !e.is_empty())),
+        None => (tag.trim(), None),
+    };
+    (Some(tag), text.trim())
+}
+
+/// Pitch of a speaker's blips, from 0.8 to about 1.25, always the same for the same name.
+/// Narration (no speaker) is at 1.
+pub fn voice_pitch(speaker: Option<&str>) -> f32 {
+    let Some(name) = speaker else { return 1.0 };
+    let hash = name.to_lowercase().bytes().fold(2166136261u32, |h, b| (h ^ b as u32).wrapping_mul(16777619));
+    0.8 + (hash % 46) as f32 / 100.0
+}
+
+/// Portrait file for a speaker and expression: lower case, spaces as underscores.
+fn portrait_path(speaker: &str, expression: Option<&str>) -> String {
+    let name = speaker.trim().to_lowercase().replace(' ', "_");
+    match expression {
+        Some(expression) => format!("/portraits/{}_{}.png", name, expression.to_lowercase().replace(' ', "_")),
+        None => format!("/portraits/{}.png", name),
+    }
+}
+
+impl DialogueBox {
+    pub fn new() -> DialogueBox {
+        DialogueBox { visible: false, pages: Vec::new(), page: 0, typed: 0.0, hold: 0.0, paused: 0, speed: 1.0, portraits: HashMap::new() }
+    }
+
+    /// Show `text`, optionally with a name tag for who's talking. Tags in the text take over
+    /// from `speaker` on the pages they start.
+    pub fn open(&mut self, speaker: Option<&str>, text: &str) {
+        let (mut speaker, mut expression) = (speaker.map(str::to_string), None);
+        self.pages.clear();
+        for page in text.split('|').map(str::trim) {
+            let (tag, text) = split_tag(page);
+            match tag {
+                Some(("", Some(e))) => expression = Some(e.to_string()),
+                Some((s, e)) => {
+                    speaker = Some(s.to_string()).filter(|s| !s.is_empty());
+                    expression = e.map(str::to_string);
+                }
+                None => {}
+            }
+            if text.is_empty() { continue; }
+            let marked = markup::parse(text);
+            self.pages.push(Page { speaker: speaker.clone(), expression: expression.clone(), text: marked.plain(), marked });
+        }
+        if self.pages.is_empty() { return; }
+        self.page = 0;
+        self.typed = 0.0;
+        self.hold = 0.0;
+        self.paused = 0;
+        self.visible = true;
+    }
+
+    /// Type out more of the page. Returns the pitch to play a blip at, on frames that get one.
+    pub fn update(&mut self, dt: f32) -> Option<f32> {
+        if !self.visible { return None; }
+        let page = self.pages.get(self.page)?;
+        let total = page.text.chars().count();
+        let before = self.typed as usize;
+        if before >= total { return None; }
+        if self.hold > 0.0 && self.speed.is_finite() {
+            self.hold -= dt;
+            return None;
+        }
+        // min() also turns the NaN of an instant speed on a zero-length frame into a full page
+        self.typed = (self.typed + dt * TYPE_RATE * self.speed).min(total as f32);
+        // stop at the next pause reached (even one before the first letter); instant text
+        // doesn't wait
+        if let Some(&(at, secs)) = page.marked.pauses.get(self.paused).filter(|&&(at, _)| at >= before && at <= self.typed as usize)
+            && self.speed.is_finite() {
+            self.typed = at as f32;
+            self.hold = secs;
+            self.paused += 1;
+        }
+        let after = self.typed as usize;
+        // spaces and punctuation come in quietly
+        let blip = page.text.chars().enumerate().take(after).skip(before).any(|(i, c)| c.is_alphanumeric() && i % BLIP_EVERY == 0);
+        blip.then(|| voice_pitch(page.speaker.as_deref()))
+    }
+
+    /// True while the current page is still being typed out.
+    pub fn typing(&self) -> bool {
+        self.current().is_some_and(|page| (self.typed as usize) < page.text.chars().count())
+    }
+
+    #[cfg(test)]
+    pub fn current_page(&self) -> Option<&str> {
+        self.current().map(|page| page.text.as_str())
+    }
+
+    fn current(&self) -> Option<&Page> {
+        if self.visible { self.pages.get(self.page) } else { None }
+    }
+
+    /// Who's talking on the current page, and how they look.
+    pub fn current_speaker(&self) -> Option<(&str, Option<&str>)> {
+        let page = self.current()?;
+        Some((page.speaker.as_deref()?, page.expression.as_deref()))
+    }
+
+    fn portrait(&mut self, ctx: &mut Context, speaker: &str, expression: Option<&str>) -> Option<Image> {
+        let path = portrait_path(speaker, expression);
+        self.portraits.entry(path.clone()).or_insert_with(|| {
+            match Image::from_path(ctx, &path) {
+                Ok(image) => Some(image),
+                Err(_) => {
+                    println!("Dialogue: no portrait {}", path);
+                    None
+                }
+            }
+        }).clone()
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let Some(spans) = self.current().map(|page| page.marked.take(self.typed as usize)) else { return Ok(()) };
+        let speaker = self.current_speaker().map(|(name, expression)| (name.to_string(), expression.map(str::to_string)));
+        let portrait = match &speaker {
+            Some((name, expression)) => self.portrait(ctx, name, expression.as_deref()),
+            None => None,
+        };
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = (w - 80.0).min(720.0);
+        let box_h = 140.0;
+        let left = (w - box_w) / 2.0;
+        let top = h - box_h - 24.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let mut text_left = left + 20.0;
+        if let Some(image) = &portrait {
+            let frame = graphics::Rect::new(left + 16.0, top + (box_h - PORTRAIT_SIZE) / 2.0, PORTRAIT_SIZE, PORTRAIT_SIZE);
+            let fit = PORTRAIT_SIZE / image.width().max(image.height()).max(1) as f32;
+            let dest = [frame.x + (PORTRAIT_SIZE - image.width() as f32 * fit) / 2.0, frame.y + (PORTRAIT_SIZE - image.height() as f32 * fit) / 2.0];
+            canvas.draw(image, DrawParam::new().dest(dest).scale([fit, fit]));
+            let edge = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), frame, Color::WHITE)?;
+            canvas.draw(&edge, DrawParam::new());
+            text_left = frame.x + PORTRAIT_SIZE + 16.0;
+        }
+        if let Some((speaker, _)) = &speaker {
+            // name plate sitting on the box's top edge
+            let name = Text::new(TextFragment::new(speaker.as_str()).scale(20.0));
+            let plate_w = name.measure(ctx)?.x + 24.0;
+            let plate = graphics::Rect::new(left + 12.0, top - 30.0, plate_w, 32.0);
+            let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), plate, Color::new(0.0, 0.12, 0.4, 0.98))?;
+            canvas.draw(&bg, DrawParam::new());
+            let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), plate, Color::WHITE)?;
+            canvas.draw(&border, DrawParam::new());
+            canvas.draw(&name, DrawParam::new().dest([plate.x + 12.0, plate.y + 6.0]).color(crate::palette::selection()));
+        }
+        let t = ctx.time.time_since_start().as_secs_f32();
+        markup::draw(ctx, canvas, &spans, [text_left, top + 16.0], left + box_w - 20.0 - text_left, 20.0, t)?;
+
+        let more = if self.typing() { "" } else if self.page + 1 < self.pages.len() { "{confirm}: next" } else { "{confirm}: close" };
+        let prompt = Text::new(TextFragment::new(crate::input::prompt(more)).scale(14.0));
+        canvas.draw(&prompt, DrawParam::new().dest([left + box_w - 16.0, top + box_h - 24.0]).offset([1.0, 0.0]).color(Color::new(0.85, 0.9, 1.0, 1.0)));
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) {
+        if !self.visible { return; }
+        match key {
+            k if Action::Confirm.matches(k) && self.typing() => (self.typed, self.hold) = (f32::MAX, 0.0),
+            k if Action::Confirm.matches(k) => {
+                self.page += 1;
+                self.typed = 0.0;
+                self.hold = 0.0;
+                self.paused = 0;
+                if self.page >= self.pages.len() { self.visible = false; }
+            }
+            k if Action::Cancel.matches(k) => self.visible = false,
+            _ => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Finish typing the page, then turn it.
+    fn turn(dialogue: &mut DialogueBox) {
+        dialogue.handle_key(KeyCode::Z);
+        dialogue.handle_key(KeyCode::Z);
+    }
+
+    #[test]
+    fn pages_advance_then_close() {
+        let mut dialogue = DialogueBox::new();
+        dialogue.open(None, "First page. | Second page.");
+        assert_eq!(dialogue.current_page(), Some("First page."));
+        turn(&mut dialogue);
+        assert_eq!(dialogue.current_page(), Some("Second page."));
+        turn(&mut dialogue);
+        assert!(!dialogue.visible);
+        dialogue.open(None, "  ");
+        assert!(!dialogue.visible, "blank text doesn't open an empty box");
+    }
+
+    #[test]
+    fn tags_switch_speaker_and_expression_for_the_pages_after_them() {
+        let mut dialogue = DialogueBox::new();
+        dialogue.open(Some("Sign"), "Keep out. | [Mira:smile] Oh, hello! | Come in. | [:frown] Mind the spikes. | [Bram] Hm. | []");
+        assert_eq!((dialogue.current_page(), dialogue.current_speaker()), (Some("Keep out."), Some(("Sign", None))));
+        turn(&mut dialogue);
+        assert_eq!((dialogue.current_page(), dialogue.current_speaker()), (Some("Oh, hello!"), Some(("Mira", Some("smile")))));
+        turn(&mut dialogue);
+        assert_eq!(dialogue.current_speaker(), Some(("Mira", Some("smile"))));
+        turn(&mut dialogue);
+        assert_eq!(dialogue.current_speaker(), Some(("Mira", Some("frown"))));
+        turn(&mut dialogue);
+        assert_eq!((dialogue.current_page(), dialogue.current_speaker()), (Some("Hm."), Some(("Bram", None))));
+        turn(&mut dialogue);
+        assert!(!dialogue.visible, "a bare tag is not a page of its own");
+        assert_eq!(portrait_path("Old Tom", Some("Sad")), "/portraits/old_tom_sad.png");
+    }
+
+    #[test]
+    fn pages_type_out_with_blips_pitched_by_speaker() {
+        let mut dialogue = DialogueBox::new();
+        dialogue.open(Some("Mira"), "Hi there.");
+        assert!(dialogue.typing());
+        assert_eq!(dialogue.update(0.0), None);
+        assert_eq!(dialogue.update(1.0 / TYPE_RATE), Some(voice_pitch(Some("Mira"))), "the first letter blips");
+        assert_eq!(dialogue.update(1.0 / TYPE_RATE), None, "every other letter is quiet");
+        dialogue.handle_key(KeyCode::Z);
+        assert!(!dialogue.typing() && dialogue.visible, "Z shows the rest of the page first");
+        assert_eq!(dialogue.update(1.0), None);
+        dialogue.handle_key(KeyCode::Z);
+        assert!(!dialogue.visible);
+        assert_eq!(voice_pitch(Some("Bram")), voice_pitch(Some("bram")));
+        assert_ne!(voice_pitch(Some("Bram")), voice_pitch(Some("Mira")));
+        assert!((0.8..1.26).contains(&voice_pitch(Some("Old Tom"))));
+        assert_eq!(voice_pitch(None), 1.0);
+    }
+
+    #[test]
+    fn markup_is_typed_as_plain_text_and_pauses_hold_it() {
+        let mut dialogue = DialogueBox::new();
+        dialogue.open(None, "<red>Wait</>.<pause 1> Go!");
+        assert_eq!(dialogue.current_page(), Some("Wait. Go!"));
+        dialogue.update(10.0 / TYPE_RATE);
+        assert_eq!(dialogue.typed, 5.0, "typing stops at the pause");
+        dialogue.update(0.5);
+        assert_eq!(dialogue.typed, 5.0);
+        dialogue.update(0.6);
+        dialogue.update(1.0);
+        assert!(!dialogue.typing(), "and carries on after it");
+
+        dialogue.open(None, "<pause 1>Hm.");
+        dialogue.update(1.0 / TYPE_RATE);
+        assert_eq!(dialogue.typed, 0.0, "a pause before the first letter holds too");
+        dialogue.update(1.1);
+        dialogue.update(1.0 / TYPE_RATE);
+        assert_eq!(dialogue.typed, 1.0, "but only once");
+    }
+
+    #[test]
+    fn text_speed_scales_typing() {
+        let mut dialogue = DialogueBox::new();
+        dialogue.speed = 2.0;
+        dialogue.open(None, "Four");
+        dialogue.update(1.0 / TYPE_RATE);
+        assert_eq!(dialogue.typed, 2.0);
+        dialogue.speed = f32::INFINITY;
+        dialogue.update(0.0);
+        assert!(!dialogue.typing(), "instant text needs no time at all");
+    }
+}
@@ -0,0 +1,151 @@
+//! Random encounters: wild rooms and what lurks in them.
+//!
+//! A room is wild if its data file has a `wild=min,max` line, the number of steps between
+//! encounters (rolled afresh after each one). Its `encounter=weight:foe,foe` lines are the
+//! groups that can turn up, picked by weight. Foe ids are looked up in `FOES`. Rolls use the
+//! world's seeded RNG, so replays meet the same foes on the same steps.
+
+use crate::loot::{self, LootTable};
+use crate::rng::GameRng;
+use crate::stats::XP_PER_KILL;
+use crate::status::StatusKind;
+
+/// A kind of foe met in random battles.
+pub struct FoeDef {
+    pub id: &'static str,
+    pub name: &'static str,
+    pub hp: i32,
+    /// Raw damage per hit (before the target's defense).
+    pub damage: i32,
+    pub xp: u32,
+    /// Inclusive range of gold dropped when defeated.
+    pub gold: (u32, u32),
+    /// Status effect (and its duration in seconds) applied to the player on a hit.
+    pub inflicts: Option<(StatusKind, f32)>,
+    /// What it might leave behind.
+    pub loot: &'static LootTable,
+}
+
+pub const FOES: &[FoeDef] = &[
+    FoeDef { id: "slime", name: "Slime", hp: 10, damage: 3, xp: XP_PER_KILL, gold: (2, 6), inflicts: None, loot: &loot::SLIME },
+    FoeDef { id: "viper", name: "Viper", hp: 8, damage: 4, xp: XP_PER_KILL + 2, gold: (3, 7), inflicts: Some((StatusKind::Poison, 5.0)), loot: &loot::VIPER },
+    FoeDef { id: "mudling", name: "Mudling", hp: 14, damage: 3, xp: XP_PER_KILL + 2, gold: (2, 8), inflicts: Some((StatusKind::Slow, 3.0)), loot: &loot::MUDLING },
+    FoeDef { id: "ember", name: "Ember", hp: 9, damage: 5, xp: XP_PER_KILL + 4, gold: (4, 10), inflicts: Some((StatusKind::Burn, 3.0)), loot: &loot::EMBER },
+];
+
+pub fn find_foe(id: &str) -> Option<&'static FoeDef> {
+    FOES.iter().find(|def| def.id == id)
+}
+
+/// A wild room's encounter rate and the groups that can turn up there.
+pub struct EncounterTable {
+    /// Steps between encounters, inclusive.
+    steps: (u32, u32),
+    groups: Vec<(u32, Vec<&'static FoeDef>)>,
+}
+
+impl EncounterTable {
+    /// Read the `wild=` and `encounter=` lines of a room data file. None unless the room is
+    /// wild and has at least one group.
+    pub fn parse(text: &str) -> Option<EncounterTable> {
+        let mut steps = None;
+        let mut groups = Vec::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            match key.trim() {
+                "wild" => {
+                    steps = value.split_once(',')
+                        .and_then(|(lo, hi)| Some((lo.trim().parse::<u32>().ok()?, hi.trim().parse::<u32>().ok()?)))
+                        .map(|(lo, hi)| (lo.max(1), hi.max(lo.max(1))));
+                    if steps.is_none() {
+                        println!("Encounters: bad step range {:?}, skipping", line);
+                    }
+                }
+                "encounter" => {
+                    let Some((weight, foes)) = value.split_once(':') else {
+                        println!("Encounters: can't read {:?}, skipping", line);
+                        continue;
+                    };
+                    let weight = weight.trim().parse::<u32>().unwrap_or(0);
+                    let foes: Option<Vec<_>> = foes.split(',').map(|id| find_foe(id.trim())).collect();
+                    match foes {
+                        Some(foes) if weight > 0 && !foes.is_empty() => groups.push((weight, foes)),
+                        _ => println!("Encounters: can't read {:?}, skipping", line),
+                    }
+                }
+                _ => {}
+            }
+        }
+        let steps = steps?;
+        if groups.is_empty() { return None; }
+        Some(EncounterTable { steps, groups })
+    }
+
+    fn roll_steps(&self, rng: &mut GameRng) -> u32 {
+        rng.range(self.steps.0 as i32, self.steps.1 as i32 + 1) as u32
+    }
+
+    /// Pick a group by weight.
+    pub fn roll_group(&self, rng: &mut GameRng) -> Vec<&'static FoeDef> {
+        let total: u32 = self.groups.iter().map(|(weight, _)| weight).sum();
+        let mut roll = rng.range(0, total as i32) as u32;
+        for (weight, foes) in &self.groups {
+            if roll < *weight { return foes.clone(); }
+            roll -= weight;
+        }
+        self.groups[0].1.clone()
+    }
+}
+
+/// Counts the player's steps through wild rooms down to the next encounter.
+#[derive(Default)]
+pub struct StepCounter {
+    left: Option<u32>,
+}
+
+impl StepCounter {
+    /// Count a step in a room with `table`. Returns true when it's time for a battle.
+    pub fn step(&mut self, table: &EncounterTable, rng: &mut GameRng) -> bool {
+        let left = self.left.get_or_insert_with(|| table.roll_steps(rng));
+        *left = left.saturating_sub(1);
+        if *left > 0 { return false; }
+        self.left = None;
+        true
+    }
+
+    /// Start counting afresh, e.g. in a new room.
+    pub fn reset(&mut self) {
+        self.left = None;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tables_read_the_step_range_and_weighted_groups() {
+        let text = "name=Cellar\nwild=3,3\nencounter=2:slime,viper\nencounter=1:dragon\nencounter=1:ember";
+        let table = EncounterTable::parse(text).unwrap();
+        assert_eq!(table.steps, (3, 3));
+        assert_eq!(table.groups.len(), 2, "the group with an unknown foe is skipped");
+        assert!(EncounterTable::parse("encounter=1:slime").is_none(), "not wild without wild=");
+        let mut rng = GameRng::new(7);
+        for _ in 0..20 {
+            let group = table.roll_group(&mut rng);
+            assert!(group.len() == 2 || group[0].id == "ember");
+        }
+    }
+
+    #[test]
+    fn the_counter_trips_after_the_rolled_steps_and_starts_over() {
+        let table = EncounterTable::parse("wild=3,3\nencounter=1:slime").unwrap();
+        let mut rng = GameRng::new(1);
+        let mut counter = StepCounter::default();
+        let trips: Vec<bool> = (0..6).map(|_| counter.step(&table, &mut rng)).collect();
+        assert_eq!(trips, vec![false, false, true, false, false, true]);
+        counter.step(&table, &mut rng);
+        counter.reset();
+        assert!(!counter.step(&table, &mut rng) && !counter.step(&table, &mut rng));
+    }
+}
@@ -1,10 +1,22 @@
 use ggez::{Context, GameResult};
-use ggez::graphics::{Canvas, DrawParam};
+use ggez::graphics::{Canvas, Color, DrawParam, Text, TextFragment};
 use nalgebra as na;
 
 use crate::player::Player;
 use crate::map::{Map, TILE_SIZE};
 use crate::assets::Assets;
+use crate::status::StatusKind;
+use crate::projectile::{Owner, Projectile};
+use crate::loot::LootTable;
+use crate::stealth::{Alertness, Awareness};
+
+/// Seconds between shots for ranged enemies.
+const SHOOT_COOLDOWN: f32 = 2.5;
+/// Ranged enemies only fire when the player is within this many tiles in a straight line.
+const SHOOT_RANGE: f32 = 5.0;
+/// An enemy touches the player (and hurts them) when squares this size, centred on their
+/// tiles, overlap.
+pub const CONTACT_SIZE: f32 = TILE_SIZE * 0.8;
 
 pub struct Enemy {
     position: na::Point2<f32>,
@@ -12,17 +24,121 @@ pub struct Enemy {
     grid_size: f32,
     moving: bool,
     target: na::Point2<f32>,
+    pub hp: i32,
+    /// Raw damage dealt to the player on contact (before the player's defense).
+    pub damage: i32,
+    pub xp_reward: u32,
+    /// Inclusive range of gold dropped when defeated.
+    pub gold_drop: (u32, u32),
+    /// Status effect (and its duration in seconds) applied to the player on a hit.
+    pub inflicts: Option<(StatusKind, f32)>,
+    /// Ranged enemies spit projectiles at the player when lined up with them.
+    pub ranged: bool,
+    /// What it might drop besides gold when defeated.
+    pub loot: Option<&'static LootTable>,
+    /// Spawn number of an enemy placed with the world; once defeated it stays gone, even
+    /// across saves.
+    pub unique: Option<u32>,
+    shoot_timer: f32,
+    /// Whether it has noticed the player; it only gives chase, or shoots, once alerted.
+    awareness: Awareness,
 }
 
 impl Enemy {
-    pub fn new(_ctx: &mut Context) -> GameResult<Enemy> {
-        let pos = na::Point2::new(200.0, 200.0);
-        Ok(Enemy { position: pos, speed: 80.0, grid_size: 32.0, moving: false, target: pos })
+    /// Create an enemy at a world pixel position (top-left of its tile).
+    pub fn at(x: f32, y: f32) -> Enemy {
+        let pos = na::Point2::new(x, y);
+        Enemy { position: pos, speed: 80.0, grid_size: 32.0, moving: false, target: pos, hp: 10, damage: 3, xp_reward: crate::stats::XP_PER_KILL, gold_drop: (2, 6), inflicts: None, ranged: false, loot: None, unique: None, shoot_timer: SHOOT_COOLDOWN, awareness: Awareness::default() }
+    }
+
+    /// An enemy whose hits also apply a status effect.
+    pub fn with_status(mut self, kind: StatusKind, duration: f32) -> Enemy {
+        self.inflicts = Some((kind, duration));
+        self
+    }
+
+    pub fn get_position(&self) -> na::Point2<f32> {
+        self.position
+    }
+
+    pub fn with_loot(mut self, table: &'static LootTable) -> Enemy {
+        self.loot = Some(table);
+        self
+    }
+
+    pub fn with_unique(mut self, id: u32) -> Enemy {
+        self.unique = Some(id);
+        self
+    }
+
+    pub fn ranged(mut self) -> Enemy {
+        self.ranged = true;
+        self
+    }
+
+    /// Count down the shot timer and fire at the player if they share a row or column
+    /// within range.
+    pub fn try_shoot(&mut self, dt: f32, player: &Player) -> Option<Projectile> {
+        if !self.ranged || self.alertness() != Alertness::Alerted { return None; }
+        self.shoot_timer = (self.shoot_timer - dt).max(0.0);
+        if self.shoot_timer > 0.0 { return None; }
+        let (tx, ty) = self.tile();
+        let (px, py) = player.tile();
+        let dir = if tx == px && ty != py {
+            (0.0, (py - ty).signum() as f32)
+        } else if ty == py && tx != px {
+            ((px - tx).signum() as f32, 0.0)
+        } else {
+            return None;
+        };
+        if ((px - tx).abs() + (py - ty).abs()) as f32 > SHOOT_RANGE { return None; }
+        self.shoot_timer = SHOOT_COOLDOWN;
+        let mut shot = Projectile::from_tile(Owner::Enemy, (tx, ty), dir, 200.0, SHOOT_RANGE + 1.0, self.damage);
+        if let Some((kind, secs)) = self.inflicts {
+            shot = shot.with_status(kind, secs);
+        }
+        Some(shot.with_look(Color::new(1.0, 0.45, 0.1, 1.0), 5.0))
+    }
+
+    pub fn alertness(&self) -> Alertness {
+        self.awareness.state()
+    }
+
+    /// What its AI is up to, for the entity inspector.
+    pub fn ai_state(&self) -> String {
+        let mut state = if self.moving {
+            let to = (((self.target.x + TILE_SIZE / 2.0) / TILE_SIZE) as i32, ((self.target.y + TILE_SIZE / 2.0) / TILE_SIZE) as i32);
+            format!("chasing, stepping to {:?}", to)
+        } else {
+            format!("{:?}", self.alertness()).to_lowercase()
+        };
+        if self.ranged {
+            state += &if self.shoot_timer > 0.0 { format!(", reloading {:.1}s", self.shoot_timer) } else { ", ready to shoot".to_string() };
+        }
+        state
     }
 
-    pub fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets) -> GameResult {
-        // default draw delegates to scaled draw with scale=1.0, offset=(0,0), entity_scale=1.0
-        self.draw_scaled(_ctx, canvas, assets, 1.0, (0.0, 0.0), 1.0)
+    /// Tile the enemy occupies (by centre point).
+    pub fn tile(&self) -> (i32, i32) {
+        (((self.position.x + TILE_SIZE / 2.0) / TILE_SIZE) as i32, ((self.position.y + TILE_SIZE / 2.0) / TILE_SIZE) as i32)
+    }
+
+    /// Apply damage; returns true when the enemy is defeated.
+    pub fn take_damage(&mut self, amount: i32) -> bool {
+        self.awareness.alert();
+        self.hp -= amount.max(1);
+        self.hp <= 0
+    }
+
+    /// True when this enemy's body overlaps the player's (contact damage).
+    pub fn touches(&self, player: &Player) -> bool {
+        self.touches_at(player.get_position())
+    }
+
+    /// Overlapping someone whose tile's top-left is at `position`?
+    pub fn touches_at(&self, position: na::Point2<f32>) -> bool {
+        let d = self.position - position;
+        d.x.abs() < CONTACT_SIZE && d.y.abs() < CONTACT_SIZE
     }
 
     /// Draw with global scale and screen offset. `entity_scale` is how many tiles this
@@ -34,14 +150,28 @@ impl Enemy {
         let draw_y = offset.1 + center_y * scale;
         let dest = ggez::mint::Point2 { x: draw_x, y: draw_y };
         let img_scale = scale * TILE_SIZE * entity_scale / assets.enemy.width() as f32;
-        canvas.draw(&assets.enemy, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
+        // tint enemies by the status they inflict so the player can tell them apart
+        let tint = match self.inflicts {
+            Some((kind, _)) => kind.color(),
+            None => Color::WHITE,
+        };
+        canvas.draw(&assets.enemy, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]).color(tint));
+        // "?" while it's suspicious, "!" once it's after the player
+        if let Some(mark) = self.alertness().mark() {
+            let color = if self.alertness() == Alertness::Alerted { Color::RED } else { Color::YELLOW };
+            let text = Text::new(TextFragment::new(mark).scale(TILE_SIZE * 0.6 * scale));
+            canvas.draw(&text, DrawParam::new().dest([draw_x, draw_y - TILE_SIZE * entity_scale * 0.5 * scale]).offset([0.5, 1.0]).color(color));
+        }
         Ok(())
     }
 
-    pub fn update(&mut self, _ctx: &mut Context, dt: f32, player: &Player, map: &Map) {
-        // Grid-like AI: if not moving, set a target one grid step towards the player
+    pub fn update(&mut self, dt: f32, player: &Player, map: &Map) {
         let player_pos = player.get_position();
-        if !self.moving {
+        let distance = (player_pos - self.position).magnitude() / TILE_SIZE;
+        self.awareness.update(dt, distance, player.gait.detection_radius(player.moving));
+        // Grid-like AI: once alerted, and not already moving, set a target one grid step towards
+        // the player; until then it stays put
+        if !self.moving && self.alertness() == Alertness::Alerted {
             let dx = (player_pos.x - self.position.x).signum();
             let dy = (player_pos.y - self.position.y).signum();
             if dx != 0.0 {
@@ -58,14 +188,14 @@ impl Enemy {
             let dist = (dir.x*dir.x + dir.y*dir.y).sqrt();
             let step = self.speed * dt;
             if dist <= step {
-                if !map.is_solid_at_point(self.target.x + 16.0, self.target.y + 16.0) {
+                if !map.blocks_enemies_at_point(self.target.x + 16.0, self.target.y + 16.0) {
                     self.position = self.target;
                 }
                 self.moving = false;
             } else {
                 let n = na::Vector2::new(dir.x/dist, dir.y/dist);
                 let new_pos = na::Point2::new(self.position.x + n.x * step, self.position.y + n.y * step);
-                if !map.is_solid_at_point(new_pos.x + 16.0, new_pos.y + 16.0) {
+                if !map.blocks_enemies_at_point(new_pos.x + 16.0, new_pos.y + 16.0) {
                     self.position = new_pos;
                 } else {
                     self.moving = false;
@@ -74,3 +204,36 @@ impl Enemy {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn closes_in_on_the_player_a_tile_at_a_time() {
+        let map = Map::new();
+        let player = Player::test_new();
+        let mut enemy = Enemy::at(6.0 * TILE_SIZE, 3.0 * TILE_SIZE);
+        // already after the player (see `crate::stealth`)
+        enemy.awareness.alert();
+        for _ in 0..40 {
+            enemy.update(0.05, &player, &map);
+        }
+        assert_eq!(enemy.position, na::Point2::new(3.0 * TILE_SIZE, 3.0 * TILE_SIZE), "caught up, on the grid");
+    }
+
+    #[test]
+    fn stays_put_until_it_notices_the_player() {
+        let map = Map::new();
+        let mut player = Player::test_new();
+        player.gait = crate::stealth::Gait::Sneak;
+        let mut enemy = Enemy::at(6.0 * TILE_SIZE, 3.0 * TILE_SIZE).ranged();
+        for _ in 0..40 {
+            enemy.update(0.05, &player, &map);
+            assert!(enemy.try_shoot(0.05, &player).is_none());
+        }
+        assert_eq!((enemy.position, enemy.alertness()), (na::Point2::new(6.0 * TILE_SIZE, 3.0 * TILE_SIZE), Alertness::Unaware), "too far to spot a sneaking player");
+        enemy.take_damage(1);
+        assert_eq!(enemy.alertness(), Alertness::Alerted);
+    }
+}
@@ -0,0 +1,98 @@
+//! Game events: things that happen in one part of the game that others want to hear about.
+//!
+//! Combat and map code `publish` what happened and move on; the bus queues each event for
+//! every subsystem subscribed to its topic, and `Game` hands them out once a frame. That way
+//! achievements and story variables (and later quests or sounds) only need a subscription,
+//! not hooks into the code that fights enemies or opens doors.
+
+use crate::items::ItemId;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Event {
+    /// An enemy was defeated, out in the world or in a battle. `unique` is its spawn number
+    /// for enemies that stay dead.
+    EnemyDied { room: usize, unique: Option<u32> },
+    DoorOpened { room: usize, tile: (i32, i32) },
+    ItemPickedUp(ItemId),
+    /// The player arrived in a room, including the one a game starts or loads in.
+    RoomEntered(usize),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Topic {
+    EnemyDied,
+    DoorOpened,
+    ItemPickedUp,
+    RoomEntered,
+}
+
+impl Event {
+    pub fn topic(&self) -> Topic {
+        match self {
+            Event::EnemyDied { .. } => Topic::EnemyDied,
+            Event::DoorOpened { .. } => Topic::DoorOpened,
+            Event::ItemPickedUp(_) => Topic::ItemPickedUp,
+            Event::RoomEntered(_) => Topic::RoomEntered,
+        }
+    }
+}
+
+/// Parts of the game that listen for events.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Subscriber {
+    /// Profile counters and achievements (see `crate::achievements`).
+    Achievements,
+    /// Story flags and counters (see `crate::game_vars`).
+    Vars,
+    /// First-time hints (see `crate::tutorial`).
+    Tutorial,
+}
+
+#[derive(Default)]
+pub struct EventBus {
+    subscriptions: Vec<(Topic, Subscriber)>,
+    queue: Vec<(Subscriber, Event)>,
+}
+
+impl EventBus {
+    /// Have `subscriber` sent every event on `topic` from now on.
+    pub fn subscribe(&mut self, topic: Topic, subscriber: Subscriber) {
+        if !self.subscriptions.contains(&(topic, subscriber)) {
+            self.subscriptions.push((topic, subscriber));
+        }
+    }
+
+    /// Queue an event for everyone subscribed to its topic. Events nobody listens for are
+    /// dropped.
+    pub fn publish(&mut self, event: Event) {
+        let topic = event.topic();
+        for &(_, subscriber) in self.subscriptions.iter().filter(|(t, _)| *t == topic) {
+            self.queue.push((subscriber, event.clone()));
+        }
+    }
+
+    /// Everything queued since the last call, in the order it was published.
+    pub fn drain(&mut self) -> Vec<(Subscriber, Event)> {
+        std::mem::take(&mut self.queue)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn events_reach_only_their_subscribers_in_order() {
+        let mut bus = EventBus::default();
+        bus.subscribe(Topic::EnemyDied, Subscriber::Achievements);
+        bus.subscribe(Topic::EnemyDied, Subscriber::Vars);
+        bus.subscribe(Topic::EnemyDied, Subscriber::Vars);
+        bus.subscribe(Topic::RoomEntered, Subscriber::Vars);
+        bus.publish(Event::DoorOpened { room: 0, tile: (6, 0) });
+        bus.publish(Event::EnemyDied { room: 1, unique: Some(3) });
+        bus.publish(Event::RoomEntered(1));
+        let died = Event::EnemyDied { room: 1, unique: Some(3) };
+        assert_eq!(bus.drain(), vec![(Subscriber::Achievements, died.clone()), (Subscriber::Vars, died), (Subscriber::Vars, Event::RoomEntered(1))]);
+        assert!(bus.drain().is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! Fog of war: which tiles of each room the player has seen.
+//!
+//! Everything within `SIGHT_RADIUS` tiles of the player counts as seen, for good. Unseen tiles
+//! are drawn under a dark fog. The record is kept in the save file as one hex string of tile
+//! bits per room (row-major, four tiles per digit), so maps can be drawn from it later.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
+
+use crate::map::TILE_SIZE;
+
+/// How far the player can see, in tiles.
+pub const SIGHT_RADIUS: i32 = 4;
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Exploration {
+    /// Seen flags per room, row-major; empty for rooms never visited.
+    rooms: Vec<Vec<bool>>,
+}
+
+impl Exploration {
+    /// Mark everything within `radius` tiles of `centre` in a `width` x `height` room as seen.
+    pub fn reveal(&mut self, room: usize, width: usize, height: usize, centre: (i32, i32), radius: i32) {
+        if self.rooms.len() <= room {
+            self.rooms.resize(room + 1, Vec::new());
+        }
+        let seen = &mut self.rooms[room];
+        seen.resize(width * height, false);
+        for y in (centre.1 - radius).max(0)..=(centre.1 + radius).min(height as i32 - 1) {
+            for x in (centre.0 - radius).max(0)..=(centre.0 + radius).min(width as i32 - 1) {
+                let (dx, dy) = (x - centre.0, y - centre.1);
+                if dx * dx + dy * dy <= radius * radius {
+                    seen[y as usize * width + x as usize] = true;
+                }
+            }
+        }
+    }
+
+    /// Has the player seen this tile of a `width`-wide room?
+    pub fn is_explored(&self, room: usize, width: usize, tile: (i32, i32)) -> bool {
+        if tile.0 < 0 || tile.1 < 0 || tile.0 as usize >= width { return false; }
+        let i = tile.1 as usize * width + tile.0 as usize;
+        self.rooms.get(room).and_then(|seen| seen.get(i)).copied().unwrap_or(false)
+    }
+
+    /// Cover every unseen tile of a `(width, height)` room with fog.
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, room: usize, (width, height): (usize, usize), scale: f32, offset: (f32, f32)) -> GameResult {
+        let mut builder = MeshBuilder::new();
+        let mut any = false;
+        for y in 0..height as i32 {
+            for x in 0..width as i32 {
+                if self.is_explored(room, width, (x, y)) { continue; }
+                let rect = Rect::new(offset.0 + x as f32 * TILE_SIZE * scale, offset.1 + y as f32 * TILE_SIZE * scale, TILE_SIZE * scale + 0.5, TILE_SIZE * scale + 0.5);
+                builder.rectangle(DrawMode::fill(), rect, Color::new(0.02, 0.02, 0.05, 0.92))?;
+                any = true;
+            }
+        }
+        if any {
+            let mesh = Mesh::from_data(ctx, builder.build());
+            canvas.draw(&mesh, DrawParam::new());
+        }
+        Ok(())
+    }
+
+    /// Save-file form: each room's bits as hex, rooms separated by commas.
+    pub fn to_text(&self) -> String {
+        let rooms = self.rooms.iter().map(|seen| {
+            seen.chunks(4).map(|bits| {
+                let digit = bits.iter().enumerate().fold(0, |acc, (i, &b)| acc | ((b as u32) << i));
+                char::from_digit(digit, 16).unwrap_or('0')
+            }).collect::<String>()
+        });
+        rooms.collect::<Vec<_>>().join(",")
+    }
+
+    /// Inverse of `to_text`. Bad digits read as unseen; lengths are fixed up by the next reveal.
+    pub fn from_text(text: &str) -> Exploration {
+        let rooms = text.trim().split(',').map(|room| {
+            room.trim().chars().flat_map(|c| {
+                let digit = c.to_digit(16).unwrap_or(0);
+                (0..4).map(move |i| digit & (1 << i) != 0)
+            }).collect()
+        });
+        let mut exploration = Exploration { rooms: rooms.collect() };
+        if exploration.rooms.iter().all(Vec::is_empty) {
+            exploration.rooms.clear();
+        }
+        exploration
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sight_reveals_a_circle_that_survives_saving() {
+        let mut exploration = Exploration::default();
+        exploration.reveal(1, 10, 8, (2, 2), 2);
+        assert!(exploration.is_explored(1, 10, (4, 2)));
+        assert!(exploration.is_explored(1, 10, (0, 2)));
+        assert!(!exploration.is_explored(1, 10, (4, 4)), "corners are outside the circle");
+        assert!(!exploration.is_explored(0, 10, (2, 2)), "other rooms are untouched");
+        let mut loaded = Exploration::from_text(&exploration.to_text());
+        assert!(loaded.is_explored(1, 10, (4, 2)) && !loaded.is_explored(1, 10, (4, 4)));
+        loaded.reveal(1, 10, 8, (2, 2), 2);
+        assert_eq!(loaded, exploration);
+        assert_eq!(Exploration::from_text(""), Exploration::default());
+    }
+}
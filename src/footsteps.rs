@@ -0,0 +1,71 @@
+//! Footstep sounds, picked by what the player is walking on.
+//!
+//! Each room sets its floor with `floor=wood|stone|grass` in its data file (stone if unset);
+//! tiles can have a sound of their own in the tile data (`crate::tiles`), like stone pressure
+//! plates and silent water, and soft props make no footstep of their own. Every step is played at a slightly different pitch and volume so long walks don't
+//! sound like a loop.
+
+use ggez::Context;
+use ggez::audio::SoundSource;
+
+use crate::assets::Assets;
+
+/// Pitch of a step varies by up to this much either way.
+const PITCH_SPREAD: f32 = 0.08;
+/// Base volume of a footstep; they should be felt more than heard.
+const STEP_VOLUME: f32 = 0.35;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Surface {
+    Wood,
+    #[default]
+    Stone,
+    Grass,
+}
+
+impl Surface {
+    pub const ALL: [Surface; 3] = [Surface::Wood, Surface::Stone, Surface::Grass];
+
+    pub fn parse(s: &str) -> Option<Surface> {
+        Surface::ALL.into_iter().find(|surface| surface.key() == s.trim())
+    }
+
+    /// Name in room data and sound file names (`/Sfx/step_<key>.wav`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Surface::Wood => "wood",
+            Surface::Stone => "stone",
+            Surface::Grass => "grass",
+        }
+    }
+}
+
+/// Play one footstep on `surface`, with sound effects at `sfx_volume`.
+pub fn play(ctx: &mut Context, assets: &mut Assets, surface: Surface, sfx_volume: f32) {
+    let (pitch, volume) = variation(rand::random(), rand::random());
+    if let Some(sound) = assets.footstep(surface) {
+        sound.set_pitch(pitch);
+        sound.set_volume(volume * sfx_volume);
+        let _ = sound.play_detached(ctx);
+    }
+}
+
+/// Pitch and volume for a step from two random numbers in 0..1.
+fn variation(a: f32, b: f32) -> (f32, f32) {
+    (1.0 + (a * 2.0 - 1.0) * PITCH_SPREAD, STEP_VOLUME * (0.8 + b * 0.2))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn surfaces_parse_and_steps_vary_a_little() {
+        assert_eq!(Surface::parse(" grass "), Some(Surface::Grass));
+        assert_eq!(Surface::parse("lava"), None);
+        let (low, quiet) = variation(0.0, 0.0);
+        let (high, loud) = variation(1.0, 1.0);
+        assert!(low < 1.0 && high > 1.0 && high - low <= PITCH_SPREAD * 2.0 + 1e-6);
+        assert!(quiet < loud && loud <= STEP_VOLUME);
+    }
+}
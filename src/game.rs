@@ -1,54 +1,249 @@
+use std::time::Instant;
+
 use ggez::{Context, GameResult};
 use ggez::event::EventHandler;
 use ggez::graphics::{Canvas, Color};
-use ggez::input::keyboard::{KeyCode, KeyInput};
+use ggez::input::gamepad::{gilrs, GamepadId};
+use ggez::input::keyboard::{KeyCode, KeyInput, KeyMods};
+use ggez::input::mouse::MouseButton;
 use ggez::audio::SoundSource;
 
 use crate::player;
 use crate::enemy;
 use crate::map;
-use crate::map::TILE_SIZE;
+use crate::map::{TileUse, TILE_SIZE};
 use crate::assets;
 use crate::title::TitleScreen;
 use crate::gui;
 use crate::intro::Intro;
-use crate::options::Options;
-use winit::window::Fullscreen;
+use crate::options::{self, Options, OptionsView};
+use crate::rng::{self, GameRng};
+use crate::save::{self, SaveMetadata};
+use crate::save_slots::{self, SaveSlotScreen, SlotAction};
+use crate::stats::Stats;
+use crate::inventory_screen::InventoryScreen;
+use crate::npc::{Npc, NpcRole};
+use crate::shop::Shop;
+use crate::shop_screen::ShopScreen;
+use crate::barter::{self, Barter};
+use crate::arena;
+use crate::daily::DailyLog;
+use crate::barter_screen::BarterScreen;
+use crate::save_menu::{SaveMenu, SaveMenuAction};
+use crate::dialogue::DialogueBox;
+use crate::credits::Credits;
+use crate::title::TitleAction;
+use crate::achievements::{Counter, Profile, Toasts};
+use crate::achievements_screen::AchievementsScreen;
+use crate::tutorial::{Hint, Tutorial};
+use crate::speedrun::{self, SpeedrunTimer};
+use crate::time_scale::TimeScale;
+use crate::calendar::{Calendar, Sky};
+use crate::game_vars::GameVars;
+use crate::events::{Event, EventBus, Subscriber, Topic};
+use crate::pickup::{Loot, Pickup};
+use crate::projectile::{Owner, Projectile};
+use crate::status::StatusKind;
+use crate::rooms::{Interaction, Water};
+use crate::items::{self, Equipment};
+use crate::splash::Splashes;
+use crate::companion::{self, Companion};
+use crate::party::{Ally, Party};
+use crate::exploration::{self, Exploration};
+use crate::cli::LaunchOptions;
+use crate::footsteps;
+use crate::menu_sfx::{self, Cue};
+use crate::stealth::Alertness;
+use crate::replay::{Press, Recorder, Replay};
+use crate::spatial::{self, SpatialHash};
+use crate::perf::{Phase, PerfOverlay};
+use crate::shake::{Falloff, ScreenShake};
+use crate::camera::Camera;
+use crate::transition::{Style, Transitions};
+use crate::scene::{Command, Scene, SceneId, SceneStack};
+use crate::input::{self, Action, Controls};
+use crate::key_repeat::KeyRepeat;
+use crate::profiles::{self, ProfileAction, ProfileList, ProfileScreen};
+use crate::waypoints::{TravelAction, TravelMenu, Waypoint, Waypoints};
+use crate::music::{Mood, Music};
+use crate::ambience::Ambience;
+use crate::teleport::{Pad, Teleporters};
+use crate::skills::{self, CastError, SkillEffect};
+use crate::encounter::{FoeDef, StepCounter};
+use crate::loot;
+use crate::battle::{Battle, Outcome};
+use crate::rumble::{self, Rumble};
+use crate::postfx::PostFx;
+use crate::clip::{self, ClipBuffer};
+use crate::inspector::{self, Inspector};
+use crate::hitboxes;
+use crate::grid_overlay;
+use crate::room_watch::RoomWatcher;
+use crate::world::{self, Used, View};
+
+/// Damage from standing on extended spikes.
+const SPIKE_DAMAGE: i32 = 2;
+/// Damage from falling down a pit with no room below.
+const PIT_DAMAGE: i32 = 3;
+
+/// How long the room name banner shows on entering a room, and how long it takes to fade.
+const ROOM_BANNER_SECS: f32 = 2.5;
+const ROOM_BANNER_FADE_SECS: f32 = 0.5;
+
+/// Length of the white flash after using a teleport pad.
+const TELEPORT_FLASH_SECS: f32 = 0.4;
+/// Seconds between the save screenshots taken by room-change autosaves.
+const AUTOSAVE_THUMBNAIL_SECS: f32 = 60.0;
+
+/// Height of the game window outside fullscreen (the ggez default); its width follows the aspect.
+const WINDOW_HEIGHT: f32 = 600.0;
+
+/// A hit taking at least this share of max HP shakes the screen.
+const HEAVY_HIT_SHARE: f32 = 0.2;
+
+/// The music's combat layer comes in with an enemy this many tiles away (see `crate::music`).
+const COMBAT_MUSIC_TILES: i32 = 4;
+/// Below this share of max HP the music's tension layer plays.
+const LOW_HP_SHARE: f32 = 0.3;
+
+/// Everything left behind in a room while the player is elsewhere: (room, enemies, NPCs, pickups).
+type ParkedRoom = (usize, Vec<enemy::Enemy>, Vec<Npc>, Vec<Pickup>);
 
-enum GameState {
-    Title,
-    Intro,
-    Playing,
+/// What a screen transition switches once the screen is covered.
+enum Cut {
+    Scene(Box<dyn Scene<Game>>),
+    Travel(Waypoint),
+    /// Knocked out: wake up in bed.
+    Respawn,
+    /// Through a doorway into another room.
+    Exit(Pad),
+    /// A random encounter: fight this group.
+    Battle(Vec<&'static FoeDef>),
 }
 
 pub struct Game {
     player: player::Player,
     map: map::Map,
     enemies: Vec<enemy::Enemy>,
+    npcs: Vec<Npc>,
+    pickups: Vec<Pickup>,
+    /// Where enemies and pickups are, for collision checks; rebuilt every frame and after kills.
+    enemy_grid: SpatialHash,
+    pickup_grid: SpatialHash,
+    projectiles: Vec<Projectile>,
+    splashes: Splashes,
+    companion: Companion,
+    party: Party,
+    /// Steps left until the next random encounter in a wild room.
+    encounter_steps: StepCounter,
+    explored: Exploration,
+    waypoints: Waypoints,
+    // rooms the player isn't in
+    parked: Vec<ParkedRoom>,
+    /// Unique enemies defeated this game: (room, spawn number).
+    defeated: Vec<(usize, u32)>,
+    // pads only fire after the player has stepped off the one they arrived on
+    teleport_armed: bool,
+    teleport_flash: f32,
     assets: assets::Assets,
-    state: GameState,
+    /// Title, save slots, intro, world, credits, with menus like the options on top.
+    scenes: SceneStack<Game>,
+    /// Keys held this frame, live or replayed.
+    controls: Controls,
     title_screen: TitleScreen,
+    credits: Credits,
     intro: Intro,
     options: Options,
+    inventory_screen: InventoryScreen,
+    /// Item descriptions for the inventory and shop detail panels.
+    item_descriptions: items::Descriptions,
+    shop_screen: ShopScreen,
+    barter_screen: BarterScreen,
+    save_menu: SaveMenu,
+    dialogue: DialogueBox,
+    travel_menu: TravelMenu,
+    /// True while the save menu was opened by lying in bed (saving then counts as a night's sleep).
+    resting_in_bed: bool,
+    profile: Profile,
+    /// First-time hints and which ones the profile has seen.
+    tutorial: Tutorial,
+    /// Up/Down held in a menu.
+    key_repeat: KeyRepeat,
+    toasts: Toasts,
+    achievements_screen: AchievementsScreen,
+    profiles: ProfileList,
+    profile_screen: ProfileScreen,
+    speedrun: SpeedrunTimer,
+    /// How fast game logic runs; see `time_scale`.
+    time_scale: TimeScale,
+    /// Day and season; a day passes with each night slept in bed.
+    calendar: Calendar,
+    /// Story flags and variables, saved with the game.
+    vars: GameVars,
+    /// What happened this frame, for the subsystems that listen for it.
+    events: EventBus,
+    save_slots: SaveSlotScreen,
+    // World seed service; reseeded from the save slot whenever a world is built
+    rng: GameRng,
+    // Slot the current game is saved to, and its metadata
+    active_slot: Option<usize>,
+    /// Slot just saved, whose thumbnail is taken from the next frame drawn.
+    thumbnail_due: Option<usize>,
+    /// When the last save screenshot was taken.
+    thumbnail_taken: Option<Instant>,
+    save_meta: Option<SaveMetadata>,
+    // Short message shown at the bottom of the screen (text, seconds left)
+    notice: Option<(String, f32)>,
+    // Name of the room just entered (text, seconds left)
+    room_banner: Option<(String, f32)>,
+    /// Scale and offset the world was last drawn at, for finding the tile under the mouse.
+    world_view: View,
     // when toggling fullscreen, allow an extra integer scale multiplier so the 4:3 game fills more of the screen
     fullscreen_scale_mul: f32,
     // Music management
-    current_music: Option<String>,
-    title_music_timer: f32,
+    music: Music,
+    ambience: Ambience,
+    rumble: Rumble,
+    postfx: PostFx,
+    clips: ClipBuffer,
+    /// Debug list of the room's entities (F3).
+    inspector: Inspector,
+    /// Debug overlay of collision boxes (F4).
+    show_hitboxes: bool,
+    /// Debug overlay of the tile grid and coordinates (F2).
+    show_grid: bool,
+    /// Rebuilds the room when its data file is saved, in debug builds.
+    room_watch: RoomWatcher,
     // FPS counter
     fps_timer: f32,
     fps_counter: u32,
     fps_display: u32,
     // GBA refresh rate limiter
     frame_limiter_accumulator: f32,
+    /// Start new games straight in the world (`--skip-intro`).
+    skip_intro: bool,
+    /// The debug keys work: always in debug builds, with `--debug` in release ones.
+    debug_keys: bool,
+    /// False while the window is in the background: everything holds still and the audio pauses.
+    focused: bool,
+    /// Frame timings for the performance overlay.
+    perf: PerfOverlay,
+    shake: ScreenShake,
+    camera: Camera,
+    transition: Transitions<Cut>,
+    /// Inputs being written out (`--record`) or played back (`--replay`).
+    recorder: Option<Recorder>,
+    replay: Option<Replay>,
+    /// The arena run under way, if the player is in the arena.
+    arena: Option<arena::Run>,
 }
 
 impl Game {
-    pub fn new(ctx: &mut Context) -> GameResult<Game> {
-        let player = player::Player::new(ctx)?;
+    pub fn new(ctx: &mut Context, launch: &LaunchOptions, assets: assets::Assets) -> GameResult<Game> {
+        let player = player::Player::new();
+        let companion = Companion::new(player.tile());
         let map = map::Map::new();
     let enemies: Vec<enemy::Enemy> = vec![];
-        let assets = assets::Assets::load(ctx)?;
 
         // Very small story for the intro segment
         let intro_lines = vec![
@@ -58,83 +253,1336 @@ impl Game {
             "Prepare yourself...".to_string(),
         ];
 
-        // Try to load a title override from assets/title.txt (first two non-empty lines: title, subtitle)
-        let title_screen = TitleScreen::from_file("assets/title.txt").unwrap_or_else(|| TitleScreen::default());
+        // Try to load a title override from title.txt (first two non-empty lines: title, subtitle)
+        let title_screen = assets::read_text(ctx, "/title.txt").map(|s| TitleScreen::from_text(&s)).unwrap_or_else(TitleScreen::default);
+        let credits = assets::read_text(ctx, "/credits.txt").map(|s| Credits::from_text(&s)).unwrap_or_else(Credits::default);
 
         println!("Game::new: initialized (Title state)");
-        Ok(Game {
+        let mut game = Game {
             player,
             map,
             enemies,
+            npcs: Vec::new(),
+            pickups: Vec::new(),
+            enemy_grid: SpatialHash::new(spatial::ENTITY_CELL),
+            pickup_grid: SpatialHash::new(spatial::ENTITY_CELL),
+            projectiles: Vec::new(),
+            splashes: Splashes::new(),
+            companion,
+            party: Party::default(),
+            encounter_steps: StepCounter::default(),
+            explored: Exploration::default(),
+            waypoints: Waypoints::default(),
+            parked: Vec::new(),
+            defeated: Vec::new(),
+            teleport_armed: true,
+            teleport_flash: 0.0,
             assets,
-            state: GameState::Title,
+            scenes: SceneStack::new(Box::new(TitleScene)),
+            controls: Controls::default(),
             title_screen,
+            credits,
             intro: Intro::new(intro_lines),
             options: Options::new(),
+            inventory_screen: InventoryScreen::new(),
+            item_descriptions: items::Descriptions::load(ctx),
+            shop_screen: ShopScreen::new(),
+            barter_screen: BarterScreen::new(),
+            save_menu: SaveMenu::new(),
+            dialogue: DialogueBox::new(),
+            travel_menu: TravelMenu::new(),
+            resting_in_bed: false,
+            profile: Profile::load(),
+            tutorial: Tutorial::load(),
+            key_repeat: KeyRepeat::default(),
+            toasts: Toasts::new(),
+            achievements_screen: AchievementsScreen::new(),
+            profiles: ProfileList::load(),
+            profile_screen: ProfileScreen::new(),
+            speedrun: SpeedrunTimer::new(),
+            time_scale: launch.time_scale.map(TimeScale::at).unwrap_or_default(),
+            calendar: Calendar::default(),
+            vars: GameVars::default(),
+            events: Game::subscriptions(),
+            save_slots: SaveSlotScreen::new(),
+            rng: GameRng::from_entropy(),
+            active_slot: None,
+            thumbnail_due: None,
+            thumbnail_taken: None,
+            save_meta: None,
+            notice: None,
+            room_banner: None,
+            world_view: (1.0, (0.0, 0.0)),
             fullscreen_scale_mul: 1.0,
-            current_music: None,
-            title_music_timer: 0.0,
+            music: Music::new(),
+            ambience: Ambience::new(crate::options::DEFAULT_AMBIENCE_VOLUME),
+            rumble: Rumble::new(),
+            postfx: PostFx::new(ctx),
+            clips: ClipBuffer::new(),
+            inspector: Inspector::default(),
+            show_hitboxes: false,
+            show_grid: false,
+            room_watch: RoomWatcher::default(),
             fps_timer: 0.0,
             fps_counter: 0,
             fps_display: 0,
             frame_limiter_accumulator: 0.0,
+            skip_intro: launch.skip_intro,
+            debug_keys: cfg!(debug_assertions) || launch.debug,
+            focused: true,
+            perf: PerfOverlay::new(),
+            shake: ScreenShake::default(),
+            camera: Camera::new(),
+            transition: Transitions::new(),
+            recorder: None,
+            replay: None,
+            arena: None,
+        };
+        // start in the profile used last time, or ask for one on first launch
+        match game.profiles.last.clone() {
+            Some(name) => game.select_profile(ctx, name),
+            None => game.profile_screen.open(&game.profiles, None, false),
+        }
+        game.apply_launch(ctx, launch)?;
+        Ok(game)
+    }
+
+    /// Act on the command-line flags that need a running game.
+    fn apply_launch(&mut self, ctx: &mut Context, launch: &LaunchOptions) -> GameResult {
+        if launch.editor {
+            println!("Game: --editor: this build has no level editor yet, starting the game");
+        }
+        if let Some(path) = &launch.replay {
+            match Replay::load(path) {
+                Ok(replay) => {
+                    println!("Replay: playing {}", path);
+                    self.pin_entropy(replay.seed);
+                    self.replay = Some(replay);
+                }
+                Err(e) => println!("Replay: {}", e),
+            }
+        }
+        if let Some(path) = &launch.record {
+            let seed = rand::random();
+            match Recorder::create(path, seed) {
+                Ok(recorder) => {
+                    println!("Replay: recording to {}", path);
+                    self.pin_entropy(seed);
+                    self.recorder = Some(recorder);
+                }
+                Err(e) => println!("Replay: can't record to {}: {}", path, e),
+            }
+        }
+        if let Some(on) = launch.fullscreen {
+            self.options.fullscreen = on;
+            self.set_fullscreen(ctx, on);
+        }
+        let Some(slot) = launch.load_slot else { return Ok(()) };
+        if self.profiles.last.is_none() {
+            println!("Game: --load: pick a profile first");
+            return Ok(());
+        }
+        let meta = match save::read_slot(slot) {
+            save::SlotFile::Saved(meta) => meta,
+            save::SlotFile::Damaged(_) => {
+                println!("Game: --load: slot {} is damaged; restore its backup from the save slot screen", slot + 1);
+                return Ok(());
+            }
+            save::SlotFile::Empty => {
+                println!("Game: --load: slot {} is empty", slot + 1);
+                return Ok(());
+            }
+        };
+        let command = self.handle_slot_action(ctx, SlotAction::Continue(slot, Box::new(meta)))?;
+        self.apply_command(command);
+        // there's no title screen to fade away from; open straight onto the room
+        if let Some(cut) = self.transition.skip() {
+            self.apply_cut(cut);
+        }
+        if let Some(room) = launch.room {
+            if room < self.map.room_count() {
+                self.change_room(room);
+                self.player.warp_to(self.map.entrance());
+            } else {
+                println!("Game: --room: there is no room {}", room);
+            }
+        }
+        Ok(())
+    }
+
+    /// Roll every random world seed from `seed` from now on, so a recording replays the same.
+    fn pin_entropy(&mut self, seed: u64) {
+        rng::pin_entropy(seed);
+        self.rng = GameRng::from_entropy();
+    }
+
+    /// Make `name` the active profile: its saves, options and achievements take over.
+    fn select_profile(&mut self, ctx: &mut Context, name: String) {
+        let folder = profiles::folder(&name);
+        if self.profiles.last.is_none() && self.profiles.names.len() == 1 {
+            // the very first profile inherits whatever was saved before profiles existed
+            profiles::adopt_legacy_files(&folder);
+        }
+        crate::save::set_profile_dir(Some(&folder));
+        self.profiles.last = Some(name.clone());
+        self.profiles.save();
+        self.profile = Profile::load();
+        self.tutorial = Tutorial::load();
+        self.options = Options::load();
+        self.apply_options(ctx);
+        self.save_slots.refresh();
+        self.title_screen.profile_name = Some(name.clone());
+        println!("Game: profile {}", name);
+    }
+
+    /// Push every setting in `options` out to the systems that use it.
+    fn apply_options(&mut self, ctx: &mut Context) {
+        crate::palette::set_colorblind(self.options.colorblind);
+        crate::palette::set_high_contrast(self.options.high_contrast);
+        self.player.turn_threshold = self.options.turn_threshold;
+        self.player.auto_run = self.options.auto_run;
+        self.dialogue.speed = self.options.text_speed.factor();
+        self.ambience.set_volume(&mut self.assets, self.options.ambience_volume);
+        self.frame_limiter_accumulator = 0.0;
+        self.set_fullscreen(ctx, self.options.fullscreen);
+    }
+
+    /// Reshape the window to the chosen aspect; fullscreen just draws bars instead.
+    fn fit_window_to_aspect(&mut self, ctx: &mut Context) {
+        if self.options.fullscreen { return; }
+        if let Err(e) = ctx.gfx.set_drawable_size(WINDOW_HEIGHT * self.options.aspect.ratio(), WINDOW_HEIGHT) {
+            println!("Game: couldn't resize the window: {}", e);
+        }
+    }
+
+    fn set_fullscreen(&mut self, ctx: &mut Context, on: bool) {
+        let window = ctx.gfx.window();
+        crate::platform::set_fullscreen(window, on);
+        if !on {
+            self.fullscreen_scale_mul = 1.0;
+            self.fit_window_to_aspect(ctx);
+        } else {
+            // try to compute an integer multiplier that scales the map larger while keeping 4:3.
+            let ws = window.inner_size();
+            let win_w = ws.width as f32;
+            let win_h = ws.height as f32;
+            let render_w = if win_w / win_h >= 4.0/3.0 { win_h * (4.0/3.0) } else { win_w };
+            let render_h = if win_w / win_h >= 4.0/3.0 { win_h } else { win_w / (4.0/3.0) };
+            let map_w = self.map.width_pixels() as f32;
+            let map_h = self.map.height_pixels() as f32;
+            let scale_x = render_w / map_w;
+            let scale_y = render_h / map_h;
+            let best = scale_x.min(scale_y);
+            // nearest integer >= 1.0
+            let mul = best.floor().max(1.0);
+            self.fullscreen_scale_mul = mul;
+        }
+    }
+
+    /// Build the world for a save slot from its seed and generation options.
+    fn start_world(&mut self, ctx: &mut Context, slot: usize, meta: SaveMetadata) -> GameResult {
+        self.rng = GameRng::new(meta.seed);
+        self.map = map::Map::generate(&meta.generation);
+        self.player = player::Player::new();
+        self.player.stats = Stats::for_level(meta.level);
+        self.player.stats.xp = meta.xp;
+        self.player.turn_threshold = self.options.turn_threshold;
+        self.player.auto_run = self.options.auto_run;
+        self.player.skills = skills::SkillBook::for_level(meta.level);
+        self.player.inventory = meta.inventory.clone();
+        self.player.equipment = meta.equipment;
+        self.player.gold = meta.gold;
+        self.companion = match meta.companion_camp {
+            Some((room, tile)) => Companion::waiting(room, tile),
+            None => Companion::new(self.player.tile()),
+        };
+        self.party = Party::from_records(&meta.party, self.player.tile());
+        self.explored = meta.explored.clone();
+        self.waypoints = meta.waypoints.clone();
+        self.pickups.clear();
+        self.projectiles.clear();
+        self.splashes.clear();
+        self.map.set_teleporters(Teleporters::load(ctx));
+        self.map.load_room_data(ctx);
+        self.map.restore_room_states(&meta.room_states);
+        self.calendar = Calendar::new(meta.day);
+        self.vars = meta.vars.clone();
+        self.map.set_sky(Sky::today(&self.calendar, meta.seed));
+        self.show_room_banner();
+        // anything still queued belonged to the last game
+        self.events.drain();
+        self.events.publish(Event::RoomEntered(self.map.current_room()));
+        self.parked.clear();
+        self.arena = None;
+        self.teleport_armed = true;
+        self.teleport_flash = 0.0;
+        self.speedrun.start();
+        // The village merchant keeps shop in the corner opposite the bed
+        let merchant_tile = (meta.generation.room_width as i32 - 3, 2);
+        self.npcs = vec![Npc::merchant("Mira", merchant_tile, Shop::load(ctx, "general"))];
+        // and an old peddler who'd rather swap than sell
+        self.npcs.push(Npc::trader("Old Tom", (10, 3), Barter::load(ctx, "peddler")));
+        // the arena master, for anyone who fancies a fight
+        self.npcs.push(Npc::arena_master("Varga", (14, 2)));
+        // a village guard waits by the door, happy to tag along
+        if !self.party.has("Bram") && !self.vars.flag("recruited_bram") {
+            self.npcs.push(Npc::recruit("Bram", (4, 3), 2, Some("rusty_sword")));
+        }
+        self.map.set_blocked_tiles(self.npcs.iter().map(|n| n.tile).collect());
+        // every enemy is still rolled so the RNG stays in step with the seed; the ones already
+        // beaten are then left out
+        let room = self.map.current_room();
+        self.defeated = meta.defeated.clone();
+        self.enemies = self.spawn_enemies(meta.generation.enemy_count);
+        self.enemies.retain(|e| !e.unique.is_some_and(|id| self.defeated.contains(&(room, id))));
+        println!("Game: world seed {} (slot {})", meta.seed, slot + 1);
+        self.active_slot = Some(slot);
+        self.thumbnail_taken = None;
+        self.save_meta = Some(meta);
+        Ok(())
+    }
+
+    /// Place `count` enemies on free floor tiles chosen by the seeded RNG, away from the player.
+    fn spawn_enemies(&mut self, count: usize) -> Vec<enemy::Enemy> {
+        let tiles_w = (self.map.width_pixels() as f32 / TILE_SIZE) as i32;
+        let tiles_h = (self.map.height_pixels() as f32 / TILE_SIZE) as i32;
+        let player_pos = self.player.get_position();
+        let mut enemies = Vec::new();
+        // bounded number of attempts so a cramped room can't loop forever
+        for _ in 0..count * 50 {
+            if enemies.len() >= count { break; }
+            let x = self.rng.range(1, tiles_w - 1) as f32 * TILE_SIZE;
+            let y = self.rng.range(1, tiles_h - 1) as f32 * TILE_SIZE;
+            let near_player = (x - player_pos.x).abs() + (y - player_pos.y).abs() < TILE_SIZE * 4.0;
+            let tile = ((x / TILE_SIZE) as i32, (y / TILE_SIZE) as i32);
+            let (cx, cy) = (x + TILE_SIZE / 2.0, y + TILE_SIZE / 2.0);
+            if near_player || self.map.is_tile_blocked(tile) || self.map.blocks_enemies_at_point(cx, cy) {
+                continue;
+            }
+            let enemy = enemy::Enemy::at(x, y).with_unique(enemies.len() as u32);
+            let enemy = match self.rng.range(0, 4) {
+                0 => enemy.with_status(StatusKind::Poison, 5.0).with_loot(&loot::VIPER),
+                1 => enemy.with_status(StatusKind::Slow, 3.0).with_loot(&loot::MUDLING),
+                2 => enemy.with_status(StatusKind::Burn, 3.0).ranged().with_loot(&loot::EMBER),
+                _ => enemy.with_loot(&loot::SLIME),
+            };
+            enemies.push(enemy);
+        }
+        enemies
+    }
+
+    /// Move to another room, parking the current room's enemies, NPCs and pickups until the
+    /// player comes back.
+    fn change_room(&mut self, room: usize) {
+        let from = self.map.current_room();
+        if room == from { return; }
+        // walking out of the arena forfeits the run
+        if from == arena::ROOM && self.end_arena_run().is_some() {
+            self.show_notice("You left the arena. The run is over.");
+        }
+        let here = (from, std::mem::take(&mut self.enemies), std::mem::take(&mut self.npcs), std::mem::take(&mut self.pickups));
+        self.parked.push(here);
+        if let Some(i) = self.parked.iter().position(|(r, ..)| *r == room) {
+            let (_, enemies, npcs, pickups) = self.parked.swap_remove(i);
+            (self.enemies, self.npcs, self.pickups) = (enemies, npcs, pickups);
+        }
+        self.projectiles.clear();
+        self.splashes.clear();
+        self.encounter_steps.reset();
+        self.inspector.deselect();
+        self.map.set_current(room);
+        self.map.set_blocked_tiles(self.npcs.iter().map(|n| n.tile).collect());
+        self.show_room_banner();
+        self.events.publish(Event::RoomEntered(room));
+        println!("Game: room {} -> {}", from, room);
+        if let Some(meta) = &mut self.save_meta
+            && self.map.room_depth() > meta.depth_reached {
+            meta.depth_reached = self.map.room_depth();
+            println!("Game: deepest floor reached is now {}", meta.depth_reached);
+        }
+        // an ironman run's only save is the one made on the way in to each room, which goes
+        // without saying
+        if self.is_ironman() {
+            self.autosave();
+        }
+    }
+
+    fn is_ironman(&self) -> bool {
+        self.save_meta.as_ref().is_some_and(|meta| meta.ironman)
+    }
+
+    /// Death in an ironman run: the save is archived, the loss goes on the profile's record and
+    /// it's back to the title with that record on show.
+    fn end_ironman_run(&mut self) {
+        let Some(slot) = self.active_slot.take() else { return };
+        if let Some(meta) = self.save_meta.take().filter(|meta| !meta.daily.is_empty()) {
+            DailyLog::record_run(&meta.daily, self.player.stats.level, meta.playtime);
+        }
+        match save::archive_slot(slot) {
+            Ok(path) => println!("Game: ironman run in slot {} is over, archived as {:?}", slot + 1, path),
+            Err(e) => println!("Game: failed to archive ironman slot {}: {}", slot + 1, e),
+        }
+        self.profile.record_fallen_run(self.player.stats.level);
+        self.profile.save();
+        self.achievements_screen.visible = true;
+        self.scenes.replace(Box::new(TitleScene));
+    }
+
+    /// Rebuild the current room after its data file changed, keeping the player where they are
+    /// unless that's now inside a wall or off the room.
+    fn reload_room(&mut self, ctx: &Context) {
+        if !self.map.reload_room(ctx) { return; }
+        let at = self.player.get_position();
+        let inset = (TILE_SIZE - player::HITBOX_SIZE) / 2.0;
+        let inside = at.x + TILE_SIZE <= self.map.width_pixels() as f32 && at.y + TILE_SIZE <= self.map.height_pixels() as f32;
+        if !inside || !self.map.is_rect_free(at.x + inset, at.y + inset, player::HITBOX_SIZE, player::HITBOX_SIZE) {
+            self.player.warp_to(self.map.entrance());
+        }
+        self.show_notice("Room reloaded.");
+    }
+
+    /// Fast travel to a waypoint shrine.
+    fn travel_to(&mut self, waypoint: &Waypoint) {
+        self.change_room(waypoint.room);
+        self.player.warp_to(waypoint.arrival());
+        println!("Game: travelled to {}", waypoint.name);
+    }
+
+    fn teleport(&mut self, pad: Pad) {
+        self.change_room(pad.room);
+        self.player.warp_to(pad.tile);
+        self.teleport_flash = TELEPORT_FLASH_SECS;
+        println!("Game: teleported to {:?} in room {}", pad.tile, pad.room);
+    }
+
+    /// The player finished falling down a pit: drop into the room below (same spot if it's
+    /// clear, otherwise its entrance), or climb out at this room's entrance a little hurt.
+    /// Returns true if the fall knocked the player out.
+    fn land_from_fall(&mut self) -> bool {
+        let tile = self.player.tile();
+        match self.map.room_below() {
+            Some(below) => {
+                self.change_room(below);
+                let (cx, cy) = ((tile.0 as f32 + 0.5) * TILE_SIZE, (tile.1 as f32 + 0.5) * TILE_SIZE);
+                let clear = !self.map.blocks_enemies_at_point(cx, cy) && !self.map.is_tile_blocked(tile);
+                self.player.warp_to(if clear { tile } else { self.map.entrance() });
+                self.transition.reveal(Style::Fade);
+                self.show_notice("You fell through to the floor below!");
+                false
+            }
+            None => {
+                self.player.warp_to(self.map.entrance());
+                self.shake.start(6.0, 0.35, Falloff::Quadratic);
+                self.rumble(rumble::Kind::Heavy);
+                self.show_notice("Ouch! You climb back out of the pit.");
+                self.player.stats.take_damage(PIT_DAMAGE)
+            }
+        }
+    }
+
+    /// Rumble the controller, if that's switched on.
+    fn rumble(&mut self, kind: rumble::Kind) {
+        if self.options.rumble {
+            self.rumble.play(kind, self.options.rumble_strength);
+        }
+    }
+
+    /// Rumble for a hit that cost `lost` HP, and jolt the screen too for a big share of max HP.
+    fn jolt_for_hit(&mut self, lost: i32) {
+        if lost > 0 {
+            self.rumble(rumble::Kind::Hit);
+        }
+        let share = lost as f32 / self.player.stats.max_hp.max(1) as f32;
+        if share >= HEAVY_HIT_SHARE {
+            self.shake.start(3.0 + share * 10.0, 0.3, Falloff::Linear);
+        }
+    }
+
+    /// Show what comes of the furniture `interaction` at `tile` (see `world::confirm`).
+    fn interact(&mut self, tile: (i32, i32), interaction: Interaction) {
+        match interaction {
+            Interaction::Read(text) => {
+                let text = self.vars.run(&text);
+                self.dialogue.open(None, &text);
+            }
+            // `world::confirm` sat the player down
+            Interaction::Sit => {}
+            Interaction::Search(Some(id)) => {
+                self.player.inventory.add(id);
+                let name = items::find(id).map_or(id, |def| def.name);
+                self.show_notice(&format!("Found a {} in the cupboard!", name));
+            }
+            Interaction::Search(None) => self.show_notice("Nothing in here but dust."),
+            Interaction::Shrine(name) => {
+                let room = self.map.current_room();
+                if self.waypoints.activate(Waypoint { name: name.clone(), room, tile }) {
+                    self.show_notice(&format!("The {} awakens.", name));
+                }
+                self.travel_menu.open(&self.waypoints, room, tile);
+            }
+            Interaction::Camp if !self.companion.is_in(self.map.current_room()) => {
+                self.show_notice(&format!("{} is waiting by another campfire.", companion::COMPANION_NAME));
+            }
+            Interaction::Camp => {
+                let text = if self.companion.toggle(self.map.current_room()) { "bounds over to join you" } else { "curls up by the fire to wait" };
+                self.show_notice(&format!("{} {}.", companion::COMPANION_NAME, text));
+            }
+        }
+    }
+
+    /// Ask the NPC at `idx` to join the party; they leave their post if there's room.
+    fn recruit(&mut self, idx: usize) {
+        if self.party.is_full() {
+            self.show_notice("Your party is full.");
+            return;
+        }
+        let npc = self.npcs.remove(idx);
+        let NpcRole::Recruit { level, weapon } = npc.role else { return };
+        let equipment = Equipment { weapon, ..Equipment::default() };
+        self.party.join(Ally::new(&npc.name, Stats::for_level(level), equipment, npc.tile));
+        self.map.set_blocked_tiles(self.npcs.iter().map(|n| n.tile).collect());
+        self.vars.set_flag(&format!("recruited_{}", npc.name.to_lowercase()), true);
+        self.show_notice(&format!("{} joins your party!", npc.name));
+    }
+
+    /// Step into the arena; the first wave comes after a breather.
+    fn enter_arena(&mut self) {
+        if self.map.room_count() <= arena::ROOM {
+            self.show_notice("The arena is closed today.");
+            return;
+        }
+        self.arena = Some(arena::Run::new());
+        self.change_room(arena::ROOM);
+        self.player.warp_to(self.map.entrance());
+        self.show_notice("Survive as long as you can!");
+    }
+
+    /// Send in the next arena wave when it's due.
+    fn update_arena(&mut self, dt: f32) {
+        if self.map.current_room() != arena::ROOM { return; }
+        let cleared = self.enemies.is_empty();
+        let Some(wave) = self.arena.as_mut().and_then(|run| run.update(dt, cleared)) else { return };
+        let enemies = self.spawn_enemies(arena::wave_size(wave));
+        self.enemies.extend(enemies.into_iter().map(|enemy| arena::toughen(enemy, wave)));
+        self.index_enemies();
+        self.show_notice(&format!("Wave {}!", wave));
+        println!("Game: arena wave {} ({} foes)", wave, self.enemies.len());
+    }
+
+    /// The player went down in the arena: the run goes on the profile and the arena is cleared
+    /// out. Returns what to tell the player, or None if there was no run.
+    fn end_arena_run(&mut self) -> Option<String> {
+        let run = self.arena.take()?;
+        self.enemies.clear();
+        self.pickups.clear();
+        self.projectiles.clear();
+        let secs = run.elapsed as u32;
+        let best = self.profile.record_arena_run(run.wave, secs);
+        self.profile.save();
+        println!("Game: arena run ended on wave {} after {}s", run.wave, secs);
+        let time = format!("{}:{:02}", secs / 60, secs % 60);
+        Some(if best {
+            format!("New arena best: wave {} in {}!", run.wave, time)
+        } else {
+            format!("You fell on wave {} after {}.", run.wave, time)
         })
     }
 
-    fn set_music(&mut self, ctx: &mut Context, music_name: &str) {
-        if self.current_music.as_ref() == Some(&music_name.to_string()) {
-            return; // Already playing this music
+    /// What the entity inspector lists, from the room as it is now.
+    fn inspected(&self) -> Vec<inspector::Entry> {
+        let companion = self.companion.is_in(self.map.current_room()).then_some(&self.companion);
+        inspector::entries(&self.player, companion, &self.party, &self.enemies, &self.npcs, &self.pickups, &self.projectiles)
+    }
+
+    /// F9: write the last few seconds to a GIF.
+    fn save_clip(&mut self) {
+        if !self.options.clip_recording {
+            self.show_notice("Turn on Clip Recording in Video options first.");
+        } else if self.clips.is_empty() {
+            self.show_notice("Nothing recorded yet.");
+        } else {
+            let path = self.clips.save(&save::data_root().join("clips"));
+            let name = path.file_name().map_or_else(String::new, |name| name.to_string_lossy().into_owned());
+            self.show_notice(&format!("Saved the last {} seconds as {}", clip::SECONDS, name));
+        }
+    }
+
+    fn show_notice(&mut self, text: &str) {
+        self.notice = Some((text.to_string(), 2.5));
+    }
+
+    fn show_room_banner(&mut self) {
+        self.room_banner = self.map.room_name().map(|name| (name.to_string(), ROOM_BANNER_SECS));
+    }
+
+    /// Write the player's progress to the active save slot.
+    fn save_progress(&mut self) {
+        if self.write_save() {
+            self.show_notice("Game saved.");
+        }
+    }
+
+    /// Save without a word, on the way through a room. The slot's screenshot is kept fresh,
+    /// but not on every doorway.
+    fn autosave(&mut self) {
+        let recent = self.thumbnail_taken.is_some_and(|taken| taken.elapsed().as_secs_f32() < AUTOSAVE_THUMBNAIL_SECS);
+        if self.write_save() && recent {
+            // the screenshot from a moment ago will do
+            self.thumbnail_due = None;
+        }
+    }
+
+    /// Write the progress to the active save slot and have the next frame kept as its
+    /// screenshot. Returns false (having told the player) if it couldn't be written.
+    fn write_save(&mut self) -> bool {
+        let (Some(slot), Some(meta)) = (self.active_slot, self.save_meta.as_mut()) else { return false };
+        meta.level = self.player.stats.level;
+        meta.xp = self.player.stats.xp;
+        meta.inventory = self.player.inventory.clone();
+        meta.equipment = self.player.equipment;
+        meta.gold = self.player.gold;
+        meta.companion_camp = self.companion.waiting_at();
+        meta.party = self.party.records();
+        meta.explored = self.explored.clone();
+        meta.waypoints = self.waypoints.clone();
+        meta.room_states = self.map.room_states();
+        meta.defeated = self.defeated.clone();
+        meta.day = self.calendar.day;
+        meta.vars = self.vars.clone();
+        meta.location = self.map.room_name().unwrap_or_default().to_string();
+        if !meta.daily.is_empty() {
+            DailyLog::record_run(&meta.daily, meta.level, meta.playtime);
+        }
+        match save::write_slot(slot, meta) {
+            Ok(()) => {
+                self.thumbnail_due = Some(slot);
+                true
+            }
+            Err(e) => {
+                println!("Game: failed to write save slot {}: {}", slot + 1, e);
+                self.show_notice("Could not save the game!");
+                false
+            }
+        }
+    }
+
+    /// Melee attack on the tile in front of the player; defeated enemies award XP and drop gold.
+    fn player_attack(&mut self) {
+        if !self.player.start_attack() { return; }
+        let facing = self.player.facing_tile();
+        let Some(idx) = self.enemies.iter().position(|e| e.tile() == facing) else { return };
+        self.damage_enemy(idx, self.player.attack_power());
+    }
+
+    /// Ranged attack: throw a knife in the facing direction.
+    fn player_throw(&mut self) {
+        if !self.player.start_throw() { return; }
+        let damage = (self.player.attack_power() / 2).max(1);
+        self.projectiles.push(Projectile::from_tile(Owner::Player, self.player.tile(), self.player.facing, 320.0, 5.0, damage));
+    }
+
+    fn index_enemies(&mut self) {
+        self.enemy_grid.rebuild(self.enemies.iter().map(|e| e.get_position()));
+    }
+
+    /// Move projectiles and resolve what they hit. Returns the first hit on the player.
+    fn update_projectiles(&mut self, dt: f32) -> Option<(i32, Option<(StatusKind, f32)>)> {
+        let mut player_hit = None;
+        let mut i = 0;
+        while i < self.projectiles.len() {
+            let alive = self.projectiles[i].update(dt, &self.map);
+            let p = &self.projectiles[i];
+            let hit = match p.owner {
+                Owner::Player => {
+                    // enemies are indexed by their tile's top-left corner
+                    let corner = p.position - nalgebra::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+                    let target = self.enemy_grid.near(corner).into_iter().find(|&i| p.hits(self.enemies[i].get_position()));
+                    if let Some(idx) = target {
+                        let damage = p.damage;
+                        self.damage_enemy(idx, damage);
+                    }
+                    target.is_some()
+                }
+                Owner::Enemy if p.hits(self.player.get_position()) => {
+                    player_hit = player_hit.or(Some((p.damage, p.inflicts)));
+                    true
+                }
+                Owner::Enemy => false,
+            };
+            if alive && !hit {
+                i += 1;
+            } else {
+                self.projectiles.remove(i);
+            }
         }
+        player_hit
+    }
 
-        // Start new music
-        match music_name {
-            "title" => {
-                if let Some(ref mut music) = self.assets.title_music {
-                    music.set_volume(1.0);
-                    let _ = music.play_detached(ctx);
-                    self.title_music_timer = 0.0;
+    /// Hit enemy `idx`; if it dies it drops gold and the player gets its XP.
+    fn damage_enemy(&mut self, idx: usize, amount: i32) {
+        if !self.enemies[idx].take_damage(amount) { return; }
+        let enemy = self.enemies.remove(idx);
+        self.index_enemies();
+        if let Some(id) = enemy.unique {
+            self.defeated.push((self.map.current_room(), id));
+        }
+        self.events.publish(Event::EnemyDied { room: self.map.current_room(), unique: enemy.unique });
+        if self.enemies.is_empty() {
+            // the last blow in a room lands in slow motion
+            self.time_scale.slow_motion(0.3, 0.6);
+            self.speedrun.split(speedrun::SPLIT_ROOM_CLEARED);
+        }
+        let (lo, hi) = enemy.gold_drop;
+        let gold = self.rng.range(lo as i32, hi as i32 + 1) as u32;
+        if gold > 0 {
+            self.pickups.push(Pickup::gold(enemy.get_position(), gold));
+        }
+        if let Some((id, rarity)) = enemy.loot.and_then(|table| table.roll(&mut self.rng)) {
+            self.pickups.push(Pickup::item(enemy.get_position(), id, rarity));
+        }
+        self.award_xp(enemy.xp_reward);
+    }
+
+    /// Share XP with the party, announcing the player's level-ups.
+    fn award_xp(&mut self, amount: u32) {
+        self.party.gain_xp(amount);
+        if self.player.gain_xp(amount) > 0 {
+            let level = self.player.stats.level;
+            let mut text = format!("Level up! You are now level {}.", level);
+            for def in skills::SKILLS.iter().filter(|d| d.learn_level == level) {
+                text.push_str(&format!(" Learned {}!", def.name));
+            }
+            self.show_notice(&text);
+        }
+    }
+
+    /// Back to the world after a battle, with the spoils or the bruises.
+    fn end_battle(&mut self, outcome: Option<Outcome>) -> Command<Game> {
+        match outcome {
+            Some(Outcome::Won { xp, gold, foes, drops }) => {
+                for _ in 0..foes {
+                    self.events.publish(Event::EnemyDied { room: self.map.current_room(), unique: None });
                 }
+                self.player.gold += gold;
+                for id in drops {
+                    self.player.inventory.add(id);
+                }
+                self.award_xp(xp);
+            }
+            Some(Outcome::Lost) => self.transition_to(Style::Fade, Cut::Respawn),
+            Some(Outcome::Fled) | None => {}
+        }
+        Command::Pop
+    }
+
+    /// Allies strike enemies next to them, and enemies running into allies hurt them.
+    fn party_fights(&mut self) {
+        for i in 0..self.party.allies.len() {
+            if let Some(idx) = self.party.allies[i].try_strike(self.enemies.iter().map(|e| e.tile())) {
+                let damage = self.party.allies[i].attack_power();
+                self.damage_enemy(idx, damage);
+            }
+        }
+        let mut downed = Vec::new();
+        for ally in &mut self.party.allies {
+            let position = ally.position();
+            let toucher = self.enemy_grid.near(position).into_iter().find(|&i| self.enemies[i].touches_at(position));
+            if let Some(i) = toucher
+                && ally.hurt(self.enemies[i].damage) {
+                downed.push(ally.name.clone());
+            }
+        }
+        for name in downed {
+            self.show_notice(&format!("{} is down! Rest to recover.", name));
+        }
+    }
+
+    /// Cast the skill in hotbar `slot`.
+    fn cast_skill(&mut self, slot: usize) {
+        let def = match self.player.skills.cast(slot, &mut self.player.stats) {
+            Ok(def) => def,
+            Err(CastError::EmptySlot) => return,
+            Err(CastError::OnCooldown) => { self.show_notice("That skill isn't ready yet."); return; }
+            Err(CastError::NotEnoughMp) => { self.show_notice("Not enough MP."); return; }
+        };
+        println!("Game: cast {}", def.name);
+        match def.effect {
+            SkillEffect::Heal(amount) => {
+                let stats = &mut self.player.stats;
+                stats.hp = (stats.hp + amount).min(stats.max_hp);
+            }
+            SkillEffect::FireBolt { damage, range } => {
+                let damage = damage + self.player.attack_power() / 2;
+                let bolt = Projectile::from_tile(Owner::Player, self.player.tile(), self.player.facing, 260.0, range as f32, damage)
+                    .with_look(Color::new(1.0, 0.5, 0.1, 1.0), 6.0);
+                self.projectiles.push(bolt);
+            }
+            SkillEffect::Purify => {
+                for kind in [StatusKind::Poison, StatusKind::Burn, StatusKind::Slow] {
+                    self.player.status.cure(kind);
+                }
+            }
+        }
+    }
+
+    /// What the music should be saying about play right now.
+    fn mood(&self) -> Mood {
+        let (px, py) = self.player.tile();
+        let close = self.enemies.iter().filter(|e| e.alertness() == Alertness::Alerted).any(|e| {
+            let (ex, ey) = e.tile();
+            (ex - px).abs() + (ey - py).abs() <= COMBAT_MUSIC_TILES
+        });
+        let stats = &self.player.stats;
+        let hurt = (stats.hp as f32) < stats.max_hp as f32 * LOW_HP_SHARE;
+        Mood { tension: hurt || !self.enemies.is_empty(), combat: close }
+    }
+
+    /// Who listens for which events.
+    fn subscriptions() -> EventBus {
+        let mut events = EventBus::default();
+        events.subscribe(Topic::EnemyDied, Subscriber::Achievements);
+        events.subscribe(Topic::DoorOpened, Subscriber::Achievements);
+        events.subscribe(Topic::EnemyDied, Subscriber::Vars);
+        events.subscribe(Topic::ItemPickedUp, Subscriber::Vars);
+        events.subscribe(Topic::RoomEntered, Subscriber::Vars);
+        events.subscribe(Topic::ItemPickedUp, Subscriber::Tutorial);
+        events
+    }
+
+    /// Hand this frame's events to their subscribers.
+    fn dispatch_events(&mut self) {
+        for (subscriber, event) in self.events.drain() {
+            match (subscriber, event) {
+                (Subscriber::Achievements, Event::EnemyDied { .. }) => self.record(Counter::Kills),
+                (Subscriber::Achievements, Event::DoorOpened { .. }) => self.record(Counter::DoorsOpened),
+                (Subscriber::Vars, Event::EnemyDied { .. }) => { self.vars.add("kills", 1); }
+                (Subscriber::Vars, Event::ItemPickedUp(id)) => self.vars.set_flag(&format!("found_{}", id), true),
+                (Subscriber::Vars, Event::RoomEntered(room)) => self.vars.set_flag(&format!("visited_room_{}", room), true),
+                (Subscriber::Tutorial, Event::ItemPickedUp(_)) => self.offer_hint(Hint::Inventory),
+                _ => {}
+            }
+        }
+    }
+
+    /// True while a menu navigated with Up/Down has the keyboard, rather than the world.
+    fn in_menu(&self) -> bool {
+        self.scenes.top() != Some(SceneId::World)
+            || self.inventory_screen.visible || self.shop_screen.visible || self.barter_screen.visible || self.save_menu.visible || self.travel_menu.visible
+    }
+
+    /// Show a first-time hint, unless hints are off or it's been shown before.
+    fn offer_hint(&mut self, hint: Hint) {
+        if self.options.tutorial_hints && self.tutorial.offer(hint) {
+            self.tutorial.save();
+        }
+    }
+
+    /// Offer the hint for whatever the player has just run into, if anything.
+    fn offer_hints(&mut self) {
+        if !self.options.tutorial_hints { return; }
+        let (at, facing) = (self.player.tile(), self.player.facing_tile());
+        let on_tile = |tile: (i32, i32)| (tile.0 >= 0 && tile.1 >= 0).then_some((tile.0 as usize, tile.1 as usize));
+        let usable = on_tile(facing).zip(on_tile(at)).is_some_and(|((fx, fy), (px, py))| self.map.can_interact_tile(fx, fy, px, py));
+        let at_save_point = on_tile(at).is_some_and(|(x, y)| self.map.is_save_point(x, y))
+            || (usable && on_tile(facing).is_some_and(|(x, y)| self.map.is_save_point(x, y)));
+        let enemy_near = self.enemies.iter().any(|e| (e.tile().0 - at.0).abs() + (e.tile().1 - at.1).abs() <= 4);
+        let hint = if self.npcs.iter().any(|npc| npc.tile == facing) {
+            Hint::Talk
+        } else if at_save_point {
+            Hint::Save
+        } else if usable {
+            Hint::Interact
+        } else if enemy_near {
+            Hint::Attack
+        } else if self.player.moving {
+            Hint::Run
+        } else {
+            return;
+        };
+        self.offer_hint(hint);
+    }
+
+    /// Bump a profile counter, toasting any achievements it unlocks.
+    fn record(&mut self, counter: Counter) {
+        for def in self.profile.record(counter) {
+            self.toasts.push(def);
+        }
+        self.profile.save();
+    }
+
+    /// Start the credits roll. Also the hook for the end of the main quest.
+    fn roll_credits(&mut self) -> Command<Game> {
+        self.credits.restart();
+        Command::Switch(Box::new(CreditsScene))
+    }
+
+    /// Switch once the screen is covered, or right away if another switch is already waiting.
+    fn transition_to(&mut self, style: Style, cut: Cut) {
+        if let Some(cut) = self.transition.start(style, cut) {
+            self.apply_cut(cut);
+        }
+    }
+
+    fn apply_cut(&mut self, cut: Cut) {
+        match cut {
+            Cut::Scene(scene) => self.scenes.replace(scene),
+            Cut::Travel(waypoint) => self.travel_to(&waypoint),
+            Cut::Exit(pad) => {
+                self.change_room(pad.room);
+                self.player.warp_to(pad.tile);
             }
-            "indoors" => {
-                if let Some(ref mut music) = self.assets.indoors_music {
-                    music.set_volume(1.0);
-                    let _ = music.play_detached(ctx);
+            Cut::Respawn => {
+                // the arena fights to a knockout, not to the death, so ironman runs survive it
+                let arena = self.end_arena_run();
+                if arena.is_none() && self.is_ironman() {
+                    self.end_ironman_run();
+                    return;
                 }
+                // the bed is in the starting room
+                self.change_room(0);
+                self.player.respawn();
+                self.party.rest();
+                self.show_notice(arena.as_deref().unwrap_or("You blacked out and woke up in bed..."));
+            }
+            Cut::Battle(group) => {
+                println!("Game: wild encounter with {} foe(s)", group.len());
+                // no bosses yet: the foes' war cry as the battle opens is the roar
+                self.rumble(rumble::Kind::Roar);
+                let battle = Battle::new(group).with_pacing(self.options.battle_speed.factor()).with_damage_numbers(self.options.damage_numbers);
+                self.scenes.push(Box::new(BattleScene { battle, cheered: false }));
+            }
+        }
+    }
+
+    fn handle_slot_action(&mut self, ctx: &mut Context, action: SlotAction) -> GameResult<Command<Game>> {
+        let next: Box<dyn Scene<Game>> = match action {
+            SlotAction::Back => Box::new(TitleScene),
+            SlotAction::Continue(slot, meta) => {
+                // Stop title music when leaving for the game
+                self.stop_music(ctx);
+                self.start_world(ctx, slot, *meta)?;
+                Box::new(WorldScene)
             }
-            "overworld" => {
-                if let Some(ref mut music) = self.assets.overworld_music {
-                    music.set_volume(1.0);
-                    let _ = music.play_detached(ctx);
+            SlotAction::NewGame { slot, seed, generation, ironman, daily } => {
+                let seed = seed.unwrap_or_else(|| GameRng::from_entropy().seed());
+                let mut meta = SaveMetadata::new(seed, generation);
+                meta.ironman = ironman;
+                meta.daily = daily.map(|day| day.code()).unwrap_or_default();
+                self.stop_music(ctx);
+                if let Err(e) = save::write_slot(slot, &meta) {
+                    println!("Game: failed to write save slot {}: {}", slot + 1, e);
+                }
+                // the old game's screenshot would be misleading until the first save
+                let _ = std::fs::remove_file(save::thumbnail_path(slot));
+                self.start_world(ctx, slot, meta)?;
+                if self.skip_intro {
+                    Box::new(WorldScene)
+                } else {
+                    self.intro.index = 0;
+                    self.intro.timer = 0.0;
+                    Box::new(IntroScene)
                 }
             }
+        };
+        Ok(Command::Switch(next))
+    }
+
+    fn handle_profile_key(&mut self, ctx: &mut Context, code: KeyCode) {
+        match self.profile_screen.handle_key(code, &mut self.profiles) {
+            Some(ProfileAction::Select(name)) => self.select_profile(ctx, name),
+            Some(ProfileAction::Close) | None => {}
+        }
+    }
+
+    /// Crossfade to the music track `key` (no-op if it's already playing).
+    fn set_music(&mut self, ctx: &mut Context, key: &str) {
+        self.music.switch_to(ctx, &mut self.assets, key);
+    }
+
+    fn stop_music(&mut self, ctx: &mut Context) {
+        self.music.stop(ctx, &mut self.assets);
+    }
+
+    /// A key went down, on the keyboard or standing in for a gamepad button.
+    fn press_key(&mut self, ctx: &mut Context, input: KeyInput, repeat: bool) -> GameResult {
+        if let Some(code) = input.keycode {
+            // Up/Down make their own repeats (see `KeyRepeat`)
+            if repeat && KeyRepeat::repeats(code) { return Ok(()); }
+            if !repeat { self.key_repeat.press(code); }
+        }
+        if let (Some(recorder), Some(code)) = (&mut self.recorder, input.keycode) {
+            recorder.key(code);
+        }
+        self.handle_key_down(ctx, input)
+    }
+
+    fn handle_key_down(&mut self, ctx: &mut Context, input: KeyInput) -> GameResult {
+        let Some(code) = input.keycode else { return Ok(()) };
+        // any key cancels a walk to a clicked tile
+        self.player.stop_walking();
+        // While typing a profile name or a seed every letter belongs to the text field
+        if self.profile_screen.is_typing() {
+            self.handle_profile_key(ctx, code);
+            return Ok(());
+        }
+        // the screen is closing in on a switch; the old screen no longer takes input
+        if self.transition.covering() {
+            return Ok(());
+        }
+
+        let typing = self.scenes.top() == Some(SceneId::SaveSlots) && self.save_slots.is_typing();
+        if code == KeyCode::F9 {
+            self.save_clip();
+            return Ok(());
+        }
+        // debug keys, only in debug builds or with --debug:
+        // game speed: F5 slower, F6 faster, F7 pause, F8 normal, F10 one step while paused
+        match code {
+            _ if !self.debug_keys => {}
+            KeyCode::F5 => self.time_scale.step(false),
+            KeyCode::F6 => self.time_scale.step(true),
+            KeyCode::F7 => self.time_scale.toggle_pause(),
+            KeyCode::F8 => self.time_scale.reset(),
+            KeyCode::F10 => self.time_scale.step_frame(),
+            KeyCode::F3 => self.inspector.toggle(),
+            KeyCode::F2 => {
+                self.show_grid = !self.show_grid;
+                println!("Game: grid {}", if self.show_grid { "on" } else { "off" });
+            }
+            KeyCode::F4 => {
+                self.show_hitboxes = !self.show_hitboxes;
+                println!("Game: hitboxes {}", if self.show_hitboxes { "on" } else { "off" });
+            }
             _ => {}
         }
+        if matches!(code, KeyCode::F2 | KeyCode::F3 | KeyCode::F4 | KeyCode::F5 | KeyCode::F6 | KeyCode::F7 | KeyCode::F8 | KeyCode::F10) {
+            return Ok(());
+        }
+        if Action::Menu.matches(code) && !typing {
+            let command = if self.scenes.top() == Some(SceneId::Options) {
+                Command::Pop
+            } else {
+                self.options.open();
+                Command::Push(Box::new(OptionsScene))
+            };
+            self.apply_command(command);
+            return Ok(());
+        }
+        self.run_top_scene(|scene, game| scene.key_down(game, ctx, input))
+    }
 
-        self.current_music = Some(music_name.to_string());
+    /// Run the top scene against the game, then do whatever it asked of the stack.
+    fn run_top_scene(&mut self, run: impl FnOnce(&mut dyn Scene<Game>, &mut Game) -> GameResult<Command<Game>>) -> GameResult {
+        let Some(mut scene) = self.scenes.take_top() else { return Ok(()) };
+        let result = run(scene.as_mut(), self);
+        self.scenes.put_back(scene);
+        self.apply_command(result?);
+        Ok(())
     }
 
-    fn stop_music(&mut self, _ctx: &mut Context) {
-        // Stop all currently playing music by setting volume to 0 and pausing
-        if let Some(ref mut music) = self.assets.title_music {
-            music.set_volume(0.0);
-            let _ = music.pause();
+    fn apply_command(&mut self, command: Command<Game>) {
+        match command {
+            Command::None => {}
+            Command::Push(scene) => self.scenes.push(scene),
+            Command::Pop => self.scenes.pop(),
+            Command::Switch(scene) => self.transition_to(Style::Fade, Cut::Scene(scene)),
         }
-        if let Some(ref mut music) = self.assets.indoors_music {
-            music.set_volume(0.0);
-            let _ = music.pause();
+    }
+
+    /// A key for the options menu.
+    fn options_key(&mut self, ctx: &mut Context, code: KeyCode) -> Command<Game> {
+        let before = (self.options.view, self.options.selected);
+        let action = self.options.handle_key(code);
+        let changed = action.is_some() || before != (self.options.view, self.options.selected);
+        // backing out of a page is a cancel, whichever key did it
+        let cue = if matches!(action, Some("return")) || (before.0 != OptionsView::Main && self.options.view == OptionsView::Main) { Some(Cue::Cancel) } else { menu_sfx::cue(code, changed) };
+        if let Some(cue) = cue {
+            self.menu_sound(ctx, cue);
         }
-        if let Some(ref mut music) = self.assets.overworld_music {
-            music.set_volume(0.0);
-            let _ = music.pause();
+        if let Some(action) = action {
+            self.options.save();
+            match action {
+                "set_aspect" => self.fit_window_to_aspect(ctx),
+                "toggle_fullscreen" => {
+                    // the window knows whether it is fullscreen right now
+                    let on = ctx.gfx.window().fullscreen().is_none();
+                    self.set_fullscreen(ctx, on);
+                }
+                "toggle_fps" | "toggle_perf_overlay" | "toggle_texture_filter" | "toggle_pixel_perfect" | "set_border" | "set_effect" | "toggle_gba_colors" => {
+                    // draw() reads these every frame
+                }
+                "toggle_accessibility" => {
+                    crate::palette::set_colorblind(self.options.colorblind);
+                    crate::palette::set_high_contrast(self.options.high_contrast);
+                }
+                "set_ambience_volume" => {
+                    self.ambience.set_volume(&mut self.assets, self.options.ambience_volume);
+                }
+                "set_sfx_volume" => {
+                    // sound effects pick the volume up each time they play
+                }
+                "set_turn_threshold" => {
+                    self.player.turn_threshold = self.options.turn_threshold;
+                }
+                "set_text_speed" => {
+                    self.dialogue.speed = self.options.text_speed.factor();
+                }
+                "toggle_auto_run" => {
+                    self.player.auto_run = self.options.auto_run;
+                }
+                "toggle_rumble" | "set_rumble_strength" => {
+                    // a taste of the new setting
+                    self.rumble(rumble::Kind::Hit);
+                }
+                "toggle_tutorial_hints" => {
+                    self.tutorial.dismiss();
+                }
+                "toggle_click_to_move" => {
+                    self.player.stop_walking();
+                }
+                "set_battle_speed" | "toggle_damage_numbers" => {
+                    // picked up when the next battle starts
+                }
+                "toggle_speedrun_timer" => {
+                    // timer overlay is drawn in draw() while enabled
+                }
+                "toggle_clip_recording" => {
+                    self.clips.clear();
+                }
+                "toggle_gba_refresh" => {
+                    // GBA refresh rate toggle - frame limiting handled in update()
+                    self.frame_limiter_accumulator = 0.0; // Reset accumulator
+                }
+                "exit" => std::process::exit(0),
+                "return" => return Command::Pop,
+                _ => {}
+            }
         }
-        self.current_music = None;
+        Command::None
     }
-}
 
-impl EventHandler for Game {
-    fn update(&mut self, ctx: &mut Context) -> GameResult {
-        // get delta time from ggez context time
-        let dt = ctx.time.delta().as_secs_f32();
+    fn title_key(&mut self, ctx: &mut Context, input: KeyInput, code: KeyCode) -> Command<Game> {
+        if self.profile_screen.visible {
+            self.handle_profile_key(ctx, code);
+            return Command::None;
+        }
+        if self.achievements_screen.visible {
+            self.achievements_screen.handle_key(code);
+            return Command::None;
+        }
+        let before = self.title_screen.selected;
+        let action = self.title_screen.handle_input(input);
+        if let Some(cue) = menu_sfx::cue(code, action.is_some() || before != self.title_screen.selected) {
+            self.menu_sound(ctx, cue);
+        }
+        match action {
+            Some(TitleAction::Start) => {
+                self.save_slots.refresh();
+                Command::Switch(Box::new(SaveSlotsScene))
+            }
+            Some(TitleAction::Achievements) => {
+                self.achievements_screen.visible = true;
+                Command::None
+            }
+            Some(TitleAction::Profiles) => {
+                self.profile_screen.open(&self.profiles, self.profiles.last.as_deref(), true);
+                Command::None
+            }
+            Some(TitleAction::Credits) => self.roll_credits(),
+            None => Command::None,
+        }
+    }
+
+    /// A key while out in the world.
+    fn world_key(&mut self, ctx: &mut Context, code: KeyCode) {
+        // Dialogue, save prompt, travel menu, shop, barter and inventory capture input while open
+        if self.dialogue.visible {
+            self.dialogue.handle_key(code);
+            // every confirm or cancel turns the page or closes the box
+            if let Some(cue) = menu_sfx::cue(code, true).filter(|&cue| cue != Cue::Move) {
+                self.menu_sound(ctx, cue);
+            }
+            return;
+        }
+        if self.travel_menu.visible {
+            if let Some(TravelAction::Travel(waypoint)) = self.travel_menu.handle_key(code, &self.waypoints) {
+                self.transition_to(Style::Wipe, Cut::Travel(waypoint));
+            }
+            return;
+        }
+        if self.save_menu.visible {
+            if let Some(SaveMenuAction::Save) = self.save_menu.handle_key(code) {
+                if self.resting_in_bed {
+                    // a night's sleep restores HP and MP
+                    let stats = &mut self.player.stats;
+                    stats.hp = stats.max_hp;
+                    stats.mp = stats.max_mp;
+                    self.party.rest();
+                    self.record(Counter::NightsSlept);
+                    self.calendar.next_day();
+                    if let Some(meta) = &self.save_meta {
+                        self.map.set_sky(Sky::today(&self.calendar, meta.seed));
+                    }
+                }
+                self.save_progress();
+            }
+            return;
+        }
+        if self.shop_screen.visible {
+            if let Some(NpcRole::Merchant(shop)) = self.npcs.get_mut(self.shop_screen.npc).map(|n| &mut n.role) {
+                self.shop_screen.handle_key(code, shop, &mut self.player);
+            } else {
+                self.shop_screen.visible = false;
+            }
+            return;
+        }
+        if self.barter_screen.visible {
+            let npc = self.barter_screen.npc;
+            let Some(name) = self.npcs.get(npc).map(|n| n.name.clone()) else {
+                self.barter_screen.visible = false;
+                return;
+            };
+            let reputation = barter::reputation_var(&name);
+            let standing = self.vars.int(&reputation);
+            if let Some(NpcRole::Trader(goods)) = self.npcs.get_mut(npc).map(|n| &mut n.role)
+                && self.barter_screen.handle_key(code, goods, &mut self.player, standing).is_some() {
+                // dealing fairly makes a friend
+                self.vars.add(&reputation, 1);
+            }
+            return;
+        }
+        if self.inventory_screen.visible {
+            let changed = self.inventory_screen.handle_key(code, &mut self.player);
+            let cue = if self.inventory_screen.visible { menu_sfx::cue(code, changed) } else { Some(Cue::Cancel) };
+            if let Some(cue) = cue {
+                self.menu_sound(ctx, cue);
+            }
+            return;
+        }
+        if Action::Inventory.matches(code) {
+            self.inventory_screen.toggle();
+            self.menu_sound(ctx, Cue::Confirm);
+            return;
+        }
+
+        // Melee attack
+        if Action::Attack.matches(code) {
+            self.player_attack();
+            return;
+        }
+
+        // Ranged attack
+        if Action::Throw.matches(code) {
+            self.player_throw();
+            return;
+        }
+
+        // Hotbar skills (1-4)
+        let hotbar_keys = [KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4];
+        if let Some(slot) = hotbar_keys.iter().position(|k| *k == code) {
+            self.cast_skill(slot);
+            return;
+        }
+
+        // Interact
+        if Action::Confirm.matches(code) {
+            let pos = self.player.get_position();
+            let player_tx = ((pos.x + TILE_SIZE/2.0) / TILE_SIZE) as usize;
+            let player_ty = ((pos.y + TILE_SIZE/2.0) / TILE_SIZE) as usize;
+
+            // The bed (standing in it) and save crystals (facing them) open the save menu
+            let facing_tile = self.player.facing_tile();
+            let facing_save_point = facing_tile.0 >= 0 && facing_tile.1 >= 0
+                && self.map.can_interact_tile(facing_tile.0 as usize, facing_tile.1 as usize, player_tx, player_ty)
+                && self.map.is_save_point(facing_tile.0 as usize, facing_tile.1 as usize);
+            if self.map.is_save_point(player_tx, player_ty) || facing_save_point {
+                if self.is_ironman() {
+                    self.show_notice("Ironman runs save themselves as you go.");
+                    return;
+                }
+                self.resting_in_bed = self.map.is_save_point(player_tx, player_ty);
+                self.save_menu.open();
+                return;
+            }
+
+            // Talk to the NPC in front of us
+            if let Some(idx) = self.npcs.iter().position(|n| n.tile == facing_tile) {
+                println!("Game: talking to {}", self.npcs[idx].name);
+                match &self.npcs[idx].role {
+                    NpcRole::Merchant(_) => self.shop_screen.open(idx),
+                    NpcRole::Recruit { .. } => self.recruit(idx),
+                    NpcRole::Trader(_) => self.barter_screen.open(idx),
+                    NpcRole::ArenaMaster => self.enter_arena(),
+                }
+                return;
+            }
+            
+            // Furniture, doors and chests, the same way the test harness presses confirm
+            match world::confirm(&mut self.map, &mut self.player) {
+                Some(Used::Interacted(tile, interaction)) => self.interact(tile, interaction),
+                Some(Used::Tile(TileUse::Chest(gold))) => {
+                    self.rumble(rumble::Kind::Heavy);
+                    let text = format!("Found {} gold in the chest!", gold);
+                    self.show_notice(&text);
+                }
+                Some(Used::Tile(TileUse::Door { open: true })) => self.events.publish(Event::DoorOpened { room: self.map.current_room(), tile: facing_tile }),
+                Some(Used::Tile(TileUse::Door { open: false })) | None => {}
+            }
+        }
+
+        // Movement is now handled in player.update() via continuous key checking
+        // No need to forward movement keys here anymore
+    }
+
+    /// A typed character, live or replayed.
+    fn handle_text_input(&mut self, character: char) {
+        if self.profile_screen.is_typing() {
+            self.profile_screen.handle_text(character);
+            return;
+        }
+        if let Some(mut scene) = self.scenes.take_top() {
+            scene.text_input(self, character);
+            self.scenes.put_back(scene);
+        }
+    }
+
+    /// One frame of game logic.
+    fn update_frame(&mut self, ctx: &mut Context) -> GameResult {
+        let frame = self.replay.as_mut().map(|replay| replay.next_frame());
+        if let Some(None) = frame {
+            println!("Replay: finished, the keyboard takes over");
+            self.replay = None;
+        }
+        let live = !matches!(frame, Some(Some(_)));
+        let (dt, controls) = match frame.flatten() {
+            // a replayed frame brings its own presses, timing and held keys
+            Some(frame) => {
+                for press in frame.presses {
+                    match press {
+                        Press::Key(key) => self.handle_key_down(ctx, KeyInput { scancode: 0, keycode: Some(key), mods: KeyMods::empty() })?,
+                        Press::Char(ch) => self.handle_text_input(ch),
+                        Press::Click(tile) => self.click_tile(tile),
+                    }
+                }
+                (frame.dt, frame.controls)
+            }
+            None if !self.focused => {
+                // alt-tabbed away: freeze everything, including timers, until the window comes back
+                return Ok(());
+            }
+            // get delta time from ggez context time
+            None => (ctx.time.delta().as_secs_f32(), Controls::read(ctx)),
+        };
+        // menus scroll while Up/Down is held; recorded like any other press
+        if live && let Some(code) = self.key_repeat.update(dt, |key| input::is_held(ctx, key)) && self.in_menu() {
+            if let Some(recorder) = &mut self.recorder {
+                recorder.key(code);
+            }
+            self.handle_key_down(ctx, KeyInput { scancode: 0, keycode: Some(code), mods: KeyMods::empty() })?;
+        }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.frame(dt, controls);
+        }
 
         // GBA refresh rate limiting
         if self.options.gba_refresh_rate {
@@ -156,59 +1604,219 @@ impl EventHandler for Game {
             self.fps_timer = 0.0;
         }
 
-        if self.options.visible {
-            // pause game updates when options visible
+        self.toasts.update(dt);
+        self.rumble.update(dt);
+        self.music.update(ctx, &mut self.assets, dt);
+        // the run clock keeps going while menus are open, like a real-time speedrun timer
+        if self.scenes.contains(SceneId::World) {
+            self.speedrun.update(dt);
+            if let Some(meta) = &mut self.save_meta {
+                meta.playtime += dt as f64;
+            }
+        }
+        if let Some(cut) = self.transition.update(dt) {
+            self.apply_cut(cut);
+        }
+        if self.transition.covering() {
+            // hold everything still while the screen closes in on a switch
             return Ok(());
         }
 
-        match self.state {
-            GameState::Playing => {
-                self.player.update(ctx, dt, &self.map);
-                for enemy in &mut self.enemies {
-                    enemy.update(ctx, dt, &self.player, &self.map);
-                }
+        if !self.scenes.contains(SceneId::World) {
+            // ambient loops belong to the world, not the menus
+            self.ambience.stop(ctx, &mut self.assets);
+        } else if cfg!(debug_assertions) && self.room_watch.poll(dt, self.map.current_room()) {
+            self.reload_room(ctx);
+        }
+        self.controls = controls;
+        // paused for debugging: the scenes stay frozen but keep drawing
+        let Some(dt) = self.time_scale.update(dt) else { return Ok(()) };
+        self.run_top_scene(|scene, game| scene.update(game, ctx, dt))
+    }
+
+    /// Walk to a clicked tile, when the world is up with no menu over it.
+    fn click_tile(&mut self, tile: (i32, i32)) {
+        let menu_open = self.inventory_screen.visible || self.shop_screen.visible || self.barter_screen.visible || self.save_menu.visible || self.dialogue.visible || self.travel_menu.visible;
+        if self.scenes.top() != Some(SceneId::World) || menu_open || self.transition.covering() {
+            return;
+        }
+        world::walk_to(&self.map, &mut self.player, tile);
+    }
+
+    fn menu_sound(&mut self, ctx: &mut Context, cue: Cue) {
+        menu_sfx::play(ctx, &mut self.assets, cue, self.options.sfx_volume);
+    }
+
+    /// Title and save slot screens share the title music; set it only once.
+    fn play_title_music(&mut self, ctx: &mut Context) {
+        // the world's music may still be playing after an ironman run ends
+        if self.music.current() != Some("title") {
+            self.set_music(ctx, "title");
+        }
+    }
+
+    /// One frame out in the world.
+    fn update_world(&mut self, ctx: &mut Context, dt: f32) -> GameResult {
+        self.dispatch_events();
+        if let Some(pitch) = self.dialogue.update(dt)
+            && let Some(blip) = &mut self.assets.blip {
+            blip.set_pitch(pitch);
+            blip.set_volume(self.options.sfx_volume);
+            let _ = blip.play_detached(ctx);
+        }
+        if self.inventory_screen.visible || self.shop_screen.visible || self.barter_screen.visible || self.save_menu.visible || self.dialogue.visible || self.travel_menu.visible {
+            // pause game updates when a menu is open
+            return Ok(());
+        }
+        let controls = self.controls;
+        // each room picks its own track; rooms without one keep the current music
+        if let Some(key) = self.map.room_music().map(str::to_string) {
+            self.set_music(ctx, &key);
+        }
+        let ambience = self.map.room_ambience().map(str::to_string);
+        self.ambience.switch_to(ctx, &mut self.assets, ambience.as_deref());
+        let step = world::step(&mut self.map, &mut self.player, controls, dt, &mut self.teleport_armed);
+        self.offer_hints();
+        if self.player.pushed {
+            self.rumble(rumble::Kind::Heavy);
+        }
+        self.teleport_flash = (self.teleport_flash - dt).max(0.0);
+        self.shake.update(dt);
+        let centre = self.player.get_position() + nalgebra::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+        self.camera.follow(centre, dt);
+        let mut knocked_out = false;
+        if self.player.tick_fall(dt) {
+            knocked_out |= self.land_from_fall();
+        } else if !self.player.moving && !self.player.is_falling() && self.map.is_pit(self.player.tile()) {
+            println!("Game: player fell down a pit");
+            self.player.start_fall();
+        }
+        if let Some(pad) = step.pad {
+            self.teleport(pad);
+        }
+        if !self.player.moving && let Some(exit) = self.map.exit_destination(self.player.tile()) {
+            self.transition_to(Style::Fade, Cut::Exit(exit));
+        }
+        self.party.update(dt, self.player.tile());
+        self.companion.update(dt, self.party.tail_tile(self.player.tile()));
+        let (w, h) = (self.map.width_pixels() / TILE_SIZE as usize, self.map.height_pixels() / TILE_SIZE as usize);
+        self.explored.reveal(self.map.current_room(), w, h, self.player.tile(), exploration::SIGHT_RADIUS);
+        self.splashes.update(dt);
+        if self.player.stepped && let Some(surface) = self.map.surface_at(self.player.tile()) {
+            footsteps::play(ctx, &mut self.assets, surface, self.options.sfx_volume);
+        }
+        // every step through a wild room counts down to the next random battle
+        if self.player.stepped
+            && let Some(table) = self.map.encounter_table()
+            && self.encounter_steps.step(table, &mut self.rng) {
+            let group = table.roll_group(&mut self.rng);
+            if let Some(sting) = &mut self.assets.battle_sting {
+                sting.set_volume(self.options.sfx_volume);
+                let _ = sting.play_detached(ctx);
             }
-            GameState::Intro => {
-                // advance intro timer (auto-advance handled by Intro struct)
-                if self.intro.update(dt) {
-                    self.state = GameState::Playing;
-                    // Set indoors music for gameplay
-                    self.set_music(ctx, "indoors");
-                    println!("Game state: Intro -> Playing");
+            self.transition_to(Style::Spiral, Cut::Battle(group));
+        }
+        if self.player.moving && let Some(water) = self.player.water(&self.map) {
+            let centre = self.player.get_position() + nalgebra::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+            if self.splashes.wade(centre, water)
+                && let Some(sound) = &mut self.assets.splash_sound {
+                sound.set_volume(if water == Water::Deep { 0.4 } else { 0.7 } * self.options.sfx_volume);
+                let _ = sound.play_detached(ctx);
+            }
+        }
+        // stepping into the doorway on the top wall leaves the starting room
+        if self.player.tile().1 == 0 {
+            self.speedrun.split(speedrun::SPLIT_LEAVE_ROOM);
+        }
+        for enemy in &mut self.enemies {
+            enemy.update(dt, &self.player, &self.map);
+            if let Some(shot) = enemy.try_shoot(dt, &self.player) {
+                self.projectiles.push(shot);
+            }
+        }
+        self.update_arena(dt);
+        self.index_enemies();
+        self.music.set_mood(self.mood());
+        if let Some(idx) = self.companion.try_nip(self.enemies.iter().map(|e| e.tile())) {
+            self.damage_enemy(idx, companion::NIP_DAMAGE);
+        }
+        self.party_fights();
+        let shot_hit = self.update_projectiles(dt);
+        // Contact damage, or an enemy projectile
+        let toucher = self.enemy_grid.near(self.player.get_position()).into_iter().find(|&i| self.enemies[i].touches(&self.player));
+        let hit = toucher.map(|i| (self.enemies[i].damage, self.enemies[i].inflicts)).or(shot_hit);
+        if let Some((damage, inflicts)) = hit
+            && self.player.hurt_timer <= 0.0 {
+            let hp = self.player.stats.hp;
+            knocked_out = self.player.hurt(damage);
+            self.jolt_for_hit(hp - self.player.stats.hp);
+            if let Some((kind, secs)) = inflicts {
+                self.player.status.apply(kind, secs);
+            }
+        }
+        // Spikes: the player is hurt whenever standing on them while they're out (the
+        // invulnerability window spaces the hits); enemies only when they shoot up
+        if self.map.spikes_up_at(self.player.tile()) && self.player.hurt_timer <= 0.0 {
+            let hp = self.player.stats.hp;
+            knocked_out |= self.player.hurt(SPIKE_DAMAGE);
+            self.jolt_for_hit(hp - self.player.stats.hp);
+        }
+        if step.spikes_sprang {
+            for i in (0..self.enemies.len()).rev() {
+                if self.map.spikes_up_at(self.enemies[i].tile()) {
+                    self.damage_enemy(i, SPIKE_DAMAGE);
                 }
             }
-            GameState::Title => {
-                // Set title music only once
-                if self.current_music.is_none() {
-                    self.set_music(ctx, "title");
+        }
+        knocked_out |= self.player.tick_status(dt);
+        if knocked_out {
+            self.transition_to(Style::Fade, Cut::Respawn);
+        }
+        // Coins and items are collected by walking over them, unless left too long
+        self.pickups.retain_mut(|pickup| pickup.update(dt));
+        self.pickup_grid.rebuild(self.pickups.iter().map(|p| p.position));
+        let collected: Vec<usize> = self.pickup_grid.near(self.player.get_position()).into_iter().filter(|&i| self.pickups[i].touches(&self.player)).collect();
+        for i in collected.into_iter().rev() {
+            match self.pickups.remove(i).loot {
+                Loot::Gold(gold) => self.player.gold += gold,
+                Loot::Item(id, _) => {
+                    self.player.inventory.add(id);
+                    self.events.publish(Event::ItemPickedUp(id));
+                    let name = items::find(id).map_or(id, |def| def.name);
+                    self.show_notice(&format!("Picked up a {}.", name));
                 }
             }
         }
-
+        if let Some((_, time_left)) = &mut self.notice {
+            *time_left -= dt;
+            if *time_left <= 0.0 { self.notice = None; }
+        }
+        if let Some((_, time_left)) = &mut self.room_banner {
+            *time_left -= dt;
+            if *time_left <= 0.0 { self.room_banner = None; }
+        }
+        self.tutorial.update(dt);
+        if self.inspector.visible {
+            let entries = self.inspected();
+            self.inspector.follow(&entries);
+        }
         Ok(())
     }
 
-    fn draw(&mut self, ctx: &mut Context) -> GameResult {
-        // create a single canvas/frame for this draw call
-        let mut canvas = Canvas::from_frame(ctx, Color::new(0.1, 0.2, 0.3, 1.0));
-        // compute scale and offset to preserve 4:3 aspect and fill as much screen as possible
+    /// The world and its HUD, scaled and scrolled to fit the window.
+    fn draw_world(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        // the game is shown in the largest 4:3 (or 16:9) area that fits the window
         let win_size = ctx.gfx.window().inner_size();
         let win_w = win_size.width as f32;
         let win_h = win_size.height as f32;
         let map_w = self.map.width_pixels() as f32;
         let map_h = self.map.height_pixels() as f32;
+        let area = self.options.aspect.view_area((win_w, win_h));
 
-        // desired aspect is 4:3; compute maximum scale that fits in window while maintaining 4:3
-        let target_aspect = 4.0 / 3.0;
-        // compute the area we can use: fit a 4:3 rectangle inside the window
-        let win_aspect = win_w / win_h;
-        let (render_w, render_h) = if win_aspect >= target_aspect {
-            // window is wider than 4:3, height is limiting
-            (win_h * target_aspect, win_h)
-        } else {
-            // width is limiting
-            (win_w, win_w / target_aspect)
-        };
+        // the scale is picked for a 4:3 view of the area's height, so 16:9 keeps the same zoom
+        // and just shows more of the room to either side
+        let render_h = area.h;
+        let render_w = render_h * options::Aspect::Standard.ratio();
 
         // compute scale to fit the map into render_w x render_h while keeping map's native size
         let scale_x = render_w / map_w;
@@ -216,24 +1824,275 @@ impl EventHandler for Game {
         let scale = scale_x.min(scale_y);
     // apply fullscreen multiplier (use integer multiples to keep pixel-art crisp)
     let scale = scale * self.fullscreen_scale_mul;
-        // center offset so render area is centered in window
-        let offset_x = (win_w - map_w * scale) / 2.0;
-        let offset_y = (win_h - map_h * scale) / 2.0;
+        // pixel-perfect: whole multiples only (unless the window is smaller than the map), placed
+        // on whole pixels, so every art pixel is the same size on screen
+        let pixel_perfect = self.options.pixel_perfect && scale >= 1.0;
+        let scale = if pixel_perfect { scale.floor() } else { scale };
+        // the camera centres rooms that fit and scrolls around ones that don't
+        let (view_x, view_y) = self.camera.view_origin((area.w / scale, area.h / scale), (map_w, map_h));
+        let mut offset_x = area.x - view_x * scale;
+        let mut offset_y = area.y - view_y * scale;
+        if pixel_perfect {
+            (offset_x, offset_y) = (offset_x.floor(), offset_y.floor());
+        }
+        // screen shake moves the world, not the HUD drawn over it
+        let (shake_x, shake_y) = self.shake.offset();
+        let world_offset = (offset_x + shake_x * scale, offset_y + shake_y * scale);
+        self.world_view = (scale, (offset_x, offset_y));
 
-        match self.state {
-            GameState::Playing => {
-                gui::draw_playing(ctx, &mut canvas, &self.map, &self.player, &self.enemies, &self.assets, scale, (offset_x, offset_y))?;
-            }
-            GameState::Title => {
-                gui::draw_title(ctx, &mut canvas, &self.title_screen, &self.assets)?;
+        gui::draw_playing(ctx, canvas, &self.map, &self.player, &self.enemies, &self.npcs, &self.pickups, &self.projectiles, &self.splashes, self.companion.is_in(self.map.current_room()).then_some(&self.companion), &self.party, &self.explored, &self.calendar, &self.assets, scale, world_offset, area, self.options.border, &mut self.perf)?;
+        if self.show_grid {
+            grid_overlay::draw(ctx, canvas, &self.map, &self.player, scale, world_offset)?;
+            let mouse = ctx.mouse.position();
+            let tile = world::tile_at_screen(self.world_view, (mouse.x, mouse.y));
+            grid_overlay::draw_tooltip(ctx, canvas, (mouse.x, mouse.y), tile, &self.map.describe_tile(tile))?;
+        }
+        if self.show_hitboxes {
+            hitboxes::draw(ctx, canvas, &self.map, &self.player, &self.enemies, scale, world_offset)?;
+        }
+        let hud_started = Instant::now();
+        if self.teleport_flash > 0.0 {
+            gui::draw_flash(ctx, canvas, self.teleport_flash / TELEPORT_FLASH_SECS)?;
+        }
+        if let Some((name, time_left)) = &self.room_banner {
+            let shown = ROOM_BANNER_SECS - time_left;
+            let alpha = (shown / ROOM_BANNER_FADE_SECS).min(time_left / ROOM_BANNER_FADE_SECS).min(1.0);
+            gui::draw_room_banner(ctx, canvas, name, alpha, area)?;
+        }
+        if let Some(run) = &self.arena {
+            let secs = run.elapsed as u32;
+            let text = if self.enemies.is_empty() {
+                format!("Next wave in {:.0}", run.breather().ceil())
+            } else {
+                format!("Wave {}  {}:{:02}", run.wave, secs / 60, secs % 60)
+            };
+            gui::draw_arena(ctx, canvas, &text, area)?;
+        }
+        if let Some((text, _)) = &self.notice {
+            gui::draw_notice(ctx, canvas, text, area)?;
+        }
+        self.tutorial.draw(ctx, canvas, area, self.options.auto_run)?;
+        self.inventory_screen.draw(ctx, canvas, &self.player, &self.item_descriptions)?;
+        if self.shop_screen.visible
+            && let Some(NpcRole::Merchant(shop)) = self.npcs.get(self.shop_screen.npc).map(|n| &n.role) {
+            self.shop_screen.draw(ctx, canvas, shop, &self.player, &self.item_descriptions)?;
+        }
+        if self.barter_screen.visible
+            && let Some(npc) = self.npcs.get(self.barter_screen.npc)
+            && let NpcRole::Trader(goods) = &npc.role {
+            self.barter_screen.draw(ctx, canvas, goods, &self.player, self.vars.int(&barter::reputation_var(&npc.name)))?;
+        }
+        self.save_menu.draw(ctx, canvas, self.active_slot, &self.player)?;
+        self.dialogue.draw(ctx, canvas)?;
+        self.travel_menu.draw(ctx, canvas, &self.waypoints)?;
+        if self.inspector.visible {
+            self.inspector.draw(ctx, canvas, &self.inspected(), self.world_view)?;
+        }
+        self.perf.measure(Phase::Ui, hud_started);
+        Ok(())
+    }
+}
+
+struct TitleScene;
+
+impl Scene<Game> for TitleScene {
+    fn id(&self) -> SceneId { SceneId::Title }
+
+    fn update(&mut self, game: &mut Game, ctx: &mut Context, _dt: f32) -> GameResult<Command<Game>> {
+        game.play_title_music(ctx);
+        Ok(Command::None)
+    }
+
+    fn key_down(&mut self, game: &mut Game, ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        let Some(code) = input.keycode else { return Ok(Command::None) };
+        Ok(game.title_key(ctx, input, code))
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        gui::draw_title(ctx, canvas, &game.title_screen, &game.assets)?;
+        game.achievements_screen.draw(ctx, canvas, &game.profile)?;
+        game.profile_screen.draw(ctx, canvas, &game.profiles)
+    }
+}
+
+struct SaveSlotsScene;
+
+impl Scene<Game> for SaveSlotsScene {
+    fn id(&self) -> SceneId { SceneId::SaveSlots }
+
+    fn update(&mut self, game: &mut Game, ctx: &mut Context, _dt: f32) -> GameResult<Command<Game>> {
+        game.play_title_music(ctx);
+        Ok(Command::None)
+    }
+
+    fn key_down(&mut self, game: &mut Game, ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        match input.keycode.and_then(|code| game.save_slots.handle_key(code)) {
+            Some(action) => game.handle_slot_action(ctx, action),
+            None => Ok(Command::None),
+        }
+    }
+
+    fn text_input(&mut self, game: &mut Game, character: char) {
+        game.save_slots.handle_text(character);
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        game.save_slots.draw(ctx, canvas)
+    }
+}
+
+struct IntroScene;
+
+impl Scene<Game> for IntroScene {
+    fn id(&self) -> SceneId { SceneId::Intro }
+
+    fn update(&mut self, game: &mut Game, _ctx: &mut Context, dt: f32) -> GameResult<Command<Game>> {
+        // the intro advances its own lines and says when it's done
+        Ok(if game.intro.update(dt) { Command::Switch(Box::new(WorldScene)) } else { Command::None })
+    }
+
+    fn key_down(&mut self, game: &mut Game, _ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        Ok(if game.intro.handle_input(input) { Command::Switch(Box::new(WorldScene)) } else { Command::None })
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        gui::draw_intro(ctx, canvas, &game.intro)
+    }
+}
+
+struct WorldScene;
+
+impl Scene<Game> for WorldScene {
+    fn id(&self) -> SceneId { SceneId::World }
+
+    fn update(&mut self, game: &mut Game, ctx: &mut Context, dt: f32) -> GameResult<Command<Game>> {
+        game.update_world(ctx, dt)?;
+        Ok(Command::None)
+    }
+
+    fn key_down(&mut self, game: &mut Game, ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        if let Some(code) = input.keycode {
+            game.world_key(ctx, code);
+        }
+        Ok(Command::None)
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        game.draw_world(ctx, canvas)
+    }
+}
+
+/// A random battle; the world waits underneath until it's over.
+struct BattleScene {
+    battle: Battle,
+    /// The victory fanfare has played.
+    cheered: bool,
+}
+
+impl Scene<Game> for BattleScene {
+    fn id(&self) -> SceneId { SceneId::Battle }
+
+    fn update(&mut self, game: &mut Game, ctx: &mut Context, dt: f32) -> GameResult<Command<Game>> {
+        self.battle.update(dt);
+        if !self.cheered && matches!(self.battle.outcome(), Some(Outcome::Won { .. })) {
+            self.cheered = true;
+            if let Some(fanfare) = &mut game.assets.fanfare {
+                fanfare.set_volume(game.options.sfx_volume);
+                let _ = fanfare.play_detached(ctx);
             }
-            GameState::Intro => {
-                gui::draw_intro(ctx, &mut canvas, &self.intro)?;
+        }
+        Ok(Command::None)
+    }
+
+    fn key_down(&mut self, game: &mut Game, _ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        let Some(code) = input.keycode else { return Ok(Command::None) };
+        let hp = game.player.stats.hp;
+        let done = self.battle.handle_key(code, &mut game.player, &mut game.party, &mut game.rng);
+        if game.player.stats.hp < hp {
+            game.rumble(rumble::Kind::Hit);
+        }
+        if done {
+            return Ok(game.end_battle(self.battle.outcome().cloned()));
+        }
+        Ok(Command::None)
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        self.battle.draw(ctx, canvas, &game.player, &game.party, &game.assets)
+    }
+}
+
+struct CreditsScene;
+
+impl Scene<Game> for CreditsScene {
+    fn id(&self) -> SceneId { SceneId::Credits }
+
+    fn update(&mut self, game: &mut Game, ctx: &mut Context, dt: f32) -> GameResult<Command<Game>> {
+        let screen_h = ctx.gfx.window().inner_size().height as f32;
+        Ok(if game.credits.update(dt, screen_h) { Command::Switch(Box::new(TitleScene)) } else { Command::None })
+    }
+
+    fn key_down(&mut self, game: &mut Game, _ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        // finished or skipped: back to the title screen
+        let done = input.keycode.is_some_and(|code| game.credits.handle_key(code));
+        Ok(if done { Command::Switch(Box::new(TitleScene)) } else { Command::None })
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        game.credits.draw(ctx, canvas, &game.assets)
+    }
+}
+
+/// The options menu, over whatever screen it was opened on.
+struct OptionsScene;
+
+impl Scene<Game> for OptionsScene {
+    fn id(&self) -> SceneId { SceneId::Options }
+
+    fn is_overlay(&self) -> bool { true }
+
+    fn key_down(&mut self, game: &mut Game, ctx: &mut Context, input: KeyInput) -> GameResult<Command<Game>> {
+        let Some(code) = input.keycode else { return Ok(Command::None) };
+        Ok(game.options_key(ctx, code))
+    }
+
+    fn draw(&mut self, game: &mut Game, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        game.options.draw(ctx, canvas)
+    }
+}
+
+impl EventHandler for Game {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        let started = Instant::now();
+        let result = self.update_frame(ctx);
+        self.perf.measure(Phase::Update, started);
+        result
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        // create a single canvas/frame for this draw call
+        // clip recording and save thumbnails read the frame back, so it has to be drawn offscreen
+        self.postfx.offscreen = self.options.clip_recording || self.thumbnail_due.is_some();
+        let mut canvas = self.postfx.canvas(ctx, self.options.effect, self.options.gba_colors, gui::BACKGROUND);
+        canvas.set_sampler(self.options.texture_filter.sampler());
+        // the world times its own room, entity and HUD drawing; every other screen is UI
+        let mut scenes = std::mem::take(&mut self.scenes);
+        let mut drawn = Ok(());
+        for scene in scenes.visible_mut() {
+            let started = Instant::now();
+            drawn = scene.draw(self, ctx, &mut canvas);
+            if scene.id() != SceneId::World {
+                self.perf.measure(Phase::Ui, started);
             }
+            if drawn.is_err() { break; }
         }
+        self.scenes = scenes;
+        drawn?;
+
+        let ui_started = Instant::now();
+        self.transition.draw(ctx, &mut canvas)?;
 
-    // draw options over everything when visible
-    self.options.draw(ctx, &mut canvas)?;
+    self.toasts.draw(ctx, &mut canvas)?;
 
         // Draw FPS counter if enabled
         if self.options.show_fps {
@@ -243,118 +2102,106 @@ impl EventHandler for Game {
             let fps_y = 10.0;
             canvas.draw(&fps_text, ggez::graphics::DrawParam::new().dest([fps_x, fps_y]).color(ggez::graphics::Color::YELLOW));
         }
+        if let Some(label) = self.time_scale.label() {
+            let txt = ggez::graphics::Text::new(ggez::graphics::TextFragment::new(label).scale(20.0));
+            let win_size = ctx.gfx.window().inner_size();
+            canvas.draw(&txt, ggez::graphics::DrawParam::new().dest([win_size.width as f32 - 80.0, 34.0]).color(ggez::graphics::Color::CYAN));
+        }
 
-        canvas.finish(ctx)
-    }
+        // Speedrun timer stays on top of every screen once a run has started
+        if self.options.speedrun_timer && self.active_slot.is_some() {
+            self.speedrun.draw(ctx, &mut canvas)?;
+        }
 
-    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, _repeat: bool) -> GameResult {
-        // global options toggle
-        if let Some(code) = input.keycode {
-            // Global bindings: X = options, Z = interact, C = cancel/back
-            match code {
-                KeyCode::X => { self.options.toggle(); return Ok(()); }
-                KeyCode::C => { if self.options.visible { self.options.visible = false; return Ok(()); } }
-                _ => {}
-            }
+        if !self.focused {
+            gui::draw_unfocused(ctx, &mut canvas)?;
+        }
 
-            if self.options.visible {
-                if let Some(action) = self.options.handle_key(code) {
-                    match action {
-                        "toggle_fullscreen" => {
-                            // toggle fullscreen via winit Fullscreen API
-                            let window = ctx.gfx.window();
-                            if window.fullscreen().is_some() {
-                                window.set_fullscreen(None);
-                                    self.fullscreen_scale_mul = 1.0;
-                            } else {
-                                window.set_fullscreen(Some(Fullscreen::Borderless(None)));
-                                    // try to compute an integer multiplier that scales the map larger while keeping 4:3.
-                                    let ws = window.inner_size();
-                                    let win_w = ws.width as f32;
-                                    let win_h = ws.height as f32;
-                                    let render_w = if win_w / win_h >= 4.0/3.0 { win_h * (4.0/3.0) } else { win_w };
-                                    let render_h = if win_w / win_h >= 4.0/3.0 { win_h } else { win_w / (4.0/3.0) };
-                                    let map_w = self.map.width_pixels() as f32;
-                                    let map_h = self.map.height_pixels() as f32;
-                                    let scale_x = render_w / map_w;
-                                    let scale_y = render_h / map_h;
-                                    let best = scale_x.min(scale_y);
-                                    // nearest integer >= 1.0
-                                    let mul = best.floor().max(1.0);
-                                    self.fullscreen_scale_mul = mul;
-                            }
-                        }
-                        "toggle_fps" => {
-                            // FPS counter toggle - no special handling needed here
-                        }
-                        "toggle_gba_refresh" => {
-                            // GBA refresh rate toggle - frame limiting handled in update()
-                            self.frame_limiter_accumulator = 0.0; // Reset accumulator
-                        }
-                        "exit" => std::process::exit(0),
-                        "return" => { /* handled inside options */ }
-                        _ => {}
-                    }
-                }
-                return Ok(());
+        if self.options.perf_overlay {
+            self.perf.draw(ctx, &mut canvas, self.assets.font(assets::FontRole::Mono))?;
+        }
+        self.perf.measure(Phase::Ui, ui_started);
+        self.perf.finish_frame(ctx.time.delta().as_secs_f32());
+
+        self.postfx.present(ctx, canvas, self.options.effect, self.options.gba_colors)?;
+        if self.options.clip_recording {
+            let frame = self.postfx.frame(ctx);
+            if let Err(e) = self.clips.capture(ctx, &frame, ctx.time.delta().as_secs_f32()) {
+                println!("Clip: couldn't capture the frame, recording stopped: {}", e);
+                self.options.clip_recording = false;
+            }
+        }
+        if let Some(slot) = self.thumbnail_due.take() {
+            let frame = self.postfx.frame(ctx);
+            if let Err(e) = save_slots::capture_thumbnail(ctx, &frame, slot) {
+                println!("Game: couldn't take the save thumbnail: {}", e);
             }
+            self.thumbnail_taken = Some(Instant::now());
+        }
+        Ok(())
+    }
 
-            match self.state {
-                GameState::Title => {
-                    if self.title_screen.handle_input(input) {
-                        // Stop title music when leaving title screen
-                        self.stop_music(ctx);
-                        self.state = GameState::Intro;
-                        // reset intro
-                        self.intro.index = 0;
-                        self.intro.timer = 0.0;
-                        println!("Game state: Title -> Intro");
-                    }
-                }
-                GameState::Intro => {
-                    if self.intro.handle_input(input) {
-                        self.state = GameState::Playing;
-                        // Set indoors music for gameplay
-                        self.set_music(ctx, "indoors");
-                        println!("Game state: Intro -> Playing");
-                    }
-                }
-                GameState::Playing => {
-                    // Interact key (Z)
-                    if code == KeyCode::Z {
-                        let pos = self.player.get_position();
-                        let player_tx = ((pos.x + TILE_SIZE/2.0) / TILE_SIZE) as usize;
-                        let player_ty = ((pos.y + TILE_SIZE/2.0) / TILE_SIZE) as usize;
-                        
-                        // First, try to interact with the tile the player is standing on (for closing doors)
-                        if self.map.can_interact_tile(player_tx, player_ty, player_tx, player_ty) {
-                            if self.map.interact_tile(player_tx, player_ty) {
-                                return Ok(());
-                            }
-                        }
-                        
-                        // If that didn't work, try the tile the player is facing (for opening doors)
-                        let facing = self.player.facing;
-                        let tx = ((pos.x + TILE_SIZE/2.0) / TILE_SIZE + facing.0) as isize;
-                        let ty = ((pos.y + TILE_SIZE/2.0) / TILE_SIZE + facing.1) as isize;
-                        if tx >= 0 && ty >= 0 {
-                            let txu = tx as usize;
-                            let tyu = ty as usize;
-                            if self.map.can_interact_tile(txu, tyu, player_tx, player_ty) {
-                                if self.map.interact_tile(txu, tyu) {
-                                    // interaction changed tile; nothing else to do for now
-                                }
-                            }
-                        }
-                        return Ok(());
-                    }
+    fn focus_event(&mut self, _ctx: &mut Context, gained: bool) -> GameResult {
+        if gained == self.focused { return Ok(()); }
+        self.focused = gained;
+        if gained {
+            self.music.resume(&mut self.assets);
+            self.ambience.resume(&mut self.assets);
+            println!("Game: window focused, resuming");
+        } else {
+            self.music.pause(&mut self.assets);
+            self.ambience.pause(&mut self.assets);
+            println!("Game: window lost focus, pausing");
+        }
+        Ok(())
+    }
 
-                    // Movement is now handled in player.update() via continuous key checking
-                    // No need to forward movement keys here anymore
-                }
+    fn key_down_event(&mut self, ctx: &mut Context, input: KeyInput, repeat: bool) -> GameResult {
+        // a replay drives the game by itself; the keyboard waits until it's over
+        if self.replay.is_some() { return Ok(()); }
+        input::set_pad_in_use(false);
+        self.press_key(ctx, input, repeat)
+    }
+
+    fn gamepad_button_down_event(&mut self, ctx: &mut Context, button: gilrs::Button, _id: GamepadId) -> GameResult {
+        if self.replay.is_some() { return Ok(()); }
+        input::set_pad_in_use(true);
+        let Some(code) = input::button_key(button) else { return Ok(()) };
+        self.press_key(ctx, KeyInput { scancode: 0, keycode: Some(code), mods: KeyMods::empty() }, false)
+    }
+
+    fn gamepad_axis_event(&mut self, _ctx: &mut Context, _axis: gilrs::Axis, value: f32, _id: GamepadId) -> GameResult {
+        // a nudge on a stick is enough to switch the prompts over, but not resting drift
+        if value.abs() > 0.5 {
+            input::set_pad_in_use(true);
+        }
+        Ok(())
+    }
+
+    fn mouse_button_down_event(&mut self, ctx: &mut Context, button: MouseButton, x: f32, y: f32) -> GameResult {
+        if button == MouseButton::Left && self.scenes.top() == Some(SceneId::World) {
+            let size = ctx.gfx.window().inner_size();
+            let entries = self.inspected();
+            // with the inspector open, clicks pick entities instead of walking
+            if self.inspector.click(&entries, (x, y), (size.width as f32, size.height as f32), self.world_view) {
+                return Ok(());
             }
         }
+        if self.replay.is_some() { return Ok(()); }
+        let Some(tile) = world::click_target(button, self.options.click_to_move, self.world_view, (x, y)) else { return Ok(()) };
+        if let Some(recorder) = &mut self.recorder {
+            recorder.click(tile);
+        }
+        self.click_tile(tile);
+        Ok(())
+    }
 
+    fn text_input_event(&mut self, _ctx: &mut Context, character: char) -> GameResult {
+        if self.replay.is_some() { return Ok(()); }
+        if let Some(recorder) = &mut self.recorder {
+            recorder.char(character);
+        }
+        self.handle_text_input(character);
         Ok(())
     }
 
@@ -0,0 +1,166 @@
+//! Story flags and variables: named bools, numbers and strings that anything in the game can
+//! set and check, kept in the save file. `Game` owns the one store.
+//!
+//! Text shown in dialogue can use them too: `{name}` is replaced with a variable's value,
+//! `{set name=value}` sets one and `{add name n}` adds to a number, e.g.
+//! `sign=3,2:{add prayers 1}You have prayed here {prayers} times.`
+
+use std::collections::BTreeMap;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Value {
+    Bool(bool),
+    Int(i64),
+    Str(String),
+}
+
+impl Value {
+    /// `true`/`false` and whole numbers become bools and numbers; anything else is a string.
+    pub fn parse(s: &str) -> Value {
+        let s = s.trim();
+        match s {
+            "true" => Value::Bool(true),
+            "false" => Value::Bool(false),
+            _ => s.parse().map(Value::Int).unwrap_or_else(|_| Value::Str(s.to_string())),
+        }
+    }
+}
+
+impl std::fmt::Display for Value {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Value::Bool(b) => write!(f, "{}", b),
+            Value::Int(n) => write!(f, "{}", n),
+            Value::Str(s) => write!(f, "{}", s),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameVars {
+    vars: BTreeMap<String, Value>,
+}
+
+impl GameVars {
+    pub fn get(&self, name: &str) -> Option<&Value> {
+        self.vars.get(name)
+    }
+
+    pub fn set(&mut self, name: &str, value: Value) {
+        self.vars.insert(name.to_string(), value);
+    }
+
+    /// A flag's value; unset flags (and non-bools) are false.
+    pub fn flag(&self, name: &str) -> bool {
+        matches!(self.get(name), Some(Value::Bool(true)))
+    }
+
+    pub fn set_flag(&mut self, name: &str, on: bool) {
+        self.set(name, Value::Bool(on));
+    }
+
+    /// A number's value; unset numbers (and non-numbers) are 0.
+    pub fn int(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Some(Value::Int(n)) => *n,
+            _ => 0,
+        }
+    }
+
+    /// Add to a number, starting from 0 if it isn't one yet. Returns the new value.
+    pub fn add(&mut self, name: &str, n: i64) -> i64 {
+        let total = self.int(name) + n;
+        self.set(name, Value::Int(total));
+        total
+    }
+
+    /// Carry out the `{set ...}` and `{add ...}` commands in `text` and fill in its `{name}`
+    /// references (blank for unset ones). Returns the text that's left to show.
+    pub fn run(&mut self, text: &str) -> String {
+        let mut out = String::new();
+        let mut rest = text;
+        while let Some(start) = rest.find('{') {
+            out.push_str(&rest[..start]);
+            rest = &rest[start..];
+            let Some(end) = rest.find('}') else { break };
+            let inner = rest[1..end].trim();
+            rest = &rest[end + 1..];
+            if let Some(assignment) = inner.strip_prefix("set ") {
+                match assignment.split_once('=') {
+                    Some((name, value)) => self.set(name.trim(), Value::parse(value)),
+                    None => println!("GameVars: can't read {{{}}}", inner),
+                }
+            } else if let Some(change) = inner.strip_prefix("add ") {
+                match change.split_whitespace().collect::<Vec<_>>()[..] {
+                    [name, n] if n.parse::<i64>().is_ok() => { self.add(name, n.parse().unwrap_or(0)); }
+                    _ => println!("GameVars: can't read {{{}}}", inner),
+                }
+            } else if let Some(value) = self.get(inner) {
+                out.push_str(&value.to_string());
+            }
+        }
+        out.push_str(rest);
+        out
+    }
+
+    /// Save-file form: one `name:type:value` entry per variable, type `b`, `i` or `s`.
+    pub fn to_lines(&self) -> Vec<String> {
+        self.vars.iter().map(|(name, value)| {
+            let kind = match value {
+                Value::Bool(_) => 'b',
+                Value::Int(_) => 'i',
+                Value::Str(_) => 's',
+            };
+            format!("{}:{}:{}", name, kind, value)
+        }).collect()
+    }
+
+    /// Read one `to_lines` entry back. Returns false if it can't be read.
+    pub fn read_line(&mut self, line: &str) -> bool {
+        let mut parts = line.splitn(3, ':');
+        let (Some(name), Some(kind), Some(value)) = (parts.next(), parts.next(), parts.next()) else { return false };
+        let value = match kind {
+            "b" => Value::Bool(value == "true"),
+            "i" => match value.parse() {
+                Ok(n) => Value::Int(n),
+                Err(_) => return false,
+            },
+            "s" => Value::Str(value.to_string()),
+            _ => return false,
+        };
+        self.set(name, value);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn text_commands_set_add_and_fill_in() {
+        let mut vars = GameVars::default();
+        vars.set("hero", Value::Str("Ada".to_string()));
+        assert_eq!(vars.run("{add prayers 1}{add prayers 2}{set shrine_seen=true}{hero} prayed {prayers} times.{missing}"), "Ada prayed 3 times.");
+        assert!(vars.flag("shrine_seen"));
+        assert_eq!(vars.int("prayers"), 3);
+        assert_eq!(vars.run("{add prayers lots} no closing {brace"), " no closing {brace");
+        assert_eq!(vars.int("prayers"), 3);
+    }
+
+    #[test]
+    fn vars_round_trip_with_their_types() {
+        let mut vars = GameVars::default();
+        vars.set_flag("met_mira", true);
+        vars.add("gold_spent", -40);
+        vars.set("motto", Value::Str("12: a string, with colons".to_string()));
+        vars.set("count", Value::parse("12"));
+        let mut loaded = GameVars::default();
+        for line in vars.to_lines() {
+            assert!(loaded.read_line(&line), "{}", line);
+        }
+        assert_eq!(loaded, vars);
+        assert_eq!(loaded.get("motto"), Some(&Value::Str("12: a string, with colons".to_string())));
+        assert!(!loaded.read_line("broken") && !loaded.read_line("x:i:many"));
+    }
+}
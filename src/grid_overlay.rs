@@ -0,0 +1,77 @@
+//! Grid debug overlay (F2): tile grid lines and coordinates over the room, with the tile the
+//! player faces (the one Z acts on) highlighted, for laying out rooms and chasing interaction
+//! bugs. Hovering the mouse over a tile shows what the room knows about it.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect, Text, TextFragment};
+
+use crate::map::{Map, TILE_SIZE};
+use crate::player::Player;
+
+/// Grid lines for a room `width` x `height` tiles, as world-pixel end points: the columns,
+/// then the rows.
+fn lines(width: usize, height: usize) -> Vec<[[f32; 2]; 2]> {
+    let (w, h) = (width as f32 * TILE_SIZE, height as f32 * TILE_SIZE);
+    let columns = (0..=width).map(|x| [[x as f32 * TILE_SIZE, 0.0], [x as f32 * TILE_SIZE, h]]);
+    let rows = (0..=height).map(|y| [[0.0, y as f32 * TILE_SIZE], [w, y as f32 * TILE_SIZE]]);
+    columns.chain(rows).collect()
+}
+
+pub fn draw(ctx: &mut Context, canvas: &mut Canvas, map: &Map, player: &Player, scale: f32, offset: (f32, f32)) -> GameResult {
+    let width = (map.width_pixels() as f32 / TILE_SIZE) as usize;
+    let height = (map.height_pixels() as f32 / TILE_SIZE) as usize;
+    let on_screen = |[x, y]: [f32; 2]| [offset.0 + x * scale, offset.1 + y * scale];
+
+    let mut mesh = MeshBuilder::new();
+    let (fx, fy) = player.facing_tile();
+    let faced = Rect::new(offset.0 + fx as f32 * TILE_SIZE * scale, offset.1 + fy as f32 * TILE_SIZE * scale, TILE_SIZE * scale, TILE_SIZE * scale);
+    mesh.rectangle(DrawMode::fill(), faced, Color::new(1.0, 0.9, 0.2, 0.3))?;
+    mesh.rectangle(DrawMode::stroke(2.0), faced, Color::new(1.0, 0.9, 0.2, 1.0))?;
+    for [a, b] in lines(width, height) {
+        mesh.line(&[on_screen(a), on_screen(b)], 1.0, Color::new(1.0, 1.0, 1.0, 0.25))?;
+    }
+    canvas.draw(&Mesh::from_data(ctx, mesh.build()), DrawParam::new());
+
+    // small enough to fit "12,10" inside a tile
+    let text_size = (TILE_SIZE * scale * 0.28).clamp(7.0, 14.0);
+    for y in 0..height {
+        for x in 0..width {
+            let color = if (x as i32, y as i32) == (fx, fy) { Color::YELLOW } else { Color::new(1.0, 1.0, 1.0, 0.6) };
+            let label = Text::new(TextFragment::new(format!("{},{}", x, y)).scale(text_size));
+            canvas.draw(&label, DrawParam::new().dest(on_screen([x as f32 * TILE_SIZE + 2.0, y as f32 * TILE_SIZE + 1.0])).color(color));
+        }
+    }
+    let (tx, ty) = player.tile();
+    let status = Text::new(TextFragment::new(format!("standing on {},{}  facing {},{}", tx, ty, fx, fy)).scale(16.0));
+    canvas.draw(&status, DrawParam::new().dest([offset.0.max(0.0) + 8.0, offset.1.max(0.0) + 8.0]).color(Color::YELLOW));
+    Ok(())
+}
+
+/// Tooltip by the mouse at `mouse` (window pixels) describing `tile`; nothing outside the room.
+pub fn draw_tooltip(ctx: &mut Context, canvas: &mut Canvas, mouse: (f32, f32), tile: (i32, i32), lines: &[String]) -> GameResult {
+    if lines.is_empty() { return Ok(()); }
+    let text = Text::new(TextFragment::new(format!("{},{}\n{}", tile.0, tile.1, lines.join("\n"))).scale(14.0));
+    let size = text.measure(ctx)?;
+    let window = ctx.gfx.window().inner_size();
+    // beside the cursor, flipped to the other side near the window's right and bottom edges
+    let x = if mouse.0 + 16.0 + size.x + 12.0 > window.width as f32 { mouse.0 - size.x - 20.0 } else { mouse.0 + 16.0 };
+    let y = if mouse.1 + 16.0 + size.y + 12.0 > window.height as f32 { mouse.1 - size.y - 20.0 } else { mouse.1 + 16.0 };
+    let rect = Rect::new(x, y, size.x + 12.0, size.y + 10.0);
+    let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.0, 0.8))?;
+    canvas.draw(&bg, DrawParam::new());
+    canvas.draw(&text, DrawParam::new().dest([x + 6.0, y + 5.0]).color(Color::WHITE));
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_line_along_every_tile_edge() {
+        let lines = lines(3, 2);
+        assert_eq!(lines.len(), 4 + 3);
+        assert_eq!(lines[3], [[3.0 * TILE_SIZE, 0.0], [3.0 * TILE_SIZE, 2.0 * TILE_SIZE]], "the right-hand edge");
+        assert_eq!(lines[6], [[0.0, 2.0 * TILE_SIZE], [3.0 * TILE_SIZE, 2.0 * TILE_SIZE]], "the bottom edge");
+    }
+}
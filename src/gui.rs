@@ -1,20 +1,112 @@
+use std::time::Instant;
+
 use ggez::{Context, GameResult};
-use ggez::graphics::{Canvas, Color, Text, TextFragment, PxScale, DrawParam};
+use ggez::graphics::{Canvas, Color, DrawMode, Mesh, Rect, Text, TextFragment, PxScale, DrawParam};
 use ggez::mint::Point2;
 
+use crate::options::Border;
+use crate::perf::{Phase, PerfOverlay};
+
+/// The window's clear colour, behind everything.
+pub const BACKGROUND: Color = Color::new(0.1, 0.2, 0.3, 1.0);
+
 /// Thin GUI layer: small helper functions that render the map, entities, and a debug overlay.
-pub fn draw_playing(ctx: &mut Context, canvas: &mut Canvas, map: &crate::map::Map, player: &crate::player::Player, enemies: &Vec<crate::enemy::Enemy>, assets: &crate::assets::Assets, scale: f32, offset: (f32, f32)) -> GameResult {
+/// The HUD is laid out inside `area`, the part of the window the game is shown in.
+#[allow(clippy::too_many_arguments)]
+pub fn draw_playing(ctx: &mut Context, canvas: &mut Canvas, map: &crate::map::Map, player: &crate::player::Player, enemies: &Vec<crate::enemy::Enemy>, npcs: &[crate::npc::Npc], pickups: &[crate::pickup::Pickup], projectiles: &[crate::projectile::Projectile], splashes: &crate::splash::Splashes, companion: Option<&crate::companion::Companion>, party: &crate::party::Party, explored: &crate::exploration::Exploration, calendar: &crate::calendar::Calendar, assets: &crate::assets::Assets, scale: f32, offset: (f32, f32), area: Rect, border: Border, perf: &mut PerfOverlay) -> GameResult {
     // delegate main world rendering
+    let started = Instant::now();
     map.draw(ctx, canvas, assets, scale, offset)?;
+    perf.measure(Phase::Room, started);
+    let started = Instant::now();
+    splashes.draw_scaled(ctx, canvas, scale, offset)?;
+
+    for npc in npcs {
+        npc.draw_scaled(ctx, canvas, assets, scale, offset)?;
+    }
+    for pickup in pickups {
+        pickup.draw_scaled(ctx, canvas, scale, offset)?;
+    }
+    if let Some(companion) = companion {
+        companion.draw_scaled(ctx, canvas, assets, scale, offset)?;
+    }
+    for ally in party.allies.iter().rev() {
+        ally.draw_scaled(ctx, canvas, assets, scale, offset)?;
+    }
 
     // draw player and enemies
     player.draw_scaled(ctx, canvas, assets, scale, offset, 1.0)?;
     for enemy in enemies {
         enemy.draw_scaled(ctx, canvas, assets, scale, offset, 1.0)?;
     }
+    for projectile in projectiles {
+        projectile.draw_scaled(ctx, canvas, scale, offset)?;
+    }
+    if crate::palette::high_contrast() {
+        draw_outline(ctx, canvas, player.get_position(), Color::WHITE, scale, offset)?;
+        for enemy in enemies {
+            draw_outline(ctx, canvas, enemy.get_position(), Color::new(1.0, 0.3, 0.3, 1.0), scale, offset)?;
+        }
+    }
+    perf.measure(Phase::Entities, started);
+    // darkness and fog belong to the room
+    let started = Instant::now();
+    map.draw_lighting(ctx, canvas, player.light(), scale, offset)?;
+    let size = (map.width_pixels() / crate::map::TILE_SIZE as usize, map.height_pixels() / crate::map::TILE_SIZE as usize);
+    explored.draw(ctx, canvas, map.current_room(), size, scale, offset)?;
+    perf.measure(Phase::Room, started);
+    if player.level_up_timer > 0.0 {
+        draw_level_up(ctx, canvas, player, scale, offset)?;
+    }
 
     // debug overlay
-    draw_overlay(ctx, canvas, player, map, assets, scale, offset)?;
+    draw_bars(ctx, canvas, area, border, assets, scale)?;
+    draw_overlay(ctx, canvas, player, calendar, area)?;
+    draw_party(ctx, canvas, party, area)?;
+    Ok(())
+}
+
+/// Cover the window outside `area`, where the world may spill over, with the chosen border: a
+/// flat colour, or a dimmed tile tiled at the world's scale.
+fn draw_bars(ctx: &mut Context, canvas: &mut Canvas, area: Rect, border: Border, assets: &crate::assets::Assets, scale: f32) -> GameResult {
+    let size = ctx.gfx.window().inner_size();
+    let (w, h) = (size.width as f32, size.height as f32);
+    let bars = [
+        Rect::new(0.0, 0.0, area.x, h),
+        Rect::new(area.right(), 0.0, w - area.right(), h),
+        Rect::new(0.0, 0.0, w, area.y),
+        Rect::new(0.0, area.bottom(), w, h - area.bottom()),
+    ];
+    let fill = match border {
+        Border::Blue => Ok(BACKGROUND),
+        Border::Black => Ok(Color::BLACK),
+        Border::Grey => Ok(Color::new(0.25, 0.25, 0.27, 1.0)),
+        Border::Planks => Err(&assets.plank),
+        Border::Stone => Err(&assets.wall),
+    };
+    // whole pixels only: the scissor rectangle rounds anything else down
+    for bar in bars.into_iter().filter(|bar| bar.w >= 1.0 && bar.h >= 1.0) {
+        match fill {
+            Ok(color) => {
+                let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), bar, color)?;
+                canvas.draw(&mesh, DrawParam::new());
+            }
+            Err(tile) => {
+                let step = tile.width() as f32 * scale.max(1.0);
+                canvas.set_scissor_rect(bar)?;
+                let (mut y, right, bottom) = ((bar.y / step).floor() * step, bar.right(), bar.bottom());
+                while y < bottom {
+                    let mut x = (bar.x / step).floor() * step;
+                    while x < right {
+                        canvas.draw(tile, DrawParam::new().dest([x, y]).scale([step / tile.width() as f32, step / tile.height() as f32]).color(Color::new(0.55, 0.55, 0.6, 1.0)));
+                        x += step;
+                    }
+                    y += step;
+                }
+                canvas.set_default_scissor_rect();
+            }
+        }
+    }
     Ok(())
 }
 
@@ -28,18 +120,197 @@ pub fn draw_intro(ctx: &mut Context, canvas: &mut Canvas, intro: &crate::intro::
     Ok(())
 }
 
-fn draw_overlay(_ctx: &mut Context, canvas: &mut Canvas, player: &crate::player::Player, _map: &crate::map::Map, _assets: &crate::assets::Assets, _scale: f32, _offset: (f32, f32)) -> GameResult {
+/// Level-up celebration: a golden ring expanding from the player with a floating banner.
+fn draw_level_up(ctx: &mut Context, canvas: &mut Canvas, player: &crate::player::Player, scale: f32, offset: (f32, f32)) -> GameResult {
+    use crate::map::TILE_SIZE;
+    let progress = 1.0 - player.level_up_timer / crate::player::LEVEL_UP_EFFECT_SECS;
+    let pos = player.get_position();
+    let cx = offset.0 + (pos.x + TILE_SIZE / 2.0) * scale;
+    let cy = offset.1 + (pos.y + TILE_SIZE / 2.0) * scale;
+    let alpha = 1.0 - progress;
+    let radius = TILE_SIZE * scale * (0.6 + progress * 1.4);
+    let ring = Mesh::new_circle(ctx, DrawMode::stroke(3.0 * scale), [cx, cy], radius, 0.5, Color::new(1.0, 0.85, 0.2, alpha))?;
+    canvas.draw(&ring, DrawParam::new());
+    let banner = Text::new(TextFragment::new(format!("LEVEL UP! Lv {}", player.stats.level)).scale(PxScale::from(18.0)));
+    let rise = progress * TILE_SIZE * scale;
+    canvas.draw(&banner, DrawParam::new().dest([cx, cy - TILE_SIZE * scale - rise]).offset([0.5, 0.5]).color(Color::new(1.0, 0.9, 0.3, alpha)));
+    Ok(())
+}
+
+/// Short message box at the bottom of the screen (saves, level-ups, ...).
+pub fn draw_notice(ctx: &mut Context, canvas: &mut Canvas, text: &str, area: Rect) -> GameResult {
+    let rect = Rect::new(area.x + 40.0, area.bottom() - 70.0, area.w - 80.0, 44.0);
+    let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.9))?;
+    canvas.draw(&bg, DrawParam::new());
+    let border = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), rect, Color::WHITE)?;
+    canvas.draw(&border, DrawParam::new());
+    let txt = Text::new(TextFragment::new(text).scale(PxScale::from(20.0)));
+    canvas.draw(&txt, DrawParam::new().dest([rect.x + 14.0, rect.y + 11.0]).color(Color::WHITE));
+    Ok(())
+}
+
+/// Full-screen white flash (teleporting), `alpha` from 0 to 1.
+pub fn draw_flash(ctx: &mut Context, canvas: &mut Canvas, alpha: f32) -> GameResult {
+    let size = ctx.gfx.window().inner_size();
+    let rect = ggez::graphics::Rect::new(0.0, 0.0, size.width as f32, size.height as f32);
+    let flash = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(1.0, 1.0, 1.0, alpha))?;
+    canvas.draw(&flash, DrawParam::new());
+    Ok(())
+}
+
+/// Name of the area just entered, across the top of the screen; `alpha` fades it in and out.
+pub fn draw_room_banner(ctx: &mut Context, canvas: &mut Canvas, name: &str, alpha: f32, area: Rect) -> GameResult {
+    let centre = area.x + area.w / 2.0;
+    let txt = Text::new(TextFragment::new(name).scale(PxScale::from(26.0)));
+    let text_w = txt.measure(ctx)?.x;
+    let rect = Rect::new(centre - text_w / 2.0 - 30.0, area.y + 24.0, text_w + 60.0, 46.0);
+    let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.0, 0.6 * alpha))?;
+    canvas.draw(&bg, DrawParam::new());
+    let line = Mesh::new_line(ctx, &[[rect.x + 10.0, rect.bottom() - 6.0], [rect.right() - 10.0, rect.bottom() - 6.0]], 1.5, Color::new(1.0, 0.85, 0.4, alpha))?;
+    canvas.draw(&line, DrawParam::new());
+    canvas.draw(&txt, DrawParam::new().dest([centre, rect.y + 9.0]).offset([0.5, 0.0]).color(Color::new(1.0, 1.0, 1.0, alpha)));
+    Ok(())
+}
+
+/// Arena wave and clock, in the top-right corner.
+pub fn draw_arena(ctx: &mut Context, canvas: &mut Canvas, text: &str, area: Rect) -> GameResult {
+    let txt = Text::new(TextFragment::new(text).scale(PxScale::from(20.0)));
+    let text_w = txt.measure(ctx)?.x;
+    let rect = Rect::new(area.right() - text_w - 36.0, area.y + 12.0, text_w + 24.0, 32.0);
+    let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.3, 0.0, 0.0, 0.75))?;
+    canvas.draw(&bg, DrawParam::new());
+    canvas.draw(&txt, DrawParam::new().dest([rect.x + 12.0, rect.y + 6.0]).color(Color::new(1.0, 0.85, 0.4, 1.0)));
+    Ok(())
+}
+
+/// Dim the screen while the window is in the background.
+pub fn draw_unfocused(ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+    let size = ctx.gfx.window().inner_size();
+    let (w, h) = (size.width as f32, size.height as f32);
+    let shade = Mesh::new_rectangle(ctx, DrawMode::fill(), ggez::graphics::Rect::new(0.0, 0.0, w, h), Color::new(0.0, 0.0, 0.0, 0.5))?;
+    canvas.draw(&shade, DrawParam::new());
+    let txt = Text::new(TextFragment::new("Paused").scale(PxScale::from(36.0)));
+    canvas.draw(&txt, DrawParam::new().dest([w / 2.0, h / 2.0]).offset([0.5, 0.5]).color(Color::WHITE));
+    Ok(())
+}
+
+fn draw_overlay(_ctx: &mut Context, canvas: &mut Canvas, player: &crate::player::Player, calendar: &crate::calendar::Calendar, area: Rect) -> GameResult {
     // small debug HUD in the top-left
     let pos = player.get_position();
     let tile_x = (pos.x / crate::map::TILE_SIZE) as i32;
     let tile_y = (pos.y / crate::map::TILE_SIZE) as i32;
 
     let mut txt = Text::new("");
-    txt.add(TextFragment::new(format!("State: Playing\n")).scale(PxScale::from(14.0)));
+    txt.add(TextFragment::new("State: Playing\n").scale(PxScale::from(14.0)));
     txt.add(TextFragment::new(format!("Player: {:.1},{:.1}\n", pos.x, pos.y)).scale(PxScale::from(14.0)));
     txt.add(TextFragment::new(format!("Tile: {},{}\n", tile_x, tile_y)).scale(PxScale::from(14.0)));
-    let dest = Point2 { x: 8.0, y: 8.0 };
+    let stats = &player.stats;
+    txt.add(TextFragment::new(format!("Lv {}  HP {}/{}  MP {}/{}  XP {}/{}\n", stats.level, stats.hp, stats.max_hp, stats.mp, stats.max_mp, stats.xp, stats.xp_to_next())).scale(PxScale::from(14.0)));
+    txt.add(TextFragment::new(format!("Gold {}   {}\n", player.gold, calendar.label())).scale(PxScale::from(14.0)));
+    let (x, y) = (area.x, area.y);
+    let dest = Point2 { x: x + 8.0, y: y + 8.0 };
     canvas.draw(&txt, DrawParam::new().dest(dest).color(Color::new(1.0,1.0,1.0,0.85)));
 
+    let hp_frac = stats.hp as f32 / stats.max_hp.max(1) as f32;
+    let mp_frac = stats.mp as f32 / stats.max_mp.max(1) as f32;
+    draw_bar(_ctx, canvas, Point2 { x: x + 8.0, y: y + 100.0 }, hp_frac, crate::palette::hp_bar(hp_frac))?;
+    draw_bar(_ctx, canvas, Point2 { x: x + 8.0, y: y + 114.0 }, mp_frac, crate::palette::mp_bar())?;
+    draw_status_icons(_ctx, canvas, &player.status, Point2 { x: x + 8.0, y: y + 132.0 })?;
+    draw_hotbar(_ctx, canvas, &player.skills, area)?;
+
+    Ok(())
+}
+
+/// Each ally's name, level and HP under the player's own bars.
+fn draw_party(ctx: &mut Context, canvas: &mut Canvas, party: &crate::party::Party, area: Rect) -> GameResult {
+    let x = area.x + 8.0;
+    for (i, ally) in party.allies.iter().enumerate() {
+        let y = area.y + 162.0 + i as f32 * 30.0;
+        let stats = &ally.stats;
+        let label = if ally.is_down() { format!("{}  Lv {}  DOWN", ally.name, stats.level) } else { format!("{}  Lv {}  HP {}/{}", ally.name, stats.level, stats.hp, stats.max_hp) };
+        let txt = Text::new(TextFragment::new(label).scale(PxScale::from(13.0)));
+        canvas.draw(&txt, DrawParam::new().dest([x, y]).color(Color::new(1.0, 1.0, 1.0, 0.85)));
+        let hp_frac = stats.hp as f32 / stats.max_hp.max(1) as f32;
+        draw_bar(ctx, canvas, Point2 { x, y: y + 15.0 }, hp_frac, crate::palette::hp_bar(hp_frac))?;
+    }
+    Ok(())
+}
+
+/// Thin HUD bar filled to `fraction`.
+fn draw_bar(ctx: &mut Context, canvas: &mut Canvas, origin: Point2<f32>, fraction: f32, fill: Color) -> GameResult {
+    let (w, h) = (160.0, 10.0);
+    let back = Mesh::new_rectangle(ctx, DrawMode::fill(), ggez::graphics::Rect::new(origin.x, origin.y, w, h), Color::new(0.0, 0.0, 0.0, 0.6))?;
+    canvas.draw(&back, DrawParam::new());
+    let filled = w * fraction.clamp(0.0, 1.0);
+    if filled > 0.0 {
+        let bar = Mesh::new_rectangle(ctx, DrawMode::fill(), ggez::graphics::Rect::new(origin.x, origin.y, filled, h), fill)?;
+        canvas.draw(&bar, DrawParam::new());
+    }
+    let border = Mesh::new_rectangle(ctx, DrawMode::stroke(1.0), ggez::graphics::Rect::new(origin.x, origin.y, w, h), Color::WHITE)?;
+    canvas.draw(&border, DrawParam::new());
+    Ok(())
+}
+
+/// High-contrast mode: a black ring with a coloured ring inside around a character's tile.
+fn draw_outline(ctx: &mut Context, canvas: &mut Canvas, pos: nalgebra::Point2<f32>, color: Color, scale: f32, offset: (f32, f32)) -> GameResult {
+    use crate::map::TILE_SIZE;
+    let cx = offset.0 + (pos.x + TILE_SIZE / 2.0) * scale;
+    let cy = offset.1 + (pos.y + TILE_SIZE / 2.0) * scale;
+    let r = TILE_SIZE * 0.55 * scale;
+    let outer = Mesh::new_circle(ctx, DrawMode::stroke(4.0 * scale), [cx, cy], r, 0.5, Color::BLACK)?;
+    canvas.draw(&outer, DrawParam::new());
+    let inner = Mesh::new_circle(ctx, DrawMode::stroke(2.0 * scale), [cx, cy], r - 1.0 * scale, 0.5, color)?;
+    canvas.draw(&inner, DrawParam::new());
+    Ok(())
+}
+
+/// One coloured badge per active status effect: initial letter plus seconds remaining.
+fn draw_status_icons(ctx: &mut Context, canvas: &mut Canvas, status: &crate::status::StatusEffects, origin: Point2<f32>) -> GameResult {
+    for (i, effect) in status.active.iter().enumerate() {
+        let x = origin.x + i as f32 * 52.0;
+        let rect = ggez::graphics::Rect::new(x, origin.y, 22.0, 22.0);
+        let badge = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, effect.kind.color())?;
+        canvas.draw(&badge, DrawParam::new());
+        let border = Mesh::new_rectangle(ctx, DrawMode::stroke(1.5), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+        let letter = &effect.kind.name()[..1];
+        let txt = Text::new(TextFragment::new(letter).scale(PxScale::from(16.0)));
+        canvas.draw(&txt, DrawParam::new().dest([x + 6.0, origin.y + 3.0]).color(Color::WHITE));
+        let secs = Text::new(TextFragment::new(format!("{:.0}", effect.remaining.ceil())).scale(PxScale::from(13.0)));
+        canvas.draw(&secs, DrawParam::new().dest([x + 25.0, origin.y + 5.0]).color(Color::new(1.0, 1.0, 1.0, 0.85)));
+    }
+    Ok(())
+}
+
+/// Skill hotbar along the bottom edge: key number, skill name, MP cost and a cooldown shade.
+fn draw_hotbar(ctx: &mut Context, canvas: &mut Canvas, book: &crate::skills::SkillBook, area: Rect) -> GameResult {
+    let slot_w = 96.0;
+    let slot_h = 48.0;
+    let left = area.x + (area.w - slot_w * crate::skills::HOTBAR_SLOTS as f32) / 2.0;
+    // sit above the notice box
+    let top = area.bottom() - slot_h - 84.0;
+    for (i, slot) in book.hotbar.iter().enumerate() {
+        let rect = ggez::graphics::Rect::new(left + i as f32 * slot_w + 2.0, top, slot_w - 4.0, slot_h);
+        let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.8))?;
+        canvas.draw(&bg, DrawParam::new());
+        let key = Text::new(TextFragment::new(format!("{}", i + 1)).scale(PxScale::from(12.0)));
+        canvas.draw(&key, DrawParam::new().dest([rect.x + 4.0, rect.y + 2.0]).color(Color::new(1.0, 1.0, 0.6, 1.0)));
+        if let Some(def) = slot.and_then(crate::skills::find) {
+            let name = Text::new(TextFragment::new(def.name).scale(PxScale::from(14.0)));
+            canvas.draw(&name, DrawParam::new().dest([rect.x + 6.0, rect.y + 16.0]).color(Color::WHITE));
+            let cost = Text::new(TextFragment::new(format!("{} MP", def.mp_cost)).scale(PxScale::from(11.0)));
+            canvas.draw(&cost, DrawParam::new().dest([rect.x + 6.0, rect.y + 33.0]).color(Color::new(0.6, 0.8, 1.0, 1.0)));
+            // shade the slot from the bottom up while it cools down
+            let left_secs = book.cooldown_left(def.id);
+            if left_secs > 0.0 {
+                let frac = (left_secs / def.cooldown).min(1.0);
+                let shade = ggez::graphics::Rect::new(rect.x, rect.y + rect.h * (1.0 - frac), rect.w, rect.h * frac);
+                let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), shade, Color::new(0.0, 0.0, 0.0, 0.6))?;
+                canvas.draw(&mesh, DrawParam::new());
+            }
+        }
+        let border = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+    }
     Ok(())
 }
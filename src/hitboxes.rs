@@ -0,0 +1,98 @@
+//! Collision debug overlay (F4): what the map and the player actually collide with, drawn as
+//! translucent boxes over the world.
+//!
+//! Solid ground is found by asking the room's own collision test about small cells, so door
+//! frames show up as the thin jambs they really are. Also shown: tiles blocked by NPCs, the
+//! walkable cells of props like the bed (with the sides they can be stepped on or off across),
+//! the player's shrunken hitbox and each enemy's contact box.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
+
+use crate::enemy::{self, Enemy};
+use crate::map::{Map, TILE_SIZE};
+use crate::player::{self, Player};
+use crate::props::Side;
+
+/// Size of the cells solidity is sampled at, in world pixels.
+const CELL: f32 = 4.0;
+
+/// Solid parts of a `width` x `height` (world pixels) area as rectangles, one per run of
+/// solid cells along a row.
+fn solid_runs(width: f32, height: f32, solid: impl Fn(f32, f32) -> bool) -> Vec<Rect> {
+    let mut runs = Vec::new();
+    let mut y = 0.0;
+    while y < height {
+        let mut start = None;
+        let mut x = 0.0;
+        while x <= width {
+            match (x < width && solid(x, y), start) {
+                (true, None) => start = Some(x),
+                (false, Some(from)) => {
+                    runs.push(Rect::new(from, y, x - from, CELL));
+                    start = None;
+                }
+                _ => {}
+            }
+            x += CELL;
+        }
+        y += CELL;
+    }
+    runs
+}
+
+pub fn draw(ctx: &mut Context, canvas: &mut Canvas, map: &Map, player: &Player, enemies: &[Enemy], scale: f32, offset: (f32, f32)) -> GameResult {
+    let on_screen = |r: Rect| Rect::new(offset.0 + r.x * scale, offset.1 + r.y * scale, r.w * scale, r.h * scale);
+    let tile_rect = |(x, y): (f32, f32)| Rect::new(x * TILE_SIZE, y * TILE_SIZE, TILE_SIZE, TILE_SIZE);
+    let mut mesh = MeshBuilder::new();
+
+    let (width, height) = (map.width_pixels() as f32, map.height_pixels() as f32);
+    // a cell counts as solid if anything in it blocks, so shave a hair off to stay inside it
+    for run in solid_runs(width, height, |x, y| !map.is_rect_free(x, y, CELL - 0.01, CELL - 0.01)) {
+        mesh.rectangle(DrawMode::fill(), on_screen(run), Color::new(1.0, 0.1, 0.1, 0.35))?;
+    }
+    for &(x, y) in map.blocked_tiles() {
+        mesh.rectangle(DrawMode::fill(), on_screen(tile_rect((x as f32, y as f32))), Color::new(1.0, 0.55, 0.0, 0.35))?;
+    }
+    for ((x, y), sides) in map.prop_cells() {
+        let cell = on_screen(tile_rect((x as f32, y as f32)));
+        mesh.rectangle(DrawMode::fill(), cell, Color::new(0.2, 0.4, 1.0, 0.3))?;
+        // entry edges in green
+        for side in sides {
+            let (a, b) = match side {
+                Side::North => ([cell.x, cell.y], [cell.right(), cell.y]),
+                Side::South => ([cell.x, cell.bottom()], [cell.right(), cell.bottom()]),
+                Side::East => ([cell.right(), cell.y], [cell.right(), cell.bottom()]),
+                Side::West => ([cell.x, cell.y], [cell.x, cell.bottom()]),
+            };
+            mesh.line(&[a, b], 3.0, Color::new(0.2, 1.0, 0.3, 0.9))?;
+        }
+    }
+
+    let centred = |at: (f32, f32), size: f32| Rect::new(at.0 + (TILE_SIZE - size) / 2.0, at.1 + (TILE_SIZE - size) / 2.0, size, size);
+    let at = player.get_position();
+    let hitbox = on_screen(centred((at.x, at.y), player::HITBOX_SIZE));
+    mesh.rectangle(DrawMode::fill(), hitbox, Color::new(0.2, 1.0, 0.3, 0.35))?;
+    mesh.rectangle(DrawMode::stroke(1.5), hitbox, Color::new(0.2, 1.0, 0.3, 1.0))?;
+    for enemy in enemies {
+        let at = enemy.get_position();
+        let contact = on_screen(centred((at.x, at.y), enemy::CONTACT_SIZE));
+        mesh.rectangle(DrawMode::fill(), contact, Color::new(1.0, 0.2, 0.9, 0.3))?;
+        mesh.rectangle(DrawMode::stroke(1.5), contact, Color::new(1.0, 0.2, 0.9, 1.0))?;
+    }
+    canvas.draw(&Mesh::from_data(ctx, mesh.build()), DrawParam::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn solid_cells_merge_into_runs_along_each_row() {
+        // a wall along the top row of cells and a post in the second row
+        let runs = solid_runs(5.0 * CELL, 2.0 * CELL, |x, y| y < CELL || (2.0 * CELL..3.0 * CELL).contains(&x));
+        assert_eq!(runs, vec![Rect::new(0.0, 0.0, 5.0 * CELL, CELL), Rect::new(2.0 * CELL, CELL, CELL, CELL)]);
+        assert!(solid_runs(3.0 * CELL, 3.0 * CELL, |_, _| false).is_empty());
+    }
+}
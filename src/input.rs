@@ -0,0 +1,269 @@
+//! Player controls, read from the keyboard and any gamepads once per frame.
+//!
+//! Game logic (player movement, and anything else that reacts to held keys) takes a `Controls`
+//! instead of the ggez `Context`, so it runs the same in tests or a headless runner with no
+//! window behind it.
+//!
+//! Keys with a job of their own across screens are named by an `Action`, and menus check them
+//! with `Action::matches` rather than naming keys. On-screen prompts say `{confirm}` rather than
+//! "Z" and go through `prompt` as they're drawn, so they always show the key that actually does
+//! it, or the gamepad button once a gamepad was the last thing touched. A button press stands
+//! in for a key of its action that no other action has (see `button_key`), so menus need
+//! nothing extra for gamepads.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ggez::Context;
+use ggez::input::gamepad::gilrs::{Axis, Button};
+use ggez::input::keyboard::KeyCode;
+
+/// How far a stick has to lean to count as a direction.
+const STICK_DEADZONE: f32 = 0.5;
+
+/// A gamepad, rather than the keyboard, was used last. Process-wide, like the palette
+/// settings, so every prompt drawn anywhere follows it.
+static PAD_IN_USE: AtomicBool = AtomicBool::new(false);
+
+/// Note which device the player last used; prompts switch over on their next draw.
+pub fn set_pad_in_use(pad: bool) {
+    if PAD_IN_USE.swap(pad, Ordering::Relaxed) != pad {
+        println!("Input: prompts now show the {}", if pad { "gamepad" } else { "keyboard" });
+    }
+}
+
+pub fn pad_in_use() -> bool {
+    PAD_IN_USE.load(Ordering::Relaxed)
+}
+
+/// Something the player does with the same key wherever they are.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    /// Interact, pick, go on.
+    Confirm,
+    /// Back out, leave, skip.
+    Cancel,
+    /// Open or close the options.
+    Menu,
+    Inventory,
+    Attack,
+    Throw,
+    Run,
+    /// Held to sneak past enemies (see `crate::stealth`).
+    Sneak,
+    /// Send off what's been put together: a barter offer, a typed seed or name.
+    Submit,
+    /// Start something new in a list: a new game in a save slot, a new profile.
+    New,
+    /// Start today's daily run.
+    Daily,
+    /// Switch ironman on or off for a new game.
+    Ironman,
+    /// Put the bag in its next order.
+    Sort,
+}
+
+impl Action {
+    pub const ALL: [Action; 13] = [Action::Confirm, Action::Cancel, Action::Menu, Action::Inventory, Action::Attack, Action::Throw, Action::Run, Action::Sneak, Action::Submit, Action::New, Action::Daily, Action::Ironman, Action::Sort];
+
+    /// Name used for it in prompt text, e.g. `{confirm}`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Action::Confirm => "confirm",
+            Action::Cancel => "cancel",
+            Action::Menu => "menu",
+            Action::Inventory => "inventory",
+            Action::Attack => "attack",
+            Action::Throw => "throw",
+            Action::Run => "run",
+            Action::Sneak => "sneak",
+            Action::Submit => "submit",
+            Action::New => "new",
+            Action::Daily => "daily",
+            Action::Ironman => "ironman",
+            Action::Sort => "sort",
+        }
+    }
+
+    /// Keys that do it; prompts show the first.
+    pub fn keys(self) -> &'static [KeyCode] {
+        match self {
+            Action::Confirm => &[KeyCode::Z, KeyCode::Return],
+            Action::Cancel => &[KeyCode::Escape, KeyCode::C],
+            Action::Menu => &[KeyCode::X],
+            Action::Inventory => &[KeyCode::I],
+            Action::Attack => &[KeyCode::Space],
+            Action::Throw => &[KeyCode::F],
+            Action::Run => &[KeyCode::LShift, KeyCode::RShift],
+            Action::Sneak => &[KeyCode::LControl, KeyCode::RControl],
+            Action::Submit => &[KeyCode::Return, KeyCode::NumpadEnter],
+            Action::New => &[KeyCode::N],
+            Action::Daily => &[KeyCode::D],
+            Action::Ironman => &[KeyCode::Tab],
+            Action::Sort => &[KeyCode::S],
+        }
+    }
+
+    /// Gamepad button that does it, laid out like an Xbox pad.
+    pub fn button(self) -> Button {
+        match self {
+            Action::Confirm => Button::South,
+            Action::Cancel => Button::East,
+            Action::Menu => Button::Start,
+            Action::Inventory => Button::North,
+            Action::Attack => Button::West,
+            Action::Throw => Button::RightTrigger,
+            Action::Run => Button::RightTrigger2,
+            Action::Sneak => Button::LeftTrigger2,
+            Action::Submit => Button::Select,
+            Action::New => Button::LeftTrigger,
+            Action::Daily => Button::LeftThumb,
+            Action::Ironman => Button::RightThumb,
+            Action::Sort => Button::Mode,
+        }
+    }
+
+    /// The key its button stands in for: the first one no other action has, so a button only
+    /// ever does its own action (Enter both confirms and submits; the pad's Back only submits).
+    fn own_key(self) -> KeyCode {
+        let shared = |key: &KeyCode| Action::ALL.iter().any(|&other| other != self && other.matches(*key));
+        self.keys().iter().copied().find(|key| !shared(key)).unwrap_or(self.keys()[0])
+    }
+
+    pub fn matches(self, code: KeyCode) -> bool {
+        self.keys().contains(&code)
+    }
+
+    /// The key, or the button while a gamepad is in use, as it's written on screen.
+    pub fn glyph(self) -> String {
+        self.glyph_for(pad_in_use())
+    }
+
+    fn glyph_for(self, pad: bool) -> String {
+        if pad {
+            return button_glyph(self.button()).to_string();
+        }
+        match self.keys()[0] {
+            KeyCode::LShift | KeyCode::RShift => "Shift".to_string(),
+            KeyCode::LControl | KeyCode::RControl => "Ctrl".to_string(),
+            KeyCode::Return => "Enter".to_string(),
+            KeyCode::Escape => "Esc".to_string(),
+            other => format!("{:?}", other),
+        }
+    }
+}
+
+fn button_glyph(button: Button) -> &'static str {
+    match button {
+        Button::South => "A",
+        Button::East => "B",
+        Button::North => "Y",
+        Button::West => "X",
+        Button::RightTrigger => "RB",
+        Button::RightTrigger2 => "RT",
+        Button::LeftTrigger => "LB",
+        Button::LeftTrigger2 => "LT",
+        Button::Start => "Start",
+        Button::Select => "Back",
+        Button::LeftThumb => "LS",
+        Button::RightThumb => "RS",
+        Button::Mode => "Guide",
+        _ => "?",
+    }
+}
+
+/// The key a gamepad button press stands in for: the d-pad's arrows, or the button's action's
+/// own key. None for buttons with no job.
+pub fn button_key(button: Button) -> Option<KeyCode> {
+    match button {
+        Button::DPadUp => Some(KeyCode::Up),
+        Button::DPadDown => Some(KeyCode::Down),
+        Button::DPadLeft => Some(KeyCode::Left),
+        Button::DPadRight => Some(KeyCode::Right),
+        _ => Action::ALL.into_iter().find(|action| action.button() == button).map(Action::own_key),
+    }
+}
+
+/// True while `key` is held on the keyboard, or its button (see `button_key`) on any gamepad.
+pub fn is_held(ctx: &Context, key: KeyCode) -> bool {
+    let dpad = [Button::DPadUp, Button::DPadDown, Button::DPadLeft, Button::DPadRight];
+    let buttons: Vec<Button> = dpad.into_iter().chain(Action::ALL.map(Action::button)).filter(|&button| button_key(button) == Some(key)).collect();
+    ctx.keyboard.is_key_pressed(key) || ctx.gamepad.gamepads().any(|(_, pad)| buttons.iter().any(|&button| pad.is_pressed(button)))
+}
+
+/// `text` with each `{action}` replaced by that action's key: "Press {confirm} to continue"
+/// reads "Press Z to continue". `{left_right}` names the arrows (or the d-pad) that change a
+/// setting. Unknown names are left as they are.
+pub fn prompt(text: &str) -> String {
+    prompt_for(text, pad_in_use())
+}
+
+fn prompt_for(text: &str, pad: bool) -> String {
+    let text = text.replace("{left_right}", if pad { "D-pad" } else { "Left/Right" });
+    Action::ALL.iter().fold(text, |text, action| text.replace(&format!("{{{}}}", action.name()), &action.glyph_for(pad)))
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Controls {
+    /// Direction of the movement key being held, if any.
+    pub held: Option<(i32, i32)>,
+    /// Shift is held: run, or walk with auto-run on.
+    pub run: bool,
+    /// Ctrl is held: sneak.
+    pub sneak: bool,
+}
+
+impl Controls {
+    /// Sample the keyboard and gamepads: arrows, WASD, the d-pad or the left stick, first
+    /// match wins.
+    pub fn read(ctx: &Context) -> Controls {
+        // the stick's y axis points up
+        let stick = |axis: Axis, sign: f32| ctx.gamepad.gamepads().any(|(_, pad)| pad.value(axis) * sign > STICK_DEADZONE);
+        let held = |a: KeyCode, b: KeyCode| is_held(ctx, a) || ctx.keyboard.is_key_pressed(b);
+        let held = if held(KeyCode::Left, KeyCode::A) || stick(Axis::LeftStickX, -1.0) {
+            Some((-1, 0))
+        } else if held(KeyCode::Right, KeyCode::D) || stick(Axis::LeftStickX, 1.0) {
+            Some((1, 0))
+        } else if held(KeyCode::Up, KeyCode::W) || stick(Axis::LeftStickY, 1.0) {
+            Some((0, -1))
+        } else if held(KeyCode::Down, KeyCode::S) || stick(Axis::LeftStickY, -1.0) {
+            Some((0, 1))
+        } else {
+            None
+        };
+        let pressed = |action: Action| action.keys().iter().any(|&key| is_held(ctx, key));
+        Controls { held, run: pressed(Action::Run), sneak: pressed(Action::Sneak) }
+    }
+
+    /// Controls with `dir` held, for scripted input.
+    #[cfg(test)]
+    pub fn holding(dir: (i32, i32)) -> Controls {
+        Controls { held: Some(dir), ..Controls::default() }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prompts_name_the_bound_keys() {
+        assert_eq!(prompt("Press {confirm} to continue"), "Press Z to continue");
+        assert_eq!(prompt("{attack}: hit   {run}: hurry   {warp}"), "Space: hit   Shift: hurry   {warp}");
+        assert!(Action::Run.matches(KeyCode::RShift) && !Action::Menu.matches(KeyCode::Z));
+
+        // buttons stand in for their action's key, and prompts follow the device in use
+        assert_eq!(button_key(Button::South), Some(KeyCode::Z));
+        assert_eq!(button_key(Button::DPadDown), Some(KeyCode::Down));
+        assert_eq!(button_key(Button::C), None);
+        assert_eq!(prompt_for("{confirm}: pick   {submit}: offer", true), "A: pick   Back: offer");
+        assert_eq!(prompt_for("{submit}", false), "Enter");
+        assert_eq!(prompt_for("{left_right}: foes   {new}: new game   {daily}: daily run", true), "D-pad: foes   LB: new game   LS: daily run");
+
+        // each button does only its own action, even where actions share a key
+        for action in Action::ALL {
+            let key = button_key(action.button()).unwrap();
+            assert_eq!(Action::ALL.into_iter().filter(|other| other.matches(key)).collect::<Vec<_>>(), vec![action]);
+        }
+        assert_eq!(button_key(Button::Select), Some(KeyCode::NumpadEnter));
+    }
+}
@@ -0,0 +1,244 @@
+//! Debug entity inspector (F3): everything alive in the room, with its position, state, HP and
+//! what its AI is doing, refreshed every frame.
+//!
+//! Clicking a row, or an entity out in the world, selects it and outlines it in the world;
+//! clicking empty ground clears the selection. While the panel is open, clicks don't walk.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text, TextFragment};
+
+use crate::companion::Companion;
+use crate::enemy::Enemy;
+use crate::map::TILE_SIZE;
+use crate::npc::{Npc, NpcRole};
+use crate::party::Party;
+use crate::pickup::{Loot, Pickup};
+use crate::player::Player;
+use crate::projectile::{Owner, Projectile};
+
+const PANEL_W: f32 = 340.0;
+const ROW_H: f32 = 32.0;
+const TITLE_H: f32 = 28.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    Player,
+    Companion,
+    Ally,
+    Enemy,
+    Npc,
+    Pickup,
+    Projectile,
+}
+
+pub struct Entry {
+    /// Which list it's in and where. Only good until that list changes length: see
+    /// `Inspector::follow`.
+    pub id: (Kind, usize),
+    pub name: String,
+    /// World pixels to outline when selected.
+    pub bounds: Rect,
+    /// Current and max HP, for things that have them.
+    pub hp: Option<(i32, Option<i32>)>,
+    pub state: String,
+    pub ai: String,
+}
+
+impl Entry {
+    pub fn tile(&self) -> (i32, i32) {
+        ((self.bounds.x / TILE_SIZE).floor() as i32, (self.bounds.y / TILE_SIZE).floor() as i32)
+    }
+}
+
+fn tile_bounds(x: f32, y: f32) -> Rect {
+    Rect::new(x, y, TILE_SIZE, TILE_SIZE)
+}
+
+/// Everything worth inspecting in the room, player first.
+pub fn entries(player: &Player, companion: Option<&Companion>, party: &Party, enemies: &[Enemy], npcs: &[Npc], pickups: &[Pickup], projectiles: &[Projectile]) -> Vec<Entry> {
+    let mut list = Vec::new();
+    let at = player.get_position();
+    let state = if player.sitting { "sitting" } else if player.attack_timer > 0.0 { "attacking" } else if player.moving { "moving" } else { "idle" };
+    let ai = if player.path_left() > 0 { format!("walking a click, {} tiles left", player.path_left()) } else { "player input".to_string() };
+    list.push(Entry { id: (Kind::Player, 0), name: "Player".to_string(), bounds: tile_bounds(at.x, at.y), hp: Some((player.stats.hp, Some(player.stats.max_hp))), state: state.to_string(), ai });
+    if let Some(companion) = companion {
+        let (x, y) = companion.tile();
+        let state = if companion.is_following() { "following" } else { "waiting by the fire" };
+        let ai = if companion.nip_cooldown() > 0.0 { format!("nip in {:.1}s", companion.nip_cooldown()) } else { "ready to nip".to_string() };
+        list.push(Entry { id: (Kind::Companion, 0), name: crate::companion::COMPANION_NAME.to_string(), bounds: tile_bounds(x as f32 * TILE_SIZE, y as f32 * TILE_SIZE), hp: None, state: state.to_string(), ai });
+    }
+    for (i, ally) in party.allies.iter().enumerate() {
+        let at = ally.position();
+        let state = if ally.is_down() { "down" } else { "following" };
+        list.push(Entry { id: (Kind::Ally, i), name: ally.name.clone(), bounds: tile_bounds(at.x, at.y), hp: Some((ally.stats.hp, Some(ally.stats.max_hp))), state: state.to_string(), ai: "trails the party".to_string() });
+    }
+    for (i, enemy) in enemies.iter().enumerate() {
+        let at = enemy.get_position();
+        let state = match (enemy.ranged, enemy.inflicts) {
+            (true, _) => "ranged".to_string(),
+            (false, Some((kind, _))) => format!("inflicts {:?}", kind),
+            (false, None) => "melee".to_string(),
+        };
+        list.push(Entry { id: (Kind::Enemy, i), name: format!("Enemy {}", i), bounds: tile_bounds(at.x, at.y), hp: Some((enemy.hp, None)), state, ai: enemy.ai_state() });
+    }
+    for (i, npc) in npcs.iter().enumerate() {
+        let role = match &npc.role {
+            NpcRole::Merchant(_) => "merchant".to_string(),
+            NpcRole::Recruit { level, .. } => format!("recruit, level {}", level),
+            NpcRole::Trader(_) => "trader".to_string(),
+            NpcRole::ArenaMaster => "arena master".to_string(),
+        };
+        list.push(Entry { id: (Kind::Npc, i), name: npc.name.clone(), bounds: tile_bounds(npc.tile.0 as f32 * TILE_SIZE, npc.tile.1 as f32 * TILE_SIZE), hp: None, state: role, ai: "stands still".to_string() });
+    }
+    for (i, pickup) in pickups.iter().enumerate() {
+        let name = match pickup.loot {
+            Loot::Gold(amount) => format!("{} gold", amount),
+            Loot::Item(id, rarity) => format!("{} ({:?})", id, rarity),
+        };
+        list.push(Entry { id: (Kind::Pickup, i), name, bounds: tile_bounds(pickup.position.x, pickup.position.y), hp: None, state: "on the ground".to_string(), ai: "-".to_string() });
+    }
+    for (i, shot) in projectiles.iter().enumerate() {
+        let half = TILE_SIZE / 4.0;
+        let from = if shot.owner == Owner::Player { "player's shot" } else { "enemy shot" };
+        list.push(Entry { id: (Kind::Projectile, i), name: format!("Projectile {}", i), bounds: Rect::new(shot.position.x - half, shot.position.y - half, half * 2.0, half * 2.0), hp: None, state: format!("{}, {} damage", from, shot.damage), ai: format!("flies for {:.1}s more", shot.lifespan) });
+    }
+    list
+}
+
+#[derive(Default)]
+pub struct Inspector {
+    pub visible: bool,
+    selected: Option<(Kind, usize)>,
+    /// How many entries of the selected kind there were when it was picked.
+    selected_of: usize,
+}
+
+impl Inspector {
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        println!("Inspector: {}", if self.visible { "on" } else { "off" });
+    }
+
+    fn select(&mut self, entries: &[Entry], id: Option<(Kind, usize)>) {
+        self.selected = id;
+        self.selected_of = id.map_or(0, |(kind, _)| entries.iter().filter(|entry| entry.id.0 == kind).count());
+    }
+
+    /// Keep the selection on the entity it was made on: ids are list positions, so once
+    /// something in that list dies or turns up they may point at someone else, and the
+    /// selection is dropped instead.
+    pub fn follow(&mut self, entries: &[Entry]) {
+        let Some((kind, _)) = self.selected else { return };
+        if entries.iter().filter(|entry| entry.id.0 == kind).count() != self.selected_of {
+            self.selected = None;
+        }
+    }
+
+    /// Drop the selection, say when the room changes under it.
+    pub fn deselect(&mut self) {
+        self.selected = None;
+    }
+
+    fn panel(window: (f32, f32)) -> Rect {
+        Rect::new(window.0 - PANEL_W - 10.0, 60.0, PANEL_W, window.1 - 70.0)
+    }
+
+    /// Rows that fit on the panel.
+    fn rows(window: (f32, f32)) -> usize {
+        ((Inspector::panel(window).h - TITLE_H) / ROW_H).max(0.0) as usize
+    }
+
+    /// Handle a click at screen `point`; `view` is the world's scale and offset. Returns false
+    /// when the panel is closed and the click is someone else's.
+    pub fn click(&mut self, entries: &[Entry], point: (f32, f32), window: (f32, f32), view: (f32, (f32, f32))) -> bool {
+        if !self.visible { return false; }
+        let panel = Inspector::panel(window);
+        if panel.contains([point.0, point.1]) {
+            let row = ((point.1 - panel.y - TITLE_H) / ROW_H).floor();
+            if row >= 0.0 && (row as usize) < Inspector::rows(window)
+                && let Some(entry) = entries.get(row as usize) {
+                self.select(entries, Some(entry.id));
+            }
+            return true;
+        }
+        let (scale, (offset_x, offset_y)) = view;
+        let world = ((point.0 - offset_x) / scale, (point.1 - offset_y) / scale);
+        // the last one drawn is on top, and the list is in drawing order closely enough
+        let hit = entries.iter().rev().find(|entry| entry.bounds.contains([world.0, world.1])).map(|entry| entry.id);
+        self.select(entries, hit);
+        true
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, entries: &[Entry], view: (f32, (f32, f32))) -> GameResult {
+        if !self.visible { return Ok(()); }
+        let size = ctx.gfx.window().inner_size();
+        let window = (size.width as f32, size.height as f32);
+        let (scale, (offset_x, offset_y)) = view;
+        if let Some(entry) = entries.iter().find(|entry| Some(entry.id) == self.selected) {
+            let b = entry.bounds;
+            let outline = Rect::new(offset_x + b.x * scale, offset_y + b.y * scale, b.w * scale, b.h * scale);
+            let mesh = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), outline, Color::MAGENTA)?;
+            canvas.draw(&mesh, DrawParam::new());
+        }
+
+        let panel = Inspector::panel(window);
+        let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), panel, Color::new(0.0, 0.0, 0.0, 0.75))?;
+        canvas.draw(&bg, DrawParam::new());
+        let title = Text::new(TextFragment::new(format!("Entities ({})  F3 closes", entries.len())).scale(16.0));
+        canvas.draw(&title, DrawParam::new().dest([panel.x + 8.0, panel.y + 6.0]).color(Color::YELLOW));
+        for (row, entry) in entries.iter().take(Inspector::rows(window)).enumerate() {
+            let y = panel.y + TITLE_H + row as f32 * ROW_H;
+            let picked = Some(entry.id) == self.selected;
+            if picked {
+                let band = Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(panel.x, y, panel.w, ROW_H), Color::new(0.6, 0.0, 0.6, 0.5))?;
+                canvas.draw(&band, DrawParam::new());
+            }
+            let hp = match entry.hp {
+                Some((hp, Some(max))) => format!("  HP {}/{}", hp, max),
+                Some((hp, None)) => format!("  HP {}", hp),
+                None => String::new(),
+            };
+            let line = Text::new(TextFragment::new(format!("{}  {:?}{}  {}\n  {}", entry.name, entry.tile(), hp, entry.state, entry.ai)).scale(13.0));
+            canvas.draw(&line, DrawParam::new().dest([panel.x + 8.0, y + 2.0]).color(if picked { Color::WHITE } else { Color::new(0.85, 0.85, 0.85, 1.0) }));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn lists_the_room_and_selects_by_row_or_by_clicking_in_the_world() {
+        let player = Player::test_new();
+        let enemies = vec![Enemy::at(5.0 * TILE_SIZE, 3.0 * TILE_SIZE).ranged()];
+        let list = entries(&player, None, &Party::default(), &enemies, &[], &[], &[]);
+        assert_eq!(list.iter().map(|e| e.id).collect::<Vec<_>>(), vec![(Kind::Player, 0), (Kind::Enemy, 0)]);
+        assert_eq!(list[1].tile(), (5, 3));
+        assert_eq!(list[1].hp, Some((10, None)));
+        assert!(list[1].ai.contains("shoot") || list[1].ai.contains("reloading"), "{}", list[1].ai);
+
+        let window = (800.0, 600.0);
+        let view = (2.0, (10.0, 20.0));
+        let mut inspector = Inspector::default();
+        assert!(!inspector.click(&list, (0.0, 0.0), window, view), "closed, the click isn't ours");
+        inspector.toggle();
+        // the enemy's tile in the world
+        assert!(inspector.click(&list, (10.0 + 5.5 * TILE_SIZE * 2.0, 20.0 + 3.5 * TILE_SIZE * 2.0), window, view));
+        assert_eq!(inspector.selected, Some((Kind::Enemy, 0)));
+        // a second enemy turning up (or the first dying) moves the ids about, so it's dropped
+        inspector.follow(&list);
+        assert_eq!(inspector.selected, Some((Kind::Enemy, 0)), "nothing changed");
+        let more = vec![Enemy::at(0.0, 0.0), Enemy::at(5.0 * TILE_SIZE, 3.0 * TILE_SIZE)];
+        inspector.follow(&entries(&player, None, &Party::default(), &more, &[], &[], &[]));
+        assert_eq!(inspector.selected, None);
+        inspector.click(&list, (10.0 + 5.5 * TILE_SIZE * 2.0, 20.0 + 3.5 * TILE_SIZE * 2.0), window, view);
+        // the first row of the panel
+        let panel = Inspector::panel(window);
+        inspector.click(&list, (panel.x + 20.0, panel.y + TITLE_H + ROW_H / 2.0), window, view);
+        assert_eq!(inspector.selected, Some((Kind::Player, 0)));
+        inspector.click(&list, (15.0, 25.0), window, view);
+        assert_eq!(inspector.selected, None, "empty ground clears it");
+    }
+}
@@ -1,6 +1,8 @@
 use ggez::{Context, GameResult};
 use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
-use ggez::input::keyboard::{KeyInput, KeyCode};
+use ggez::input::keyboard::KeyInput;
+
+use crate::input::Action;
 
 pub struct Intro {
     pub lines: Vec<String>,
@@ -23,7 +25,7 @@ impl Intro {
         let line = &self.lines[idx];
         let text = Text::new(TextFragment::new(line.clone()).scale(24.0));
         canvas.draw(&text, DrawParam::new().dest([40.0, 40.0]).color(Color::WHITE));
-        let prompt = Text::new(TextFragment::new("Press Z to continue").scale(18.0));
+        let prompt = Text::new(TextFragment::new(crate::input::prompt("Press {confirm} to continue")).scale(18.0));
         canvas.draw(&prompt, DrawParam::new().dest([40.0, h - 60.0]).color(Color::WHITE));
         Ok(())
     }
@@ -43,13 +45,12 @@ impl Intro {
 
     /// Manual advance via key input. Returns true when finished.
     pub fn handle_input(&mut self, input: KeyInput) -> bool {
-        if let Some(k) = input.keycode {
-            if k == KeyCode::Z {
-                self.index += 1;
-                self.timer = 0.0;
-                if self.index >= self.lines.len() {
-                    return true;
-                }
+        if let Some(k) = input.keycode
+            && Action::Confirm.matches(k) {
+            self.index += 1;
+            self.timer = 0.0;
+            if self.index >= self.lines.len() {
+                return true;
             }
         }
         false
@@ -0,0 +1,302 @@
+//! Inventory screen: equipment slots on top, carried items below.
+//!
+//! Up/Down moves the cursor. Z on a slot unequips it, Z on an item equips it (swapping with
+//! whatever was in that slot) or uses it if it's a consumable. Left/Right switches between
+//! category tabs (everything, equipment, consumables, key items) and S cycles the bag's order
+//! (by type, by name, newest first); a long bag scrolls. I or C closes the screen.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+use crate::items::{self, Descriptions, ItemId, ItemKind};
+use crate::item_details::{self, PANEL_GAP, PANEL_W};
+use crate::player::Player;
+
+const SLOT_KINDS: [ItemKind; 3] = [ItemKind::Weapon, ItemKind::Armor, ItemKind::Accessory];
+/// Bag rows shown at once; longer bags scroll.
+const BAG_ROWS: usize = 7;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Tab {
+    All,
+    Equipment,
+    Consumables,
+    KeyItems,
+}
+
+impl Tab {
+    const ALL: [Tab; 4] = [Tab::All, Tab::Equipment, Tab::Consumables, Tab::KeyItems];
+
+    fn label(self) -> &'static str {
+        match self {
+            Tab::All => "All",
+            Tab::Equipment => "Equipment",
+            Tab::Consumables => "Consumables",
+            Tab::KeyItems => "Key Items",
+        }
+    }
+
+    fn holds(self, kind: ItemKind) -> bool {
+        match self {
+            Tab::All => true,
+            Tab::Equipment => SLOT_KINDS.contains(&kind),
+            Tab::Consumables => kind == ItemKind::Consumable,
+            Tab::KeyItems => kind == ItemKind::Key,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Sort {
+    Type,
+    Name,
+    Newest,
+}
+
+impl Sort {
+    fn next(self) -> Sort {
+        match self {
+            Sort::Type => Sort::Name,
+            Sort::Name => Sort::Newest,
+            Sort::Newest => Sort::Type,
+        }
+    }
+
+    fn label(self) -> &'static str {
+        match self {
+            Sort::Type => "type",
+            Sort::Name => "name",
+            Sort::Newest => "newest",
+        }
+    }
+}
+
+/// Where a kind of item sorts when sorting by type.
+fn kind_order(kind: ItemKind) -> usize {
+    [ItemKind::Weapon, ItemKind::Armor, ItemKind::Accessory, ItemKind::Consumable, ItemKind::Key].iter().position(|k| *k == kind).unwrap_or(0)
+}
+
+pub struct InventoryScreen {
+    pub visible: bool,
+    selected: usize,
+    tab: Tab,
+    sort: Sort,
+    /// First bag row shown.
+    scroll: usize,
+}
+
+impl InventoryScreen {
+    pub fn new() -> InventoryScreen {
+        InventoryScreen { visible: false, selected: 0, tab: Tab::All, sort: Sort::Type, scroll: 0 }
+    }
+
+    /// Open or close; the tab and sort order are kept for next time.
+    pub fn toggle(&mut self) {
+        self.visible = !self.visible;
+        self.selected = 0;
+        self.scroll = 0;
+    }
+
+    /// Inventory indices of the items on the current tab, in the current order.
+    fn bag(&self, player: &Player) -> Vec<usize> {
+        let items = &player.inventory.items;
+        let def = |i: usize| items::find(items[i]);
+        let mut bag: Vec<usize> = (0..items.len()).filter(|&i| def(i).is_none_or(|d| self.tab.holds(d.kind))).collect();
+        let name = |i: usize| def(i).map_or(items[i], |d| d.name);
+        match self.sort {
+            Sort::Type => bag.sort_by_key(|&i| (def(i).map_or(0, |d| kind_order(d.kind)), name(i))),
+            Sort::Name => bag.sort_by_key(|&i| name(i)),
+            // items are added to the end of the bag as they're picked up
+            Sort::Newest => bag.reverse(),
+        }
+        bag
+    }
+
+    /// The item under the cursor: in an equipment slot or in the bag.
+    fn selected_item(&self, player: &Player) -> Option<ItemId> {
+        match self.selected.checked_sub(SLOT_KINDS.len()) {
+            None => player.equipment.slot(SLOT_KINDS[self.selected]),
+            Some(row) => self.bag(player).get(row).map(|&i| player.inventory.items[i]),
+        }
+    }
+
+    fn row_count(&self, player: &Player) -> usize {
+        SLOT_KINDS.len() + self.bag(player).len()
+    }
+
+    /// Keep the selected bag row in view.
+    fn follow_selection(&mut self) {
+        let Some(row) = self.selected.checked_sub(SLOT_KINDS.len()) else {
+            self.scroll = 0;
+            return;
+        };
+        if row < self.scroll {
+            self.scroll = row;
+        } else if row >= self.scroll + BAG_ROWS {
+            self.scroll = row + 1 - BAG_ROWS;
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, player: &Player, descriptions: &Descriptions) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 560.0;
+        let box_h = 470.0;
+        // the list and the detail panel beside it are centred together
+        let left = (w - box_w - PANEL_GAP - PANEL_W) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new("Inventory").scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+        let stats = Text::new(TextFragment::new(format!("ATK {}   DEF {}", player.attack_power(), player.defense())).scale(18.0));
+        canvas.draw(&stats, DrawParam::new().dest([left + box_w - 170.0, top + 24.0]).color(Color::new(1.0, 1.0, 0.6, 1.0)));
+
+        let mut rows: Vec<(usize, String, Color)> = Vec::new();
+        for (i, kind) in SLOT_KINDS.into_iter().enumerate() {
+            let name = player.equipment.slot(kind).and_then(items::find).map(|d| d.name).unwrap_or("-");
+            rows.push((i, format!("{:<10} {}", slot_label(kind), name), Color::WHITE));
+        }
+        let bag = self.bag(player);
+        for (row, &index) in bag.iter().enumerate().skip(self.scroll).take(BAG_ROWS) {
+            let id = player.inventory.items[index];
+            let text = match items::find(id) {
+                Some(def) if def.bonuses().is_empty() => def.name.to_string(),
+                Some(def) => format!("{}  ({})", def.name, def.bonuses().join(" ")),
+                None => id.to_string(),
+            };
+            rows.push((SLOT_KINDS.len() + row, text, Color::new(0.85, 0.9, 1.0, 1.0)));
+        }
+
+        let line_height = 30.0;
+        // tabs between the slots and the bag
+        let tabs_y = top + 70.0 + SLOT_KINDS.len() as f32 * line_height + 4.0;
+        let mut tab_x = left + 40.0;
+        for tab in Tab::ALL {
+            let color = if tab == self.tab { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::new(0.7, 0.7, 0.7, 1.0) };
+            let txt = Text::new(TextFragment::new(tab.label()).scale(16.0));
+            canvas.draw(&txt, DrawParam::new().dest([tab_x, tabs_y]).color(color));
+            tab_x += tab.label().len() as f32 * 8.5 + 18.0;
+        }
+        let bag_top = tabs_y + 30.0;
+        for (shown, (row, text, color)) in rows.iter().enumerate() {
+            let y = if *row < SLOT_KINDS.len() {
+                top + 70.0 + *row as f32 * line_height
+            } else {
+                bag_top + (shown - SLOT_KINDS.len()) as f32 * line_height
+            };
+            let txt = Text::new(TextFragment::new(text.as_str()).scale(20.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(*color));
+            if *row == self.selected {
+                let sel_rect = graphics::Rect::new(left + 30.0, y - 5.0, box_w - 60.0, 28.0);
+                let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                canvas.draw(&sel_box, DrawParam::new());
+            }
+        }
+        if bag.is_empty() {
+            let empty = if player.inventory.items.is_empty() { "(bag is empty)" } else { "(nothing of this kind)" };
+            let txt = Text::new(TextFragment::new(empty).scale(18.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, bag_top]).color(Color::new(0.7, 0.7, 0.7, 1.0)));
+        }
+        // marks for more of the bag above or below what's shown
+        let above = self.scroll > 0;
+        let below = bag.len() > self.scroll + BAG_ROWS;
+        for (shown, y, mark) in [(above, bag_top, "^"), (below, bag_top + (BAG_ROWS - 1) as f32 * line_height, "v")] {
+            if !shown { continue; }
+            let txt = Text::new(TextFragment::new(mark).scale(16.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + box_w - 50.0, y]).color(Color::new(0.8, 0.8, 0.8, 1.0)));
+        }
+
+        let prompt = Text::new(TextFragment::new(crate::input::prompt(&format!("{{confirm}}: equip / use   {{left_right}}: tab   {{sort}}: sort ({})   {{inventory}}: close", self.sort.label()))).scale(16.0));
+        canvas.draw(&prompt, DrawParam::new().dest([left + 20.0, top + box_h - 30.0]).color(Color::WHITE));
+        item_details::draw(ctx, canvas, left + box_w + PANEL_GAP, top, box_h, self.selected_item(player), descriptions)
+    }
+
+    /// Returns whether the key changed anything: the cursor, the view, the bag or what's worn.
+    pub fn handle_key(&mut self, key: KeyCode, player: &mut Player) -> bool {
+        if !self.visible { return false; }
+        let before = (self.selected, self.tab, self.sort, player.inventory.items.len(), player.equipment);
+        let rows = self.row_count(player);
+        match key {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(rows - 1); }
+            KeyCode::Left | KeyCode::Right => {
+                let i = Tab::ALL.iter().position(|t| *t == self.tab).unwrap_or(0);
+                let step = if key == KeyCode::Left { Tab::ALL.len() - 1 } else { 1 };
+                self.tab = Tab::ALL[(i + step) % Tab::ALL.len()];
+                self.selected = self.selected.min(self.row_count(player) - 1);
+                self.scroll = 0;
+            }
+            k if Action::Sort.matches(k) => { self.sort = self.sort.next(); }
+            k if Action::Confirm.matches(k) => {
+                if self.selected < SLOT_KINDS.len() {
+                    player.equipment.unequip(&mut player.inventory, SLOT_KINDS[self.selected]);
+                } else if let Some(&index) = self.bag(player).get(self.selected - SLOT_KINDS.len())
+                    && !player.use_item(index) {
+                    player.equipment.equip(&mut player.inventory, index);
+                }
+                // the bag may have shrunk
+                self.selected = self.selected.min(self.row_count(player) - 1);
+            }
+            k if Action::Inventory.matches(k) || Action::Cancel.matches(k) => { self.visible = false; }
+            _ => {}
+        }
+        self.follow_selection();
+        !self.visible || before != (self.selected, self.tab, self.sort, player.inventory.items.len(), player.equipment)
+    }
+}
+
+fn slot_label(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Weapon => "Weapon:",
+        ItemKind::Armor => "Armor:",
+        ItemKind::Accessory => "Accessory:",
+        ItemKind::Consumable | ItemKind::Key => "",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Inventory;
+
+    #[test]
+    fn tabs_filter_and_sorts_order_the_bag() {
+        let mut player = Player::test_new();
+        player.inventory = Inventory { items: vec!["potion", "iron_sword", "old_key", "antidote", "chain_mail"] };
+        let mut screen = InventoryScreen::new();
+        let names = |screen: &InventoryScreen, player: &Player| -> Vec<&str> {
+            screen.bag(player).into_iter().map(|i| player.inventory.items[i]).collect()
+        };
+        assert_eq!(names(&screen, &player), vec!["iron_sword", "chain_mail", "antidote", "potion", "old_key"]);
+        screen.sort = Sort::Name;
+        assert_eq!(names(&screen, &player), vec!["antidote", "chain_mail", "iron_sword", "old_key", "potion"]);
+        screen.sort = Sort::Newest;
+        screen.tab = Tab::Consumables;
+        assert_eq!(names(&screen, &player), vec!["antidote", "potion"]);
+        screen.tab = Tab::KeyItems;
+        assert_eq!(names(&screen, &player), vec!["old_key"]);
+    }
+
+    #[test]
+    fn using_an_item_goes_through_the_sorted_view() {
+        let mut player = Player::test_new();
+        player.inventory = Inventory { items: vec!["potion", "iron_sword"] };
+        let mut screen = InventoryScreen::new();
+        screen.toggle();
+        // sorted by type the sword comes first
+        for key in [KeyCode::Down, KeyCode::Down, KeyCode::Down, KeyCode::Z] {
+            screen.handle_key(key, &mut player);
+        }
+        assert_eq!(player.equipment.weapon, Some("iron_sword"));
+        assert_eq!(player.inventory.items, vec!["potion"]);
+    }
+}
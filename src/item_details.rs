@@ -0,0 +1,65 @@
+//! Detail panel for the highlighted item, drawn beside the inventory and shop lists: its icon,
+//! name and kind, description, stats and what a merchant would pay for it.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, DrawParam, Text, TextFragment};
+
+use crate::items::{self, Descriptions, ItemId, ItemKind};
+use crate::shop::Shop;
+
+/// Width of the panel; callers leave this much room beside their box.
+pub const PANEL_W: f32 = 220.0;
+/// Gap between a list box and its panel.
+pub const PANEL_GAP: f32 = 10.0;
+
+fn kind_label(kind: ItemKind) -> &'static str {
+    match kind {
+        ItemKind::Weapon => "Weapon",
+        ItemKind::Armor => "Armor",
+        ItemKind::Accessory => "Accessory",
+        ItemKind::Consumable => "Consumable",
+        ItemKind::Key => "Key item",
+    }
+}
+
+/// Draw the panel with its top-left at (`left`, `top`), `height` tall. With no item selected
+/// it's an empty box.
+pub fn draw(ctx: &mut Context, canvas: &mut Canvas, left: f32, top: f32, height: f32, item: Option<ItemId>, descriptions: &Descriptions) -> GameResult {
+    let rect = graphics::Rect::new(left, top, PANEL_W, height);
+    let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.15, 0.45, 0.95))?;
+    canvas.draw(&bg, DrawParam::new());
+    let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), rect, Color::WHITE)?;
+    canvas.draw(&border, DrawParam::new());
+    let Some(def) = item.and_then(items::find) else { return Ok(()) };
+
+    // icon: a swatch of the item's colour until items get sprites of their own
+    let (r, g, b) = def.color;
+    let icon = graphics::Rect::new(left + 14.0, top + 14.0, 40.0, 40.0);
+    let swatch = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), icon, Color::new(r, g, b, 1.0))?;
+    canvas.draw(&swatch, DrawParam::new());
+    let frame = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), icon, Color::WHITE)?;
+    canvas.draw(&frame, DrawParam::new());
+    let name = Text::new(TextFragment::new(def.name).scale(20.0));
+    canvas.draw(&name, DrawParam::new().dest([left + 64.0, top + 14.0]).color(Color::WHITE));
+    let kind = Text::new(TextFragment::new(kind_label(def.kind)).scale(15.0));
+    canvas.draw(&kind, DrawParam::new().dest([left + 64.0, top + 38.0]).color(Color::new(0.7, 0.75, 0.85, 1.0)));
+
+    let mut y = top + 70.0;
+    if let Some(text) = descriptions.get(def.id) {
+        let mut description = Text::new(TextFragment::new(text).scale(16.0));
+        description.set_bounds([PANEL_W - 28.0, f32::INFINITY]);
+        let lines = description.measure(ctx)?.y;
+        canvas.draw(&description, DrawParam::new().dest([left + 14.0, y]).color(Color::new(0.9, 0.9, 0.95, 1.0)));
+        y += lines + 12.0;
+    }
+    for bonus in def.bonuses() {
+        let txt = Text::new(TextFragment::new(bonus).scale(17.0));
+        canvas.draw(&txt, DrawParam::new().dest([left + 14.0, y]).color(Color::new(1.0, 1.0, 0.6, 1.0)));
+        y += 22.0;
+    }
+
+    let value = if def.kind == ItemKind::Key { "Can't be sold".to_string() } else { format!("Sells for {} G", Shop::sell_price(def.id)) };
+    let txt = Text::new(TextFragment::new(value).scale(16.0));
+    canvas.draw(&txt, DrawParam::new().dest([left + 14.0, top + height - 30.0]).color(Color::new(1.0, 0.85, 0.2, 1.0)));
+    Ok(())
+}
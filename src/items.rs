@@ -0,0 +1,228 @@
+//! Item definitions, the player's inventory and equipment slots.
+//!
+//! Item stats live in `ITEMS`; the longer descriptions shown in the inventory and shop come
+//! from `assets/items.txt`, one `item_id=text` line per item.
+
+use crate::status::StatusKind;
+
+/// Items are referred to by their stable string id (used in saves and data files).
+pub type ItemId = &'static str;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ItemKind {
+    Weapon,
+    Armor,
+    Accessory,
+    /// Used up from the inventory screen instead of being equipped.
+    Consumable,
+    /// Story items: carried, never used up, equipped or sold.
+    Key,
+}
+
+/// What happens when a consumable is used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UseEffect {
+    Heal(i32),
+    /// Apply a status effect for this many seconds.
+    Apply(StatusKind, f32),
+    /// Remove harmful effects (poison, burn, slow).
+    Cure,
+}
+
+#[derive(Debug)]
+pub struct ItemDef {
+    pub id: ItemId,
+    pub name: &'static str,
+    pub kind: ItemKind,
+    pub attack: i32,
+    pub defense: i32,
+    /// Shop price in gold.
+    pub price: u32,
+    /// Tint used when drawing the item (e.g. the blade colour of a weapon swing).
+    pub color: (f32, f32, f32),
+    pub effect: Option<UseEffect>,
+}
+
+impl ItemDef {
+    /// Short lines for what the item does: "ATK +2", "HP +10" and so on.
+    pub fn bonuses(&self) -> Vec<String> {
+        let mut parts = Vec::new();
+        if self.attack != 0 { parts.push(format!("ATK {:+}", self.attack)); }
+        if self.defense != 0 { parts.push(format!("DEF {:+}", self.defense)); }
+        match self.effect {
+            Some(UseEffect::Heal(hp)) => parts.push(format!("HP +{}", hp)),
+            Some(UseEffect::Apply(kind, secs)) => parts.push(format!("{} {:.0}s", kind.name(), secs)),
+            Some(UseEffect::Cure) => parts.push("cures ailments".to_string()),
+            None => {}
+        }
+        parts
+    }
+}
+
+/// Every item in the game. Ids must be unique.
+pub const ITEMS: &[ItemDef] = &[
+    ItemDef { id: "rusty_sword", name: "Rusty Sword", kind: ItemKind::Weapon, attack: 2, defense: 0, price: 10, color: (0.75, 0.55, 0.4), effect: None },
+    ItemDef { id: "iron_sword", name: "Iron Sword", kind: ItemKind::Weapon, attack: 5, defense: 0, price: 40, color: (0.85, 0.88, 0.95), effect: None },
+    ItemDef { id: "leather_armor", name: "Leather Armor", kind: ItemKind::Armor, attack: 0, defense: 2, price: 15, color: (0.55, 0.35, 0.2), effect: None },
+    ItemDef { id: "chain_mail", name: "Chain Mail", kind: ItemKind::Armor, attack: 0, defense: 4, price: 60, color: (0.6, 0.6, 0.65), effect: None },
+    ItemDef { id: "lucky_charm", name: "Lucky Charm", kind: ItemKind::Accessory, attack: 1, defense: 1, price: 30, color: (0.3, 0.9, 0.4), effect: None },
+    ItemDef { id: "swim_fins", name: "Swim Fins", kind: ItemKind::Accessory, attack: 0, defense: 0, price: 45, color: (0.2, 0.55, 0.9), effect: None },
+    ItemDef { id: "potion", name: "Potion", kind: ItemKind::Consumable, attack: 0, defense: 0, price: 12, color: (0.9, 0.2, 0.3), effect: Some(UseEffect::Heal(10)) },
+    ItemDef { id: "regen_tonic", name: "Regen Tonic", kind: ItemKind::Consumable, attack: 0, defense: 0, price: 20, color: (0.25, 0.8, 0.35), effect: Some(UseEffect::Apply(StatusKind::Regen, 8.0)) },
+    ItemDef { id: "antidote", name: "Antidote", kind: ItemKind::Consumable, attack: 0, defense: 0, price: 8, color: (0.6, 0.3, 0.8), effect: Some(UseEffect::Cure) },
+    ItemDef { id: "old_key", name: "Old Key", kind: ItemKind::Key, attack: 0, defense: 0, price: 0, color: (0.8, 0.65, 0.25), effect: None },
+];
+
+pub fn find(id: &str) -> Option<&'static ItemDef> {
+    ITEMS.iter().find(|d| d.id == id)
+}
+
+/// The item `id` if it can be worn in `kind`'s slot; for reading saved equipment, which may
+/// name anything.
+pub fn find_for_slot(id: &str, kind: ItemKind) -> Option<ItemId> {
+    find(id).filter(|d| d.kind == kind).map(|d| d.id)
+}
+
+/// Items the player carries (equipped items live in `Equipment` instead).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Inventory {
+    pub items: Vec<ItemId>,
+}
+
+impl Inventory {
+    /// What a new game starts with.
+    pub fn starter() -> Inventory {
+        Inventory { items: vec!["rusty_sword", "leather_armor", "lucky_charm"] }
+    }
+
+    pub fn add(&mut self, id: ItemId) {
+        self.items.push(id);
+    }
+
+    /// Remove the item at `index`, if any.
+    pub fn take(&mut self, index: usize) -> Option<ItemId> {
+        if index < self.items.len() { Some(self.items.remove(index)) } else { None }
+    }
+}
+
+/// Item descriptions from `assets/items.txt`.
+#[derive(Default)]
+pub struct Descriptions {
+    texts: Vec<(ItemId, String)>,
+}
+
+impl Descriptions {
+    /// Parse `item_id=text` lines. Unknown item ids are reported and skipped.
+    pub fn from_text(text: &str) -> Descriptions {
+        let mut texts = Vec::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            match line.split_once('=').and_then(|(id, text)| Some((find(id.trim())?, text.trim()))) {
+                Some((def, text)) => texts.push((def.id, text.to_string())),
+                None => println!("Items: skipping bad description line {:?}", line),
+            }
+        }
+        Descriptions { texts }
+    }
+
+    /// Load `assets/items.txt`; items just go without descriptions if it's missing.
+    pub fn load(ctx: &ggez::Context) -> Descriptions {
+        match crate::assets::read_text(ctx, "/items.txt") {
+            Some(text) => Descriptions::from_text(&text),
+            None => {
+                println!("Items: no items.txt, items have no descriptions");
+                Descriptions::default()
+            }
+        }
+    }
+
+    pub fn get(&self, id: &str) -> Option<&str> {
+        self.texts.iter().find(|(i, _)| *i == id).map(|(_, text)| text.as_str())
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Equipment {
+    pub weapon: Option<ItemId>,
+    pub armor: Option<ItemId>,
+    pub accessory: Option<ItemId>,
+}
+
+impl Equipment {
+    pub fn slot(&self, kind: ItemKind) -> Option<ItemId> {
+        match kind {
+            ItemKind::Weapon => self.weapon,
+            ItemKind::Armor => self.armor,
+            ItemKind::Accessory => self.accessory,
+            ItemKind::Consumable | ItemKind::Key => None,
+        }
+    }
+
+    fn slot_mut(&mut self, kind: ItemKind) -> Option<&mut Option<ItemId>> {
+        match kind {
+            ItemKind::Weapon => Some(&mut self.weapon),
+            ItemKind::Armor => Some(&mut self.armor),
+            ItemKind::Accessory => Some(&mut self.accessory),
+            ItemKind::Consumable | ItemKind::Key => None,
+        }
+    }
+
+    /// Equip inventory item `index` into its slot; whatever was in the slot goes back to the
+    /// inventory. Returns false if the index is invalid or the item can't be equipped.
+    pub fn equip(&mut self, inventory: &mut Inventory, index: usize) -> bool {
+        let Some(def) = inventory.items.get(index).and_then(|id| find(id)) else { return false };
+        let Some(slot) = self.slot_mut(def.kind) else { return false };
+        let old = slot.replace(def.id);
+        inventory.take(index);
+        if let Some(old) = old {
+            inventory.add(old);
+        }
+        true
+    }
+
+    /// Move the item in `kind`'s slot back to the inventory.
+    pub fn unequip(&mut self, inventory: &mut Inventory, kind: ItemKind) -> bool {
+        match self.slot_mut(kind).and_then(Option::take) {
+            Some(id) => { inventory.add(id); true }
+            None => false,
+        }
+    }
+
+    fn equipped(&self) -> impl Iterator<Item = &'static ItemDef> + '_ {
+        [self.weapon, self.armor, self.accessory].into_iter().flatten().filter_map(find)
+    }
+
+    pub fn attack_bonus(&self) -> i32 {
+        self.equipped().map(|d| d.attack).sum()
+    }
+
+    pub fn defense_bonus(&self) -> i32 {
+        self.equipped().map(|d| d.defense).sum()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn equipping_swaps_with_the_current_slot_and_applies_bonuses() {
+        let mut inv = Inventory { items: vec!["rusty_sword", "iron_sword"] };
+        let mut eq = Equipment::default();
+        assert!(eq.equip(&mut inv, 0));
+        assert_eq!(eq.attack_bonus(), 2);
+        // iron sword is now at index 0; equipping it returns the rusty sword to the bag
+        assert!(eq.equip(&mut inv, 0));
+        assert_eq!(eq.weapon, Some("iron_sword"));
+        assert_eq!(inv.items, vec!["rusty_sword"]);
+        assert!(eq.unequip(&mut inv, ItemKind::Weapon));
+        assert_eq!(eq.attack_bonus(), 0);
+    }
+
+    #[test]
+    fn descriptions_are_read_by_item_id() {
+        let texts = Descriptions::from_text("# comment\npotion=Tastes of cherries.\nno_such_item=Lost.\n");
+        assert_eq!(texts.get("potion"), Some("Tastes of cherries."));
+        assert_eq!(texts.get("no_such_item"), None);
+        assert_eq!(find("iron_sword").unwrap().bonuses(), vec!["ATK +5"]);
+    }
+}
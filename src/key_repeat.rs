@@ -0,0 +1,68 @@
+//! Held Up/Down in menus: after a short pause the key repeats on its own, so long lists can be
+//! scrolled without a press per row.
+//!
+//! The game makes its own repeats rather than taking the system's, which vary by platform (and
+//! are missing on some), so every menu scrolls at the same pace everywhere.
+
+use ggez::input::keyboard::KeyCode;
+
+/// Keys that repeat.
+const KEYS: [KeyCode; 2] = [KeyCode::Up, KeyCode::Down];
+/// Seconds a key is held before it starts repeating.
+pub const DELAY: f32 = 0.35;
+/// Seconds between repeats after that.
+pub const INTERVAL: f32 = 0.08;
+
+#[derive(Default)]
+pub struct KeyRepeat {
+    /// The repeating key and seconds until its next repeat.
+    held: Option<(KeyCode, f32)>,
+}
+
+impl KeyRepeat {
+    /// True for keys this handles, whose system repeats should be ignored.
+    pub fn repeats(key: KeyCode) -> bool {
+        KEYS.contains(&key)
+    }
+
+    /// A key went down; a new one takes over from whatever was repeating.
+    pub fn press(&mut self, key: KeyCode) {
+        self.held = KeyRepeat::repeats(key).then_some((key, DELAY));
+    }
+
+    /// Advance `dt` seconds with `is_down` telling which keys are still held. Returns the key
+    /// when it's due to repeat (at most once a frame, so a hitch can't skip rows).
+    pub fn update(&mut self, dt: f32, is_down: impl Fn(KeyCode) -> bool) -> Option<KeyCode> {
+        let (key, left) = self.held.as_mut()?;
+        let key = *key;
+        if !is_down(key) {
+            self.held = None;
+            return None;
+        }
+        *left -= dt;
+        if *left > 0.0 { return None; }
+        *left = (*left + INTERVAL).max(0.0);
+        Some(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn repeats_after_the_delay_until_let_go() {
+        let mut repeat = KeyRepeat::default();
+        repeat.press(KeyCode::Z);
+        assert_eq!(repeat.update(1.0, |_| true), None, "only Up and Down repeat");
+        repeat.press(KeyCode::Down);
+        assert_eq!(repeat.update(DELAY - 0.01, |_| true), None);
+        assert_eq!(repeat.update(0.02, |_| true), Some(KeyCode::Down));
+        assert_eq!(repeat.update(INTERVAL / 2.0, |_| true), None);
+        assert_eq!(repeat.update(INTERVAL / 2.0, |_| true), Some(KeyCode::Down));
+        assert_eq!(repeat.update(5.0, |_| true), Some(KeyCode::Down), "one per frame at most");
+        repeat.press(KeyCode::Up);
+        assert_eq!(repeat.update(DELAY, |key| key == KeyCode::Down), None, "Up was let go");
+        assert_eq!(repeat.update(DELAY, |_| true), None);
+    }
+}
@@ -0,0 +1,87 @@
+//! Darkness and light emitters for dim rooms.
+//!
+//! Rooms with a darkness above zero are shaded over, half a tile at a time, except where light
+//! reaches. Emitters (wall torches, the player's own glow) register each frame with `add`; light
+//! falls off linearly to nothing at the emitter's radius.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
+use nalgebra as na;
+
+use crate::map::TILE_SIZE;
+
+/// Shading cells per tile along each axis.
+const CELLS_PER_TILE: usize = 2;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Light {
+    /// Centre, in world pixels.
+    pub position: na::Point2<f32>,
+    /// Reach in world pixels.
+    pub radius: f32,
+    /// Brightness at the centre, 0 to 1.
+    pub intensity: f32,
+}
+
+pub struct Lighting {
+    /// How dark unlit areas are, 0 (fully lit) to 1 (pitch black).
+    darkness: f32,
+    lights: Vec<Light>,
+}
+
+impl Lighting {
+    pub fn new(darkness: f32) -> Lighting {
+        Lighting { darkness: darkness.clamp(0.0, 1.0), lights: Vec::new() }
+    }
+
+    pub fn add(&mut self, light: Light) {
+        self.lights.push(light);
+    }
+
+    /// Light reaching a world point, 0 to 1.
+    pub fn level_at(&self, point: na::Point2<f32>) -> f32 {
+        let lit: f32 = self.lights.iter().map(|l| {
+            let falloff = 1.0 - (point - l.position).magnitude() / l.radius;
+            falloff.max(0.0) * l.intensity
+        }).sum();
+        lit.min(1.0)
+    }
+
+    /// Shade a `width` x `height` pixel room. Does nothing in rooms that aren't dark.
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, width: f32, height: f32, scale: f32, offset: (f32, f32)) -> GameResult {
+        if self.darkness <= 0.0 { return Ok(()); }
+        let cell = TILE_SIZE / CELLS_PER_TILE as f32;
+        let (cols, rows) = ((width / cell) as usize, (height / cell) as usize);
+        let mut builder = MeshBuilder::new();
+        for row in 0..rows {
+            for col in 0..cols {
+                let (x, y) = (col as f32 * cell, row as f32 * cell);
+                let centre = na::Point2::new(x + cell / 2.0, y + cell / 2.0);
+                let alpha = self.darkness * (1.0 - self.level_at(centre));
+                if alpha <= 0.01 { continue; }
+                let rect = Rect::new(offset.0 + x * scale, offset.1 + y * scale, cell * scale + 0.5, cell * scale + 0.5);
+                builder.rectangle(DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.02, alpha))?;
+            }
+        }
+        let mesh = Mesh::from_data(ctx, builder.build());
+        canvas.draw(&mesh, DrawParam::new());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn light_falls_off_with_distance_and_saturates() {
+        let mut lighting = Lighting::new(0.9);
+        let here = na::Point2::new(100.0, 100.0);
+        lighting.add(Light { position: here, radius: 64.0, intensity: 1.0 });
+        assert_eq!(lighting.level_at(here), 1.0);
+        assert_eq!(lighting.level_at(na::Point2::new(132.0, 100.0)), 0.5);
+        assert_eq!(lighting.level_at(na::Point2::new(200.0, 100.0)), 0.0);
+        lighting.add(Light { position: here, radius: 64.0, intensity: 1.0 });
+        assert_eq!(lighting.level_at(na::Point2::new(132.0, 100.0)), 1.0);
+    }
+}
@@ -0,0 +1,165 @@
+//! Loading screen shown while the assets are read in the background.
+//!
+//! A worker thread reads every file `Assets::load` needs (from the loose assets or the data
+//! archive), decodes the images and synthesizes missing sound effects, sending each one back as
+//! it's done. Meanwhile the window stays responsive and shows a progress bar; once everything
+//! has arrived the assets are uploaded and the game starts.
+
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+use ggez::{Context, GameError, GameResult};
+use ggez::event::{ErrorOrigin, EventHandler};
+use ggez::event::winit_event::TouchPhase;
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect, Text, TextFragment};
+use ggez::input::gamepad::{gilrs, GamepadId};
+use ggez::input::keyboard::KeyInput;
+use ggez::input::mouse::MouseButton;
+
+use crate::assets::{self, Assets, Fetched, Preloaded};
+use crate::cli::LaunchOptions;
+use crate::game::Game;
+
+pub struct Loader {
+    files: Receiver<(String, Option<Fetched>)>,
+    total: usize,
+    done: usize,
+    preloaded: Preloaded,
+}
+
+impl Loader {
+    /// Start fetching on a worker thread.
+    pub fn start() -> Loader {
+        let files = assets::files();
+        let total = files.len();
+        let (tx, rx) = mpsc::channel();
+        thread::spawn(move || {
+            for path in files {
+                let fetched = assets::fetch(&path);
+                // the receiver only goes away if the game quit mid-load
+                if tx.send((path, fetched)).is_err() { return; }
+            }
+        });
+        Loader { files: rx, total, done: 0, preloaded: Preloaded::default() }
+    }
+
+    /// Collect whatever the worker has finished. True once every file is in.
+    pub fn poll(&mut self) -> bool {
+        for (path, fetched) in self.files.try_iter() {
+            self.done += 1;
+            if let Some(fetched) = fetched {
+                self.preloaded.insert(path, fetched);
+            }
+        }
+        self.done == self.total
+    }
+
+    /// Share of files fetched, 0 to 1.
+    pub fn progress(&self) -> f32 {
+        if self.total == 0 { 1.0 } else { self.done as f32 / self.total as f32 }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let bar = Rect::new(w * 0.25, h * 0.55, w * 0.5, 18.0);
+        let fill = Rect::new(bar.x, bar.y, bar.w * self.progress(), bar.h);
+        let filled = Mesh::new_rectangle(ctx, DrawMode::fill(), fill, Color::new(1.0, 1.0, 0.6, 1.0))?;
+        canvas.draw(&filled, DrawParam::new());
+        let outline = Mesh::new_rectangle(ctx, DrawMode::stroke(2.0), bar, Color::WHITE)?;
+        canvas.draw(&outline, DrawParam::new());
+        let text = Text::new(TextFragment::new("Loading...").scale(24.0));
+        canvas.draw(&text, DrawParam::new().dest([bar.x, bar.y - 36.0]).color(Color::WHITE));
+        Ok(())
+    }
+}
+
+/// The whole program: the loading screen until the assets are in, then the game. Every event
+/// is passed on to the game once it's running; the loading screen takes no input.
+pub enum App {
+    Loading { loader: Loader, launch: LaunchOptions },
+    Running(Box<Game>),
+}
+
+impl App {
+    pub fn new(launch: LaunchOptions) -> App {
+        App::Loading { loader: Loader::start(), launch }
+    }
+}
+
+/// Every handler of `EventHandler` besides `update` and `draw`, in the order ggez declares
+/// them: passed on to the game once it's running, answered with the value after `=` while
+/// loading. Listing the whole trait here means the game can start handling any event without
+/// the app needing a change.
+macro_rules! forward {
+    ($(fn $name:ident(&mut self, $ctx:ident $(, $arg:ident: $ty:ty)*) -> $ret:ty = $loading:expr;)*) => {
+        $(fn $name(&mut self, $ctx: &mut Context $(, $arg: $ty)*) -> $ret {
+            match self {
+                App::Loading { .. } => $loading,
+                App::Running(game) => game.$name($ctx $(, $arg)*),
+            }
+        })*
+    };
+}
+
+impl EventHandler for App {
+    fn update(&mut self, ctx: &mut Context) -> GameResult {
+        match self {
+            App::Loading { loader, launch } => {
+                if loader.poll() {
+                    let files = std::mem::take(&mut loader.preloaded);
+                    let assets = Assets::load(ctx, files)?;
+                    *self = App::Running(Box::new(Game::new(ctx, launch, assets)?));
+                }
+                Ok(())
+            }
+            App::Running(game) => game.update(ctx),
+        }
+    }
+
+    fn draw(&mut self, ctx: &mut Context) -> GameResult {
+        match self {
+            App::Loading { loader, .. } => {
+                let mut canvas = Canvas::from_frame(ctx, Color::new(0.1, 0.2, 0.3, 1.0));
+                loader.draw(ctx, &mut canvas)?;
+                canvas.finish(ctx)
+            }
+            App::Running(game) => game.draw(ctx),
+        }
+    }
+
+    forward! {
+        fn mouse_button_down_event(&mut self, ctx, button: MouseButton, x: f32, y: f32) -> GameResult = Ok(());
+        fn mouse_button_up_event(&mut self, ctx, button: MouseButton, x: f32, y: f32) -> GameResult = Ok(());
+        fn mouse_motion_event(&mut self, ctx, x: f32, y: f32, dx: f32, dy: f32) -> GameResult = Ok(());
+        fn mouse_enter_or_leave(&mut self, ctx, entered: bool) -> GameResult = Ok(());
+        fn mouse_wheel_event(&mut self, ctx, x: f32, y: f32) -> GameResult = Ok(());
+        fn key_down_event(&mut self, ctx, input: KeyInput, repeat: bool) -> GameResult = Ok(());
+        fn key_up_event(&mut self, ctx, input: KeyInput) -> GameResult = Ok(());
+        fn text_input_event(&mut self, ctx, character: char) -> GameResult = Ok(());
+        fn touch_event(&mut self, ctx, phase: TouchPhase, x: f64, y: f64) -> GameResult = Ok(());
+        fn gamepad_button_down_event(&mut self, ctx, button: gilrs::Button, id: GamepadId) -> GameResult = Ok(());
+        fn gamepad_button_up_event(&mut self, ctx, button: gilrs::Button, id: GamepadId) -> GameResult = Ok(());
+        fn gamepad_axis_event(&mut self, ctx, axis: gilrs::Axis, value: f32, id: GamepadId) -> GameResult = Ok(());
+        fn focus_event(&mut self, ctx, gained: bool) -> GameResult = Ok(());
+        fn quit_event(&mut self, ctx) -> GameResult<bool> = Ok(false);
+        fn resize_event(&mut self, ctx, width: f32, height: f32) -> GameResult = Ok(());
+        fn on_error(&mut self, ctx, origin: ErrorOrigin, e: GameError) -> bool = true;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worker_fetches_every_file_and_synthesizes_sound_effects() {
+        let mut loader = Loader::start();
+        while !loader.poll() {
+            thread::yield_now();
+        }
+        assert_eq!(loader.progress(), 1.0);
+        // no recordings ship for the footsteps, so they must have been generated
+        assert!(matches!(loader.preloaded.get("/Sfx/step_grass.wav"), Some(Fetched::Bytes(_))));
+    }
+}
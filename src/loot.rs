@@ -0,0 +1,109 @@
+//! Loot tables: what each kind of enemy might leave behind, and how rarely.
+//!
+//! A table has a percent chance of dropping anything at all, then picks a rarity by weight
+//! (common, uncommon or rare, among the rarities it has entries for) and an item of that
+//! rarity. The same tables serve enemies out in the world and foes met in random battles; rolls
+//! use the world's seeded RNG.
+
+use ggez::graphics::Color;
+
+use crate::items::ItemId;
+use crate::rng::GameRng;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Rarity {
+    Common,
+    Uncommon,
+    Rare,
+}
+
+impl Rarity {
+    const ALL: [Rarity; 3] = [Rarity::Common, Rarity::Uncommon, Rarity::Rare];
+
+    /// Relative odds of rolling this rarity.
+    fn weight(self) -> i32 {
+        match self {
+            Rarity::Common => 60,
+            Rarity::Uncommon => 30,
+            Rarity::Rare => 10,
+        }
+    }
+
+    /// Glow around a dropped item.
+    pub fn color(self) -> Color {
+        match self {
+            Rarity::Common => Color::new(0.85, 0.85, 0.85, 1.0),
+            Rarity::Uncommon => Color::new(0.3, 0.85, 0.4, 1.0),
+            Rarity::Rare => Color::new(0.65, 0.4, 1.0, 1.0),
+        }
+    }
+}
+
+pub struct LootTable {
+    /// Percent chance of dropping anything.
+    pub chance: i32,
+    pub entries: &'static [(ItemId, Rarity)],
+}
+
+impl LootTable {
+    /// Roll for a drop.
+    pub fn roll(&self, rng: &mut GameRng) -> Option<(ItemId, Rarity)> {
+        if rng.range(0, 100) >= self.chance { return None; }
+        let present: Vec<Rarity> = Rarity::ALL.into_iter().filter(|r| self.entries.iter().any(|(_, e)| e == r)).collect();
+        let mut roll = rng.range(0, present.iter().map(|r| r.weight()).sum());
+        let rarity = *present.iter().find(|r| {
+            roll -= r.weight();
+            roll < 0
+        })?;
+        let items: Vec<ItemId> = self.entries.iter().filter(|(_, r)| *r == rarity).map(|(id, _)| *id).collect();
+        Some((items[rng.range(0, items.len() as i32) as usize], rarity))
+    }
+}
+
+/// Plain melee enemies.
+pub const SLIME: LootTable = LootTable {
+    chance: 30,
+    entries: &[("potion", Rarity::Common), ("antidote", Rarity::Uncommon), ("lucky_charm", Rarity::Rare)],
+};
+/// Poisonous biters.
+pub const VIPER: LootTable = LootTable {
+    chance: 40,
+    entries: &[("antidote", Rarity::Common), ("potion", Rarity::Uncommon), ("leather_armor", Rarity::Rare)],
+};
+/// Slowing sludge.
+pub const MUDLING: LootTable = LootTable {
+    chance: 35,
+    entries: &[("potion", Rarity::Common), ("regen_tonic", Rarity::Uncommon), ("swim_fins", Rarity::Rare)],
+};
+/// Burning spitters.
+pub const EMBER: LootTable = LootTable {
+    chance: 45,
+    entries: &[("regen_tonic", Rarity::Common), ("potion", Rarity::Uncommon), ("iron_sword", Rarity::Rare)],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn drops_come_from_the_table_and_commons_outnumber_rares() {
+        let mut rng = GameRng::new(11);
+        let table = LootTable { chance: 100, ..SLIME };
+        let mut counts = [0; 3];
+        for _ in 0..1000 {
+            let (id, rarity) = table.roll(&mut rng).expect("always drops at 100%");
+            assert!(table.entries.contains(&(id, rarity)));
+            counts[Rarity::ALL.iter().position(|r| *r == rarity).unwrap()] += 1;
+        }
+        assert!(counts[0] > counts[1] && counts[1] > counts[2] && counts[2] > 0, "{:?}", counts);
+        let never = LootTable { chance: 0, ..SLIME };
+        assert!((0..100).all(|_| never.roll(&mut rng).is_none()));
+    }
+
+    #[test]
+    fn tables_only_name_real_items() {
+        for table in [&SLIME, &VIPER, &MUDLING, &EMBER] {
+            assert!(table.entries.iter().all(|(id, _)| crate::items::find(id).is_some()));
+        }
+    }
+}
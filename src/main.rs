@@ -8,18 +8,109 @@ mod gui;
 mod title;
 mod intro;
 mod options;
+mod rng;
+mod save;
+mod save_slots;
+mod stats;
+mod items;
+mod inventory_screen;
+mod item_details;
+mod npc;
+mod shop;
+mod shop_screen;
+mod pickup;
+mod status;
+mod skills;
+mod projectile;
+mod save_menu;
+mod credits;
+mod achievements;
+mod achievements_screen;
+mod speedrun;
+mod palette;
+mod splash;
+mod teleport;
+mod lighting;
+mod dialogue;
+mod props;
+mod companion;
+mod party;
+mod exploration;
+mod waypoints;
+mod music;
+mod ambience;
+mod footsteps;
+mod profiles;
+mod cli;
+mod input;
+mod replay;
+mod spatial;
+mod perf;
+mod loading;
+mod shake;
+mod camera;
+mod transition;
+mod scene;
+mod encounter;
+mod battle;
+mod loot;
+mod tiles;
+mod time_scale;
+mod calendar;
+mod game_vars;
+mod events;
+mod pathfind;
+mod rumble;
+mod postfx;
+mod clip;
+mod inspector;
+mod hitboxes;
+mod grid_overlay;
+mod room_watch;
+mod platform;
+mod tutorial;
+mod key_repeat;
+mod menu_sfx;
+mod stealth;
+mod barter;
+mod barter_screen;
+mod arena;
+mod daily;
+mod tileset;
+mod markup;
+mod world;
+#[cfg(test)]
+mod testing;
 
 use ggez::{ContextBuilder, GameResult};
 use ggez::event;
 
 fn main() -> GameResult {
-    let resource_dir = std::env::current_dir().unwrap().join("assets");
+    let launch = match cli::LaunchOptions::parse(std::env::args().skip(1)) {
+        Ok(launch) => launch,
+        Err(e) => {
+            eprintln!("{}\n{}", e, cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+    if launch.help {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+    // mods/ goes first so its files shadow the shipped assets/ with the same path
+    let [mods_dir, resource_dir] = assets::loose_dirs().try_into().unwrap();
+    if mods_dir.is_dir() {
+        println!("Assets: loading overrides from {:?}", mods_dir);
+    }
         let cb = ContextBuilder::new("TALE-the-rpg", "YourName")
+            .add_resource_path(mods_dir)
             .add_resource_path(resource_dir)
             .window_setup(ggez::conf::WindowSetup::default().title("2D RPG in Rust"))
             .window_mode(ggez::conf::WindowMode::default().resizable(false));
-    let (mut ctx, event_loop) = cb.build()?;
-    let game = game::Game::new(&mut ctx)?;
-    event::run(ctx, event_loop, game)
+    let (ctx, event_loop) = cb.build()?;
+    assets::mount_data_archive(&ctx)?;
+    save::adopt_working_dir_saves();
+    // the window opens on the loading screen; the game starts once the assets are in
+    event::run(ctx, event_loop, loading::App::new(launch))
 }
 
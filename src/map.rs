@@ -1,26 +1,87 @@
+use std::sync::Arc;
+
 use ggez::{Context, GameResult};
 use ggez::graphics::Canvas;
 use crate::assets::Assets;
-use crate::rooms::{Room, GridRoom};
+use crate::rooms::{Interaction, Room, GridRoom, OutdoorRoom, Water};
+use crate::save::GenerationOptions;
+use crate::teleport::{self, Pad, Teleporters};
+use crate::props::Props;
+use crate::lighting::{Light, Lighting};
+use crate::encounter::EncounterTable;
+use crate::tiles::TileRegistry;
+use crate::calendar::Sky;
 // Re-export TILE_SIZE so existing code can continue to import it from crate::map::TILE_SIZE
 pub use crate::rooms::TILE_SIZE;
 
+/// What pressing Z did to a plain tile (see `Map::use_tile`).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TileUse {
+    /// A door swung open or shut.
+    Door { open: bool },
+    /// A chest was looted for this much gold.
+    Chest(u32),
+}
+
+/// Rooms built in code; data files numbered past these make rooms of their own.
+const BUILTIN_ROOMS: usize = 2;
+
 /// Map now manages multiple rooms and delegates drawing/collision to the active room.
 pub struct Map {
     rooms: Vec<Box<dyn Room>>,
     current: usize,
+    // tiles occupied by NPCs in the current room; nothing may walk onto them
+    blocked_tiles: Vec<(i32, i32)>,
+    teleporters: Teleporters,
+    /// Random encounters for each room, from the room data files; None outside wild rooms.
+    encounters: Vec<Option<EncounterTable>>,
+    /// Doorways out of each room, from the room data files: (tile, where it leads).
+    exits: Vec<Vec<((i32, i32), Pad)>>,
+    /// Today's season and weather, shown in outdoor rooms.
+    sky: Sky,
+    /// What the world was built from, for rebuilding a room (see `reload_room`).
+    generation: GenerationOptions,
 }
 
 impl Map {
     pub fn new() -> Map {
-        let mut rooms: Vec<Box<dyn Room>> = Vec::new();
-        // start with a single GridRoom 20x15, matching previous map size
-        rooms.push(Box::new(GridRoom::new(20, 15)));
-        Map { rooms, current: 0 }
+        Map::generate(&GenerationOptions::default())
+    }
+
+    /// Build the world described by a save slot's generation options.
+    pub fn generate(options: &GenerationOptions) -> Map {
+        // the starting GridRoom (20x15 by default, matching previous map size) and a cellar
+        // reached by teleporter
+        let rooms: Vec<Box<dyn Room>> = (0..BUILTIN_ROOMS).filter_map(|i| Map::builtin_room(options, i)).collect();
+        Map { rooms, current: 0, blocked_tiles: Vec::new(), teleporters: Teleporters::default(), encounters: Vec::new(), exits: Vec::new(), sky: Sky::default(), generation: *options }
+    }
+
+    /// A room made from nothing but its data file: an outdoor room for `type=outdoor`,
+    /// otherwise an indoor one.
+    fn data_room(text: &str) -> Box<dyn Room> {
+        if text.lines().any(|line| line.trim() == "type=outdoor") {
+            Box::new(OutdoorRoom::from_data(text))
+        } else {
+            Box::new(GridRoom::from_data(text))
+        }
+    }
+
+    /// Room `i` as built before its data file is applied; None past the built-in rooms.
+    fn builtin_room(options: &GenerationOptions, i: usize) -> Option<Box<dyn Room>> {
+        match i {
+            0 => Some(Box::new(GridRoom::new(options.room_width, options.room_height).with_room_below(1))),
+            1 => Some(Box::new(GridRoom::cellar())),
+            _ => None,
+        }
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32)) -> GameResult {
-        self.rooms[self.current].draw(ctx, canvas, assets, scale, offset)
+        let room = &self.rooms[self.current];
+        room.draw(ctx, canvas, assets, scale, offset)?;
+        if room.outdoor() {
+            self.sky.draw_tint(ctx, canvas, room.width_pixels() as f32, room.height_pixels() as f32, scale, offset)?;
+        }
+        self.teleporters.draw(ctx, canvas, self.current, scale, offset)
     }
 
     pub fn is_solid_at_point(&self, x: f32, y: f32) -> bool {
@@ -43,20 +104,264 @@ impl Map {
         self.rooms[self.current].interact_tile(tx, ty)
     }
 
+    /// Use the plain tiles around a player standing on `standing` and facing `facing`: shut the
+    /// door they're standing in, or else loot the chest or work the door in front of them.
+    /// Furniture and NPCs are handled before this, by the caller.
+    pub fn use_tile(&mut self, standing: (i32, i32), facing: (i32, i32)) -> Option<TileUse> {
+        if standing.0 < 0 || standing.1 < 0 { return None; }
+        let (px, py) = (standing.0 as usize, standing.1 as usize);
+        if self.can_interact_tile(px, py, px, py) && self.interact_tile(px, py) {
+            return Some(TileUse::Door { open: self.is_door_open(px, py) });
+        }
+        if facing.0 < 0 || facing.1 < 0 { return None; }
+        let (tx, ty) = (facing.0 as usize, facing.1 as usize);
+        if !self.can_interact_tile(tx, ty, px, py) { return None; }
+        if let Some(gold) = self.open_chest(tx, ty) {
+            return Some(TileUse::Chest(gold));
+        }
+        self.interact_tile(tx, ty).then(|| TileUse::Door { open: self.is_door_open(tx, ty) })
+    }
+
     pub fn can_interact_tile(&self, tx: usize, ty: usize, player_tx: usize, player_ty: usize) -> bool {
         self.rooms[self.current].can_interact_tile(tx, ty, player_tx, player_ty)
     }
 
     pub fn is_movement_allowed(&self, from_x: f32, from_y: f32, to_x: f32, to_y: f32, w: f32, h: f32) -> bool {
-        self.rooms[self.current].is_movement_allowed(from_x, from_y, to_x, to_y, w, h)
+        let hits_blocked = crate::rooms::sweep((from_x, from_y), (to_x, to_y)).any(|(x, y)| self.blocked_tiles.iter().any(|&(tx, ty)| {
+            let (bx, by) = (tx as f32 * TILE_SIZE, ty as f32 * TILE_SIZE);
+            x < bx + TILE_SIZE && x + w > bx && y < by + TILE_SIZE && y + h > by
+        }));
+        !hits_blocked && self.rooms[self.current].is_movement_allowed(from_x, from_y, to_x, to_y, w, h)
+    }
+
+    /// Mark tiles (e.g. where NPCs stand) as impassable.
+    pub fn set_blocked_tiles(&mut self, tiles: Vec<(i32, i32)>) {
+        self.blocked_tiles = tiles;
+    }
+
+    /// Tiles marked impassable with `set_blocked_tiles`.
+    pub fn blocked_tiles(&self) -> &[(i32, i32)] {
+        &self.blocked_tiles
+    }
+
+    /// See `Room::describe_tile`; also says when an NPC blocks the tile.
+    pub fn describe_tile(&self, tile: (i32, i32)) -> Vec<String> {
+        if tile.0 < 0 || tile.1 < 0 { return Vec::new(); }
+        let mut lines = self.rooms[self.current].describe_tile(tile.0 as usize, tile.1 as usize);
+        if !lines.is_empty() && self.is_tile_blocked(tile) {
+            lines.push("blocked (someone stands here)".to_string());
+        }
+        lines
+    }
+
+    /// See `Room::prop_cells`.
+    pub fn prop_cells(&self) -> Vec<((usize, usize), Vec<crate::props::Side>)> {
+        self.rooms[self.current].prop_cells()
     }
 
+    pub fn is_tile_blocked(&self, tile: (i32, i32)) -> bool {
+        self.blocked_tiles.contains(&tile)
+    }
+
+
+    pub fn open_chest(&mut self, tx: usize, ty: usize) -> Option<u32> {
+        self.rooms[self.current].open_chest(tx, ty)
+    }
+
+    pub fn is_save_point(&self, tx: usize, ty: usize) -> bool {
+        self.rooms[self.current].is_save_point(tx, ty)
+    }
+
+    pub fn push_block(&mut self, tx: usize, ty: usize, dx: i32, dy: i32) -> bool {
+        self.rooms[self.current].push_block(tx, ty, dx, dy)
+    }
+
+    /// Advance the current room's animated tiles. Returns true when its spikes shoot up.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.rooms[self.current].update(dt)
+    }
+
+    pub fn spikes_up_at(&self, tile: (i32, i32)) -> bool {
+        tile.0 >= 0 && tile.1 >= 0 && self.rooms[self.current].spikes_up_at(tile.0 as usize, tile.1 as usize)
+    }
+
+    pub fn is_pit(&self, tile: (i32, i32)) -> bool {
+        tile.0 >= 0 && tile.1 >= 0 && self.rooms[self.current].is_pit(tile.0 as usize, tile.1 as usize)
+    }
+
+    /// Room the current room's pits lead down to.
+    pub fn room_below(&self) -> Option<usize> {
+        self.rooms[self.current].room_below().filter(|&room| room < self.rooms.len())
+    }
+
+    pub fn room_music(&self) -> Option<&str> {
+        self.rooms[self.current].music()
+    }
+
+    pub fn room_ambience(&self) -> Option<&str> {
+        self.rooms[self.current].ambience()
+    }
 
+    pub fn room_depth(&self) -> u32 {
+        self.rooms[self.current].depth()
+    }
+
+    pub fn room_name(&self) -> Option<&str> {
+        self.rooms[self.current].name()
+    }
+
+    pub fn entrance(&self) -> (i32, i32) {
+        self.rooms[self.current].entrance()
+    }
+
+    /// Whether enemies avoid the world-pixel point: walls, deep water and pits.
+    pub fn blocks_enemies_at_point(&self, x: f32, y: f32) -> bool {
+        let tile = ((x / TILE_SIZE).floor() as i32, (y / TILE_SIZE).floor() as i32);
+        self.is_solid_at_point(x, y) || self.water_at_point(x, y) == Some(Water::Deep) || self.is_pit(tile)
+    }
+
+    pub fn is_ledge(&self, tx: i32, ty: i32) -> bool {
+        tx >= 0 && ty >= 0 && self.rooms[self.current].is_ledge(tx as usize, ty as usize)
+    }
+
+    /// Water under a world-pixel point, if any.
+    pub fn water_at_point(&self, x: f32, y: f32) -> Option<Water> {
+        if x < 0.0 || y < 0.0 { return None; }
+        self.rooms[self.current].water_at((x / TILE_SIZE) as usize, (y / TILE_SIZE) as usize)
+    }
+
+    pub fn surface_at(&self, tile: (i32, i32)) -> Option<crate::footsteps::Surface> {
+        if tile.0 < 0 || tile.1 < 0 { return None; }
+        self.rooms[self.current].surface_at(tile.0 as usize, tile.1 as usize)
+    }
+
+    pub fn is_door_open(&self, tx: usize, ty: usize) -> bool {
+        self.rooms[self.current].is_door_open(tx, ty)
+    }
+
+    /// Apply each room's data file, if it has one. Data files numbered past the built-in rooms
+    /// (from mods) add rooms of their own, reached by teleporter or shrine.
+    pub fn load_room_data(&mut self, ctx: &Context) {
+        let props = Props::load(ctx);
+        let kinds = Arc::new(TileRegistry::load(ctx));
+        self.encounters.clear();
+        self.exits.clear();
+        for (i, room) in self.rooms.iter_mut().enumerate() {
+            room.set_tile_kinds(kinds.clone());
+            let text = crate::assets::read_text(ctx, &format!("/rooms/room{}.txt", i));
+            if let Some(text) = &text {
+                room.apply_data(text, &props);
+            }
+            self.encounters.push(text.as_deref().and_then(EncounterTable::parse));
+            self.exits.push(text.as_deref().map(teleport::parse_exits).unwrap_or_default());
+        }
+        while let Some(text) = crate::assets::read_text(ctx, &format!("/rooms/room{}.txt", self.rooms.len())) {
+            let mut room = Map::data_room(&text);
+            room.set_tile_kinds(kinds.clone());
+            room.apply_data(&text, &props);
+            self.encounters.push(EncounterTable::parse(&text));
+            self.exits.push(teleport::parse_exits(&text));
+            println!("Map: added room {} from its data file", self.rooms.len());
+            self.rooms.push(room);
+        }
+    }
+
+    /// Rebuild the current room from scratch and its data file as it is now, for iterating on
+    /// room files. Changes made in play (opened doors, looted chests) are dropped. Returns
+    /// false, keeping the room as it was, if a data-only room's file is gone.
+    pub fn reload_room(&mut self, ctx: &Context) -> bool {
+        let i = self.current;
+        let text = crate::assets::read_text(ctx, &format!("/rooms/room{}.txt", i));
+        let Some(mut room) = Map::builtin_room(&self.generation, i).or_else(|| text.as_deref().map(Map::data_room)) else {
+            println!("Map: room {} has no data file to reload from", i);
+            return false;
+        };
+        room.set_tile_kinds(Arc::new(TileRegistry::load(ctx)));
+        if let Some(text) = &text {
+            room.apply_data(text, &Props::load(ctx));
+        }
+        self.rooms[i] = room;
+        if let Some(encounters) = self.encounters.get_mut(i) {
+            *encounters = text.as_deref().and_then(EncounterTable::parse);
+        }
+        if let Some(exits) = self.exits.get_mut(i) {
+            *exits = text.as_deref().map(teleport::parse_exits).unwrap_or_default();
+        }
+        println!("Map: reloaded room {}", i);
+        true
+    }
+
+    /// Set the season and weather outdoor rooms are drawn with.
+    pub fn set_sky(&mut self, sky: Sky) {
+        self.sky = sky;
+    }
+
+    /// Shade the current room if it's dark, lit by its torches and the player's own glow
+    /// (`carried`), with rain or snow on top if it's outdoors.
+    pub fn draw_lighting(&self, ctx: &mut Context, canvas: &mut Canvas, carried: Light, scale: f32, offset: (f32, f32)) -> GameResult {
+        let room = &self.rooms[self.current];
+        let mut lighting = Lighting::new(room.darkness());
+        for light in room.lights(ctx.time.time_since_start().as_secs_f32()) {
+            lighting.add(light);
+        }
+        lighting.add(carried);
+        let (width, height) = (room.width_pixels() as f32, room.height_pixels() as f32);
+        lighting.draw(ctx, canvas, width, height, scale, offset)?;
+        if room.outdoor() {
+            self.sky.draw_weather(ctx, canvas, width, height, scale, offset)?;
+        }
+        Ok(())
+    }
+
+    pub fn interaction(&mut self, tile: (i32, i32)) -> Option<Interaction> {
+        if tile.0 < 0 || tile.1 < 0 { return None; }
+        self.rooms[self.current].interaction(tile.0 as usize, tile.1 as usize)
+    }
+
+    pub fn set_teleporters(&mut self, teleporters: Teleporters) {
+        self.teleporters = teleporters;
+    }
+
+    /// Where the teleport pad on `tile` of the current room leads, if there is one. Pads
+    /// pointing at rooms that don't exist are ignored.
+    pub fn teleport_destination(&self, tile: (i32, i32)) -> Option<Pad> {
+        self.teleporters.destination(self.current, tile).filter(|pad| pad.room < self.rooms.len())
+    }
+
+    /// Where the doorway on `tile` of the current room leads, if it's one. Exits into rooms
+    /// that don't exist are ignored.
+    pub fn exit_destination(&self, tile: (i32, i32)) -> Option<Pad> {
+        let exits = self.exits.get(self.current)?;
+        exits.iter().find(|(at, _)| *at == tile).map(|&(_, to)| to).filter(|pad| pad.room < self.rooms.len())
+    }
+
+    /// What has changed in each room during play, for rooms where anything has (see
+    /// `Room::changes`).
+    pub fn room_states(&self) -> Vec<(usize, String)> {
+        self.rooms.iter().map(|room| room.changes()).enumerate().filter(|(_, changes)| !changes.is_empty()).collect()
+    }
+
+    /// Put back the changes saved by `room_states`, ignoring rooms that no longer exist.
+    pub fn restore_room_states(&mut self, states: &[(usize, String)]) {
+        for (i, changes) in states {
+            match self.rooms.get_mut(*i) {
+                Some(room) => room.restore_changes(changes),
+                None => println!("Map: no room {} to restore, skipping", i),
+            }
+        }
+    }
+
+    /// The current room's random encounters, if it's a wild room.
+    pub fn encounter_table(&self) -> Option<&EncounterTable> {
+        self.encounters.get(self.current)?.as_ref()
+    }
+
+    pub fn current_room(&self) -> usize {
+        self.current
+    }
 
-    /// Add a new room and return its index.
-    pub fn add_room(&mut self, room: Box<dyn Room>) -> usize {
-        self.rooms.push(room);
-        self.rooms.len() - 1
+    pub fn room_count(&self) -> usize {
+        self.rooms.len()
     }
 
     /// Switch to another room index (no bounds checking - caller should ensure valid).
@@ -0,0 +1,232 @@
+//! Inline markup for dialogue text: coloured words, wobbling words, pauses, item names and
+//! key caps, written straight into room data and other text files.
+//!
+//! Tags are in angle brackets so they don't clash with `{variables}` (see `crate::game_vars`)
+//! or `[speaker]` tags (see `crate::dialogue`):
+//!
+//! ```text
+//! <red>Danger</> ahead. <wave>Ooh...</> <pause> Press <key confirm> to take the <item old_key>.
+//! ```
+//!
+//! `<red>` (or gold, green, blue, purple, gray, white) colours, `<wave>` bobs and `<shake>`
+//! jitters the text up to the next `</>` (or `</red>` and so on: any closing tag ends the
+//! innermost one, and they nest). `<pause>` holds the typing for half a second, `<pause 1.5>`
+//! for longer. `<item id>` is the item's name in its own colour and `<key action>` the key for
+//! an input action, drawn as a key cap. Anything else in angle brackets is left as it is.
+
+use ggez::graphics::{self, Canvas, Color, DrawParam, Text, TextFragment};
+use ggez::{Context, GameResult};
+
+/// Seconds a bare `<pause>` holds the typing for.
+const PAUSE: f32 = 0.5;
+/// Key cap text colour.
+const KEY_COLOR: Color = Color { r: 0.1, g: 0.1, b: 0.15, a: 1.0 };
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+pub enum Effect {
+    #[default]
+    None,
+    Wave,
+    Shake,
+}
+
+/// A run of text drawn the same way.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Span {
+    pub text: String,
+    pub color: Option<Color>,
+    pub effect: Effect,
+    /// Drawn as a key cap.
+    pub key: bool,
+}
+
+/// Text with its markup read: the styled spans, and where typing pauses.
+#[derive(Clone, Debug, PartialEq, Default)]
+pub struct Marked {
+    pub spans: Vec<Span>,
+    /// (characters in, seconds) for each pause.
+    pub pauses: Vec<(usize, f32)>,
+}
+
+fn named_color(name: &str) -> Option<Color> {
+    Some(match name {
+        "red" => Color::new(1.0, 0.35, 0.3, 1.0),
+        "gold" => Color::new(1.0, 0.85, 0.3, 1.0),
+        "green" => Color::new(0.45, 0.9, 0.45, 1.0),
+        "blue" => Color::new(0.5, 0.75, 1.0, 1.0),
+        "purple" => Color::new(0.8, 0.55, 1.0, 1.0),
+        "gray" => Color::new(0.65, 0.65, 0.7, 1.0),
+        "white" => Color::WHITE,
+        _ => return None,
+    })
+}
+
+/// The style a tag opens, if it's one that does.
+fn opening(tag: &str) -> Option<(Option<Color>, Effect)> {
+    match tag {
+        "wave" => Some((None, Effect::Wave)),
+        "shake" => Some((None, Effect::Shake)),
+        _ => named_color(tag).map(|color| (Some(color), Effect::None)),
+    }
+}
+
+/// Read the markup in `text`.
+pub fn parse(text: &str) -> Marked {
+    let mut marked = Marked::default();
+    // styles opened and not yet closed, innermost last
+    let mut open: Vec<(Option<Color>, Effect)> = Vec::new();
+    let mut typed = 0;
+    let mut rest = text;
+    while !rest.is_empty() {
+        let (plain, tag) = match rest.find('<').and_then(|start| Some((start, rest[start..].find('>')? + start))) {
+            Some((start, end)) => (&rest[..start], Some(&rest[start + 1..end])),
+            None => (rest, None),
+        };
+        let style = Span {
+            color: open.iter().rev().find_map(|(color, _)| *color),
+            effect: open.iter().rev().map(|(_, effect)| *effect).find(|e| *e != Effect::None).unwrap_or_default(),
+            ..Span::default()
+        };
+        marked.push(Span { text: plain.to_string(), ..style.clone() });
+        typed += plain.chars().count();
+        let Some(tag) = tag else { break };
+        rest = &rest[plain.len() + tag.len() + 2..];
+        let (name, arg) = tag.trim().split_once(' ').map_or((tag.trim(), ""), |(name, arg)| (name, arg.trim()));
+        if name.starts_with('/') {
+            open.pop();
+        } else if let Some(opened) = opening(name) {
+            open.push(opened);
+        } else if name == "pause" {
+            marked.pauses.push((typed, arg.parse().unwrap_or(PAUSE)));
+        } else if let Some(item) = crate::items::find(arg).filter(|_| name == "item") {
+            let (r, g, b) = item.color;
+            marked.push(Span { text: item.name.to_string(), color: Some(Color::new(r, g, b, 1.0)), ..style });
+            typed += item.name.chars().count();
+        } else if let Some(action) = crate::input::Action::ALL.into_iter().find(|action| name == "key" && action.name() == arg) {
+            let glyph = action.glyph();
+            typed += glyph.chars().count();
+            marked.push(Span { text: glyph, color: Some(KEY_COLOR), key: true, ..style });
+        } else {
+            // not ours: keep it as written
+            let written = format!("<{}>", tag);
+            typed += written.chars().count();
+            marked.push(Span { text: written, ..style });
+        }
+    }
+    marked
+}
+
+impl Marked {
+    /// Add a span, joining it onto the last one if they look the same.
+    fn push(&mut self, span: Span) {
+        if span.text.is_empty() { return; }
+        match self.spans.last_mut() {
+            Some(last) if !span.key && !last.key && (last.color, last.effect) == (span.color, span.effect) => last.text.push_str(&span.text),
+            _ => self.spans.push(span),
+        }
+    }
+
+    /// The text without its markup.
+    pub fn plain(&self) -> String {
+        self.spans.iter().map(|span| span.text.as_str()).collect()
+    }
+
+    /// The first `count` characters, still styled.
+    pub fn take(&self, count: usize) -> Vec<Span> {
+        let mut left = count;
+        let mut spans = Vec::new();
+        for span in &self.spans {
+            if left == 0 { break; }
+            let text: String = span.text.chars().take(left).collect();
+            left -= text.chars().count();
+            spans.push(Span { text, ..span.clone() });
+        }
+        spans
+    }
+}
+
+/// Draw `spans` from `origin`, wrapping at `width` pixels, `scale` pixels high; `t` (seconds)
+/// moves the waving and shaking.
+pub fn draw(ctx: &mut Context, canvas: &mut Canvas, spans: &[Span], origin: [f32; 2], width: f32, scale: f32, t: f32) -> GameResult {
+    let line_h = scale * 1.25;
+    let (mut x, mut y) = (0.0, 0.0);
+    let mut index = 0usize;
+    for span in spans {
+        let color = span.color.unwrap_or(Color::WHITE);
+        // words with the spaces after them, so wrapping only happens between words
+        for word in span.text.split_inclusive(' ') {
+            let measured = |ctx: &Context, s: &str| Text::new(TextFragment::new(s).scale(scale)).measure(ctx).map(|size| size.x);
+            let w = measured(ctx, word)?;
+            if x > 0.0 && x + measured(ctx, word.trim_end())? > width {
+                (x, y) = (0.0, y + line_h);
+            }
+            if span.key {
+                let cap = graphics::Rect::new(origin[0] + x - 3.0, origin[1] + y - 1.0, measured(ctx, word.trim_end())? + 6.0, line_h);
+                let mesh = graphics::Mesh::new_rounded_rectangle(ctx, graphics::DrawMode::fill(), cap, 4.0, Color::new(0.9, 0.9, 0.85, 1.0))?;
+                canvas.draw(&mesh, DrawParam::new());
+            }
+            if span.effect == Effect::None {
+                canvas.draw(&Text::new(TextFragment::new(word).scale(scale)), DrawParam::new().dest([origin[0] + x, origin[1] + y]).color(color));
+                index += word.chars().count();
+            } else {
+                // letter by letter, each nudged on its own
+                let mut cx = x;
+                for c in word.chars() {
+                    let letter = Text::new(TextFragment::new(c).scale(scale));
+                    let (dx, dy) = wobble(span.effect, t, index, scale);
+                    canvas.draw(&letter, DrawParam::new().dest([origin[0] + cx + dx, origin[1] + y + dy]).color(color));
+                    cx += letter.measure(ctx)?.x;
+                    index += 1;
+                }
+            }
+            x += w;
+        }
+    }
+    Ok(())
+}
+
+/// How far letter `index` is nudged at time `t`.
+fn wobble(effect: Effect, t: f32, index: usize, scale: f32) -> (f32, f32) {
+    match effect {
+        Effect::None => (0.0, 0.0),
+        Effect::Wave => (0.0, (t * 6.0 - index as f32 * 0.6).sin() * scale * 0.15),
+        Effect::Shake => {
+            // a jump to a new spot about 20 times a second, different for each letter
+            let step = (t * 20.0) as u32 ^ (index as u32).wrapping_mul(0x9e37_79b9);
+            let hash = crate::tiles::spot_hash(step as usize, index);
+            let jitter = |bits: u32| ((bits & 15) as f32 / 15.0 - 0.5) * scale * 0.12;
+            (jitter(hash), jitter(hash >> 4))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn tags_become_styled_spans_and_pauses() {
+        let marked = parse("A <red>big <wave>red</> fox</>.<pause> Hit <key confirm> for the <item old_key><pause 2>, <b>ok");
+        assert_eq!(marked.plain(), "A big red fox. Hit Z for the Old Key, <b>ok");
+        let styles: Vec<_> = marked.spans.iter().map(|s| (s.text.as_str(), s.color.is_some(), s.effect, s.key)).collect();
+        assert_eq!(styles, vec![
+            ("A ", false, Effect::None, false),
+            ("big ", true, Effect::None, false),
+            ("red", true, Effect::Wave, false),
+            (" fox", true, Effect::None, false),
+            (". Hit ", false, Effect::None, false),
+            ("Z", true, Effect::None, true),
+            (" for the ", false, Effect::None, false),
+            ("Old Key", true, Effect::None, false),
+            (", <b>ok", false, Effect::None, false),
+        ]);
+        assert_eq!(marked.spans[2].color, named_color("red"), "colours carry into nested tags");
+        assert_eq!(marked.pauses, vec![(14, PAUSE), (36, 2.0)]);
+
+        let typed = marked.take(7);
+        assert_eq!(typed.iter().map(|s| s.text.as_str()).collect::<Vec<_>>(), vec!["A ", "big ", "r"]);
+        assert_eq!(typed[2].effect, Effect::Wave);
+        assert_eq!(parse("no tags, 1 < 2").plain(), "no tags, 1 < 2");
+        assert_eq!(parse("<key nothing> <item nothing>").plain(), "<key nothing> <item nothing>");
+    }
+}
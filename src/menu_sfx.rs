@@ -0,0 +1,80 @@
+//! Menu sounds: a tick for moving the cursor, and cues for confirming, backing out and for
+//! things that can't be done.
+//!
+//! Each cue is `/Sfx/menu_<key>.wav` if there's a recording, otherwise synthesized (see
+//! `crate::assets`). They play at the SFX volume like every other effect.
+
+use ggez::Context;
+use ggez::audio::SoundSource;
+use ggez::input::keyboard::KeyCode;
+
+use crate::assets::Assets;
+use crate::input::Action;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Cue {
+    /// The cursor moved, or a setting changed.
+    Move,
+    Confirm,
+    /// A menu closed or stepped back.
+    Cancel,
+    /// Confirmed something that isn't possible.
+    Error,
+}
+
+impl Cue {
+    pub const ALL: [Cue; 4] = [Cue::Move, Cue::Confirm, Cue::Cancel, Cue::Error];
+
+    pub fn parse(s: &str) -> Option<Cue> {
+        Cue::ALL.into_iter().find(|cue| cue.key() == s)
+    }
+
+    /// Name in sound file names (`/Sfx/menu_<key>.wav`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Cue::Move => "move",
+            Cue::Confirm => "confirm",
+            Cue::Cancel => "cancel",
+            Cue::Error => "error",
+        }
+    }
+}
+
+/// Cue for `code` pressed in a menu, given whether it `changed` anything there. Moving into the
+/// end of a list stays quiet; confirming with nothing to show for it buzzes.
+pub fn cue(code: KeyCode, changed: bool) -> Option<Cue> {
+    match code {
+        KeyCode::Up | KeyCode::Down | KeyCode::Left | KeyCode::Right => changed.then_some(Cue::Move),
+        KeyCode::Space => Some(if changed { Cue::Confirm } else { Cue::Error }),
+        _ if Action::Confirm.matches(code) => Some(if changed { Cue::Confirm } else { Cue::Error }),
+        _ if Action::Cancel.matches(code) => Some(Cue::Cancel),
+        _ => changed.then_some(Cue::Move),
+    }
+}
+
+/// Play `cue`, with sound effects at `sfx_volume`.
+pub fn play(ctx: &mut Context, assets: &mut Assets, cue: Cue, sfx_volume: f32) {
+    if let Some(sound) = assets.menu_sound(cue) {
+        sound.set_volume(sfx_volume);
+        let _ = sound.play_detached(ctx);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keys_pick_cues_by_what_they_did() {
+        assert_eq!(cue(KeyCode::Down, true), Some(Cue::Move));
+        assert_eq!(cue(KeyCode::Down, false), None, "bumping the end of a list is quiet");
+        assert_eq!(cue(KeyCode::Z, true), Some(Cue::Confirm));
+        assert_eq!(cue(KeyCode::Z, false), Some(Cue::Error));
+        assert_eq!(cue(KeyCode::C, true), Some(Cue::Cancel));
+        assert_eq!(cue(KeyCode::Escape, false), Some(Cue::Cancel));
+        assert_eq!(cue(KeyCode::Q, false), None);
+        for c in Cue::ALL {
+            assert_eq!(Cue::parse(c.key()), Some(c));
+        }
+    }
+}
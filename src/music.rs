@@ -0,0 +1,227 @@
+//! Background music, crossfading between tracks.
+//!
+//! Tracks are named by key (`title`, `indoors`, `overworld`; see `Assets::music`). Switching to
+//! a new key fades the old track out while the new one fades in; `none` (or any key without a
+//! track) just fades to silence. Rooms pick their track with `music=key` in their data file.
+//!
+//! On top of the track, stems (`Music/stem_<layer>.wav`) fade in and out with the mood of
+//! play: a tension layer while enemies are about or the player is badly hurt, and a combat
+//! layer while an enemy is close. They loop on their own beat, so recordings should share a
+//! tempo; the synthesized stand-ins run at 120 bpm.
+
+use ggez::Context;
+use ggez::audio::SoundSource;
+
+use crate::assets::Assets;
+
+/// Length of a crossfade.
+const CROSSFADE_SECS: f32 = 1.0;
+/// How long a stem takes to fade all the way in or out.
+const LAYER_FADE_SECS: f32 = 2.0;
+/// Volume of a stem at full strength, under the track.
+const LAYER_VOLUME: f32 = 0.6;
+
+/// Stems layered over the room's track.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Layer {
+    Tension,
+    Combat,
+}
+
+impl Layer {
+    pub const ALL: [Layer; 2] = [Layer::Tension, Layer::Combat];
+
+    /// Name in stem file names (`Music/stem_<key>.wav`).
+    pub fn key(self) -> &'static str {
+        match self {
+            Layer::Tension => "tension",
+            Layer::Combat => "combat",
+        }
+    }
+}
+
+/// Which layers the game wants playing right now.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Mood {
+    pub tension: bool,
+    pub combat: bool,
+}
+
+impl Mood {
+    fn wants(&self, layer: Layer) -> bool {
+        match layer {
+            Layer::Tension => self.tension,
+            Layer::Combat => self.combat,
+        }
+    }
+}
+
+pub struct Music {
+    current: Option<String>,
+    /// Track being faded out.
+    outgoing: Option<String>,
+    /// Crossfade progress, 0 (just switched) to 1 (done).
+    fade: f32,
+    mood: Mood,
+    /// Strength of each layer in `Layer::ALL` order, 0 (silent, stopped) to 1.
+    layers: [f32; 2],
+}
+
+impl Music {
+    pub fn new() -> Music {
+        Music { current: None, outgoing: None, fade: 1.0, mood: Mood::default(), layers: [0.0; 2] }
+    }
+
+    /// Fade the layers towards `mood`.
+    pub fn set_mood(&mut self, mood: Mood) {
+        if mood != self.mood {
+            println!("Music: mood {:?}", mood);
+        }
+        self.mood = mood;
+    }
+
+    pub fn current(&self) -> Option<&str> {
+        self.current.as_deref()
+    }
+
+    /// Start fading over to `key`. Does nothing if it's already the current track.
+    pub fn switch_to(&mut self, ctx: &mut Context, assets: &mut Assets, key: &str) {
+        if self.current.as_deref() == Some(key) { return; }
+        // a crossfade still running is cut short
+        if let Some(old) = self.outgoing.take()
+            && let Some(track) = assets.music(&old) {
+            let _ = track.stop(ctx);
+        }
+        self.outgoing = self.current.replace(key.to_string());
+        self.fade = 0.0;
+        if let Some(track) = assets.music(key) {
+            let _ = track.play(ctx);
+            track.set_volume(0.0);
+        }
+        println!("Music: {:?} -> {}", self.outgoing, key);
+    }
+
+    /// Advance the crossfade and the layer fades.
+    pub fn update(&mut self, ctx: &mut Context, assets: &mut Assets, dt: f32) {
+        self.update_layers(ctx, assets, dt);
+        if self.fade >= 1.0 { return; }
+        let (incoming, outgoing) = self.advance(dt);
+        if let Some(track) = self.current.as_deref().and_then(|key| assets.music(key)) {
+            track.set_volume(incoming);
+        }
+        if let Some(track) = self.outgoing.as_deref().and_then(|key| assets.music(key)) {
+            track.set_volume(outgoing);
+            if self.fade >= 1.0 {
+                let _ = track.stop(ctx);
+            }
+        }
+        if self.fade >= 1.0 {
+            self.outgoing = None;
+        }
+    }
+
+    fn update_layers(&mut self, ctx: &mut Context, assets: &mut Assets, dt: f32) {
+        for (i, layer) in Layer::ALL.into_iter().enumerate() {
+            let (before, after) = self.fade_layer(i, dt);
+            if before == after { continue; }
+            let Some(stem) = assets.stem(layer) else { continue };
+            if before == 0.0 {
+                let _ = stem.play(ctx);
+            }
+            stem.set_volume(after * LAYER_VOLUME);
+            if after == 0.0 {
+                let _ = stem.stop(ctx);
+            }
+        }
+    }
+
+    /// Move layer `i` towards what the mood wants; returns its strength before and after.
+    fn fade_layer(&mut self, i: usize, dt: f32) -> (f32, f32) {
+        let before = self.layers[i];
+        let step = dt / LAYER_FADE_SECS;
+        self.layers[i] = if self.mood.wants(Layer::ALL[i]) { (before + step).min(1.0) } else { (before - step).max(0.0) };
+        (before, self.layers[i])
+    }
+
+    /// Move the crossfade along; returns the volumes of the current and outgoing tracks.
+    fn advance(&mut self, dt: f32) -> (f32, f32) {
+        self.fade = (self.fade + dt / CROSSFADE_SECS).min(1.0);
+        (self.fade, 1.0 - self.fade)
+    }
+
+    /// Layers currently sounding.
+    fn playing_layers(&self) -> impl Iterator<Item = Layer> + '_ {
+        Layer::ALL.into_iter().zip(self.layers).filter(|&(_, level)| level > 0.0).map(|(layer, _)| layer)
+    }
+
+    /// Hold every playing track where it is (window lost focus).
+    pub fn pause(&self, assets: &mut Assets) {
+        for key in self.current.iter().chain(&self.outgoing) {
+            if let Some(track) = assets.music(key) {
+                track.pause();
+            }
+        }
+        for layer in self.playing_layers() {
+            if let Some(stem) = assets.stem(layer) {
+                stem.pause();
+            }
+        }
+    }
+
+    /// Carry on from `pause`.
+    pub fn resume(&self, assets: &mut Assets) {
+        for key in self.current.iter().chain(&self.outgoing) {
+            if let Some(track) = assets.music(key) {
+                track.resume();
+            }
+        }
+        for layer in self.playing_layers() {
+            if let Some(stem) = assets.stem(layer) {
+                stem.resume();
+            }
+        }
+    }
+
+    /// Cut all music straight away, layers included.
+    pub fn stop(&mut self, ctx: &mut Context, assets: &mut Assets) {
+        for key in self.current.take().into_iter().chain(self.outgoing.take()) {
+            if let Some(track) = assets.music(&key) {
+                let _ = track.stop(ctx);
+            }
+        }
+        for layer in self.playing_layers().collect::<Vec<_>>() {
+            if let Some(stem) = assets.stem(layer) {
+                let _ = stem.stop(ctx);
+            }
+        }
+        self.fade = 1.0;
+        self.mood = Mood::default();
+        self.layers = [0.0; 2];
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn crossfade_volumes_meet_in_the_middle() {
+        let mut music = Music::new();
+        music.fade = 0.0;
+        assert_eq!(music.advance(CROSSFADE_SECS / 2.0), (0.5, 0.5));
+        assert_eq!(music.advance(CROSSFADE_SECS), (1.0, 0.0), "never overshoots");
+    }
+
+    #[test]
+    fn layers_fade_towards_the_mood() {
+        let mut music = Music::new();
+        music.set_mood(Mood { tension: true, combat: false });
+        assert_eq!(music.fade_layer(0, LAYER_FADE_SECS / 2.0), (0.0, 0.5));
+        assert_eq!(music.fade_layer(1, 1.0), (0.0, 0.0), "combat stays out");
+        assert_eq!(music.fade_layer(0, LAYER_FADE_SECS), (0.5, 1.0));
+        assert_eq!(music.playing_layers().collect::<Vec<_>>(), vec![Layer::Tension]);
+        music.set_mood(Mood::default());
+        assert_eq!(music.fade_layer(0, LAYER_FADE_SECS * 2.0), (1.0, 0.0));
+        assert_eq!(music.playing_layers().count(), 0);
+    }
+}
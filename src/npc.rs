@@ -0,0 +1,57 @@
+//! Non-player characters that stand on a tile and react to the interact key.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawParam};
+
+use crate::assets::Assets;
+use crate::barter::Barter;
+use crate::items::ItemId;
+use crate::map::TILE_SIZE;
+use crate::shop::Shop;
+
+pub enum NpcRole {
+    /// Opens a shop when talked to.
+    Merchant(Shop),
+    /// Joins the party when talked to (if there's room), at this level and wielding this.
+    Recruit { level: u32, weapon: Option<ItemId> },
+    /// Swaps items for items (see `crate::barter`).
+    Trader(Barter),
+    /// Sends the player into the arena (see `crate::arena`).
+    ArenaMaster,
+}
+
+pub struct Npc {
+    pub name: String,
+    /// Tile the NPC stands on; NPCs don't move.
+    pub tile: (i32, i32),
+    pub role: NpcRole,
+    tint: Color,
+}
+
+impl Npc {
+    pub fn merchant(name: &str, tile: (i32, i32), shop: Shop) -> Npc {
+        Npc { name: name.to_string(), tile, role: NpcRole::Merchant(shop), tint: Color::new(0.75, 0.5, 1.0, 1.0) }
+    }
+
+    pub fn recruit(name: &str, tile: (i32, i32), level: u32, weapon: Option<ItemId>) -> Npc {
+        Npc { name: name.to_string(), tile, role: NpcRole::Recruit { level, weapon }, tint: Color::new(0.55, 0.95, 0.6, 1.0) }
+    }
+
+    pub fn trader(name: &str, tile: (i32, i32), barter: Barter) -> Npc {
+        Npc { name: name.to_string(), tile, role: NpcRole::Trader(barter), tint: Color::new(1.0, 0.75, 0.4, 1.0) }
+    }
+
+    pub fn arena_master(name: &str, tile: (i32, i32)) -> Npc {
+        Npc { name: name.to_string(), tile, role: NpcRole::ArenaMaster, tint: Color::new(1.0, 0.45, 0.45, 1.0) }
+    }
+
+    /// NPCs reuse the player sprite with a tint until they get their own art.
+    pub fn draw_scaled(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32)) -> GameResult {
+        let center_x = (self.tile.0 as f32 + 0.5) * TILE_SIZE;
+        let center_y = (self.tile.1 as f32 + 0.5) * TILE_SIZE;
+        let dest = ggez::mint::Point2 { x: offset.0 + center_x * scale, y: offset.1 + center_y * scale };
+        let img_scale = scale * TILE_SIZE / assets.player.width() as f32;
+        canvas.draw(&assets.player, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]).color(self.tint));
+        Ok(())
+    }
+}
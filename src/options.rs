@@ -2,13 +2,184 @@ use ggez::{Context, GameResult};
 use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
 use ggez::input::keyboard::KeyCode;
 
+use crate::input::Action;
+use crate::postfx::Effect;
+
+/// Choices for the tap-to-turn delay (seconds); 0 turns it off.
+const TURN_DELAYS: [f32; 5] = [0.0, 0.06, 0.1, 0.15, 0.2];
+/// Volume steps for ambient sound loops and sound effects.
+const VOLUMES: [f32; 6] = [0.0, 0.2, 0.4, 0.6, 0.8, 1.0];
+/// Default for `Options::ambience_volume`.
+pub const DEFAULT_AMBIENCE_VOLUME: f32 = 0.6;
+/// Default for `Options::sfx_volume`.
+pub const DEFAULT_SFX_VOLUME: f32 = 0.8;
+
+/// Pace of dialogue typing and battle animations.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Speed {
+    Slow,
+    Normal,
+    Fast,
+    /// No waiting at all.
+    Instant,
+}
+
+impl Speed {
+    const ALL: [Speed; 4] = [Speed::Slow, Speed::Normal, Speed::Fast, Speed::Instant];
+
+    /// How many times faster than normal things run.
+    pub fn factor(self) -> f32 {
+        match self {
+            Speed::Slow => 0.5,
+            Speed::Normal => 1.0,
+            Speed::Fast => 2.0,
+            Speed::Instant => f32::INFINITY,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Speed::Slow => "Slow",
+            Speed::Normal => "Normal",
+            Speed::Fast => "Fast",
+            Speed::Instant => "Instant",
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            Speed::Slow => "slow",
+            Speed::Normal => "normal",
+            Speed::Fast => "fast",
+            Speed::Instant => "instant",
+        }
+    }
+
+    fn parse(s: &str) -> Option<Speed> {
+        Speed::ALL.into_iter().find(|speed| speed.key() == s)
+    }
+
+    fn cycled(self, forward: bool) -> Speed {
+        let current = Speed::ALL.iter().position(|&s| s == self).unwrap_or(1);
+        let n = Speed::ALL.len();
+        Speed::ALL[if forward { (current + 1) % n } else { (current + n - 1) % n }]
+    }
+}
+
+/// Shape of the game's view of the world.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Aspect {
+    /// 4:3, like the handhelds the art is drawn for.
+    Standard,
+    /// 16:9, showing more of each room to the sides.
+    Wide,
+}
+
+impl Aspect {
+    pub fn ratio(self) -> f32 {
+        match self {
+            Aspect::Standard => 4.0 / 3.0,
+            Aspect::Wide => 16.0 / 9.0,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Aspect::Standard => "4:3",
+            Aspect::Wide => "16:9",
+        }
+    }
+
+    fn toggled(self) -> Aspect {
+        match self {
+            Aspect::Standard => Aspect::Wide,
+            Aspect::Wide => Aspect::Standard,
+        }
+    }
+
+    /// The largest rectangle of this shape centred in a `window` sized screen; the world and
+    /// HUD are drawn inside it, with bars either side (or above and below) when they don't match.
+    pub fn view_area(self, window: (f32, f32)) -> graphics::Rect {
+        let (w, h) = window;
+        let (area_w, area_h) = if w / h >= self.ratio() { (h * self.ratio(), h) } else { (w, w / self.ratio()) };
+        graphics::Rect::new((w - area_w) / 2.0, (h - area_h) / 2.0, area_w, area_h)
+    }
+}
+
+/// What fills the bars around the view when the window is a different shape from it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Border {
+    /// The window's blue background.
+    Blue,
+    Black,
+    Grey,
+    /// Floor planks, tiled.
+    Planks,
+    /// Stone wall, tiled.
+    Stone,
+}
+
+impl Border {
+    const ALL: [Border; 5] = [Border::Blue, Border::Black, Border::Grey, Border::Planks, Border::Stone];
+
+    fn name(self) -> &'static str {
+        match self {
+            Border::Blue => "Blue",
+            Border::Black => "Black",
+            Border::Grey => "Grey",
+            Border::Planks => "Planks",
+            Border::Stone => "Stone",
+        }
+    }
+
+    fn cycled(self, forward: bool) -> Border {
+        let current = Border::ALL.iter().position(|&b| b == self).unwrap_or(0);
+        let n = Border::ALL.len();
+        Border::ALL[if forward { (current + 1) % n } else { (current + n - 1) % n }]
+    }
+}
+
+/// How sprites are sampled when scaled up.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextureFilter {
+    /// Hard pixel edges, for the 32px art.
+    Nearest,
+    Linear,
+}
+
+impl TextureFilter {
+    pub fn sampler(self) -> graphics::Sampler {
+        match self {
+            TextureFilter::Nearest => graphics::Sampler::nearest_clamp(),
+            TextureFilter::Linear => graphics::Sampler::linear_clamp(),
+        }
+    }
+
+    fn key(self) -> &'static str {
+        match self {
+            TextureFilter::Nearest => "nearest",
+            TextureFilter::Linear => "linear",
+        }
+    }
+
+    fn toggled(self) -> TextureFilter {
+        match self {
+            TextureFilter::Nearest => TextureFilter::Linear,
+            TextureFilter::Linear => TextureFilter::Nearest,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
 pub enum OptionsView {
     Main,
     Video,
+    Audio,
+    Gameplay,
+    Accessibility,
 }
 
 pub struct Options {
-    pub visible: bool,
     pub view: OptionsView,
     pub selected: usize,
     pub scroll_offset: usize,
@@ -16,31 +187,159 @@ pub struct Options {
     // Video settings
     pub fullscreen: bool,
     pub show_fps: bool,
+    /// Frame-time graph and per-phase timings (see `crate::perf`).
+    pub perf_overlay: bool,
     pub gba_refresh_rate: bool,
-    // resolution locked to 4:3, shown but disabled
-    pub resolution: &'static str,
+    /// Squash colours down to the GBA's 15-bit palette.
+    pub gba_colors: bool,
+    pub texture_filter: TextureFilter,
+    /// Scale the game by whole multiples only, in a window as well as fullscreen.
+    pub pixel_perfect: bool,
+    /// Shape of the view; windowed mode resizes the window to match.
+    pub aspect: Aspect,
+    /// Fill for the bars around the view.
+    pub border: Border,
+    /// Retro post-process shader over the whole frame.
+    pub effect: Effect,
+    /// Keep the last few seconds of play so F9 can save them as a GIF.
+    pub clip_recording: bool,
+
+    // Audio settings
+    /// Volume of room ambience loops, 0 to 1 (see `crate::ambience`).
+    pub ambience_volume: f32,
+    /// Volume of sound effects (footsteps, splashes, jingles, dialogue blips), 0 to 1.
+    pub sfx_volume: f32,
+
+    // Gameplay settings
+    pub speedrun_timer: bool,
+    /// How long a direction must be held before stepping (see `Player::turn_threshold`).
+    pub turn_threshold: f32,
+    /// How fast dialogue types out.
+    pub text_speed: Speed,
+    /// Run without holding Shift; Shift then walks.
+    pub auto_run: bool,
+    /// How long battles pause on each round's hits.
+    pub battle_speed: Speed,
+    /// Numbers floating up from whoever takes damage in battle.
+    pub damage_numbers: bool,
+    /// Clicking a tile walks the player there.
+    pub click_to_move: bool,
+    /// One-time hints the first time a mechanic comes up (see `crate::tutorial`).
+    pub tutorial_hints: bool,
+
+    // Accessibility settings
+    pub colorblind: bool,
+    pub high_contrast: bool,
+    /// Controller vibration (see `crate::rumble`).
+    pub rumble: bool,
+    /// How hard the controller vibrates, one of `rumble::STRENGTHS`.
+    pub rumble_strength: f32,
 }
 
 impl Options {
     pub fn new() -> Options {
-        Options { visible: false, view: OptionsView::Main, selected: 0, scroll_offset: 0, fullscreen: false, show_fps: false, gba_refresh_rate: false, resolution: "1024x768 (4:3)" }
+        Options { view: OptionsView::Main, selected: 0, scroll_offset: 0, fullscreen: false, show_fps: false, perf_overlay: false, gba_refresh_rate: false, gba_colors: false, texture_filter: TextureFilter::Nearest, pixel_perfect: false, aspect: Aspect::Standard, border: Border::Blue, effect: Effect::Off, clip_recording: false, ambience_volume: DEFAULT_AMBIENCE_VOLUME, sfx_volume: DEFAULT_SFX_VOLUME, speedrun_timer: false, turn_threshold: crate::player::DEFAULT_TURN_THRESHOLD, text_speed: Speed::Normal, auto_run: false, battle_speed: Speed::Normal, damage_numbers: true, click_to_move: false, tutorial_hints: true, colorblind: false, high_contrast: false, rumble: true, rumble_strength: crate::rumble::DEFAULT_STRENGTH }
     }
 
-    pub fn toggle(&mut self) {
-        self.visible = !self.visible;
+    /// Settings file in the active profile's config folder.
+    fn path() -> std::path::PathBuf {
+        crate::save::config_dir().join("options.txt")
+    }
+
+    /// Load the active profile's settings, or the defaults if it has none yet.
+    pub fn load() -> Options {
+        std::fs::read_to_string(Options::path()).map(|t| Options::from_text(&t)).unwrap_or_else(|_| Options::new())
+    }
+
+    pub fn save(&self) {
+        let result = std::fs::create_dir_all(crate::save::config_dir()).and_then(|_| std::fs::write(Options::path(), self.to_text()));
+        if let Err(e) = result {
+            println!("Options: failed to write settings: {}", e);
+        }
+    }
+
+    /// Settings as `key=value` lines, like save files.
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!("fullscreen={}\n", self.fullscreen));
+        s.push_str(&format!("show_fps={}\n", self.show_fps));
+        s.push_str(&format!("perf_overlay={}\n", self.perf_overlay));
+        s.push_str(&format!("gba_refresh_rate={}\n", self.gba_refresh_rate));
+        s.push_str(&format!("gba_colors={}\n", self.gba_colors));
+        s.push_str(&format!("texture_filter={}\n", self.texture_filter.key()));
+        s.push_str(&format!("pixel_perfect={}\n", self.pixel_perfect));
+        s.push_str(&format!("aspect={}\n", self.aspect.name()));
+        s.push_str(&format!("border={}\n", self.border.name().to_lowercase()));
+        s.push_str(&format!("effect={}\n", self.effect.key()));
+        s.push_str(&format!("clip_recording={}\n", self.clip_recording));
+        s.push_str(&format!("ambience_volume={}\n", self.ambience_volume));
+        s.push_str(&format!("sfx_volume={}\n", self.sfx_volume));
+        s.push_str(&format!("speedrun_timer={}\n", self.speedrun_timer));
+        s.push_str(&format!("turn_threshold={}\n", self.turn_threshold));
+        s.push_str(&format!("text_speed={}\n", self.text_speed.key()));
+        s.push_str(&format!("auto_run={}\n", self.auto_run));
+        s.push_str(&format!("battle_speed={}\n", self.battle_speed.key()));
+        s.push_str(&format!("damage_numbers={}\n", self.damage_numbers));
+        s.push_str(&format!("click_to_move={}\n", self.click_to_move));
+        s.push_str(&format!("tutorial_hints={}\n", self.tutorial_hints));
+        s.push_str(&format!("colorblind={}\n", self.colorblind));
+        s.push_str(&format!("high_contrast={}\n", self.high_contrast));
+        s.push_str(&format!("rumble={}\n", self.rumble));
+        s.push_str(&format!("rumble_strength={}\n", self.rumble_strength));
+        s
+    }
+
+    /// Inverse of `to_text`; missing or bad values keep their defaults.
+    pub fn from_text(text: &str) -> Options {
+        let mut options = Options::new();
+        for (key, value) in text.lines().filter_map(|l| l.split_once('=')) {
+            let value = value.trim();
+            let flag = value == "true";
+            match key.trim() {
+                "fullscreen" => options.fullscreen = flag,
+                "show_fps" => options.show_fps = flag,
+                "perf_overlay" => options.perf_overlay = flag,
+                "gba_refresh_rate" => options.gba_refresh_rate = flag,
+                "gba_colors" => options.gba_colors = flag,
+                "pixel_perfect" => options.pixel_perfect = flag,
+                "border" => options.border = Border::ALL.into_iter().find(|b| b.name().eq_ignore_ascii_case(value)).unwrap_or(options.border),
+                "effect" => options.effect = Effect::parse(value).unwrap_or(options.effect),
+                "clip_recording" => options.clip_recording = flag,
+                "aspect" => options.aspect = if value == Aspect::Wide.name() { Aspect::Wide } else { Aspect::Standard },
+                "texture_filter" => options.texture_filter = if value == "linear" { TextureFilter::Linear } else { TextureFilter::Nearest },
+                "ambience_volume" => options.ambience_volume = value.parse::<f32>().map_or(options.ambience_volume, |v| v.clamp(0.0, 1.0)),
+                "sfx_volume" => options.sfx_volume = value.parse::<f32>().map_or(options.sfx_volume, |v| v.clamp(0.0, 1.0)),
+                "speedrun_timer" => options.speedrun_timer = flag,
+                "turn_threshold" => options.turn_threshold = value.parse::<f32>().map_or(options.turn_threshold, |v| v.max(0.0)),
+                "text_speed" => options.text_speed = Speed::parse(value).unwrap_or(options.text_speed),
+                "auto_run" => options.auto_run = flag,
+                "battle_speed" => options.battle_speed = Speed::parse(value).unwrap_or(options.battle_speed),
+                "damage_numbers" => options.damage_numbers = flag,
+                "click_to_move" => options.click_to_move = flag,
+                "tutorial_hints" => options.tutorial_hints = flag,
+                "colorblind" => options.colorblind = flag,
+                "high_contrast" => options.high_contrast = flag,
+                "rumble" => options.rumble = flag,
+                "rumble_strength" => options.rumble_strength = value.parse::<f32>().map_or(options.rumble_strength, |v| v.clamp(0.0, 1.0)),
+                _ => {}
+            }
+        }
+        options
+    }
+
+    /// Reset to the top of the main page, for opening the menu.
+    pub fn open(&mut self) {
         self.view = OptionsView::Main;
         self.selected = 0;
         self.scroll_offset = 0;
     }
 
     pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
-        if !self.visible { return Ok(()); }
-
         // centered blue box with white inner border
     let size = ctx.gfx.window().inner_size();
     let (w, h) = (size.width as f32, size.height as f32);
         let box_w = 400.0;
-        let box_h = 300.0;
+        let box_h = 340.0;
         let left = (w - box_w) / 2.0;
         let top = (h - box_h) / 2.0;
 
@@ -56,7 +355,7 @@ impl Options {
                 let title = Text::new(TextFragment::new("Options").scale(32.0));
                 canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 20.0]).color(Color::WHITE));
 
-                let opts = vec!["Video", "Return to Game", "Exit to Desktop"];
+                let opts = ["Video", "Audio", "Gameplay", "Accessibility", "Return to Game", "Exit to Desktop"];
                 for (i, o) in opts.iter().enumerate() {
                     let y = top + 80.0 + i as f32 * 40.0;
                     let txt = Text::new(TextFragment::new(*o).scale(24.0));
@@ -66,7 +365,7 @@ impl Options {
                     // draw yellow outline around selected entry
                     if i == self.selected {
                         let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 34.0);
-                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, Color::new(1.0, 0.85, 0.05, 1.0))?;
+                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
                         canvas.draw(&sel_box, DrawParam::new());
                     }
                 }
@@ -76,11 +375,18 @@ impl Options {
                 canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 20.0]).color(Color::WHITE));
 
                 // Define all video options
-                let video_options = vec![
-                    (format!("{} (locked)", self.resolution), Color::new(0.7,0.7,0.7,1.0), false), // Resolution - not interactive
+                let video_options = [
+                    (format!("Aspect  <  {}  >", self.aspect.name()), Color::WHITE, true),
                     (format!("Fullscreen  <  {}  >", if self.fullscreen { "On" } else { "Off" }), Color::WHITE, true),
                     (format!("FPS Counter  <  {}  >", if self.show_fps { "On" } else { "Off" }), Color::WHITE, true),
+                    (format!("Frame Timings  <  {}  >", if self.perf_overlay { "On" } else { "Off" }), Color::WHITE, true),
                     (format!("GBA Refresh Rate  <  {}  >", if self.gba_refresh_rate { "On" } else { "Off" }), Color::WHITE, true),
+                    (format!("GBA Colors  <  {}  >", if self.gba_colors { "On" } else { "Off" }), Color::WHITE, true),
+                    (format!("Texture Filter  <  {}  >", if self.texture_filter == TextureFilter::Nearest { "Nearest" } else { "Linear" }), Color::WHITE, true),
+                    (format!("Pixel Perfect  <  {}  >", if self.pixel_perfect { "On" } else { "Off" }), Color::WHITE, true),
+                    (format!("Borders  <  {}  >", self.border.name()), Color::WHITE, true),
+                    (format!("Retro Shader  <  {}  >", self.effect.name()), Color::WHITE, true),
+                    (format!("Clip Recording (F9)  <  {}  >", if self.clip_recording { "On" } else { "Off" }), Color::WHITE, true),
                     ("Back".to_string(), Color::WHITE, true),
                 ];
 
@@ -100,7 +406,7 @@ impl Options {
                     // Highlight selected item
                     if actual_index == self.selected {
                         let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 30.0);
-                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, Color::new(1.0, 0.85, 0.05, 1.0))?;
+                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
                         canvas.draw(&sel_box, DrawParam::new());
                     }
                 }
@@ -121,7 +427,7 @@ impl Options {
                     for i in 0..total_items {
                         let line_y = scroll_start_y + (i as f32 / total_items as f32) * scroll_height;
                         let line_color = if i == self.selected {
-                            Color::new(1.0, 0.85, 0.05, 1.0) // Yellow for current selection
+                            crate::palette::selection() // Highlight for current selection
                         } else {
                             Color::new(0.7, 0.7, 0.7, 0.8) // Gray for other items
                         };
@@ -132,92 +438,303 @@ impl Options {
                     }
                 }
             }
+            OptionsView::Audio => {
+                let title = Text::new(TextFragment::new("Audio").scale(28.0));
+                canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 20.0]).color(Color::WHITE));
+
+                let audio_options = [
+                    if self.ambience_volume > 0.0 { format!("Ambience  <  {:.0}%  >", self.ambience_volume * 100.0) } else { "Ambience  <  Off  >".to_string() },
+                    if self.sfx_volume > 0.0 { format!("Sound Effects  <  {:.0}%  >", self.sfx_volume * 100.0) } else { "Sound Effects  <  Off  >".to_string() },
+                    "Back".to_string(),
+                ];
+                for (i, text) in audio_options.iter().enumerate() {
+                    let y = top + 80.0 + i as f32 * 40.0;
+                    let txt = Text::new(TextFragment::new(text).scale(20.0));
+                    canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(Color::WHITE));
+                    if i == self.selected {
+                        let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 30.0);
+                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                        canvas.draw(&sel_box, DrawParam::new());
+                    }
+                }
+            }
+            OptionsView::Gameplay => {
+                let title = Text::new(TextFragment::new("Gameplay").scale(28.0));
+                canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 20.0]).color(Color::WHITE));
+
+                let gameplay_options = [
+                    format!("Speedrun Timer  <  {}  >", if self.speedrun_timer { "On" } else { "Off" }),
+                    if self.turn_threshold > 0.0 { format!("Tap to Turn  <  {:.2}s  >", self.turn_threshold) } else { "Tap to Turn  <  Off  >".to_string() },
+                    format!("Text Speed  <  {}  >", self.text_speed.name()),
+                    format!("Auto-Run  <  {}  >", if self.auto_run { "On" } else { "Off" }),
+                    format!("Battle Speed  <  {}  >", self.battle_speed.name()),
+                    format!("Damage Numbers  <  {}  >", if self.damage_numbers { "On" } else { "Off" }),
+                    format!("Click to Move  <  {}  >", if self.click_to_move { "On" } else { "Off" }),
+                    format!("Tutorial Hints  <  {}  >", if self.tutorial_hints { "On" } else { "Off" }),
+                    "Back".to_string(),
+                ];
+                // nine rows, so a little tighter than the other pages
+                for (i, text) in gameplay_options.iter().enumerate() {
+                    let y = top + 70.0 + i as f32 * 29.0;
+                    let txt = Text::new(TextFragment::new(text).scale(20.0));
+                    canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(Color::WHITE));
+                    if i == self.selected {
+                        let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 30.0);
+                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                        canvas.draw(&sel_box, DrawParam::new());
+                    }
+                }
+            }
+            OptionsView::Accessibility => {
+                let title = Text::new(TextFragment::new("Accessibility").scale(28.0));
+                canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 20.0]).color(Color::WHITE));
+
+                let accessibility_options = [
+                    format!("Colorblind Mode  <  {}  >", if self.colorblind { "On" } else { "Off" }),
+                    format!("High Contrast  <  {}  >", if self.high_contrast { "On" } else { "Off" }),
+                    format!("Rumble  <  {}  >", if self.rumble { "On" } else { "Off" }),
+                    format!("Rumble Strength  <  {:.0}%  >", self.rumble_strength * 100.0),
+                    "Back".to_string(),
+                ];
+                for (i, text) in accessibility_options.iter().enumerate() {
+                    let y = top + 80.0 + i as f32 * 40.0;
+                    let txt = Text::new(TextFragment::new(text).scale(20.0));
+                    canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(Color::WHITE));
+                    if i == self.selected {
+                        let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 30.0);
+                        let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                        canvas.draw(&sel_box, DrawParam::new());
+                    }
+                }
+            }
         }
 
         Ok(())
     }
 
-    /// Handle key input while the options menu is visible. Returns
+    /// Step to the next (or previous) entry of `TURN_DELAYS`, wrapping around.
+    fn cycle_turn_threshold(&mut self, forward: bool) {
+        let current = TURN_DELAYS.iter().position(|d| (d - self.turn_threshold).abs() < 0.001).unwrap_or(0);
+        let n = TURN_DELAYS.len();
+        let next = if forward { (current + 1) % n } else { (current + n - 1) % n };
+        self.turn_threshold = TURN_DELAYS[next];
+    }
+
+    /// Step to the next (or previous) entry of `rumble::STRENGTHS`, wrapping around.
+    fn cycle_rumble_strength(&mut self, forward: bool) {
+        let strengths = crate::rumble::STRENGTHS;
+        let current = strengths.iter().position(|s| (s - self.rumble_strength).abs() < 0.001).unwrap_or(0);
+        let n = strengths.len();
+        self.rumble_strength = strengths[if forward { (current + 1) % n } else { (current + n - 1) % n }];
+    }
+
+    /// Step the ambience volume up or down through `VOLUMES`, wrapping around.
+    fn cycle_ambience_volume(&mut self, forward: bool) {
+        self.ambience_volume = cycle_volume(self.ambience_volume, forward);
+    }
+
+    fn cycle_sfx_volume(&mut self, forward: bool) {
+        self.sfx_volume = cycle_volume(self.sfx_volume, forward);
+    }
+
+    /// Handle key input while the options menu is open. Returns
     /// Some(action) when an action should be performed by the game (like Exit).
     pub fn handle_key(&mut self, key: KeyCode) -> Option<&'static str> {
-        if !self.visible { return None; }
-
         match self.view {
             OptionsView::Main => {
                 match key {
-                    KeyCode::Up => { if self.selected > 0 { self.selected -= 1; } }
-                    KeyCode::Down => { self.selected = (self.selected + 1).min(2); }
-                    KeyCode::Return | KeyCode::Space | KeyCode::Z => {
+                    KeyCode::Up if self.selected > 0 => { self.selected -= 1; }
+                    KeyCode::Down => { self.selected = (self.selected + 1).min(5); }
+                    k if k == KeyCode::Space || Action::Confirm.matches(k) => {
                         match self.selected {
                             0 => { self.view = OptionsView::Video; self.selected = 0; self.scroll_offset = 0; }
-                            1 => { self.visible = false; return Some("return"); }
-                            2 => { return Some("exit"); }
+                            1 => { self.view = OptionsView::Audio; self.selected = 0; }
+                            2 => { self.view = OptionsView::Gameplay; self.selected = 0; }
+                            3 => { self.view = OptionsView::Accessibility; self.selected = 0; }
+                            4 => { return Some("return"); }
+                            5 => { return Some("exit"); }
                             _ => {}
                         }
                     }
-                    KeyCode::Escape => { self.visible = false; return Some("return"); }
+                    k if Action::Cancel.matches(k) => { return Some("return"); }
                     _ => {}
                 }
             }
             OptionsView::Video => {
-                let total_options = 5; // Resolution, Fullscreen, FPS Counter, GBA Refresh Rate, Back
+                let total_options = 12; // Aspect, Fullscreen, FPS Counter, Frame Timings, GBA Refresh Rate, GBA Colors, Texture Filter, Pixel Perfect, Borders, Retro Shader, Clip Recording, Back
                 let max_visible = 3;
                 
                 match key {
-                    KeyCode::Up => { 
-                        if self.selected > 0 { 
-                            self.selected -= 1; 
-                            // Adjust scroll if needed
-                            if self.selected < self.scroll_offset {
-                                self.scroll_offset = self.selected;
-                            }
-                        } 
-                    }
-                    KeyCode::Down => { 
-                        if self.selected < total_options - 1 { 
-                            self.selected += 1; 
-                            // Adjust scroll if needed
-                            if self.selected >= self.scroll_offset + max_visible {
-                                self.scroll_offset = self.selected - max_visible + 1;
-                            }
-                        } 
+                    KeyCode::Up if self.selected > 0 => {
+                        self.selected -= 1;
+                        // Adjust scroll if needed
+                        if self.selected < self.scroll_offset {
+                            self.scroll_offset = self.selected;
+                        }
+                    }
+                    KeyCode::Down if self.selected < total_options - 1 => {
+                        self.selected += 1;
+                        // Adjust scroll if needed
+                        if self.selected >= self.scroll_offset + max_visible {
+                            self.scroll_offset = self.selected - max_visible + 1;
+                        }
                     }
                     KeyCode::Left => {
-                        if self.selected == 1 {
+                        if self.selected == 0 {
+                            self.aspect = self.aspect.toggled();
+                            return Some("set_aspect");
+                        } else if self.selected == 1 {
                             self.fullscreen = !self.fullscreen;
                             return Some("toggle_fullscreen");
                         } else if self.selected == 2 {
                             self.show_fps = !self.show_fps;
                             return Some("toggle_fps");
                         } else if self.selected == 3 {
+                            self.perf_overlay = !self.perf_overlay;
+                            return Some("toggle_perf_overlay");
+                        } else if self.selected == 4 {
                             self.gba_refresh_rate = !self.gba_refresh_rate;
                             return Some("toggle_gba_refresh");
+                        } else if self.selected == 5 {
+                            self.gba_colors = !self.gba_colors;
+                            return Some("toggle_gba_colors");
+                        } else if self.selected == 6 {
+                            self.texture_filter = self.texture_filter.toggled();
+                            return Some("toggle_texture_filter");
+                        } else if self.selected == 7 {
+                            self.pixel_perfect = !self.pixel_perfect;
+                            return Some("toggle_pixel_perfect");
+                        } else if self.selected == 8 {
+                            self.border = self.border.cycled(false);
+                            return Some("set_border");
+                        } else if self.selected == 9 {
+                            self.effect = self.effect.cycled(false);
+                            return Some("set_effect");
+                        } else if self.selected == 10 {
+                            self.clip_recording = !self.clip_recording;
+                            return Some("toggle_clip_recording");
                         }
                     }
                     KeyCode::Right => {
-                        if self.selected == 1 {
+                        if self.selected == 0 {
+                            self.aspect = self.aspect.toggled();
+                            return Some("set_aspect");
+                        } else if self.selected == 1 {
                             self.fullscreen = !self.fullscreen;
                             return Some("toggle_fullscreen");
                         } else if self.selected == 2 {
                             self.show_fps = !self.show_fps;
                             return Some("toggle_fps");
                         } else if self.selected == 3 {
+                            self.perf_overlay = !self.perf_overlay;
+                            return Some("toggle_perf_overlay");
+                        } else if self.selected == 4 {
                             self.gba_refresh_rate = !self.gba_refresh_rate;
                             return Some("toggle_gba_refresh");
+                        } else if self.selected == 5 {
+                            self.gba_colors = !self.gba_colors;
+                            return Some("toggle_gba_colors");
+                        } else if self.selected == 6 {
+                            self.texture_filter = self.texture_filter.toggled();
+                            return Some("toggle_texture_filter");
+                        } else if self.selected == 7 {
+                            self.pixel_perfect = !self.pixel_perfect;
+                            return Some("toggle_pixel_perfect");
+                        } else if self.selected == 8 {
+                            self.border = self.border.cycled(true);
+                            return Some("set_border");
+                        } else if self.selected == 9 {
+                            self.effect = self.effect.cycled(true);
+                            return Some("set_effect");
+                        } else if self.selected == 10 {
+                            self.clip_recording = !self.clip_recording;
+                            return Some("toggle_clip_recording");
                         }
                     }
-                    KeyCode::Return | KeyCode::Space | KeyCode::Z => {
-                        // activate the selected item: resolution (no-op), fullscreen toggles, fps toggles, gba refresh toggles, Back
+                    k if k == KeyCode::Space || Action::Confirm.matches(k) => {
+                        // activate the selected item: aspect toggles, fullscreen toggles, fps toggles, timings toggle, gba refresh and colours toggle, filter toggles, pixel perfect toggles, borders and shaders cycle, clip recording toggles, Back
                         match self.selected {
-                            0 => { /* resolution locked */ }
+                            0 => { self.aspect = self.aspect.toggled(); return Some("set_aspect"); }
                             1 => { self.fullscreen = !self.fullscreen; return Some("toggle_fullscreen"); }
                             2 => { self.show_fps = !self.show_fps; return Some("toggle_fps"); }
-                            3 => { self.gba_refresh_rate = !self.gba_refresh_rate; return Some("toggle_gba_refresh"); }
-                            4 => { self.view = OptionsView::Main; self.selected = 0; self.scroll_offset = 0; }
+                            3 => { self.perf_overlay = !self.perf_overlay; return Some("toggle_perf_overlay"); }
+                            4 => { self.gba_refresh_rate = !self.gba_refresh_rate; return Some("toggle_gba_refresh"); }
+                            5 => { self.gba_colors = !self.gba_colors; return Some("toggle_gba_colors"); }
+                            6 => { self.texture_filter = self.texture_filter.toggled(); return Some("toggle_texture_filter"); }
+                            7 => { self.pixel_perfect = !self.pixel_perfect; return Some("toggle_pixel_perfect"); }
+                            8 => { self.border = self.border.cycled(true); return Some("set_border"); }
+                            9 => { self.effect = self.effect.cycled(true); return Some("set_effect"); }
+                            10 => { self.clip_recording = !self.clip_recording; return Some("toggle_clip_recording"); }
+                            11 => { self.view = OptionsView::Main; self.selected = 0; self.scroll_offset = 0; }
                             _ => {}
                         }
                     }
-                    KeyCode::Escape => { self.view = OptionsView::Main; self.selected = 0; self.scroll_offset = 0; }
+                    k if Action::Cancel.matches(k) => { self.view = OptionsView::Main; self.selected = 0; self.scroll_offset = 0; }
+                    _ => {}
+                }
+            }
+            OptionsView::Audio => {
+                match key {
+                    KeyCode::Up if self.selected > 0 => { self.selected -= 1; }
+                    KeyCode::Down => { self.selected = (self.selected + 1).min(2); }
+                    KeyCode::Left | KeyCode::Right if self.selected == 0 => {
+                        self.cycle_ambience_volume(key == KeyCode::Right);
+                        return Some("set_ambience_volume");
+                    }
+                    KeyCode::Left | KeyCode::Right if self.selected == 1 => {
+                        self.cycle_sfx_volume(key == KeyCode::Right);
+                        return Some("set_sfx_volume");
+                    }
+                    k if k == KeyCode::Space || Action::Confirm.matches(k) => {
+                        match self.selected {
+                            0 => { self.cycle_ambience_volume(true); return Some("set_ambience_volume"); }
+                            1 => { self.cycle_sfx_volume(true); return Some("set_sfx_volume"); }
+                            2 => { self.view = OptionsView::Main; self.selected = 1; }
+                            _ => {}
+                        }
+                    }
+                    k if Action::Cancel.matches(k) => { self.view = OptionsView::Main; self.selected = 1; }
+                    _ => {}
+                }
+            }
+            OptionsView::Gameplay => {
+                match key {
+                    KeyCode::Up if self.selected > 0 => { self.selected -= 1; }
+                    KeyCode::Down => { self.selected = (self.selected + 1).min(8); }
+                    k if matches!(k, KeyCode::Left | KeyCode::Right | KeyCode::Space) || Action::Confirm.matches(k) => {
+                        let forward = key != KeyCode::Left;
+                        match self.selected {
+                            0 => { self.speedrun_timer = !self.speedrun_timer; return Some("toggle_speedrun_timer"); }
+                            1 => { self.cycle_turn_threshold(forward); return Some("set_turn_threshold"); }
+                            2 => { self.text_speed = self.text_speed.cycled(forward); return Some("set_text_speed"); }
+                            3 => { self.auto_run = !self.auto_run; return Some("toggle_auto_run"); }
+                            4 => { self.battle_speed = self.battle_speed.cycled(forward); return Some("set_battle_speed"); }
+                            5 => { self.damage_numbers = !self.damage_numbers; return Some("toggle_damage_numbers"); }
+                            6 => { self.click_to_move = !self.click_to_move; return Some("toggle_click_to_move"); }
+                            7 => { self.tutorial_hints = !self.tutorial_hints; return Some("toggle_tutorial_hints"); }
+                            8 if !matches!(key, KeyCode::Left | KeyCode::Right) => { self.view = OptionsView::Main; self.selected = 2; }
+                            _ => {}
+                        }
+                    }
+                    k if Action::Cancel.matches(k) => { self.view = OptionsView::Main; self.selected = 2; }
+                    _ => {}
+                }
+            }
+            OptionsView::Accessibility => {
+                match key {
+                    KeyCode::Up if self.selected > 0 => { self.selected -= 1; }
+                    KeyCode::Down => { self.selected = (self.selected + 1).min(4); }
+                    k if matches!(k, KeyCode::Left | KeyCode::Right | KeyCode::Space) || Action::Confirm.matches(k) => {
+                        match self.selected {
+                            0 => { self.colorblind = !self.colorblind; return Some("toggle_accessibility"); }
+                            1 => { self.high_contrast = !self.high_contrast; return Some("toggle_accessibility"); }
+                            2 => { self.rumble = !self.rumble; return Some("toggle_rumble"); }
+                            3 => { self.cycle_rumble_strength(key != KeyCode::Left); return Some("set_rumble_strength"); }
+                            4 if !matches!(key, KeyCode::Left | KeyCode::Right) => { self.view = OptionsView::Main; self.selected = 3; }
+                            _ => {}
+                        }
+                    }
+                    k if Action::Cancel.matches(k) => { self.view = OptionsView::Main; self.selected = 3; }
                     _ => {}
                 }
             }
@@ -226,3 +743,59 @@ impl Options {
         None
     }
 }
+
+/// The next (or previous) entry of `VOLUMES` after `volume`, wrapping around.
+fn cycle_volume(volume: f32, forward: bool) -> f32 {
+    let current = VOLUMES.iter().position(|v| (v - volume).abs() < 0.001).unwrap_or(0);
+    let n = VOLUMES.len();
+    VOLUMES[if forward { (current + 1) % n } else { (current + n - 1) % n }]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn settings_round_trip_and_ignore_junk() {
+        let mut options = Options::new();
+        options.show_fps = true;
+        options.ambience_volume = 0.2;
+        options.sfx_volume = 0.4;
+        options.turn_threshold = 0.0;
+        options.colorblind = true;
+        options.texture_filter = TextureFilter::Linear;
+        options.pixel_perfect = true;
+        options.aspect = Aspect::Wide;
+        options.border = Border::Planks;
+        options.effect = Effect::Lcd;
+        options.gba_colors = true;
+        options.clip_recording = true;
+        options.text_speed = Speed::Instant;
+        options.auto_run = true;
+        options.battle_speed = Speed::Slow;
+        options.damage_numbers = false;
+        options.click_to_move = true;
+        options.tutorial_hints = false;
+        options.rumble = false;
+        options.rumble_strength = 0.25;
+        let loaded = Options::from_text(&options.to_text());
+        assert!(loaded.show_fps && loaded.colorblind && !loaded.high_contrast);
+        assert_eq!(loaded.texture_filter, TextureFilter::Linear);
+        assert!(loaded.pixel_perfect && loaded.gba_colors && loaded.clip_recording);
+        assert_eq!((loaded.aspect, loaded.border, loaded.effect), (Aspect::Wide, Border::Planks, Effect::Lcd));
+        assert_eq!((loaded.ambience_volume, loaded.sfx_volume, loaded.turn_threshold), (0.2, 0.4, 0.0));
+        assert_eq!((loaded.text_speed, loaded.battle_speed), (Speed::Instant, Speed::Slow));
+        assert!(loaded.auto_run && !loaded.damage_numbers && loaded.click_to_move && !loaded.tutorial_hints);
+        assert_eq!((loaded.rumble, loaded.rumble_strength), (false, 0.25));
+        let junk = Options::from_text("ambience_volume=loud\nturn_threshold=-3\ntext_speed=warp\nbogus");
+        assert_eq!((junk.ambience_volume, junk.turn_threshold, junk.text_speed), (DEFAULT_AMBIENCE_VOLUME, 0.0, Speed::Normal));
+    }
+
+    #[test]
+    fn view_area_fits_the_aspect_inside_the_window() {
+        assert_eq!(Aspect::Standard.view_area((800.0, 600.0)), graphics::Rect::new(0.0, 0.0, 800.0, 600.0));
+        assert_eq!(Aspect::Standard.view_area((1920.0, 1080.0)), graphics::Rect::new(240.0, 0.0, 1440.0, 1080.0), "pillarboxed");
+        assert_eq!(Aspect::Wide.view_area((1920.0, 1080.0)), graphics::Rect::new(0.0, 0.0, 1920.0, 1080.0));
+        assert_eq!(Aspect::Wide.view_area((1600.0, 1200.0)), graphics::Rect::new(0.0, 150.0, 1600.0, 900.0), "letterboxed");
+    }
+}
@@ -0,0 +1,52 @@
+//! Palette-sensitive UI colours, switchable by the accessibility options.
+//!
+//! The settings are process-wide so every screen picks them up without threading the options
+//! through each draw call; `Game` keeps them in sync with `Options`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use ggez::graphics::Color;
+
+static COLORBLIND: AtomicBool = AtomicBool::new(false);
+static HIGH_CONTRAST: AtomicBool = AtomicBool::new(false);
+
+pub fn set_colorblind(on: bool) {
+    COLORBLIND.store(on, Ordering::Relaxed);
+}
+
+pub fn set_high_contrast(on: bool) {
+    HIGH_CONTRAST.store(on, Ordering::Relaxed);
+}
+
+pub fn colorblind() -> bool {
+    COLORBLIND.load(Ordering::Relaxed)
+}
+
+/// Whether characters get an outline so they stand out from the floor.
+pub fn high_contrast() -> bool {
+    HIGH_CONTRAST.load(Ordering::Relaxed)
+}
+
+/// Outline/text colour of the highlighted menu entry.
+pub fn selection() -> Color {
+    // Okabe-Ito orange reads better than yellow against the blue menus for most colour
+    // vision deficiencies
+    if colorblind() { Color::new(0.9, 0.6, 0.0, 1.0) } else { Color::new(1.0, 0.85, 0.05, 1.0) }
+}
+
+/// HP bar fill for a given fraction of max HP.
+pub fn hp_bar(fraction: f32) -> Color {
+    match (colorblind(), fraction) {
+        // blue / vermillion stay distinguishable without relying on red vs green
+        (true, f) if f > 0.3 => Color::new(0.0, 0.45, 0.7, 1.0),
+        (true, _) => Color::new(0.84, 0.37, 0.0, 1.0),
+        (false, f) if f > 0.6 => Color::new(0.2, 0.8, 0.3, 1.0),
+        (false, f) if f > 0.3 => Color::new(0.95, 0.8, 0.1, 1.0),
+        (false, _) => Color::new(0.9, 0.15, 0.1, 1.0),
+    }
+}
+
+/// MP bar fill.
+pub fn mp_bar() -> Color {
+    if colorblind() { Color::new(0.35, 0.7, 0.9, 1.0) } else { Color::new(0.3, 0.5, 1.0, 1.0) }
+}
@@ -0,0 +1,304 @@
+//! The player's party: up to three allies who walk in a line behind the leader and fight
+//! alongside them.
+//!
+//! Every ally has their own stats, equipment and HP. Each retraces the steps of whoever walks in
+//! front of them (the player, for the first), so the party snakes along like a conga line, and
+//! strikes any enemy that ends up next to them. Enemies that run into an ally hurt them; an ally
+//! at 0 HP keeps walking with the party but sits out fights until the party rests.
+
+use std::collections::VecDeque;
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawParam};
+use nalgebra as na;
+
+use crate::assets::Assets;
+use crate::items::{self, Equipment, ItemKind};
+use crate::map::TILE_SIZE;
+use crate::stats::Stats;
+
+/// Largest party, counting the player.
+pub const MAX_PARTY: usize = 4;
+/// Seconds between an ally's strikes.
+const STRIKE_COOLDOWN: f32 = 1.2;
+/// Seconds an ally can't be hurt again after a hit.
+const HURT_COOLDOWN: f32 = 1.0;
+/// Walking speed in pixels per second; the player's own pace.
+const SPEED: f32 = 160.0;
+/// Lagging further than this many tiles behind (teleports, falls) and a follower just
+/// reappears behind whoever it follows.
+const MAX_LAG_TILES: i32 = 2;
+
+/// Walking one tile behind someone, retracing their steps.
+pub struct Trail {
+    /// Top-left of the follower's tile, in world pixels.
+    position: na::Point2<f32>,
+    /// Tiles the one in front has walked through, oldest first; the follower steps onto the
+    /// front one while they're further along.
+    path: VecDeque<(i32, i32)>,
+}
+
+impl Trail {
+    pub fn new(tile: (i32, i32)) -> Trail {
+        let mut trail = Trail { position: na::Point2::origin(), path: VecDeque::new() };
+        trail.warp_to(tile);
+        trail
+    }
+
+    pub fn position(&self) -> na::Point2<f32> {
+        self.position
+    }
+
+    pub fn tile(&self) -> (i32, i32) {
+        (((self.position.x + TILE_SIZE / 2.0) / TILE_SIZE) as i32, ((self.position.y + TILE_SIZE / 2.0) / TILE_SIZE) as i32)
+    }
+
+    pub fn warp_to(&mut self, tile: (i32, i32)) {
+        self.position = na::Point2::new(tile.0 as f32 * TILE_SIZE, tile.1 as f32 * TILE_SIZE);
+        self.path.clear();
+        self.path.push_back(tile);
+    }
+
+    /// Step after whoever is on `ahead`, staying a tile behind them.
+    pub fn follow(&mut self, dt: f32, ahead: (i32, i32)) {
+        let last = self.path.back().copied().unwrap_or(self.tile());
+        if last != ahead {
+            if (last.0 - ahead.0).abs() + (last.1 - ahead.1).abs() > MAX_LAG_TILES {
+                self.warp_to(ahead);
+                return;
+            }
+            self.path.push_back(ahead);
+        }
+        if self.path.len() < 2 { return; }
+        // hurry to catch up if the trail is getting long
+        let speed = if self.path.len() > 3 { SPEED * 1.5 } else { SPEED };
+        let next = self.path[0];
+        let target = na::Point2::new(next.0 as f32 * TILE_SIZE, next.1 as f32 * TILE_SIZE);
+        let to_target = target - self.position;
+        let step = speed * dt;
+        if to_target.magnitude() <= step {
+            self.position = target;
+            self.path.pop_front();
+        } else {
+            self.position += to_target.normalize() * step;
+        }
+    }
+}
+
+pub struct Ally {
+    pub name: String,
+    pub stats: Stats,
+    pub equipment: Equipment,
+    trail: Trail,
+    strike_timer: f32,
+    hurt_timer: f32,
+}
+
+impl Ally {
+    pub fn new(name: &str, stats: Stats, equipment: Equipment, tile: (i32, i32)) -> Ally {
+        Ally { name: name.to_string(), stats, equipment, trail: Trail::new(tile), strike_timer: 0.0, hurt_timer: 0.0 }
+    }
+
+    pub fn position(&self) -> na::Point2<f32> {
+        self.trail.position()
+    }
+
+    pub fn tile(&self) -> (i32, i32) {
+        self.trail.tile()
+    }
+
+    pub fn is_down(&self) -> bool {
+        self.stats.hp == 0
+    }
+
+    /// Attack including equipment bonuses.
+    pub fn attack_power(&self) -> i32 {
+        self.stats.attack + self.equipment.attack_bonus()
+    }
+
+    /// Take a hit unless still reeling from the last one. Returns true if it knocked the ally
+    /// down.
+    pub fn hurt(&mut self, raw_damage: i32) -> bool {
+        if self.hurt_timer > 0.0 || self.is_down() { return false; }
+        self.hurt_timer = HURT_COOLDOWN;
+        self.take_hit(raw_damage)
+    }
+
+    /// Take a hit regardless of the hurt cooldown (turn-based battles). Returns true if it
+    /// knocked the ally down.
+    pub fn take_hit(&mut self, raw_damage: i32) -> bool {
+        if self.is_down() { return false; }
+        // armor soaks damage on top of base defense, as for the player
+        self.stats.take_damage(raw_damage - self.equipment.defense_bonus())
+    }
+
+    /// Strike an enemy on a tile next to the ally, if ready to. Returns the index of the enemy
+    /// struck.
+    pub fn try_strike(&mut self, enemy_tiles: impl Iterator<Item = (i32, i32)>) -> Option<usize> {
+        if self.is_down() || self.strike_timer > 0.0 { return None; }
+        let (tx, ty) = self.tile();
+        let idx = enemy_tiles.into_iter().position(|(ex, ey)| (ex - tx).abs() + (ey - ty).abs() <= 1)?;
+        self.strike_timer = STRIKE_COOLDOWN;
+        Some(idx)
+    }
+
+    /// Allies reuse the player sprite with a tint until they get their own art; greyed out
+    /// while down.
+    pub fn draw_scaled(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32)) -> GameResult {
+        let pos = self.position();
+        let dest = ggez::mint::Point2 { x: offset.0 + (pos.x + TILE_SIZE / 2.0) * scale, y: offset.1 + (pos.y + TILE_SIZE / 2.0) * scale };
+        let img_scale = scale * TILE_SIZE / assets.player.width() as f32;
+        let flashing = self.hurt_timer > 0.0 && (self.hurt_timer * 10.0) as i32 % 2 == 0;
+        let tint = if self.is_down() {
+            Color::new(0.45, 0.45, 0.45, 0.8)
+        } else if flashing {
+            Color::new(1.0, 0.5, 0.5, 1.0)
+        } else {
+            Color::new(0.55, 0.95, 0.6, 1.0)
+        };
+        canvas.draw(&assets.player, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]).color(tint));
+        Ok(())
+    }
+
+    /// "name,level,xp,hp,weapon,armor,accessory" for save files.
+    fn to_record(&self) -> String {
+        let e = &self.equipment;
+        format!("{},{},{},{},{},{},{}", self.name, self.stats.level, self.stats.xp, self.stats.hp, e.weapon.unwrap_or(""), e.armor.unwrap_or(""), e.accessory.unwrap_or(""))
+    }
+
+    /// Inverse of `to_record`, standing on `tile`; unknown items are dropped.
+    fn from_record(record: &str, tile: (i32, i32)) -> Option<Ally> {
+        let mut parts = record.split(',').map(str::trim);
+        let name = parts.next().filter(|n| !n.is_empty())?;
+        let mut stats = Stats::for_level(parts.next()?.parse().ok()?);
+        stats.xp = parts.next()?.parse().ok()?;
+        stats.hp = parts.next()?.parse::<i32>().ok()?.clamp(0, stats.max_hp);
+        let mut item = |kind| parts.next().and_then(|id| items::find_for_slot(id, kind));
+        let equipment = Equipment { weapon: item(ItemKind::Weapon), armor: item(ItemKind::Armor), accessory: item(ItemKind::Accessory) };
+        Some(Ally::new(name, stats, equipment, tile))
+    }
+}
+
+#[derive(Default)]
+pub struct Party {
+    /// Allies in marching order, first one right behind the player.
+    pub allies: Vec<Ally>,
+}
+
+impl Party {
+    /// Rebuild a saved party (one record per ally) around the player on `tile`.
+    pub fn from_records(records: &[String], tile: (i32, i32)) -> Party {
+        let allies = records.iter().filter_map(|r| Ally::from_record(r, tile)).take(MAX_PARTY - 1).collect();
+        Party { allies }
+    }
+
+    pub fn records(&self) -> Vec<String> {
+        self.allies.iter().map(Ally::to_record).collect()
+    }
+
+    pub fn is_full(&self) -> bool {
+        self.allies.len() + 1 >= MAX_PARTY
+    }
+
+    pub fn has(&self, name: &str) -> bool {
+        self.allies.iter().any(|a| a.name == name)
+    }
+
+    /// Add an ally to the back of the line. False if the party is full.
+    pub fn join(&mut self, ally: Ally) -> bool {
+        if self.is_full() { return false; }
+        self.allies.push(ally);
+        true
+    }
+
+    /// Walk the line: each ally follows the one in front, the first follows the player on
+    /// `leader`.
+    pub fn update(&mut self, dt: f32, leader: (i32, i32)) {
+        let mut ahead = leader;
+        for ally in &mut self.allies {
+            ally.strike_timer = (ally.strike_timer - dt).max(0.0);
+            ally.hurt_timer = (ally.hurt_timer - dt).max(0.0);
+            ally.trail.follow(dt, ahead);
+            ahead = ally.tile();
+        }
+    }
+
+    /// Tile at the back of the line, for anyone tagging along behind the party.
+    pub fn tail_tile(&self, leader: (i32, i32)) -> (i32, i32) {
+        self.allies.last().map_or(leader, Ally::tile)
+    }
+
+    /// Everyone back on their feet with full HP and MP (a night's rest, waking up in bed).
+    pub fn rest(&mut self) {
+        for ally in &mut self.allies {
+            ally.stats.hp = ally.stats.max_hp;
+            ally.stats.mp = ally.stats.max_mp;
+        }
+    }
+
+    /// Share in a victory: every ally still standing gains `amount` XP.
+    pub fn gain_xp(&mut self, amount: u32) {
+        for ally in self.allies.iter_mut().filter(|a| !a.is_down()) {
+            ally.stats.gain_xp(amount);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn walk(party: &mut Party, tiles: &[(i32, i32)]) {
+        for &tile in tiles {
+            for _ in 0..30 {
+                party.update(0.05, tile);
+            }
+        }
+    }
+
+    #[test]
+    fn allies_walk_in_a_line_behind_the_leader() {
+        let mut party = Party::default();
+        for name in ["Bram", "Cora", "Dell"] {
+            assert!(party.join(Ally::new(name, Stats::new(), Equipment::default(), (2, 2))));
+        }
+        assert!(party.is_full());
+        assert!(!party.join(Ally::new("Eve", Stats::new(), Equipment::default(), (2, 2))));
+        walk(&mut party, &[(3, 2), (4, 2), (5, 2), (5, 3), (5, 4)]);
+        let line: Vec<(i32, i32)> = party.allies.iter().map(Ally::tile).collect();
+        assert_eq!(line, vec![(5, 3), (5, 2), (4, 2)]);
+        assert_eq!(party.tail_tile((5, 4)), (4, 2));
+    }
+
+    #[test]
+    fn downed_allies_sit_out_fights_until_they_rest() {
+        let mut ally = Ally::new("Bram", Stats::for_level(1), Equipment { weapon: Some("rusty_sword"), ..Equipment::default() }, (5, 5));
+        assert_eq!(ally.attack_power(), 7);
+        assert_eq!(ally.try_strike([(9, 9), (5, 6)].into_iter()), Some(1));
+        assert_eq!(ally.try_strike([(5, 6)].into_iter()), None, "on cooldown");
+        while !ally.hurt(100) {
+            ally.hurt_timer = 0.0;
+        }
+        let mut party = Party { allies: vec![ally] };
+        party.update(STRIKE_COOLDOWN, (5, 5));
+        assert_eq!(party.allies[0].try_strike([(5, 6)].into_iter()), None, "down");
+        party.gain_xp(10);
+        assert_eq!(party.allies[0].stats.xp, 0);
+        party.rest();
+        assert!(!party.allies[0].is_down());
+    }
+
+    #[test]
+    fn allies_round_trip_through_save_records() {
+        let mut stats = Stats::for_level(3);
+        stats.xp = 7;
+        stats.hp = 9;
+        let equipment = Equipment { weapon: Some("iron_sword"), armor: None, accessory: Some("lucky_charm") };
+        let party = Party { allies: vec![Ally::new("Bram", stats, equipment, (1, 1))] };
+        let loaded = Party::from_records(&party.records(), (4, 4));
+        assert_eq!(loaded.allies[0].stats, stats);
+        assert_eq!(loaded.allies[0].equipment, equipment);
+        assert_eq!(loaded.allies[0].tile(), (4, 4));
+        assert!(Party::from_records(&["".to_string(), "Cora,x".to_string()], (0, 0)).allies.is_empty());
+    }
+}
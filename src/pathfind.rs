@@ -0,0 +1,69 @@
+//! A* over a room's tile grid, for click-to-move.
+//!
+//! The caller says which single steps are allowed (`can_step`), so the same search works for
+//! anything that walks the grid one tile at a time; the player uses it with its own rules for
+//! walls, deep water, ledges and pits.
+
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+const STEPS: [(i32, i32); 4] = [(0, -1), (1, 0), (0, 1), (-1, 0)];
+
+fn distance(a: (i32, i32), b: (i32, i32)) -> u32 {
+    a.0.abs_diff(b.0) + a.1.abs_diff(b.1)
+}
+
+/// Shortest path of single steps from `from` to `to` on a `size` grid (in tiles). The path
+/// leaves out `from` and ends on `to`; it's empty when they're the same tile and None when `to`
+/// can't be reached.
+pub fn find_path(from: (i32, i32), to: (i32, i32), size: (i32, i32), can_step: impl Fn((i32, i32), (i32, i32)) -> bool) -> Option<Vec<(i32, i32)>> {
+    let inside = |(x, y): (i32, i32)| x >= 0 && y >= 0 && x < size.0 && y < size.1;
+    if !inside(to) { return None; }
+    let mut open = BinaryHeap::new();
+    let mut came_from: HashMap<(i32, i32), (i32, i32)> = HashMap::new();
+    let mut cost: HashMap<(i32, i32), u32> = HashMap::from([(from, 0)]);
+    // ties go to the tile nearer the goal, which keeps the search heading straight for it
+    open.push(Reverse((distance(from, to), distance(from, to), from)));
+    while let Some(Reverse((_, _, tile))) = open.pop() {
+        if tile == to {
+            let (mut path, mut at) = (Vec::new(), tile);
+            while let Some(&prev) = came_from.get(&at) {
+                path.push(at);
+                at = prev;
+            }
+            path.reverse();
+            return Some(path);
+        }
+        let here = cost[&tile];
+        for (dx, dy) in STEPS {
+            let next = (tile.0 + dx, tile.1 + dy);
+            if !inside(next) || cost.get(&next).is_some_and(|&c| c <= here + 1) || !can_step(tile, next) {
+                continue;
+            }
+            cost.insert(next, here + 1);
+            came_from.insert(next, tile);
+            open.push(Reverse((here + 1 + distance(next, to), distance(next, to), next)));
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths_go_round_walls_and_give_up_on_closed_rooms() {
+        // a wall down column 2 with a gap at the bottom
+        let wall = |(x, y): (i32, i32)| x == 2 && y < 4;
+        let path = find_path((0, 0), (4, 0), (5, 5), |_, to| !wall(to)).unwrap();
+        assert_eq!(path.len(), 12);
+        assert_eq!(path.last(), Some(&(4, 0)));
+        assert!(path.iter().all(|&tile| !wall(tile)));
+        assert!(path.windows(2).all(|w| distance(w[0], w[1]) == 1));
+        assert_eq!(distance((0, 0), path[0]), 1, "the start isn't part of the path");
+        assert_eq!(find_path((1, 1), (1, 1), (5, 5), |_, _| true), Some(vec![]));
+        assert_eq!(find_path((0, 0), (4, 0), (5, 5), |_, to| to.0 != 2), None);
+        assert_eq!(find_path((0, 0), (9, 0), (5, 5), |_, _| true), None);
+    }
+}
@@ -0,0 +1,136 @@
+//! Performance overlay: a frame-time graph and how long each part of a frame took.
+//!
+//! The game times its update, the room and entity drawing and the UI every frame; the overlay
+//! (Video options, "Frame Timings") shows those averaged over the last few frames next to a graph
+//! of recent frame times, so a stutter shows up as a spike and the phase that grew points at the
+//! cause. Draw timings are CPU time spent building the frame, not GPU time.
+
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect, Text, TextFragment};
+
+/// Frames kept for the graph.
+const HISTORY: usize = 120;
+/// Frame time (ms) at the top of the graph.
+const GRAPH_MAX_MS: f32 = 50.0;
+/// One frame at 60 fps, marked on the graph.
+const TARGET_MS: f32 = 1000.0 / 60.0;
+/// How much of each new frame's timing goes into the shown average.
+const SMOOTHING: f32 = 0.1;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Phase {
+    Update,
+    Room,
+    Entities,
+    Ui,
+}
+
+impl Phase {
+    const ALL: [Phase; 4] = [Phase::Update, Phase::Room, Phase::Entities, Phase::Ui];
+
+    fn label(self) -> &'static str {
+        match self {
+            Phase::Update => "update",
+            Phase::Room => "room",
+            Phase::Entities => "entities",
+            Phase::Ui => "ui",
+        }
+    }
+}
+
+pub struct PerfOverlay {
+    /// Recent frame times in ms, newest last.
+    frame_ms: VecDeque<f32>,
+    /// Time spent in each phase so far this frame.
+    current: [Duration; 4],
+    /// Smoothed ms per phase, as shown.
+    shown: [f32; 4],
+}
+
+impl PerfOverlay {
+    pub fn new() -> PerfOverlay {
+        PerfOverlay { frame_ms: VecDeque::with_capacity(HISTORY), current: [Duration::ZERO; 4], shown: [0.0; 4] }
+    }
+
+    /// Count the time since `started` towards `phase` (phases can be timed in pieces).
+    pub fn measure(&mut self, phase: Phase, started: Instant) {
+        self.current[phase as usize] += started.elapsed();
+    }
+
+    /// Close off a frame that took `dt` seconds in all.
+    pub fn finish_frame(&mut self, dt: f32) {
+        if self.frame_ms.len() == HISTORY {
+            self.frame_ms.pop_front();
+        }
+        self.frame_ms.push_back(dt * 1000.0);
+        for (shown, spent) in self.shown.iter_mut().zip(&mut self.current) {
+            let ms = spent.as_secs_f32() * 1000.0;
+            *shown += (ms - *shown) * SMOOTHING;
+            *spent = Duration::ZERO;
+        }
+    }
+
+    /// Longest frame in the graph, in ms.
+    fn worst_ms(&self) -> f32 {
+        self.frame_ms.iter().copied().fold(0.0, f32::max)
+    }
+
+    /// Draw in the top-right corner, under the FPS counter, in `font` (the columns want a
+    /// fixed-width one; see `FontRole::Mono`).
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, font: Option<String>) -> GameResult {
+        let width = ctx.gfx.window().inner_size().width as f32;
+        let (graph_w, graph_h) = (HISTORY as f32 * 2.0, 60.0);
+        let rect = Rect::new(width - graph_w - 20.0, 36.0, graph_w + 10.0, graph_h + 110.0);
+        let bg = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.0, 0.7))?;
+        canvas.draw(&bg, DrawParam::new());
+
+        let (left, bottom) = (rect.x + 5.0, rect.y + 5.0 + graph_h);
+        let y_for = |ms: f32| bottom - (ms / GRAPH_MAX_MS).min(1.0) * graph_h;
+        let mut builder = MeshBuilder::new();
+        for (i, &ms) in self.frame_ms.iter().enumerate() {
+            let color = if ms <= TARGET_MS * 1.1 {
+                Color::new(0.3, 0.9, 0.3, 1.0)
+            } else if ms <= TARGET_MS * 2.0 {
+                Color::new(1.0, 0.85, 0.2, 1.0)
+            } else {
+                Color::new(1.0, 0.3, 0.3, 1.0)
+            };
+            let top = y_for(ms);
+            builder.rectangle(DrawMode::fill(), Rect::new(left + i as f32 * 2.0, top, 2.0, bottom - top), color)?;
+        }
+        let target = y_for(TARGET_MS);
+        builder.line(&[[left, target], [left + graph_w, target]], 1.0, Color::new(1.0, 1.0, 1.0, 0.5))?;
+        let graph = Mesh::from_data(ctx, builder.build());
+        canvas.draw(&graph, DrawParam::new());
+
+        let last = self.frame_ms.back().copied().unwrap_or(0.0);
+        let mut lines = vec![format!("frame {:.1} ms (worst {:.1})", last, self.worst_ms())];
+        lines.extend(Phase::ALL.iter().map(|&phase| format!("{:<9}{:.2} ms", phase.label(), self.shown[phase as usize])));
+        for (i, line) in lines.iter().enumerate() {
+            let text = Text::new(TextFragment { font: font.clone(), ..TextFragment::new(line.as_str()).scale(16.0) });
+            canvas.draw(&text, DrawParam::new().dest([left, bottom + 6.0 + i as f32 * 19.0]).color(Color::WHITE));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_a_window_of_frames_and_smooths_phases() {
+        let mut perf = PerfOverlay::new();
+        for i in 0..HISTORY + 10 {
+            perf.current[Phase::Room as usize] = Duration::from_millis(4);
+            perf.finish_frame(if i == 5 { 0.1 } else { 0.016 });
+        }
+        assert_eq!(perf.frame_ms.len(), HISTORY);
+        assert!(perf.worst_ms() < 17.0, "the spike has scrolled off the graph");
+        assert!((perf.shown[Phase::Room as usize] - 4.0).abs() < 0.1);
+        assert_eq!(perf.current, [Duration::ZERO; 4]);
+    }
+}
@@ -0,0 +1,98 @@
+//! Collectables lying in the world: gold coins and items dropped by enemies.
+//!
+//! Anything left lying around for too long vanishes, blinking for its last few seconds.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect};
+use nalgebra as na;
+
+use crate::items::{self, ItemId};
+use crate::loot::Rarity;
+use crate::map::TILE_SIZE;
+use crate::player::Player;
+
+/// Seconds before a pickup disappears.
+pub const LIFETIME: f32 = 30.0;
+/// Pickups blink for this long before they disappear.
+const BLINK_SECS: f32 = 5.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Loot {
+    Gold(u32),
+    Item(ItemId, Rarity),
+}
+
+pub struct Pickup {
+    /// World pixel position (top-left of the tile it sits on).
+    pub position: na::Point2<f32>,
+    pub loot: Loot,
+    // drives the little bobbing animation, and the timeout
+    age: f32,
+}
+
+impl Pickup {
+    pub fn gold(position: na::Point2<f32>, amount: u32) -> Pickup {
+        Pickup { position, loot: Loot::Gold(amount), age: 0.0 }
+    }
+
+    pub fn item(position: na::Point2<f32>, id: ItemId, rarity: Rarity) -> Pickup {
+        Pickup { position, loot: Loot::Item(id, rarity), age: 0.0 }
+    }
+
+    /// Returns false once the pickup has lain there too long and should go.
+    pub fn update(&mut self, dt: f32) -> bool {
+        self.age += dt;
+        self.age < LIFETIME
+    }
+
+    /// Collected when the player's body overlaps the coin.
+    pub fn touches(&self, player: &Player) -> bool {
+        let d = self.position - player.get_position();
+        d.x.abs() < TILE_SIZE * 0.6 && d.y.abs() < TILE_SIZE * 0.6
+    }
+
+    pub fn draw_scaled(&self, ctx: &mut Context, canvas: &mut Canvas, scale: f32, offset: (f32, f32)) -> GameResult {
+        // blink out of sight every other tenth of a second near the end
+        if LIFETIME - self.age < BLINK_SECS && (self.age * 10.0) as i32 % 2 == 0 {
+            return Ok(());
+        }
+        let bob = (self.age * 4.0).sin() * 2.0;
+        let cx = offset.0 + (self.position.x + TILE_SIZE / 2.0) * scale;
+        let cy = offset.1 + (self.position.y + TILE_SIZE / 2.0 + bob) * scale;
+        match self.loot {
+            Loot::Gold(_) => {
+                let coin = Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy], 6.0 * scale, 0.5, Color::new(1.0, 0.8, 0.15, 1.0))?;
+                canvas.draw(&coin, DrawParam::new());
+                let rim = Mesh::new_circle(ctx, DrawMode::stroke(1.5 * scale), [cx, cy], 6.0 * scale, 0.5, Color::new(0.6, 0.4, 0.05, 1.0))?;
+                canvas.draw(&rim, DrawParam::new());
+            }
+            Loot::Item(id, rarity) => {
+                // a little box in the item's colour, beside any coin dropped with it, ringed by
+                // its rarity
+                let (r, g, b) = items::find(id).map_or((1.0, 1.0, 1.0), |def| def.color);
+                let cx = cx + 7.0 * scale;
+                let half = 5.0 * scale;
+                let glow = Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy], half * 1.8, 0.5, Color { a: 0.35, ..rarity.color() })?;
+                canvas.draw(&glow, DrawParam::new());
+                let rect = Rect::new(cx - half, cy - half, half * 2.0, half * 2.0);
+                let body = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(r, g, b, 1.0))?;
+                canvas.draw(&body, DrawParam::new());
+                let rim = Mesh::new_rectangle(ctx, DrawMode::stroke(1.5 * scale), rect, rarity.color())?;
+                canvas.draw(&rim, DrawParam::new());
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pickups_go_once_their_time_is_up() {
+        let mut pickup = Pickup::item(na::Point2::origin(), "potion", Rarity::Common);
+        assert!(pickup.update(LIFETIME - 1.0));
+        assert!(!pickup.update(1.0));
+    }
+}
@@ -0,0 +1,26 @@
+//! The few places the game reaches past ggez to the machine it runs on: the folder loose assets
+//! and `data.pak` are found in, the folders saves and settings go to, and the window's
+//! fullscreen switch.
+//!
+//! Keeping them here gives a port one file to fill in. Only desktop builds exist: ggez 0.9
+//! doesn't target the web, so there are no browser versions of these yet.
+
+use std::path::PathBuf;
+
+use winit::window::{Fullscreen, Window};
+
+/// Folder holding `assets/`, `mods/` and `data.pak`: wherever the game was launched from.
+pub fn working_dir() -> PathBuf {
+    std::env::current_dir().unwrap_or_default()
+}
+
+/// The platform's folders for player data and for settings, if it has them.
+pub fn user_dirs(app: &str) -> Option<(PathBuf, PathBuf)> {
+    let dirs = directories::ProjectDirs::from("", "", app)?;
+    Some((dirs.data_dir().to_path_buf(), dirs.config_dir().to_path_buf()))
+}
+
+/// Borderless fullscreen on the current monitor, or back to a window.
+pub fn set_fullscreen(window: &Window, on: bool) {
+    window.set_fullscreen(on.then_some(Fullscreen::Borderless(None)));
+}
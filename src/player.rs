@@ -1,21 +1,92 @@
+use std::collections::VecDeque;
+
 use ggez::{Context, GameResult};
 use ggez::graphics::{Canvas, DrawParam};
 use nalgebra as na;
-use ggez::input::keyboard::KeyCode;
 
 use crate::map::{Map, TILE_SIZE};
+use crate::rooms::Water;
 use crate::assets::Assets;
+use crate::stats::Stats;
+use crate::items::{self, Equipment, Inventory, ItemKind, UseEffect};
+use crate::status::{StatusEffects, StatusKind};
+use crate::skills::SkillBook;
+use crate::input::Controls;
+use crate::stealth::Gait;
+
+/// Start on the bottom-right walkable bed tile: tile (2,12) = pixel position (64, 384)
+const START_POS: (f32, f32) = (64.0, 384.0);
+/// Gold a new adventurer starts with.
+pub const STARTING_GOLD: u32 = 50;
+/// How long a melee swing lasts (seconds); also the attack cooldown.
+pub const ATTACK_DURATION: f32 = 0.25;
+/// Cooldown between thrown knives.
+pub const THROW_COOLDOWN: f32 = 0.6;
+/// Default for `Player::turn_threshold`.
+pub const DEFAULT_TURN_THRESHOLD: f32 = 0.1;
+/// Side of the square the player collides with the map by, centred on their tile; a little
+/// smaller than a tile so they slide past corners.
+pub const HITBOX_SIZE: f32 = TILE_SIZE * 0.9;
+/// Speed multiplier while running.
+const RUN_SPEED: f32 = 1.6;
+/// Speed multiplier while sneaking.
+const SNEAK_SPEED: f32 = 0.5;
+/// Invulnerability window after taking a hit.
+const HURT_COOLDOWN: f32 = 1.0;
+/// How long the level-up celebration plays.
+pub const LEVEL_UP_EFFECT_SECS: f32 = 1.5;
+/// Seconds per point of MP regenerated.
+const MP_REGEN_SECS: f32 = 2.0;
+/// How long the fall animation plays after stepping into a pit.
+const FALL_SECS: f32 = 0.6;
+/// Accessory that lets the player swim through deep water.
+const SWIM_ITEM: &str = "swim_fins";
 
 pub struct Player {
     position: na::Point2<f32>,
     speed: f32,
     // grid movement fields
-    pub grid_size: f32,
     pub moving: bool,
     pub target: na::Point2<f32>,
     pub facing: (f32, f32), // (dx, dy) facing direction
+    pub stats: Stats,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    pub gold: u32,
+    pub status: StatusEffects,
+    pub skills: SkillBook,
+    // combat / feedback timers, counted down in update()
+    pub attack_timer: f32,
+    pub hurt_timer: f32,
+    pub level_up_timer: f32,
+    pub throw_timer: f32,
+    mp_regen_timer: f32,
+    /// Seconds a direction key must be held before the player steps after turning to face a
+    /// new direction; a shorter tap only turns. 0 disables tap-to-turn.
+    pub turn_threshold: f32,
+    /// Run by default and walk while Shift is held, instead of the other way round.
+    pub auto_run: bool,
+    // direction held last frame and how long it has been held while turning in place
+    last_held: Option<(i32, i32)>,
+    turn_hold: f32,
+    /// Y position a ledge hop started from, while one is in progress.
+    hop_from: Option<f32>,
+    /// Seconds left of a fall down a pit (0 when not falling).
+    falling: f32,
+    /// Sitting on a chair; any movement key stands back up.
+    pub sitting: bool,
+    /// Set on the frame a step lands on its tile (for footsteps); cleared every update.
+    pub stepped: bool,
+    /// Set on the frame the player shoves a block; cleared every update.
+    pub pushed: bool,
+    /// Tiles still to walk through after a click, in order (see `walk_path`).
+    path: VecDeque<(i32, i32)>,
+    /// Walking, running or sneaking, from this frame's controls; enemies hear it (see
+    /// `crate::stealth`).
+    pub gait: Gait,
 }
 
+#[allow(clippy::items_after_test_module)]
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -24,19 +95,98 @@ mod tests {
     #[test]
     fn player_moves_right() {
         let mut p = Player::test_new();
-        let map = Map::new();
+        let mut map = Map::new();
         let start_x = p.position.x;
-        p.update_with_dir(na::Vector2::new(1.0, 0.0), 0.5, &map);
+        for _ in 0..10 {
+            p.update(Controls::holding((1, 0)), 0.05, &mut map);
+        }
         assert!(p.position.x > start_x, "Player should have moved right");
     }
+
+    #[test]
+    fn held_keys_walk_whole_tiles_and_stop_at_walls() {
+        let mut map = Map::new();
+        let mut p = Player::test_new();
+        p.facing = (-1.0, 0.0);
+        // (3,3) to the wall at x=0: two tiles, then nothing more however long it's held
+        for _ in 0..60 {
+            p.update(Controls::holding((-1, 0)), 0.05, &mut map);
+        }
+        assert_eq!(p.tile(), (1, 3));
+        p.update(Controls::default(), 0.05, &mut map);
+        assert!(!p.moving);
+        assert_eq!(p.get_position(), na::Point2::new(TILE_SIZE, 3.0 * TILE_SIZE), "grid-aligned");
+    }
+
+    #[test]
+    fn queued_step_carries_leftover_movement_and_stops_at_walls() {
+        let mut p = Player::test_new();
+        let map = Map::new();
+        p.queue_next_step((1, 0), 5.0, &map);
+        assert!(p.moving);
+        assert_eq!(p.target, na::Point2::new(128.0, 96.0));
+        assert_eq!(p.position.x, 101.0);
+
+        // tile (1,3) has the outer wall to its left
+        let mut p = Player::test_new();
+        p.position = na::Point2::new(32.0, 96.0);
+        p.queue_next_step((-1, 0), 5.0, &map);
+        assert!(!p.moving);
+        assert_eq!(p.facing, (-1.0, 0.0));
+    }
+
+    #[test]
+    fn deep_water_needs_swim_fins() {
+        let map = Map::new();
+        // (12,10) is the shallow rim of the pond, (13,10) is deep
+        let mut p = Player::test_new();
+        p.position = na::Point2::new(12.0 * TILE_SIZE, 10.0 * TILE_SIZE);
+        assert_eq!(p.water(&map), Some(Water::Shallow));
+        p.queue_next_step((1, 0), 0.0, &map);
+        assert!(!p.moving);
+        p.equipment.accessory = Some(SWIM_ITEM);
+        p.queue_next_step((1, 0), 0.0, &map);
+        assert!(p.moving);
+    }
+
+    #[test]
+    fn ledges_are_hopped_down_but_not_climbed() {
+        let map = Map::new();
+        // the ledge runs along row 5 from the left wall
+        let mut p = Player::test_new();
+        p.position = na::Point2::new(4.0 * TILE_SIZE, 4.0 * TILE_SIZE);
+        p.queue_next_step((0, 1), 0.0, &map);
+        assert!(p.moving && p.hop_from.is_some());
+        assert_eq!(p.target, na::Point2::new(4.0 * TILE_SIZE, 6.0 * TILE_SIZE));
+
+        let mut p = Player::test_new();
+        p.position = na::Point2::new(4.0 * TILE_SIZE, 6.0 * TILE_SIZE);
+        p.queue_next_step((0, -1), 0.0, &map);
+        assert!(!p.moving);
+    }
+
+    #[test]
+    fn tapping_a_new_direction_turns_without_stepping() {
+        let mut p = Player::test_new();
+        p.facing = (0.0, 1.0);
+        // a one-frame tap to the left only turns
+        assert!(!p.tap_to_turn(Some((-1, 0)), 0.016));
+        assert_eq!(p.facing, (-1.0, 0.0));
+        assert!(!p.tap_to_turn(None, 0.016));
+        // pressing the direction already faced steps at once
+        assert!(p.tap_to_turn(Some((-1, 0)), 0.016));
+        // holding a new direction steps once the threshold has passed
+        p.tap_to_turn(None, 0.016);
+        assert!(!p.tap_to_turn(Some((0, -1)), 0.016));
+        assert!(p.tap_to_turn(Some((0, -1)), DEFAULT_TURN_THRESHOLD));
+    }
 }
 
 impl Player {
-    pub fn new(_ctx: &mut Context) -> GameResult<Player> {
-        // Start on the bottom-right walkable bed tile: tile (2,12) = pixel position (64, 384)
+    pub fn new() -> Player {
         // The walkable bed area is 2x2 (top 4 tiles), bottom 2 are faux walls
-        let pos = na::Point2::new(64.0, 384.0);
-        Ok(Player { position: pos, speed: 160.0, grid_size: 32.0, moving: false, target: pos, facing: (0.0, 1.0) })
+        let pos = na::Point2::new(START_POS.0, START_POS.1);
+        Player { position: pos, speed: 160.0, moving: false, target: pos, facing: (0.0, 1.0), stats: Stats::new(), inventory: Inventory::default(), equipment: Equipment::default(), gold: STARTING_GOLD, status: StatusEffects::default(), skills: SkillBook::for_level(1), attack_timer: 0.0, hurt_timer: 0.0, level_up_timer: 0.0, throw_timer: 0.0, mp_regen_timer: 0.0, turn_threshold: DEFAULT_TURN_THRESHOLD, auto_run: false, last_held: None, turn_hold: 0.0, hop_from: None, falling: 0.0, sitting: false, stepped: false, pushed: false, path: VecDeque::new(), gait: Gait::Walk }
     }
 
     /// Test helper: construct a player without needing a ggez Context
@@ -44,50 +194,262 @@ impl Player {
     pub fn test_new() -> Player {
         // Start at grid-aligned position: tile (3,3) = pixel position (96, 96)
         let pos = na::Point2::new(96.0, 96.0);
-        Player { position: pos, speed: 160.0, grid_size: 32.0, moving: false, target: pos, facing: (0.0, 1.0) }
+        Player { position: pos, speed: 160.0, moving: false, target: pos, facing: (0.0, 1.0), stats: Stats::new(), inventory: Inventory::default(), equipment: Equipment::default(), gold: STARTING_GOLD, status: StatusEffects::default(), skills: SkillBook::for_level(1), attack_timer: 0.0, hurt_timer: 0.0, level_up_timer: 0.0, throw_timer: 0.0, mp_regen_timer: 0.0, turn_threshold: DEFAULT_TURN_THRESHOLD, auto_run: false, last_held: None, turn_hold: 0.0, hop_from: None, falling: 0.0, sitting: false, stepped: false, pushed: false, path: VecDeque::new(), gait: Gait::Walk }
     }
 
-    /// Update using an explicit direction vector (headless/test-friendly)
-    #[cfg(test)]
-    pub fn update_with_dir(&mut self, dir: na::Vector2<f32>, dt: f32, map: &Map) {
-        if dir != na::Vector2::new(0.0, 0.0) {
-            let norm = dir.normalize();
-            let displacement = norm * self.speed * dt;
-            let new_pos = na::Point2::new(self.position.x + displacement.x, self.position.y + displacement.y);
-
-            // Simple collision: reject movement if the new position overlaps a solid tile
-            // Use slightly smaller hitbox to allow smooth movement along walls
-            let hitbox_size = TILE_SIZE * 0.9;
-            let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
-            if map.is_movement_allowed(
-                self.position.x + hitbox_offset, 
-                self.position.y + hitbox_offset,
-                new_pos.x + hitbox_offset, 
-                new_pos.y + hitbox_offset, 
-                hitbox_size, 
-                hitbox_size
-            ) {
-                self.position = new_pos;
+    pub fn get_position(&self) -> na::Point2<f32> {
+        self.position
+    }
+
+    /// Tile the player is standing on (by centre point).
+    pub fn tile(&self) -> (i32, i32) {
+        (((self.position.x + TILE_SIZE / 2.0) / TILE_SIZE) as i32, ((self.position.y + TILE_SIZE / 2.0) / TILE_SIZE) as i32)
+    }
+
+    /// Tile directly in front of the player.
+    pub fn facing_tile(&self) -> (i32, i32) {
+        let (tx, ty) = self.tile();
+        (tx + self.facing.0 as i32, ty + self.facing.1 as i32)
+    }
+
+    /// Water the player is standing in, if any.
+    pub fn water(&self, map: &Map) -> Option<Water> {
+        map.water_at_point(self.position.x + TILE_SIZE / 2.0, self.position.y + TILE_SIZE / 2.0)
+    }
+
+    pub fn can_swim(&self) -> bool {
+        self.equipment.accessory == Some(SWIM_ITEM)
+    }
+
+    /// The faint glow around the player that keeps dark rooms navigable.
+    pub fn light(&self) -> crate::lighting::Light {
+        let position = na::Point2::new(self.position.x + TILE_SIZE / 2.0, self.position.y + TILE_SIZE / 2.0);
+        crate::lighting::Light { position, radius: TILE_SIZE * 2.5, intensity: 0.9 }
+    }
+
+    /// Deep water stops anyone who can't swim from stepping onto the tile at `pos`. Someone
+    /// already in deep water (fins taken off mid-swim) may still flounder their way out.
+    fn blocked_by_water(&self, map: &Map, pos: na::Point2<f32>) -> bool {
+        if self.can_swim() || self.water(map) == Some(Water::Deep) { return false; }
+        map.water_at_point(pos.x + TILE_SIZE / 2.0, pos.y + TILE_SIZE / 2.0) == Some(Water::Deep)
+    }
+
+    /// Whether the player could take one step from tile `from` to the tile next to it, for
+    /// planning paths: walls, deep water without fins, and pits and ledges all count as blocked.
+    pub fn can_walk(&self, map: &Map, from: (i32, i32), to: (i32, i32)) -> bool {
+        if map.is_ledge(to.0, to.1) || map.is_pit(to) { return false; }
+        let hitbox_size = HITBOX_SIZE;
+        let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
+        let (from, to) = (na::Point2::new(from.0 as f32 * TILE_SIZE, from.1 as f32 * TILE_SIZE), na::Point2::new(to.0 as f32 * TILE_SIZE, to.1 as f32 * TILE_SIZE));
+        !self.blocked_by_water(map, to) && map.is_movement_allowed(
+            from.x + hitbox_offset,
+            from.y + hitbox_offset,
+            to.x + hitbox_offset,
+            to.y + hitbox_offset,
+            hitbox_size,
+            hitbox_size
+        )
+    }
+
+    /// Find a way to `goal` and walk it a step at a time until it's reached, something gets in
+    /// the way, or a direction key is pressed. Returns false (and stays put) if there's no way.
+    pub fn walk_to(&mut self, map: &Map, goal: (i32, i32)) -> bool {
+        let size = ((map.width_pixels() as f32 / TILE_SIZE) as i32, (map.height_pixels() as f32 / TILE_SIZE) as i32);
+        let Some(path) = crate::pathfind::find_path(self.tile(), goal, size, |from, to| self.can_walk(map, from, to)) else { return false };
+        self.path = path.into();
+        self.sitting = false;
+        true
+    }
+
+    pub fn stop_walking(&mut self) {
+        self.path.clear();
+    }
+
+    /// Tiles left to walk after a click.
+    pub fn path_left(&self) -> usize {
+        self.path.len()
+    }
+
+    /// Direction of the next step along the walked path from tile `at`, dropping `at` from
+    /// the path once it's been reached. The path is abandoned if it no longer starts next door.
+    fn path_step(&mut self, at: (i32, i32)) -> Option<(i32, i32)> {
+        if self.path.front() == Some(&at) {
+            self.path.pop_front();
+        }
+        let &next = self.path.front()?;
+        let dir = (next.0 - at.0, next.1 - at.1);
+        if dir.0.abs() + dir.1.abs() != 1 {
+            self.path.clear();
+            return None;
+        }
+        Some(dir)
+    }
+
+    /// Whether a ledge hop can land at `pos` (the tile below the ledge must be walkable).
+    fn can_land(&self, map: &Map, pos: na::Point2<f32>) -> bool {
+        let hitbox_size = HITBOX_SIZE;
+        let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
+        !self.blocked_by_water(map, pos) && map.is_movement_allowed(
+            pos.x + hitbox_offset,
+            pos.y - TILE_SIZE + hitbox_offset,
+            pos.x + hitbox_offset,
+            pos.y + hitbox_offset,
+            hitbox_size,
+            hitbox_size
+        )
+    }
+
+    /// Attack including equipment bonuses.
+    pub fn attack_power(&self) -> i32 {
+        self.stats.attack + self.equipment.attack_bonus()
+    }
+
+    /// Defense including equipment bonuses.
+    pub fn defense(&self) -> i32 {
+        self.stats.defense + self.equipment.defense_bonus()
+    }
+
+    /// Start a melee swing. Returns false while the previous swing is still playing.
+    pub fn start_attack(&mut self) -> bool {
+        if self.attack_timer > 0.0 { return false; }
+        self.attack_timer = ATTACK_DURATION;
+        true
+    }
+
+    /// Throw a knife (ranged attack). Returns false while still on cooldown.
+    pub fn start_throw(&mut self) -> bool {
+        if self.throw_timer > 0.0 { return false; }
+        self.throw_timer = THROW_COOLDOWN;
+        true
+    }
+
+    /// Take a hit from an enemy. Ignored during the invulnerability window.
+    /// Returns true if the player was knocked out.
+    pub fn hurt(&mut self, raw_damage: i32) -> bool {
+        if self.hurt_timer > 0.0 { return false; }
+        self.hurt_timer = HURT_COOLDOWN;
+        self.take_hit(raw_damage)
+    }
+
+    /// Take a hit regardless of the hurt cooldown (turn-based battles). Returns true if it
+    /// knocked the player out.
+    pub fn take_hit(&mut self, raw_damage: i32) -> bool {
+        // armor soaks damage on top of base defense
+        self.stats.take_damage(raw_damage - self.equipment.defense_bonus())
+    }
+
+    /// Advance status effects. Returns true if a damage tick knocked the player out.
+    pub fn tick_status(&mut self, dt: f32) -> bool {
+        let delta = self.status.update(dt);
+        if delta == 0 { return false; }
+        self.stats.hp = (self.stats.hp + delta).clamp(0, self.stats.max_hp);
+        self.stats.hp == 0
+    }
+
+    /// Use the consumable at inventory `index`. Returns false (and keeps the item) if it
+    /// isn't a consumable.
+    pub fn use_item(&mut self, index: usize) -> bool {
+        let Some(def) = self.inventory.items.get(index).and_then(|id| items::find(id)) else { return false };
+        let (ItemKind::Consumable, Some(effect)) = (def.kind, def.effect) else { return false };
+        match effect {
+            UseEffect::Heal(amount) => {
+                self.stats.hp = (self.stats.hp + amount).min(self.stats.max_hp);
+            }
+            UseEffect::Apply(kind, secs) => self.status.apply(kind, secs),
+            UseEffect::Cure => {
+                for kind in [StatusKind::Poison, StatusKind::Burn, StatusKind::Slow] {
+                    self.status.cure(kind);
+                }
             }
         }
+        self.inventory.take(index);
+        println!("Player: used {}", def.name);
+        true
+    }
 
-        // clamp
-        let (w, h) = (map.width_pixels() as f32, map.height_pixels() as f32);
-        self.position.x = self.position.x.max(0.0).min(w - 32.0);
-        self.position.y = self.position.y.max(0.0).min(h - 32.0);
+    /// Award XP; starts the celebration effect on level-up. Returns levels gained.
+    pub fn gain_xp(&mut self, amount: u32) -> u32 {
+        let gained = self.stats.gain_xp(amount);
+        if gained > 0 {
+            self.level_up_timer = LEVEL_UP_EFFECT_SECS;
+            println!("Player: reached level {}", self.stats.level);
+            for def in self.skills.learn_up_to(self.stats.level) {
+                println!("Player: learned {}", def.name);
+            }
+        }
+        gained
     }
 
-    pub fn draw(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets) -> GameResult {
-        // default draw with scale 1.0, no offset, entity size 1x
-        self.draw_scaled(_ctx, canvas, assets, 1.0, (0.0, 0.0), 1.0)
+    /// Start falling down a pit: movement stops until `tick_fall` reports the fall is over.
+    pub fn start_fall(&mut self) {
+        self.falling = FALL_SECS;
+        self.moving = false;
+        self.target = self.position;
+        self.hop_from = None;
     }
 
-    pub fn get_position(&self) -> na::Point2<f32> {
-        self.position
+    pub fn is_falling(&self) -> bool {
+        self.falling > 0.0
+    }
+
+    /// Advance the fall animation. Returns true on the frame it finishes.
+    pub fn tick_fall(&mut self, dt: f32) -> bool {
+        if self.falling <= 0.0 { return false; }
+        self.falling = (self.falling - dt).max(0.0);
+        self.falling == 0.0
+    }
+
+    /// Instantly move to `tile` (teleport pads), cancelling any step in progress.
+    pub fn warp_to(&mut self, tile: (i32, i32)) {
+        self.position = na::Point2::new(tile.0 as f32 * TILE_SIZE, tile.1 as f32 * TILE_SIZE);
+        self.target = self.position;
+        self.moving = false;
+        self.hop_from = None;
+        self.sitting = false;
+    }
+
+    /// Sit down on the chair at `tile`.
+    pub fn sit_at(&mut self, tile: (i32, i32)) {
+        self.warp_to(tile);
+        self.sitting = true;
+        self.facing = (0.0, 1.0);
+    }
+
+    /// Wake up back in bed with full health (after being knocked out).
+    pub fn respawn(&mut self) {
+        self.position = na::Point2::new(START_POS.0, START_POS.1);
+        self.target = self.position;
+        self.moving = false;
+        self.facing = (0.0, 1.0);
+        self.hop_from = None;
+        self.falling = 0.0;
+        self.stats.hp = self.stats.max_hp;
+        self.stats.mp = self.stats.max_mp;
+        self.status.clear();
+        self.hurt_timer = HURT_COOLDOWN;
     }
 
     // Update player: move towards target if grid-moving, or check for new input
-    pub fn update(&mut self, ctx: &mut Context, dt: f32, map: &Map) {
+    pub fn update(&mut self, controls: Controls, dt: f32, map: &mut Map) {
+        self.stepped = false;
+        self.pushed = false;
+        self.attack_timer = (self.attack_timer - dt).max(0.0);
+        self.hurt_timer = (self.hurt_timer - dt).max(0.0);
+        self.level_up_timer = (self.level_up_timer - dt).max(0.0);
+        self.throw_timer = (self.throw_timer - dt).max(0.0);
+        self.skills.update(dt);
+        self.mp_regen_timer += dt;
+        while self.mp_regen_timer >= MP_REGEN_SECS {
+            self.mp_regen_timer -= MP_REGEN_SECS;
+            self.stats.mp = (self.stats.mp + 1).min(self.stats.max_mp);
+        }
+
+        // sneaking wins over running
+        self.gait = if controls.sneak { Gait::Sneak } else if controls.run != self.auto_run { Gait::Run } else { Gait::Walk };
+
+        // no control while tumbling down a pit
+        if self.is_falling() { return; }
+
         // Get current grid position (where we should be on the grid)
         let current_grid_x = (self.position.x / TILE_SIZE).round() as i32;
         let current_grid_y = (self.position.y / TILE_SIZE).round() as i32;
@@ -100,33 +462,43 @@ impl Player {
         let grid_pos = na::Point2::new(current_grid_x as f32 * TILE_SIZE, current_grid_y as f32 * TILE_SIZE);
         let is_at_grid_position = (self.position - grid_pos).magnitude() < 1.0;
         
-        // Only allow new input when not moving OR when we're at a grid position
-        let should_check_input = !self.moving || is_at_grid_position;
+        // Only allow new input when not moving OR when we're at a grid position (but never
+        // mid-hop: the player is in the air over the ledge)
+        let should_check_input = (!self.moving || is_at_grid_position) && self.hop_from.is_none();
         
-        if should_check_input {
-            if ctx.keyboard.is_key_pressed(KeyCode::Left) || ctx.keyboard.is_key_pressed(KeyCode::A) {
-                new_direction = Some((-1, 0));
-                self.facing = (-1.0, 0.0);
-            } else if ctx.keyboard.is_key_pressed(KeyCode::Right) || ctx.keyboard.is_key_pressed(KeyCode::D) {
-                new_direction = Some((1, 0));
-                self.facing = (1.0, 0.0);
-            } else if ctx.keyboard.is_key_pressed(KeyCode::Up) || ctx.keyboard.is_key_pressed(KeyCode::W) {
-                new_direction = Some((0, -1));
-                self.facing = (0.0, -1.0);
-            } else if ctx.keyboard.is_key_pressed(KeyCode::Down) || ctx.keyboard.is_key_pressed(KeyCode::S) {
-                new_direction = Some((0, 1));
-                self.facing = (0.0, 1.0);
-            }
+        let held = controls.held;
+        if self.sitting && held.is_some() {
+            self.sitting = false;
+        }
+        if held.is_some() {
+            // the keyboard takes over from a clicked path
+            self.path.clear();
+        }
+        let may_step = self.tap_to_turn(held, dt);
+        if should_check_input && may_step && let Some((dx, dy)) = held {
+            new_direction = Some((dx, dy));
+            self.facing = (dx as f32, dy as f32);
+        } else if should_check_input && held.is_none() && let Some((dx, dy)) = self.path_step((current_grid_x, current_grid_y)) {
+            new_direction = Some((dx, dy));
+            self.facing = (dx as f32, dy as f32);
         }
 
         // If we have a new direction, calculate the target
         if let Some((dx, dy)) = new_direction {
             // Calculate target from current grid position to ensure grid alignment
             let target_grid_x = current_grid_x + dx;
-            let target_grid_y = current_grid_y + dy;
+            let mut target_grid_y = current_grid_y + dy;
+            // Heading south onto a ledge hops clear over it
+            let hop = dy == 1 && map.is_ledge(target_grid_x, target_grid_y);
+            if hop { target_grid_y += 1; }
             let target_x = target_grid_x as f32 * TILE_SIZE;
             let target_y = target_grid_y as f32 * TILE_SIZE;
             let potential_target = na::Point2::new(target_x, target_y);
+
+            // Walking into a pushable block shoves it ahead (fails quietly if it's stuck)
+            if !self.moving && target_grid_x >= 0 && target_grid_y >= 0 {
+                self.pushed = map.push_block(target_grid_x as usize, target_grid_y as usize, dx, dy);
+            }
             
             // If we're moving and this would be a direction change, snap to grid first
             if self.moving && !is_at_grid_position {
@@ -145,13 +517,22 @@ impl Player {
             }
             
             // Set new target if it's different from current target
-            if !self.moving || (self.target - potential_target).magnitude() > 0.1 {
+            if self.blocked_by_water(map, potential_target) || (hop && !self.can_land(map, potential_target)) {
+                // can't swim, or nowhere to land below the ledge: just face that way
+            } else if !self.moving || (self.target - potential_target).magnitude() > 0.1 {
                 new_target = Some(potential_target);
             }
         }
 
         // Apply new target if we have one
         if let Some(target) = new_target {
+            // Retargeting on the tile we were heading for: that step has landed
+            if self.moving && (self.target - grid_pos).magnitude() < 1.0 {
+                self.stepped = true;
+            }
+            if (target.y - self.position.y) > TILE_SIZE * 1.5 {
+                self.hop_from = Some(self.position.y);
+            }
             self.target = target;
             self.moving = true;
         }
@@ -163,12 +544,22 @@ impl Player {
                 self.moving = false;
                 return;
             }
-            let step = self.speed * dt;
+            let wading = match self.water(map) {
+                Some(Water::Shallow) => 0.6,
+                Some(Water::Deep) => 0.5,
+                None => 1.0,
+            };
+            let pace = match self.gait {
+                Gait::Walk => 1.0,
+                Gait::Run => RUN_SPEED,
+                Gait::Sneak => SNEAK_SPEED,
+            };
+            let step = self.speed * self.status.speed_multiplier() * wading * pace * dt;
             if dist <= step {
                 // snap to target
                 // collision check at target using rectangle test with special bed movement rules
                 // Use slightly smaller hitbox to allow smooth movement along walls
-                let hitbox_size = TILE_SIZE * 0.9;
+                let hitbox_size = HITBOX_SIZE;
                 let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
                 if map.is_movement_allowed(
                     self.position.x + hitbox_offset, 
@@ -179,8 +570,15 @@ impl Player {
                     hitbox_size
                 ) {
                     self.position = self.target;
+                    self.stepped = true;
                 }
                 self.moving = false;
+                self.hop_from = None;
+                // Key still held (or more path to walk): chain straight into the next step so
+                // there's no pause at the tile boundary
+                if let Some(dir) = controls.held.or_else(|| self.path_step(self.tile())) {
+                    self.queue_next_step(dir, step - dist, map);
+                }
             } else {
                 // Ensure movement is strictly horizontal or vertical (no diagonal interpolation)
                 let mut movement = na::Vector2::new(0.0, 0.0);
@@ -194,7 +592,7 @@ impl Player {
                 
                 let new_pos = na::Point2::new(self.position.x + movement.x, self.position.y + movement.y);
                 // Use slightly smaller hitbox to allow smooth movement along walls
-                let hitbox_size = TILE_SIZE * 0.9;
+                let hitbox_size = HITBOX_SIZE;
                 let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
                 if map.is_movement_allowed(
                     self.position.x + hitbox_offset, 
@@ -208,6 +606,8 @@ impl Player {
                 } else {
                     // stop if blocked
                     self.moving = false;
+                    self.hop_from = None;
+                    self.path.clear();
                 }
             }
         }
@@ -228,41 +628,77 @@ impl Player {
         }
     }
 
-    // Handle a single key press to initiate a single grid move
-    pub fn handle_key(&mut self, key: KeyCode) {
-        // allow changing direction immediately, even while moving
-        let gs = self.grid_size.max(16.0);
-        let mut target = self.position.clone();
-        match key {
-            KeyCode::Left | KeyCode::A => { target.x -= gs; }
-            KeyCode::Right | KeyCode::D => { target.x += gs; }
-            KeyCode::Up | KeyCode::W => { target.y -= gs; }
-            KeyCode::Down | KeyCode::S => { target.y += gs; }
-            _ => { return; }
+    /// Tap-to-turn: while standing still, pressing a direction we aren't facing turns the
+    /// player immediately but only steps once the key has been held for `turn_threshold`.
+    /// Returns whether a step may start this frame.
+    fn tap_to_turn(&mut self, held: Option<(i32, i32)>, dt: f32) -> bool {
+        let pressed_now = held.is_some() && held != self.last_held;
+        self.last_held = held;
+        let Some((dx, dy)) = held else {
+            self.turn_hold = 0.0;
+            return false;
+        };
+        if self.moving { return true; }
+        let dir = (dx as f32, dy as f32);
+        if pressed_now {
+            self.turn_hold = if self.facing == dir { self.turn_threshold } else { 0.0 };
+            self.facing = dir;
+        } else {
+            self.turn_hold += dt;
+        }
+        self.turn_hold >= self.turn_threshold
+    }
+
+    /// Start the next grid step in `dir` right away, spending `leftover` pixels of this frame's
+    /// movement on it. Does nothing (beyond turning) if the next tile is blocked.
+    fn queue_next_step(&mut self, dir: (i32, i32), leftover: f32, map: &Map) {
+        self.facing = (dir.0 as f32, dir.1 as f32);
+        let mut next = na::Point2::new(self.position.x + dir.0 as f32 * TILE_SIZE, self.position.y + dir.1 as f32 * TILE_SIZE);
+        let (tx, ty) = ((next.x / TILE_SIZE).round() as i32, (next.y / TILE_SIZE).round() as i32);
+        let hop = dir == (0, 1) && map.is_ledge(tx, ty);
+        if hop { next.y += TILE_SIZE; }
+        let hitbox_size = HITBOX_SIZE;
+        let hitbox_offset = (TILE_SIZE - hitbox_size) / 2.0;
+        if self.blocked_by_water(map, next) || !map.is_movement_allowed(
+            self.position.x + hitbox_offset,
+            self.position.y + hitbox_offset,
+            next.x + hitbox_offset,
+            next.y + hitbox_offset,
+            hitbox_size,
+            hitbox_size
+        ) {
+            return;
         }
-        // Align target to tile center
-        let tx = (target.x / TILE_SIZE).round() as i32;
-        let ty = (target.y / TILE_SIZE).round() as i32;
-        let new_x = (tx as f32) * TILE_SIZE;
-        let new_y = (ty as f32) * TILE_SIZE;
-        self.target = na::Point2::new(new_x, new_y);
+        if hop { self.hop_from = Some(self.position.y); }
+        self.target = next;
         self.moving = true;
+        let carry = leftover.clamp(0.0, TILE_SIZE);
+        self.position.x += dir.0 as f32 * carry;
+        self.position.y += dir.1 as f32 * carry;
     }
 
     /// Draw with global scale and screen offset. `entity_scale` is how many tiles this
     /// entity occupies (1.0 = 1x1, 2.0 = 2x2).
-    pub fn draw_scaled(&self, _ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32), entity_scale: f32) -> GameResult {
+    pub fn draw_scaled(&self, ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, scale: f32, offset: (f32, f32), entity_scale: f32) -> GameResult {
         // compute center position in world coordinates, then apply scale and offset
         let center_x = self.position.x + TILE_SIZE * (entity_scale) / 2.0;
         let center_y = self.position.y + TILE_SIZE * (entity_scale) / 2.0;
         let draw_x = offset.0 + center_x * scale;
-        let draw_y = offset.1 + center_y * scale;
-        let dest = ggez::mint::Point2 { x: draw_x, y: draw_y };
+        let mut draw_y = offset.1 + center_y * scale;
         let img_scale = scale * TILE_SIZE * entity_scale / assets.player.width() as f32;
+
+        // Ledge hop: a shadow stays on the ground while the sprite arcs up and over
+        if let Some(from_y) = self.hop_from && self.moving {
+            let progress = ((self.position.y - from_y) / (TILE_SIZE * 2.0)).clamp(0.0, 1.0);
+            let shadow = ggez::graphics::Mesh::new_ellipse(ctx, ggez::graphics::DrawMode::fill(), [draw_x, draw_y + TILE_SIZE * 0.35 * scale], TILE_SIZE * 0.35 * scale, TILE_SIZE * 0.15 * scale, 0.5, ggez::graphics::Color::new(0.0, 0.0, 0.0, 0.35))?;
+            canvas.draw(&shadow, DrawParam::new());
+            draw_y -= (progress * std::f32::consts::PI).sin() * TILE_SIZE * 0.6 * scale;
+        }
+        let dest = ggez::mint::Point2 { x: draw_x, y: draw_y };
         
         // Calculate rotation based on facing direction
         // Assuming the sprite faces down by default (0.0, 1.0)
-        let rotation = if self.facing.0.abs() > self.facing.1.abs() {
+        let mut rotation = if self.facing.0.abs() > self.facing.1.abs() {
             // Horizontal movement
             if self.facing.0 > 0.0 { std::f32::consts::FRAC_PI_2 } // Right: 90 degrees
             else { -std::f32::consts::FRAC_PI_2 } // Left: -90 degrees
@@ -272,16 +708,56 @@ impl Player {
             else { std::f32::consts::PI } // Up: 180 degrees
         };
         
-        canvas.draw(&assets.player, DrawParam::new().dest(dest).offset([0.5, 0.5]).rotation(rotation).scale([img_scale, img_scale]));
+        // Falling: spin and shrink away into the hole
+        let mut img_scale = [img_scale, img_scale];
+        let mut dest = dest;
+        if self.is_falling() {
+            let left = self.falling / FALL_SECS;
+            img_scale = [img_scale[0] * left, img_scale[1] * left];
+            rotation += (1.0 - left) * std::f32::consts::TAU;
+        }
+        // Sitting: squashed down onto the seat
+        if self.sitting {
+            img_scale[1] *= 0.8;
+            dest.y += TILE_SIZE * 0.1 * scale;
+        }
+
+        // Sneaking: crouched low and in the shadows
+        let tint = if self.gait == Gait::Sneak {
+            img_scale = [img_scale[0] * 0.9, img_scale[1] * 0.9];
+            ggez::graphics::Color::new(0.6, 0.6, 0.75, 1.0)
+        } else {
+            ggez::graphics::Color::WHITE
+        };
+
+        // Blink while invulnerable after a hit
+        let visible = self.hurt_timer <= 0.0 || ((self.hurt_timer * 10.0) as i32) % 2 == 0;
+        if visible {
+            canvas.draw(&assets.player, DrawParam::new().dest(dest).offset([0.5, 0.5]).rotation(rotation).scale(img_scale).color(tint));
+        }
+
+        if self.attack_timer > 0.0 {
+            self.draw_swing(ctx, canvas, dest, scale)?;
+        }
         Ok(())
     }
 
-    pub fn snap_to_grid_center(&mut self) {
-        let grid_x = (self.position.x / TILE_SIZE as f32).round() as i32;
-        let grid_y = (self.position.y / TILE_SIZE as f32).round() as i32;
-        self.position.x = grid_x as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0;
-        self.position.y = grid_y as f32 * TILE_SIZE as f32 + TILE_SIZE as f32 / 2.0;
+    /// Melee swing: the equipped weapon's blade sweeping across the facing tile
+    /// (a short, skin-coloured jab when unarmed).
+    fn draw_swing(&self, ctx: &mut Context, canvas: &mut Canvas, center: ggez::mint::Point2<f32>, scale: f32) -> GameResult {
+        let progress = 1.0 - self.attack_timer / ATTACK_DURATION;
+        let base_angle = self.facing.1.atan2(self.facing.0);
+        let weapon = self.equipment.weapon.and_then(items::find);
+        let (color, reach, sweep) = match weapon {
+            Some(def) => (def.color, TILE_SIZE * 1.3, 1.8),
+            None => ((0.95, 0.8, 0.65), TILE_SIZE * 0.8, 0.4),
+        };
+        let angle = base_angle - sweep / 2.0 + sweep * progress;
+        let reach = reach * scale;
+        let start = [center.x + angle.cos() * reach * 0.3, center.y + angle.sin() * reach * 0.3];
+        let end = [center.x + angle.cos() * reach, center.y + angle.sin() * reach];
+        let blade = ggez::graphics::Mesh::new_line(ctx, &[start, end], 4.0 * scale, ggez::graphics::Color::new(color.0, color.1, color.2, 1.0 - progress * 0.5))?;
+        canvas.draw(&blade, DrawParam::new());
+        Ok(())
     }
-
-    // Call snap_to_grid_center() after creating the player in Game::new
 }
@@ -0,0 +1,152 @@
+//! Optional retro look for the finished frame: scanlines, a curved CRT tube or an LCD grid, and
+//! the GBA's 15-bit colour.
+//!
+//! With any of them on, every screen draws into an offscreen image first, which then goes through
+//! each shader in turn on its way to the window. The shaders live in `assets/shaders` and are
+//! built into the binary.
+
+use ggez::graphics::{Canvas, Color, DrawParam, Image, Sampler, ScreenImage, Shader, ShaderBuilder};
+use ggez::{Context, GameResult};
+
+/// Post-process effect picked in the Video options.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Effect {
+    Off,
+    /// Dark gaps between rows, like an old TV.
+    Scanlines,
+    /// Scanlines on a bulging tube with dark corners.
+    Curvature,
+    /// The red/green/blue cells of a handheld screen.
+    Lcd,
+}
+
+impl Effect {
+    pub const ALL: [Effect; 4] = [Effect::Off, Effect::Scanlines, Effect::Curvature, Effect::Lcd];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Effect::Off => "Off",
+            Effect::Scanlines => "Scanlines",
+            Effect::Curvature => "CRT Curve",
+            Effect::Lcd => "LCD Grid",
+        }
+    }
+
+    /// Name used in options.txt.
+    pub fn key(self) -> &'static str {
+        match self {
+            Effect::Off => "off",
+            Effect::Scanlines => "scanlines",
+            Effect::Curvature => "curvature",
+            Effect::Lcd => "lcd",
+        }
+    }
+
+    pub fn parse(key: &str) -> Option<Effect> {
+        Effect::ALL.into_iter().find(|e| e.key() == key)
+    }
+
+    pub fn cycled(self, forward: bool) -> Effect {
+        let current = Effect::ALL.iter().position(|&e| e == self).unwrap_or(0);
+        let n = Effect::ALL.len();
+        Effect::ALL[if forward { (current + 1) % n } else { (current + n - 1) % n }]
+    }
+
+    fn source(self) -> Option<&'static str> {
+        match self {
+            Effect::Off => None,
+            Effect::Scanlines => Some(include_str!("../assets/shaders/scanlines.wgsl")),
+            Effect::Curvature => Some(include_str!("../assets/shaders/curvature.wgsl")),
+            Effect::Lcd => Some(include_str!("../assets/shaders/lcd.wgsl")),
+        }
+    }
+}
+
+pub struct PostFx {
+    /// Window-sized images the frame is drawn into, and handed between, while a pass is on.
+    screens: [ScreenImage; 2],
+    shaders: Vec<(Effect, Shader)>,
+    /// GBA colour; runs before the effect so the effect's own shading doesn't get banded.
+    palette: Option<Shader>,
+    /// Draw offscreen even with no passes, so the frame can be read back (clip recording).
+    pub offscreen: bool,
+}
+
+impl PostFx {
+    pub fn new(ctx: &Context) -> PostFx {
+        let mut shaders = Vec::new();
+        for effect in Effect::ALL {
+            let Some(source) = effect.source() else { continue };
+            match ShaderBuilder::new().fragment_code(source).build(ctx) {
+                Ok(shader) => shaders.push((effect, shader)),
+                Err(e) => println!("PostFx: couldn't build the {} shader: {}", effect.name(), e),
+            }
+        }
+        let palette = ShaderBuilder::new().fragment_code(include_str!("../assets/shaders/gba_palette.wgsl")).build(ctx);
+        if let Err(e) = &palette {
+            println!("PostFx: couldn't build the GBA colour shader: {}", e);
+        }
+        let screen = || ScreenImage::new(ctx, None, 1.0, 1.0, 1);
+        PostFx { screens: [screen(), screen()], shaders, palette: palette.ok(), offscreen: false }
+    }
+
+    /// Shaders the frame goes through, in order; a plain copy (None) when drawing offscreen
+    /// without any.
+    fn passes(&self, effect: Effect, gba_colors: bool) -> Vec<Option<Shader>> {
+        let palette = self.palette.iter().filter(|_| gba_colors);
+        let effect = self.shaders.iter().filter(|(e, _)| *e == effect).map(|(_, shader)| shader);
+        let passes: Vec<_> = palette.chain(effect).cloned().map(Some).collect();
+        if passes.is_empty() && self.offscreen { vec![None] } else { passes }
+    }
+
+    /// Canvas for this frame: the window itself, or an offscreen image when any pass is on.
+    pub fn canvas(&mut self, ctx: &Context, effect: Effect, gba_colors: bool, clear: Color) -> Canvas {
+        if self.passes(effect, gba_colors).is_empty() {
+            Canvas::from_frame(ctx, clear)
+        } else {
+            Canvas::from_screen_image(ctx, &mut self.screens[0], clear)
+        }
+    }
+
+    /// Finish `canvas` and run it through the passes, the last of which draws to the window.
+    pub fn present(&mut self, ctx: &mut Context, canvas: Canvas, effect: Effect, gba_colors: bool) -> GameResult {
+        canvas.finish(ctx)?;
+        let passes = self.passes(effect, gba_colors);
+        for (i, shader) in passes.iter().enumerate() {
+            let image = self.screens[i % 2].image(ctx);
+            let mut target = if i + 1 == passes.len() {
+                Canvas::from_frame(ctx, Color::BLACK)
+            } else {
+                Canvas::from_screen_image(ctx, &mut self.screens[(i + 1) % 2], Color::BLACK)
+            };
+            target.set_sampler(Sampler::linear_clamp());
+            if let Some(shader) = shader {
+                target.set_shader(shader);
+            }
+            target.draw(&image, DrawParam::new());
+            target.finish(ctx)?;
+        }
+        Ok(())
+    }
+
+    /// The frame as drawn, before any pass; only meaningful while drawing offscreen.
+    pub fn frame(&mut self, ctx: &Context) -> Image {
+        self.screens[0].image(ctx)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_cycle_and_round_trip_their_keys() {
+        for effect in Effect::ALL {
+            assert_eq!(Effect::parse(effect.key()), Some(effect));
+            assert_eq!(effect.cycled(true).cycled(false), effect);
+            assert_eq!(effect.source().is_some(), effect != Effect::Off);
+        }
+        assert_eq!(Effect::Lcd.cycled(true), Effect::Off);
+        assert_eq!(Effect::parse("bloom"), None);
+    }
+}
@@ -0,0 +1,279 @@
+//! Named player profiles and the screen for choosing one.
+//!
+//! Every profile has its own folder holding its save slots and achievement profile (see
+//! `save::dir`), and another for its options (`save::config_dir`). The list of profiles and the
+//! one used last are kept in `profiles.txt` in the data root; the game starts in the last
+//! profile, or asks for one on first launch.
+//! The title menu can switch profiles at any time.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+use crate::save;
+
+const MAX_NAME_LEN: usize = 16;
+
+/// Folder name for a profile: its name in lowercase, with anything but letters and digits as `_`.
+pub fn folder(name: &str) -> String {
+    name.trim().chars().map(|c| if c.is_ascii_alphanumeric() { c.to_ascii_lowercase() } else { '_' }).collect()
+}
+
+/// Move files saved before profiles existed (`slot*.sav`, `profile.txt` in the data root) into
+/// a profile's folder, so the first profile picks up where the player left off.
+pub fn adopt_legacy_files(folder: &str) {
+    let root = save::data_root();
+    let target = root.join(folder);
+    let legacy = (1..=crate::save::SAVE_SLOT_COUNT).map(|n| format!("slot{}.sav", n)).chain(["profile.txt".to_string()]);
+    for file in legacy {
+        let from = root.join(&file);
+        if !from.is_file() { continue; }
+        match fs::create_dir_all(&target).and_then(|_| fs::rename(&from, target.join(&file))) {
+            Ok(()) => println!("Profiles: moved {} into {}", file, folder),
+            Err(e) => println!("Profiles: failed to move {}: {}", file, e),
+        }
+    }
+}
+
+/// Every profile, in creation order, and the one used last.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileList {
+    pub names: Vec<String>,
+    pub last: Option<String>,
+}
+
+impl ProfileList {
+    fn path() -> PathBuf {
+        save::data_root().join("profiles.txt")
+    }
+
+    /// Load the profile list, or an empty one on first launch.
+    pub fn load() -> ProfileList {
+        fs::read_to_string(ProfileList::path()).map(|t| ProfileList::from_text(&t)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = fs::create_dir_all(save::data_root()).and_then(|_| fs::write(ProfileList::path(), self.to_text()));
+        if let Err(e) = result {
+            println!("Profiles: failed to write profile list: {}", e);
+        }
+    }
+
+    /// `names=` separated by `|`, then `last=`.
+    pub fn to_text(&self) -> String {
+        format!("names={}\nlast={}\n", self.names.join("|"), self.last.as_deref().unwrap_or(""))
+    }
+
+    pub fn from_text(text: &str) -> ProfileList {
+        let mut list = ProfileList::default();
+        for (key, value) in text.lines().filter_map(|l| l.split_once('=')) {
+            match key.trim() {
+                "names" => list.names = value.split('|').map(str::trim).filter(|n| !n.is_empty()).map(str::to_string).collect(),
+                "last" => list.last = Some(value.trim().to_string()).filter(|n| !n.is_empty()),
+                _ => {}
+            }
+        }
+        // a last profile that's gone from the list means asking again
+        if list.last.as_ref().is_some_and(|last| !list.names.contains(last)) {
+            list.last = None;
+        }
+        list
+    }
+
+    /// Create a profile. Returns its tidied name, or why it can't be used.
+    pub fn add(&mut self, name: &str) -> Result<String, &'static str> {
+        let name = name.trim();
+        if folder(name).chars().all(|c| c == '_') { return Err("Type a name first"); }
+        if self.names.iter().any(|n| folder(n) == folder(name)) { return Err("That name is taken"); }
+        self.names.push(name.to_string());
+        Ok(name.to_string())
+    }
+}
+
+pub enum ProfileAction {
+    /// Switch to this profile.
+    Select(String),
+    Close,
+}
+
+/// Name form state while the player is typing a new profile's name.
+struct NameEntry {
+    text: String,
+    // the letter key that opened the form also arrives as a typed character; drop it
+    swallow_next_char: bool,
+}
+
+pub struct ProfileScreen {
+    pub visible: bool,
+    /// Index into the names, or one past the end for "New profile".
+    selected: usize,
+    entry: Option<NameEntry>,
+    /// False on first launch, when a profile has to be picked before anything else.
+    can_close: bool,
+    error: Option<&'static str>,
+}
+
+impl ProfileScreen {
+    pub fn new() -> ProfileScreen {
+        ProfileScreen { visible: false, selected: 0, entry: None, can_close: true, error: None }
+    }
+
+    /// Open with the `current` profile selected. With no profiles yet it goes straight to
+    /// typing a name.
+    pub fn open(&mut self, list: &ProfileList, current: Option<&str>, can_close: bool) {
+        self.visible = true;
+        self.can_close = can_close;
+        self.error = None;
+        self.selected = current.and_then(|c| list.names.iter().position(|n| n == c)).unwrap_or(0);
+        self.entry = list.names.is_empty().then(|| NameEntry { text: String::new(), swallow_next_char: false });
+    }
+
+    /// True while the name form is open; typed characters belong to it.
+    pub fn is_typing(&self) -> bool {
+        self.visible && self.entry.is_some()
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, list: &ProfileList) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let rows = list.names.len() + 1;
+        let box_w = 400.0;
+        let box_h = 130.0 + rows as f32 * 40.0;
+        let left = (w - box_w) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new("Who's playing?").scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+
+        for i in 0..rows {
+            let y = top + 66.0 + i as f32 * 40.0;
+            let label = match (list.names.get(i), &self.entry) {
+                (Some(name), _) => name.clone(),
+                (None, Some(entry)) => format!("Name: {}_", entry.text),
+                (None, None) => "New profile".to_string(),
+            };
+            let txt = Text::new(TextFragment::new(label).scale(22.0));
+            let color = if i == self.selected { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::WHITE };
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(color));
+            if i == self.selected {
+                let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 34.0);
+                let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                canvas.draw(&sel_box, DrawParam::new());
+            }
+        }
+
+        let hint = match (self.error, &self.entry) {
+            (Some(error), _) => error,
+            (None, Some(_)) => "{submit}: create   Esc: cancel",
+            (None, None) if self.can_close => "{confirm}: choose   {new}: new profile   {cancel}: back",
+            (None, None) => "{confirm}: choose   {new}: new profile",
+        };
+        let hint_txt = Text::new(TextFragment::new(crate::input::prompt(hint)).scale(16.0));
+        canvas.draw(&hint_txt, DrawParam::new().dest([left + 20.0, top + box_h - 34.0]).color(Color::WHITE));
+        Ok(())
+    }
+
+    /// Typed characters while the name form is open.
+    pub fn handle_text(&mut self, ch: char) {
+        let Some(entry) = self.entry.as_mut().filter(|_| self.visible) else { return; };
+        if entry.swallow_next_char {
+            entry.swallow_next_char = false;
+            return;
+        }
+        if (ch.is_ascii_alphanumeric() || ch == ' ' || ch == '-' || ch == '_') && entry.text.len() < MAX_NAME_LEN {
+            entry.text.push(ch);
+            self.error = None;
+        }
+    }
+
+    /// Input while open. Creating a profile adds it to `list` (the caller saves the list).
+    pub fn handle_key(&mut self, key: KeyCode, list: &mut ProfileList) -> Option<ProfileAction> {
+        if !self.visible { return None; }
+        if let Some(entry) = &mut self.entry {
+            match key {
+                KeyCode::Back => { entry.text.pop(); }
+                k if Action::Submit.matches(k) => match list.add(&entry.text) {
+                    Ok(name) => {
+                        self.entry = None;
+                        self.visible = false;
+                        return Some(ProfileAction::Select(name));
+                    }
+                    Err(error) => self.error = Some(error),
+                },
+                // the very first profile can't be skipped
+                KeyCode::Escape if !list.names.is_empty() => { self.entry = None; self.error = None; }
+                _ => {}
+            }
+            return None;
+        }
+
+        match key {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(list.names.len()); }
+            k if Action::Confirm.matches(k) => match list.names.get(self.selected) {
+                Some(name) => {
+                    self.visible = false;
+                    return Some(ProfileAction::Select(name.clone()));
+                }
+                // a gamepad's stand-in Z types nothing to swallow
+                None => self.open_entry(key == KeyCode::Z && !crate::input::pad_in_use(), list),
+            },
+            k if Action::New.matches(k) => self.open_entry(!crate::input::pad_in_use(), list),
+            k if Action::Cancel.matches(k) && self.can_close => {
+                self.visible = false;
+                return Some(ProfileAction::Close);
+            }
+            _ => {}
+        }
+        None
+    }
+
+    fn open_entry(&mut self, swallow_next_char: bool, list: &ProfileList) {
+        self.selected = list.names.len();
+        self.entry = Some(NameEntry { text: String::new(), swallow_next_char });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn names_are_unique_by_folder_and_the_list_round_trips() {
+        let mut list = ProfileList::default();
+        assert_eq!(list.add("  Ada "), Ok("Ada".to_string()));
+        assert_eq!(list.add("ada"), Err("That name is taken"));
+        assert_eq!(list.add(" - "), Err("Type a name first"));
+        assert_eq!(list.add("Mira 2"), Ok("Mira 2".to_string()));
+        assert_eq!(folder("Mira 2"), "mira_2");
+        list.last = Some("Mira 2".to_string());
+        assert_eq!(ProfileList::from_text(&list.to_text()), list);
+        assert_eq!(ProfileList::from_text("names=Ada\nlast=Gone").last, None);
+    }
+
+    #[test]
+    fn first_launch_insists_on_a_new_profile() {
+        let mut list = ProfileList::default();
+        let mut screen = ProfileScreen::new();
+        screen.open(&list, None, false);
+        assert!(screen.is_typing());
+        assert!(screen.handle_key(KeyCode::Escape, &mut list).is_none());
+        assert!(screen.is_typing(), "can't back out of the first profile");
+        for ch in "Pip".chars() {
+            screen.handle_text(ch);
+        }
+        assert!(matches!(screen.handle_key(KeyCode::Return, &mut list), Some(ProfileAction::Select(name)) if name == "Pip"));
+        assert_eq!(list.names, vec!["Pip".to_string()]);
+    }
+}
@@ -0,0 +1,110 @@
+//! Projectiles: thrown knives, spell bolts and enemy fire.
+//!
+//! Each projectile flies in a straight line until its lifespan runs out or it hits a solid
+//! tile. Hitting entities is resolved by the game, which knows who the owner may damage.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh};
+use nalgebra as na;
+
+use crate::map::{Map, TILE_SIZE};
+use crate::status::StatusKind;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Owner {
+    Player,
+    Enemy,
+}
+
+pub struct Projectile {
+    /// World pixel position of the projectile's centre.
+    pub position: na::Point2<f32>,
+    /// Pixels per second.
+    pub velocity: na::Vector2<f32>,
+    pub owner: Owner,
+    pub damage: i32,
+    /// Seconds left before it fizzles out.
+    pub lifespan: f32,
+    /// Status effect applied to whatever it hits.
+    pub inflicts: Option<(StatusKind, f32)>,
+    color: Color,
+    radius: f32,
+}
+
+impl Projectile {
+    /// Launch from the centre of `tile` towards `dir` (a unit grid direction), covering at
+    /// most `range` tiles.
+    pub fn from_tile(owner: Owner, tile: (i32, i32), dir: (f32, f32), speed: f32, range: f32, damage: i32) -> Projectile {
+        let position = na::Point2::new((tile.0 as f32 + 0.5) * TILE_SIZE, (tile.1 as f32 + 0.5) * TILE_SIZE);
+        Projectile {
+            position,
+            velocity: na::Vector2::new(dir.0, dir.1) * speed,
+            owner,
+            damage,
+            lifespan: range * TILE_SIZE / speed,
+            inflicts: None,
+            color: Color::new(0.85, 0.88, 0.95, 1.0),
+            radius: 4.0,
+        }
+    }
+
+    pub fn with_status(mut self, kind: StatusKind, duration: f32) -> Projectile {
+        self.inflicts = Some((kind, duration));
+        self
+    }
+
+    pub fn with_look(mut self, color: Color, radius: f32) -> Projectile {
+        self.color = color;
+        self.radius = radius;
+        self
+    }
+
+    /// Move and age the projectile. Returns false once it should be removed.
+    pub fn update(&mut self, dt: f32, map: &Map) -> bool {
+        self.position += self.velocity * dt;
+        self.lifespan -= dt;
+        self.lifespan > 0.0 && !map.is_solid_at_point(self.position.x, self.position.y)
+    }
+
+    /// True when the projectile overlaps an entity whose tile top-left is `entity_pos`.
+    pub fn hits(&self, entity_pos: na::Point2<f32>) -> bool {
+        let centre = entity_pos + na::Vector2::new(TILE_SIZE / 2.0, TILE_SIZE / 2.0);
+        let d = self.position - centre;
+        d.x.abs() < TILE_SIZE * 0.45 && d.y.abs() < TILE_SIZE * 0.45
+    }
+
+    pub fn draw_scaled(&self, ctx: &mut Context, canvas: &mut Canvas, scale: f32, offset: (f32, f32)) -> GameResult {
+        let cx = offset.0 + self.position.x * scale;
+        let cy = offset.1 + self.position.y * scale;
+        // short fading trail behind the head
+        let dir = self.velocity.try_normalize(0.0).unwrap_or_else(na::Vector2::zeros);
+        for i in 1..=3 {
+            let back = dir * (i as f32 * self.radius * 1.2 * scale);
+            let mut trail = self.color;
+            trail.a = 0.6 - i as f32 * 0.15;
+            let r = self.radius * scale * (1.0 - i as f32 * 0.2);
+            let mesh = Mesh::new_circle(ctx, DrawMode::fill(), [cx - back.x, cy - back.y], r, 0.5, trail)?;
+            canvas.draw(&mesh, DrawParam::new());
+        }
+        let head = Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy], self.radius * scale, 0.5, self.color)?;
+        canvas.draw(&head, DrawParam::new());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn projectile_stops_at_walls_and_expires() {
+        let map = Map::new();
+        // fired left from tile (1,3): the outer wall is one tile away
+        let mut shot = Projectile::from_tile(Owner::Player, (1, 3), (-1.0, 0.0), 320.0, 5.0, 3);
+        assert!((0..10).map(|_| shot.update(0.05, &map)).any(|alive| !alive));
+        // fired into open floor it fizzles out after its range
+        let mut shot = Projectile::from_tile(Owner::Player, (4, 4), (1.0, 0.0), 320.0, 1.0, 3);
+        assert!(shot.update(0.05, &map));
+        assert!(!shot.update(0.1, &map));
+    }
+}
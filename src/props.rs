@@ -0,0 +1,155 @@
+//! Multi-tile furniture ("props") defined in `assets/props.txt`.
+//!
+//! Each prop is a `[name]` block giving its sprite, its footprint one row per line (top to
+//! bottom; `.` is a cell the player can walk on, `#` a solid one), which sides of the walkable
+//! part can be stepped on and off from, and whether resting on it saves the game:
+//!
+//! ```text
+//! [bed]
+//! sprite=bed
+//! rows=..,..,##
+//! entry=east
+//! save=true
+//! ```
+//!
+//! Rooms place props from their data files with `prop=x,y:name` (top-left corner).
+
+use ggez::Context;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Side {
+    North,
+    South,
+    East,
+    West,
+}
+
+impl Side {
+    fn parse(s: &str) -> Option<Side> {
+        match s.trim() {
+            "north" => Some(Side::North),
+            "south" => Some(Side::South),
+            "east" => Some(Side::East),
+            "west" => Some(Side::West),
+            _ => None,
+        }
+    }
+
+    pub fn opposite(self) -> Side {
+        match self {
+            Side::North => Side::South,
+            Side::South => Side::North,
+            Side::East => Side::West,
+            Side::West => Side::East,
+        }
+    }
+
+    /// Tile offset of the neighbour across this side.
+    pub fn offset(self) -> (i32, i32) {
+        match self {
+            Side::North => (0, -1),
+            Side::South => (0, 1),
+            Side::East => (1, 0),
+            Side::West => (-1, 0),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct PropDef {
+    pub name: String,
+    /// Image name, see `Assets::sprite`.
+    pub sprite: String,
+    /// Footprint, `solid[y][x]`; false cells can be walked on.
+    pub solid: Vec<Vec<bool>>,
+    /// Sides the walkable cells can be entered and left from.
+    pub entry: Vec<Side>,
+    pub save: bool,
+}
+
+impl PropDef {
+    fn new(name: &str) -> PropDef {
+        PropDef { name: name.to_string(), sprite: name.to_string(), solid: Vec::new(), entry: Vec::new(), save: false }
+    }
+
+    pub fn width(&self) -> usize {
+        self.solid.first().map_or(0, Vec::len)
+    }
+
+    pub fn height(&self) -> usize {
+        self.solid.len()
+    }
+
+    /// Whether the footprint cell at `x`,`y` (relative to the top-left) can be walked on.
+    pub fn is_walkable(&self, x: usize, y: usize) -> bool {
+        self.solid.get(y).and_then(|row| row.get(x)) == Some(&false)
+    }
+}
+
+#[derive(Default)]
+pub struct Props {
+    defs: Vec<PropDef>,
+}
+
+impl Props {
+    /// Parse a prop data file. Bad lines and props with no footprint are reported and skipped.
+    pub fn from_text(text: &str) -> Props {
+        let mut defs: Vec<PropDef> = Vec::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+                defs.push(PropDef::new(name.trim()));
+                continue;
+            }
+            let (Some(def), Some((key, value))) = (defs.last_mut(), line.split_once('=')) else {
+                println!("Props: skipping bad line {:?}", line);
+                continue;
+            };
+            let value = value.trim();
+            match key.trim() {
+                "sprite" => def.sprite = value.to_string(),
+                "rows" => def.solid = value.split(',').map(|row| row.trim().chars().map(|c| c != '.').collect()).collect(),
+                "entry" => def.entry = value.split(',').filter_map(Side::parse).collect(),
+                "save" => def.save = value == "true",
+                _ => println!("Props: skipping bad line {:?}", line),
+            }
+        }
+        defs.retain(|def| {
+            let ok = def.width() > 0 && def.solid.iter().all(|row| row.len() == def.width());
+            if !ok { println!("Props: {} has no usable footprint", def.name); }
+            ok
+        });
+        Props { defs }
+    }
+
+    /// Load `assets/props.txt`, or no props at all if the file is missing.
+    pub fn load(ctx: &Context) -> Props {
+        match crate::assets::read_text(ctx, "/props.txt") {
+            Some(text) => Props::from_text(&text),
+            None => {
+                println!("Props: no prop data");
+                Props::default()
+            }
+        }
+    }
+
+    pub fn find(&self, name: &str) -> Option<&PropDef> {
+        self.defs.iter().find(|def| def.name == name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_footprints_and_skips_broken_props() {
+        let props = Props::from_text("[bed]\nsprite=bed\nrows=..,..,##\nentry=east\nsave=true\n[crate]\nrows=#,##\n");
+        let bed = props.find("bed").unwrap();
+        assert_eq!((bed.width(), bed.height()), (2, 3));
+        assert!(bed.is_walkable(1, 1));
+        assert!(!bed.is_walkable(0, 2));
+        assert_eq!(bed.entry, vec![Side::East]);
+        assert!(bed.save);
+        assert!(props.find("crate").is_none(), "ragged footprint");
+    }
+}
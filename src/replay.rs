@@ -0,0 +1,193 @@
+//! Input recording and deterministic replay (`--record <file>`, `--replay <file>`).
+//!
+//! A recording is the session's random seed followed by one line per frame: the frame's length,
+//! the movement key held, and any keys pressed, characters typed or tiles clicked just before it.
+//!
+//! ```text
+//! seed=8812034417
+//! 0.016666668 -
+//! 0.016666668 R
+//! 0.016666668 - key:Z char:97
+//! 0.016666668 U+
+//! 0.016666668 - click:12,4
+//! ```
+//!
+//! A `+` after the direction means Shift was held too, and a `~` after that, Ctrl.
+//!
+//! Replaying feeds those frames back in place of the clock and keyboard, so the same launch
+//! flags and save files give the same game; live keys are ignored until the recording runs
+//! out, then the player takes over. Good for reproducing bug reports and for demos.
+
+use std::fs::{self, File};
+use std::io::{self, LineWriter, Write};
+
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Controls;
+
+/// Keys the game responds to; anything else does nothing and isn't recorded.
+const KEYS: [KeyCode; 24] = [
+    KeyCode::Up, KeyCode::Down, KeyCode::Left, KeyCode::Right,
+    KeyCode::W, KeyCode::A, KeyCode::S, KeyCode::D,
+    KeyCode::Z, KeyCode::X, KeyCode::C, KeyCode::F, KeyCode::I, KeyCode::N,
+    KeyCode::Key1, KeyCode::Key2, KeyCode::Key3, KeyCode::Key4,
+    KeyCode::Space, KeyCode::Return, KeyCode::NumpadEnter, KeyCode::Escape, KeyCode::Back, KeyCode::Tab,
+];
+
+const DIRECTIONS: [((i32, i32), &str); 4] = [((-1, 0), "L"), ((1, 0), "R"), ((0, -1), "U"), ((0, 1), "D")];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Press {
+    Key(KeyCode),
+    Char(char),
+    /// A click on a room tile, for click-to-move.
+    Click((i32, i32)),
+}
+
+impl Press {
+    fn to_text(self) -> String {
+        match self {
+            Press::Key(key) => format!("key:{:?}", key),
+            Press::Char(ch) => format!("char:{}", ch as u32),
+            Press::Click((x, y)) => format!("click:{},{}", x, y),
+        }
+    }
+
+    fn parse(s: &str) -> Option<Press> {
+        match s.split_once(':')? {
+            ("key", name) => KEYS.into_iter().find(|key| format!("{:?}", key) == name).map(Press::Key),
+            ("char", code) => code.parse().ok().and_then(char::from_u32).map(Press::Char),
+            ("click", tile) => {
+                let (x, y) = tile.split_once(',')?;
+                Some(Press::Click((x.parse().ok()?, y.parse().ok()?)))
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct Frame {
+    pub dt: f32,
+    pub controls: Controls,
+    /// Presses handled before this frame's update, in order.
+    pub presses: Vec<Press>,
+}
+
+impl Frame {
+    fn to_text(&self) -> String {
+        let held = DIRECTIONS.iter().find(|(dir, _)| Some(*dir) == self.controls.held).map_or("-", |(_, name)| name);
+        let mut line = format!("{} {}{}{}", self.dt, held, if self.controls.run { "+" } else { "" }, if self.controls.sneak { "~" } else { "" });
+        for press in &self.presses {
+            line.push(' ');
+            line.push_str(&press.to_text());
+        }
+        line
+    }
+
+    fn parse(line: &str) -> Option<Frame> {
+        let mut words = line.split_whitespace();
+        let dt = words.next()?.parse().ok()?;
+        let word = words.next()?;
+        let (word, sneak) = match word.strip_suffix('~') {
+            Some(word) => (word, true),
+            None => (word, false),
+        };
+        let (word, run) = match word.strip_suffix('+') {
+            Some(word) => (word, true),
+            None => (word, false),
+        };
+        let held = match word {
+            "-" => None,
+            name => Some(DIRECTIONS.iter().find(|(_, n)| *n == name)?.0),
+        };
+        let presses = words.map(Press::parse).collect::<Option<Vec<_>>>()?;
+        Some(Frame { dt, controls: Controls { held, run, sneak }, presses })
+    }
+}
+
+/// Writes frames to a recording as they happen, so a crash still leaves everything up to it.
+pub struct Recorder {
+    out: LineWriter<File>,
+    pending: Vec<Press>,
+}
+
+impl Recorder {
+    pub fn create(path: &str, seed: u64) -> io::Result<Recorder> {
+        let mut out = LineWriter::new(File::create(path)?);
+        writeln!(out, "seed={}", seed)?;
+        Ok(Recorder { out, pending: Vec::new() })
+    }
+
+    pub fn key(&mut self, key: KeyCode) {
+        if KEYS.contains(&key) {
+            self.pending.push(Press::Key(key));
+        }
+    }
+
+    pub fn char(&mut self, ch: char) {
+        self.pending.push(Press::Char(ch));
+    }
+
+    pub fn click(&mut self, tile: (i32, i32)) {
+        self.pending.push(Press::Click(tile));
+    }
+
+    /// Close off a frame, taking the presses since the last one.
+    pub fn frame(&mut self, dt: f32, controls: Controls) {
+        let frame = Frame { dt, controls, presses: std::mem::take(&mut self.pending) };
+        if let Err(e) = writeln!(self.out, "{}", frame.to_text()) {
+            println!("Replay: failed to write recording: {}", e);
+        }
+    }
+}
+
+pub struct Replay {
+    pub seed: u64,
+    frames: std::vec::IntoIter<Frame>,
+}
+
+impl Replay {
+    pub fn load(path: &str) -> Result<Replay, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("can't read {}: {}", path, e))?;
+        Replay::from_text(&text)
+    }
+
+    pub fn from_text(text: &str) -> Result<Replay, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, l)| !l.trim().is_empty());
+        let seed = lines.next()
+            .and_then(|(_, l)| l.trim().strip_prefix("seed="))
+            .and_then(|s| s.parse().ok())
+            .ok_or("recording doesn't start with seed=")?;
+        let frames = lines
+            .map(|(n, l)| Frame::parse(l).ok_or_else(|| format!("bad frame on line {}: {:?}", n + 1, l)))
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(Replay { seed, frames: frames.into_iter() })
+    }
+
+    /// The next frame to play, or None once the recording is over.
+    pub fn next_frame(&mut self) -> Option<Frame> {
+        self.frames.next()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn frames_round_trip_through_text() {
+        let frames = [
+            Frame { dt: 1.0 / 60.0, controls: Controls::default(), presses: vec![] },
+            Frame { dt: 0.0171, controls: Controls { held: Some((0, -1)), run: true, sneak: true }, presses: vec![Press::Key(KeyCode::Z), Press::Char(' '), Press::Click((12, -1))] },
+        ];
+        let text = format!("seed=7\n{}\n{}\n", frames[0].to_text(), frames[1].to_text());
+        let mut replay = Replay::from_text(&text).unwrap();
+        assert_eq!(replay.seed, 7);
+        assert_eq!(replay.next_frame().as_ref(), Some(&frames[0]));
+        assert_eq!(replay.next_frame().as_ref(), Some(&frames[1]));
+        assert_eq!(replay.next_frame(), None);
+        assert!(Replay::from_text("0.016 -").is_err(), "no seed");
+        assert!(Replay::from_text("seed=1\n0.016 - key:F13").is_err());
+    }
+}
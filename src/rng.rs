@@ -0,0 +1,84 @@
+//! Seeded RNG service.
+//!
+//! Everything that should be reproducible for a given world (enemy placement, loot rolls,
+//! generation choices) draws from a `GameRng` so the same seed always builds the same world.
+
+use std::sync::Mutex;
+
+use rand::{Rng, SeedableRng};
+use rand::rngs::StdRng;
+
+/// While set, "random" seeds come from here instead, so a replayed session rolls the same worlds
+/// as the recorded one.
+static PINNED_ENTROPY: Mutex<Option<StdRng>> = Mutex::new(None);
+
+/// Draw every fresh seed from now on from a sequence fixed by `seed`.
+pub fn pin_entropy(seed: u64) {
+    *PINNED_ENTROPY.lock().unwrap() = Some(StdRng::seed_from_u64(seed));
+}
+
+pub struct GameRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl GameRng {
+    /// Create an RNG that will always produce the same sequence for `seed`.
+    pub fn new(seed: u64) -> GameRng {
+        GameRng { seed, rng: StdRng::seed_from_u64(seed) }
+    }
+
+    /// Create an RNG with a fresh random seed (used when the player leaves the seed blank).
+    pub fn from_entropy() -> GameRng {
+        let pinned = PINNED_ENTROPY.lock().unwrap().as_mut().map(|rng| rng.random());
+        GameRng::new(pinned.unwrap_or_else(rand::random))
+    }
+
+    /// The seed this RNG was created from; stored in save metadata.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Random integer in `lo..hi` (hi exclusive). Returns `lo` for an empty range.
+    pub fn range(&mut self, lo: i32, hi: i32) -> i32 {
+        if hi <= lo { return lo; }
+        self.rng.random_range(lo..hi)
+    }
+}
+
+/// Parse a seed typed by the player. Plain numbers are used as-is; any other text is hashed
+/// (FNV-1a, stable across runs and platforms) so words like "dragon" work as seeds too.
+/// Returns None for blank input so the caller can pick a random seed instead.
+pub fn parse_seed(text: &str) -> Option<u64> {
+    let text = text.trim();
+    if text.is_empty() { return None; }
+    if let Ok(n) = text.parse::<u64>() { return Some(n); }
+    let mut hash: u64 = 0xcbf29ce484222325;
+    for b in text.bytes() {
+        hash ^= b as u64;
+        hash = hash.wrapping_mul(0x100000001b3);
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_same_sequence() {
+        let mut a = GameRng::new(42);
+        let mut b = GameRng::new(42);
+        for _ in 0..16 {
+            assert_eq!(a.range(0, 1000), b.range(0, 1000));
+        }
+    }
+
+    #[test]
+    fn parse_seed_accepts_numbers_and_words() {
+        assert_eq!(parse_seed(" 1234 "), Some(1234));
+        assert_eq!(parse_seed(""), None);
+        assert_eq!(parse_seed("dragon"), parse_seed("dragon"));
+        assert_ne!(parse_seed("dragon"), parse_seed("wyvern"));
+    }
+}
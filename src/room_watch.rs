@@ -0,0 +1,72 @@
+//! Debug builds watch the current room's data file on disk and rebuild the room when it's
+//! saved, so edits to `assets/rooms/room<n>.txt` (or a mod's copy) show up straight away.
+//!
+//! Files are polled a couple of times a second rather than watched through the OS; only loose
+//! files are looked at, since nothing edits `data.pak` in place.
+
+use std::path::PathBuf;
+use std::time::SystemTime;
+
+/// Seconds between looks at the file.
+const POLL_SECS: f32 = 0.5;
+
+#[derive(Default)]
+pub struct RoomWatcher {
+    /// Room being watched and its file's last modified time, if it has a file.
+    watching: Option<(usize, Option<SystemTime>)>,
+    timer: f32,
+}
+
+/// Last modified time of room `room`'s data file, from the first of `dirs` that has one.
+fn stamp(dirs: &[PathBuf], room: usize) -> Option<SystemTime> {
+    let name = format!("rooms/room{}.txt", room);
+    dirs.iter().find_map(|dir| std::fs::metadata(dir.join(&name)).ok()).and_then(|meta| meta.modified().ok())
+}
+
+impl RoomWatcher {
+    /// Call every frame with the current room; true when its data file has changed since the
+    /// last look. Moving to another room starts watching that one afresh.
+    pub fn poll(&mut self, dt: f32, room: usize) -> bool {
+        self.poll_in(&crate::assets::loose_dirs(), dt, room)
+    }
+
+    fn poll_in(&mut self, dirs: &[PathBuf], dt: f32, room: usize) -> bool {
+        self.timer -= dt;
+        let same_room = self.watching.is_some_and(|(r, _)| r == room);
+        if same_room && self.timer > 0.0 { return false; }
+        self.timer = POLL_SECS;
+        let now = stamp(dirs, room);
+        let changed = same_room && self.watching.is_some_and(|(_, before)| before != now) && now.is_some();
+        self.watching = Some((room, now));
+        changed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn notices_saves_to_the_current_rooms_file() {
+        let dir = std::env::temp_dir().join(format!("tale-room-watch-{}", std::process::id()));
+        std::fs::create_dir_all(dir.join("rooms")).unwrap();
+        let file = dir.join("rooms/room3.txt");
+        let touch = |secs: u64| {
+            std::fs::write(&file, "name=Test\n").unwrap();
+            std::fs::File::options().write(true).open(&file).unwrap().set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(secs)).unwrap();
+        };
+        let dirs = [dir.clone()];
+        touch(1000);
+        let mut watcher = RoomWatcher::default();
+        assert!(!watcher.poll_in(&dirs, 0.0, 3), "the first look just notes the file");
+        touch(2000);
+        assert!(!watcher.poll_in(&dirs, 0.1, 3), "not time to look again yet");
+        assert!(watcher.poll_in(&dirs, POLL_SECS, 3));
+        assert!(!watcher.poll_in(&dirs, POLL_SECS, 3), "only once per save");
+        touch(3000);
+        assert!(!watcher.poll_in(&dirs, POLL_SECS, 4), "a different room starts afresh");
+        assert!(!watcher.poll_in(&dirs, POLL_SECS, 4), "room 4 has no file to change");
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
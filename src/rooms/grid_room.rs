@@ -1,67 +1,276 @@
+use std::sync::Arc;
 use ggez::{Context, GameResult};
 use ggez::graphics::{Canvas, DrawParam};
 use crate::assets::Assets;
-use super::TILE_SIZE;
+use crate::footsteps::Surface;
+use super::{Interaction, SpikeTiming, TILE_SIZE, Water};
+use crate::lighting::Light;
+use crate::props::{PropDef, Props, Side};
+use crate::tiles::{spot_hash, Action, Base, Solidity, TileDef, TileRegistry, WallJoin};
+use nalgebra as na;
 
-#[derive(Clone, Copy, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Tile {
     Floor,
     Wall,
     DoorClosed,
     DoorOpen,
-    Bed,
-    Fwall, // Faux wall - solid like wall but doesn't affect corner rendering (for beds, tables, rocks)
+    Fwall, // Faux wall - solid like wall but doesn't affect corner rendering (solid prop cells, rocks)
     Table, // Table - solid faux wall that renders as table
+    Chest { gold: u32 }, // Closed chest holding some gold (solid)
+    ChestOpen, // Looted chest (solid)
+    SaveCrystal, // Glowing crystal that opens the save menu (solid)
+    DoorLocked, // Door that only opens once every Target in the room is covered by a Block
+    Block { on_target: bool }, // Pushable block (solid); remembers the Target underneath
+    Target, // Floor plate that wants a Block on it
+    ShallowWater, // Wadeable water that slows movement
+    DeepWater, // Only passable while able to swim
+    Ledge, // One-way drop: hopped over going south, a wall from every other side
+    Spikes, // Trap that cycles between retracted (safe) and extended (hurts whoever stands on it)
+    Pit, // Hole: the player falls through to the room below, or back to the entrance
+    Torch, // Wall with a burning torch on it; a light source in dark rooms
+    Sign, // Signpost (solid); its text comes from room data
+    Bookshelf, // Shelf of lore books (solid); its text comes from room data
+    Chair, // Walkable; interacting sits the player down on it
+    Cupboard { item: Option<crate::items::ItemId> }, // Searchable (solid); item is taken on the first search
+    Campfire, // Camp (solid); a light source where the companion can be left or collected
+    Shrine, // Fast travel waypoint (solid); its name comes from room data
+    Custom(u16), // A tile added in tile data, by its position in the registry
+}
+
+/// One of each built-in tile.
+const BUILTIN_TILES: [Tile; 24] = [
+    Tile::Floor, Tile::Wall, Tile::DoorClosed, Tile::DoorOpen, Tile::Fwall, Tile::Table, Tile::Chest { gold: 0 }, Tile::ChestOpen,
+    Tile::SaveCrystal, Tile::DoorLocked, Tile::Block { on_target: false }, Tile::Target, Tile::ShallowWater, Tile::DeepWater,
+    Tile::Ledge, Tile::Spikes, Tile::Pit, Tile::Torch, Tile::Sign, Tile::Bookshelf, Tile::Chair, Tile::Cupboard { item: None },
+    Tile::Campfire, Tile::Shrine,
+];
+
+impl Tile {
+    /// Id of a built-in tile in the tile data (see `crate::tiles`).
+    fn id(self) -> &'static str {
+        match self {
+            Tile::Floor => "floor",
+            Tile::Wall => "wall",
+            Tile::DoorClosed => "door_closed",
+            Tile::DoorOpen => "door_open",
+            Tile::Fwall => "fwall",
+            Tile::Table => "table",
+            Tile::Chest { .. } => "chest",
+            Tile::ChestOpen => "chest_open",
+            Tile::SaveCrystal => "save_crystal",
+            Tile::DoorLocked => "door_locked",
+            Tile::Block { .. } => "block",
+            Tile::Target => "target",
+            Tile::ShallowWater => "shallow_water",
+            Tile::DeepWater => "deep_water",
+            Tile::Ledge => "ledge",
+            Tile::Spikes => "spikes",
+            Tile::Pit => "pit",
+            Tile::Torch => "torch",
+            Tile::Sign => "sign",
+            Tile::Bookshelf => "bookshelf",
+            Tile::Chair => "chair",
+            Tile::Cupboard { .. } => "cupboard",
+            Tile::Campfire => "campfire",
+            Tile::Shrine => "shrine",
+            Tile::Custom(_) => "",
+        }
+    }
+}
+
+/// Seconds spikes take to shoot up or sink back down.
+const SPIKE_MOTION_SECS: f32 = 0.1;
+/// How dark a cave is when its data doesn't say.
+const CAVE_DARKNESS: f32 = 0.8;
+
+/// What the room's floor and walls are made of, set with `style=` in room data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default)]
+enum RoomStyle {
+    /// Wooden planks and timber walls.
+    #[default]
+    Interior,
+    /// Bare stone floor and rough rock walls; dark unless the data says otherwise.
+    Cave,
 }
 
 pub struct GridRoom {
     tiles: Vec<Vec<Tile>>,
+    spike_timing: SpikeTiming,
+    // position in the spike cycle; extended for the first `spike_timing.extended` seconds
+    spike_clock: f32,
+    below: Option<usize>,
+    entrance: (i32, i32),
+    // 0 = fully lit; see crate::lighting
+    darkness: f32,
+    // floors below the surface, for the save's deepest floor reached
+    depth: u32,
+    // under the open sky (see crate::calendar)
+    outdoor: bool,
+    name: Option<String>,
+    music: Option<String>,
+    ambience: Option<String>,
+    // what plain floor is made of, for footsteps
+    floor: Surface,
+    style: RoomStyle,
+    // text of each sign and bookshelf (and each shrine's name), from room data
+    texts: Vec<((usize, usize), String)>,
+    props: Vec<PlacedProp>,
+    // tiles changed by play (doors, chests, blocks, cupboards), in the order they first changed;
+    // see `changes`
+    touched: Vec<(usize, usize)>,
+    // what each kind of tile does and looks like
+    kinds: Arc<TileRegistry>,
+}
+
+/// A multi-tile prop and the tile of its top-left corner.
+struct PlacedProp {
+    def: PropDef,
+    x: usize,
+    y: usize,
+}
+
+impl PlacedProp {
+    fn is_walkable(&self, tx: usize, ty: usize) -> bool {
+        tx >= self.x && ty >= self.y && self.def.is_walkable(tx - self.x, ty - self.y)
+    }
+
+    /// Whether the walkable cell `tx`,`ty` can be stepped on or off across `side`: it has to be
+    /// an entry side, and the cell has to be on that edge of the walkable area.
+    fn can_cross(&self, tx: usize, ty: usize, side: Side) -> bool {
+        let (dx, dy) = side.offset();
+        let (nx, ny) = (tx as i32 + dx, ty as i32 + dy);
+        self.def.entry.contains(&side) && !(nx >= 0 && ny >= 0 && self.is_walkable(nx as usize, ny as usize))
+    }
 }
 
 impl GridRoom {
-    pub fn new(width: usize, height: usize) -> GridRoom {
+    /// Bare room: floor surrounded by walls.
+    pub fn empty(width: usize, height: usize) -> GridRoom {
         let mut tiles = vec![vec![Tile::Floor; width]; height];
-        for x in 0..width {
-            tiles[0][x] = Tile::Wall;
-            tiles[height - 1][x] = Tile::Wall;
+        tiles[0].fill(Tile::Wall);
+        tiles[height - 1].fill(Tile::Wall);
+        for row in tiles.iter_mut() {
+            row[0] = Tile::Wall;
+            row[width - 1] = Tile::Wall;
         }
-        for y in 0..height {
-            tiles[y][0] = Tile::Wall;
-            tiles[y][width - 1] = Tile::Wall;
+        GridRoom { tiles, spike_timing: SpikeTiming::default(), spike_clock: 0.0, below: None, entrance: (1, 1), darkness: 0.0, depth: 0, outdoor: false, name: None, music: None, ambience: None, floor: Surface::default(), style: RoomStyle::default(), texts: Vec::new(), props: Vec::new(), touched: Vec::new(), kinds: TileRegistry::builtin() }
+    }
+
+    /// A room made from nothing but its data file: walls around open floor, `size=w,h` tiles
+    /// (20x15 if unset), entered at `entrance=x,y` (top-left corner if unset).
+    pub fn from_data(text: &str) -> GridRoom {
+        let pair = |wanted: &str| text.lines().find_map(|line| {
+            let (key, value) = line.split_once('=')?;
+            let (x, y) = value.split_once(',').filter(|_| key.trim() == wanted)?;
+            Some((x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?))
+        });
+        let (width, height) = pair("size").filter(|&(w, h)| w >= 3 && h >= 3).unwrap_or((20, 15));
+        let mut room = GridRoom::empty(width, height);
+        if let Some(entrance) = pair("entrance").filter(|&(x, y)| x > 0 && y > 0 && x < width - 1 && y < height - 1) {
+            room.entrance = (entrance.0 as i32, entrance.1 as i32);
+        }
+        room
+    }
+
+    /// The cellar under the starting room: a row of fast spikes with a single safe gap.
+    pub fn cellar() -> GridRoom {
+        let mut room = GridRoom::empty(12, 9).with_spike_timing(SpikeTiming { extended: 0.8, retracted: 1.2 });
+        for tile in room.tiles[4].iter_mut().take(11).skip(1) {
+            *tile = Tile::Spikes;
         }
+        room.tiles[4][1] = Tile::Floor;
+        // nothing below the cellar: falling in just hurts
+        room.tiles[6][9] = Tile::Pit;
+        room.entrance = (1, 2);
+        room
+    }
+
+    /// Pits in this room drop into room `below`.
+    pub fn with_room_below(mut self, below: usize) -> GridRoom {
+        self.below = Some(below);
+        self
+    }
+
+    pub fn with_spike_timing(mut self, timing: SpikeTiming) -> GridRoom {
+        self.spike_timing = timing;
+        self
+    }
+
+    /// How far the spikes are out, 0 (retracted) to 1 (fully extended).
+    fn spike_extension(&self) -> f32 {
+        let phase = self.spike_clock;
+        let extended = self.spike_timing.extended;
+        if phase < extended {
+            (phase / SPIKE_MOTION_SECS).min(1.0)
+        } else {
+            1.0 - ((phase - extended) / SPIKE_MOTION_SECS).min(1.0)
+        }
+    }
+
+    pub fn new(width: usize, height: usize) -> GridRoom {
+        let mut tiles = GridRoom::empty(width, height).tiles;
         // central door for demo (removed the left one closest to bed)
         tiles[0][width/2] = Tile::DoorClosed;
         
-        // Add bed in bottom-left area (2x3 tiles) flush with left wall
-        // Top 4 tiles are walkable bed, bottom 2 tiles are solid bed (faux walls)
+        // The bed itself is a prop placed from room data; tables stand just above where it goes
         // TODO: Implement day/night exclusive quests and health recovery systems
         if width > 3 && height > 4 {
-            // Place bed tiles in a 2x3 area, flush with left wall
-            for by in 0..3 { // 3 tiles tall
-                for bx in 0..2 { // 2 tiles wide
-                    let y_pos = height - 4 + by;
-                    let x_pos = 1 + bx;
-                    if by < 2 {
-                        // Top 4 tiles (2x2) are walkable bed
-                        tiles[y_pos][x_pos] = Tile::Bed;
-                    } else {
-                        // Bottom 2 tiles (2x1) are solid bed (faux walls)
-                        tiles[y_pos][x_pos] = Tile::Fwall;
-                    }
+            tiles[height - 5][1] = Tile::Table;
+            tiles[height - 5][2] = Tile::Table;
+        }
+
+        // A small savings chest tucked into the bottom-right corner
+        if width > 4 && height > 4 {
+            tiles[height - 2][width - 2] = Tile::Chest { gold: 25 };
+        }
+
+        // Save crystal against the right wall, for when the bed is out of reach
+        if width > 4 && height > 6 {
+            tiles[height / 2][width - 2] = Tile::SaveCrystal;
+        }
+
+        // A pond between the puzzle and the right wall: a ring of shallows around a deep middle
+        if width > 17 && height > 13 {
+            for (y, row) in tiles.iter_mut().enumerate().skip(8).take(5) {
+                for (x, tile) in row.iter_mut().enumerate().skip(12).take(5) {
+                    let edge = y == 8 || y == 12 || x == 12 || x == 16;
+                    *tile = if edge { Tile::ShallowWater } else { Tile::DeepWater };
                 }
             }
-            
-            // Add table tiles above the top bed tiles
-            if height >= 5 {
-                // Place tables at (1, height-5) and (2, height-5) - directly above top bed tiles
-                tiles[height - 5][1] = Tile::Table;
-                tiles[height - 5][2] = Tile::Table;
+        }
+
+        // A ledge off the left wall: a quick hop down from the north-west corner, but the long
+        // way round to get back up
+        if width > 10 && height > 8 {
+            for tile in tiles[5].iter_mut().take(9).skip(1) {
+                *tile = Tile::Ledge;
             }
-            
-            // The invisible walls are no longer needed - replaced with custom movement logic
         }
-        
-        GridRoom { tiles }
+
+        // Tiny block puzzle: push the block down onto the plate to unlock the bottom door
+        if width > 8 && height > 8 {
+            let x = width / 2;
+            tiles[height - 1][x] = Tile::DoorLocked;
+            tiles[height - 3][x] = Tile::Target;
+            tiles[height - 5][x] = Tile::Block { on_target: false };
+        }
+
+        // A hole down to the cellar below the ledge
+        if width > 16 && height > 12 {
+            tiles[7][6] = Tile::Pit;
+        }
+
+        // A short strip of spikes under the top wall
+        if width > 16 && height > 5 {
+            for tile in tiles[3].iter_mut().take(14).skip(11) {
+                *tile = Tile::Spikes;
+            }
+        }
+
+        // fallers are put back beside the bed
+        let entrance = (3, height as i32 - 3);
+        GridRoom { tiles, spike_timing: SpikeTiming::default(), spike_clock: 0.0, below: None, entrance, darkness: 0.0, depth: 0, outdoor: false, name: None, music: None, ambience: None, floor: Surface::default(), style: RoomStyle::default(), texts: Vec::new(), props: Vec::new(), touched: Vec::new(), kinds: TileRegistry::builtin() }
     }
 }
 
@@ -76,227 +285,68 @@ impl super::Room for GridRoom {
                 let dest_y = offset.1 + (py + TILE_SIZE / 2.0) * scale;
                 let dest = ggez::mint::Point2 { x: dest_x, y: dest_y };
 
-                // Check neighbors to determine wall orientation 
-                let height = self.tiles.len();
-                let width = if height > 0 { self.tiles[0].len() } else { 0 };
-                let mut left_is_wall = false;
-                let mut right_is_wall = false;
-                let mut up_is_wall = false;
-                let mut down_is_wall = false;
-                // For joints, only consider actual walls and closed doors, not open doors
-                let mut left_is_joint_wall = false;
-                let mut right_is_joint_wall = false;
-                let mut up_is_joint_wall = false;
-                let mut down_is_joint_wall = false;
-                if x > 0 {
-                    left_is_wall = matches!(self.tiles[y][x-1], Tile::Wall | Tile::DoorClosed | Tile::DoorOpen);
-                    left_is_joint_wall = matches!(self.tiles[y][x-1], Tile::Wall | Tile::DoorClosed);
-                }
-                if x + 1 < width {
-                    right_is_wall = matches!(self.tiles[y][x+1], Tile::Wall | Tile::DoorClosed | Tile::DoorOpen);
-                    right_is_joint_wall = matches!(self.tiles[y][x+1], Tile::Wall | Tile::DoorClosed);
-                }
-                if y > 0 {
-                    up_is_wall = matches!(self.tiles[y-1][x], Tile::Wall | Tile::DoorClosed | Tile::DoorOpen);
-                    up_is_joint_wall = matches!(self.tiles[y-1][x], Tile::Wall | Tile::DoorClosed);
-                }
-                if y + 1 < height {
-                    down_is_wall = matches!(self.tiles[y+1][x], Tile::Wall | Tile::DoorClosed | Tile::DoorOpen);
-                    down_is_joint_wall = matches!(self.tiles[y+1][x], Tile::Wall | Tile::DoorClosed);
-                }
-                let horiz_neighbors = (left_is_wall as u8) + (right_is_wall as u8);
-                let vert_neighbors = (up_is_wall as u8) + (down_is_wall as u8);
-                // Force horizontal orientation for top/bottom rows to make top/bottom walls look like horizontal planks
-                let mut is_horizontal = horiz_neighbors >= vert_neighbors;
-                if y == 0 || y + 1 == height { is_horizontal = true; }
-
-                match tile {
-                    Tile::Floor => {
-                        let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
-                        canvas.draw(&assets.plank, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
-                        
-                        // Draw black outlines where floor meets walls
-                        use ggez::graphics::{Mesh, DrawMode, Color};
-                        let outline_color = Color::BLACK;
-                        let outline_thickness = scale * 2.0; // 2 pixel outline
-                        let half_tile = TILE_SIZE as f32 * scale * 0.5;
-                        
-                        // Top edge outline if there's a wall above
-                        if up_is_wall {
-                            let outline_rect = ggez::graphics::Rect::new(
-                                dest_x - half_tile, 
-                                dest_y - half_tile, 
-                                TILE_SIZE as f32 * scale, 
-                                outline_thickness
-                            );
-                            let outline_mesh = Mesh::new_rectangle(_ctx, DrawMode::fill(), outline_rect, outline_color)?;
-                            canvas.draw(&outline_mesh, DrawParam::new());
-                        }
-                        
-                        // Bottom edge outline if there's a wall below
-                        if down_is_wall {
-                            let outline_rect = ggez::graphics::Rect::new(
-                                dest_x - half_tile, 
-                                dest_y + half_tile - outline_thickness, 
-                                TILE_SIZE as f32 * scale, 
-                                outline_thickness
-                            );
-                            let outline_mesh = Mesh::new_rectangle(_ctx, DrawMode::fill(), outline_rect, outline_color)?;
-                            canvas.draw(&outline_mesh, DrawParam::new());
-                        }
-                        
-                        // Left edge outline if there's a wall to the left
-                        if left_is_wall {
-                            let outline_rect = ggez::graphics::Rect::new(
-                                dest_x - half_tile, 
-                                dest_y - half_tile, 
-                                outline_thickness, 
-                                TILE_SIZE as f32 * scale
-                            );
-                            let outline_mesh = Mesh::new_rectangle(_ctx, DrawMode::fill(), outline_rect, outline_color)?;
-                            canvas.draw(&outline_mesh, DrawParam::new());
-                        }
-                        
-                        // Right edge outline if there's a wall to the right
-                        if right_is_wall {
-                            let outline_rect = ggez::graphics::Rect::new(
-                                dest_x + half_tile - outline_thickness, 
-                                dest_y - half_tile, 
-                                outline_thickness, 
-                                TILE_SIZE as f32 * scale
-                            );
-                            let outline_mesh = Mesh::new_rectangle(_ctx, DrawMode::fill(), outline_rect, outline_color)?;
-                            canvas.draw(&outline_mesh, DrawParam::new());
-                        }
+                let spot = self.spot(x, y, dest_x, dest_y, scale);
+                let def = self.def(tile);
+                // the tileset cell, if the kind has one, stands in for the plank or wall image
+                let cell = |canvas: &mut Canvas, param: DrawParam| match (def.atlas, &assets.tileset) {
+                    (Some(index), Some(tileset)) => tileset.draw(canvas, index, param),
+                    _ => false,
+                };
+                match def.base {
+                    Base::None => {
+                        cell(canvas, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([scale, scale]));
                     }
-                    Tile::Wall => {
-                        let img_scale = scale * TILE_SIZE / assets.wall.width() as f32;
-                        let rotation = if is_horizontal { std::f32::consts::FRAC_PI_2 } else { 0.0 };
-                        canvas.draw(&assets.wall, DrawParam::new().dest(dest).offset([0.5, 0.5]).rotation(rotation).scale([img_scale, img_scale]));
-                        
-                        // Wall joint overlays: centered on corner wall tiles (this current wall tile forms a corner)
-                        let joint_scale = scale * TILE_SIZE / assets.wall_joint.width() as f32 * 1.15; // Scale up to touch floor outline
-                        
-                        // Only draw joint if this wall tile forms a corner with adjacent solid walls (not open doors)
-                        // Bottom-right corner - this wall has walls to right AND down
-                        if right_is_joint_wall && down_is_joint_wall {
-                            canvas.draw(&assets.wall_joint, DrawParam::new()
-                                .dest(dest)
-                                .offset([0.5, 0.5])
-                                .rotation(0.0) // No rotation for bottom-right
-                                .scale([joint_scale, joint_scale]));
-                        }
-                        
-                        // Bottom-left corner - this wall has walls to left AND down  
-                        if left_is_joint_wall && down_is_joint_wall {
-                            canvas.draw(&assets.wall_joint, DrawParam::new()
-                                .dest(dest)
-                                .offset([0.5, 0.5])
-                                .rotation(std::f32::consts::FRAC_PI_2) // 90° rotation for bottom-left
-                                .scale([joint_scale, joint_scale]));
+                    Base::Plank | Base::Floor if self.style == RoomStyle::Cave => {
+                        draw_cave_floor(_ctx, canvas, dest_x, dest_y, scale, spot_hash(x, y))?;
+                        if def.base == Base::Floor {
+                            draw_floor_edges(_ctx, canvas, &spot)?;
                         }
-                        
-                        // Top-right corner - this wall has walls to right AND up
-                        if right_is_joint_wall && up_is_joint_wall {
-                            canvas.draw(&assets.wall_joint, DrawParam::new()
-                                .dest(dest)
-                                .offset([0.5, 0.5])
-                                .rotation(-std::f32::consts::FRAC_PI_2) // -90° rotation for top-right
-                                .scale([joint_scale, joint_scale]));
+                    }
+                    Base::Wall if self.style == RoomStyle::Cave => draw_rock(_ctx, canvas, &spot, spot_hash(x, y))?,
+                    Base::Plank | Base::Floor => {
+                        // every plank is mirrored or shaded a little differently, by position, so
+                        // floors don't look stamped out
+                        let hash = spot_hash(x, y);
+                        let flip = if hash & 0x100 != 0 { -1.0 } else { 1.0 };
+                        let shade = 1.0 - (hash >> 12 & 7) as f32 * 0.012;
+                        let param = DrawParam::new().dest(dest).offset([0.5, 0.5]).color(ggez::graphics::Color::new(shade, shade, shade, 1.0));
+                        if !cell(canvas, param.scale([scale * flip, scale])) {
+                            let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
+                            canvas.draw(&assets.plank, param.scale([img_scale * flip, img_scale]));
                         }
-                        
-                        // Top-left corner - this wall has walls to left AND up
-                        if left_is_joint_wall && up_is_joint_wall {
-                            canvas.draw(&assets.wall_joint, DrawParam::new()
-                                .dest(dest)
-                                .offset([0.5, 0.5])
-                                .rotation(std::f32::consts::PI) // 180° rotation for top-left
-                                .scale([joint_scale, joint_scale]));
+                        if def.base == Base::Floor {
+                            draw_floor_edges(_ctx, canvas, &spot)?;
                         }
                     }
-                    Tile::DoorClosed => {
-                        let img_scale = scale * TILE_SIZE / assets.wall.width() as f32;
-                        let rotation = if is_horizontal { std::f32::consts::FRAC_PI_2 } else { 0.0 };
-                        canvas.draw(&assets.wall, DrawParam::new().dest(dest).offset([0.5, 0.5]).rotation(rotation).scale([img_scale, img_scale]));
-                        let door_color = ggez::graphics::Mesh::new_rectangle(_ctx, ggez::graphics::DrawMode::fill(), ggez::graphics::Rect::new(dest_x - TILE_SIZE*scale/2.0, dest_y - TILE_SIZE*scale/2.0, TILE_SIZE*scale, TILE_SIZE*scale), ggez::graphics::Color::new(0.1, 0.05, 0.0, 0.6))?;
-                        canvas.draw(&door_color, DrawParam::new());
-                    }
-                    Tile::DoorOpen => {
-                        // Draw floor as base
-                        let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
-                        canvas.draw(&assets.plank, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
-                        
-                        // Draw door frame/opening indicators
-                        let frame_color = ggez::graphics::Color::new(0.3, 0.2, 0.1, 0.8);
-                        let opening_width = TILE_SIZE * scale * 0.8;
-                        let opening_height = TILE_SIZE * scale * 0.8;
-                        let frame_thickness = TILE_SIZE * scale * 0.1;
-                        
-                        if is_horizontal {
-                            // Horizontal door opening - draw vertical frame sides
-                            let left_frame = ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y - opening_height/2.0, frame_thickness, opening_height);
-                            let right_frame = ggez::graphics::Rect::new(dest_x + opening_width/2.0 - frame_thickness, dest_y - opening_height/2.0, frame_thickness, opening_height);
-                            let left_mesh = ggez::graphics::Mesh::new_rectangle(_ctx, ggez::graphics::DrawMode::fill(), left_frame, frame_color)?;
-                            let right_mesh = ggez::graphics::Mesh::new_rectangle(_ctx, ggez::graphics::DrawMode::fill(), right_frame, frame_color)?;
-                            canvas.draw(&left_mesh, DrawParam::new());
-                            canvas.draw(&right_mesh, DrawParam::new());
-                        } else {
-                            // Vertical door opening - draw horizontal frame sides
-                            let top_frame = ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y - opening_height/2.0, opening_width, frame_thickness);
-                            let bottom_frame = ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y + opening_height/2.0 - frame_thickness, opening_width, frame_thickness);
-                            let top_mesh = ggez::graphics::Mesh::new_rectangle(_ctx, ggez::graphics::DrawMode::fill(), top_frame, frame_color)?;
-                            let bottom_mesh = ggez::graphics::Mesh::new_rectangle(_ctx, ggez::graphics::DrawMode::fill(), bottom_frame, frame_color)?;
-                            canvas.draw(&top_mesh, DrawParam::new());
-                            canvas.draw(&bottom_mesh, DrawParam::new());
+                    Base::Wall => {
+                        let rotation = if spot.horizontal { std::f32::consts::FRAC_PI_2 } else { 0.0 };
+                        let param = DrawParam::new().dest(dest).offset([0.5, 0.5]).rotation(rotation);
+                        if !cell(canvas, param.scale([scale, scale])) {
+                            let img_scale = scale * TILE_SIZE / assets.wall.width() as f32;
+                            canvas.draw(&assets.wall, param.scale([img_scale, img_scale]));
                         }
                     }
-                    Tile::Bed => {
-                        // For bed tiles, just draw floor in first pass (bed will be drawn on top later)
-                        let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
-                        canvas.draw(&assets.plank, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
-                    }
-                    Tile::Fwall => {
-                        // Faux walls (solid bed parts) - draw floor first, bed will be drawn on top later
-                        let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
-                        canvas.draw(&assets.plank, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
-                    }
-                    Tile::Table => {
-                        // Tables - draw floor first, then table on top
-                        let img_scale = scale * TILE_SIZE / assets.plank.width() as f32;
-                        canvas.draw(&assets.plank, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([img_scale, img_scale]));
-                        // Draw table on top
-                        let table_scale = scale * TILE_SIZE / assets.table.width() as f32;
-                        canvas.draw(&assets.table, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([table_scale, table_scale]));
-                    }
+                }
+                if let Some(variant) = def.variant(x, y) {
+                    self.draw_sprite(_ctx, canvas, assets, variant, tile, &spot)?;
+                }
+                if !def.sprite.is_empty() {
+                    self.draw_sprite(_ctx, canvas, assets, &def.sprite, tile, &spot)?;
                 }
             }
         }
         
-        // Second pass: render beds on top of everything else
-        for (y, row) in self.tiles.iter().enumerate() {
-            for (x, &tile) in row.iter().enumerate() {
-                if matches!(tile, Tile::Bed) {
-                    // Only draw the bed once on the top-left bed tile (1,height-4)
-                    if x == 1 && y == (self.tiles.len() - 4) {
-
-                        
-                        // Draw bed scaled to fit 2x3 tile area and rotated 90 degrees counter-clockwise
-                        let bed_width = 2.0 * TILE_SIZE;
-                        let bed_height = 3.0 * TILE_SIZE;
-                        let bed_scale_x = (bed_width * scale) / assets.bed.width() as f32;
-                        let bed_scale_y = (bed_height * scale) / assets.bed.height() as f32;
-                        
-                        // Position bed at the center of the 2x3 area
-                        let bed_center_x = offset.0 + ((x as f32 + 1.0) * TILE_SIZE) * scale;
-                        let bed_center_y = offset.1 + ((y as f32 + 1.5) * TILE_SIZE) * scale;
-                        
-                        canvas.draw(&assets.bed, DrawParam::new()
-                            .dest([bed_center_x, bed_center_y])
-                            .offset([0.5, 0.5])
-                            .scale([bed_scale_x, bed_scale_y]));
-                    }
-                }
-            }
+        // Second pass: render props on top of everything else, each sprite stretched over its footprint
+        for prop in &self.props {
+            let Some(image) = assets.sprite(&prop.def.sprite) else { continue };
+            let prop_width = prop.def.width() as f32 * TILE_SIZE;
+            let prop_height = prop.def.height() as f32 * TILE_SIZE;
+            let center_x = offset.0 + (prop.x as f32 * TILE_SIZE + prop_width / 2.0) * scale;
+            let center_y = offset.1 + (prop.y as f32 * TILE_SIZE + prop_height / 2.0) * scale;
+            canvas.draw(image, DrawParam::new()
+                .dest([center_x, center_y])
+                .offset([0.5, 0.5])
+                .scale([prop_width * scale / image.width() as f32, prop_height * scale / image.height() as f32]));
         }
         
         Ok(())
@@ -304,11 +354,11 @@ impl super::Room for GridRoom {
 
     fn is_solid_at_point(&self, x: f32, y: f32) -> bool {
         // Treat a point as a tiny rectangle centered on the coordinates
-        self.is_rect_free(x, y, 1.0, 1.0) == false
+        !self.is_rect_free(x, y, 1.0, 1.0)
     }
 
     fn width_pixels(&self) -> usize {
-        if let Some(row) = self.tiles.get(0) { row.len() * TILE_SIZE as usize } else { 0 }
+        if let Some(row) = self.tiles.first() { row.len() * TILE_SIZE as usize } else { 0 }
     }
 
     fn height_pixels(&self) -> usize {
@@ -318,8 +368,8 @@ impl super::Room for GridRoom {
     fn interact_tile(&mut self, tx: usize, ty: usize) -> bool {
         if ty >= self.tiles.len() || tx >= self.tiles[0].len() { return false; }
         match self.tiles[ty][tx] {
-            Tile::DoorClosed => { self.tiles[ty][tx] = Tile::DoorOpen; println!("GridRoom: opened door at {},{}", tx, ty); true }
-            Tile::DoorOpen => { self.tiles[ty][tx] = Tile::DoorClosed; println!("GridRoom: closed door at {},{}", tx, ty); true }
+            Tile::DoorClosed => { self.set_tile(tx, ty, Tile::DoorOpen); println!("GridRoom: opened door at {},{}", tx, ty); true }
+            Tile::DoorOpen => { self.set_tile(tx, ty, Tile::DoorClosed); println!("GridRoom: closed door at {},{}", tx, ty); true }
             _ => false,
         }
     }
@@ -337,12 +387,9 @@ impl super::Room for GridRoom {
                 let tyu = ty as usize;
                 let txu = tx as usize;
                 if tyu >= self.tiles.len() || txu >= self.tiles[tyu].len() { return false; }
-                match self.tiles[tyu][txu] {
-                    Tile::Wall | Tile::DoorClosed | Tile::Fwall | Tile::Table => return false,
-                    Tile::Bed => {
-                        // Bed tiles are walkable (treated like floor)
-                    }
-                    Tile::DoorOpen => {
+                match self.def(self.tiles[tyu][txu]).solid {
+                    Solidity::Solid => return false,
+                    Solidity::Frame => {
                         // Open doors are passable with minimal frame collision
                         // Only block movement at the very edges (frame thickness = 8% on each side)
                         let door_left = txu as f32 * TILE_SIZE;
@@ -350,37 +397,30 @@ impl super::Room for GridRoom {
                         let door_top = tyu as f32 * TILE_SIZE;
                         let door_bottom = (tyu as f32 + 1.0) * TILE_SIZE;
                         
-                        // Frame thickness matches visual: 10% of tile size
-                        let frame_thickness = TILE_SIZE * 0.08;
+                        // Thinner than the 5% margin around the player's hitbox, so someone walking
+                        // straight through the middle clears the frame
+                        let frame_thickness = TILE_SIZE * 0.04;
                         
                         // Determine door orientation
-                        let height = self.tiles.len();
-                        let width = if height > 0 { self.tiles[0].len() } else { 0 };
-                        let mut horiz_walls = 0;
-                        let mut vert_walls = 0;
-                        
-                        if txu > 0 && matches!(self.tiles[tyu][txu-1], Tile::Wall | Tile::DoorClosed) { horiz_walls += 1; }
-                        if txu + 1 < width && matches!(self.tiles[tyu][txu+1], Tile::Wall | Tile::DoorClosed) { horiz_walls += 1; }
-                        if tyu > 0 && matches!(self.tiles[tyu-1][txu], Tile::Wall | Tile::DoorClosed) { vert_walls += 1; }
-                        if tyu + 1 < height && matches!(self.tiles[tyu+1][txu], Tile::Wall | Tile::DoorClosed) { vert_walls += 1; }
-                        
-                        let is_horizontal = horiz_walls >= vert_walls;
+                        let [left, right, up, down] = [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dy)| self.wall_join(txu, tyu, dx, dy) == WallJoin::Wall);
+                        let is_horizontal = left as u8 + right as u8 >= up as u8 + down as u8;
                         
                         if is_horizontal {
-                            // Horizontal door: block only the top and bottom frame edges
-                            if (y < door_top + frame_thickness && y + h > door_top) ||
-                               (y < door_bottom && y + h > door_bottom - frame_thickness) {
+                            // Door in a horizontal wall: walked through north-south, so only its
+                            // left and right jambs block
+                            if (x < door_left + frame_thickness && x + w > door_left) ||
+                               (x < door_right && x + w > door_right - frame_thickness) {
                                 return false;
                             }
                         } else {
-                            // Vertical door: block only the left and right frame edges
-                            if (x < door_left + frame_thickness && x + w > door_left) ||
-                               (x < door_right && x + w > door_right - frame_thickness) {
+                            // Door in a vertical wall: only its top and bottom jambs block
+                            if (y < door_top + frame_thickness && y + h > door_top) ||
+                               (y < door_bottom && y + h > door_bottom - frame_thickness) {
                                 return false;
                             }
                         }
                     }
-                    _ => {}
+                    Solidity::Open => {}
                 }
             }
         }
@@ -389,9 +429,9 @@ impl super::Room for GridRoom {
 
     fn can_interact_tile(&self, tx: usize, ty: usize, player_tx: usize, player_ty: usize) -> bool {
         if ty >= self.tiles.len() || tx >= self.tiles[0].len() { return false; }
-        match self.tiles[ty][tx] {
-            Tile::DoorClosed | Tile::DoorOpen => {
-                // Can interact with doors if player is adjacent
+        match self.def(self.tiles[ty][tx]).interaction {
+            Some(Action::Door | Action::Chest | Action::Save | Action::Read) => {
+                // Can interact with doors, chests, save crystals and signs if player is adjacent
                 let dx = (tx as i32 - player_tx as i32).abs();
                 let dy = (ty as i32 - player_ty as i32).abs();
                 (dx == 1 && dy == 0) || (dx == 0 && dy == 1)
@@ -401,84 +441,1083 @@ impl super::Room for GridRoom {
     }
 
     fn is_movement_allowed(&self, from_x: f32, from_y: f32, to_x: f32, to_y: f32, w: f32, h: f32) -> bool {
-        // First check normal collision
-        if !self.is_rect_free(to_x, to_y, w, h) {
+        // First check normal collision, all the way there
+        if !super::sweep((from_x, from_y), (to_x, to_y)).all(|(x, y)| self.is_rect_free(x, y, w, h)) {
             return false;
         }
         
-        // Bed cozy nook logic
-        let from_on_bed = self.is_on_top_bed_tile(from_x, from_y);
-        let to_on_bed = self.is_on_top_bed_tile(to_x, to_y);
+        // Props: their walkable cells are only stepped on and off across an entry side
+        let from_tile = ((from_x / TILE_SIZE).floor() as usize, (from_y / TILE_SIZE).floor() as usize);
+        let to_tile = ((to_x / TILE_SIZE).floor() as usize, (to_y / TILE_SIZE).floor() as usize);
+        let from_prop = self.prop_walkable_at(from_tile);
+        let to_prop = self.prop_walkable_at(to_tile);
+        if from_prop != to_prop {
+            // the sides crossed moving this way, seen from the cell being left
+            let sides = [
+                if to_x > from_x { Some(Side::East) } else if to_x < from_x { Some(Side::West) } else { None },
+                if to_y > from_y { Some(Side::South) } else if to_y < from_y { Some(Side::North) } else { None },
+            ];
+            let crossable = |prop: Option<usize>, (tx, ty): (usize, usize), entering: bool| prop.is_none_or(|i| {
+                sides.iter().flatten().any(|&side| self.props[i].can_cross(tx, ty, if entering { side.opposite() } else { side }))
+            });
+            return crossable(from_prop, from_tile, false) && crossable(to_prop, to_tile, true);
+        }
         
-        // Rule 1: Block northward movement from top bed tiles to floor
-        if from_on_bed && !to_on_bed && to_y < from_y {
-            // Check if we're moving north from a top bed tile to floor
-            let from_tx = (from_x / TILE_SIZE).floor() as usize;
-            let from_ty = (from_y / TILE_SIZE).floor() as usize;
-            let height = self.tiles.len();
-            if height >= 4 && from_ty == height - 4 { // Top bed row
-                return false; // Block northward movement from top bed tiles
+        // Ledges can only be crossed heading straight south
+        if self.rect_touches_ledge(to_x, to_y, w, h) && !(to_y > from_y && to_x == from_x) {
+            return false;
+        }
+
+        // Movement within a prop or outside any is allowed (after normal collision check)
+        true
+    }
+
+    fn open_chest(&mut self, tx: usize, ty: usize) -> Option<u32> {
+        let tile = self.tiles.get(ty).and_then(|row| row.get(tx))?;
+        if let Tile::Chest { gold } = *tile {
+            self.set_tile(tx, ty, Tile::ChestOpen);
+            println!("GridRoom: opened chest at {},{} ({} gold)", tx, ty, gold);
+            return Some(gold);
+        }
+        None
+    }
+
+    fn is_save_point(&self, tx: usize, ty: usize) -> bool {
+        // Resting on a saving prop (the bed) or touching a save crystal saves the game
+        self.prop_walkable_at((tx, ty)).is_some_and(|i| self.props[i].def.save)
+            || self.tiles.get(ty).and_then(|row| row.get(tx)).is_some_and(|&t| self.def(t).interaction == Some(Action::Save))
+    }
+
+    fn push_block(&mut self, tx: usize, ty: usize, dx: i32, dy: i32) -> bool {
+        let Some(&Tile::Block { on_target }) = self.tiles.get(ty).and_then(|row| row.get(tx)) else { return false };
+        let (nx, ny) = (tx as i32 + dx, ty as i32 + dy);
+        if nx < 0 || ny < 0 { return false; }
+        let (nx, ny) = (nx as usize, ny as usize);
+        let dest = match self.tiles.get(ny).and_then(|row| row.get(nx)) {
+            Some(Tile::Floor) => Tile::Block { on_target: false },
+            Some(Tile::Target) => Tile::Block { on_target: true },
+            // walls, other blocks, furniture...
+            _ => return false,
+        };
+        self.set_tile(nx, ny, dest);
+        self.set_tile(tx, ty, if on_target { Tile::Target } else { Tile::Floor });
+        println!("GridRoom: pushed block to {},{}", nx, ny);
+        if self.puzzle_solved() {
+            for (x, y) in self.tiles_where(|t| t == Tile::DoorLocked) {
+                self.set_tile(x, y, Tile::DoorOpen);
+                println!("GridRoom: puzzle solved, door unlocked");
             }
         }
-        
-        // Rule 2: Only allow entry to bed from the right side (walking left onto right-side bed tiles)
-        if !from_on_bed && to_on_bed {
-            // Must be moving west (left) onto a right-side door tile
-            if to_x < from_x && self.is_bed_door_tile(to_x, to_y) {
-                return true; // Allow entry from right
-            } else {
-                return false; // Block all other entries
+        true
+    }
+
+    fn update(&mut self, dt: f32) -> bool {
+        let SpikeTiming { extended, retracted } = self.spike_timing;
+        let cycle = extended + retracted;
+        if cycle <= 0.0 { return false; }
+        let was_up = self.spike_extension() >= 1.0;
+        self.spike_clock = (self.spike_clock + dt) % cycle;
+        !was_up && self.spike_extension() >= 1.0
+    }
+
+    fn apply_data(&mut self, text: &str, props: &Props) {
+        let mut lit = false;
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let key = key.trim();
+            if key == "depth" {
+                self.depth = value.trim().parse().unwrap_or(0);
+                continue;
+            }
+            if key == "darkness" {
+                self.darkness = value.trim().parse::<f32>().unwrap_or(0.0).clamp(0.0, 1.0);
+                lit = true;
+                continue;
+            }
+            if key == "style" {
+                match value.trim() {
+                    "interior" => self.style = RoomStyle::Interior,
+                    "cave" => self.style = RoomStyle::Cave,
+                    other => println!("GridRoom: unknown style {:?}", other),
+                }
+                continue;
+            }
+            if key == "outdoor" {
+                self.outdoor = value.trim() == "true";
+                continue;
+            }
+            if key == "name" {
+                self.name = Some(value.trim().to_string()).filter(|name| !name.is_empty());
+                continue;
+            }
+            if key == "music" {
+                self.music = Some(value.trim().to_string()).filter(|music| !music.is_empty());
+                continue;
+            }
+            if key == "floor" {
+                match Surface::parse(value) {
+                    Some(surface) => self.floor = surface,
+                    None => println!("GridRoom: unknown floor {:?}", value.trim()),
+                }
+                continue;
+            }
+            if key == "ambience" {
+                self.ambience = Some(value.trim().to_string()).filter(|ambience| !ambience.is_empty());
+                continue;
+            }
+            if key == "size" || key == "entrance" {
+                // only rooms added by data files use these; see `from_data`
+                continue;
+            }
+            if key == "wild" || key == "encounter" || key == "exit" {
+                // random encounters and doorways are the map's business; see `EncounterTable`
+                // and `crate::teleport::parse_exits`
+                continue;
+            }
+            // everything else places something: "x,y" or "x,y:extra"
+            let (at, extra) = value.split_once(':').unwrap_or((value, ""));
+            let extra = extra.trim();
+            let tile = at.split_once(',').and_then(|(x, y)| Some((x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?)));
+            let current = tile.and_then(|(x, y)| self.tiles.get(y)?.get(x).copied());
+            if key == "prop" {
+                match (tile, props.find(extra)) {
+                    (Some((x, y)), Some(def)) if self.is_clear_for(def, x, y) => self.place_prop(def.clone(), x, y),
+                    _ => println!("GridRoom: can't place {:?}, skipping", line),
+                }
+                continue;
+            }
+            let placed = match (key, current) {
+                ("torch", Some(Tile::Wall)) => Some(Tile::Torch),
+                ("sign", Some(Tile::Floor)) => Some(Tile::Sign),
+                ("bookshelf", Some(Tile::Floor)) => Some(Tile::Bookshelf),
+                ("chair", Some(Tile::Floor)) => Some(Tile::Chair),
+                ("campfire", Some(Tile::Floor)) => Some(Tile::Campfire),
+                ("shrine", Some(Tile::Floor)) if !extra.is_empty() => Some(Tile::Shrine),
+                ("cupboard", Some(Tile::Floor)) if extra.is_empty() => Some(Tile::Cupboard { item: None }),
+                ("cupboard", Some(Tile::Floor)) => crate::items::find(extra).map(|def| Tile::Cupboard { item: Some(def.id) }),
+                // tiles added in tile data, with text after the id for ones that are read
+                ("tile", Some(Tile::Floor)) => {
+                    let id = extra.split_once(':').map_or(extra, |(id, _)| id).trim();
+                    self.kinds.position(id).filter(|_| BUILTIN_TILES.iter().all(|t| t.id() != id)).map(|i| Tile::Custom(i as u16))
+                }
+                _ => None,
+            };
+            match (placed, tile) {
+                (Some(placed), Some((x, y))) => {
+                    self.tiles[y][x] = placed;
+                    let text = if key == "tile" { extra.split_once(':').map_or("", |(_, text)| text) } else { extra };
+                    if matches!(self.def(placed).interaction, Some(Action::Read | Action::Shrine)) {
+                        self.texts.push(((x, y), text.to_string()));
+                    }
+                }
+                _ => println!("GridRoom: can't place {:?}, skipping", line),
             }
         }
-        
-        // Rule 3: Only allow exit from bed through right-side door tiles
-        if from_on_bed && !to_on_bed {
-            // Must be exiting from a right-side door tile moving east (right)
-            if to_x > from_x && self.is_bed_door_tile(from_x, from_y) {
-                return true; // Allow exit to right
-            } else {
-                return false; // Block all other exits
+        if self.style == RoomStyle::Cave && !lit {
+            self.darkness = CAVE_DARKNESS;
+        }
+    }
+
+    fn interaction(&mut self, tx: usize, ty: usize) -> Option<Interaction> {
+        let tile = *self.tiles.get(ty)?.get(tx)?;
+        match self.def(tile).interaction? {
+            Action::Read => {
+                let text = self.texts.iter().find(|(at, _)| *at == (tx, ty)).map(|(_, text)| text.clone())?;
+                Some(Interaction::Read(text))
+            }
+            Action::Sit => Some(Interaction::Sit),
+            Action::Camp => Some(Interaction::Camp),
+            Action::Shrine => {
+                let name = self.texts.iter().find(|(at, _)| *at == (tx, ty)).map(|(_, name)| name.clone())?;
+                Some(Interaction::Shrine(name))
+            }
+            Action::Search => match tile {
+                Tile::Cupboard { item } => {
+                    // whatever was inside is taken; searching again finds nothing
+                    self.set_tile(tx, ty, Tile::Cupboard { item: None });
+                    Some(Interaction::Search(item))
+                }
+                _ => Some(Interaction::Search(None)),
+            },
+            Action::Door | Action::Chest | Action::Save => None,
+        }
+    }
+
+    fn darkness(&self) -> f32 {
+        self.darkness
+    }
+
+    fn outdoor(&self) -> bool {
+        self.outdoor
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn music(&self) -> Option<&str> {
+        self.music.as_deref()
+    }
+
+    fn ambience(&self) -> Option<&str> {
+        self.ambience.as_deref()
+    }
+
+    fn depth(&self) -> u32 {
+        self.depth
+    }
+
+    fn lights(&self, t: f32) -> Vec<Light> {
+        let mut lights = Vec::new();
+        for (y, row) in self.tiles.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                let Some(reach) = self.def(tile).light else { continue };
+                let position = na::Point2::new((x as f32 + 0.5) * TILE_SIZE, (y as f32 + 0.5) * TILE_SIZE);
+                lights.push(Light { position, radius: TILE_SIZE * reach * flicker(t, x, y), intensity: 1.0 });
             }
         }
-        
-        // Movement within bed area or outside bed area is allowed (after normal collision check)
-        true
+        lights
     }
 
+    fn is_pit(&self, tx: usize, ty: usize) -> bool {
+        matches!(self.tiles.get(ty).and_then(|row| row.get(tx)), Some(Tile::Pit))
+    }
+
+    fn room_below(&self) -> Option<usize> {
+        self.below
+    }
+
+    fn entrance(&self) -> (i32, i32) {
+        self.entrance
+    }
+
+    fn spikes_up_at(&self, tx: usize, ty: usize) -> bool {
+        matches!(self.tiles.get(ty).and_then(|row| row.get(tx)), Some(Tile::Spikes)) && self.spike_extension() >= 1.0
+    }
+
+    fn is_ledge(&self, tx: usize, ty: usize) -> bool {
+        matches!(self.tiles.get(ty).and_then(|row| row.get(tx)), Some(Tile::Ledge))
+    }
+
+    fn surface_at(&self, tx: usize, ty: usize) -> Option<Surface> {
+        if self.prop_walkable_at((tx, ty)).is_some() { return None; }
+        let &tile = self.tiles.get(ty).and_then(|row| row.get(tx))?;
+        self.def(tile).footstep.surface(self.floor)
+    }
+
+    fn water_at(&self, tx: usize, ty: usize) -> Option<Water> {
+        match self.tiles.get(ty).and_then(|row| row.get(tx))? {
+            Tile::ShallowWater => Some(Water::Shallow),
+            Tile::DeepWater => Some(Water::Deep),
+            _ => None,
+        }
+    }
+
+    fn is_door_open(&self, tx: usize, ty: usize) -> bool {
+        self.tiles.get(ty).and_then(|row| row.get(tx)) == Some(&Tile::DoorOpen)
+    }
+
+    fn set_tile_kinds(&mut self, kinds: Arc<TileRegistry>) {
+        self.kinds = kinds;
+    }
+
+    fn changes(&self) -> String {
+        self.touched.iter().filter_map(|&(x, y)| {
+            let code = match self.tiles[y][x] {
+                Tile::Floor => 'f',
+                Tile::Target => 't',
+                Tile::DoorOpen => 'o',
+                Tile::DoorClosed => 'd',
+                Tile::ChestOpen => 'c',
+                Tile::Block { on_target: false } => 'b',
+                Tile::Block { on_target: true } => 'B',
+                Tile::Cupboard { item: None } => 'e',
+                _ => return None,
+            };
+            Some(format!("{},{}:{}", x, y, code))
+        }).collect::<Vec<_>>().join(" ")
+    }
+
+    fn restore_changes(&mut self, text: &str) {
+        for change in text.split_whitespace() {
+            let parsed = change.split_once(':').and_then(|(at, code)| {
+                let (x, y) = at.split_once(',')?;
+                let tile = match code {
+                    "f" => Tile::Floor,
+                    "t" => Tile::Target,
+                    "o" => Tile::DoorOpen,
+                    "d" => Tile::DoorClosed,
+                    "c" => Tile::ChestOpen,
+                    "b" => Tile::Block { on_target: false },
+                    "B" => Tile::Block { on_target: true },
+                    "e" => Tile::Cupboard { item: None },
+                    _ => return None,
+                };
+                Some((x.parse::<usize>().ok()?, y.parse::<usize>().ok()?, tile))
+            });
+            match parsed {
+                Some((x, y, tile)) if y < self.tiles.len() && x < self.tiles[y].len() => self.set_tile(x, y, tile),
+                _ => println!("GridRoom: can't restore {:?}, skipping", change),
+            }
+        }
+    }
+
+    fn describe_tile(&self, tx: usize, ty: usize) -> Vec<String> {
+        let Some(&tile) = self.tiles.get(ty).and_then(|row| row.get(tx)) else { return Vec::new() };
+        let def = self.def(tile);
+        let mut lines = vec![format!("{}  ({:?})", def.id, tile), format!("solid: {:?}", def.solid)];
+        if let Some(action) = def.interaction {
+            lines.push(format!("interaction: {:?}", action));
+        }
+        if let Some(reach) = def.light {
+            lines.push(format!("light: {} tiles", reach));
+        }
+        if let Some(surface) = self.surface_at(tx, ty) {
+            lines.push(format!("footstep: {:?}", surface));
+        }
+        if let Some((_, text)) = self.texts.iter().find(|(at, _)| *at == (tx, ty)) {
+            lines.push(format!("text: {}", text));
+        }
+        if let Some(i) = self.prop_walkable_at((tx, ty)) {
+            lines.push(format!("on prop: {}", self.props[i].def.name));
+        }
+        if matches!(tile, Tile::Spikes) {
+            lines.push(format!("spikes {}", if self.spikes_up_at(tx, ty) { "up" } else { "down" }));
+        }
+        lines
+    }
+
+    fn prop_cells(&self) -> Vec<((usize, usize), Vec<Side>)> {
+        let mut cells = Vec::new();
+        for prop in &self.props {
+            for y in prop.y..prop.y + prop.def.height() {
+                for x in (prop.x..prop.x + prop.def.width()).filter(|&x| prop.is_walkable(x, y)) {
+                    let sides = [Side::North, Side::East, Side::South, Side::West].into_iter().filter(|&side| prop.can_cross(x, y, side)).collect();
+                    cells.push(((x, y), sides));
+                }
+            }
+        }
+        cells
+    }
 }
 
 impl GridRoom {
-    /// Check if a position is on a top bed tile (the walkable bed area)
-    fn is_on_top_bed_tile(&self, x: f32, y: f32) -> bool {
-        let tx = (x / TILE_SIZE).floor() as usize;
-        let ty = (y / TILE_SIZE).floor() as usize;
-        let height = self.tiles.len();
-        
-        // Top bed tiles are at (1,height-4), (2,height-4), (1,height-3), (2,height-3)
-        if height >= 4 && tx >= 1 && tx <= 2 {
-            let top_bed_y1 = height - 4;
-            let top_bed_y2 = height - 3;
-            ty == top_bed_y1 || ty == top_bed_y2
-        } else {
-            false
+    /// The registry entry for a tile.
+    fn def(&self, tile: Tile) -> &TileDef {
+        match tile {
+            Tile::Custom(i) => self.kinds.get(i as usize),
+            _ => self.kinds.find(tile.id()),
         }
     }
 
-    /// Check if a position is on a bed "door" tile (entry/exit points for the cozy nook)
-    fn is_bed_door_tile(&self, x: f32, y: f32) -> bool {
-        let tx = (x / TILE_SIZE).floor() as usize;
-        let ty = (y / TILE_SIZE).floor() as usize;
-        let height = self.tiles.len();
-        
-        // Door tiles are the right-side bed tiles: (2, height-4) and (2, height-3)
-        if height >= 4 && tx == 2 {
-            let top_bed_y = height - 4;
-            let middle_bed_y = height - 3;
-            ty == top_bed_y || ty == middle_bed_y
-        } else {
-            false
+    /// How the neighbour `dx`,`dy` away from `x`,`y` joins the walls (None off the edge).
+    fn wall_join(&self, x: usize, y: usize, dx: i32, dy: i32) -> WallJoin {
+        let (nx, ny) = (x as i32 + dx, y as i32 + dy);
+        if nx < 0 || ny < 0 { return WallJoin::None; }
+        self.tiles.get(ny as usize).and_then(|row| row.get(nx as usize)).map_or(WallJoin::None, |&t| self.def(t).wall)
+    }
+
+    fn spot(&self, x: usize, y: usize, dest_x: f32, dest_y: f32, scale: f32) -> Spot {
+        let joins = [(-1, 0), (1, 0), (0, -1), (0, 1)].map(|(dx, dy)| self.wall_join(x, y, dx, dy));
+        let walls = joins.map(|join| join != WallJoin::None);
+        // For joints, only consider actual walls and closed doors, not open doors
+        let joints = joins.map(|join| join == WallJoin::Wall);
+        // Force horizontal orientation for top/bottom rows to make top/bottom walls look like horizontal planks
+        let horizontal = walls[0] as u8 + walls[1] as u8 >= walls[2] as u8 + walls[3] as u8 || y == 0 || y + 1 == self.tiles.len();
+        Spot { x, y, dest_x, dest_y, scale, horizontal, walls, joints }
+    }
+
+    /// Draw a tile's sprite over its base: one painted here, or else an image from the assets.
+    fn draw_sprite(&self, ctx: &mut Context, canvas: &mut Canvas, assets: &Assets, sprite: &str, tile: Tile, spot: &Spot) -> GameResult {
+        let Spot { x, y, dest_x, dest_y, scale, .. } = *spot;
+        let dest = ggez::mint::Point2 { x: dest_x, y: dest_y };
+        let t = ctx.time.time_since_start().as_secs_f32();
+        let cave = self.style == RoomStyle::Cave;
+        match sprite {
+            // rock has no timber joints, and stone no knots or worn varnish
+            "wall_joints" | "knot" | "worn" if cave => {}
+            "wall_joints" => {
+                // Wall joint overlays: centered on corner wall tiles (this current wall tile forms a corner)
+                let joint_scale = scale * TILE_SIZE / assets.wall_joint.width() as f32 * 1.15; // Scale up to touch floor outline
+                let [left, right, up, down] = spot.joints;
+                // Only draw joint if this wall tile forms a corner with adjacent solid walls (not open doors):
+                // bottom-right, bottom-left, top-right and top-left
+                let corners = [
+                    (right && down, 0.0),
+                    (left && down, std::f32::consts::FRAC_PI_2),
+                    (right && up, -std::f32::consts::FRAC_PI_2),
+                    (left && up, std::f32::consts::PI),
+                ];
+                for (_, rotation) in corners.into_iter().filter(|(corner, _)| *corner) {
+                    canvas.draw(&assets.wall_joint, DrawParam::new()
+                        .dest(dest)
+                        .offset([0.5, 0.5])
+                        .rotation(rotation)
+                        .scale([joint_scale, joint_scale]));
+                }
+            }
+            "door" => {
+                let door_color = ggez::graphics::Mesh::new_rectangle(ctx, ggez::graphics::DrawMode::fill(), ggez::graphics::Rect::new(dest_x - TILE_SIZE*scale/2.0, dest_y - TILE_SIZE*scale/2.0, TILE_SIZE*scale, TILE_SIZE*scale), ggez::graphics::Color::new(0.1, 0.05, 0.0, 0.6))?;
+                canvas.draw(&door_color, DrawParam::new());
+            }
+            "door_frame" => {
+                // Draw door frame/opening indicators
+                let frame_color = ggez::graphics::Color::new(0.3, 0.2, 0.1, 0.8);
+                let opening_width = TILE_SIZE * scale * 0.8;
+                let opening_height = TILE_SIZE * scale * 0.8;
+                let frame_thickness = TILE_SIZE * scale * 0.1;
+                let frames = if spot.horizontal {
+                    // Horizontal door opening - draw vertical frame sides
+                    [ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y - opening_height/2.0, frame_thickness, opening_height),
+                     ggez::graphics::Rect::new(dest_x + opening_width/2.0 - frame_thickness, dest_y - opening_height/2.0, frame_thickness, opening_height)]
+                } else {
+                    // Vertical door opening - draw horizontal frame sides
+                    [ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y - opening_height/2.0, opening_width, frame_thickness),
+                     ggez::graphics::Rect::new(dest_x - opening_width/2.0, dest_y + opening_height/2.0 - frame_thickness, opening_width, frame_thickness)]
+                };
+                for frame in frames {
+                    let mesh = ggez::graphics::Mesh::new_rectangle(ctx, ggez::graphics::DrawMode::fill(), frame, frame_color)?;
+                    canvas.draw(&mesh, DrawParam::new());
+                }
+            }
+            "torch" => draw_torch(ctx, canvas, dest_x, dest_y, scale, flicker(t, x, y))?,
+            "lock" => draw_lock(ctx, canvas, dest_x, dest_y, scale)?,
+            "block" => draw_block(ctx, canvas, dest_x, dest_y, scale, tile == Tile::Block { on_target: true })?,
+            "shallow_water" | "deep_water" => draw_water(ctx, canvas, dest_x, dest_y, scale, t + (x + y) as f32 * 0.7, sprite == "deep_water")?,
+            "ledge" => draw_ledge(ctx, canvas, dest_x, dest_y, scale)?,
+            "spikes" => draw_spikes(ctx, canvas, dest_x, dest_y, scale, self.spike_extension())?,
+            "pit" => draw_pit(ctx, canvas, dest_x, dest_y, scale)?,
+            "campfire" => draw_campfire(ctx, canvas, dest_x, dest_y, scale, flicker(t, x, y))?,
+            "shrine" => draw_shrine(ctx, canvas, dest_x, dest_y, scale, t)?,
+            "sign" => draw_sign(ctx, canvas, dest_x, dest_y, scale)?,
+            "bookshelf" => draw_bookshelf(ctx, canvas, dest_x, dest_y, scale)?,
+            "chair" => draw_chair(ctx, canvas, dest_x, dest_y, scale)?,
+            "cupboard" => draw_cupboard(ctx, canvas, dest_x, dest_y, scale)?,
+            "target" => draw_target(ctx, canvas, dest_x, dest_y, scale)?,
+            "chest" | "open_chest" => draw_chest(ctx, canvas, dest_x, dest_y, scale, sprite == "open_chest")?,
+            "save_crystal" => draw_save_crystal(ctx, canvas, dest_x, dest_y, scale, t)?,
+            "rubble" => draw_rubble(ctx, canvas, dest_x, dest_y, scale, spot_hash(x, y))?,
+            "glowshroom" => draw_glowshroom(ctx, canvas, dest_x, dest_y, scale, t + (x * 3 + y) as f32)?,
+            "knot" | "worn" | "cracks" => draw_wear(ctx, canvas, dest_x, dest_y, scale, sprite, spot_hash(x, y))?,
+            _ => {
+                if let Some(image) = assets.sprite(sprite) {
+                    let image_scale = scale * TILE_SIZE / image.width() as f32;
+                    canvas.draw(image, DrawParam::new().dest(dest).offset([0.5, 0.5]).scale([image_scale, image_scale]));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Change a tile during play, remembering it for `changes`.
+    fn set_tile(&mut self, x: usize, y: usize, tile: Tile) {
+        self.tiles[y][x] = tile;
+        if !self.touched.contains(&(x, y)) {
+            self.touched.push((x, y));
+        }
+    }
+
+    /// Every tile position matching `wanted`.
+    fn tiles_where(&self, wanted: impl Fn(Tile) -> bool) -> Vec<(usize, usize)> {
+        let mut found = Vec::new();
+        for (y, row) in self.tiles.iter().enumerate() {
+            found.extend(row.iter().enumerate().filter(|&(_, &t)| wanted(t)).map(|(x, _)| (x, y)));
+        }
+        found
+    }
+
+    /// True if the rectangle overlaps any ledge tile.
+    fn rect_touches_ledge(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        let (left, right) = ((x / TILE_SIZE).floor() as usize, ((x + w) / TILE_SIZE).floor() as usize);
+        let (top, bottom) = ((y / TILE_SIZE).floor() as usize, ((y + h) / TILE_SIZE).floor() as usize);
+        (top..=bottom).any(|ty| (left..=right).any(|tx| matches!(self.tiles.get(ty).and_then(|row| row.get(tx)), Some(Tile::Ledge))))
+    }
+
+    /// All targets covered: no bare Target plates, and at least one block sitting on one.
+    fn puzzle_solved(&self) -> bool {
+        let tiles = || self.tiles.iter().flatten();
+        !tiles().any(|t| *t == Tile::Target) && tiles().any(|t| *t == Tile::Block { on_target: true })
+    }
+
+    /// The prop whose walkable cells include this tile, if any.
+    fn prop_walkable_at(&self, (tx, ty): (usize, usize)) -> Option<usize> {
+        self.props.iter().position(|prop| prop.is_walkable(tx, ty))
+    }
+
+    /// Every tile under `def` placed at `x`,`y` is bare floor.
+    fn is_clear_for(&self, def: &PropDef, x: usize, y: usize) -> bool {
+        (0..def.height()).all(|dy| (0..def.width()).all(|dx| self.tiles.get(y + dy).and_then(|row| row.get(x + dx)) == Some(&Tile::Floor)))
+    }
+
+    /// Solid cells become faux walls; walkable ones stay floor and are handled by the prop rules.
+    fn place_prop(&mut self, def: PropDef, x: usize, y: usize) {
+        for (dy, row) in def.solid.iter().enumerate() {
+            for (dx, _) in row.iter().enumerate().filter(|(_, solid)| **solid) {
+                self.tiles[y + dy][x + dx] = Tile::Fwall;
+            }
+        }
+        self.props.push(PlacedProp { def, x, y });
+    }
+
+}
+
+/// Where a tile is being drawn and how the walls around it lie.
+#[derive(Clone, Copy)]
+struct Spot {
+    x: usize,
+    y: usize,
+    dest_x: f32,
+    dest_y: f32,
+    scale: f32,
+    // which way wall planks (and door frames) run here
+    horizontal: bool,
+    // left, right, up and down neighbours that are walls or openings in them
+    walls: [bool; 4],
+    // the same, counting only solid wall
+    joints: [bool; 4],
+}
+
+/// Black outlines where floor meets walls.
+fn draw_floor_edges(ctx: &mut Context, canvas: &mut Canvas, spot: &Spot) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let Spot { dest_x, dest_y, scale, .. } = *spot;
+    let thickness = scale * 2.0; // 2 pixel outline
+    let half_tile = TILE_SIZE * scale * 0.5;
+    let [left, right, up, down] = spot.walls;
+    let edges = [
+        (up, Rect::new(dest_x - half_tile, dest_y - half_tile, TILE_SIZE * scale, thickness)),
+        (down, Rect::new(dest_x - half_tile, dest_y + half_tile - thickness, TILE_SIZE * scale, thickness)),
+        (left, Rect::new(dest_x - half_tile, dest_y - half_tile, thickness, TILE_SIZE * scale)),
+        (right, Rect::new(dest_x + half_tile - thickness, dest_y - half_tile, thickness, TILE_SIZE * scale)),
+    ];
+    for (_, rect) in edges.into_iter().filter(|(wall, _)| *wall) {
+        let outline = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::BLACK)?;
+        canvas.draw(&outline, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// Chests have no sprite yet: a wooden box with a gold band (lid raised once looted).
+fn draw_chest(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, open: bool) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let w = TILE_SIZE * 0.8 * scale;
+    let h = TILE_SIZE * 0.6 * scale;
+    let body = Rect::new(cx - w / 2.0, cy - h / 2.0 + 3.0 * scale, w, h);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), body, Color::new(0.45, 0.25, 0.1, 1.0))?, DrawParam::new());
+    if open {
+        // dark interior and the lid tipped back above the box
+        let inside = Rect::new(body.x + 2.0 * scale, body.y, body.w - 4.0 * scale, h * 0.35);
+        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), inside, Color::new(0.1, 0.05, 0.02, 1.0))?, DrawParam::new());
+        let lid = Rect::new(body.x, body.y - h * 0.45, w, h * 0.35);
+        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), lid, Color::new(0.55, 0.32, 0.12, 1.0))?, DrawParam::new());
+    } else {
+        let band = Rect::new(body.x, body.y + h * 0.35, w, 3.0 * scale);
+        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), band, Color::new(0.95, 0.75, 0.2, 1.0))?, DrawParam::new());
+        let lock = Rect::new(cx - 2.0 * scale, body.y + h * 0.3, 4.0 * scale, 6.0 * scale);
+        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), lock, Color::new(0.95, 0.75, 0.2, 1.0))?, DrawParam::new());
+    }
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(1.5 * scale), body, Color::BLACK)?, DrawParam::new());
+    Ok(())
+}
+
+/// Save crystal: a diamond whose glow pulses over time (`t` in seconds) so it stands out.
+fn draw_save_crystal(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, t: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh};
+    let pulse = 0.5 + 0.5 * (t * 3.0).sin();
+    let glow = Color::new(0.4, 0.9, 1.0, 0.15 + 0.3 * pulse);
+    let glow_r = TILE_SIZE * (0.45 + 0.1 * pulse) * scale;
+    canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy], glow_r, 0.5, glow)?, DrawParam::new());
+    let (hw, hh) = (TILE_SIZE * 0.22 * scale, TILE_SIZE * 0.38 * scale);
+    let diamond = [[cx, cy - hh], [cx + hw, cy], [cx, cy + hh], [cx - hw, cy]];
+    let body = Color::new(0.3 + 0.3 * pulse, 0.75 + 0.2 * pulse, 1.0, 1.0);
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &diamond, body)?, DrawParam::new());
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::stroke(1.5 * scale), &diamond, Color::WHITE)?, DrawParam::new());
+    Ok(())
+}
+
+/// Padlock drawn over a locked door.
+fn draw_lock(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let gold = Color::new(0.95, 0.75, 0.2, 1.0);
+    canvas.draw(&Mesh::new_circle(ctx, DrawMode::stroke(2.0 * scale), [cx, cy - 3.0 * scale], 4.0 * scale, 0.5, gold)?, DrawParam::new());
+    let body = Rect::new(cx - 6.0 * scale, cy - 2.0 * scale, 12.0 * scale, 9.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), body, gold)?, DrawParam::new());
+    Ok(())
+}
+
+/// Pushable stone block; glows green once it sits on a target plate.
+fn draw_block(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, on_target: bool) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * 0.86 * scale;
+    let rect = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    let fill = if on_target { Color::new(0.45, 0.65, 0.45, 1.0) } else { Color::new(0.55, 0.55, 0.58, 1.0) };
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rect, fill)?, DrawParam::new());
+    // bevel lines so it reads as a block rather than a floor tile
+    let inset = Rect::new(rect.x + 4.0 * scale, rect.y + 4.0 * scale, size - 8.0 * scale, size - 8.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(1.5 * scale), inset, Color::new(0.3, 0.3, 0.32, 1.0))?, DrawParam::new());
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(2.0 * scale), rect, Color::BLACK)?, DrawParam::new());
+    Ok(())
+}
+
+/// Target plate: a recessed square outline on the floor.
+fn draw_target(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * 0.7 * scale;
+    let rect = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.2, 0.15, 0.1, 0.5))?, DrawParam::new());
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(2.0 * scale), rect, Color::new(0.95, 0.75, 0.2, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Spike trap: four holes in a metal plate, with spikes poking out of them by `extension`.
+fn draw_spikes(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, extension: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * 0.8 * scale;
+    let plate = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), plate, Color::new(0.35, 0.35, 0.38, 1.0))?, DrawParam::new());
+    for (ox, oy) in [(-0.25, -0.25), (0.25, -0.25), (-0.25, 0.25), (0.25, 0.25)] {
+        let (hx, hy) = (cx + ox * size, cy + oy * size);
+        canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [hx, hy], size * 0.1, 0.5, Color::new(0.1, 0.1, 0.1, 1.0))?, DrawParam::new());
+        if extension > 0.0 {
+            let (half, height) = (size * 0.1, size * 0.35 * extension);
+            let spike = [[hx - half, hy + half * 0.5], [hx + half, hy + half * 0.5], [hx, hy - height]];
+            canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &spike, Color::new(0.85, 0.87, 0.9, 1.0))?, DrawParam::new());
         }
     }
+    Ok(())
+}
 
+/// Pit: a black hole with a lit rim on the far side, fading into the dark.
+fn draw_pit(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * scale;
+    let (left, top) = (cx - size / 2.0, cy - size / 2.0);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(left, top, size, size), Color::new(0.02, 0.02, 0.03, 1.0))?, DrawParam::new());
+    let rim = Rect::new(left, top, size, size * 0.2);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rim, Color::new(0.3, 0.2, 0.12, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Floor wear: a knot in the wood, a worn patch or a few cracks, placed and turned by `hash`.
+fn draw_wear(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, kind: &str, hash: u32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh};
+    let size = TILE_SIZE * scale;
+    // somewhere in the middle half of the tile
+    let (ox, oy) = (((hash >> 4 & 15) as f32 / 15.0 - 0.5) * size * 0.5, ((hash >> 20 & 15) as f32 / 15.0 - 0.5) * size * 0.5);
+    let (x, y) = (cx + ox, cy + oy);
+    match kind {
+        "knot" => {
+            let knot = Mesh::new_ellipse(ctx, DrawMode::fill(), [x, y], size * 0.09, size * 0.05, 0.5, Color::new(0.25, 0.15, 0.08, 0.55))?;
+            canvas.draw(&knot, DrawParam::new());
+            let ring = Mesh::new_ellipse(ctx, DrawMode::stroke(scale), [x, y], size * 0.14, size * 0.08, 0.5, Color::new(0.3, 0.2, 0.1, 0.35))?;
+            canvas.draw(&ring, DrawParam::new());
+        }
+        "worn" => {
+            let patch = Mesh::new_ellipse(ctx, DrawMode::fill(), [x, y], size * 0.22, size * 0.14, 0.5, Color::new(1.0, 0.95, 0.85, 0.12))?;
+            canvas.draw(&patch, DrawParam::new());
+        }
+        _ => {
+            // a jagged crack, mirrored either way
+            let dir = if hash & 0x200 != 0 { 1.0 } else { -1.0 };
+            let points = [[x - size * 0.2 * dir, y - size * 0.15], [x - size * 0.05 * dir, y - size * 0.02], [x + size * 0.02 * dir, y + size * 0.08], [x + size * 0.18 * dir, y + size * 0.14]];
+            let crack = Mesh::new_line(ctx, &points, 1.2 * scale, Color::new(0.1, 0.07, 0.05, 0.6))?;
+            canvas.draw(&crack, DrawParam::new());
+            let branch = Mesh::new_line(ctx, &[points[1], [x + size * 0.08 * dir, y - size * 0.12]], 0.9 * scale, Color::new(0.1, 0.07, 0.05, 0.5))?;
+            canvas.draw(&branch, DrawParam::new());
+        }
+    }
+    Ok(())
+}
 
+/// Cave floor: grey stone, shaded by position, with a few pebbles.
+fn draw_cave_floor(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, hash: u32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * scale;
+    let shade = 0.3 + (hash >> 12 & 7) as f32 * 0.01;
+    let stone = Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(cx - size / 2.0, cy - size / 2.0, size, size), Color::new(shade, shade * 0.95, shade * 0.9, 1.0))?;
+    canvas.draw(&stone, DrawParam::new());
+    for i in 0..2 {
+        let bits = hash >> (i * 8);
+        let (ox, oy) = (((bits & 15) as f32 / 15.0 - 0.5) * size * 0.7, ((bits >> 4 & 15) as f32 / 15.0 - 0.5) * size * 0.7);
+        let pebble = Mesh::new_ellipse(ctx, DrawMode::fill(), [cx + ox, cy + oy], size * 0.06, size * 0.04, 0.5, Color::new(0.2, 0.19, 0.18, 0.7))?;
+        canvas.draw(&pebble, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// Cave wall: dark rock with a few lumps of lighter stone, and a lit lip along any face that
+/// looks down onto open floor.
+fn draw_rock(ctx: &mut Context, canvas: &mut Canvas, spot: &Spot, hash: u32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let Spot { dest_x: cx, dest_y: cy, scale, .. } = *spot;
+    let size = TILE_SIZE * scale;
+    let (left, top) = (cx - size / 2.0, cy - size / 2.0);
+    let rock = Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(left, top, size, size), Color::new(0.17, 0.15, 0.14, 1.0))?;
+    canvas.draw(&rock, DrawParam::new());
+    for i in 0..3 {
+        let bits = hash >> (i * 7);
+        let (ox, oy) = (((bits & 7) as f32 / 7.0 - 0.5) * size * 0.6, ((bits >> 3 & 7) as f32 / 7.0 - 0.5) * size * 0.6);
+        let radius = size * (0.18 + (bits >> 6 & 1) as f32 * 0.08);
+        let shade = 0.24 + i as f32 * 0.03;
+        let lump = Mesh::new_circle(ctx, DrawMode::fill(), [cx + ox, cy + oy], radius, 0.5, Color::new(shade, shade * 0.93, shade * 0.88, 1.0))?;
+        canvas.draw(&lump, DrawParam::new());
+    }
+    if !spot.walls[3] {
+        let lip = Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(left, top + size * 0.85, size, size * 0.15), Color::new(0.38, 0.35, 0.32, 1.0))?;
+        canvas.draw(&lip, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// A heap of fallen stones, piled differently by position.
+fn draw_rubble(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, hash: u32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh};
+    let size = TILE_SIZE * scale;
+    let stones = [(-0.2, 0.15, 0.2), (0.18, 0.18, 0.18), (0.0, -0.05, 0.24), (-0.15, -0.22, 0.12), (0.22, -0.15, 0.13)];
+    for (i, (ox, oy, r)) in stones.into_iter().enumerate() {
+        let nudge = ((hash >> (i * 3) & 7) as f32 / 7.0 - 0.5) * 0.08;
+        let shade = 0.3 + (hash >> (i * 2 + 16) & 3) as f32 * 0.05;
+        let (x, y) = (cx + (ox + nudge) * size, cy + (oy - nudge) * size);
+        let stone = Mesh::new_ellipse(ctx, DrawMode::fill(), [x, y], r * size, r * size * 0.8, 0.5, Color::new(shade, shade * 0.95, shade * 0.9, 1.0))?;
+        canvas.draw(&stone, DrawParam::new());
+        let shadow = Mesh::new_ellipse(ctx, DrawMode::stroke(scale), [x, y], r * size, r * size * 0.8, 0.5, Color::new(0.08, 0.07, 0.06, 0.8))?;
+        canvas.draw(&shadow, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// A cluster of pale mushrooms whose caps pulse softly.
+fn draw_glowshroom(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, t: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * scale;
+    let pulse = 0.75 + 0.25 * (t * 1.7).sin();
+    for (ox, height, cap) in [(-0.2, 0.25, 0.14), (0.05, 0.35, 0.18), (0.25, 0.2, 0.11)] {
+        let (x, base) = (cx + ox * size, cy + size * 0.3);
+        let stem = Mesh::new_rectangle(ctx, DrawMode::fill(), Rect::new(x - size * 0.03, base - height * size, size * 0.06, height * size), Color::new(0.8, 0.85, 0.75, 1.0))?;
+        canvas.draw(&stem, DrawParam::new());
+        let glow = Mesh::new_circle(ctx, DrawMode::fill(), [x, base - height * size], cap * size * 1.8, 0.5, Color::new(0.3, 0.9, 0.8, 0.15 * pulse))?;
+        canvas.draw(&glow, DrawParam::new());
+        let top = Mesh::new_ellipse(ctx, DrawMode::fill(), [x, base - height * size], cap * size, cap * size * 0.6, 0.5, Color::new(0.35 * pulse, 0.95 * pulse, 0.85 * pulse, 1.0))?;
+        canvas.draw(&top, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// Torch flicker factor around 1.0, desynchronised per tile so torches don't pulse in step.
+fn flicker(t: f32, x: usize, y: usize) -> f32 {
+    let seed = (x * 7 + y * 13) as f32;
+    1.0 + 0.05 * (t * 9.0 + seed).sin() + 0.03 * (t * 23.0 + seed * 0.5).sin()
+}
+
+/// Wall torch: a wooden handle with a flame whose size follows `flicker`.
+fn draw_torch(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, flicker: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let handle = Rect::new(cx - 2.0 * scale, cy, 4.0 * scale, 10.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), handle, Color::new(0.35, 0.2, 0.1, 1.0))?, DrawParam::new());
+    let size = 6.0 * scale * flicker * flicker;
+    let outer = [[cx - size * 0.7, cy], [cx + size * 0.7, cy], [cx, cy - size * 1.8]];
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &outer, Color::new(1.0, 0.45, 0.1, 0.95))?, DrawParam::new());
+    let inner = [[cx - size * 0.35, cy], [cx + size * 0.35, cy], [cx, cy - size]];
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &inner, Color::new(1.0, 0.9, 0.4, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Campfire: crossed logs under a flame whose size follows `flicker`.
+fn draw_campfire(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, flicker: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh};
+    let wood = Color::new(0.4, 0.24, 0.1, 1.0);
+    for (dx, dy) in [(1.0, 0.35), (1.0, -0.35)] {
+        let log = [[cx - 11.0 * dx * scale, cy + 8.0 * scale + 11.0 * dy * scale], [cx + 11.0 * dx * scale, cy + 8.0 * scale - 11.0 * dy * scale]];
+        canvas.draw(&Mesh::new_line(ctx, &log, 4.0 * scale, wood)?, DrawParam::new());
+    }
+    let size = 9.0 * scale * flicker * flicker;
+    let base = cy + 8.0 * scale;
+    let outer = [[cx - size * 0.7, base], [cx + size * 0.7, base], [cx, base - size * 1.8]];
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &outer, Color::new(1.0, 0.45, 0.1, 0.95))?, DrawParam::new());
+    let inner = [[cx - size * 0.35, base], [cx + size * 0.35, base], [cx, base - size]];
+    canvas.draw(&Mesh::new_polygon(ctx, DrawMode::fill(), &inner, Color::new(1.0, 0.9, 0.4, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Waypoint shrine: a stone pillar topped with a softly bobbing orb.
+fn draw_shrine(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, t: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let pillar = Rect::new(cx - 6.0 * scale, cy - 2.0 * scale, 12.0 * scale, 16.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), pillar, Color::new(0.6, 0.62, 0.68, 1.0))?, DrawParam::new());
+    let cap = Rect::new(cx - 9.0 * scale, cy - 4.0 * scale, 18.0 * scale, 4.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), cap, Color::new(0.45, 0.47, 0.52, 1.0))?, DrawParam::new());
+    let orb_y = cy - 10.0 * scale + (t * 2.0).sin() * 1.5 * scale;
+    let glow = Mesh::new_circle(ctx, DrawMode::fill(), [cx, orb_y], 8.0 * scale, 0.5, Color::new(0.4, 0.9, 1.0, 0.3))?;
+    canvas.draw(&glow, DrawParam::new());
+    let orb = Mesh::new_circle(ctx, DrawMode::fill(), [cx, orb_y], 4.5 * scale, 0.5, Color::new(0.7, 0.97, 1.0, 1.0))?;
+    canvas.draw(&orb, DrawParam::new());
+    Ok(())
+}
+
+/// Signpost: a wooden board on a short post.
+pub(super) fn draw_sign(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let wood = Color::new(0.55, 0.35, 0.18, 1.0);
+    let post = Rect::new(cx - 2.0 * scale, cy, 4.0 * scale, 13.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), post, wood)?, DrawParam::new());
+    let board = Rect::new(cx - 12.0 * scale, cy - 10.0 * scale, 24.0 * scale, 13.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), board, Color::new(0.7, 0.5, 0.28, 1.0))?, DrawParam::new());
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(1.5 * scale), board, Color::new(0.3, 0.18, 0.08, 1.0))?, DrawParam::new());
+    // a couple of scratched lines of "writing"
+    for (i, len) in [16.0, 11.0].into_iter().enumerate() {
+        let y = board.y + (4.0 + i as f32 * 4.5) * scale;
+        let line = [[cx - len / 2.0 * scale, y], [cx + len / 2.0 * scale, y]];
+        canvas.draw(&Mesh::new_line(ctx, &line, 1.0 * scale, Color::new(0.3, 0.18, 0.08, 1.0))?, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// Bookshelf: a dark frame with three shelves of coloured spines.
+fn draw_bookshelf(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * 0.9 * scale;
+    let frame = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), frame, Color::new(0.35, 0.2, 0.1, 1.0))?, DrawParam::new());
+    let spines = [(0.7, 0.2, 0.2), (0.2, 0.4, 0.7), (0.75, 0.65, 0.3), (0.3, 0.55, 0.3)];
+    let shelf_h = size / 3.0;
+    for shelf in 0..3 {
+        let y = frame.y + shelf as f32 * shelf_h + 2.0 * scale;
+        for (i, &(r, g, b)) in spines.iter().enumerate() {
+            // vary the order per shelf so it doesn't look tiled
+            let x = frame.x + 3.0 * scale + ((i + shelf) % spines.len()) as f32 * (size - 6.0 * scale) / 4.0;
+            let book = Rect::new(x, y, (size - 6.0 * scale) / 4.0 - 1.0 * scale, shelf_h - 4.0 * scale);
+            canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), book, Color::new(r, g, b, 1.0))?, DrawParam::new());
+        }
+    }
+    Ok(())
+}
+
+/// Chair seen from above: a seat with the backrest along the top edge.
+fn draw_chair(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let wood = Color::new(0.6, 0.38, 0.18, 1.0);
+    let seat = Rect::new(cx - 9.0 * scale, cy - 6.0 * scale, 18.0 * scale, 16.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), seat, wood)?, DrawParam::new());
+    let back = Rect::new(cx - 10.0 * scale, cy - 11.0 * scale, 20.0 * scale, 5.0 * scale);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), back, Color::new(0.45, 0.28, 0.12, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Cupboard: two doors with little brass knobs.
+fn draw_cupboard(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * 0.85 * scale;
+    let body = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), body, Color::new(0.55, 0.33, 0.16, 1.0))?, DrawParam::new());
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::stroke(1.5 * scale), body, Color::new(0.3, 0.18, 0.08, 1.0))?, DrawParam::new());
+    let split = [[cx, body.y], [cx, body.y + size]];
+    canvas.draw(&Mesh::new_line(ctx, &split, 1.5 * scale, Color::new(0.3, 0.18, 0.08, 1.0))?, DrawParam::new());
+    for dx in [-3.0, 3.0] {
+        canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [cx + dx * scale, cy], 1.5 * scale, 0.3, Color::new(0.95, 0.75, 0.2, 1.0))?, DrawParam::new());
+    }
+    Ok(())
+}
+
+/// Ledge: a shadowed drop along the bottom edge of the tile with a lit lip above it.
+fn draw_ledge(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * scale;
+    let (left, top) = (cx - size / 2.0, cy - size / 2.0);
+    let face = Rect::new(left, top + size * 0.55, size, size * 0.45);
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), face, Color::new(0.3, 0.2, 0.1, 1.0))?, DrawParam::new());
+    let lip = [[left, top + size * 0.55], [left + size, top + size * 0.55]];
+    canvas.draw(&Mesh::new_line(ctx, &lip, 3.0 * scale, Color::new(0.85, 0.7, 0.45, 1.0))?, DrawParam::new());
+    Ok(())
+}
+
+/// Water tile: a flat blue fill with a couple of drifting highlight streaks. Deep water is
+/// darker and its streaks move slower.
+pub(super) fn draw_water(ctx: &mut Context, canvas: &mut Canvas, cx: f32, cy: f32, scale: f32, t: f32, deep: bool) -> GameResult {
+    use ggez::graphics::{Color, DrawMode, Mesh, Rect};
+    let size = TILE_SIZE * scale;
+    let rect = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+    let fill = if deep { Color::new(0.1, 0.25, 0.55, 1.0) } else { Color::new(0.35, 0.6, 0.85, 1.0) };
+    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rect, fill)?, DrawParam::new());
+    let speed = if deep { 0.6 } else { 1.2 };
+    for i in 0..2 {
+        let phase = t * speed + i as f32 * std::f32::consts::PI;
+        let y = rect.y + size * (0.3 + 0.4 * i as f32);
+        let x = cx + phase.sin() * size * 0.15;
+        let streak = [[x - size * 0.2, y], [x + size * 0.2, y]];
+        let alpha = 0.35 + 0.2 * phase.cos();
+        canvas.draw(&Mesh::new_line(ctx, &streak, 1.5 * scale, Color::new(0.85, 0.95, 1.0, alpha))?, DrawParam::new());
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rooms::Room;
+
+    #[test]
+    fn pushing_block_onto_target_unlocks_door() {
+        let mut room = GridRoom::new(12, 12);
+        let x = 6;
+        assert!(!room.push_block(x, 6, 0, 1), "nothing to push on plain floor");
+        assert!(room.push_block(x, 7, 0, 1));
+        assert!(room.tiles[11][x] == Tile::DoorLocked);
+        assert!(room.push_block(x, 8, 0, 1));
+        assert!(room.tiles[9][x] == Tile::Block { on_target: true });
+        assert!(room.tiles[11][x] == Tile::DoorOpen);
+        assert!(room.push_block(x, 9, 0, 1));
+        assert!(room.tiles[9][x] == Tile::Target, "the plate is left behind");
+        assert!(!room.push_block(x, 10, 0, 1), "doorways stop the block");
+    }
+
+    #[test]
+    fn changes_made_in_play_carry_over_to_a_fresh_room() {
+        let mut room = GridRoom::new(12, 12);
+        let x = 6;
+        room.push_block(x, 7, 0, 1);
+        room.push_block(x, 8, 0, 1);
+        room.interact_tile(6, 0);
+        let changes = room.changes();
+        let mut fresh = GridRoom::new(12, 12);
+        fresh.restore_changes(&changes);
+        assert!(fresh.tiles[9][x] == Tile::Block { on_target: true });
+        assert!(fresh.tiles[7][x] == Tile::Floor);
+        assert!(fresh.tiles[11][x] == Tile::DoorOpen, "the unlocked door stays open");
+        assert!(fresh.tiles[0][6] == Tile::DoorOpen);
+        assert_eq!(fresh.changes(), changes);
+        fresh.restore_changes("1,1:x 99,99:o");
+        assert_eq!(fresh.changes(), changes, "bad entries are skipped");
+    }
+
+    #[test]
+    fn data_files_can_make_whole_rooms() {
+        let text = "name=Modded Hall\nsize=10,8\nentrance=4,3\ntorch=0,2\n";
+        let mut room = GridRoom::from_data(text);
+        room.apply_data(text, &Props::default());
+        assert_eq!((room.tiles[0].len(), room.tiles.len()), (10, 8));
+        assert_eq!(room.entrance, (4, 3));
+        assert!(room.tiles[2][0] == Tile::Torch);
+        assert_eq!(room.name(), Some("Modded Hall"));
+        let fallback = GridRoom::from_data("size=2,2\nentrance=0,0\n");
+        assert_eq!((fallback.tiles[0].len(), fallback.entrance), (20, (1, 1)));
+    }
+
+    #[test]
+    fn room_data_places_torches_and_furniture() {
+        let mut room = GridRoom::empty(8, 6);
+        room.apply_data("name=Test Room\ndarkness=0.8\ndepth=2\noutdoor=true\ntorch=0,2\n# floor tiles can't hold torches\ntorch=3,3\nsign=2,2:Hello|there\nsign=0,0:on a wall\ncupboard=4,2:potion\n", &Props::default());
+        assert_eq!(room.darkness(), 0.8);
+        assert_eq!(room.depth(), 2);
+        assert!(room.outdoor());
+        assert_eq!(room.name(), Some("Test Room"));
+        assert!(room.tiles[2][0] == Tile::Torch);
+        assert!(room.tiles[3][3] == Tile::Floor);
+        assert_eq!(room.lights(0.0).len(), 1);
+        assert_eq!(room.interaction(2, 2), Some(Interaction::Read("Hello|there".to_string())));
+        assert_eq!(room.interaction(0, 0), None);
+        assert_eq!(room.interaction(4, 2), Some(Interaction::Search(Some("potion"))));
+        assert_eq!(room.interaction(4, 2), Some(Interaction::Search(None)), "cupboards empty out");
+    }
+
+    #[test]
+    fn caves_are_dark_unless_their_data_says_otherwise() {
+        let mut cave = GridRoom::empty(8, 6);
+        cave.apply_data("style=cave\n", &Props::default());
+        assert_eq!((cave.style, cave.darkness()), (RoomStyle::Cave, CAVE_DARKNESS));
+        let mut lit = GridRoom::empty(8, 6);
+        lit.apply_data("darkness=0.3\nstyle=cave\n", &Props::default());
+        assert_eq!(lit.darkness(), 0.3);
+        let mut plain = GridRoom::empty(8, 6);
+        plain.apply_data("style=marble\n", &Props::default());
+        assert_eq!((plain.style, plain.darkness()), (RoomStyle::Interior, 0.0));
+        let kinds = TileRegistry::builtin();
+        assert!(kinds.position("rubble").is_some() && kinds.position("glowshroom").is_some());
+    }
+
+    #[test]
+    fn every_builtin_tile_has_tile_data() {
+        let kinds = TileRegistry::builtin();
+        for tile in BUILTIN_TILES {
+            assert!(kinds.position(tile.id()).is_some(), "{} is missing from tiles.ron", tile.id());
+        }
+    }
+
+    #[test]
+    fn tiles_added_in_tile_data_are_placed_by_id() {
+        let mut room = GridRoom::empty(8, 6);
+        room.set_tile_kinds(Arc::new(TileRegistry::from_text(r#"[(id: "plinth", solid: Solid, interaction: Read, footstep: Grass, light: 2.0)]"#)));
+        room.apply_data("tile=2,2:plinth:Here lies no one.
+tile=3,3:chest
+tile=4,4:nothing
+tile=0,0:plinth
+", &Props::default());
+        assert!(matches!(room.tiles[2][2], Tile::Custom(_)));
+        assert!(room.tiles[3][3] == Tile::Floor && room.tiles[4][4] == Tile::Floor, "built-in and unknown ids aren't placed");
+        assert!(room.tiles[0][0] == Tile::Wall);
+        assert!(room.is_solid_at_point(2.5 * TILE_SIZE, 2.5 * TILE_SIZE));
+        assert_eq!(room.lights(0.0).len(), 1);
+        assert_eq!(room.interaction(2, 2), Some(Interaction::Read("Here lies no one.".to_string())));
+        assert!(room.can_interact_tile(2, 2, 2, 3));
+    }
+
+    #[test]
+    fn props_are_only_entered_and_left_across_entry_sides() {
+        let props = Props::from_text("[bed]\nrows=..,..,##\nentry=east\nsave=true\n");
+        let mut room = GridRoom::empty(8, 8);
+        room.apply_data("prop=1,3:bed\n# overlaps the wall\nprop=0,0:bed\n", &props);
+        assert!(room.tiles[5][1] == Tile::Fwall && room.tiles[4][2] == Tile::Floor);
+        assert_eq!(room.props.len(), 1);
+        assert!(room.is_save_point(2, 4));
+        assert!(!room.is_save_point(2, 5));
+        let t = TILE_SIZE;
+        let s = t / 2.0;
+        // in from the east, and back out the same way
+        assert!(room.is_movement_allowed(3.0 * t, 4.0 * t, 3.0 * t - 1.0, 4.0 * t, s, s));
+        assert!(room.is_movement_allowed(2.0 * t, 4.0 * t, 3.0 * t, 4.0 * t, s, s));
+        // not from the north, and not off the west column
+        assert!(!room.is_movement_allowed(1.0 * t, 3.0 * t - 1.0, 1.0 * t, 3.0 * t, s, s));
+        assert!(!room.is_movement_allowed(2.0 * t, 3.0 * t, 2.0 * t, 3.0 * t - 1.0, s, s));
+        assert!(room.is_movement_allowed(1.0 * t, 3.0 * t, 2.0 * t, 3.0 * t, s, s), "moving about on the bed is fine");
+        assert!(room.describe_tile(2, 4).contains(&"on prop: bed".to_string()));
+        assert_eq!(room.describe_tile(0, 0)[1], "solid: Solid");
+        assert!(room.describe_tile(9, 9).is_empty());
+        assert_eq!(room.prop_cells(), vec![((1, 3), vec![]), ((2, 3), vec![Side::East]), ((1, 4), vec![]), ((2, 4), vec![Side::East])]);
+    }
+
+    #[test]
+    fn long_moves_are_swept_and_cant_tunnel() {
+        let mut room = GridRoom::empty(8, 5);
+        // a wall across column 4 with an open door in it
+        for y in 1..4 {
+            room.set_tile(4, y, Tile::Wall);
+        }
+        room.set_tile(4, 2, Tile::DoorOpen);
+        let t = TILE_SIZE;
+        let s = t * 0.9;
+        let inset = (t - s) / 2.0;
+        // a huge frame's worth of movement lands clear of the wall, but passes through it
+        assert!(room.is_rect_free(6.0 * t + inset, t + inset, s, s));
+        assert!(!room.is_movement_allowed(2.0 * t + inset, t + inset, 6.0 * t + inset, t + inset, s, s));
+        // straight through the middle of the door is fine; skimming its frame isn't
+        assert!(room.is_movement_allowed(2.0 * t + inset, 2.0 * t + inset, 6.0 * t + inset, 2.0 * t + inset, s, s));
+        assert!(!room.is_movement_allowed(2.0 * t, 2.0 * t - 2.0, 6.0 * t, 2.0 * t - 2.0, s, s));
+        let points: Vec<_> = super::super::sweep((0.0, 0.0), (2.5, 0.0)).collect();
+        assert_eq!(points.len(), 3);
+        assert!((points[0].0 - 2.5 / 3.0).abs() < 1e-5 && points[2] == (2.5, 0.0));
+        assert_eq!(super::super::sweep((1.0, 1.0), (1.0, 1.0)).collect::<Vec<_>>(), vec![(1.0, 1.0)]);
+    }
+
+    #[test]
+    fn spikes_follow_the_room_timing() {
+        // cellar: 0.8s out, 1.2s in
+        let mut room = GridRoom::cellar();
+        assert!(!room.spikes_up_at(2, 4));
+        assert!(room.update(0.15), "fully out after the rise");
+        assert!(room.spikes_up_at(2, 4));
+        assert!(!room.spikes_up_at(1, 4), "the gap is safe");
+        assert!(!room.update(0.7));
+        assert!(!room.spikes_up_at(2, 4));
+        assert!(!room.update(1.0));
+        assert!(room.update(0.3), "back up once the cycle wraps");
+    }
 }
@@ -1,10 +1,67 @@
 use ggez::{Context, GameResult};
 use ggez::graphics::Canvas;
+use crate::footsteps::Surface;
+use crate::props::Props;
 
 pub const TILE_SIZE: f32 = 32.0;
 
+/// Farthest a rect moves between collision samples when sweeping a move. Thinner than an open
+/// door's frame, the thinnest thing that blocks, so no move can skip over anything.
+pub const SWEEP_STEP: f32 = 1.0;
+
+/// Top-left corners of a rect moved in a straight line from `from` to `to`, no more than
+/// `SWEEP_STEP` apart. `from` itself is left out and the last one is exactly `to`.
+pub fn sweep(from: (f32, f32), to: (f32, f32)) -> impl Iterator<Item = (f32, f32)> {
+    let (dx, dy) = (to.0 - from.0, to.1 - from.1);
+    let steps = (dx.abs().max(dy.abs()) / SWEEP_STEP).ceil().max(1.0) as usize;
+    (1..=steps).map(move |i| if i == steps { to } else {
+        let t = i as f32 / steps as f32;
+        (from.0 + dx * t, from.1 + dy * t)
+    })
+}
+
 pub mod grid_room;
+pub mod outdoor_room;
 pub use grid_room::GridRoom;
+pub use outdoor_room::OutdoorRoom;
+
+/// How deep the water on a tile is.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Water {
+    /// Wadeable, but slows you down.
+    Shallow,
+    /// Impassable without a way to swim.
+    Deep,
+}
+
+/// How long a room's spike traps stay extended and retracted, in seconds. Every spike in a
+/// room shares one cycle.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpikeTiming {
+    pub extended: f32,
+    pub retracted: f32,
+}
+
+impl Default for SpikeTiming {
+    fn default() -> SpikeTiming {
+        SpikeTiming { extended: 1.0, retracted: 2.0 }
+    }
+}
+
+/// What interacting with a piece of furniture does (see `Room::interaction`).
+#[derive(Clone, Debug, PartialEq)]
+pub enum Interaction {
+    /// Signs and bookshelves: show this text in the dialogue box.
+    Read(String),
+    /// Chairs: sit down on the tile.
+    Sit,
+    /// Cupboards: whatever was inside (None once it's been emptied).
+    Search(Option<crate::items::ItemId>),
+    /// Campfires: leave the companion here, or collect it.
+    Camp,
+    /// Waypoint shrines: activate this one (by name) and open the travel menu.
+    Shrine(String),
+}
 
 /// Room trait: encapsulates a game screen / map area.
 pub trait Room {
@@ -16,6 +73,68 @@ pub trait Room {
     fn height_pixels(&self) -> usize;
     fn interact_tile(&mut self, tx: usize, ty: usize) -> bool;
     fn can_interact_tile(&self, tx: usize, ty: usize, player_tx: usize, player_ty: usize) -> bool;
-    /// Check if movement from (from_x, from_y) to (to_x, to_y) is allowed, considering special rules like bed movement
+    /// Check if movement from (from_x, from_y) to (to_x, to_y) is allowed, considering special rules like bed movement.
+    /// The rect is swept along the whole way (see `sweep`), so a long move can't skip a wall.
     fn is_movement_allowed(&self, from_x: f32, from_y: f32, to_x: f32, to_y: f32, w: f32, h: f32) -> bool;
+    /// Loot a closed chest, returning the gold inside. None if there's no unopened chest here.
+    fn open_chest(&mut self, tx: usize, ty: usize) -> Option<u32>;
+    /// True if interacting while standing on this tile saves the game (e.g. the bed).
+    fn is_save_point(&self, tx: usize, ty: usize) -> bool;
+    fn is_door_open(&self, tx: usize, ty: usize) -> bool;
+    /// Shove the pushable block at (tx, ty) one tile along (dx, dy). Returns false if there is
+    /// no block there or the destination is blocked.
+    fn push_block(&mut self, tx: usize, ty: usize, dx: i32, dy: i32) -> bool;
+    /// True for one-way ledges, which can only be hopped down (south) over.
+    fn is_ledge(&self, tx: usize, ty: usize) -> bool;
+    /// Water covering tile (tx, ty), if any.
+    fn water_at(&self, tx: usize, ty: usize) -> Option<Water>;
+    /// What a footstep on (tx, ty) sounds like; None for tiles that make no footstep of their own.
+    fn surface_at(&self, tx: usize, ty: usize) -> Option<Surface>;
+    /// Advance animated tiles. Returns true on the frame the room's spikes shoot up.
+    fn update(&mut self, dt: f32) -> bool;
+    /// Apply a room data file (`assets/rooms/room<index>.txt`): `name=...`, `music=key`,
+    /// `ambience=key`, `floor=wood|stone|grass`, `style=interior|cave` (caves default to dark),
+    /// `darkness=0..1`, `depth=n`, `outdoor=true`, `torch=x,y`, `sign=x,y:text`,
+    /// `bookshelf=x,y:text`, `chair=x,y`, `campfire=x,y`, `shrine=x,y:name`,
+    /// `cupboard=x,y[:item_id]` and `prop=x,y:name` lines, with prop names looked up in `props`.
+    /// `wild=` and `encounter=` lines are left to the map (see `EncounterTable`), as are
+    /// `exit=` doorways (see `crate::teleport::parse_exits`).
+    fn apply_data(&mut self, text: &str, props: &Props);
+    /// Interact with the furniture at (tx, ty), if there is any. Searching a cupboard empties it.
+    fn interaction(&mut self, tx: usize, ty: usize) -> Option<Interaction>;
+    /// How dark the room is without light, 0 (lit) to 1 (pitch black).
+    fn darkness(&self) -> f32;
+    /// True for rooms under the open sky, which show the season and the weather.
+    fn outdoor(&self) -> bool;
+    /// Display name shown when the player enters, if the room has one.
+    fn name(&self) -> Option<&str>;
+    /// Music track key for the room; None keeps whatever is playing.
+    fn music(&self) -> Option<&str>;
+    /// Ambient loop key for the room; None is quiet.
+    fn ambience(&self) -> Option<&str>;
+    /// Floors below the surface: 0 for the village and the house, 1 for the cellar under it.
+    fn depth(&self) -> u32;
+    /// Light emitters in the room at time `t` (seconds).
+    fn lights(&self, t: f32) -> Vec<crate::lighting::Light>;
+    /// True if (tx, ty) is a hole to fall down.
+    fn is_pit(&self, tx: usize, ty: usize) -> bool;
+    /// Room index that this room's pits drop into, if any.
+    fn room_below(&self) -> Option<usize>;
+    /// Tile the player is put back on after falling down a pit with nothing below.
+    fn entrance(&self) -> (i32, i32);
+    /// True if (tx, ty) is a spike trap that is currently extended.
+    fn spikes_up_at(&self, tx: usize, ty: usize) -> bool;
+    /// Use this tile data (see `crate::tiles`) from now on. Set before `apply_data`, which
+    /// places tiles from it.
+    fn set_tile_kinds(&mut self, kinds: std::sync::Arc<crate::tiles::TileRegistry>);
+    /// Tiles changed in play (doors, chests, blocks, emptied cupboards) as text for the save
+    /// file; empty if nothing has changed.
+    fn changes(&self) -> String;
+    /// Apply `changes` saved from this room to a freshly built copy of it.
+    fn restore_changes(&mut self, text: &str);
+    /// Walkable cells of multi-tile props (the bed), each with the sides it can be stepped on
+    /// or off across; for the collision overlay.
+    fn prop_cells(&self) -> Vec<((usize, usize), Vec<crate::props::Side>)>;
+    /// What's at (tx, ty), a fact per line, for the debug tile tooltip; empty outside the room.
+    fn describe_tile(&self, tx: usize, ty: usize) -> Vec<String>;
 }
@@ -0,0 +1,472 @@
+//! Outdoor rooms: grass, dirt paths, trees, fences and water under the open sky.
+//!
+//! The whole room is laid out in its data file, one `row=` line per row of tiles, after
+//! `type=outdoor`:
+//!
+//! ```text
+//! . grass   , flowers   : dirt path   T tree   # fence
+//! ~ shallow water   = deep water   H house wall   D house door
+//! ```
+//!
+//! Paths, water and fences join up with their neighbours (see `Joins`), so a layout drawn in
+//! plain characters comes out as winding paths, ponds with shores and runs of fencing. Signs go
+//! on top with `sign=x,y:text`, as in indoor rooms.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, Rect};
+use crate::assets::Assets;
+use crate::footsteps::Surface;
+use crate::lighting::Light;
+use crate::props::{Props, Side};
+use crate::tiles::spot_hash;
+use super::{Interaction, TILE_SIZE, Water};
+use super::grid_room::{draw_sign, draw_water};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Ground {
+    Grass,
+    Flowers,
+    Path,
+    Tree,
+    Fence,
+    ShallowWater,
+    DeepWater,
+    HouseWall,
+    HouseDoor,
+    Sign,
+}
+
+impl Ground {
+    fn parse(c: char) -> Option<Ground> {
+        Some(match c {
+            '.' => Ground::Grass,
+            ',' => Ground::Flowers,
+            ':' => Ground::Path,
+            'T' => Ground::Tree,
+            '#' => Ground::Fence,
+            '~' => Ground::ShallowWater,
+            '=' => Ground::DeepWater,
+            'H' => Ground::HouseWall,
+            'D' => Ground::HouseDoor,
+            _ => return None,
+        })
+    }
+
+    fn is_solid(self) -> bool {
+        matches!(self, Ground::Tree | Ground::Fence | Ground::HouseWall | Ground::Sign)
+    }
+
+    fn is_water(self) -> bool {
+        matches!(self, Ground::ShallowWater | Ground::DeepWater)
+    }
+}
+
+/// Which of a tile's four neighbours belong with it, as N, E, S, W bits; what the autotiling
+/// draws from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Joins(u8);
+
+impl Joins {
+    const N: u8 = 1;
+    const E: u8 = 2;
+    const S: u8 = 4;
+    const W: u8 = 8;
+
+    fn has(self, side: u8) -> bool {
+        self.0 & side != 0
+    }
+}
+
+pub struct OutdoorRoom {
+    tiles: Vec<Vec<Ground>>,
+    entrance: (i32, i32),
+    name: Option<String>,
+    music: Option<String>,
+    ambience: Option<String>,
+    /// Text of each sign, from room data.
+    texts: Vec<((usize, usize), String)>,
+    /// Seconds since the room was built, for the water.
+    clock: f32,
+}
+
+impl OutdoorRoom {
+    /// Build the room from its data file's `row=` lines; rows are padded with grass to the
+    /// widest one. `entrance=x,y` is where fallers and newcomers land (the middle if unset).
+    pub fn from_data(text: &str) -> OutdoorRoom {
+        let rows: Vec<&str> = text.lines().filter_map(|line| line.split_once('=').filter(|(key, _)| key.trim() == "row").map(|(_, row)| row.trim())).collect();
+        let width = rows.iter().map(|row| row.chars().count()).max().unwrap_or(0).max(3);
+        let mut tiles: Vec<Vec<Ground>> = rows.iter().map(|row| {
+            let mut tiles: Vec<Ground> = row.chars().map(|c| Ground::parse(c).unwrap_or_else(|| {
+                println!("OutdoorRoom: unknown tile {:?}, using grass", c);
+                Ground::Grass
+            })).collect();
+            tiles.resize(width, Ground::Grass);
+            tiles
+        }).collect();
+        while tiles.len() < 3 {
+            tiles.push(vec![Ground::Grass; width]);
+        }
+        let middle = (width as i32 / 2, tiles.len() as i32 / 2);
+        let entrance = text.lines().find_map(|line| {
+            let (_, value) = line.split_once('=').filter(|(key, _)| key.trim() == "entrance")?;
+            let (x, y) = value.split_once(',')?;
+            Some((x.trim().parse().ok()?, y.trim().parse().ok()?))
+        }).unwrap_or(middle);
+        OutdoorRoom { tiles, entrance, name: None, music: None, ambience: None, texts: Vec::new(), clock: 0.0 }
+    }
+
+    fn at(&self, x: i32, y: i32) -> Option<Ground> {
+        if x < 0 || y < 0 { return None; }
+        self.tiles.get(y as usize)?.get(x as usize).copied()
+    }
+
+    /// Neighbours of (x, y) that `belongs` says go with it; off the edge counts as joined, so
+    /// paths and rivers run off the map rather than stopping short of it.
+    pub fn joins(&self, x: usize, y: usize, belongs: impl Fn(Ground) -> bool) -> Joins {
+        let (x, y) = (x as i32, y as i32);
+        let sides = [(0, -1, Joins::N), (1, 0, Joins::E), (0, 1, Joins::S), (-1, 0, Joins::W)];
+        Joins(sides.iter().filter(|&&(dx, dy, _)| self.at(x + dx, y + dy).is_none_or(&belongs)).fold(0, |bits, &(_, _, side)| bits | side))
+    }
+
+    fn draw_tile(&self, ctx: &mut Context, canvas: &mut Canvas, x: usize, y: usize, scale: f32, offset: (f32, f32)) -> GameResult {
+        let cx = offset.0 + (x as f32 + 0.5) * TILE_SIZE * scale;
+        let cy = offset.1 + (y as f32 + 0.5) * TILE_SIZE * scale;
+        let size = TILE_SIZE * scale;
+        let rect = Rect::new(cx - size / 2.0, cy - size / 2.0, size, size);
+        let hash = spot_hash(x, y);
+        let ground = self.tiles[y][x];
+        // everything but water and walls stands on grass
+        if !ground.is_water() && ground != Ground::HouseWall {
+            let shade = 1.0 - (hash >> 12 & 7) as f32 * 0.02;
+            canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.3 * shade, 0.58 * shade, 0.25 * shade, 1.0))?, DrawParam::new());
+            if hash & 3 == 0 {
+                let tuft_x = rect.x + (8.0 + (hash >> 4 & 15) as f32) * scale;
+                let tuft_y = rect.y + (8.0 + (hash >> 8 & 15) as f32) * scale;
+                for lean in [-2.0, 0.0, 2.0] {
+                    let blade = [[tuft_x, tuft_y], [tuft_x + lean * scale, tuft_y - 4.0 * scale]];
+                    canvas.draw(&Mesh::new_line(ctx, &blade, 1.0 * scale, Color::new(0.2, 0.45, 0.15, 1.0))?, DrawParam::new());
+                }
+            }
+        }
+        match ground {
+            Ground::Grass => {}
+            Ground::Flowers => {
+                let colors = [Color::new(1.0, 0.85, 0.3, 1.0), Color::new(0.95, 0.45, 0.6, 1.0), Color::WHITE];
+                for i in 0..3 {
+                    let fx = rect.x + (6.0 + (hash >> (i * 5) & 19) as f32) * scale;
+                    let fy = rect.y + (6.0 + (hash >> (i * 5 + 3) & 19) as f32) * scale;
+                    canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [fx, fy], 2.0 * scale, 0.5, colors[(hash >> (i + 20)) as usize % 3])?, DrawParam::new());
+                }
+            }
+            Ground::Path => {
+                // a square of dirt in the middle, with an arm out to every path beside it
+                let dirt = Color::new(0.6, 0.46, 0.3, 1.0);
+                let joins = self.joins(x, y, |g| matches!(g, Ground::Path | Ground::HouseDoor));
+                let inset = 5.0 * scale;
+                let core = Rect::new(rect.x + inset, rect.y + inset, size - inset * 2.0, size - inset * 2.0);
+                canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), core, dirt)?, DrawParam::new());
+                let arms = [
+                    (Joins::N, Rect::new(core.x, rect.y, core.w, inset)),
+                    (Joins::E, Rect::new(core.right(), core.y, inset, core.h)),
+                    (Joins::S, Rect::new(core.x, core.bottom(), core.w, inset)),
+                    (Joins::W, Rect::new(rect.x, core.y, inset, core.h)),
+                ];
+                for (side, arm) in arms {
+                    if joins.has(side) {
+                        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), arm, dirt)?, DrawParam::new());
+                    }
+                }
+                if hash & 7 == 1 {
+                    let pebble = [rect.x + (10.0 + (hash >> 5 & 11) as f32) * scale, rect.y + (10.0 + (hash >> 9 & 11) as f32) * scale];
+                    canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), pebble, 1.5 * scale, 0.5, Color::new(0.45, 0.4, 0.35, 1.0))?, DrawParam::new());
+                }
+            }
+            Ground::Tree => {
+                let trunk = Rect::new(cx - 3.0 * scale, cy + 2.0 * scale, 6.0 * scale, 12.0 * scale);
+                canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), trunk, Color::new(0.4, 0.25, 0.12, 1.0))?, DrawParam::new());
+                let canopy = 0.9 + (hash >> 3 & 3) as f32 * 0.05;
+                canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy - 3.0 * scale], 13.0 * scale * canopy, 0.5, Color::new(0.12, 0.4, 0.16, 1.0))?, DrawParam::new());
+                canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [cx - 3.0 * scale, cy - 6.0 * scale], 7.0 * scale * canopy, 0.5, Color::new(0.2, 0.52, 0.22, 1.0))?, DrawParam::new());
+            }
+            Ground::Fence => {
+                // rails run to the fences beside this one; a lone post gets a short stub
+                let wood = Color::new(0.62, 0.45, 0.28, 1.0);
+                let joins = self.joins(x, y, |g| g == Ground::Fence);
+                let joins = if joins.0 == 0 { Joins(Joins::E | Joins::W) } else { joins };
+                let half = size / 2.0;
+                let ends = [(Joins::N, [cx, cy - half]), (Joins::E, [cx + half, cy]), (Joins::S, [cx, cy + half]), (Joins::W, [cx - half, cy])];
+                for (side, end) in ends {
+                    if joins.has(side) {
+                        for lift in [-4.0, 2.0] {
+                            let rail = [[cx, cy + lift * scale], [end[0], end[1] + lift * scale]];
+                            canvas.draw(&Mesh::new_line(ctx, &rail, 2.5 * scale, wood)?, DrawParam::new());
+                        }
+                    }
+                }
+                let post = Rect::new(cx - 3.0 * scale, cy - 9.0 * scale, 6.0 * scale, 16.0 * scale);
+                canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), post, Color::new(0.5, 0.35, 0.2, 1.0))?, DrawParam::new());
+            }
+            Ground::ShallowWater | Ground::DeepWater => {
+                draw_water(ctx, canvas, cx, cy, scale, self.clock + (x + y) as f32 * 0.7, ground == Ground::DeepWater)?;
+                // a sandy shore along every side that meets land
+                let joins = self.joins(x, y, Ground::is_water);
+                let bank = 4.0 * scale;
+                let shores = [
+                    (Joins::N, Rect::new(rect.x, rect.y, size, bank)),
+                    (Joins::E, Rect::new(rect.right() - bank, rect.y, bank, size)),
+                    (Joins::S, Rect::new(rect.x, rect.bottom() - bank, size, bank)),
+                    (Joins::W, Rect::new(rect.x, rect.y, bank, size)),
+                ];
+                for (side, shore) in shores {
+                    if !joins.has(side) {
+                        canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), shore, Color::new(0.82, 0.74, 0.5, 1.0))?, DrawParam::new());
+                    }
+                }
+            }
+            Ground::HouseWall | Ground::HouseDoor => {
+                canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), rect, Color::new(0.55, 0.36, 0.2, 1.0))?, DrawParam::new());
+                for i in 1..4 {
+                    let seam = rect.y + size * i as f32 / 4.0;
+                    canvas.draw(&Mesh::new_line(ctx, &[[rect.x, seam], [rect.right(), seam]], 1.0 * scale, Color::new(0.38, 0.24, 0.12, 1.0))?, DrawParam::new());
+                }
+                // the roof's edge along the top of the house
+                if !self.joins(x, y, |g| matches!(g, Ground::HouseWall | Ground::HouseDoor)).has(Joins::N) {
+                    let eave = Rect::new(rect.x, rect.y, size, 8.0 * scale);
+                    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), eave, Color::new(0.55, 0.18, 0.14, 1.0))?, DrawParam::new());
+                }
+                if ground == Ground::HouseDoor {
+                    let door = Rect::new(cx - 9.0 * scale, cy - 10.0 * scale, 18.0 * scale, 26.0 * scale);
+                    canvas.draw(&Mesh::new_rectangle(ctx, DrawMode::fill(), door, Color::new(0.25, 0.14, 0.06, 1.0))?, DrawParam::new());
+                    canvas.draw(&Mesh::new_circle(ctx, DrawMode::fill(), [cx + 5.0 * scale, cy + 3.0 * scale], 1.5 * scale, 0.5, Color::new(0.9, 0.75, 0.3, 1.0))?, DrawParam::new());
+                }
+            }
+            Ground::Sign => draw_sign(ctx, canvas, cx, cy, scale)?,
+        }
+        Ok(())
+    }
+}
+
+impl super::Room for OutdoorRoom {
+    fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, _assets: &Assets, scale: f32, offset: (f32, f32)) -> GameResult {
+        for y in 0..self.tiles.len() {
+            for x in 0..self.tiles[y].len() {
+                self.draw_tile(ctx, canvas, x, y, scale, offset)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn is_solid_at_point(&self, x: f32, y: f32) -> bool {
+        !self.is_rect_free(x, y, 1.0, 1.0)
+    }
+
+    fn is_rect_free(&self, x: f32, y: f32, w: f32, h: f32) -> bool {
+        if x < 0.0 || y < 0.0 { return false; }
+        let (left, right) = ((x / TILE_SIZE).floor() as i32, ((x + w) / TILE_SIZE).floor() as i32);
+        let (top, bottom) = ((y / TILE_SIZE).floor() as i32, ((y + h) / TILE_SIZE).floor() as i32);
+        (top..=bottom).all(|ty| (left..=right).all(|tx| self.at(tx, ty).is_some_and(|g| !g.is_solid())))
+    }
+
+    fn width_pixels(&self) -> usize {
+        self.tiles.first().map_or(0, |row| row.len() * TILE_SIZE as usize)
+    }
+
+    fn height_pixels(&self) -> usize {
+        self.tiles.len() * TILE_SIZE as usize
+    }
+
+    fn interact_tile(&mut self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn can_interact_tile(&self, tx: usize, ty: usize, player_tx: usize, player_ty: usize) -> bool {
+        self.at(tx as i32, ty as i32) == Some(Ground::Sign) && tx.abs_diff(player_tx) + ty.abs_diff(player_ty) == 1
+    }
+
+    fn is_movement_allowed(&self, from_x: f32, from_y: f32, to_x: f32, to_y: f32, w: f32, h: f32) -> bool {
+        super::sweep((from_x, from_y), (to_x, to_y)).all(|(x, y)| self.is_rect_free(x, y, w, h))
+    }
+
+    fn open_chest(&mut self, _tx: usize, _ty: usize) -> Option<u32> {
+        None
+    }
+
+    fn is_save_point(&self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn is_door_open(&self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn push_block(&mut self, _tx: usize, _ty: usize, _dx: i32, _dy: i32) -> bool {
+        false
+    }
+
+    fn is_ledge(&self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn water_at(&self, tx: usize, ty: usize) -> Option<Water> {
+        match self.at(tx as i32, ty as i32)? {
+            Ground::ShallowWater => Some(Water::Shallow),
+            Ground::DeepWater => Some(Water::Deep),
+            _ => None,
+        }
+    }
+
+    fn surface_at(&self, tx: usize, ty: usize) -> Option<Surface> {
+        match self.at(tx as i32, ty as i32)? {
+            Ground::Grass | Ground::Flowers => Some(Surface::Grass),
+            Ground::Path => Some(Surface::Stone),
+            Ground::HouseDoor => Some(Surface::Wood),
+            _ => None,
+        }
+    }
+
+    fn update(&mut self, dt: f32) -> bool {
+        self.clock += dt;
+        false
+    }
+
+    fn apply_data(&mut self, text: &str, _props: &Props) {
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let value = value.trim();
+            match key.trim() {
+                "name" => self.name = Some(value.to_string()),
+                "music" => self.music = Some(value.to_string()).filter(|music| !music.is_empty()),
+                "ambience" => self.ambience = Some(value.to_string()).filter(|ambience| !ambience.is_empty()),
+                "sign" => {
+                    let placed = value.split_once(':').and_then(|(at, text)| {
+                        let (x, y) = at.split_once(',')?;
+                        let (x, y) = (x.trim().parse::<usize>().ok()?, y.trim().parse::<usize>().ok()?);
+                        let tile = self.tiles.get_mut(y)?.get_mut(x).filter(|g| !g.is_solid() && !g.is_water())?;
+                        *tile = Ground::Sign;
+                        Some(((x, y), text.to_string()))
+                    });
+                    match placed {
+                        Some(sign) => self.texts.push(sign),
+                        None => println!("OutdoorRoom: can't place {:?}, skipping", line),
+                    }
+                }
+                // the layout was read when the room was built; exits and encounters are the map's
+                "type" | "row" | "entrance" | "exit" | "wild" | "encounter" => {}
+                _ => println!("OutdoorRoom: unknown setting {:?}, skipping", line),
+            }
+        }
+    }
+
+    fn interaction(&mut self, tx: usize, ty: usize) -> Option<Interaction> {
+        let text = self.texts.iter().find(|(at, _)| *at == (tx, ty)).map(|(_, text)| text.clone())?;
+        Some(Interaction::Read(text))
+    }
+
+    fn darkness(&self) -> f32 {
+        0.0
+    }
+
+    fn outdoor(&self) -> bool {
+        true
+    }
+
+    fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    fn music(&self) -> Option<&str> {
+        self.music.as_deref()
+    }
+
+    fn ambience(&self) -> Option<&str> {
+        self.ambience.as_deref()
+    }
+
+    fn depth(&self) -> u32 {
+        0
+    }
+
+    fn lights(&self, _t: f32) -> Vec<Light> {
+        Vec::new()
+    }
+
+    fn is_pit(&self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn room_below(&self) -> Option<usize> {
+        None
+    }
+
+    fn entrance(&self) -> (i32, i32) {
+        self.entrance
+    }
+
+    fn spikes_up_at(&self, _tx: usize, _ty: usize) -> bool {
+        false
+    }
+
+    fn set_tile_kinds(&mut self, _kinds: std::sync::Arc<crate::tiles::TileRegistry>) {}
+
+    fn changes(&self) -> String {
+        // nothing outdoors changes in play
+        String::new()
+    }
+
+    fn restore_changes(&mut self, _text: &str) {}
+
+    fn prop_cells(&self) -> Vec<((usize, usize), Vec<Side>)> {
+        Vec::new()
+    }
+
+    fn describe_tile(&self, tx: usize, ty: usize) -> Vec<String> {
+        let Some(ground) = self.at(tx as i32, ty as i32) else { return Vec::new() };
+        let mut lines = vec![format!("{:?} (outdoor)", ground), format!("solid: {}", ground.is_solid())];
+        if let Some(surface) = self.surface_at(tx, ty) {
+            lines.push(format!("footstep: {:?}", surface));
+        }
+        if let Some((_, text)) = self.texts.iter().find(|(at, _)| *at == (tx, ty)) {
+            lines.push(format!("text: {}", text));
+        }
+        lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rooms::Room;
+
+    #[test]
+    fn builds_from_rows_and_joins_neighbours() {
+        let text = "type=outdoor\nrow=T:T\nrow=#:~\nrow=#.=\nentrance=1,1\nsign=1,2:Welcome to Ordo.\nname=Ordo Village";
+        let mut room = OutdoorRoom::from_data(text);
+        room.apply_data(text, &Props::default());
+        assert_eq!((room.width_pixels(), room.height_pixels()), (3 * TILE_SIZE as usize, 3 * TILE_SIZE as usize));
+        assert_eq!(room.entrance(), (1, 1));
+        assert_eq!(room.name(), Some("Ordo Village"));
+        assert!(room.outdoor());
+
+        // the path runs off the top edge and down to the sign below it
+        assert_eq!(room.joins(1, 1, |g| g == Ground::Path), Joins(Joins::N));
+        assert_eq!(room.joins(0, 1, |g| g == Ground::Fence), Joins(Joins::S | Joins::W));
+        assert_eq!(room.joins(2, 1, Ground::is_water), Joins(Joins::E | Joins::S));
+
+        assert!(!room.is_rect_free(0.0, 0.0, 4.0, 4.0), "trees are solid");
+        assert!(room.is_rect_free(TILE_SIZE + 4.0, 4.0, 8.0, 8.0));
+        assert_eq!(room.water_at(2, 2), Some(Water::Deep));
+        assert_eq!(room.surface_at(1, 0), Some(Surface::Stone));
+        assert!(room.can_interact_tile(1, 2, 1, 1));
+        assert_eq!(room.interaction(1, 2), Some(Interaction::Read("Welcome to Ordo.".to_string())));
+
+        // the shipped village: its front door leads home, and the way out of the house lands
+        // on the path just below it
+        let text = include_str!("../../assets/rooms/room3.txt");
+        let village = OutdoorRoom::from_data(text);
+        let exits = crate::teleport::parse_exits(text);
+        let door = exits[0].0;
+        assert_eq!(village.at(door.0, door.1), Some(Ground::HouseDoor));
+        assert_eq!(village.at(village.entrance().0, village.entrance().1), Some(Ground::Path));
+        assert!(crate::teleport::parse_exits(include_str!("../../assets/rooms/room0.txt")).iter().any(|(_, to)| to.room == 3 && to.tile == village.entrance()));
+    }
+}
@@ -0,0 +1,101 @@
+//! Controller vibration for hits, roars and heavy interactions.
+//!
+//! ggez keeps its gamepad handle to itself, so force feedback goes through a `Gilrs` of our
+//! own. Platforms or pads without force feedback just do nothing. How strong (and whether) a
+//! rumble plays is set in the Accessibility options.
+
+use ggez::input::gamepad::gilrs::{self, Gilrs};
+use gilrs::ff::{BaseEffect, BaseEffectType, EffectBuilder, Effect, Repeat, Replay, Ticks};
+
+/// Strengths the options step through.
+pub const STRENGTHS: [f32; 4] = [0.25, 0.5, 0.75, 1.0];
+/// Default for `Options::rumble_strength`.
+pub const DEFAULT_STRENGTH: f32 = 0.75;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Kind {
+    /// The player took a hit.
+    Hit,
+    /// A big foe making itself known.
+    Roar,
+    /// Shoving a block, hauling a chest open, landing from a fall.
+    Heavy,
+}
+
+impl Kind {
+    /// Motor strength at full intensity, 0 to 1, and how long it lasts.
+    fn feel(self) -> (f32, u32) {
+        match self {
+            Kind::Hit => (0.7, 180),
+            Kind::Roar => (1.0, 700),
+            Kind::Heavy => (0.45, 250),
+        }
+    }
+
+    /// Strong-motor magnitude at `intensity` (0 to 1).
+    pub fn magnitude(self, intensity: f32) -> u16 {
+        (self.feel().0 * intensity.clamp(0.0, 1.0) * u16::MAX as f32) as u16
+    }
+}
+
+pub struct Rumble {
+    gilrs: Option<Gilrs>,
+    /// Effects still playing, with seconds left; dropping an effect ends it.
+    playing: Vec<(Effect, f32)>,
+}
+
+impl Rumble {
+    pub fn new() -> Rumble {
+        let gilrs = match Gilrs::new() {
+            Ok(gilrs) => Some(gilrs),
+            Err(e) => {
+                println!("Rumble: no force feedback: {}", e);
+                None
+            }
+        };
+        Rumble { gilrs, playing: Vec::new() }
+    }
+
+    /// Keep the list of pads current and let finished effects go.
+    pub fn update(&mut self, dt: f32) {
+        if let Some(gilrs) = &mut self.gilrs {
+            while gilrs.next_event().is_some() {}
+        }
+        for (_, left) in &mut self.playing {
+            *left -= dt;
+        }
+        self.playing.retain(|(_, left)| *left > 0.0);
+    }
+
+    /// Rumble every connected pad that can, at `intensity` (0 to 1; 0 plays nothing).
+    pub fn play(&mut self, kind: Kind, intensity: f32) {
+        let Some(gilrs) = &mut self.gilrs else { return };
+        let magnitude = kind.magnitude(intensity);
+        let pads: Vec<_> = gilrs.gamepads().filter(|(_, pad)| pad.is_ff_supported()).map(|(id, _)| id).collect();
+        if magnitude == 0 || pads.is_empty() { return; }
+        let millis = kind.feel().1;
+        let effect = EffectBuilder::new()
+            .add_effect(BaseEffect { kind: BaseEffectType::Strong { magnitude }, scheduling: Replay { play_for: Ticks::from_ms(millis), ..Default::default() }, ..Default::default() })
+            .repeat(Repeat::For(Ticks::from_ms(millis)))
+            .gamepads(&pads)
+            .finish(gilrs);
+        match effect.and_then(|effect| effect.play().map(|_| effect)) {
+            Ok(effect) => self.playing.push((effect, millis as f32 / 1000.0)),
+            Err(e) => println!("Rumble: couldn't play {:?}: {}", kind, e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn magnitude_follows_kind_and_intensity() {
+        assert_eq!(Kind::Roar.magnitude(1.0), u16::MAX);
+        assert_eq!(Kind::Hit.magnitude(0.0), 0);
+        assert!(Kind::Heavy.magnitude(1.0) < Kind::Hit.magnitude(1.0));
+        assert!(Kind::Hit.magnitude(0.5) < Kind::Hit.magnitude(1.0));
+        assert_eq!(Kind::Roar.magnitude(3.0), u16::MAX, "intensity is capped");
+    }
+}
@@ -0,0 +1,583 @@
+//! Save slots on disk.
+//!
+//! Each slot is a small UTF-8 `key=value` file (one entry per line, unknown keys are ignored)
+//! so it stays readable and easy to extend. The first line gives the format version; saves from
+//! an older version are upgraded as they're read (see `MIGRATIONS`). Saves are written to a
+//! temporary file and renamed into place, and the save they replace is kept as a `.bak`, which
+//! the slot screen offers to restore if the save itself won't read.
+//!
+//! Files live in the active player profile's folder (see `crate::profiles`) under the
+//! platform's data directory: `~/.local/share/tale-the-rpg` on Linux,
+//! `%APPDATA%\TALE-the-rpg\data` on Windows, `~/Library/Application Support/TALE-the-rpg` on
+//! macOS. Settings go to the matching config directory. Either way the game finds its files
+//! whatever folder it's launched from.
+
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use crate::exploration::Exploration;
+use crate::game_vars::GameVars;
+use crate::items::{self, Equipment, Inventory, ItemKind};
+use crate::waypoints::Waypoints;
+
+pub const SAVE_SLOT_COUNT: usize = 3;
+/// Where older versions kept player files, in the working directory. Also the fallback root on
+/// platforms without a data directory.
+pub const SAVE_DIR: &str = "saves";
+/// Folder name under the platform directories.
+const APP_NAME: &str = "TALE-the-rpg";
+
+/// Save format written by this build. Bump it when a change would make older saves load wrongly
+/// (a key renamed, a value that means something new) and add the step upgrading them to
+/// `MIGRATIONS`. New keys with a sensible default don't need a bump.
+pub const FORMAT_VERSION: u32 = 2;
+
+/// A save's entries in file order, before they're interpreted.
+type Fields = Vec<(String, String)>;
+
+/// Upgrade steps: `MIGRATIONS[i]` turns a version `i + 1` save into a version `i + 2` one.
+const MIGRATIONS: [fn(&mut Fields); FORMAT_VERSION as usize - 1] = [
+    // 1 -> 2: saves from before the version line; their entries read the same
+    |_| {},
+];
+
+/// Folder name of the active profile under the data and config roots. Process-wide, like the
+/// palette settings, so everything that reads or writes player files follows a profile switch.
+static PROFILE_DIR: Mutex<Option<String>> = Mutex::new(None);
+
+/// Point every save, options and achievement file at a profile's folder.
+pub fn set_profile_dir(dir: Option<&str>) {
+    *PROFILE_DIR.lock().unwrap_or_else(|e| e.into_inner()) = dir.map(str::to_string);
+}
+
+/// Root for saves, profiles and achievements.
+pub fn data_root() -> PathBuf {
+    crate::platform::user_dirs(APP_NAME).map_or_else(|| PathBuf::from(SAVE_DIR), |(data, _)| data)
+}
+
+/// Root for settings.
+pub fn config_root() -> PathBuf {
+    crate::platform::user_dirs(APP_NAME).map_or_else(|| PathBuf::from(SAVE_DIR), |(_, config)| config)
+}
+
+/// The active profile's folder under `root`.
+fn profile_dir(root: PathBuf) -> PathBuf {
+    let profile = PROFILE_DIR.lock().unwrap_or_else(|e| e.into_inner());
+    match profile.as_deref() {
+        Some(profile) => root.join(profile),
+        None => root,
+    }
+}
+
+/// Folder the active profile's saves and achievements live in.
+pub fn dir() -> PathBuf {
+    profile_dir(data_root())
+}
+
+/// Folder the active profile's settings live in.
+pub fn config_dir() -> PathBuf {
+    profile_dir(config_root())
+}
+
+/// Move a `saves/` folder left in the working directory by an older version to the data root,
+/// with every `options.txt` in it going to the same place under the config root. Only done
+/// while the data root doesn't exist yet, so it never overwrites anything.
+pub fn adopt_working_dir_saves() {
+    let (old, root) = (PathBuf::from(SAVE_DIR), data_root());
+    if !old.is_dir() || root.exists() || root == old { return; }
+    let moved = root.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| move_dir(&old, &root));
+    if let Err(e) = moved {
+        println!("Save: failed to move {} to {:?}: {}", SAVE_DIR, root, e);
+        return;
+    }
+    println!("Save: moved {} to {:?}", SAVE_DIR, root);
+    let folders = fs::read_dir(&root).into_iter().flatten().flatten().map(|entry| entry.path()).filter(|path| path.is_dir());
+    for folder in std::iter::once(root.clone()).chain(folders) {
+        let relative = folder.strip_prefix(&root).unwrap_or(Path::new(""));
+        move_file(&folder.join("options.txt"), &config_root().join(relative).join("options.txt"));
+    }
+}
+
+/// Move a folder and everything in it to `to`, which mustn't exist yet.
+fn move_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    // rename can't cross drives, so copy and remove instead
+    fs::rename(from, to).or_else(|_| copy_dir(from, to).and_then(|_| fs::remove_dir_all(from)))
+}
+
+fn copy_dir(from: &Path, to: &Path) -> std::io::Result<()> {
+    fs::create_dir_all(to)?;
+    for entry in fs::read_dir(from)? {
+        let path = entry?.path();
+        let Some(name) = path.file_name() else { continue };
+        if path.is_dir() {
+            copy_dir(&path, &to.join(name))?;
+        } else {
+            fs::copy(&path, to.join(name))?;
+        }
+    }
+    Ok(())
+}
+
+fn move_file(from: &Path, to: &Path) {
+    if !from.is_file() { return; }
+    // rename can't cross drives, so copy and remove instead
+    let moved = to.parent().map_or(Ok(()), fs::create_dir_all).and_then(|_| fs::copy(from, to)).and_then(|_| fs::remove_file(from));
+    if let Err(e) = moved {
+        println!("Save: failed to move {:?}: {}", from, e);
+    }
+}
+
+/// Knobs used when building a world from a seed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GenerationOptions {
+    pub room_width: usize,
+    pub room_height: usize,
+    pub enemy_count: usize,
+}
+
+impl Default for GenerationOptions {
+    fn default() -> GenerationOptions {
+        GenerationOptions { room_width: 20, room_height: 15, enemy_count: 0 }
+    }
+}
+
+impl GenerationOptions {
+    pub const MAX_ENEMIES: usize = 5;
+    /// Room sizes a world can be built with, in tiles: big enough for the walls, door, bed and
+    /// furniture the starting room is given.
+    pub const ROOM_SIZES: std::ops::RangeInclusive<usize> = 8..=100;
+
+    /// Whether a world can be built with these rooms.
+    pub fn fits(&self) -> bool {
+        Self::ROOM_SIZES.contains(&self.room_width) && Self::ROOM_SIZES.contains(&self.room_height)
+    }
+
+    /// Short one-line description for the save slot screen.
+    pub fn summary(&self) -> String {
+        format!("{}x{} rooms, {} foes", self.room_width, self.room_height, self.enemy_count)
+    }
+}
+
+/// Everything we persist about a save slot.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SaveMetadata {
+    pub seed: u64,
+    pub depth_reached: u32,
+    pub generation: GenerationOptions,
+    // player progress
+    pub level: u32,
+    pub xp: u32,
+    pub inventory: Inventory,
+    pub equipment: Equipment,
+    pub gold: u32,
+    /// Room and tile where the companion was left by a campfire; None while it follows.
+    pub companion_camp: Option<(usize, (i32, i32))>,
+    /// One record per ally in the party (see `crate::party`), in marching order.
+    pub party: Vec<String>,
+    /// Tiles seen in each room (fog of war).
+    pub explored: Exploration,
+    /// Shrines activated for fast travel.
+    pub waypoints: Waypoints,
+    /// Tiles changed in play in each room that has any, as `crate::rooms::Room::changes` text.
+    pub room_states: Vec<(usize, String)>,
+    /// Unique enemies defeated: (room, spawn number).
+    pub defeated: Vec<(usize, u32)>,
+    /// Days since the game began, counted from 1 (see `crate::calendar`).
+    pub day: u32,
+    /// Story flags and variables.
+    pub vars: GameVars,
+    /// Name of the room saved in, for the slot screen; empty for rooms without one.
+    pub location: String,
+    /// Seconds spent in the world over every session.
+    pub playtime: f64,
+    /// One life: the game saves itself on entering a room, and dying ends the run.
+    pub ironman: bool,
+    /// Code of the day this daily run belongs to (see `crate::daily`); empty for other games.
+    pub daily: String,
+}
+
+impl SaveMetadata {
+    pub fn new(seed: u64, generation: GenerationOptions) -> SaveMetadata {
+        SaveMetadata { seed, depth_reached: 0, generation, level: 1, xp: 0, inventory: Inventory::starter(), equipment: Equipment::default(), gold: crate::player::STARTING_GOLD, companion_camp: None, party: Vec::new(), explored: Exploration::default(), waypoints: Waypoints::default(), room_states: Vec::new(), defeated: Vec::new(), day: 1, vars: GameVars::default(), location: String::new(), playtime: 0.0, ironman: false, daily: String::new() }
+    }
+
+    pub fn to_text(&self) -> String {
+        let mut s = String::new();
+        s.push_str(&format!("version={}\n", FORMAT_VERSION));
+        s.push_str(&format!("seed={}\n", self.seed));
+        s.push_str(&format!("depth_reached={}\n", self.depth_reached));
+        s.push_str(&format!("room_width={}\n", self.generation.room_width));
+        s.push_str(&format!("room_height={}\n", self.generation.room_height));
+        s.push_str(&format!("enemy_count={}\n", self.generation.enemy_count));
+        s.push_str(&format!("level={}\n", self.level));
+        s.push_str(&format!("xp={}\n", self.xp));
+        s.push_str(&format!("gold={}\n", self.gold));
+        s.push_str(&format!("inventory={}\n", self.inventory.items.join(",")));
+        s.push_str(&format!("weapon={}\n", self.equipment.weapon.unwrap_or("")));
+        s.push_str(&format!("armor={}\n", self.equipment.armor.unwrap_or("")));
+        s.push_str(&format!("accessory={}\n", self.equipment.accessory.unwrap_or("")));
+        let camp = self.companion_camp.map(|(room, (x, y))| format!("{},{},{}", room, x, y));
+        s.push_str(&format!("companion_camp={}\n", camp.unwrap_or_default()));
+        for ally in &self.party {
+            s.push_str(&format!("ally={}\n", ally));
+        }
+        s.push_str(&format!("explored={}\n", self.explored.to_text()));
+        s.push_str(&format!("waypoints={}\n", self.waypoints.to_text()));
+        for (room, changes) in &self.room_states {
+            s.push_str(&format!("room_state={}:{}\n", room, changes));
+        }
+        let defeated: Vec<String> = self.defeated.iter().map(|(room, id)| format!("{}:{}", room, id)).collect();
+        s.push_str(&format!("defeated={}\n", defeated.join(",")));
+        s.push_str(&format!("day={}\n", self.day));
+        for var in self.vars.to_lines() {
+            s.push_str(&format!("var={}\n", var));
+        }
+        s.push_str(&format!("location={}\n", self.location));
+        s.push_str(&format!("playtime={:.0}\n", self.playtime));
+        s.push_str(&format!("ironman={}\n", self.ironman));
+        s.push_str(&format!("daily={}\n", self.daily));
+        s
+    }
+
+    /// Parse a save file, upgrading it first if an older version wrote it. Returns None if the
+    /// seed is missing or unreadable, or the rooms are a size no world can be built in; other
+    /// missing fields fall back to their defaults.
+    pub fn from_text(text: &str) -> Option<SaveMetadata> {
+        let mut fields = parse_fields(text);
+        // saves from before versioning have no version line
+        let version = fields.iter().find(|(key, _)| key == "version").and_then(|(_, v)| v.parse().ok()).unwrap_or(1);
+        if version > FORMAT_VERSION {
+            println!("Save: written by a newer version (format {}), reading what we can", version);
+        } else if version < FORMAT_VERSION {
+            println!("Save: upgrading from format {} to {}", version, FORMAT_VERSION);
+            migrate(&mut fields, version, &MIGRATIONS);
+        }
+        let mut seed = None;
+        let mut meta = SaveMetadata::new(0, GenerationOptions::default());
+        for (key, value) in &fields {
+            let value = value.as_str();
+            match key.as_str() {
+                "seed" => seed = value.parse().ok(),
+                "depth_reached" => meta.depth_reached = value.parse().unwrap_or(0),
+                "room_width" => if let Ok(v) = value.parse() { meta.generation.room_width = v; },
+                "room_height" => if let Ok(v) = value.parse() { meta.generation.room_height = v; },
+                "enemy_count" => if let Ok(v) = value.parse() { meta.generation.enemy_count = v; },
+                "level" => meta.level = value.parse().unwrap_or(1),
+                "xp" => meta.xp = value.parse().unwrap_or(0),
+                "gold" => meta.gold = value.parse().unwrap_or(0),
+                // unknown item ids (e.g. from a newer version) are dropped
+                "inventory" => meta.inventory.items = value.split(',').filter_map(items::find).map(|d| d.id).collect(),
+                "weapon" => meta.equipment.weapon = items::find_for_slot(value, ItemKind::Weapon),
+                "armor" => meta.equipment.armor = items::find_for_slot(value, ItemKind::Armor),
+                "accessory" => meta.equipment.accessory = items::find_for_slot(value, ItemKind::Accessory),
+                "companion_camp" => meta.companion_camp = parse_camp(value),
+                "ally" => meta.party.push(value.to_string()),
+                "explored" => meta.explored = Exploration::from_text(value),
+                "waypoints" => meta.waypoints = Waypoints::from_text(value),
+                "room_state" => match value.split_once(':').and_then(|(room, changes)| Some((room.parse().ok()?, changes.to_string()))) {
+                    Some(state) => meta.room_states.push(state),
+                    None => println!("Save: can't read room state {:?}, skipping", value),
+                },
+                "defeated" => meta.defeated = value.split(',').filter_map(|entry| {
+                    let (room, id) = entry.split_once(':')?;
+                    Some((room.trim().parse().ok()?, id.trim().parse().ok()?))
+                }).collect(),
+                "day" => meta.day = value.parse().unwrap_or(1),
+                "var" if !meta.vars.read_line(value) => println!("Save: can't read variable {:?}, skipping", value),
+                "location" => meta.location = value.to_string(),
+                "playtime" => meta.playtime = value.parse().unwrap_or(0.0),
+                "ironman" => meta.ironman = value.parse().unwrap_or(false),
+                "daily" => meta.daily = value.to_string(),
+                _ => {}
+            }
+        }
+        if !meta.generation.fits() {
+            println!("Save: can't build {}x{} rooms", meta.generation.room_width, meta.generation.room_height);
+            return None;
+        }
+        meta.seed = seed?;
+        Some(meta)
+    }
+}
+
+fn parse_fields(text: &str) -> Fields {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .collect()
+}
+
+/// Run the `steps` that bring a save written as `version` up to date.
+fn migrate(fields: &mut Fields, version: u32, steps: &[fn(&mut Fields)]) {
+    for step in steps.iter().skip(version.saturating_sub(1) as usize) {
+        step(fields);
+    }
+}
+
+/// "room,x,y"
+fn parse_camp(s: &str) -> Option<(usize, (i32, i32))> {
+    let mut parts = s.split(',').map(str::trim);
+    let room = parts.next()?.parse().ok()?;
+    let x = parts.next()?.parse().ok()?;
+    let y = parts.next()?.parse().ok()?;
+    Some((room, (x, y)))
+}
+
+pub fn slot_path(slot: usize) -> PathBuf {
+    dir().join(format!("slot{}.sav", slot + 1))
+}
+
+/// The save a slot held before its last save, kept in case the newest one is damaged.
+pub fn backup_path(slot: usize) -> PathBuf {
+    dir().join(format!("slot{}.sav.bak", slot + 1))
+}
+
+/// What's on disk for a save slot.
+#[derive(Clone, Debug, PartialEq)]
+pub enum SlotFile {
+    Empty,
+    Saved(SaveMetadata),
+    /// The save won't read, or went missing mid-write; holds the backup if that one reads.
+    Damaged(Option<SaveMetadata>),
+}
+
+fn read_meta(path: &Path) -> Option<SaveMetadata> {
+    SaveMetadata::from_text(&fs::read_to_string(path).ok()?)
+}
+
+pub fn read_slot(slot: usize) -> SlotFile {
+    read_slot_at(&slot_path(slot), &backup_path(slot))
+}
+
+fn read_slot_at(path: &Path, backup: &Path) -> SlotFile {
+    if let Some(meta) = read_meta(path) { return SlotFile::Saved(meta); }
+    let backup = read_meta(backup);
+    if !path.exists() && backup.is_none() { return SlotFile::Empty; }
+    println!("Save: {:?} is damaged{}", path, if backup.is_some() { ", the backup reads fine" } else { " and has no usable backup" });
+    SlotFile::Damaged(backup)
+}
+
+/// Read all slots in order.
+pub fn load_all_slots() -> Vec<SlotFile> {
+    (0..SAVE_SLOT_COUNT).map(read_slot).collect()
+}
+
+/// Put a damaged slot's backup back in its place. The backup itself is kept.
+pub fn restore_backup(slot: usize) -> std::io::Result<()> {
+    restore_at(&slot_path(slot), &backup_path(slot))
+}
+
+fn restore_at(path: &Path, backup: &Path) -> std::io::Result<()> {
+    let temp = path.with_extension("tmp");
+    fs::copy(backup, &temp)?;
+    fs::rename(&temp, path)
+}
+
+/// Screenshot taken when `slot` was last saved (see `crate::save_slots::capture_thumbnail`).
+pub fn thumbnail_path(slot: usize) -> PathBuf {
+    dir().join(format!("slot{}.png", slot + 1))
+}
+
+/// Playtime as hours, minutes and seconds, e.g. "1:02:03".
+pub fn format_playtime(secs: f64) -> String {
+    let secs = secs.max(0.0) as u64;
+    format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+}
+
+/// Folder the saves of finished ironman runs are kept in.
+pub fn fallen_dir() -> PathBuf {
+    dir().join("fallen")
+}
+
+/// End an ironman run: its save moves to `fallen_dir()` (returned), and the slot, with its
+/// backup and screenshot, is left empty.
+pub fn archive_slot(slot: usize) -> std::io::Result<PathBuf> {
+    let stamp = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).map_or(0, |d| d.as_secs());
+    let archived = archive_at(&slot_path(slot), &backup_path(slot), &fallen_dir(), &format!("slot{}-{}.sav", slot + 1, stamp))?;
+    let _ = fs::remove_file(thumbnail_path(slot));
+    Ok(archived)
+}
+
+fn archive_at(path: &Path, backup: &Path, fallen: &Path, name: &str) -> std::io::Result<PathBuf> {
+    fs::create_dir_all(fallen)?;
+    let target = fallen.join(name);
+    fs::rename(path, &target)?;
+    if backup.exists() {
+        fs::remove_file(backup)?;
+    }
+    Ok(target)
+}
+
+pub fn write_slot(slot: usize, meta: &SaveMetadata) -> std::io::Result<()> {
+    fs::create_dir_all(dir())?;
+    write_at(&slot_path(slot), &backup_path(slot), &meta.to_text())
+}
+
+/// Write `text` to a temporary file beside `path` and rename it into place, so a crash can't
+/// leave half a save. The save it replaces becomes `backup`, unless it's damaged itself: a bad
+/// save mustn't push out a good backup.
+fn write_at(path: &Path, backup: &Path, text: &str) -> std::io::Result<()> {
+    let temp = path.with_extension("tmp");
+    let mut file = fs::File::create(&temp)?;
+    file.write_all(text.as_bytes())?;
+    file.sync_all()?;
+    if read_meta(path).is_some() {
+        fs::rename(path, backup)?;
+    }
+    fs::rename(&temp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn metadata_round_trips_through_text() {
+        let mut meta = SaveMetadata::new(987654321, GenerationOptions { enemy_count: 3, ..GenerationOptions::default() });
+        meta.depth_reached = 4;
+        meta.level = 3;
+        meta.xp = 17;
+        meta.equipment.weapon = Some("iron_sword");
+        meta.inventory.items = vec!["rusty_sword"];
+        meta.gold = 123;
+        meta.companion_camp = Some((1, (4, 6)));
+        meta.party = vec!["Bram,2,5,18,rusty_sword,,".to_string(), "Cora,1,0,20,,leather_armor,".to_string()];
+        meta.explored.reveal(1, 12, 9, (3, 3), 2);
+        meta.waypoints.activate(crate::waypoints::Waypoint { name: "Cellar Shrine".to_string(), room: 1, tile: (8, 2) });
+        meta.room_states = vec![(0, "10,0:o 18,13:c".to_string()), (1, "4,4:e".to_string())];
+        meta.defeated = vec![(0, 1), (0, 4)];
+        meta.day = 23;
+        meta.vars.set_flag("met_mira", true);
+        meta.vars.add("prayers", 2);
+        meta.location = "Dusty Cellar".to_string();
+        meta.playtime = 3723.0;
+        meta.ironman = true;
+        assert_eq!(SaveMetadata::from_text(&meta.to_text()), Some(meta));
+    }
+
+    #[test]
+    fn saves_with_rooms_too_small_to_build_are_damaged() {
+        assert_eq!(SaveMetadata::from_text("seed=1\nroom_width=0\nroom_height=0\n"), None);
+        assert_eq!(SaveMetadata::from_text("seed=1\nroom_height=3\n"), None);
+        assert!(SaveMetadata::from_text("seed=1\nroom_width=8\n").is_some());
+
+        let root = std::env::temp_dir().join(format!("tale-tiny-rooms-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let path = root.join("slot1.txt");
+        fs::write(&path, "seed=1\nroom_width=0\nroom_height=0\n").unwrap();
+        assert_eq!(read_slot_at(&path, &root.join("slot1.bak")), SlotFile::Damaged(None));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn equipment_only_loads_into_its_own_slot() {
+        let meta = SaveMetadata::from_text("seed=1\nweapon=chain_mail\narmor=chain_mail\naccessory=potion\n").unwrap();
+        assert_eq!((meta.equipment.weapon, meta.equipment.armor, meta.equipment.accessory), (None, Some("chain_mail"), None));
+    }
+
+    #[test]
+    fn old_saves_are_upgraded_step_by_step() {
+        // written before versioning
+        let old = SaveMetadata::from_text("seed=42\nlevel=3\n").unwrap();
+        assert_eq!((old.seed, old.level), (42, 3));
+        assert!(old.to_text().starts_with(&format!("version={}\n", FORMAT_VERSION)));
+        assert_eq!(SaveMetadata::from_text("version=99\nseed=7\nshiny=new\n").map(|m| m.seed), Some(7), "newer saves load what they can");
+
+        // steps run from the save's own version onwards, in order
+        let steps: [fn(&mut Fields); 2] = [
+            |fields| fields.push(("gold".to_string(), "5".to_string())),
+            |fields| for (key, _) in fields.iter_mut().filter(|(key, _)| key == "lvl") { *key = "level".to_string(); },
+        ];
+        let mut fields = parse_fields("seed=1\nlvl=4\n");
+        migrate(&mut fields, 2, &steps);
+        assert_eq!(fields, vec![("seed".to_string(), "1".to_string()), ("level".to_string(), "4".to_string())]);
+        let mut fields = parse_fields("seed=1\nlvl=4\n");
+        migrate(&mut fields, 1, &steps);
+        assert_eq!(fields.len(), 3);
+    }
+
+    #[test]
+    fn saves_keep_a_backup_to_fall_back_on() {
+        let root = std::env::temp_dir().join(format!("tale-backups-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let (path, backup) = (root.join("slot1.sav"), root.join("slot1.sav.bak"));
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Empty);
+        let first = SaveMetadata::new(1, GenerationOptions::default());
+        let second = SaveMetadata::new(2, GenerationOptions::default());
+        write_at(&path, &backup, &first.to_text()).unwrap();
+        assert!(!backup.exists(), "nothing to back up on the first save");
+        write_at(&path, &backup, &second.to_text()).unwrap();
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Saved(second.clone()));
+        assert_eq!(read_meta(&backup), Some(first.clone()));
+        assert!(!path.with_extension("tmp").exists());
+
+        fs::write(&path, "garbage").unwrap();
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Damaged(Some(first.clone())));
+        // saving over the damaged file leaves the good backup alone
+        write_at(&path, &backup, &second.to_text()).unwrap();
+        assert_eq!(read_meta(&backup), Some(first.clone()));
+        fs::write(&path, "").unwrap();
+        restore_at(&path, &backup).unwrap();
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Saved(first));
+        fs::remove_file(&backup).unwrap();
+        fs::write(&path, "garbage").unwrap();
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Damaged(None));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn fallen_runs_are_archived_not_deleted() {
+        let root = std::env::temp_dir().join(format!("tale-fallen-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let (path, backup) = (root.join("slot2.sav"), root.join("slot2.sav.bak"));
+        let mut run = SaveMetadata::new(5, GenerationOptions::default());
+        run.ironman = true;
+        write_at(&path, &backup, &run.to_text()).unwrap();
+        write_at(&path, &backup, &run.to_text()).unwrap();
+        let archived = archive_at(&path, &backup, &root.join("fallen"), "slot2-1.sav").unwrap();
+        assert_eq!(read_slot_at(&path, &backup), SlotFile::Empty, "no backup left to restore the run from");
+        assert_eq!(read_meta(&archived), Some(run));
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn playtime_reads_as_hours_minutes_and_seconds() {
+        assert_eq!(format_playtime(3723.4), "1:02:03");
+        assert_eq!(format_playtime(59.9), "0:00:59");
+        assert_eq!(format_playtime(-5.0), "0:00:00");
+    }
+
+    #[test]
+    fn moving_settings_creates_the_target_folder() {
+        let root = std::env::temp_dir().join(format!("tale-saves-{}", std::process::id()));
+        let (from, to) = (root.join("old/options.txt"), root.join("config/ada/options.txt"));
+        fs::create_dir_all(from.parent().unwrap()).unwrap();
+        fs::write(&from, "show_fps=true\n").unwrap();
+        move_file(&from, &to);
+        assert!(!from.exists());
+        assert_eq!(fs::read_to_string(&to).unwrap(), "show_fps=true\n");
+        move_file(&from, &to);
+        assert!(to.exists(), "nothing to move leaves the target alone");
+        fs::remove_dir_all(root).unwrap();
+    }
+
+    #[test]
+    fn folders_are_copied_whole_when_they_cant_be_renamed() {
+        let root = std::env::temp_dir().join(format!("tale-adopt-{}", std::process::id()));
+        fs::create_dir_all(root.join("saves/ada")).unwrap();
+        fs::write(root.join("saves/slot1.txt"), "seed=1\n").unwrap();
+        fs::write(root.join("saves/ada/slot2.txt"), "seed=2\n").unwrap();
+        // the copy is what a move across drives falls back to
+        copy_dir(&root.join("saves"), &root.join("copy")).unwrap();
+        assert_eq!(fs::read_to_string(root.join("copy/slot1.txt")).unwrap(), "seed=1\n");
+        assert_eq!(fs::read_to_string(root.join("copy/ada/slot2.txt")).unwrap(), "seed=2\n");
+        move_dir(&root.join("saves"), &root.join("data/tale")).unwrap();
+        assert!(!root.join("saves").exists());
+        assert!(root.join("data/tale/ada/slot2.txt").is_file());
+        fs::remove_dir_all(root).unwrap();
+    }
+}
@@ -0,0 +1,86 @@
+//! Save prompt opened from the bed or a save crystal.
+//!
+//! Shows which slot will be written and a summary of the progress being saved. Up/Down picks
+//! Save or Cancel, Z confirms, C cancels.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+use crate::player::Player;
+
+const ENTRIES: [&str; 2] = ["Save", "Cancel"];
+
+pub enum SaveMenuAction {
+    Save,
+    Close,
+}
+
+pub struct SaveMenu {
+    pub visible: bool,
+    selected: usize,
+}
+
+impl SaveMenu {
+    pub fn new() -> SaveMenu {
+        SaveMenu { visible: false, selected: 0 }
+    }
+
+    pub fn open(&mut self) {
+        self.visible = true;
+        self.selected = 0;
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, slot: Option<usize>, player: &Player) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 360.0;
+        let box_h = 240.0;
+        let left = (w - box_w) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new("Save Game").scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+        let slot_text = match slot {
+            Some(slot) => format!("Slot {}   Lv {}   {} G", slot + 1, player.stats.level, player.gold),
+            None => "No save slot selected".to_string(),
+        };
+        let info = Text::new(TextFragment::new(slot_text).scale(18.0));
+        canvas.draw(&info, DrawParam::new().dest([left + 20.0, top + 60.0]).color(Color::new(0.85, 0.9, 1.0, 1.0)));
+
+        for (i, label) in ENTRIES.iter().enumerate() {
+            let y = top + 110.0 + i as f32 * 40.0;
+            let txt = Text::new(TextFragment::new(*label).scale(24.0));
+            let color = if i == self.selected { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::WHITE };
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(color));
+            if i == self.selected {
+                let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 34.0);
+                let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                canvas.draw(&sel_box, DrawParam::new());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<SaveMenuAction> {
+        if !self.visible { return None; }
+        match key {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); None }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(ENTRIES.len() - 1); None }
+            k if Action::Confirm.matches(k) => {
+                self.visible = false;
+                if self.selected == 0 { Some(SaveMenuAction::Save) } else { Some(SaveMenuAction::Close) }
+            }
+            k if Action::Cancel.matches(k) => { self.visible = false; Some(SaveMenuAction::Close) }
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,261 @@
+//! Save slot selection screen shown between the title and the intro.
+//!
+//! Lists each slot with the character's level, where and for how long they've played, a small
+//! screenshot taken when it was saved, its world seed, deepest dungeon floor and generation
+//! options. A slot whose save won't read offers to restore the backup kept from the save
+//! before. Picking a filled slot continues it; picking an empty one (or pressing N on a filled
+//! one) opens a small form where the player can type a custom seed, tweak generation and choose
+//! an ironman run before starting. D starts today's daily run in an empty slot (see
+//! `crate::daily`); on a filled one it opens the form with today's code typed in, so the save
+//! isn't lost to one key.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Image, ImageFormat, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::clip;
+use crate::daily::{self, DailyLog, Date};
+use crate::input::Action;
+use crate::rng;
+use crate::save::{self, GenerationOptions, SaveMetadata, SlotFile};
+
+/// What the game should do after input on this screen.
+pub enum SlotAction {
+    /// Continue an existing save.
+    Continue(usize, Box<SaveMetadata>),
+    /// Start a new game in the slot. `seed` is None when the player left it blank (random);
+    /// `daily` is the day of a daily run.
+    NewGame { slot: usize, seed: Option<u64>, generation: GenerationOptions, ironman: bool, daily: Option<Date> },
+    Back,
+}
+
+/// New-game form state while the player is typing a seed.
+struct SeedEntry {
+    text: String,
+    generation: GenerationOptions,
+    ironman: bool,
+    // the letter key that opened the form also arrives as a typed character; drop it
+    swallow_next_char: bool,
+}
+
+const MAX_SEED_LEN: usize = 20;
+/// Width of the screenshot kept with each save.
+const THUMBNAIL_WIDTH: u32 = 96;
+
+/// Shrink the finished `frame` and keep it as `slot`'s thumbnail.
+pub fn capture_thumbnail(ctx: &mut Context, frame: &Image, slot: usize) -> GameResult {
+    let (w, h) = clip::fit_width(frame.width(), frame.height(), THUMBNAIL_WIDTH);
+    let small = Image::new_canvas_image(ctx, ImageFormat::Rgba8UnormSrgb, w, h, 1);
+    let Some(pixels) = clip::shrink(ctx, frame, &small)? else { return Ok(()) };
+    if let Err(e) = pixels.save(save::thumbnail_path(slot)) {
+        println!("SaveSlots: couldn't write the thumbnail for slot {}: {}", slot + 1, e);
+    }
+    Ok(())
+}
+
+fn load_thumbnail(ctx: &Context, slot: usize) -> Option<Image> {
+    let pixels = image::open(save::thumbnail_path(slot)).ok()?.to_rgba8();
+    Some(Image::from_pixels(ctx, &pixels, ImageFormat::Rgba8UnormSrgb, pixels.width(), pixels.height()))
+}
+
+pub struct SaveSlotScreen {
+    slots: Vec<SlotFile>,
+    /// Each slot's screenshot, read on the first draw after a refresh.
+    thumbnails: Option<Vec<Option<Image>>>,
+    selected: usize,
+    seed_entry: Option<SeedEntry>,
+    /// Each day's best daily run, for today's line.
+    daily_log: DailyLog,
+}
+
+impl SaveSlotScreen {
+    pub fn new() -> SaveSlotScreen {
+        SaveSlotScreen { slots: save::load_all_slots(), thumbnails: None, selected: 0, seed_entry: None, daily_log: DailyLog::load() }
+    }
+
+    /// Re-read slots from disk (call when entering the screen).
+    pub fn refresh(&mut self) {
+        self.slots = save::load_all_slots();
+        self.thumbnails = None;
+        self.seed_entry = None;
+        self.daily_log = DailyLog::load();
+    }
+
+    /// True while the seed form is open; the game should route typed characters here and
+    /// skip global letter bindings.
+    pub fn is_typing(&self) -> bool {
+        self.seed_entry.is_some()
+    }
+
+    pub fn draw(&mut self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), graphics::Rect::new(0.0, 0.0, w, h), Color::new(0.02, 0.02, 0.05, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+
+        let title = Text::new(TextFragment::new("Select a save slot").scale(32.0));
+        canvas.draw(&title, DrawParam::new().dest([40.0, 30.0]).color(Color::WHITE));
+
+        let box_w = w - 80.0;
+        let box_h = 90.0;
+        let thumbnails = self.thumbnails.get_or_insert_with(|| {
+            self.slots.iter().enumerate().map(|(i, slot)| matches!(slot, SlotFile::Saved(_)).then(|| load_thumbnail(ctx, i)).flatten()).collect()
+        });
+        for (i, slot) in self.slots.iter().enumerate() {
+            let top = 90.0 + i as f32 * (box_h + 16.0);
+            let rect = graphics::Rect::new(40.0, top, box_w, box_h);
+            let fill = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+            canvas.draw(&fill, DrawParam::new());
+            let border_color = if i == self.selected { crate::palette::selection() } else { Color::WHITE };
+            let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), rect, border_color)?;
+            canvas.draw(&border, DrawParam::new());
+
+            let mut txt = Text::new(TextFragment::new(format!("Slot {}\n", i + 1)).scale(24.0));
+            match slot {
+                SlotFile::Saved(meta) => {
+                    let location = if meta.location.is_empty() { "Somewhere" } else { &meta.location };
+                    let ironman = if !meta.daily.is_empty() { format!("   {}", meta.daily) } else if meta.ironman { "   Ironman".to_string() } else { String::new() };
+                    txt.add(TextFragment::new(format!("Lv {}   {}   Played {}{}\n", meta.level, location, save::format_playtime(meta.playtime), ironman)).scale(18.0));
+                    txt.add(TextFragment::new(format!("Seed: {}   Depth reached: {}   {}", meta.seed, meta.depth_reached, meta.generation.summary())).scale(16.0).color(Color::new(0.8, 0.85, 1.0, 1.0)));
+                }
+                SlotFile::Empty => {
+                    txt.add(TextFragment::new("Empty").scale(18.0).color(Color::new(0.7, 0.7, 0.7, 1.0)));
+                }
+                SlotFile::Damaged(Some(backup)) => {
+                    txt.add(TextFragment::new("This save is damaged.\n").scale(18.0).color(Color::new(1.0, 0.6, 0.5, 1.0)));
+                    txt.add(TextFragment::new(crate::input::prompt(&format!("{{confirm}}: restore the backup (Lv {}, played {})", backup.level, save::format_playtime(backup.playtime)))).scale(16.0));
+                }
+                SlotFile::Damaged(None) => {
+                    txt.add(TextFragment::new("This save is damaged and has no backup.").scale(18.0).color(Color::new(1.0, 0.6, 0.5, 1.0)));
+                }
+            }
+            canvas.draw(&txt, DrawParam::new().dest([56.0, top + 10.0]).color(Color::WHITE));
+            if let Some(Some(thumbnail)) = thumbnails.get(i) {
+                let scale = (box_h - 12.0) / thumbnail.height() as f32;
+                let left = rect.x + rect.w - 6.0 - thumbnail.width() as f32 * scale;
+                canvas.draw(thumbnail, DrawParam::new().dest([left, top + 6.0]).scale([scale, scale]));
+            }
+        }
+
+        let prompt = if self.seed_entry.is_some() {
+            "Type a seed or daily code (blank = random)   {left_right}: foes   {ironman}: ironman   {submit}: start   {cancel}: cancel"
+        } else {
+            "{confirm}: play   {new}: new game in slot   {daily}: today's daily run   {cancel}: back"
+        };
+        let prompt_txt = Text::new(TextFragment::new(crate::input::prompt(prompt)).scale(16.0));
+        canvas.draw(&prompt_txt, DrawParam::new().dest([40.0, h - 40.0]).color(Color::WHITE));
+        let today = Date::today().code();
+        let best = match self.daily_log.best(&today) {
+            Some((level, secs)) => format!("best Lv {} at {}", level, save::format_playtime(secs)),
+            None => "not played yet".to_string(),
+        };
+        let daily_txt = Text::new(TextFragment::new(format!("Today's daily run: {}   {}", today, best)).scale(16.0));
+        canvas.draw(&daily_txt, DrawParam::new().dest([40.0, h - 64.0]).color(Color::new(1.0, 0.85, 0.4, 1.0)));
+
+        if let Some(entry) = &self.seed_entry {
+            self.draw_seed_entry(ctx, canvas, entry, w, h)?;
+        }
+        Ok(())
+    }
+
+    fn draw_seed_entry(&self, ctx: &mut Context, canvas: &mut Canvas, entry: &SeedEntry, w: f32, h: f32) -> GameResult {
+        let box_w = 420.0;
+        let box_h = 220.0;
+        let rect = graphics::Rect::new((w - box_w) / 2.0, (h - box_h) / 2.0, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.98))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let mut txt = Text::new(TextFragment::new(format!("New game - Slot {}\n\n", self.selected + 1)).scale(24.0));
+        let seed_shown = if entry.text.is_empty() { "(random)".to_string() } else { format!("{}_", entry.text) };
+        txt.add(TextFragment::new(format!("Seed: {}\n", seed_shown)).scale(20.0));
+        txt.add(TextFragment::new(format!("Foes:  <  {}  >\n", entry.generation.enemy_count)).scale(20.0));
+        txt.add(TextFragment::new(format!("Ironman: {}\n", if entry.ironman { "On - one life, no manual saves" } else { "Off" })).scale(20.0));
+        if !matches!(self.slots[self.selected], SlotFile::Empty | SlotFile::Damaged(None)) {
+            txt.add(TextFragment::new("Starting replaces the save in this slot.").scale(16.0).color(Color::new(1.0, 0.6, 0.5, 1.0)));
+        }
+        canvas.draw(&txt, DrawParam::new().dest([rect.x + 20.0, rect.y + 16.0]).color(Color::WHITE));
+        Ok(())
+    }
+
+    /// Typed characters while the seed form is open.
+    pub fn handle_text(&mut self, ch: char) {
+        if let Some(entry) = &mut self.seed_entry {
+            if entry.swallow_next_char {
+                entry.swallow_next_char = false;
+                return;
+            }
+            if !ch.is_control() && entry.text.len() < MAX_SEED_LEN {
+                entry.text.push(ch);
+            }
+        }
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode) -> Option<SlotAction> {
+        if let Some(entry) = &mut self.seed_entry {
+            match key {
+                KeyCode::Back => { entry.text.pop(); }
+                KeyCode::Left => { entry.generation.enemy_count = entry.generation.enemy_count.saturating_sub(1); }
+                KeyCode::Right => { entry.generation.enemy_count = (entry.generation.enemy_count + 1).min(GenerationOptions::MAX_ENEMIES); }
+                k if Action::Ironman.matches(k) => { entry.ironman = !entry.ironman; }
+                k if Action::Submit.matches(k) => {
+                    let entry = self.seed_entry.take()?;
+                    // a shared daily code starts that day's run, on that day's terms
+                    if let Some(day) = Date::parse_code(&entry.text) {
+                        return Some(self.daily_run(day));
+                    }
+                    return Some(SlotAction::NewGame { slot: self.selected, seed: rng::parse_seed(&entry.text), generation: entry.generation, ironman: entry.ironman, daily: None });
+                }
+                KeyCode::Escape => { self.seed_entry = None; }
+                _ => {}
+            }
+            return None;
+        }
+
+        match key {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(self.slots.len().saturating_sub(1)); }
+            k if Action::Confirm.matches(k) => {
+                match &self.slots[self.selected] {
+                    SlotFile::Saved(meta) => return Some(SlotAction::Continue(self.selected, Box::new(meta.clone()))),
+                    SlotFile::Damaged(Some(_)) => match save::restore_backup(self.selected) {
+                        Ok(()) => {
+                            println!("SaveSlots: restored the backup of slot {}", self.selected + 1);
+                            self.refresh();
+                        }
+                        Err(e) => println!("SaveSlots: couldn't restore the backup of slot {}: {}", self.selected + 1, e),
+                    },
+                    // a gamepad's stand-in Z types nothing to swallow
+                    SlotFile::Empty | SlotFile::Damaged(None) => self.open_seed_entry(key == KeyCode::Z && !crate::input::pad_in_use()),
+                }
+            }
+            k if Action::New.matches(k) => self.open_seed_entry(!crate::input::pad_in_use()),
+            k if Action::Daily.matches(k) => match &self.slots[self.selected] {
+                SlotFile::Empty | SlotFile::Damaged(None) => return Some(self.daily_run(Date::today())),
+                // there's a save to lose: ask through the form, which takes daily codes too
+                SlotFile::Saved(_) | SlotFile::Damaged(Some(_)) => {
+                    self.open_seed_entry(!crate::input::pad_in_use());
+                    if let Some(entry) = &mut self.seed_entry {
+                        entry.text = Date::today().code();
+                    }
+                }
+            },
+            k if Action::Cancel.matches(k) => return Some(SlotAction::Back),
+            _ => {}
+        }
+        None
+    }
+
+    fn open_seed_entry(&mut self, swallow_next_char: bool) {
+        let generation = match &self.slots[self.selected] {
+            SlotFile::Saved(meta) | SlotFile::Damaged(Some(meta)) => meta.generation,
+            _ => GenerationOptions::default(),
+        };
+        self.seed_entry = Some(SeedEntry { text: String::new(), generation, ironman: false, swallow_next_char });
+    }
+
+    fn daily_run(&self, day: Date) -> SlotAction {
+        SlotAction::NewGame { slot: self.selected, seed: Some(day.seed()), generation: daily::generation(), ironman: true, daily: Some(day) }
+    }
+}
@@ -0,0 +1,140 @@
+//! The scene stack: whatever screens are up, topmost last.
+//!
+//! Each screen (title, save slots, intro, the world, battles, credits, the options menu) is a
+//! `Scene`. Only the top scene updates and takes input, so opening a menu over the world pauses
+//! it. Drawing starts from the highest scene that covers the whole window, so overlays such as
+//! the options menu are drawn over the screens underneath. Scenes ask for changes to the stack
+//! by returning a `Command` instead of touching it, since they run while lifted off it.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::Canvas;
+use ggez::input::keyboard::KeyInput;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SceneId {
+    Title,
+    SaveSlots,
+    Intro,
+    World,
+    Battle,
+    Credits,
+    Options,
+}
+
+/// A screen on the stack, working on the game `G` that owns the stack.
+pub trait Scene<G> {
+    fn id(&self) -> SceneId;
+
+    /// Overlays leave the scene below visible (but paused).
+    fn is_overlay(&self) -> bool { false }
+
+    fn update(&mut self, _game: &mut G, _ctx: &mut Context, _dt: f32) -> GameResult<Command<G>> {
+        Ok(Command::None)
+    }
+
+    fn key_down(&mut self, game: &mut G, ctx: &mut Context, input: KeyInput) -> GameResult<Command<G>>;
+
+    fn text_input(&mut self, _game: &mut G, _character: char) {}
+
+    fn draw(&mut self, game: &mut G, ctx: &mut Context, canvas: &mut Canvas) -> GameResult;
+}
+
+/// What a scene wants done to the stack.
+pub enum Command<G> {
+    None,
+    /// Put a scene on top, pausing this one.
+    Push(Box<dyn Scene<G>>),
+    /// Close this scene.
+    Pop,
+    /// Replace the whole stack (behind a screen transition).
+    Switch(Box<dyn Scene<G>>),
+}
+
+pub struct SceneStack<G> {
+    scenes: Vec<Box<dyn Scene<G>>>,
+}
+
+impl<G> Default for SceneStack<G> {
+    fn default() -> SceneStack<G> {
+        SceneStack { scenes: Vec::new() }
+    }
+}
+
+impl<G> SceneStack<G> {
+    pub fn new(base: Box<dyn Scene<G>>) -> SceneStack<G> {
+        SceneStack { scenes: vec![base] }
+    }
+
+    pub fn top(&self) -> Option<SceneId> {
+        self.scenes.last().map(|s| s.id())
+    }
+
+    pub fn contains(&self, id: SceneId) -> bool {
+        self.scenes.iter().any(|s| s.id() == id)
+    }
+
+    pub fn push(&mut self, scene: Box<dyn Scene<G>>) {
+        println!("Scene: push {:?}", scene.id());
+        self.scenes.push(scene);
+    }
+
+    /// Close the top scene; the bottom one always stays.
+    pub fn pop(&mut self) {
+        if self.scenes.len() > 1
+            && let Some(scene) = self.scenes.pop() {
+            println!("Scene: pop {:?}", scene.id());
+        }
+    }
+
+    /// Drop everything for `scene`.
+    pub fn replace(&mut self, scene: Box<dyn Scene<G>>) {
+        println!("Scene: {:?} -> {:?}", self.top(), scene.id());
+        self.scenes.clear();
+        self.scenes.push(scene);
+    }
+
+    /// Lift the top scene off to run it against the game, then put it back.
+    pub fn take_top(&mut self) -> Option<Box<dyn Scene<G>>> {
+        self.scenes.pop()
+    }
+
+    pub fn put_back(&mut self, scene: Box<dyn Scene<G>>) {
+        self.scenes.push(scene);
+    }
+
+    /// The scenes that show, bottom first: the top one and everything it overlays.
+    pub fn visible_mut(&mut self) -> impl Iterator<Item = &mut Box<dyn Scene<G>>> {
+        let first = self.scenes.iter().rposition(|s| !s.is_overlay()).unwrap_or(0);
+        self.scenes[first..].iter_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Screen(SceneId, bool);
+
+    impl Scene<()> for Screen {
+        fn id(&self) -> SceneId { self.0 }
+        fn is_overlay(&self) -> bool { self.1 }
+        fn key_down(&mut self, _: &mut (), _: &mut Context, _: KeyInput) -> GameResult<Command<()>> { Ok(Command::None) }
+        fn draw(&mut self, _: &mut (), _: &mut Context, _: &mut Canvas) -> GameResult { Ok(()) }
+    }
+
+    #[test]
+    fn overlays_show_what_is_under_them_and_the_base_stays() {
+        let mut stack: SceneStack<()> = SceneStack::new(Box::new(Screen(SceneId::Title, false)));
+        stack.push(Box::new(Screen(SceneId::World, false)));
+        stack.push(Box::new(Screen(SceneId::Options, true)));
+        let shown: Vec<SceneId> = stack.visible_mut().map(|s| s.id()).collect();
+        assert_eq!(shown, vec![SceneId::World, SceneId::Options]);
+        assert!(stack.contains(SceneId::Title));
+        stack.pop();
+        stack.pop();
+        stack.pop();
+        assert_eq!(stack.top(), Some(SceneId::Title), "the bottom scene can't be popped");
+        stack.replace(Box::new(Screen(SceneId::Credits, false)));
+        assert!(!stack.contains(SceneId::Title));
+    }
+}
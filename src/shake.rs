@@ -0,0 +1,81 @@
+//! Camera shake: jolts the world render for heavy impacts.
+//!
+//! A shake has a magnitude (world pixels), a duration and a falloff curve for how it dies
+//! away. Only the world moves; the HUD and menus stay put. A new shake replaces the current one
+//! only if it's stronger than what's left of it, so a stream of small hits can't cut a big one
+//! short.
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Falloff {
+    Linear,
+    /// Drops off fast, then lingers as a faint tremble.
+    Quadratic,
+}
+
+impl Falloff {
+    /// Strength left at `progress` (0 at the start, 1 at the end).
+    fn strength(self, progress: f32) -> f32 {
+        let left = (1.0 - progress).clamp(0.0, 1.0);
+        match self {
+            Falloff::Linear => left,
+            Falloff::Quadratic => left * left,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct ScreenShake {
+    magnitude: f32,
+    duration: f32,
+    elapsed: f32,
+    falloff: Option<Falloff>,
+}
+
+impl ScreenShake {
+    pub fn start(&mut self, magnitude: f32, duration: f32, falloff: Falloff) {
+        if magnitude < self.intensity() || duration <= 0.0 { return; }
+        *self = ScreenShake { magnitude, duration, elapsed: 0.0, falloff: Some(falloff) };
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if self.falloff.is_none() { return; }
+        self.elapsed += dt;
+        if self.elapsed >= self.duration {
+            *self = ScreenShake::default();
+        }
+    }
+
+    /// Current reach of the shake in world pixels.
+    fn intensity(&self) -> f32 {
+        self.falloff.map_or(0.0, |f| self.magnitude * f.strength(self.elapsed / self.duration))
+    }
+
+    /// How far to move the world this frame, in world pixels. Two out-of-step waves, so it
+    /// rattles in every direction without needing randomness (replays look the same).
+    pub fn offset(&self) -> (f32, f32) {
+        let reach = self.intensity();
+        let t = self.elapsed;
+        (reach * (t * 71.0).sin(), reach * (t * 53.0 + 1.3).cos())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shakes_fade_out_and_small_ones_dont_cut_big_ones_short() {
+        let mut shake = ScreenShake::default();
+        assert_eq!(shake.offset(), (0.0, 0.0));
+        shake.start(8.0, 0.5, Falloff::Linear);
+        shake.update(0.25);
+        assert!((shake.intensity() - 4.0).abs() < 1e-4);
+        let (x, y) = shake.offset();
+        assert!(x.abs() <= 4.0 && y.abs() <= 4.0);
+        shake.start(2.0, 1.0, Falloff::Linear);
+        assert!((shake.intensity() - 4.0).abs() < 1e-4, "the weaker shake was ignored");
+        shake.update(0.3);
+        assert_eq!(shake.offset(), (0.0, 0.0));
+        assert_eq!(Falloff::Quadratic.strength(0.5), 0.25);
+    }
+}
@@ -0,0 +1,120 @@
+//! Shop stock and transactions.
+//!
+//! Stock lives in data files under `assets/shops/<id>.txt`, in the same `key=value` style as
+//! save files:
+//!
+//! ```text
+//! name=Mira's Wares
+//! # item_id=quantity
+//! iron_sword=1
+//! chain_mail=2
+//! ```
+
+use crate::items::{self, ItemId};
+use crate::player::Player;
+
+/// Merchants buy items back for this fraction of their price.
+const SELL_RATIO: f32 = 0.5;
+
+pub struct Shop {
+    pub name: String,
+    /// (item, quantity left). Items sold to the shop are added here too.
+    pub stock: Vec<(ItemId, u32)>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum TradeError {
+    NotEnoughGold,
+    SoldOut,
+    NoSuchItem,
+    /// Key items stay with the player.
+    KeyItem,
+}
+
+impl Shop {
+    /// Parse a shop data file. Unknown item ids are reported and skipped.
+    pub fn from_text(text: &str) -> Shop {
+        let mut shop = Shop { name: "Shop".to_string(), stock: Vec::new() };
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let Some((key, value)) = line.split_once('=') else { continue };
+            let (key, value) = (key.trim(), value.trim());
+            if key == "name" {
+                shop.name = value.to_string();
+                continue;
+            }
+            match (items::find(key), value.parse::<u32>()) {
+                (Some(def), Ok(qty)) => shop.stock.push((def.id, qty)),
+                _ => println!("Shop: skipping bad stock line {:?}", line),
+            }
+        }
+        shop
+    }
+
+    /// Load `assets/shops/<id>.txt`, or an empty shop if the file is missing.
+    pub fn load(ctx: &ggez::Context, id: &str) -> Shop {
+        match crate::assets::read_text(ctx, &format!("/shops/{}.txt", id)) {
+            Some(text) => Shop::from_text(&text),
+            None => {
+                println!("Shop: no data file for shop {:?}", id);
+                Shop { name: "Closed".to_string(), stock: Vec::new() }
+            }
+        }
+    }
+
+    pub fn buy_price(id: &str) -> u32 {
+        items::find(id).map(|d| d.price).unwrap_or(0)
+    }
+
+    pub fn sell_price(id: &str) -> u32 {
+        (Shop::buy_price(id) as f32 * SELL_RATIO) as u32
+    }
+
+    /// Player buys stock entry `index`.
+    pub fn buy(&mut self, index: usize, player: &mut Player) -> Result<ItemId, TradeError> {
+        let Some(&(id, qty)) = self.stock.get(index) else { return Err(TradeError::NoSuchItem) };
+        if qty == 0 { return Err(TradeError::SoldOut); }
+        let price = Shop::buy_price(id);
+        if player.gold < price { return Err(TradeError::NotEnoughGold); }
+        player.gold -= price;
+        self.stock[index].1 -= 1;
+        player.inventory.add(id);
+        Ok(id)
+    }
+
+    /// Player sells inventory item `index`.
+    pub fn sell(&mut self, index: usize, player: &mut Player) -> Result<ItemId, TradeError> {
+        let id = *player.inventory.items.get(index).ok_or(TradeError::NoSuchItem)?;
+        if crate::items::find(id).is_some_and(|def| def.kind == crate::items::ItemKind::Key) {
+            return Err(TradeError::KeyItem);
+        }
+        player.inventory.take(index);
+        player.gold += Shop::sell_price(id);
+        match self.stock.iter_mut().find(|(s, _)| *s == id) {
+            Some(entry) => entry.1 += 1,
+            None => self.stock.push((id, 1)),
+        }
+        Ok(id)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn buying_and_selling_moves_gold_and_stock() {
+        let mut shop = Shop::from_text("name=Test\niron_sword=1\nnot_an_item=3\n");
+        assert_eq!(shop.stock.len(), 1);
+        let mut player = Player::test_new();
+        player.gold = 0;
+        assert_eq!(shop.buy(0, &mut player), Err(TradeError::NotEnoughGold));
+        player.gold = Shop::buy_price("iron_sword");
+        assert_eq!(shop.buy(0, &mut player), Ok("iron_sword"));
+        assert_eq!(player.gold, 0);
+        assert_eq!(shop.buy(0, &mut player), Err(TradeError::SoldOut));
+        let idx = player.inventory.items.iter().position(|i| *i == "iron_sword").unwrap();
+        assert_eq!(shop.sell(idx, &mut player), Ok("iron_sword"));
+        assert_eq!(player.gold, Shop::sell_price("iron_sword"));
+        assert_eq!(shop.stock[0], ("iron_sword", 1));
+    }
+}
@@ -0,0 +1,183 @@
+//! Shop UI: Buy / Sell tabs with a confirmation prompt before every trade.
+//!
+//! Left/Right switches tabs, Up/Down picks an entry, Z asks for confirmation and a second Z
+//! (or Left/Right to pick Yes/No) completes the trade. C backs out of the prompt or the shop.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+use crate::items::{self, Descriptions};
+use crate::item_details::{self, PANEL_GAP, PANEL_W};
+use crate::player::Player;
+use crate::shop::{Shop, TradeError};
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Tab {
+    Buy,
+    Sell,
+}
+
+pub struct ShopScreen {
+    pub visible: bool,
+    /// Index of the merchant NPC whose shop is open.
+    pub npc: usize,
+    tab: Tab,
+    selected: usize,
+    /// Some(yes_selected) while the confirmation prompt is up.
+    confirm: Option<bool>,
+    message: Option<String>,
+}
+
+impl ShopScreen {
+    pub fn new() -> ShopScreen {
+        ShopScreen { visible: false, npc: 0, tab: Tab::Buy, selected: 0, confirm: None, message: None }
+    }
+
+    pub fn open(&mut self, npc: usize) {
+        *self = ShopScreen::new();
+        self.visible = true;
+        self.npc = npc;
+    }
+
+    fn entry_count(&self, shop: &Shop, player: &Player) -> usize {
+        match self.tab {
+            Tab::Buy => shop.stock.len(),
+            Tab::Sell => player.inventory.items.len(),
+        }
+    }
+
+    /// (item id, price) for the highlighted entry.
+    fn selected_entry(&self, shop: &Shop, player: &Player) -> Option<(&'static str, u32)> {
+        match self.tab {
+            Tab::Buy => shop.stock.get(self.selected).map(|(id, _)| (*id, Shop::buy_price(id))),
+            Tab::Sell => player.inventory.items.get(self.selected).map(|id| (*id, Shop::sell_price(id))),
+        }
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, shop: &Shop, player: &Player, descriptions: &Descriptions) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 520.0;
+        let box_h = 440.0;
+        // the list and the detail panel beside it are centred together
+        let left = (w - box_w - PANEL_GAP - PANEL_W) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new(shop.name.as_str()).scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+        let gold = Text::new(TextFragment::new(format!("{} G", player.gold)).scale(20.0));
+        canvas.draw(&gold, DrawParam::new().dest([left + box_w - 110.0, top + 22.0]).color(Color::new(1.0, 0.85, 0.2, 1.0)));
+
+        for (i, (label, tab)) in [("Buy", Tab::Buy), ("Sell", Tab::Sell)].iter().enumerate() {
+            let color = if *tab == self.tab { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::new(0.7, 0.7, 0.7, 1.0) };
+            let txt = Text::new(TextFragment::new(*label).scale(22.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0 + i as f32 * 100.0, top + 60.0]).color(color));
+        }
+
+        let rows: Vec<(String, Color)> = match self.tab {
+            Tab::Buy => shop.stock.iter().map(|(id, qty)| {
+                let name = items::find(id).map(|d| d.name).unwrap_or(id);
+                let color = if *qty == 0 { Color::new(0.6, 0.6, 0.6, 1.0) } else { Color::WHITE };
+                (format!("{:<16} {:>4} G   x{}", name, Shop::buy_price(id), qty), color)
+            }).collect(),
+            Tab::Sell => player.inventory.items.iter().map(|id| {
+                let name = items::find(id).map(|d| d.name).unwrap_or(id);
+                (format!("{:<16} {:>4} G", name, Shop::sell_price(id)), Color::WHITE)
+            }).collect(),
+        };
+        for (i, (text, color)) in rows.iter().enumerate() {
+            let y = top + 100.0 + i as f32 * 30.0;
+            let txt = Text::new(TextFragment::new(text.as_str()).scale(20.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(*color));
+            if i == self.selected {
+                let sel_rect = graphics::Rect::new(left + 30.0, y - 5.0, box_w - 60.0, 28.0);
+                let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                canvas.draw(&sel_box, DrawParam::new());
+            }
+        }
+        if rows.is_empty() {
+            let empty = if self.tab == Tab::Buy { "(nothing for sale)" } else { "(nothing to sell)" };
+            let txt = Text::new(TextFragment::new(empty).scale(18.0));
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, top + 100.0]).color(Color::new(0.7, 0.7, 0.7, 1.0)));
+        }
+
+        let footer = match (&self.confirm, &self.message) {
+            (Some(yes), _) => {
+                let (id, price) = self.selected_entry(shop, player).unwrap_or(("", 0));
+                let name = items::find(id).map(|d| d.name).unwrap_or(id);
+                let verb = if self.tab == Tab::Buy { "Buy" } else { "Sell" };
+                let (y_mark, n_mark) = if *yes { ("[Yes]", " No ") } else { (" Yes ", "[No]") };
+                format!("{} {} for {} G?   {}  {}", verb, name, price, y_mark, n_mark)
+            }
+            (None, Some(msg)) => msg.clone(),
+            (None, None) => crate::input::prompt("{left_right}: tab   {confirm}: select   {cancel}: leave"),
+        };
+        let txt = Text::new(TextFragment::new(footer).scale(18.0));
+        canvas.draw(&txt, DrawParam::new().dest([left + 20.0, top + box_h - 36.0]).color(Color::WHITE));
+        let item = self.selected_entry(shop, player).map(|(id, _)| id);
+        item_details::draw(ctx, canvas, left + box_w + PANEL_GAP, top, box_h, item, descriptions)
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode, shop: &mut Shop, player: &mut Player) {
+        if !self.visible { return; }
+
+        if let Some(yes) = self.confirm {
+            match key {
+                KeyCode::Left | KeyCode::Right => { self.confirm = Some(!yes); }
+                k if Action::Confirm.matches(k) && yes => {
+                    self.confirm = None;
+                    self.trade(shop, player);
+                }
+                k if Action::Confirm.matches(k) || Action::Cancel.matches(k) => { self.confirm = None; }
+                _ => {}
+            }
+            return;
+        }
+
+        self.message = None;
+        match key {
+            KeyCode::Left | KeyCode::Right => {
+                self.tab = if self.tab == Tab::Buy { Tab::Sell } else { Tab::Buy };
+                self.selected = 0;
+            }
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); }
+            KeyCode::Down => {
+                let count = self.entry_count(shop, player);
+                self.selected = (self.selected + 1).min(count.saturating_sub(1));
+            }
+            k if Action::Confirm.matches(k) && self.selected_entry(shop, player).is_some() => {
+                self.confirm = Some(true);
+            }
+            k if Action::Cancel.matches(k) => { self.visible = false; }
+            _ => {}
+        }
+    }
+
+    fn trade(&mut self, shop: &mut Shop, player: &mut Player) {
+        let result = match self.tab {
+            Tab::Buy => shop.buy(self.selected, player),
+            Tab::Sell => shop.sell(self.selected, player),
+        };
+        self.message = Some(match result {
+            Ok(id) => {
+                let name = items::find(id).map(|d| d.name).unwrap_or(id);
+                if self.tab == Tab::Buy { format!("Bought {}.", name) } else { format!("Sold {}.", name) }
+            }
+            Err(TradeError::NotEnoughGold) => "You can't afford that.".to_string(),
+            Err(TradeError::SoldOut) => "Sold out!".to_string(),
+            Err(TradeError::NoSuchItem) => "Nothing there.".to_string(),
+            Err(TradeError::KeyItem) => "You can't part with that.".to_string(),
+        });
+        let count = self.entry_count(shop, player);
+        self.selected = self.selected.min(count.saturating_sub(1));
+    }
+}
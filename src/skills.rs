@@ -0,0 +1,126 @@
+//! Skills (spells): definitions, the skills the player knows and the hotbar they're cast from.
+//!
+//! Skills are learned automatically on reaching their level, cost MP and have a cooldown.
+//! The number keys cast whatever sits in the matching hotbar slot.
+
+use crate::stats::Stats;
+
+pub type SkillId = &'static str;
+
+/// Number of hotbar slots (bound to keys 1..=HOTBAR_SLOTS).
+pub const HOTBAR_SLOTS: usize = 4;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum SkillEffect {
+    /// Restore this much HP.
+    Heal(i32),
+    /// Bolt of fire flying in the facing direction: damage (plus half the caster's attack)
+    /// and range in tiles.
+    FireBolt { damage: i32, range: i32 },
+    /// Remove poison, burn and slow.
+    Purify,
+}
+
+#[derive(Debug)]
+pub struct SkillDef {
+    pub id: SkillId,
+    pub name: &'static str,
+    pub mp_cost: i32,
+    /// Seconds before the skill can be cast again.
+    pub cooldown: f32,
+    /// Level at which the skill is learned.
+    pub learn_level: u32,
+    pub effect: SkillEffect,
+}
+
+pub const SKILLS: &[SkillDef] = &[
+    SkillDef { id: "heal", name: "Heal", mp_cost: 5, cooldown: 3.0, learn_level: 1, effect: SkillEffect::Heal(12) },
+    SkillDef { id: "fire_bolt", name: "Fire Bolt", mp_cost: 4, cooldown: 1.0, learn_level: 1, effect: SkillEffect::FireBolt { damage: 6, range: 6 } },
+    SkillDef { id: "purify", name: "Purify", mp_cost: 6, cooldown: 5.0, learn_level: 3, effect: SkillEffect::Purify },
+];
+
+pub fn find(id: &str) -> Option<&'static SkillDef> {
+    SKILLS.iter().find(|d| d.id == id)
+}
+
+#[derive(Debug, PartialEq)]
+pub enum CastError {
+    EmptySlot,
+    OnCooldown,
+    NotEnoughMp,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SkillBook {
+    pub known: Vec<SkillId>,
+    pub hotbar: [Option<SkillId>; HOTBAR_SLOTS],
+    /// Seconds left on each skill that is cooling down.
+    cooldowns: Vec<(SkillId, f32)>,
+}
+
+impl SkillBook {
+    /// Everything learnable up to `level`, placed on the hotbar in learn order.
+    pub fn for_level(level: u32) -> SkillBook {
+        let mut book = SkillBook::default();
+        book.learn_up_to(level);
+        book
+    }
+
+    /// Learn any skills unlocked at or below `level`. Returns the newly learned ones.
+    pub fn learn_up_to(&mut self, level: u32) -> Vec<&'static SkillDef> {
+        let mut learned = Vec::new();
+        for def in SKILLS.iter().filter(|d| d.learn_level <= level) {
+            if self.known.contains(&def.id) { continue; }
+            self.known.push(def.id);
+            if let Some(slot) = self.hotbar.iter_mut().find(|s| s.is_none()) {
+                *slot = Some(def.id);
+            }
+            learned.push(def);
+        }
+        learned
+    }
+
+    pub fn cooldown_left(&self, id: &str) -> f32 {
+        self.cooldowns.iter().find(|(s, _)| *s == id).map(|(_, t)| *t).unwrap_or(0.0)
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        for (_, t) in &mut self.cooldowns {
+            *t -= dt;
+        }
+        self.cooldowns.retain(|(_, t)| *t > 0.0);
+    }
+
+    /// Pay the MP and start the cooldown for hotbar `slot`. The caller applies the effect.
+    pub fn cast(&mut self, slot: usize, stats: &mut Stats) -> Result<&'static SkillDef, CastError> {
+        let def = self.hotbar.get(slot).copied().flatten().and_then(find).ok_or(CastError::EmptySlot)?;
+        if self.cooldown_left(def.id) > 0.0 { return Err(CastError::OnCooldown); }
+        if stats.mp < def.mp_cost { return Err(CastError::NotEnoughMp); }
+        stats.mp -= def.mp_cost;
+        self.cooldowns.push((def.id, def.cooldown));
+        Ok(def)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn casting_costs_mp_and_respects_cooldowns() {
+        let mut book = SkillBook::for_level(1);
+        assert_eq!(book.hotbar[0], Some("heal"));
+        assert!(!book.known.contains(&"purify"));
+        let mut stats = Stats::new();
+        let mp = stats.mp;
+        assert!(book.cast(0, &mut stats).is_ok());
+        assert_eq!(stats.mp, mp - 5);
+        assert_eq!(book.cast(0, &mut stats).unwrap_err(), CastError::OnCooldown);
+        book.update(3.0);
+        stats.mp = 0;
+        assert_eq!(book.cast(0, &mut stats).unwrap_err(), CastError::NotEnoughMp);
+        assert_eq!(book.cast(3, &mut stats).unwrap_err(), CastError::EmptySlot);
+        assert_eq!(book.learn_up_to(3).len(), 1);
+        assert_eq!(book.hotbar[2], Some("purify"));
+    }
+}
@@ -0,0 +1,69 @@
+//! Uniform grid hash for finding entities near a point.
+//!
+//! Entities are bucketed by their position into square cells; a query looks at the cell under
+//! the point and the eight around it. As long as things only interact within one cell of each
+//! other, collision checks then cost the number of nearby entities instead of all of them.
+
+use std::collections::HashMap;
+
+use nalgebra as na;
+
+use crate::map::TILE_SIZE;
+
+/// Cell size for entity checks: every touch or hit in the game reaches less than a tile.
+pub const ENTITY_CELL: f32 = TILE_SIZE;
+
+pub struct SpatialHash {
+    cell: f32,
+    cells: HashMap<(i32, i32), Vec<usize>>,
+}
+
+impl SpatialHash {
+    pub fn new(cell: f32) -> SpatialHash {
+        SpatialHash { cell, cells: HashMap::new() }
+    }
+
+    fn cell_of(&self, point: na::Point2<f32>) -> (i32, i32) {
+        ((point.x / self.cell).floor() as i32, (point.y / self.cell).floor() as i32)
+    }
+
+    /// Replace the contents with `positions`, remembered by their index.
+    pub fn rebuild(&mut self, positions: impl IntoIterator<Item = na::Point2<f32>>) {
+        self.cells.clear();
+        for (i, point) in positions.into_iter().enumerate() {
+            let cell = self.cell_of(point);
+            self.cells.entry(cell).or_default().push(i);
+        }
+    }
+
+    /// Indices of everything within a cell of `point`, lowest first so callers pick the same
+    /// entity a plain scan would.
+    pub fn near(&self, point: na::Point2<f32>) -> Vec<usize> {
+        let (cx, cy) = self.cell_of(point);
+        let mut found: Vec<usize> = (cy - 1..=cy + 1)
+            .flat_map(|y| (cx - 1..=cx + 1).map(move |x| (x, y)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .copied()
+            .collect();
+        found.sort_unstable();
+        found
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn finds_only_neighbours_in_index_order() {
+        let mut grid = SpatialHash::new(32.0);
+        let points = [(100.0, 100.0), (10.0, 10.0), (120.0, 90.0), (400.0, 400.0), (-5.0, 70.0)];
+        grid.rebuild(points.iter().map(|&(x, y)| na::Point2::new(x, y)));
+        assert_eq!(grid.near(na::Point2::new(110.0, 100.0)), vec![0, 2]);
+        assert_eq!(grid.near(na::Point2::new(20.0, 40.0)), vec![1, 4]);
+        assert!(grid.near(na::Point2::new(250.0, 250.0)).is_empty());
+        grid.rebuild([]);
+        assert!(grid.near(na::Point2::new(110.0, 100.0)).is_empty());
+    }
+}
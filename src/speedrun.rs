@@ -0,0 +1,86 @@
+//! Speedrun timer: a run clock started with each new world plus named splits recorded the first
+//! time certain events happen. Drawn on top of everything when enabled in the options.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+
+/// Split names, in the order they're normally reached.
+pub const SPLIT_LEAVE_ROOM: &str = "Leave first room";
+pub const SPLIT_ROOM_CLEARED: &str = "Room cleared";
+
+pub struct SpeedrunTimer {
+    /// Seconds since the run started.
+    pub elapsed: f32,
+    running: bool,
+    /// (split name, run time when it was hit)
+    pub splits: Vec<(&'static str, f32)>,
+}
+
+impl SpeedrunTimer {
+    pub fn new() -> SpeedrunTimer {
+        SpeedrunTimer { elapsed: 0.0, running: false, splits: Vec::new() }
+    }
+
+    pub fn start(&mut self) {
+        *self = SpeedrunTimer::new();
+        self.running = true;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if self.running { self.elapsed += dt; }
+    }
+
+    /// Record a split the first time `name` happens during this run.
+    pub fn split(&mut self, name: &'static str) {
+        if !self.running || self.splits.iter().any(|(n, _)| *n == name) { return; }
+        println!("Speedrun: {} at {}", name, format_time(self.elapsed));
+        self.splits.push((name, self.elapsed));
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let w = ctx.gfx.window().inner_size().width as f32;
+        let box_w = 220.0;
+        let box_h = 40.0 + self.splits.len() as f32 * 20.0;
+        let rect = graphics::Rect::new((w - box_w) / 2.0, 8.0, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.0, 0.0, 0.6))?;
+        canvas.draw(&bg, DrawParam::new());
+
+        let clock = Text::new(TextFragment::new(format_time(self.elapsed)).scale(26.0));
+        canvas.draw(&clock, DrawParam::new().dest([rect.x + box_w / 2.0, rect.y + 6.0]).offset([0.5, 0.0]).color(Color::new(0.4, 1.0, 0.5, 1.0)));
+        for (i, (name, time)) in self.splits.iter().enumerate() {
+            let y = rect.y + 36.0 + i as f32 * 20.0;
+            let label = Text::new(TextFragment::new(*name).scale(14.0));
+            canvas.draw(&label, DrawParam::new().dest([rect.x + 10.0, y]).color(Color::WHITE));
+            let t = Text::new(TextFragment::new(format_time(*time)).scale(14.0));
+            canvas.draw(&t, DrawParam::new().dest([rect.x + box_w - 10.0, y]).offset([1.0, 0.0]).color(Color::WHITE));
+        }
+        Ok(())
+    }
+}
+
+/// `m:ss.cc`, or `h:mm:ss.cc` for runs over an hour.
+pub fn format_time(secs: f32) -> String {
+    let centis = (secs * 100.0) as u64;
+    let (h, m, s, c) = (centis / 360_000, centis / 6000 % 60, centis / 100 % 60, centis % 100);
+    if h > 0 { format!("{}:{:02}:{:02}.{:02}", h, m, s, c) } else { format!("{}:{:02}.{:02}", m, s, c) }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_are_recorded_once_and_times_format() {
+        let mut timer = SpeedrunTimer::new();
+        timer.split(SPLIT_LEAVE_ROOM);
+        assert!(timer.splits.is_empty(), "no splits before the run starts");
+        timer.start();
+        timer.update(61.5);
+        timer.split(SPLIT_LEAVE_ROOM);
+        timer.update(1.0);
+        timer.split(SPLIT_LEAVE_ROOM);
+        assert_eq!(timer.splits, vec![(SPLIT_LEAVE_ROOM, 61.5)]);
+        assert_eq!(format_time(61.5), "1:01.50");
+        assert_eq!(format_time(3725.0), "1:02:05.00");
+    }
+}
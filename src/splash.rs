@@ -0,0 +1,83 @@
+//! Ripple rings left behind while wading through shallow water or swimming in deep water.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh};
+use nalgebra as na;
+
+use crate::map::TILE_SIZE;
+use crate::rooms::Water;
+
+/// Seconds a ripple takes to spread out and fade.
+const RIPPLE_SECS: f32 = 0.6;
+/// Time between splashes while the player keeps moving through water.
+const SPLASH_INTERVAL: f32 = 0.3;
+
+struct Ripple {
+    /// Centre, in world pixels.
+    position: na::Point2<f32>,
+    age: f32,
+    deep: bool,
+}
+
+pub struct Splashes {
+    ripples: Vec<Ripple>,
+    cooldown: f32,
+}
+
+impl Splashes {
+    pub fn new() -> Splashes {
+        Splashes { ripples: Vec::new(), cooldown: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.cooldown = (self.cooldown - dt).max(0.0);
+        for ripple in &mut self.ripples {
+            ripple.age += dt;
+        }
+        self.ripples.retain(|r| r.age < RIPPLE_SECS);
+    }
+
+    /// Called every frame the player moves through water. Returns true when a new splash
+    /// started (so the caller can play the sound).
+    pub fn wade(&mut self, position: na::Point2<f32>, water: Water) -> bool {
+        if self.cooldown > 0.0 { return false; }
+        self.cooldown = SPLASH_INTERVAL;
+        self.ripples.push(Ripple { position, age: 0.0, deep: water == Water::Deep });
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.ripples.clear();
+        self.cooldown = 0.0;
+    }
+
+    pub fn draw_scaled(&self, ctx: &mut Context, canvas: &mut Canvas, scale: f32, offset: (f32, f32)) -> GameResult {
+        for ripple in &self.ripples {
+            let t = ripple.age / RIPPLE_SECS;
+            let radius = TILE_SIZE * (0.25 + 0.45 * t) * scale;
+            let shade = if ripple.deep { 0.75 } else { 1.0 };
+            let color = Color::new(0.8 * shade, 0.92 * shade, 1.0, 0.8 * (1.0 - t));
+            let center = [offset.0 + ripple.position.x * scale, offset.1 + ripple.position.y * scale];
+            let ring = Mesh::new_circle(ctx, DrawMode::stroke(1.5 * scale), center, radius, 0.5, color)?;
+            canvas.draw(&ring, DrawParam::new());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wading_splashes_on_an_interval_and_ripples_fade() {
+        let mut splashes = Splashes::new();
+        let pos = na::Point2::new(16.0, 16.0);
+        assert!(splashes.wade(pos, Water::Shallow));
+        assert!(!splashes.wade(pos, Water::Shallow), "still on cooldown");
+        splashes.update(SPLASH_INTERVAL);
+        assert!(splashes.wade(pos, Water::Deep));
+        splashes.update(RIPPLE_SECS);
+        assert!(splashes.ripples.is_empty());
+    }
+}
@@ -0,0 +1,76 @@
+//! Character stats, experience and level-ups.
+
+/// XP awarded for defeating a basic enemy.
+pub const XP_PER_KILL: u32 = 5;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Stats {
+    pub level: u32,
+    /// XP collected towards the next level (resets on level-up).
+    pub xp: u32,
+    pub hp: i32,
+    pub max_hp: i32,
+    pub mp: i32,
+    pub max_mp: i32,
+    pub attack: i32,
+    pub defense: i32,
+}
+
+impl Stats {
+    pub fn new() -> Stats {
+        Stats::for_level(1)
+    }
+
+    /// Base stats for a level, at full health. Used for new games and when loading a save.
+    pub fn for_level(level: u32) -> Stats {
+        let level = level.max(1);
+        let gained = (level - 1) as i32;
+        let max_hp = 20 + gained * 4;
+        let max_mp = 10 + gained * 3;
+        Stats { level, xp: 0, hp: max_hp, max_hp, mp: max_mp, max_mp, attack: 5 + gained * 2, defense: 1 + gained }
+    }
+
+    /// XP needed to go from the current level to the next.
+    pub fn xp_to_next(&self) -> u32 {
+        20 * self.level
+    }
+
+    /// Add XP and apply any level-ups. Returns how many levels were gained.
+    /// Levelling up raises the stats and refills HP and MP.
+    pub fn gain_xp(&mut self, amount: u32) -> u32 {
+        self.xp += amount;
+        let mut gained = 0;
+        while self.xp >= self.xp_to_next() {
+            self.xp -= self.xp_to_next();
+            let xp = self.xp;
+            *self = Stats::for_level(self.level + 1);
+            self.xp = xp;
+            gained += 1;
+        }
+        gained
+    }
+
+    /// Apply raw damage reduced by defense (always at least 1). Returns true if HP hit zero.
+    pub fn take_damage(&mut self, raw: i32) -> bool {
+        let dmg = (raw - self.defense).max(1);
+        self.hp = (self.hp - dmg).max(0);
+        self.hp == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gaining_enough_xp_levels_up_and_raises_stats() {
+        let mut s = Stats::new();
+        let before = s;
+        assert_eq!(s.gain_xp(25), 1);
+        assert_eq!(s.level, 2);
+        assert_eq!(s.xp, 5);
+        assert!(s.max_hp > before.max_hp && s.attack > before.attack);
+        assert_eq!(s.hp, s.max_hp);
+        assert!(s.max_mp > before.max_mp && s.mp == s.max_mp);
+    }
+}
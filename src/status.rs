@@ -0,0 +1,129 @@
+//! Timed status effects (poison, slow, burn, regen).
+//!
+//! Damage/heal effects tick once per `TICK_INTERVAL`; slow just lowers movement speed while
+//! it lasts. Re-applying an effect refreshes its duration instead of stacking.
+
+use ggez::graphics::Color;
+
+/// Seconds between damage/heal ticks.
+pub const TICK_INTERVAL: f32 = 1.0;
+/// Movement speed multiplier while slowed.
+pub const SLOW_FACTOR: f32 = 0.5;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StatusKind {
+    Poison,
+    Slow,
+    Burn,
+    Regen,
+}
+
+impl StatusKind {
+    /// HP change applied on every tick (negative = damage).
+    pub fn hp_per_tick(self) -> i32 {
+        match self {
+            StatusKind::Poison => -1,
+            StatusKind::Burn => -2,
+            StatusKind::Regen => 2,
+            StatusKind::Slow => 0,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            StatusKind::Poison => "Poison",
+            StatusKind::Slow => "Slow",
+            StatusKind::Burn => "Burn",
+            StatusKind::Regen => "Regen",
+        }
+    }
+
+    /// Colour of the HUD icon.
+    pub fn color(self) -> Color {
+        match self {
+            StatusKind::Poison => Color::new(0.55, 0.2, 0.75, 1.0),
+            StatusKind::Slow => Color::new(0.3, 0.55, 0.95, 1.0),
+            StatusKind::Burn => Color::new(0.95, 0.4, 0.1, 1.0),
+            StatusKind::Regen => Color::new(0.25, 0.8, 0.35, 1.0),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StatusEffect {
+    pub kind: StatusKind,
+    /// Seconds left before the effect wears off.
+    pub remaining: f32,
+    // time until the next tick
+    tick_timer: f32,
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct StatusEffects {
+    pub active: Vec<StatusEffect>,
+}
+
+impl StatusEffects {
+    /// Start `kind` for `duration` seconds, or refresh it if already active.
+    pub fn apply(&mut self, kind: StatusKind, duration: f32) {
+        match self.active.iter_mut().find(|e| e.kind == kind) {
+            Some(effect) => effect.remaining = effect.remaining.max(duration),
+            None => self.active.push(StatusEffect { kind, remaining: duration, tick_timer: TICK_INTERVAL }),
+        }
+        println!("Status: {} for {:.0}s", kind.name(), duration);
+    }
+
+    pub fn has(&self, kind: StatusKind) -> bool {
+        self.active.iter().any(|e| e.kind == kind)
+    }
+
+    /// Remove one effect (e.g. an antidote curing poison).
+    pub fn cure(&mut self, kind: StatusKind) -> bool {
+        let before = self.active.len();
+        self.active.retain(|e| e.kind != kind);
+        self.active.len() != before
+    }
+
+    pub fn clear(&mut self) {
+        self.active.clear();
+    }
+
+    pub fn speed_multiplier(&self) -> f32 {
+        if self.has(StatusKind::Slow) { SLOW_FACTOR } else { 1.0 }
+    }
+
+    /// Advance all effects by `dt`. Returns the total HP change from ticks this frame.
+    pub fn update(&mut self, dt: f32) -> i32 {
+        let mut hp_delta = 0;
+        for effect in &mut self.active {
+            effect.remaining -= dt;
+            effect.tick_timer -= dt;
+            while effect.tick_timer <= 0.0 {
+                effect.tick_timer += TICK_INTERVAL;
+                hp_delta += effect.kind.hp_per_tick();
+            }
+        }
+        self.active.retain(|e| e.remaining > 0.0);
+        hp_delta
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn effects_tick_refresh_and_expire() {
+        let mut fx = StatusEffects::default();
+        fx.apply(StatusKind::Poison, 2.5);
+        fx.apply(StatusKind::Slow, 1.0);
+        assert_eq!(fx.speed_multiplier(), SLOW_FACTOR);
+        assert_eq!(fx.update(1.0), -1);
+        // slow has worn off, poison refreshed rather than stacked
+        assert!(!fx.has(StatusKind::Slow));
+        fx.apply(StatusKind::Poison, 3.0);
+        assert_eq!(fx.active.len(), 1);
+        assert_eq!(fx.update(3.0), -3);
+        assert!(fx.active.is_empty());
+    }
+}
@@ -0,0 +1,131 @@
+//! Sneaking past enemies.
+//!
+//! Enemies don't know where the player is until they notice them. Each one has an awareness
+//! that fills while the player is inside its detection radius (faster the closer they are)
+//! and drains once they're out of it. Part filled the enemy is suspicious and stops to look;
+//! full, it's alerted and gives chase until it has lost the player for a while.
+//!
+//! The radius is how far the enemy can see the player or hear them moving, whichever is
+//! further. Sneaking (Ctrl) halves the distance the player can be seen from and makes their
+//! steps near silent, at half walking speed; running carries furthest of all.
+
+/// How the player is moving (or would be, if standing still).
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Gait {
+    #[default]
+    Walk,
+    Run,
+    Sneak,
+}
+
+impl Gait {
+    /// Tiles away the player's steps can be heard from.
+    pub fn noise(self, moving: bool) -> f32 {
+        if !moving { return 0.0; }
+        match self {
+            Gait::Walk => 3.0,
+            Gait::Run => 6.0,
+            Gait::Sneak => 1.0,
+        }
+    }
+
+    /// Tiles away an enemy notices the player from, by sight or sound.
+    pub fn detection_radius(self, moving: bool) -> f32 {
+        let sight = if self == Gait::Sneak { SIGHT_TILES / 2.0 } else { SIGHT_TILES };
+        sight.max(self.noise(moving))
+    }
+}
+
+/// How far an enemy sees a player who isn't sneaking.
+const SIGHT_TILES: f32 = 4.0;
+/// Awareness gained per second at the edge of the radius; up close it's double.
+const NOTICE_RATE: f32 = 1.5;
+/// Awareness lost per second with the player out of range, so an alerted enemy gives up
+/// after this many seconds' worth of the meter.
+const FORGET_RATE: f32 = 0.2;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Alertness {
+    Unaware,
+    /// Noticed something; stands and looks.
+    Suspicious,
+    /// Knows where the player is and goes after them.
+    Alerted,
+}
+
+impl Alertness {
+    /// Mark drawn over the enemy's head, if any.
+    pub fn mark(self) -> Option<&'static str> {
+        match self {
+            Alertness::Unaware => None,
+            Alertness::Suspicious => Some("?"),
+            Alertness::Alerted => Some("!"),
+        }
+    }
+}
+
+/// One enemy's awareness of the player.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Awareness {
+    /// 0 to 1.
+    level: f32,
+    /// Reached 1 and hasn't drained back to 0 since.
+    alerted: bool,
+}
+
+impl Awareness {
+    /// The player is `distance` tiles away and can be noticed within `radius` tiles.
+    pub fn update(&mut self, dt: f32, distance: f32, radius: f32) {
+        if distance <= radius && radius > 0.0 {
+            self.level = (self.level + dt * NOTICE_RATE * (2.0 - distance / radius)).min(1.0);
+            self.alerted |= self.level >= 1.0;
+        } else {
+            self.level = (self.level - dt * FORGET_RATE).max(0.0);
+            self.alerted &= self.level > 0.0;
+        }
+    }
+
+    /// There's no mistaking being hit.
+    pub fn alert(&mut self) {
+        self.level = 1.0;
+        self.alerted = true;
+    }
+
+    pub fn state(&self) -> Alertness {
+        if self.alerted {
+            Alertness::Alerted
+        } else if self.level > 0.0 {
+            Alertness::Suspicious
+        } else {
+            Alertness::Unaware
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sneaking_gets_closer_and_alerted_enemies_give_up_slowly() {
+        assert!(Gait::Sneak.detection_radius(true) < Gait::Walk.detection_radius(false));
+        assert!(Gait::Run.detection_radius(true) > Gait::Walk.detection_radius(true));
+        assert_eq!(Gait::Run.noise(false), 0.0, "standing still makes no noise");
+
+        let mut awareness = Awareness::default();
+        // sneaking by just out of range goes unnoticed
+        awareness.update(1.0, 2.5, Gait::Sneak.detection_radius(true));
+        assert_eq!(awareness.state(), Alertness::Unaware);
+        awareness.update(0.2, 3.0, Gait::Walk.detection_radius(true));
+        assert_eq!(awareness.state(), Alertness::Suspicious);
+        awareness.update(1.0, 3.0, Gait::Walk.detection_radius(true));
+        assert_eq!(awareness.state(), Alertness::Alerted);
+        // out of sight it keeps looking for a while before calming down
+        awareness.update(2.0, 10.0, 4.0);
+        assert_eq!(awareness.state(), Alertness::Alerted);
+        awareness.update(4.0, 10.0, 4.0);
+        assert_eq!(awareness.state(), Alertness::Unaware);
+        awareness.alert();
+        assert_eq!(awareness.state(), Alertness::Alerted);
+    }
+}
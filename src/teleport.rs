@@ -0,0 +1,125 @@
+//! Linked teleport pads.
+//!
+//! Pads are listed in `assets/rooms/teleporters.txt`, one linked pair per line, in the same
+//! `key=value` spirit as the other data files:
+//!
+//! ```text
+//! # room,x,y=room,x,y
+//! 0,15,4=1,2,2
+//! ```
+//!
+//! Every link works both ways. Stepping onto a pad moves the player to the pad at the other end.
+//!
+//! Doorways between rooms are one-way exits instead, listed in the room they leave from with
+//! `exit=x,y:room,x,y` (see `parse_exits`): walking onto the tile takes the player to the other
+//! room's tile, which shouldn't be an exit itself.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh};
+
+use crate::map::TILE_SIZE;
+
+/// A pad's location: room index plus tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Pad {
+    pub room: usize,
+    pub tile: (i32, i32),
+}
+
+#[derive(Default)]
+pub struct Teleporters {
+    links: Vec<(Pad, Pad)>,
+}
+
+impl Teleporters {
+    /// Parse a teleporter data file. Malformed lines are reported and skipped.
+    pub fn from_text(text: &str) -> Teleporters {
+        let mut links = Vec::new();
+        for line in text.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let link = line.split_once('=').and_then(|(a, b)| Some((parse_pad(a)?, parse_pad(b)?)));
+            match link {
+                Some(link) => links.push(link),
+                None => println!("Teleporters: skipping bad line {:?}", line),
+            }
+        }
+        Teleporters { links }
+    }
+
+    /// Load `assets/rooms/teleporters.txt`, or no pads at all if the file is missing.
+    pub fn load(ctx: &Context) -> Teleporters {
+        match crate::assets::read_text(ctx, "/rooms/teleporters.txt") {
+            Some(text) => Teleporters::from_text(&text),
+            None => {
+                println!("Teleporters: no teleporter data");
+                Teleporters::default()
+            }
+        }
+    }
+
+    /// The pad linked to the one at `room`/`tile`, if there is a pad there.
+    pub fn destination(&self, room: usize, tile: (i32, i32)) -> Option<Pad> {
+        let here = Pad { room, tile };
+        self.links.iter().find_map(|&(a, b)| {
+            if a == here { Some(b) } else if b == here { Some(a) } else { None }
+        })
+    }
+
+    /// Draw every pad in `room` as a softly pulsing ring.
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, room: usize, scale: f32, offset: (f32, f32)) -> GameResult {
+        let t = ctx.time.time_since_start().as_secs_f32();
+        let pads = self.links.iter().flat_map(|&(a, b)| [a, b]).filter(|p| p.room == room);
+        for pad in pads {
+            let cx = offset.0 + (pad.tile.0 as f32 + 0.5) * TILE_SIZE * scale;
+            let cy = offset.1 + (pad.tile.1 as f32 + 0.5) * TILE_SIZE * scale;
+            let glow = 0.5 + 0.3 * (t * 3.0).sin();
+            let base = Mesh::new_circle(ctx, DrawMode::fill(), [cx, cy], TILE_SIZE * 0.42 * scale, 0.5, Color::new(0.25, 0.15, 0.45, 1.0))?;
+            canvas.draw(&base, DrawParam::new());
+            let ring = Mesh::new_circle(ctx, DrawMode::stroke(2.5 * scale), [cx, cy], TILE_SIZE * 0.32 * scale, 0.5, Color::new(0.7, 0.5, 1.0, glow))?;
+            canvas.draw(&ring, DrawParam::new());
+        }
+        Ok(())
+    }
+}
+
+/// A room data file's `exit=x,y:room,x,y` lines: each exit tile and where it leads.
+/// Malformed lines are reported and skipped.
+pub fn parse_exits(text: &str) -> Vec<((i32, i32), Pad)> {
+    let mut exits = Vec::new();
+    for line in text.lines().map(str::trim) {
+        let Some(value) = line.strip_prefix("exit=") else { continue };
+        let exit = value.split_once(':').and_then(|(at, to)| {
+            let (x, y) = at.split_once(',')?;
+            Some(((x.trim().parse().ok()?, y.trim().parse().ok()?), parse_pad(to)?))
+        });
+        match exit {
+            Some(exit) => exits.push(exit),
+            None => println!("Teleporters: skipping bad exit {:?}", line),
+        }
+    }
+    exits
+}
+
+/// "room,x,y"
+fn parse_pad(text: &str) -> Option<Pad> {
+    let mut parts = text.split(',').map(|p| p.trim().parse::<i32>().ok());
+    let (room, x, y) = (parts.next()??, parts.next()??, parts.next()??);
+    if parts.next().is_some() || room < 0 { return None; }
+    Some(Pad { room: room as usize, tile: (x, y) })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn links_work_both_ways_and_bad_lines_are_skipped() {
+        let tp = Teleporters::from_text("# pads\n0,15,4=1,2,2\n0,1=2\nnonsense\n");
+        assert_eq!(tp.links.len(), 1);
+        assert_eq!(tp.destination(0, (15, 4)), Some(Pad { room: 1, tile: (2, 2) }));
+        assert_eq!(tp.destination(1, (2, 2)), Some(Pad { room: 0, tile: (15, 4) }));
+        assert_eq!(tp.destination(1, (15, 4)), None);
+
+        let exits = parse_exits("name=Home\nexit=10,0:3,12,9\nexit=4:1\n");
+        assert_eq!(exits, vec![((10, 0), Pad { room: 3, tile: (12, 9) })]);
+    }
+}
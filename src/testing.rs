@@ -0,0 +1,165 @@
+//! Scripted input harness for logic tests.
+//!
+//! A `Harness` holds the windowless part of a game (the map and the player) and plays a script of
+//! held keys, key presses and idle time through it at a fixed 60 frames a second, with the same
+//! steps `Game` takes while playing (see `crate::world`): rooms tick, the player moves, and
+//! teleport pads carry them between rooms. Pressing Z uses the furniture, door or chest around
+//! the player. Tests then look at positions, the current room and what each press did.
+//!
+//! ```ignore
+//! let mut game = Harness::at((10, 2));
+//! game.run(&[Input::Walk(UP, 1), Input::Press(KeyCode::Z), Input::Walk(UP, 1)]);
+//! assert_eq!(game.player.tile(), (10, 0));
+//! ```
+
+use ggez::input::keyboard::KeyCode;
+use ggez::input::mouse::MouseButton;
+
+use crate::input::{Action, Controls};
+use crate::map::{Map, TileUse, TILE_SIZE};
+use crate::player::Player;
+use crate::teleport::Teleporters;
+use crate::world::{self, Used, View};
+
+/// Length of one frame.
+pub const DT: f32 = 1.0 / 60.0;
+/// Where the world is drawn for clicks: natural size, top-left corner.
+const VIEW: View = (1.0, (0.0, 0.0));
+
+pub const UP: (i32, i32) = (0, -1);
+pub const LEFT: (i32, i32) = (-1, 0);
+pub const RIGHT: (i32, i32) = (1, 0);
+
+pub enum Input {
+    /// Hold a direction for this many frames.
+    Hold((i32, i32), u32),
+    /// Walk this many tiles in a direction (or until stuck) and come to a stop.
+    Walk((i32, i32), u32),
+    /// Let go of everything for this many frames.
+    Idle(u32),
+    /// Tap a key between frames.
+    Press(KeyCode),
+    /// Left-click the middle of a tile, as the mouse handler would, to walk there.
+    Click((i32, i32)),
+}
+
+pub struct Harness {
+    pub player: Player,
+    pub map: Map,
+    /// What each press did, oldest first.
+    pub events: Vec<Used>,
+    teleport_armed: bool,
+}
+
+impl Harness {
+    /// The default world with the player standing on `tile` of the first room.
+    pub fn at(tile: (i32, i32)) -> Harness {
+        let mut player = Player::test_new();
+        player.warp_to(tile);
+        Harness { player, map: Map::new(), events: Vec::new(), teleport_armed: true }
+    }
+
+    /// Link teleport pads, from text in the `assets/rooms/teleporters.txt` format.
+    pub fn with_teleporters(mut self, text: &str) -> Harness {
+        self.map.set_teleporters(Teleporters::from_text(text));
+        self
+    }
+
+    pub fn run(&mut self, script: &[Input]) {
+        for input in script {
+            match *input {
+                Input::Hold(dir, frames) => (0..frames).for_each(|_| self.frame(Controls::holding(dir))),
+                Input::Walk(dir, tiles) => {
+                    let (x, y) = self.player.tile();
+                    let goal = (x + dir.0 * tiles as i32, y + dir.1 * tiles as i32);
+                    // hold until the last step is under way (letting go finishes it), giving up
+                    // well after the walk should be over; a tile takes 12 frames
+                    for _ in 0..(tiles + 1) * 30 {
+                        if self.heading_for() == goal { break; }
+                        self.frame(Controls::holding(dir));
+                    }
+                    while self.player.moving {
+                        self.frame(Controls::default());
+                    }
+                }
+                Input::Idle(frames) => (0..frames).for_each(|_| self.frame(Controls::default())),
+                Input::Press(key) => self.press(key),
+                Input::Click(tile) => self.click(tile),
+            }
+        }
+    }
+
+    fn frame(&mut self, controls: Controls) {
+        let step = world::step(&mut self.map, &mut self.player, controls, DT, &mut self.teleport_armed);
+        if let Some(pad) = step.pad {
+            self.map.set_current(pad.room);
+            self.player.warp_to(pad.tile);
+        }
+    }
+
+    /// A left click on the middle of `tile`, through the same steps as `Game`'s mouse handler,
+    /// with the world drawn at its natural size in the corner of the window.
+    fn click(&mut self, (x, y): (i32, i32)) {
+        let point = ((x as f32 + 0.5) * TILE_SIZE, (y as f32 + 0.5) * TILE_SIZE);
+        if let Some(tile) = world::click_target(MouseButton::Left, true, VIEW, point) {
+            world::walk_to(&self.map, &mut self.player, tile);
+        }
+    }
+
+    /// Tile the player is stepping onto, or standing on.
+    fn heading_for(&self) -> (i32, i32) {
+        if !self.player.moving { return self.player.tile(); }
+        let target = self.player.target / TILE_SIZE;
+        (target.x.round() as i32, target.y.round() as i32)
+    }
+
+    fn press(&mut self, key: KeyCode) {
+        if !Action::Confirm.matches(key) { return; }
+        if let Some(used) = world::confirm(&mut self.map, &mut self.player) {
+            self.events.push(used);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walk_to_the_door_open_it_and_walk_through() {
+        let mut game = Harness::at((10, 2));
+        game.run(&[Input::Hold(UP, 40)]);
+        assert_eq!(game.player.tile(), (10, 1), "the closed door stops the player");
+        game.run(&[Input::Press(KeyCode::Z)]);
+        assert_eq!(game.events, vec![Used::Tile(TileUse::Door { open: true })]);
+        assert!(game.map.is_door_open(10, 0));
+        game.run(&[Input::Walk(UP, 1)]);
+        assert_eq!(game.player.tile(), (10, 0), "through the doorway");
+    }
+
+    #[test]
+    fn pads_carry_the_player_down_to_the_cellar_and_back() {
+        let mut game = Harness::at((13, 4)).with_teleporters(include_str!("../assets/rooms/teleporters.txt"));
+        // holding the key carries the player straight over a pad; stopping on it teleports
+        game.run(&[Input::Hold(RIGHT, 60)]);
+        assert_eq!(game.map.current_room(), 0);
+        let mut game = Harness::at((13, 4)).with_teleporters(include_str!("../assets/rooms/teleporters.txt"));
+        game.run(&[Input::Walk(RIGHT, 2)]);
+        assert_eq!((game.map.current_room(), game.player.tile()), (1, (2, 2)));
+        // arriving doesn't bounce straight back; stepping off and on again does
+        game.run(&[Input::Idle(30)]);
+        assert_eq!(game.map.current_room(), 1);
+        game.run(&[Input::Walk(RIGHT, 1), Input::Walk(LEFT, 1)]);
+        assert_eq!((game.map.current_room(), game.player.tile()), (0, (15, 4)));
+    }
+
+    #[test]
+    fn clicking_walks_there_until_a_key_takes_over() {
+        let mut game = Harness::at((10, 2));
+        game.run(&[Input::Click((12, 4)), Input::Idle(120)]);
+        assert_eq!(game.player.tile(), (12, 4));
+        game.run(&[Input::Click((10, 2)), Input::Idle(6), Input::Hold(RIGHT, 1), Input::Idle(120)]);
+        assert_ne!(game.player.tile(), (10, 2), "holding a direction drops the path");
+        assert!(!game.player.walk_to(&game.map, (10, -1)), "off the map");
+    }
+}
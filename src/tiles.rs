@@ -0,0 +1,261 @@
+//! Tile kinds defined in `assets/tiles.ron`: how each one collides, how it's drawn, what using
+//! it does, what it sounds like underfoot and whether it gives off light.
+//!
+//! The file is a RON list with one entry per tile id; anything left out takes its default
+//! (walkable, plank floor, no sprite, no interaction, the room's floor sound, no light):
+//!
+//! ```text
+//! (id: "torch", solid: Solid, wall: Wall, base: Wall, sprite: "torch", light: 4.0),
+//! ```
+//!
+//! With a tileset image (see `crate::tileset`), `atlas: n` draws a tile's base from cell `n`
+//! of the sheet in place of the plank or wall image.
+//!
+//! A tile can also list `variants`: decorations (knots, worn patches, cracks, or any sprite)
+//! of which each tile of that kind gets one, picked from its position so a floor doesn't repeat
+//! exactly and looks the same every time. Repeat `""` in the list to leave more tiles plain.
+//!
+//! The built-in tiles (walls, doors, chests, blocks and the rest) keep their game logic in code,
+//! but their entries here can be changed, and a mod's `tiles.ron` only needs the entries it
+//! changes or adds. Ids that aren't built in are new tiles, placed from room data with
+//! `tile=x,y:id` (or `tile=x,y:id:text` for ones that are read).
+
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+
+use ggez::Context;
+use serde::Deserialize;
+
+use crate::footsteps::Surface;
+
+/// The game's own tile data, also the fallback for anything a mod's file leaves out.
+const BUILTIN_TILES: &str = include_str!("../assets/tiles.ron");
+
+/// Whether a tile stops whoever walks into it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum Solidity {
+    #[default]
+    Open,
+    Solid,
+    /// Passable except for a thin frame at the sides it's walked through between (open doors).
+    Frame,
+}
+
+/// How a tile counts when the walls around it are drawn.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum WallJoin {
+    #[default]
+    None,
+    /// A gap in the wall (open doors): floors get an edge against it and walls line up with it,
+    /// but no corner joints are drawn onto it.
+    Opening,
+    /// Part of the wall.
+    Wall,
+}
+
+/// What's drawn underneath a tile's sprite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum Base {
+    /// Just the sprite (water, pits).
+    None,
+    #[default]
+    Plank,
+    /// Plank with a dark edge wherever it meets a wall.
+    Floor,
+    /// A wall plank turned to run along the wall.
+    Wall,
+}
+
+/// What using a tile does. Doors, chests and crystals only do anything on the built-in tiles,
+/// which hold the state they work on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+pub enum Action {
+    Door,
+    Chest,
+    Save,
+    /// Show the tile's text from room data.
+    Read,
+    Sit,
+    Camp,
+    /// Fast travel shrine, named by its text from room data.
+    Shrine,
+    Search,
+}
+
+/// The sound of a step on a tile.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Default, Deserialize)]
+pub enum Footstep {
+    /// Whatever the room's floor is made of.
+    #[default]
+    Floor,
+    Silent,
+    Wood,
+    Stone,
+    Grass,
+}
+
+impl Footstep {
+    pub fn surface(self, floor: Surface) -> Option<Surface> {
+        match self {
+            Footstep::Floor => Some(floor),
+            Footstep::Silent => None,
+            Footstep::Wood => Some(Surface::Wood),
+            Footstep::Stone => Some(Surface::Stone),
+            Footstep::Grass => Some(Surface::Grass),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+pub struct TileDef {
+    pub id: String,
+    #[serde(default)]
+    pub solid: Solidity,
+    #[serde(default)]
+    pub wall: WallJoin,
+    #[serde(default)]
+    pub base: Base,
+    /// Drawn over the base: one of the painted sprites in `GridRoom`, or else an image name
+    /// (see `Assets::sprite`). Empty for none.
+    #[serde(default)]
+    pub sprite: String,
+    /// Cell of the tileset image drawn as the base, when there's a tileset.
+    #[serde(default)]
+    pub atlas: Option<u32>,
+    #[serde(default)]
+    pub interaction: Option<Action>,
+    #[serde(default)]
+    pub footstep: Footstep,
+    /// Reach of its light, in tiles.
+    #[serde(default)]
+    pub light: Option<f32>,
+    /// Sprites drawn over the base, one picked per tile by position; `""` for none.
+    #[serde(default)]
+    pub variants: Vec<String>,
+}
+
+impl TileDef {
+    /// The variant drawn on this kind of tile at `x`,`y`, if any.
+    pub fn variant(&self, x: usize, y: usize) -> Option<&str> {
+        if self.variants.is_empty() { return None; }
+        let variant = &self.variants[spot_hash(x, y) as usize % self.variants.len()];
+        Some(variant.as_str()).filter(|v| !v.is_empty())
+    }
+}
+
+/// A well-mixed number for a tile position: looks random from tile to tile, but is always the
+/// same for the same spot.
+pub fn spot_hash(x: usize, y: usize) -> u32 {
+    let mut h = (x as u32).wrapping_mul(0x8da6_b343) ^ (y as u32).wrapping_mul(0xd816_3841);
+    h ^= h >> 16;
+    h = h.wrapping_mul(0x7feb_352d);
+    h ^= h >> 15;
+    h = h.wrapping_mul(0x846c_a68b);
+    h ^ (h >> 16)
+}
+
+pub struct TileRegistry {
+    defs: Vec<TileDef>,
+    index: HashMap<String, usize>,
+}
+
+impl TileRegistry {
+    fn parse(text: &str) -> Result<Vec<TileDef>, ron::error::SpannedError> {
+        // optional fields take a bare value (`light: 4.0`), with or without the file's
+        // `#![enable(implicit_some)]`
+        ron::Options::default().with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME).from_str(text)
+    }
+
+    fn from_defs(defs: Vec<TileDef>) -> TileRegistry {
+        let mut registry = TileRegistry { defs: Vec::new(), index: HashMap::new() };
+        for def in defs {
+            if def.id.is_empty() {
+                println!("Tiles: skipping a tile with no id");
+                continue;
+            }
+            match registry.index.get(&def.id) {
+                Some(&i) => registry.defs[i] = def,
+                None => {
+                    registry.index.insert(def.id.clone(), registry.defs.len());
+                    registry.defs.push(def);
+                }
+            }
+        }
+        registry
+    }
+
+    /// The game's own tiles.
+    pub fn builtin() -> Arc<TileRegistry> {
+        static BUILTIN: OnceLock<Arc<TileRegistry>> = OnceLock::new();
+        BUILTIN.get_or_init(|| Arc::new(TileRegistry::from_defs(TileRegistry::parse(BUILTIN_TILES).expect("assets/tiles.ron should parse")))).clone()
+    }
+
+    /// Tile data from `text`, laid over the built-in tiles. If it can't be read at all the
+    /// built-in tiles are used as they are.
+    pub fn from_text(text: &str) -> TileRegistry {
+        let mut defs = TileRegistry::builtin().defs.clone();
+        match TileRegistry::parse(text) {
+            Ok(changed) => defs.extend(changed),
+            Err(e) => println!("Tiles: can't read tile data ({}), using the built-in tiles", e),
+        }
+        TileRegistry::from_defs(defs)
+    }
+
+    /// Load `assets/tiles.ron` (or a mod's), falling back to the built-in tiles.
+    pub fn load(ctx: &Context) -> TileRegistry {
+        match crate::assets::read_text(ctx, "/tiles.ron") {
+            Some(text) => TileRegistry::from_text(&text),
+            None => TileRegistry::from_defs(TileRegistry::builtin().defs.clone()),
+        }
+    }
+
+    /// Position of a tile id in the registry.
+    pub fn position(&self, id: &str) -> Option<usize> {
+        self.index.get(id).copied()
+    }
+
+    /// The tile at `index`, or the first one (plain floor) if there's no such tile.
+    pub fn get(&self, index: usize) -> &TileDef {
+        self.defs.get(index).unwrap_or(&self.defs[0])
+    }
+
+    pub fn find(&self, id: &str) -> &TileDef {
+        self.get(self.position(id).unwrap_or(0))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_builtin_file_reads_and_starts_with_floor() {
+        let tiles = TileRegistry::builtin();
+        assert_eq!(tiles.get(0).id, "floor");
+        let torch = tiles.find("torch");
+        assert_eq!((torch.solid, torch.wall, torch.light), (Solidity::Solid, WallJoin::Wall, Some(4.0)));
+        assert_eq!(tiles.find("sign").interaction, Some(Action::Read));
+        assert_eq!(tiles.find("pit").footstep.surface(Surface::Wood), None);
+        assert_eq!((tiles.find("floor").atlas, tiles.find("wall").atlas), (Some(0), Some(1)), "cells of the shipped tileset.png");
+    }
+
+    #[test]
+    fn mod_files_change_and_add_tiles_over_the_builtin_ones() {
+        let tiles = TileRegistry::from_text(r#"[(id: "torch", solid: Solid, wall: Wall, base: Wall, sprite: "torch", light: 7.0), (id: "lantern", solid: Solid, sprite: "torch", light: 2.0)]"#);
+        assert_eq!(tiles.find("torch").light, Some(7.0));
+        assert_eq!(tiles.find("lantern").base, Base::Plank);
+        assert!(tiles.position("wall").is_some(), "untouched tiles are kept");
+        let broken = TileRegistry::from_text("[(id: \"x\", solid: Maybe)]");
+        assert!(broken.position("x").is_none() && broken.position("wall").is_some());
+    }
+
+    #[test]
+    fn variants_stay_put_and_share_the_floor() {
+        let floor = TileDef { variants: vec!["".into(), "".into(), "knot".into(), "cracks".into()], ..TileRegistry::builtin().find("floor").clone() };
+        let picks: Vec<Option<&str>> = (0..20).flat_map(|y| (0..20).map(move |x| (x, y))).map(|(x, y)| floor.variant(x, y)).collect();
+        assert_eq!(picks, (0..20).flat_map(|y| (0..20).map(move |x| (x, y))).map(|(x, y)| floor.variant(x, y)).collect::<Vec<_>>());
+        let count = |v: Option<&str>| picks.iter().filter(|p| **p == v).count();
+        assert!(count(None) > count(Some("knot")) && count(Some("knot")) > 50 && count(Some("cracks")) > 50, "{} {} {}", count(None), count(Some("knot")), count(Some("cracks")));
+        assert_ne!(spot_hash(1, 0), spot_hash(0, 1));
+    }
+}
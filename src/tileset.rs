@@ -0,0 +1,62 @@
+//! Tileset atlas: one image holding many tiles, each addressed by its index.
+//!
+//! `assets/tileset.png` (or a mod's) is cut into `TILE_SIZE` squares, numbered left to right
+//! and then top to bottom from 0. A tile kind with `atlas: n` in `tiles.ron` has cell `n` as
+//! its base, in place of the plank or log wall image: it's still mirrored, shaded or turned the
+//! same way, and its sprite is drawn over it. The shipped sheet holds the plank floor (0) and
+//! the log wall (1). Without a tileset (or with an index past its end) tiles are drawn the
+//! usual way.
+
+use ggez::graphics::{Canvas, DrawParam, Image, Rect};
+
+use crate::assets::TILE_SIZE;
+
+pub const PATH: &str = "/tileset.png";
+
+pub struct Tileset {
+    image: Image,
+    columns: u32,
+    rows: u32,
+}
+
+impl Tileset {
+    /// Cut `image` into tiles; None if it's too small to hold even one.
+    pub fn new(image: Image) -> Option<Tileset> {
+        let (columns, rows) = (image.width() / TILE_SIZE as u32, image.height() / TILE_SIZE as u32);
+        if columns == 0 || rows == 0 {
+            println!("Tileset: {} is smaller than one tile, ignoring it", PATH);
+            return None;
+        }
+        Some(Tileset { image, columns, rows })
+    }
+
+    /// Draw cell `index` with `param`, whose scale is relative to one `TILE_SIZE` cell.
+    /// Returns false (drawing nothing) if the sheet has no such cell.
+    pub fn draw(&self, canvas: &mut Canvas, index: u32, param: DrawParam) -> bool {
+        let Some(src) = cell(index, self.columns, self.rows) else { return false };
+        canvas.draw(&self.image, param.src(src));
+        true
+    }
+}
+
+/// Where cell `index` of a `columns` x `rows` sheet is, as a fraction of the whole image
+/// (which is what `DrawParam::src` takes).
+pub fn cell(index: u32, columns: u32, rows: u32) -> Option<Rect> {
+    if columns == 0 || index >= columns.saturating_mul(rows) { return None; }
+    let (w, h) = (1.0 / columns as f32, 1.0 / rows as f32);
+    Some(Rect::new((index % columns) as f32 * w, (index / columns) as f32 * h, w, h))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cells_run_along_rows_and_stop_at_the_end_of_the_sheet() {
+        assert_eq!(cell(0, 4, 2), Some(Rect::new(0.0, 0.0, 0.25, 0.5)));
+        assert_eq!(cell(5, 4, 2), Some(Rect::new(0.25, 0.5, 0.25, 0.5)));
+        assert_eq!(cell(7, 4, 2), Some(Rect::new(0.75, 0.5, 0.25, 0.5)));
+        assert_eq!(cell(8, 4, 2), None);
+        assert_eq!(cell(0, 0, 0), None);
+    }
+}
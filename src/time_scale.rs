@@ -0,0 +1,155 @@
+//! Global time scale for game logic: slow motion, fast forward and a hard pause.
+//!
+//! Scenes are updated with the frame's time multiplied by the scale; music, toasts, screen
+//! transitions and the speedrun clock keep real time. The debug scale is set with F5 (slower),
+//! F6 (faster), F7 (pause) and F8 (back to 1x) in debug builds or with `--debug`, or with
+//! `--time-scale` at launch. Dramatic moments layer a short `slow_motion` on top of it, timed in
+//! real seconds so it always lasts as long.
+//!
+//! Pausing freezes the scenes outright, so they aren't updated at all while the screen keeps
+//! drawing; F10 then runs exactly one `STEP_SECS` update at a time, for watching collisions and
+//! animations frame by frame.
+
+/// Debug speeds F5 and F6 step through.
+pub const STEPS: [f32; 6] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0];
+/// Game time one F10 step advances while paused: a frame at 60 fps.
+pub const STEP_SECS: f32 = 1.0 / 60.0;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct SlowMotion {
+    scale: f32,
+    /// Real seconds left.
+    left: f32,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TimeScale {
+    debug: f32,
+    paused: bool,
+    /// Single steps asked for while paused and not yet run.
+    steps: u32,
+    slow_motion: Option<SlowMotion>,
+}
+
+impl Default for TimeScale {
+    fn default() -> TimeScale {
+        TimeScale { debug: 1.0, paused: false, steps: 0, slow_motion: None }
+    }
+}
+
+impl TimeScale {
+    /// Start at a debug speed, clamped to the slowest and fastest steps.
+    pub fn at(scale: f32) -> TimeScale {
+        TimeScale { debug: scale.clamp(STEPS[0], STEPS[STEPS.len() - 1]), ..TimeScale::default() }
+    }
+
+    /// How fast game time runs right now.
+    pub fn scale(&self) -> f32 {
+        if self.paused { return 0.0; }
+        self.debug * self.slow_motion.map_or(1.0, |slow| slow.scale)
+    }
+
+    /// Advance by a real frame of `dt` seconds; returns the game time that passed, or None when
+    /// paused with no step to run, in which case nothing should be updated.
+    pub fn update(&mut self, dt: f32) -> Option<f32> {
+        if self.paused {
+            if self.steps == 0 { return None; }
+            self.steps -= 1;
+            return Some(STEP_SECS);
+        }
+        let scaled = dt * self.scale();
+        if let Some(slow) = &mut self.slow_motion {
+            slow.left -= dt;
+            if slow.left <= 0.0 {
+                self.slow_motion = None;
+            }
+        }
+        Some(scaled)
+    }
+
+    /// Run at `scale` for `secs` real seconds, e.g. for the final blow on a room.
+    pub fn slow_motion(&mut self, scale: f32, secs: f32) {
+        self.slow_motion = Some(SlowMotion { scale: scale.max(0.0), left: secs });
+    }
+
+    /// Next debug step down (`faster` false) or up.
+    pub fn step(&mut self, faster: bool) {
+        let next = if faster {
+            STEPS.iter().find(|&&s| s > self.debug)
+        } else {
+            STEPS.iter().rev().find(|&&s| s < self.debug)
+        };
+        if let Some(&next) = next {
+            self.debug = next;
+        }
+        println!("TimeScale: {}x", self.debug);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        self.paused = !self.paused;
+        self.steps = 0;
+        println!("TimeScale: {}", if self.paused { "paused" } else { "resumed" });
+    }
+
+    /// Run one `STEP_SECS` update on the next frame; only while paused.
+    pub fn step_frame(&mut self) {
+        if self.paused {
+            self.steps += 1;
+        }
+    }
+
+    /// Back to normal speed, unpaused.
+    pub fn reset(&mut self) {
+        *self = TimeScale::default();
+        println!("TimeScale: 1x");
+    }
+
+    /// Corner label while the debug speed isn't normal; None at 1x.
+    pub fn label(&self) -> Option<String> {
+        if self.paused { return Some("PAUSED".to_string()); }
+        (self.debug != 1.0).then(|| format!("x{}", self.debug))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn steps_pause_and_slow_motion_scale_game_time() {
+        let mut time = TimeScale::default();
+        assert_eq!(time.update(0.1), Some(0.1));
+        time.step(true);
+        time.step(true);
+        assert_eq!((time.update(0.1), time.label()), (Some(0.4), Some("x4".to_string())));
+        for _ in 0..5 {
+            time.step(true);
+        }
+        assert_eq!(time.scale(), 8.0, "the fastest step is the limit");
+        time.toggle_pause();
+        assert_eq!(time.update(0.1), None);
+        time.reset();
+        time.slow_motion(0.25, 0.5);
+        assert_eq!(time.update(0.4), Some(0.1));
+        assert_eq!(time.update(0.2), Some(0.05), "still slow for the rest of its last frame");
+        assert_eq!(time.update(0.2), Some(0.2));
+        assert_eq!(TimeScale::at(100.0).scale(), 8.0);
+    }
+
+    #[test]
+    fn paused_time_only_moves_one_step_at_a_time() {
+        let mut time = TimeScale::at(4.0);
+        time.step_frame();
+        assert_eq!(time.update(0.1), Some(0.4), "stepping does nothing unless paused");
+        time.toggle_pause();
+        assert_eq!(time.update(0.1), None);
+        time.step_frame();
+        time.step_frame();
+        assert_eq!(time.update(0.1), Some(STEP_SECS));
+        assert_eq!(time.update(0.1), Some(STEP_SECS), "each press is one step");
+        assert_eq!(time.update(0.1), None);
+        time.step_frame();
+        time.toggle_pause();
+        assert_eq!(time.update(0.1), Some(0.4), "unpausing drops steps not yet run");
+    }
+}
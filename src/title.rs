@@ -2,13 +2,13 @@
 //!
 //! This module focuses on a tiny, well-documented API for a title card.
 //! Edit the `default_title` / `default_subtitle` variables below or call
-//! `TitleScreen::from_file` at runtime to load text from disk.
+//! `TitleScreen::from_text` at runtime with the contents of `title.txt`.
 
 use ggez::{Context, GameResult};
 use ggez::graphics::{Canvas, Color, Text, TextFragment, DrawParam, PxScale};
 use ggez::input::keyboard::{KeyInput, KeyCode};
-use std::path::Path;
-use std::fs;
+use crate::assets::FontRole;
+use crate::input::Action;
 
 /// Title screen representation. Small, focused responsibilities:
 /// - holds the strings to render
@@ -27,6 +27,27 @@ pub struct TitleScreen {
     /// Layout offsets (you can tweak these instead of hardcoding in draw)
     pub title_offset: [f32; 2],
     pub subtitle_offset: [f32; 2],
+
+    /// Highlighted entry of `MENU`.
+    pub selected: usize,
+    /// Active player profile, shown in the corner.
+    pub profile_name: Option<String>,
+}
+
+/// Title menu entries, in display order.
+const MENU: [(&str, TitleAction); 4] = [
+    ("Start", TitleAction::Start),
+    ("Achievements", TitleAction::Achievements),
+    ("Profiles", TitleAction::Profiles),
+    ("Credits", TitleAction::Credits),
+];
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TitleAction {
+    Start,
+    Achievements,
+    Profiles,
+    Credits,
 }
 
 impl TitleScreen {
@@ -39,18 +60,19 @@ impl TitleScreen {
             subtitle_scale: 24.0,
             title_offset: [-200.0, -40.0],
             subtitle_offset: [-100.0, 10.0],
+            selected: 0,
+            profile_name: None,
         }
     }
 
-    /// Load a title and subtitle from a UTF-8 text file.
-    /// File format: first non-empty line is title, next non-empty line is subtitle.
-    /// Returns None if the file can't be read.
-    pub fn from_file<P: AsRef<Path>>(path: P) -> Option<TitleScreen> {
-        let s = fs::read_to_string(path).ok()?;
+    /// Build a title and subtitle from UTF-8 text (usually `title.txt` read through
+    /// `assets::read_text`, so it works from loose files or the data archive).
+    /// Format: first non-empty line is title, next non-empty line is subtitle.
+    pub fn from_text(s: &str) -> TitleScreen {
         let mut lines = s.lines().map(str::trim).filter(|l| !l.is_empty());
         let title = lines.next().unwrap_or("").to_string();
         let subtitle = lines.next().unwrap_or("").to_string();
-        Some(TitleScreen::new(title, subtitle))
+        TitleScreen::new(title, subtitle)
     }
 
     /// Draw the title screen. Keep this method simple — layout is tunable via fields.
@@ -71,29 +93,44 @@ impl TitleScreen {
         }
 
     // Build Text objects using TextFragment and PxScale. Use the font registered in assets.
-    let font_opt = if assets.title_font_name.is_empty() { None } else { Some(assets.title_font_name.clone()) };
-    let title = Text::new(TextFragment { text: self.title.clone(), font: font_opt.clone(), scale: Some(PxScale::from(self.title_scale)), color: None });
-    let subtitle = Text::new(TextFragment { text: self.subtitle.clone(), font: font_opt.clone(), scale: Some(PxScale::from(self.subtitle_scale)), color: None });
+    let (title_font, body_font) = (assets.font(FontRole::Title), assets.font(FontRole::Body));
+    let title = Text::new(TextFragment { text: self.title.clone(), font: title_font, scale: Some(PxScale::from(self.title_scale)), color: None });
+    let subtitle = Text::new(TextFragment { text: crate::input::prompt(&self.subtitle), font: body_font.clone(), scale: Some(PxScale::from(self.subtitle_scale)), color: None });
 
         // Positioning: centered + offsets; place near top if offsets indicate that
         canvas.draw(&title, DrawParam::new().dest([w / 2.0 + self.title_offset[0], h / 6.0 + self.title_offset[1]]).color(Color::WHITE));
         canvas.draw(&subtitle, DrawParam::new().dest([w / 2.0 + self.subtitle_offset[0], h / 6.0 + self.subtitle_offset[1] + 60.0]).color(Color::WHITE));
+
+        // menu entries in the lower third, selected one highlighted
+        for (i, (label, _)) in MENU.iter().enumerate() {
+            let color = if i == self.selected { crate::palette::selection() } else { Color::WHITE };
+            let marker = if i == self.selected { "> " } else { "  " };
+            let entry = Text::new(TextFragment { text: format!("{}{}", marker, label), font: body_font.clone(), scale: Some(PxScale::from(28.0)), color: None });
+            canvas.draw(&entry, DrawParam::new().dest([w / 2.0, h * 0.65 + i as f32 * 40.0]).offset([0.5, 0.0]).color(color));
+        }
+
+        if let Some(name) = &self.profile_name {
+            let profile = Text::new(TextFragment::new(format!("Profile: {}", name)).scale(PxScale::from(18.0)));
+            canvas.draw(&profile, DrawParam::new().dest([20.0, h - 36.0]).color(Color::WHITE));
+        }
         Ok(())
     }
 
-    /// Simple input handler: return true when the player pressed start
-    /// (Z key is the global confirm key). Keep this thin so the `Game` state machine decides what
+    /// Simple input handler: Up/Down move through the menu and Z (the global confirm key)
+    /// returns the chosen entry. Keep this thin so the `Game` state machine decides what
     /// to do next.
-    pub fn handle_input(&self, input: KeyInput) -> bool {
-        if let Some(k) = input.keycode {
-            return k == KeyCode::Z;
+    pub fn handle_input(&mut self, input: KeyInput) -> Option<TitleAction> {
+        match input.keycode? {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); None }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(MENU.len() - 1); None }
+            k if Action::Confirm.matches(k) => Some(MENU[self.selected].1),
+            _ => None,
         }
-        false
     }
 
     /// Default title used when no file is present. Edit these if you want quick changes.
     pub fn default() -> TitleScreen {
-    let mut s = TitleScreen::new("TALE", "Press Z to begin");
+    let mut s = TitleScreen::new("TALE", "Press {confirm} to choose");
         // EDIT POINT: increase scale for epic lettering; change this if too large
         s.title_scale = 96.0;
         s.subtitle_scale = 20.0;
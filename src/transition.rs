@@ -0,0 +1,181 @@
+//! Screen transitions: cover the screen, switch what's behind it, uncover it again.
+//!
+//! A transition carries a "cut" (whatever the game wants to switch: a state, a room) and
+//! hands it back once the screen is fully covered, so the switch itself is never seen. The
+//! cover is a fade to black, a black wipe that sweeps in from the left and leaves to the right,
+//! or (into battles) a spiral of black blocks winding in from the edges and unwinding again.
+//! Transitions run on the frame's `dt`, so replays see them end on the same frame.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{Canvas, Color, DrawMode, DrawParam, Mesh, MeshBuilder, Rect};
+
+/// Seconds to cover the screen, and again to uncover it.
+pub const HALF_SECS: f32 = 0.3;
+/// Blocks across and down the screen for the spiral.
+const SPIRAL_GRID: (usize, usize) = (16, 12);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Style {
+    Fade,
+    Wipe,
+    Spiral,
+}
+
+/// Every cell of a `cols` x `rows` grid, clockwise from the top-left corner and winding inwards.
+fn spiral_order(cols: usize, rows: usize) -> Vec<(usize, usize)> {
+    let mut order = Vec::with_capacity(cols * rows);
+    let (mut left, mut top, mut right, mut bottom) = (0, 0, cols, rows);
+    while left < right && top < bottom {
+        order.extend((left..right).map(|x| (x, top)));
+        order.extend((top + 1..bottom).map(|y| (right - 1, y)));
+        if bottom - top > 1 {
+            order.extend((left..right - 1).rev().map(|x| (x, bottom - 1)));
+        }
+        if right - left > 1 {
+            order.extend((top + 1..bottom - 1).rev().map(|y| (left, y)));
+        }
+        (left, top, right, bottom) = (left + 1, top + 1, right - 1, bottom - 1);
+    }
+    order
+}
+
+struct Running<C> {
+    style: Style,
+    elapsed: f32,
+    /// Handed back at the midpoint; `None` once it has been.
+    cut: Option<C>,
+}
+
+pub struct Transitions<C> {
+    running: Option<Running<C>>,
+}
+
+impl<C> Transitions<C> {
+    pub fn new() -> Transitions<C> {
+        Transitions { running: None }
+    }
+
+    /// Cover the screen and hand `cut` back from `update` once it's hidden. If a cut is
+    /// already waiting, `cut` comes straight back for the caller to apply at once.
+    pub fn start(&mut self, style: Style, cut: C) -> Option<C> {
+        if self.covering() { return Some(cut); }
+        self.running = Some(Running { style, elapsed: 0.0, cut: Some(cut) });
+        None
+    }
+
+    /// Start from a covered screen and just uncover it, for switches that already happened.
+    pub fn reveal(&mut self, style: Style) {
+        if self.covering() { return; }
+        self.running = Some(Running { style, elapsed: HALF_SECS, cut: None });
+    }
+
+    /// Jump a waiting transition to its midpoint and take its cut; the screen uncovers from
+    /// there as usual.
+    pub fn skip(&mut self) -> Option<C> {
+        let running = self.running.as_mut()?;
+        running.elapsed = running.elapsed.max(HALF_SECS);
+        running.cut.take()
+    }
+
+    /// Advance by `dt`, returning the cut on the frame the screen becomes fully covered.
+    pub fn update(&mut self, dt: f32) -> Option<C> {
+        let running = self.running.as_mut()?;
+        running.elapsed += dt;
+        let cut = if running.elapsed >= HALF_SECS { running.cut.take() } else { None };
+        if running.elapsed >= HALF_SECS * 2.0 {
+            self.running = None;
+        }
+        cut
+    }
+
+    /// True while the screen is still closing in on a cut; the game holds still meanwhile.
+    pub fn covering(&self) -> bool {
+        self.running.as_ref().is_some_and(|r| r.cut.is_some())
+    }
+
+    /// How much of the screen is covered, 0 to 1.
+    fn cover(&self) -> f32 {
+        self.running.as_ref().map_or(0.0, |r| {
+            let progress = r.elapsed / HALF_SECS;
+            if progress < 1.0 { progress } else { (2.0 - progress).max(0.0) }
+        })
+    }
+
+    /// Draw the cover over the whole window.
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas) -> GameResult {
+        let Some(running) = &self.running else { return Ok(()) };
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let cover = self.cover();
+        let (rect, color) = match running.style {
+            Style::Fade => (Rect::new(0.0, 0.0, w, h), Color::new(0.0, 0.0, 0.0, cover)),
+            // in from the left while covering, out to the right while uncovering
+            Style::Wipe if running.cut.is_some() => (Rect::new(0.0, 0.0, w * cover, h), Color::BLACK),
+            Style::Wipe => (Rect::new(w * (1.0 - cover), 0.0, w * cover, h), Color::BLACK),
+            Style::Spiral => return draw_spiral(ctx, canvas, w, h, cover),
+        };
+        if rect.w <= 0.0 { return Ok(()); }
+        let mesh = Mesh::new_rectangle(ctx, DrawMode::fill(), rect, color)?;
+        canvas.draw(&mesh, DrawParam::new());
+        Ok(())
+    }
+}
+
+/// The spiral cover: blocks land in spiral order, and the last ones in are the first to go.
+fn draw_spiral(ctx: &mut Context, canvas: &mut Canvas, w: f32, h: f32, cover: f32) -> GameResult {
+    let (cols, rows) = SPIRAL_GRID;
+    let (cell_w, cell_h) = (w / cols as f32, h / rows as f32);
+    let shown = (cover * (cols * rows) as f32).round() as usize;
+    if shown == 0 { return Ok(()); }
+    let mut blocks = MeshBuilder::new();
+    for (x, y) in spiral_order(cols, rows).into_iter().take(shown) {
+        // a hair oversized so no seams show between blocks
+        blocks.rectangle(DrawMode::fill(), Rect::new(x as f32 * cell_w, y as f32 * cell_h, cell_w + 1.0, cell_h + 1.0), Color::BLACK)?;
+    }
+    canvas.draw(&Mesh::from_data(ctx, blocks.build()), DrawParam::new());
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_cut_arrives_once_the_screen_is_covered() {
+        let mut transitions = Transitions::new();
+        assert_eq!(transitions.start(Style::Fade, "title"), None);
+        assert_eq!(transitions.start(Style::Fade, "credits"), Some("credits"), "one cut at a time");
+        assert_eq!(transitions.update(HALF_SECS * 0.5), None);
+        assert!(transitions.covering());
+        assert!((transitions.cover() - 0.5).abs() < 1e-4);
+        assert_eq!(transitions.update(HALF_SECS * 0.6), Some("title"));
+        assert!(!transitions.covering());
+        assert!(transitions.cover() > 0.8, "still dark right after the cut");
+        assert_eq!(transitions.update(HALF_SECS), None);
+        assert_eq!(transitions.cover(), 0.0);
+    }
+
+    #[test]
+    fn skipping_and_revealing_start_from_black() {
+        let mut transitions = Transitions::new();
+        transitions.start(Style::Wipe, 7);
+        assert_eq!(transitions.skip(), Some(7));
+        assert_eq!(transitions.cover(), 1.0);
+        assert_eq!(transitions.update(HALF_SECS * 2.0), None);
+        transitions.reveal(Style::Fade);
+        assert_eq!(transitions.cover(), 1.0);
+        assert!(!transitions.covering());
+    }
+
+    #[test]
+    fn the_spiral_covers_every_cell_once_from_the_edge_in() {
+        let order = spiral_order(4, 3);
+        assert_eq!(order.len(), 12);
+        assert_eq!(&order[..6], &[(0, 0), (1, 0), (2, 0), (3, 0), (3, 1), (3, 2)]);
+        assert_eq!(&order[10..], &[(1, 1), (2, 1)], "the middle row comes last");
+        let mut sorted = spiral_order(5, 5);
+        sorted.sort();
+        sorted.dedup();
+        assert_eq!(sorted.len(), 25);
+    }
+}
@@ -0,0 +1,161 @@
+//! One-time hints that explain a mechanic the first time the player runs into it: facing a
+//! door, meeting an enemy, picking something up...
+//!
+//! Each hint shows once per player profile, ever. The ones already seen are kept in the
+//! profile's folder as `tutorial.txt`, in the same `key=value` format as the other player
+//! files. Hints can be turned off in the Gameplay options.
+
+use std::fs;
+use std::path::PathBuf;
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, DrawParam, Rect, Text, TextFragment};
+
+/// How long a hint stays up.
+const HINT_SECS: f32 = 6.0;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Hint {
+    /// The first steps.
+    Run,
+    /// Facing a door, chest or sign.
+    Interact,
+    /// Facing someone.
+    Talk,
+    /// At the bed or a save crystal.
+    Save,
+    /// An enemy close by.
+    Attack,
+    /// The first thing picked up.
+    Inventory,
+}
+
+impl Hint {
+    pub const ALL: [Hint; 6] = [Hint::Run, Hint::Interact, Hint::Talk, Hint::Save, Hint::Attack, Hint::Inventory];
+
+    /// Name used in tutorial.txt.
+    fn key(self) -> &'static str {
+        match self {
+            Hint::Run => "run",
+            Hint::Interact => "interact",
+            Hint::Talk => "talk",
+            Hint::Save => "save",
+            Hint::Attack => "attack",
+            Hint::Inventory => "inventory",
+        }
+    }
+
+    /// What the hint says, with its keys as `crate::input::prompt` placeholders; running reads
+    /// the other way round with auto-run on.
+    pub fn text(self, auto_run: bool) -> &'static str {
+        match self {
+            Hint::Run if auto_run => "Hold {run} to walk instead of running.",
+            Hint::Run => "Hold {run} to run.",
+            Hint::Interact => "Press {confirm} to open doors and chests, or read signs.",
+            Hint::Talk => "Press {confirm} to talk.",
+            Hint::Save => "Press {confirm} here to save your game.",
+            Hint::Attack => "Press {attack} to attack, or {throw} to throw.",
+            Hint::Inventory => "Press {inventory} to open your inventory.",
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Tutorial {
+    seen: Vec<Hint>,
+    /// The hint up now, with seconds left.
+    showing: Option<(Hint, f32)>,
+}
+
+impl Tutorial {
+    pub fn path() -> PathBuf {
+        crate::save::dir().join("tutorial.txt")
+    }
+
+    /// The active profile's seen hints; none if it hasn't any yet.
+    pub fn load() -> Tutorial {
+        fs::read_to_string(Tutorial::path()).map(|t| Tutorial::from_text(&t)).unwrap_or_default()
+    }
+
+    pub fn save(&self) {
+        let result = fs::create_dir_all(crate::save::dir()).and_then(|_| fs::write(Tutorial::path(), self.to_text()));
+        if let Err(e) = result {
+            println!("Tutorial: failed to write seen hints: {}", e);
+        }
+    }
+
+    fn to_text(&self) -> String {
+        let seen: Vec<&str> = self.seen.iter().map(|hint| hint.key()).collect();
+        format!("seen={}\n", seen.join(","))
+    }
+
+    fn from_text(text: &str) -> Tutorial {
+        let seen = text.lines()
+            .filter_map(|line| line.split_once('='))
+            .filter(|(key, _)| key.trim() == "seen")
+            .flat_map(|(_, value)| value.split(','))
+            .filter_map(|key| Hint::ALL.into_iter().find(|hint| hint.key() == key.trim()))
+            .collect();
+        Tutorial { seen, showing: None }
+    }
+
+    /// Show `hint` if it's never been shown and nothing else is up. Returns true if it was,
+    /// and the seen list wants saving.
+    pub fn offer(&mut self, hint: Hint) -> bool {
+        if self.showing.is_some() || self.seen.contains(&hint) { return false; }
+        println!("Tutorial: {:?}", hint);
+        self.seen.push(hint);
+        self.showing = Some((hint, HINT_SECS));
+        true
+    }
+
+    /// Take down the hint on screen, e.g. when hints are turned off.
+    pub fn dismiss(&mut self) {
+        self.showing = None;
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        if let Some((_, left)) = &mut self.showing {
+            *left -= dt;
+            if *left <= 0.0 { self.showing = None; }
+        }
+    }
+
+    /// The hint on screen, in a box just under the room banner.
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, area: Rect, auto_run: bool) -> GameResult {
+        let Some((hint, left)) = self.showing else { return Ok(()) };
+        let alpha = left.min(1.0).min((HINT_SECS - left) * 4.0);
+        let rect = Rect::new(area.x + 40.0, area.y + 84.0, area.w - 80.0, 54.0);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.05, 0.1, 0.2, 0.9 * alpha))?;
+        canvas.draw(&bg, DrawParam::new());
+        let mut border_color = crate::palette::selection();
+        border_color.a = alpha;
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(2.0), rect, border_color)?;
+        canvas.draw(&border, DrawParam::new());
+        let heading = Text::new(TextFragment::new("Tip").scale(14.0));
+        canvas.draw(&heading, DrawParam::new().dest([rect.x + 12.0, rect.y + 6.0]).color(Color::new(1.0, 0.85, 0.2, alpha)));
+        let text = Text::new(TextFragment::new(crate::input::prompt(hint.text(auto_run))).scale(20.0));
+        canvas.draw(&text, DrawParam::new().dest([rect.x + 12.0, rect.y + 24.0]).color(Color::new(1.0, 1.0, 1.0, alpha)));
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hints_show_once_one_at_a_time_and_stay_seen() {
+        let mut tutorial = Tutorial { seen: vec![Hint::Talk], showing: None };
+        assert!(!tutorial.offer(Hint::Talk), "seen in an earlier session");
+        assert!(tutorial.offer(Hint::Run));
+        assert!(!tutorial.offer(Hint::Attack), "one at a time");
+        tutorial.update(HINT_SECS);
+        assert!(!tutorial.offer(Hint::Run), "never twice");
+        assert!(tutorial.offer(Hint::Attack));
+        tutorial.dismiss();
+        let loaded = Tutorial::from_text(&tutorial.to_text());
+        assert_eq!(loaded.seen, vec![Hint::Talk, Hint::Run, Hint::Attack]);
+        assert!(Tutorial::from_text("seen=run,warp\n").seen == vec![Hint::Run], "unknown hints are dropped");
+    }
+}
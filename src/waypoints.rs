@@ -0,0 +1,156 @@
+//! Waypoint shrines and the fast travel menu.
+//!
+//! Shrines are placed from room data (`shrine=x,y:name`). Touching one activates it for good and
+//! opens the travel menu, which lists every shrine activated so far; picking one warps the player
+//! to the tile in front of (below) it. Up/Down choose, Z travels, C closes.
+
+use ggez::{Context, GameResult};
+use ggez::graphics::{self, Canvas, Color, Text, TextFragment, DrawParam};
+use ggez::input::keyboard::KeyCode;
+
+use crate::input::Action;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Waypoint {
+    pub name: String,
+    pub room: usize,
+    /// The shrine's own tile; travellers arrive on the tile below it.
+    pub tile: (i32, i32),
+}
+
+impl Waypoint {
+    pub fn arrival(&self) -> (i32, i32) {
+        (self.tile.0, self.tile.1 + 1)
+    }
+}
+
+/// Every shrine activated so far, in activation order.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Waypoints {
+    pub list: Vec<Waypoint>,
+}
+
+impl Waypoints {
+    /// Remember a shrine. Returns false if it was already active.
+    pub fn activate(&mut self, waypoint: Waypoint) -> bool {
+        if self.list.iter().any(|w| w.room == waypoint.room && w.tile == waypoint.tile) { return false; }
+        println!("Waypoints: activated {}", waypoint.name);
+        self.list.push(waypoint);
+        true
+    }
+
+    /// Save-file form: `room,x,y:name` entries separated by `|`.
+    pub fn to_text(&self) -> String {
+        let entries: Vec<String> = self.list.iter().map(|w| format!("{},{},{}:{}", w.room, w.tile.0, w.tile.1, w.name)).collect();
+        entries.join("|")
+    }
+
+    pub fn from_text(text: &str) -> Waypoints {
+        let list = text.split('|').filter_map(|entry| {
+            let (at, name) = entry.split_once(':')?;
+            let mut parts = at.split(',').map(str::trim);
+            let room = parts.next()?.parse().ok()?;
+            let tile = (parts.next()?.parse().ok()?, parts.next()?.parse().ok()?);
+            Some(Waypoint { name: name.trim().to_string(), room, tile })
+        });
+        Waypoints { list: list.collect() }
+    }
+}
+
+pub enum TravelAction {
+    Travel(Waypoint),
+    Close,
+}
+
+pub struct TravelMenu {
+    pub visible: bool,
+    selected: usize,
+    /// Shrine the menu was opened from; travelling there just closes the menu.
+    here: Option<(usize, (i32, i32))>,
+}
+
+impl TravelMenu {
+    pub fn new() -> TravelMenu {
+        TravelMenu { visible: false, selected: 0, here: None }
+    }
+
+    /// Open at the shrine at `tile` in `room`, with that shrine selected.
+    pub fn open(&mut self, waypoints: &Waypoints, room: usize, tile: (i32, i32)) {
+        self.visible = true;
+        self.here = Some((room, tile));
+        self.selected = waypoints.list.iter().position(|w| w.room == room && w.tile == tile).unwrap_or(0);
+    }
+
+    pub fn draw(&self, ctx: &mut Context, canvas: &mut Canvas, waypoints: &Waypoints) -> GameResult {
+        if !self.visible { return Ok(()); }
+
+        let size = ctx.gfx.window().inner_size();
+        let (w, h) = (size.width as f32, size.height as f32);
+        let box_w = 380.0;
+        let box_h = 100.0 + waypoints.list.len() as f32 * 40.0;
+        let left = (w - box_w) / 2.0;
+        let top = (h - box_h) / 2.0;
+        let rect = graphics::Rect::new(left, top, box_w, box_h);
+        let bg = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::fill(), rect, Color::new(0.0, 0.2, 0.6, 0.95))?;
+        canvas.draw(&bg, DrawParam::new());
+        let border = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(4.0), rect, Color::WHITE)?;
+        canvas.draw(&border, DrawParam::new());
+
+        let title = Text::new(TextFragment::new("Travel to...").scale(28.0));
+        canvas.draw(&title, DrawParam::new().dest([left + 20.0, top + 16.0]).color(Color::WHITE));
+
+        for (i, waypoint) in waypoints.list.iter().enumerate() {
+            let y = top + 66.0 + i as f32 * 40.0;
+            let label = if self.here == Some((waypoint.room, waypoint.tile)) { format!("{} (here)", waypoint.name) } else { waypoint.name.clone() };
+            let txt = Text::new(TextFragment::new(label).scale(22.0));
+            let color = if i == self.selected { Color::new(1.0, 1.0, 0.6, 1.0) } else { Color::WHITE };
+            canvas.draw(&txt, DrawParam::new().dest([left + 40.0, y]).color(color));
+            if i == self.selected {
+                let sel_rect = graphics::Rect::new(left + 30.0, y - 6.0, box_w - 60.0, 34.0);
+                let sel_box = graphics::Mesh::new_rectangle(ctx, graphics::DrawMode::stroke(3.0), sel_rect, crate::palette::selection())?;
+                canvas.draw(&sel_box, DrawParam::new());
+            }
+        }
+        Ok(())
+    }
+
+    pub fn handle_key(&mut self, key: KeyCode, waypoints: &Waypoints) -> Option<TravelAction> {
+        if !self.visible { return None; }
+        match key {
+            KeyCode::Up => { self.selected = self.selected.saturating_sub(1); None }
+            KeyCode::Down => { self.selected = (self.selected + 1).min(waypoints.list.len().saturating_sub(1)); None }
+            k if Action::Confirm.matches(k) => {
+                self.visible = false;
+                match waypoints.list.get(self.selected) {
+                    Some(w) if self.here != Some((w.room, w.tile)) => Some(TravelAction::Travel(w.clone())),
+                    _ => Some(TravelAction::Close),
+                }
+            }
+            k if Action::Cancel.matches(k) => { self.visible = false; Some(TravelAction::Close) }
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn shrines_activate_once_and_travel_elsewhere() {
+        let mut waypoints = Waypoints::default();
+        let home = Waypoint { name: "Home Shrine".to_string(), room: 0, tile: (16, 5) };
+        let cellar = Waypoint { name: "Cellar Shrine".to_string(), room: 1, tile: (8, 2) };
+        assert!(waypoints.activate(home.clone()));
+        assert!(!waypoints.activate(home.clone()));
+        assert!(waypoints.activate(cellar.clone()));
+        assert_eq!(Waypoints::from_text(&waypoints.to_text()), waypoints);
+
+        let mut menu = TravelMenu::new();
+        menu.open(&waypoints, 1, (8, 2));
+        assert!(matches!(menu.handle_key(KeyCode::Z, &waypoints), Some(TravelAction::Close)), "already here");
+        menu.open(&waypoints, 1, (8, 2));
+        menu.handle_key(KeyCode::Up, &waypoints);
+        assert!(matches!(menu.handle_key(KeyCode::Z, &waypoints), Some(TravelAction::Travel(w)) if w == home));
+    }
+}
@@ -0,0 +1,104 @@
+//! Bits of a world frame that don't need a window, shared by `Game` and the scripted test
+//! harness (`crate::testing`) so tests play by the same rules the game does.
+
+use ggez::input::mouse::MouseButton;
+
+use crate::input::Controls;
+use crate::map::{Map, TileUse, TILE_SIZE};
+use crate::player::Player;
+use crate::rooms::Interaction;
+use crate::teleport::Pad;
+
+/// The world's scale and offset on the window, as last drawn.
+pub type View = (f32, (f32, f32));
+
+/// What a frame of the world turned up for the caller to act on.
+pub struct Step {
+    /// Spike traps shot up this frame.
+    pub spikes_sprang: bool,
+    /// The player stopped on a teleport pad that leads here.
+    pub pad: Option<Pad>,
+}
+
+/// One frame of the room and the player: the room ticks, the player moves, and a teleport pad
+/// the player has stopped on is reported once, then not again until they've stepped off it
+/// (`teleport_armed` keeps track of that between frames).
+pub fn step(map: &mut Map, player: &mut Player, controls: Controls, dt: f32, teleport_armed: &mut bool) -> Step {
+    let spikes_sprang = map.update(dt);
+    player.update(controls, dt, map);
+    let pad = match map.teleport_destination(player.tile()) {
+        Some(pad) if *teleport_armed && !player.moving => {
+            *teleport_armed = false;
+            Some(pad)
+        }
+        Some(_) => None,
+        None => {
+            *teleport_armed = true;
+            None
+        }
+    };
+    Step { spikes_sprang, pad }
+}
+
+/// What pressing confirm did to the room.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Used {
+    /// Furniture at this tile; what comes of it is up to the caller.
+    Interacted((i32, i32), Interaction),
+    Tile(TileUse),
+}
+
+/// Press confirm in the world, short of the save points and NPCs the game checks first:
+/// furniture (the chair stood on, or whatever is in front), then doors (standing in one closes
+/// it) and chests. Sitting down and a chest's gold happen here; the rest is the caller's.
+pub fn confirm(map: &mut Map, player: &mut Player) -> Option<Used> {
+    let (standing, facing) = (player.tile(), player.facing_tile());
+    for tile in [standing, facing] {
+        if let Some(interaction) = map.interaction(tile) {
+            if interaction == Interaction::Sit {
+                player.sit_at(tile);
+            }
+            return Some(Used::Interacted(tile, interaction));
+        }
+    }
+    let used = map.use_tile(standing, facing)?;
+    if let TileUse::Chest(gold) = used {
+        player.gold += gold;
+    }
+    Some(Used::Tile(used))
+}
+
+/// Tile under a point on the window, going back through the world's scale and offset.
+pub fn tile_at_screen((scale, (offset_x, offset_y)): View, (x, y): (f32, f32)) -> (i32, i32) {
+    (((x - offset_x) / scale / TILE_SIZE).floor() as i32, ((y - offset_y) / scale / TILE_SIZE).floor() as i32)
+}
+
+/// Tile a mouse click at `point` asks the player to walk to: left clicks only, and only with
+/// click-to-move on.
+pub fn click_target(button: MouseButton, click_to_move: bool, view: View, point: (f32, f32)) -> Option<(i32, i32)> {
+    (button == MouseButton::Left && click_to_move).then(|| tile_at_screen(view, point))
+}
+
+/// Set the player walking to `tile`. Returns false if there's no way there.
+pub fn walk_to(map: &Map, player: &mut Player, tile: (i32, i32)) -> bool {
+    let found = player.walk_to(map, tile);
+    if !found {
+        println!("Game: no way to {:?}", tile);
+    }
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn clicks_find_their_tile_through_the_view() {
+        let view = (2.0, (10.0, 20.0));
+        assert_eq!(tile_at_screen(view, (10.0 + 5.5 * TILE_SIZE * 2.0, 20.0 + 3.5 * TILE_SIZE * 2.0)), (5, 3));
+        assert_eq!(tile_at_screen(view, (0.0, 0.0)), (-1, -1));
+        assert_eq!(click_target(MouseButton::Left, true, view, (10.0, 20.0)), Some((0, 0)));
+        assert_eq!(click_target(MouseButton::Right, true, view, (10.0, 20.0)), None);
+        assert_eq!(click_target(MouseButton::Left, false, view, (10.0, 20.0)), None, "click-to-move is off");
+    }
+}